@@ -189,6 +189,83 @@ fn bench_parsing_debug_info_raw(b: &mut test::Bencher) {
     });
 }
 
+fn leb128_values() -> Vec<u64> {
+    // A mix of sizes similar to what is found in `.debug_info`.
+    (0..4096u64)
+        .map(|i| match i % 8 {
+            0..=3 => i % 0x80,
+            4 | 5 => i * 0x11 % 0x4000,
+            6 => i * 0x1234_5678,
+            _ => i << 48,
+        })
+        .collect()
+}
+
+fn leb128_unsigned_buffer() -> Vec<u8> {
+    let mut buf = Vec::new();
+    for value in leb128_values() {
+        gimli::leb128::write::unsigned(&mut buf, value).unwrap();
+    }
+    buf
+}
+
+fn leb128_signed_buffer() -> Vec<u8> {
+    let mut buf = Vec::new();
+    for (i, value) in leb128_values().into_iter().enumerate() {
+        let value = if i % 2 == 0 {
+            value as i64
+        } else {
+            -(value as i64)
+        };
+        gimli::leb128::write::signed(&mut buf, value).unwrap();
+    }
+    buf
+}
+
+#[bench]
+fn bench_reading_uleb128_loop(b: &mut test::Bencher) {
+    let buf = leb128_unsigned_buffer();
+    b.iter(|| {
+        let mut input = EndianSlice::new(&buf, LittleEndian);
+        while !input.is_empty() {
+            test::black_box(gimli::leb128::read::unsigned(&mut input).unwrap());
+        }
+    });
+}
+
+#[bench]
+fn bench_reading_uleb128(b: &mut test::Bencher) {
+    let buf = leb128_unsigned_buffer();
+    b.iter(|| {
+        let mut input = EndianSlice::new(&buf, LittleEndian);
+        while !input.is_empty() {
+            test::black_box(input.read_uleb128().unwrap());
+        }
+    });
+}
+
+#[bench]
+fn bench_reading_sleb128_loop(b: &mut test::Bencher) {
+    let buf = leb128_signed_buffer();
+    b.iter(|| {
+        let mut input = EndianSlice::new(&buf, LittleEndian);
+        while !input.is_empty() {
+            test::black_box(gimli::leb128::read::signed(&mut input).unwrap());
+        }
+    });
+}
+
+#[bench]
+fn bench_reading_sleb128(b: &mut test::Bencher) {
+    let buf = leb128_signed_buffer();
+    b.iter(|| {
+        let mut input = EndianSlice::new(&buf, LittleEndian);
+        while !input.is_empty() {
+            test::black_box(input.read_sleb128().unwrap());
+        }
+    });
+}
+
 #[bench]
 fn bench_parsing_debug_aranges(b: &mut test::Bencher) {
     let debug_aranges = read_section("debug_aranges");
@@ -278,6 +355,31 @@ fn bench_executing_line_number_programs(b: &mut test::Bencher) {
     });
 }
 
+#[bench]
+fn bench_executing_line_number_programs_batched(b: &mut test::Bencher) {
+    let debug_line = read_section("debug_line");
+    let debug_line = DebugLine::new(&debug_line, LittleEndian);
+
+    let mut buf = Vec::with_capacity(256);
+    b.iter(|| {
+        let program = debug_line
+            .program(OFFSET, ADDRESS_SIZE, None, None)
+            .expect("Should parse line number program header");
+
+        let mut rows = program.rows();
+        loop {
+            buf.clear();
+            let count = rows
+                .next_rows(&mut buf, 256)
+                .expect("Should parse and execute all rows in the line number program");
+            test::black_box(&buf);
+            if count < 256 {
+                break;
+            }
+        }
+    });
+}
+
 #[bench]
 fn bench_parsing_debug_loc(b: &mut test::Bencher) {
     let debug_info = read_section("debug_info");
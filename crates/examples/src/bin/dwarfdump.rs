@@ -1424,6 +1424,13 @@ fn dump_attr_value<R: Reader, W: Write>(
         gimli::AttributeValue::DwoId(value) => {
             writeln!(w, "0x{:016x}", value.0)?;
         }
+        gimli::AttributeValue::Unknown { form, bytes } => {
+            write!(w, "{}: ", form)?;
+            for byte in bytes.to_slice()?.iter() {
+                write!(w, "{:02x}", byte)?;
+            }
+            writeln!(w)?;
+        }
     }
 
     Ok(())
@@ -1656,6 +1663,18 @@ fn dump_op<R: Reader, W: Write>(
             let wasmop = pc.read_u8()?;
             write!(w, " 0x{:x} 0x{:x}", wasmop, index)?;
         }
+        gimli::Operation::FormAspaceAddress => {
+            write!(w, " {}", gimli::DW_OP_LLVM_form_aspace_address)?;
+        }
+        gimli::Operation::AspaceRegisterOffset { register, offset } => {
+            write!(
+                w,
+                " {} {} {:+}",
+                gimli::DW_OP_LLVM_aspace_bregx,
+                register.0,
+                offset
+            )?;
+        }
         gimli::Operation::Drop
         | gimli::Operation::Swap
         | gimli::Operation::Rot
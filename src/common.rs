@@ -1,5 +1,6 @@
 /// Whether the format of a compilation unit is 32- or 64-bit.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Format {
     /// 64-bit DWARF
     Dwarf64 = 8,
@@ -29,6 +30,7 @@ impl Format {
 
 /// Which vendor extensions to support.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[non_exhaustive]
 pub enum Vendor {
     /// A default set of extensions, including some common GNU extensions.
@@ -41,6 +43,7 @@ pub enum Vendor {
 ///
 /// This is intended to be small enough to pass by value.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 // `address_size` and `format` are used more often than `version`, so keep
 // them first.
 #[repr(C)]
@@ -59,6 +62,7 @@ pub struct Encoding {
 
 /// Encoding parameters for a line number program.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LineEncoding {
     /// The size in bytes of the smallest target machine instruction.
     pub minimum_instruction_length: u8,
@@ -95,55 +99,68 @@ impl Default for LineEncoding {
 /// The meaning of this value is ABI dependent. This is generally encoded as
 /// a ULEB128, but supported architectures need 16 bits at most.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Register(pub u16);
 
 /// An offset into the `.debug_abbrev` section.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DebugAbbrevOffset<T = usize>(pub T);
 
 /// An offset to a set of entries in the `.debug_addr` section.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DebugAddrBase<T = usize>(pub T);
 
 /// An index into a set of addresses in the `.debug_addr` section.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DebugAddrIndex<T = usize>(pub T);
 
 /// An offset into the `.debug_aranges` section.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DebugArangesOffset<T = usize>(pub T);
 
 /// An offset into the `.debug_info` section.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DebugInfoOffset<T = usize>(pub T);
 
 /// An offset into the `.debug_line` section.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DebugLineOffset<T = usize>(pub T);
 
 /// An offset into the `.debug_line_str` section.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DebugLineStrOffset<T = usize>(pub T);
 
 /// An offset into either the `.debug_loc` section or the `.debug_loclists` section,
 /// depending on the version of the unit the offset was contained in.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LocationListsOffset<T = usize>(pub T);
 
 /// An offset to a set of location list offsets in the `.debug_loclists` section.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DebugLocListsBase<T = usize>(pub T);
 
 /// An index into a set of location list offsets in the `.debug_loclists` section.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DebugLocListsIndex<T = usize>(pub T);
 
 /// An offset into the `.debug_macinfo` section.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DebugMacinfoOffset<T = usize>(pub T);
 
 /// An offset into the `.debug_macro` section.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DebugMacroOffset<T = usize>(pub T);
 
 /// An offset into either the `.debug_ranges` section or the `.debug_rnglists` section,
@@ -152,43 +169,53 @@ pub struct DebugMacroOffset<T = usize>(pub T);
 /// If this is from a DWARF 4 DWO file, then it must additionally be offset by the
 /// value of `DW_AT_GNU_ranges_base`. You can use `Dwarf::ranges_offset_from_raw` to do this.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RawRangeListsOffset<T = usize>(pub T);
 
 /// An offset into either the `.debug_ranges` section or the `.debug_rnglists` section,
 /// depending on the version of the unit the offset was contained in.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RangeListsOffset<T = usize>(pub T);
 
 /// An offset to a set of range list offsets in the `.debug_rnglists` section.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DebugRngListsBase<T = usize>(pub T);
 
 /// An index into a set of range list offsets in the `.debug_rnglists` section.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DebugRngListsIndex<T = usize>(pub T);
 
 /// An offset into the `.debug_str` section.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DebugStrOffset<T = usize>(pub T);
 
 /// An offset to a set of entries in the `.debug_str_offsets` section.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DebugStrOffsetsBase<T = usize>(pub T);
 
 /// An index into a set of entries in the `.debug_str_offsets` section.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DebugStrOffsetsIndex<T = usize>(pub T);
 
 /// An offset into the `.debug_types` section.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DebugTypesOffset<T = usize>(pub T);
 
 /// A type signature as used in the `.debug_types` section.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DebugTypeSignature(pub u64);
 
 /// An offset into the `.debug_frame` section.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DebugFrameOffset<T = usize>(pub T);
 
 impl<T> From<T> for DebugFrameOffset<T> {
@@ -200,6 +227,7 @@ impl<T> From<T> for DebugFrameOffset<T> {
 
 /// An offset into the `.eh_frame` section.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EhFrameOffset<T = usize>(pub T);
 
 impl<T> From<T> for EhFrameOffset<T> {
@@ -211,6 +239,7 @@ impl<T> From<T> for EhFrameOffset<T> {
 
 /// An offset into the `.debug_info` or `.debug_types` sections.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum UnitSectionOffset<T = usize> {
     /// An offset into the `.debug_info` section.
     DebugInfoOffset(DebugInfoOffset<T>),
@@ -252,6 +281,7 @@ where
 
 /// An identifier for a DWARF section.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SectionId {
     /// The `.debug_abbrev` section.
     DebugAbbrev,
@@ -263,6 +293,10 @@ pub enum SectionId {
     DebugCuIndex,
     /// The `.debug_frame` section.
     DebugFrame,
+    /// The `.debug_gnu_pubnames` section.
+    DebugGnuPubNames,
+    /// The `.debug_gnu_pubtypes` section.
+    DebugGnuPubTypes,
     /// The `.eh_frame` section.
     EhFrame,
     /// The `.eh_frame_hdr` section.
@@ -297,9 +331,43 @@ pub enum SectionId {
     DebugTuIndex,
     /// The `.debug_types` section.
     DebugTypes,
+    /// The `.debug_names` section.
+    DebugNames,
 }
 
 impl SectionId {
+    /// All of the section identifiers, in declaration order.
+    ///
+    /// The index of a section in this array is `id as usize`.
+    /// New sections must be added at the end of both the enum and this array.
+    pub(crate) const ALL: [SectionId; 25] = [
+        SectionId::DebugAbbrev,
+        SectionId::DebugAddr,
+        SectionId::DebugAranges,
+        SectionId::DebugCuIndex,
+        SectionId::DebugFrame,
+        SectionId::DebugGnuPubNames,
+        SectionId::DebugGnuPubTypes,
+        SectionId::EhFrame,
+        SectionId::EhFrameHdr,
+        SectionId::DebugInfo,
+        SectionId::DebugLine,
+        SectionId::DebugLineStr,
+        SectionId::DebugLoc,
+        SectionId::DebugLocLists,
+        SectionId::DebugMacinfo,
+        SectionId::DebugMacro,
+        SectionId::DebugPubNames,
+        SectionId::DebugPubTypes,
+        SectionId::DebugRanges,
+        SectionId::DebugRngLists,
+        SectionId::DebugStr,
+        SectionId::DebugStrOffsets,
+        SectionId::DebugTuIndex,
+        SectionId::DebugTypes,
+        SectionId::DebugNames,
+    ];
+
     /// Returns the ELF section name for this kind.
     pub fn name(self) -> &'static str {
         match self {
@@ -308,6 +376,8 @@ impl SectionId {
             SectionId::DebugAranges => ".debug_aranges",
             SectionId::DebugCuIndex => ".debug_cu_index",
             SectionId::DebugFrame => ".debug_frame",
+            SectionId::DebugGnuPubNames => ".debug_gnu_pubnames",
+            SectionId::DebugGnuPubTypes => ".debug_gnu_pubtypes",
             SectionId::EhFrame => ".eh_frame",
             SectionId::EhFrameHdr => ".eh_frame_hdr",
             SectionId::DebugInfo => ".debug_info",
@@ -317,6 +387,7 @@ impl SectionId {
             SectionId::DebugLocLists => ".debug_loclists",
             SectionId::DebugMacinfo => ".debug_macinfo",
             SectionId::DebugMacro => ".debug_macro",
+            SectionId::DebugNames => ".debug_names",
             SectionId::DebugPubNames => ".debug_pubnames",
             SectionId::DebugPubTypes => ".debug_pubtypes",
             SectionId::DebugRanges => ".debug_ranges",
@@ -366,16 +437,37 @@ impl SectionId {
             _ => return None,
         })
     }
+
+    /// Returns the kind of section for the given section name.
+    ///
+    /// This accepts ELF section names, including the names used in .dwo and .dwp
+    /// files, and XCOFF section names.
+    ///
+    /// ```
+    /// use gimli::SectionId;
+    ///
+    /// assert_eq!(SectionId::from_name(".debug_info"), Some(SectionId::DebugInfo));
+    /// assert_eq!(SectionId::from_name(".debug_info.dwo"), Some(SectionId::DebugInfo));
+    /// assert_eq!(SectionId::from_name(".dwinfo"), Some(SectionId::DebugInfo));
+    /// assert_eq!(SectionId::from_name(".text"), None);
+    /// ```
+    pub fn from_name(name: &str) -> Option<SectionId> {
+        SectionId::ALL.iter().copied().find(|id| {
+            id.name() == name || id.dwo_name() == Some(name) || id.xcoff_name() == Some(name)
+        })
+    }
 }
 
 /// An optionally-provided implementation-defined compilation unit ID to enable
 /// split DWARF and linking a split compilation unit back together.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DwoId(pub u64);
 
 /// The "type" of file with DWARF debugging information. This determines, among other things,
 /// which files DWARF sections should be loaded from.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum DwarfFileType {
     /// A normal executable or object file.
     Main,
@@ -389,3 +481,33 @@ impl Default for DwarfFileType {
         DwarfFileType::Main
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_section_id_all() {
+        for (index, id) in SectionId::ALL.iter().enumerate() {
+            assert_eq!(*id as usize, index);
+        }
+        // The last variant.
+        assert_eq!(SectionId::ALL.len(), SectionId::DebugNames as usize + 1);
+    }
+
+    #[test]
+    fn test_section_id_from_name() {
+        for id in SectionId::ALL.iter().copied() {
+            assert_eq!(SectionId::from_name(id.name()), Some(id));
+            if let Some(name) = id.dwo_name() {
+                assert_eq!(SectionId::from_name(name), Some(id));
+            }
+            if let Some(name) = id.xcoff_name() {
+                assert_eq!(SectionId::from_name(name), Some(id));
+            }
+        }
+        assert_eq!(SectionId::from_name(""), None);
+        assert_eq!(SectionId::from_name(".text"), None);
+        assert_eq!(SectionId::from_name("debug_info"), None);
+    }
+}
@@ -25,7 +25,20 @@
 #![allow(non_upper_case_globals)]
 #![allow(missing_docs)]
 
-use core::{fmt, ops};
+use core::{fmt, ops, str};
+
+/// An error returned when parsing the name of a constant fails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseConstantError;
+
+impl fmt::Display for ParseConstantError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(f, "Unknown DWARF constant name.")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseConstantError {}
 
 // The `dw!` macro turns this:
 //
@@ -55,6 +68,13 @@ use core::{fmt, ops};
 //             ...
 //         }
 //     }
+//
+//     impl str::FromStr for DwFoo {
+//         type Err = ParseConstantError;
+//         fn from_str(s: &str) -> Result<Self, Self::Err> {
+//             ...
+//         }
+//     }
 macro_rules! dw {
     ($(#[$meta:meta])* $struct_name:ident($struct_type:ty)
         { $($name:ident = $val:expr),+ $(,)? }
@@ -62,6 +82,7 @@ macro_rules! dw {
     ) => {
         $(#[$meta])*
         #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
         pub struct $struct_name(pub $struct_type);
 
         $(
@@ -80,6 +101,33 @@ macro_rules! dw {
                     _ => return None,
                 })
             }
+
+            /// Return an iterator over all of the known constants of this type.
+            ///
+            /// Aliases are not included.
+            pub fn all() -> impl Iterator<Item = Self> {
+                const ALL: &[$struct_name] = &[$($name),+];
+                ALL.iter().copied()
+            }
+        }
+
+        impl str::FromStr for $struct_name {
+            type Err = ParseConstantError;
+
+            /// Parse the name of a constant, such as `DW_TAG_subprogram`.
+            ///
+            /// Aliases are also accepted.
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                Ok(match s {
+                    $(
+                        stringify!($name) => $name,
+                    )+
+                    $($(
+                        stringify!($alias_name) => $alias_name,
+                    )+)*
+                    _ => return Err(ParseConstantError),
+                })
+            }
         }
 
         impl fmt::Display for $struct_name {
@@ -482,6 +530,10 @@ DwAt(u16) {
     DW_AT_defaulted = 0x8b,
     DW_AT_loclists_base = 0x8c,
 
+// DWARF 6 draft.
+    DW_AT_language_name = 0x90,
+    DW_AT_language_version = 0x91,
+
     DW_AT_lo_user = 0x2000,
     DW_AT_hi_user = 0x3fff,
 
@@ -768,6 +820,13 @@ DwLle(u8) {
     DW_LLE_start_end = 0x07,
     DW_LLE_start_length = 0x08,
     DW_LLE_GNU_view_pair = 0x09,
+},
+aliases {
+    // The entry kinds used in `.debug_loc` by the GNU split DWARF extension.
+    DW_LLE_GNU_end_of_list_entry = 0x00,
+    DW_LLE_GNU_base_address_selection_entry = 0x01,
+    DW_LLE_GNU_start_end_entry = 0x02,
+    DW_LLE_GNU_start_length_entry = 0x03,
 });
 
 dw!(
@@ -886,6 +945,47 @@ DwLang(u16) {
     DW_LANG_BORLAND_Delphi = 0xb000,
 });
 
+dw!(
+/// The encodings of the constants used in the `DW_AT_language_name` attribute.
+///
+/// This is from the DWARF 6 draft, and may change.
+DwLname(u16) {
+    DW_LNAME_Ada = 0x0001,
+    DW_LNAME_BLISS = 0x0002,
+    DW_LNAME_C = 0x0003,
+    DW_LNAME_C_plus_plus = 0x0004,
+    DW_LNAME_Cobol = 0x0005,
+    DW_LNAME_Crystal = 0x0006,
+    DW_LNAME_D = 0x0007,
+    DW_LNAME_Dylan = 0x0008,
+    DW_LNAME_Fortran = 0x0009,
+    DW_LNAME_Go = 0x000a,
+    DW_LNAME_Haskell = 0x000b,
+    DW_LNAME_Java = 0x000c,
+    DW_LNAME_Julia = 0x000d,
+    DW_LNAME_Kotlin = 0x000e,
+    DW_LNAME_Modula2 = 0x000f,
+    DW_LNAME_Modula3 = 0x0010,
+    DW_LNAME_ObjC = 0x0011,
+    DW_LNAME_ObjC_plus_plus = 0x0012,
+    DW_LNAME_OCaml = 0x0013,
+    DW_LNAME_OpenCL_C = 0x0014,
+    DW_LNAME_Pascal = 0x0015,
+    DW_LNAME_PLI = 0x0016,
+    DW_LNAME_Python = 0x0017,
+    DW_LNAME_RenderScript = 0x0018,
+    DW_LNAME_Rust = 0x0019,
+    DW_LNAME_Swift = 0x001a,
+    DW_LNAME_UPC = 0x001b,
+    DW_LNAME_Zig = 0x001c,
+    DW_LNAME_Assembly = 0x001d,
+    DW_LNAME_C_sharp = 0x001e,
+    DW_LNAME_Mojo = 0x001f,
+
+    DW_LNAME_lo_user = 0x8000,
+    DW_LNAME_hi_user = 0xffff,
+});
+
 impl DwLang {
     /// Get the default DW_AT_lower_bound for this language.
     pub fn default_lower_bound(self) -> Option<usize> {
@@ -1275,10 +1375,34 @@ DwOp(u8) {
     DW_OP_GNU_addr_index = 0xfb,
     DW_OP_GNU_const_index = 0xfc,
 
+    // LLVM extensions
+    DW_OP_LLVM_user = 0xe9,
+
     // Wasm extensions
     DW_OP_WASM_location = 0xed,
 });
 
+dw!(
+/// The sub-opcodes of the `DW_OP_LLVM_user` operation, which are encoded as a
+/// ULEB128 following the opcode.
+///
+/// These are used by the LLVM extensions for heterogeneous debugging.
+/// See `<https://llvm.org/docs/AMDGPUDwarfExtensionsForHeterogeneousDebugging.html>`.
+DwOpLlvmUser(u16) {
+    DW_OP_LLVM_nop = 0x01,
+    DW_OP_LLVM_form_aspace_address = 0x02,
+    DW_OP_LLVM_push_lane = 0x03,
+    DW_OP_LLVM_offset = 0x04,
+    DW_OP_LLVM_offset_uconst = 0x05,
+    DW_OP_LLVM_bit_offset = 0x06,
+    DW_OP_LLVM_call_frame_entry_reg = 0x07,
+    DW_OP_LLVM_undefined = 0x08,
+    DW_OP_LLVM_aspace_bregx = 0x09,
+    DW_OP_LLVM_piece_end = 0x0a,
+    DW_OP_LLVM_extend = 0x0b,
+    DW_OP_LLVM_select_bit_piece = 0x0c,
+});
+
 dw!(
 /// Pointer encoding used by `.eh_frame`.
 ///
@@ -1403,6 +1527,47 @@ impl DwEhPe {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_from_str() {
+        assert_eq!("DW_TAG_subprogram".parse(), Ok(DW_TAG_subprogram));
+        assert_eq!("DW_AT_name".parse(), Ok(DW_AT_name));
+        assert_eq!("DW_FORM_strx1".parse(), Ok(DW_FORM_strx1));
+        assert_eq!("DW_OP_lit0".parse(), Ok(DW_OP_lit0));
+        assert_eq!(
+            "DW_CFA_AARCH64_negate_ra_state".parse(),
+            Ok(DW_CFA_AARCH64_negate_ra_state)
+        );
+        assert_eq!("DW_TAG_name".parse::<DwTag>(), Err(ParseConstantError));
+        assert_eq!(
+            "dw_tag_subprogram".parse::<DwTag>(),
+            Err(ParseConstantError)
+        );
+    }
+
+    #[test]
+    fn test_all() {
+        assert!(DwTag::all().any(|tag| tag == DW_TAG_subprogram));
+        for tag in DwTag::all() {
+            let name = tag.static_string().unwrap();
+            assert_eq!(name.parse(), Ok(tag));
+        }
+        for form in DwForm::all() {
+            assert_eq!(form.static_string().unwrap().parse(), Ok(form));
+        }
+        assert_eq!(DwChildren::all().count(), 2);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_serde() {
+        let json = serde_json::to_string(&(DW_TAG_subprogram, DW_AT_name)).unwrap();
+        assert_eq!(json, "[46,3]");
+        assert_eq!(
+            serde_json::from_str::<(DwTag, DwAt)>(&json).unwrap(),
+            (DW_TAG_subprogram, DW_AT_name)
+        );
+    }
+
     #[test]
     fn test_dw_eh_pe_format() {
         let encoding = DW_EH_PE_pcrel | DW_EH_PE_uleb128;
@@ -173,6 +173,7 @@ pub trait Endianity: Debug + Default + Clone + Copy + PartialEq + Eq {
 
 /// Byte order that is selectable at runtime.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum RunTimeEndian {
     /// Little endian byte order.
     Little,
@@ -203,6 +204,7 @@ impl Endianity for RunTimeEndian {
 
 /// Little endian byte order.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LittleEndian;
 
 impl Default for LittleEndian {
@@ -221,6 +223,7 @@ impl Endianity for LittleEndian {
 
 /// Big endian byte order.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BigEndian;
 
 impl Default for BigEndian {
@@ -153,6 +153,87 @@ pub mod read {
 
         Ok(result)
     }
+
+    /// Decode the first 8 bytes of `slice` as a little-endian word, and return
+    /// the bytes of the LEB128 number at the start of the word, along with the
+    /// number of bytes.
+    ///
+    /// Returns `None` if there are fewer than 8 bytes, or if the number is
+    /// longer than 8 bytes.
+    #[inline]
+    fn fast_word(slice: &[u8]) -> Option<(u64, usize)> {
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(slice.get(..8)?);
+        let word = u64::from_le_bytes(bytes);
+        // The high bit of each byte that terminates a number.
+        let terminators = !word & 0x8080_8080_8080_8080;
+        if terminators == 0 {
+            return None;
+        }
+        // All bits up to and including the first terminator.
+        let mask = terminators ^ (terminators - 1);
+        let len = (terminators.trailing_zeros() / 8 + 1) as usize;
+        Some((word & mask, len))
+    }
+
+    /// Pack the low 7 bits of each byte of the word into a 56 bit value.
+    #[inline]
+    fn pack_low_bits(word: u64) -> u64 {
+        let x = word & 0x7f7f_7f7f_7f7f_7f7f;
+        let x = (x & 0x00ff_00ff_00ff_00ff) | ((x & 0xff00_ff00_ff00_ff00) >> 1);
+        let x = (x & 0x0000_ffff_0000_ffff) | ((x & 0xffff_0000_ffff_0000) >> 2);
+        (x & 0x0000_0000_ffff_ffff) | ((x & 0xffff_ffff_0000_0000) >> 4)
+    }
+
+    /// Return the number of bytes in the LEB128 number at the start of `slice`.
+    ///
+    /// This avoids looping over each byte when there are at least 8 bytes
+    /// available. Returns `None` if the fast path cannot be used, in which
+    /// case the caller should fall back to [`skip`].
+    #[inline]
+    pub(crate) fn skip_fast(slice: &[u8]) -> Option<usize> {
+        match slice.first() {
+            Some(&byte) if byte & CONTINUATION_BIT == 0 => Some(1),
+            _ => fast_word(slice).map(|(_, len)| len),
+        }
+    }
+
+    /// Decode an unsigned LEB128 number at the start of `slice`, and return
+    /// it along with the number of bytes it used.
+    ///
+    /// This avoids looping over each byte when there are at least 8 bytes
+    /// available. Returns `None` if the fast path cannot be used, in which
+    /// case the caller should fall back to [`unsigned`].
+    #[inline]
+    pub(crate) fn unsigned_fast(slice: &[u8]) -> Option<(u64, usize)> {
+        match slice.first() {
+            Some(&byte) if byte & CONTINUATION_BIT == 0 => Some((u64::from(byte), 1)),
+            _ => {
+                let (word, len) = fast_word(slice)?;
+                Some((pack_low_bits(word), len))
+            }
+        }
+    }
+
+    /// Decode a signed LEB128 number at the start of `slice`, and return
+    /// it along with the number of bytes it used.
+    ///
+    /// This avoids looping over each byte when there are at least 8 bytes
+    /// available. Returns `None` if the fast path cannot be used, in which
+    /// case the caller should fall back to [`signed`].
+    #[inline]
+    pub(crate) fn signed_fast(slice: &[u8]) -> Option<(i64, usize)> {
+        let (value, len) = match slice.first() {
+            Some(&byte) if byte & CONTINUATION_BIT == 0 => (u64::from(byte), 1),
+            _ => {
+                let (word, len) = fast_word(slice)?;
+                (pack_low_bits(word), len)
+            }
+        };
+        // Sign extend from the highest bit that was decoded.
+        let shift = 64 - 7 * len as u32;
+        Some((((value << shift) as i64) >> shift, len))
+    }
 }
 
 /// A module for writing integers encoded as LEB128.
@@ -251,7 +332,7 @@ pub mod write {
 mod tests {
     use super::{low_bits_of_byte, low_bits_of_u64, read, write, CONTINUATION_BIT};
     use crate::endianity::NativeEndian;
-    use crate::read::{EndianSlice, Error, ReaderOffsetId};
+    use crate::read::{EndianSlice, Error, Reader, ReaderOffsetId};
 
     trait ResultExt {
         fn map_eof(self, input: &[u8]) -> Self;
@@ -609,4 +690,59 @@ mod tests {
             assert!(read::u16(&mut readable).is_err(), "{:?}", buf);
         }
     }
+
+    #[test]
+    #[cfg(feature = "write")]
+    fn test_read_fast() {
+        let mut values = vec![0, 1, 0x7f, 0x80, 0x3fff, 0x4000, u64::MAX];
+        for shift in 0..64 {
+            values.push(1 << shift);
+            values.push((1 << shift) - 1);
+        }
+        for &value in &values {
+            let mut buf = [0xffu8; 16];
+            let len = write::unsigned(&mut &mut buf[..], value).unwrap();
+            let mut readable = EndianSlice::new(&buf[..], NativeEndian);
+            let expect = read::unsigned(&mut readable).unwrap();
+            assert_eq!(expect, value);
+            match read::unsigned_fast(&buf) {
+                Some(fast) => assert_eq!(fast, (value, len)),
+                None => assert!(len > 8),
+            }
+            match read::skip_fast(&buf) {
+                Some(fast) => assert_eq!(fast, len),
+                None => assert!(len > 8),
+            }
+            assert_eq!(
+                read::unsigned_fast(&buf[..len]).is_some(),
+                len == 1 || len == 8
+            );
+
+            let value = value as i64;
+            let mut buf = [0xffu8; 16];
+            let len = write::signed(&mut &mut buf[..], value).unwrap();
+            match read::signed_fast(&buf) {
+                Some(fast) => assert_eq!(fast, (value, len)),
+                None => assert!(len > 8),
+            }
+            let value = value.wrapping_neg();
+            let len = write::signed(&mut &mut buf[..], value).unwrap();
+            match read::signed_fast(&buf) {
+                Some(fast) => assert_eq!(fast, (value, len)),
+                None => assert!(len > 8),
+            }
+        }
+
+        // Check that the readers fall back to the slow path at the end of the buffer,
+        // and for values that are too long for the fast path.
+        let buf = [
+            0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 1, 0x81,
+        ];
+        let mut readable = EndianSlice::new(&buf[..], NativeEndian);
+        assert_eq!(readable.read_uleb128(), Ok(1 << 63));
+        assert_eq!(
+            readable.read_uleb128().map_eof(&buf),
+            Err(Error::UnexpectedEof(ReaderOffsetId(11)))
+        );
+    }
 }
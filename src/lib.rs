@@ -18,6 +18,25 @@
 //!
 //! * `write`: Enabled by default. Enables the `write` module. Always uses
 //! the `std` library.
+//!
+//! * `serde`: Disabled by default. Implements `serde::Serialize` and
+//!   `serde::Deserialize` for constants, offsets and the plain value types
+//!   returned by the `read` module, including `read::LineRow`,
+//!   `read::AttributeValue`, `read::UnitHeader`, `read::ArangeEntry` and
+//!   `read::FrameDescriptionEntry`. Types that hold section data can be
+//!   deserialized when their reader can: `read::EndianSlice` borrows the
+//!   data, so it needs a format that supports borrowed bytes, and
+//!   `read::EndianReader` copies the data into a new buffer.
+//!
+//! * `compression`: Disabled by default. Enables `read::CompressedSection`,
+//!   which decompresses zlib and Zstandard compressed sections. Always uses
+//!   the `std` library.
+//!
+//! * `dwarf-6-draft`: Disabled by default. Accepts version 6 unit and line
+//!   number program headers in the `read` module, using the DWARF 5 layout.
+//!   The attributes and forms of the DWARF 6 draft that do not have a
+//!   dedicated `AttributeValue` are returned as raw values. The draft is not
+//!   finalized, so this may change in any release.
 #![deny(missing_docs)]
 #![deny(missing_debug_implementations)]
 // Selectively enable rust 2018 warnings
@@ -6,11 +6,13 @@ use alloc::vec::Vec;
 use core::convert::TryFrom;
 use core::fmt::{self, Debug};
 use core::iter::FromIterator;
-use core::ops::Deref;
+use core::ops::{Deref, Range};
+use core::sync::atomic::{AtomicUsize, Ordering};
 
-use crate::common::{DebugAbbrevOffset, Encoding, SectionId};
+use crate::common::{DebugAbbrevOffset, DwarfFileType, Encoding, SectionId};
 use crate::constants;
 use crate::endianity::Endianity;
+use crate::read::lazy::CacheLock;
 use crate::read::{
     DebugInfoUnitHeadersIter, EndianSlice, Error, Reader, ReaderOffset, Result, Section, UnitHeader,
 };
@@ -21,6 +23,8 @@ use crate::read::{
 #[derive(Debug, Default, Clone, Copy)]
 pub struct DebugAbbrev<R> {
     debug_abbrev_section: R,
+    unknown_form_size: Option<fn(constants::DwForm) -> Option<UnknownFormSize>>,
+    max_abbreviations: Option<usize>,
 }
 
 impl<'input, Endian> DebugAbbrev<EndianSlice<'input, Endian>>
@@ -54,10 +58,82 @@ impl<R: Reader> DebugAbbrev<R> {
     pub fn abbreviations(
         &self,
         debug_abbrev_offset: DebugAbbrevOffset<R::Offset>,
+    ) -> Result<Abbreviations> {
+        self.abbreviations_with_limit(debug_abbrev_offset, self.max_abbreviations)
+    }
+
+    /// Parse the abbreviations at the given `offset`, using `max_abbreviations`
+    /// instead of the limit set for this section.
+    pub(crate) fn abbreviations_with_limit(
+        &self,
+        debug_abbrev_offset: DebugAbbrevOffset<R::Offset>,
+        max_abbreviations: Option<usize>,
     ) -> Result<Abbreviations> {
         let input = &mut self.debug_abbrev_section.clone();
         input.skip(debug_abbrev_offset.0)?;
-        Abbreviations::parse(input)
+        Abbreviations::parse_with(input, self.unknown_form_size, max_abbreviations)
+    }
+
+    /// Iterate the raw abbreviation declarations in the table at the given `offset`
+    /// within this `.debug_abbrev` section.
+    ///
+    /// Unlike `DebugAbbrev::abbreviations`, this does not require the codes to
+    /// be unique, and gives the location and encoding of each declaration.
+    pub fn raw_abbreviations(
+        &self,
+        debug_abbrev_offset: DebugAbbrevOffset<R::Offset>,
+    ) -> Result<RawAbbreviationIter<R>> {
+        let mut input = self.debug_abbrev_section.clone();
+        input.skip(debug_abbrev_offset.0)?;
+        Ok(RawAbbreviationIter {
+            section: self.debug_abbrev_section.clone(),
+            input,
+        })
+    }
+
+    /// Set the function to use for determining the size of forms that are not
+    /// known to `gimli`.
+    ///
+    /// By default, parsing an entry that uses an unknown form fails with
+    /// `Error::UnknownForm`, which prevents reading the rest of the unit.
+    /// If this function returns a size for an unknown form, then attributes using
+    /// that form are parsed as `AttributeValue::Unknown` instead.
+    ///
+    /// This must be set before any abbreviations are parsed, including by
+    /// `Dwarf::abbreviations`.
+    ///
+    /// ```
+    /// use gimli::{constants, DebugAbbrev, LittleEndian, UnknownFormSize};
+    ///
+    /// # let buf = [];
+    /// let mut debug_abbrev = DebugAbbrev::new(&buf, LittleEndian);
+    /// debug_abbrev.set_unknown_form_size(|form| match form.0 {
+    ///     // Vendor forms in the user range that are known to be blocks.
+    ///     0x1f30..=0x1f3f => Some(UnknownFormSize::Block),
+    ///     _ => None,
+    /// });
+    /// ```
+    pub fn set_unknown_form_size(
+        &mut self,
+        unknown_form_size: fn(constants::DwForm) -> Option<UnknownFormSize>,
+    ) {
+        self.unknown_form_size = Some(unknown_form_size);
+    }
+
+    /// Set the maximum number of abbreviations in a single abbreviations table.
+    ///
+    /// Parsing a table with more abbreviations than this fails with
+    /// `Error::TooManyAbbreviations`. `None` means there is no limit, which is
+    /// the default.
+    ///
+    /// `Dwarf::abbreviations` uses `Limits::max_abbreviations` instead, if it is set.
+    pub fn set_max_abbreviations(&mut self, max_abbreviations: Option<usize>) {
+        self.max_abbreviations = max_abbreviations;
+    }
+
+    /// Return the maximum number of abbreviations in a single abbreviations table.
+    pub(crate) fn max_abbreviations(&self) -> Option<usize> {
+        self.max_abbreviations
     }
 }
 
@@ -71,7 +147,11 @@ impl<T> DebugAbbrev<T> {
     where
         F: FnMut(&'a T) -> R,
     {
-        borrow(&self.debug_abbrev_section).into()
+        DebugAbbrev {
+            debug_abbrev_section: borrow(&self.debug_abbrev_section),
+            unknown_form_size: self.unknown_form_size,
+            max_abbreviations: self.max_abbreviations,
+        }
     }
 }
 
@@ -89,6 +169,8 @@ impl<R> From<R> for DebugAbbrev<R> {
     fn from(debug_abbrev_section: R) -> Self {
         DebugAbbrev {
             debug_abbrev_section,
+            unknown_form_size: None,
+            max_abbreviations: None,
         }
     }
 }
@@ -107,10 +189,185 @@ pub enum AbbreviationsCacheStrategy {
     All,
 }
 
+impl AbbreviationsCacheStrategy {
+    /// Return the offsets that should be cached, given the offsets used by each unit.
+    fn select(self, mut offsets: Vec<u64>) -> Vec<u64> {
+        offsets.sort_unstable();
+        match self {
+            AbbreviationsCacheStrategy::Duplicates => {
+                let mut prev_offset = 0;
+                let mut count = 0;
+                offsets.retain(|offset| {
+                    if count == 0 || prev_offset != *offset {
+                        prev_offset = *offset;
+                        count = 1;
+                    } else {
+                        count += 1;
+                    }
+                    count == 2
+                });
+            }
+            AbbreviationsCacheStrategy::All => {
+                offsets.dedup();
+            }
+        }
+        offsets
+    }
+}
+
+/// The policy that determines which abbreviations are stored in an `AbbreviationsCache`.
+///
+/// The policy is applied whenever an entry is added to the cache, and when the
+/// policy is changed with `AbbreviationsCache::set_policy`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum AbbreviationsCachePolicy {
+    /// Never store abbreviations in the cache.
+    Off,
+    /// Only store abbreviations that are added by `AbbreviationsCache::populate`
+    /// or `AbbreviationsCache::set`.
+    ///
+    /// Abbreviations that are parsed by `AbbreviationsCache::get` are not stored.
+    /// This is the default.
+    Populated,
+    /// Store every abbreviation, including those parsed by `AbbreviationsCache::get`.
+    All,
+    /// Store at most the given number of abbreviations, including those parsed
+    /// by `AbbreviationsCache::get`.
+    ///
+    /// When the cache is full, the least recently used entry is evicted.
+    Lru(usize),
+}
+
+impl AbbreviationsCachePolicy {
+    /// Return true if abbreviations parsed by `AbbreviationsCache::get` are stored.
+    fn stores_lookups(self) -> bool {
+        match self {
+            AbbreviationsCachePolicy::Off | AbbreviationsCachePolicy::Populated => false,
+            AbbreviationsCachePolicy::All | AbbreviationsCachePolicy::Lru(_) => true,
+        }
+    }
+}
+
+impl Default for AbbreviationsCachePolicy {
+    fn default() -> Self {
+        AbbreviationsCachePolicy::Populated
+    }
+}
+
+/// Statistics for an `AbbreviationsCache`.
+///
+/// These are intended to help with tuning the cache policy and strategy.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct AbbreviationsCacheStats {
+    /// The number of lookups that found an entry in the cache.
+    pub hits: usize,
+    /// The number of lookups that did not find an entry in the cache.
+    pub misses: usize,
+    /// The number of entries that were added to the cache.
+    pub insertions: usize,
+    /// The number of entries that were removed to satisfy the cache policy.
+    pub evictions: usize,
+    /// The number of entries currently in the cache.
+    pub entries: usize,
+}
+
+#[derive(Debug)]
+struct AbbreviationsCacheEntry {
+    abbreviations: Result<Arc<Abbreviations>>,
+    last_used: u64,
+}
+
+type AbbreviationsCacheKey = (DwarfFileType, u64);
+
+#[derive(Debug, Default)]
+struct AbbreviationsCacheEntries {
+    entries: btree_map::BTreeMap<AbbreviationsCacheKey, AbbreviationsCacheEntry>,
+    // The keys of `entries`, ordered from least to most recently used.
+    recent: btree_map::BTreeMap<u64, AbbreviationsCacheKey>,
+    clock: u64,
+}
+
+impl AbbreviationsCacheEntries {
+    fn tick(&mut self) -> u64 {
+        let now = self.clock;
+        self.clock += 1;
+        now
+    }
+
+    fn lookup(
+        &mut self,
+        key: AbbreviationsCacheKey,
+        policy: AbbreviationsCachePolicy,
+    ) -> Option<Result<Arc<Abbreviations>>> {
+        let now = self.tick();
+        let entry = self.entries.get_mut(&key)?;
+        if let AbbreviationsCachePolicy::Lru(_) = policy {
+            self.recent.remove(&entry.last_used);
+            self.recent.insert(now, key);
+            entry.last_used = now;
+        }
+        Some(entry.abbreviations.clone())
+    }
+
+    fn insert(&mut self, key: AbbreviationsCacheKey, abbreviations: Result<Arc<Abbreviations>>) {
+        let now = self.tick();
+        let entry = AbbreviationsCacheEntry {
+            abbreviations,
+            last_used: now,
+        };
+        if let Some(old) = self.entries.insert(key, entry) {
+            self.recent.remove(&old.last_used);
+        }
+        self.recent.insert(now, key);
+    }
+
+    /// Remove the least recently used entries until at most `capacity` remain.
+    ///
+    /// Returns the number of entries that were removed.
+    fn evict(&mut self, capacity: usize) -> usize {
+        let mut evictions = 0;
+        while self.entries.len() > capacity {
+            let (last_used, key) = match self.recent.iter().next() {
+                Some((last_used, key)) => (*last_used, *key),
+                None => break,
+            };
+            self.recent.remove(&last_used);
+            self.entries.remove(&key);
+            evictions += 1;
+        }
+        evictions
+    }
+
+    fn retain<F: FnMut(&AbbreviationsCacheKey) -> bool>(&mut self, mut f: F) {
+        self.entries.retain(|key, _| f(key));
+        self.recent.retain(|_, key| f(key));
+    }
+}
+
 /// A cache of previously parsed `Abbreviations`.
+///
+/// Entries are keyed by the abbreviations offset and by the type of file that
+/// the `.debug_abbrev` section belongs to, so that entries for a `.debug_abbrev`
+/// section are never returned for a `.debug_abbrev.dwo` section.
+///
+/// The cache can be shared between threads. With the `std` feature, a thread
+/// waits while another thread is using the cache, except while parsing.
+/// Without the `std` feature there is no way to wait, so a thread that finds that
+/// another thread is using the cache parses the abbreviations without using or
+/// updating the cache. In that case the cache may store fewer entries than its
+/// policy allows, but never more.
 #[derive(Debug, Default)]
 pub struct AbbreviationsCache {
-    abbreviations: btree_map::BTreeMap<u64, Result<Arc<Abbreviations>>>,
+    abbreviations: CacheLock<AbbreviationsCacheEntries>,
+    file_type: DwarfFileType,
+    policy: AbbreviationsCachePolicy,
+    hits: AtomicUsize,
+    misses: AtomicUsize,
+    insertions: AtomicUsize,
+    evictions: AtomicUsize,
+    entries: AtomicUsize,
 }
 
 impl AbbreviationsCache {
@@ -119,81 +376,258 @@ impl AbbreviationsCache {
         Self::default()
     }
 
+    /// Create an empty abbreviations cache with the given policy.
+    pub fn with_policy(policy: AbbreviationsCachePolicy) -> Self {
+        AbbreviationsCache {
+            policy,
+            ..Self::default()
+        }
+    }
+
+    /// Return the policy for this cache.
+    pub fn policy(&self) -> AbbreviationsCachePolicy {
+        self.policy
+    }
+
+    /// Set the policy for this cache.
+    ///
+    /// Existing entries are evicted if required by the new policy.
+    pub fn set_policy(&mut self, policy: AbbreviationsCachePolicy) {
+        self.policy = policy;
+        // Locking can't fail, since `self` is borrowed mutably.
+        if let Some(mut entries) = self.abbreviations.lock() {
+            self.evict(&mut entries, policy);
+        }
+    }
+
+    /// Return the type of file for the `.debug_abbrev` section used with this cache.
+    ///
+    /// This defaults to `DwarfFileType::Main`.
+    pub fn file_type(&self) -> DwarfFileType {
+        self.file_type
+    }
+
+    /// Set the type of file for the `.debug_abbrev` section used with this cache.
+    ///
+    /// Entries that were added for a different type of file remain in the cache,
+    /// but are not used until the file type is changed back.
+    ///
+    /// This is called by `Dwarf::make_dwo`.
+    pub fn set_file_type(&mut self, file_type: DwarfFileType) {
+        self.file_type = file_type;
+    }
+
+    /// Return statistics for this cache.
+    pub fn stats(&self) -> AbbreviationsCacheStats {
+        AbbreviationsCacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            insertions: self.insertions.load(Ordering::Relaxed),
+            evictions: self.evictions.load(Ordering::Relaxed),
+            entries: self.entries.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Reset the statistics for this cache.
+    ///
+    /// This does not modify the cache entries.
+    pub fn reset_stats(&mut self) {
+        *self.hits.get_mut() = 0;
+        *self.misses.get_mut() = 0;
+        *self.insertions.get_mut() = 0;
+        *self.evictions.get_mut() = 0;
+    }
+
+    /// Remove all entries from the cache.
+    pub fn clear(&mut self) {
+        self.abbreviations.get_mut().retain(|_| false);
+        *self.entries.get_mut() = 0;
+    }
+
     /// Parse abbreviations and store them in the cache.
     ///
     /// This will iterate over the given units to determine the abbreviations
-    /// offsets. Any existing cache entries are discarded.
+    /// offsets. Any existing cache entries for the current file type are discarded.
     ///
     /// Errors during parsing abbreviations are also stored in the cache.
     /// Errors during iterating over the units are ignored.
     pub fn populate<R: Reader>(
+        &mut self,
+        strategy: AbbreviationsCacheStrategy,
+        debug_abbrev: &DebugAbbrev<R>,
+        units: DebugInfoUnitHeadersIter<R>,
+    ) {
+        self.populate_with_limit(
+            strategy,
+            debug_abbrev,
+            units,
+            debug_abbrev.max_abbreviations,
+        );
+    }
+
+    /// Parse abbreviations and store them in the cache, using `max_abbreviations`
+    /// instead of the limit set for `debug_abbrev`.
+    pub(crate) fn populate_with_limit<R: Reader>(
         &mut self,
         strategy: AbbreviationsCacheStrategy,
         debug_abbrev: &DebugAbbrev<R>,
         mut units: DebugInfoUnitHeadersIter<R>,
+        max_abbreviations: Option<usize>,
     ) {
         let mut offsets = Vec::new();
-        match strategy {
-            AbbreviationsCacheStrategy::Duplicates => {
-                while let Ok(Some(unit)) = units.next() {
-                    offsets.push(unit.debug_abbrev_offset());
-                }
-                offsets.sort_unstable_by_key(|offset| offset.0);
-                let mut prev_offset = R::Offset::from_u8(0);
-                let mut count = 0;
-                offsets.retain(|offset| {
-                    if count == 0 || prev_offset != offset.0 {
-                        prev_offset = offset.0;
-                        count = 1;
-                    } else {
-                        count += 1;
-                    }
-                    count == 2
-                });
-            }
-            AbbreviationsCacheStrategy::All => {
-                while let Ok(Some(unit)) = units.next() {
-                    offsets.push(unit.debug_abbrev_offset());
-                }
-                offsets.sort_unstable_by_key(|offset| offset.0);
-                offsets.dedup();
-            }
+        while let Ok(Some(unit)) = units.next() {
+            offsets.push(unit.debug_abbrev_offset().0.into_u64());
+        }
+        self.populate_offsets(strategy, debug_abbrev, offsets, max_abbreviations);
+    }
+
+    /// Parse the abbreviations at offsets selected by `strategy` and store them
+    /// in the cache.
+    pub(crate) fn populate_offsets<R: Reader>(
+        &mut self,
+        strategy: AbbreviationsCacheStrategy,
+        debug_abbrev: &DebugAbbrev<R>,
+        offsets: Vec<u64>,
+        max_abbreviations: Option<usize>,
+    ) {
+        let file_type = self.file_type;
+        self.abbreviations
+            .get_mut()
+            .retain(|key| key.0 != file_type);
+        *self.entries.get_mut() = self.abbreviations.get_mut().entries.len();
+        for offset in strategy.select(offsets) {
+            let abbreviations = R::Offset::from_u64(offset)
+                .and_then(|offset| {
+                    debug_abbrev
+                        .abbreviations_with_limit(DebugAbbrevOffset(offset), max_abbreviations)
+                })
+                .map(Arc::new);
+            self.set_offset(offset, abbreviations);
         }
-        self.abbreviations = offsets
-            .into_iter()
-            .map(|offset| {
-                (
-                    offset.0.into_u64(),
-                    debug_abbrev.abbreviations(offset).map(Arc::new),
-                )
-            })
-            .collect();
     }
 
     /// Set an entry in the abbreviations cache.
     ///
     /// This is only required if you want to manually populate the cache.
+    /// The entry is discarded if the cache policy is `AbbreviationsCachePolicy::Off`.
     pub fn set<R: Reader>(
         &mut self,
         offset: DebugAbbrevOffset<R::Offset>,
         abbreviations: Arc<Abbreviations>,
     ) {
-        self.abbreviations
-            .insert(offset.0.into_u64(), Ok(abbreviations));
+        self.set_offset(offset.0.into_u64(), Ok(abbreviations));
+    }
+
+    fn set_offset(&mut self, offset: u64, abbreviations: Result<Arc<Abbreviations>>) {
+        let key = (self.file_type, offset);
+        // Locking can't fail, since `self` is borrowed mutably.
+        if let Some(mut entries) = self.abbreviations.lock() {
+            self.insert(&mut entries, key, abbreviations, self.policy);
+        }
     }
 
     /// Parse the abbreviations at the given offset.
     ///
-    /// This uses the cache if possible, but does not update it.
+    /// This uses the cache if possible. Otherwise the parsed abbreviations are
+    /// stored in the cache if the cache policy is `AbbreviationsCachePolicy::All`
+    /// or `AbbreviationsCachePolicy::Lru`.
+    ///
+    /// This is used by `Dwarf::abbreviations`.
     pub fn get<R: Reader>(
         &self,
         debug_abbrev: &DebugAbbrev<R>,
         offset: DebugAbbrevOffset<R::Offset>,
     ) -> Result<Arc<Abbreviations>> {
-        match self.abbreviations.get(&offset.0.into_u64()) {
-            Some(entry) => entry.clone(),
-            None => debug_abbrev.abbreviations(offset).map(Arc::new),
+        self.get_with_limit(debug_abbrev, offset, debug_abbrev.max_abbreviations)
+    }
+
+    /// Parse the abbreviations at the given offset, using `max_abbreviations`
+    /// instead of the limit set for `debug_abbrev`.
+    pub(crate) fn get_with_limit<R: Reader>(
+        &self,
+        debug_abbrev: &DebugAbbrev<R>,
+        offset: DebugAbbrevOffset<R::Offset>,
+        max_abbreviations: Option<usize>,
+    ) -> Result<Arc<Abbreviations>> {
+        let key = (self.file_type, offset.0.into_u64());
+        if let Some(mut entries) = self.abbreviations.lock() {
+            // The limit may have changed since the entry was added, so check
+            // it again, and parse again if the entry exceeded the old limit.
+            match entries.lookup(key, self.policy) {
+                Some(Err(Error::TooManyAbbreviations)) | None => {}
+                Some(abbreviations) => {
+                    self.hits.fetch_add(1, Ordering::Relaxed);
+                    return match abbreviations {
+                        Ok(abbreviations)
+                            if max_abbreviations.map_or(false, |max| abbreviations.len() > max) =>
+                        {
+                            Err(Error::TooManyAbbreviations)
+                        }
+                        abbreviations => abbreviations,
+                    };
+                }
+            }
+        }
+        self.misses.fetch_add(1, Ordering::Relaxed);
+
+        // Don't hold the lock while parsing.
+        let abbreviations = debug_abbrev
+            .abbreviations_with_limit(offset, max_abbreviations)
+            .map(Arc::new);
+        if self.policy.stores_lookups() {
+            if let Some(mut entries) = self.abbreviations.lock() {
+                self.insert(&mut entries, key, abbreviations.clone(), self.policy);
+            }
         }
+        abbreviations
+    }
+
+    /// Copy the entries for offsets in `start..start + size` from `other` into `self`,
+    /// with the offsets made relative to `start`.
+    ///
+    /// This is used to share the abbreviations from a `.dwp` file with the units it contains.
+    /// No entries are copied if `other` is being used by another thread.
+    pub(crate) fn copy_range(&mut self, other: &AbbreviationsCache, start: u64, size: u64) {
+        let other_entries = match other.abbreviations.lock() {
+            Some(entries) => entries,
+            None => return,
+        };
+        let file_type = other.file_type;
+        let end = start.saturating_add(size);
+        for (key, entry) in other_entries
+            .entries
+            .range((file_type, start)..(file_type, end))
+        {
+            self.set_offset(key.1 - start, entry.abbreviations.clone());
+        }
+    }
+
+    /// Add an entry to the cache, and remove entries as required by the policy.
+    fn insert(
+        &self,
+        entries: &mut AbbreviationsCacheEntries,
+        key: AbbreviationsCacheKey,
+        abbreviations: Result<Arc<Abbreviations>>,
+        policy: AbbreviationsCachePolicy,
+    ) {
+        if policy == AbbreviationsCachePolicy::Off {
+            return;
+        }
+        entries.insert(key, abbreviations);
+        self.insertions.fetch_add(1, Ordering::Relaxed);
+        self.evict(entries, policy);
+    }
+
+    /// Remove entries until the cache satisfies its policy.
+    fn evict(&self, entries: &mut AbbreviationsCacheEntries, policy: AbbreviationsCachePolicy) {
+        let capacity = match policy {
+            AbbreviationsCachePolicy::Off => 0,
+            AbbreviationsCachePolicy::Populated | AbbreviationsCachePolicy::All => usize::MAX,
+            AbbreviationsCachePolicy::Lru(capacity) => capacity,
+        };
+        let evictions = entries.evict(capacity);
+        self.evictions.fetch_add(evictions, Ordering::Relaxed);
+        self.entries.store(entries.entries.len(), Ordering::Relaxed);
     }
 }
 
@@ -217,6 +651,11 @@ impl Abbreviations {
         }
     }
 
+    /// Return the number of abbreviations in the set.
+    fn len(&self) -> usize {
+        self.vec.len() + self.map.len()
+    }
+
     /// Insert an abbreviation into the set.
     ///
     /// Returns `Ok` if it is the first abbreviation in the set with its code,
@@ -262,11 +701,54 @@ impl Abbreviations {
         self.map.get(&code)
     }
 
+    /// Iterate the abbreviations in order of code.
+    pub fn iter(&self) -> impl Iterator<Item = &Abbreviation> {
+        // Codes in `map` are always greater than those in `vec`.
+        self.vec.iter().chain(self.map.values())
+    }
+
     /// Parse a series of abbreviations, terminated by a null abbreviation.
+    ///
+    /// The attribute specifications for all of the abbreviations are stored
+    /// in a single allocation.
+    #[cfg(test)]
     fn parse<R: Reader>(input: &mut R) -> Result<Abbreviations> {
-        let mut abbrevs = Abbreviations::empty();
+        Self::parse_with(input, None, None)
+    }
+
+    /// Parse a series of abbreviations, using `unknown_form_size` for forms
+    /// that are not known, and failing if there are more than `max_abbreviations`.
+    fn parse_with<R: Reader>(
+        input: &mut R,
+        unknown_form_size: Option<fn(constants::DwForm) -> Option<UnknownFormSize>>,
+        max_abbreviations: Option<usize>,
+    ) -> Result<Abbreviations> {
+        let mut arena = Vec::new();
+        let mut headers = Vec::new();
+        while let Some((code, tag, has_children)) = Abbreviation::parse_header(input)? {
+            if max_abbreviations.map_or(false, |max| headers.len() >= max) {
+                return Err(Error::TooManyAbbreviations);
+            }
+            let start = arena.len();
+            Abbreviation::parse_attributes_into(input, &mut arena)?;
+            headers.push((code, tag, has_children, start..arena.len()));
+        }
+
+        if let Some(unknown_form_size) = unknown_form_size {
+            for spec in &mut arena {
+                if spec.form().static_string().is_none() {
+                    if let Some(size) = unknown_form_size(spec.form()) {
+                        spec.set_unknown_form_size(size);
+                    }
+                }
+            }
+        }
 
-        while let Some(abbrev) = Abbreviation::parse(input)? {
+        let arena = Arc::<[AttributeSpecification]>::from(arena);
+        let mut abbrevs = Abbreviations::empty();
+        for (code, tag, has_children, range) in headers {
+            let attributes = Attributes::new(arena.clone(), range);
+            let abbrev = Abbreviation::new(code, tag, has_children, attributes);
             if abbrevs.insert(abbrev).is_err() {
                 return Err(Error::DuplicateAbbreviationCode);
             }
@@ -354,19 +836,30 @@ impl Abbreviation {
 
     /// Parse a series of attribute specifications, terminated by a null attribute
     /// specification.
+    #[cfg(test)]
     fn parse_attributes<R: Reader>(input: &mut R) -> Result<Attributes> {
-        let mut attrs = Attributes::new();
+        let mut attrs = Vec::new();
+        Self::parse_attributes_into(input, &mut attrs)?;
+        Ok(attrs.into())
+    }
 
+    /// Parse a series of attribute specifications, terminated by a null attribute
+    /// specification, and append them to `attrs`.
+    fn parse_attributes_into<R: Reader>(
+        input: &mut R,
+        attrs: &mut Vec<AttributeSpecification>,
+    ) -> Result<()> {
         while let Some(attr) = AttributeSpecification::parse(input)? {
             attrs.push(attr);
         }
-
-        Ok(attrs)
+        Ok(())
     }
 
-    /// Parse an abbreviation. Return `None` for the null abbreviation, `Some`
-    /// for an actual abbreviation.
-    fn parse<R: Reader>(input: &mut R) -> Result<Option<Abbreviation>> {
+    /// Parse the code, tag, and children byte of an abbreviation. Return `None`
+    /// for the null abbreviation.
+    fn parse_header<R: Reader>(
+        input: &mut R,
+    ) -> Result<Option<(u64, constants::DwTag, constants::DwChildren)>> {
         let code = input.read_uleb128()?;
         if code == 0 {
             return Ok(None);
@@ -374,6 +867,17 @@ impl Abbreviation {
 
         let tag = Self::parse_tag(input)?;
         let has_children = Self::parse_has_children(input)?;
+        Ok(Some((code, tag, has_children)))
+    }
+
+    /// Parse an abbreviation. Return `None` for the null abbreviation, `Some`
+    /// for an actual abbreviation.
+    #[cfg(test)]
+    fn parse<R: Reader>(input: &mut R) -> Result<Option<Abbreviation>> {
+        let (code, tag, has_children) = match Self::parse_header(input)? {
+            Some(header) => header,
+            None => return Ok(None),
+        };
         let attributes = Self::parse_attributes(input)?;
         let abbrev = Abbreviation::new(code, tag, has_children, attributes);
         Ok(Some(abbrev))
@@ -381,45 +885,24 @@ impl Abbreviation {
 }
 
 /// A list of attributes found in an `Abbreviation`
+///
+/// This is a range within storage that is shared by all of the abbreviations
+/// in an `Abbreviations`, which avoids an allocation per abbreviation.
 #[derive(Clone)]
-pub(crate) enum Attributes {
-    Inline {
-        buf: [AttributeSpecification; MAX_ATTRIBUTES_INLINE],
-        len: usize,
-    },
-    Heap(Vec<AttributeSpecification>),
+pub(crate) struct Attributes {
+    arena: Arc<[AttributeSpecification]>,
+    start: usize,
+    end: usize,
 }
 
-// Length of 5 based on benchmark results for both x86-64 and i686.
-const MAX_ATTRIBUTES_INLINE: usize = 5;
-
 impl Attributes {
-    /// Returns a new empty list of attributes
-    fn new() -> Attributes {
-        let default =
-            AttributeSpecification::new(constants::DW_AT_null, constants::DW_FORM_null, None);
-        Attributes::Inline {
-            buf: [default; 5],
-            len: 0,
-        }
-    }
-
-    /// Pushes a new value onto this list of attributes.
-    fn push(&mut self, attr: AttributeSpecification) {
-        match self {
-            Attributes::Heap(list) => list.push(attr),
-            Attributes::Inline {
-                buf,
-                len: MAX_ATTRIBUTES_INLINE,
-            } => {
-                let mut list = buf.to_vec();
-                list.push(attr);
-                *self = Attributes::Heap(list);
-            }
-            Attributes::Inline { buf, len } => {
-                buf[*len] = attr;
-                *len += 1;
-            }
+    /// Returns the attributes within the given range of `arena`.
+    fn new(arena: Arc<[AttributeSpecification]>, range: Range<usize>) -> Attributes {
+        debug_assert!(range.start <= range.end && range.end <= arena.len());
+        Attributes {
+            arena,
+            start: range.start,
+            end: range.end,
         }
     }
 }
@@ -440,11 +923,9 @@ impl Eq for Attributes {}
 
 impl Deref for Attributes {
     type Target = [AttributeSpecification];
+    #[inline]
     fn deref(&self) -> &[AttributeSpecification] {
-        match self {
-            Attributes::Inline { buf, len } => &buf[..*len],
-            Attributes::Heap(list) => list,
-        }
+        &self.arena[self.start..self.end]
     }
 }
 
@@ -453,27 +934,72 @@ impl FromIterator<AttributeSpecification> for Attributes {
     where
         I: IntoIterator<Item = AttributeSpecification>,
     {
-        let mut list = Attributes::new();
-        for item in iter {
-            list.push(item);
-        }
-        list
+        iter.into_iter().collect::<Vec<_>>().into()
     }
 }
 
 impl From<Vec<AttributeSpecification>> for Attributes {
     fn from(list: Vec<AttributeSpecification>) -> Attributes {
-        Attributes::Heap(list)
+        let end = list.len();
+        Attributes {
+            arena: list.into(),
+            start: 0,
+            end,
+        }
     }
 }
 
+/// The size of the value of a form that is not known to `gimli`.
+///
+/// See `DebugAbbrev::set_unknown_form_size`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnknownFormSize {
+    /// A constant with the given size in bytes.
+    Fixed(u8),
+    /// A LEB128 encoded constant.
+    Leb128,
+    /// A block with a 1 byte length.
+    Block1,
+    /// A block with a 2 byte length.
+    Block2,
+    /// A block with a 4 byte length.
+    Block4,
+    /// A block with an unsigned LEB128 length.
+    Block,
+    /// A null terminated string.
+    String,
+}
+
 /// The description of an attribute in an abbreviated type. It is a pair of name
 /// and form.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub struct AttributeSpecification {
-    name: constants::DwAt,
-    form: constants::DwForm,
-    implicit_const_value: i64,
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct AttributeSpecification(AttributeSpecificationKind);
+
+// The name is stored in each variant, and the implicit const value is stored
+// as two halves, so that the enum only needs 4 byte alignment and its tag can
+// share space with the name. This keeps the size of `AttributeSpecification`
+// at 12 bytes.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum AttributeSpecificationKind {
+    ImplicitConst {
+        name: constants::DwAt,
+        value: [u32; 2],
+    },
+    Form {
+        name: constants::DwAt,
+        form: constants::DwForm,
+        unknown_form_size: Option<UnknownFormSize>,
+    },
+}
+
+impl Debug for AttributeSpecification {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AttributeSpecification")
+            .field("name", &self.name())
+            .field("form", &self.form())
+            .field("implicit_const_value", &self.implicit_const_value())
+            .finish()
+    }
 }
 
 impl AttributeSpecification {
@@ -489,32 +1015,72 @@ impl AttributeSpecification {
             (form == constants::DW_FORM_implicit_const && implicit_const_value.is_some())
                 || (form != constants::DW_FORM_implicit_const && implicit_const_value.is_none())
         );
-        AttributeSpecification {
-            name,
-            form,
-            implicit_const_value: implicit_const_value.unwrap_or(0),
+        if form == constants::DW_FORM_implicit_const {
+            let value = implicit_const_value.unwrap_or(0) as u64;
+            AttributeSpecification(AttributeSpecificationKind::ImplicitConst {
+                name,
+                value: [value as u32, (value >> 32) as u32],
+            })
+        } else {
+            AttributeSpecification(AttributeSpecificationKind::Form {
+                name,
+                form,
+                unknown_form_size: None,
+            })
         }
     }
 
     /// Get the attribute's name.
     #[inline]
     pub fn name(&self) -> constants::DwAt {
-        self.name
+        match self.0 {
+            AttributeSpecificationKind::ImplicitConst { name, .. }
+            | AttributeSpecificationKind::Form { name, .. } => name,
+        }
     }
 
     /// Get the attribute's form.
     #[inline]
     pub fn form(&self) -> constants::DwForm {
-        self.form
+        match self.0 {
+            AttributeSpecificationKind::ImplicitConst { .. } => constants::DW_FORM_implicit_const,
+            AttributeSpecificationKind::Form { form, .. } => form,
+        }
     }
 
     /// Get the attribute's implicit const value.
     #[inline]
     pub fn implicit_const_value(&self) -> Option<i64> {
-        if self.form == constants::DW_FORM_implicit_const {
-            Some(self.implicit_const_value)
-        } else {
-            None
+        match self.0 {
+            AttributeSpecificationKind::ImplicitConst {
+                value: [low, high], ..
+            } => Some((u64::from(low) | (u64::from(high) << 32)) as i64),
+            AttributeSpecificationKind::Form { .. } => None,
+        }
+    }
+
+    /// Get the size that is used for the attribute's form if it is not known.
+    ///
+    /// See `DebugAbbrev::set_unknown_form_size`.
+    #[inline]
+    pub fn unknown_form_size(&self) -> Option<UnknownFormSize> {
+        match self.0 {
+            AttributeSpecificationKind::ImplicitConst { .. } => None,
+            AttributeSpecificationKind::Form {
+                unknown_form_size, ..
+            } => unknown_form_size,
+        }
+    }
+
+    fn set_unknown_form_size(&mut self, size: UnknownFormSize) {
+        match self.0 {
+            AttributeSpecificationKind::ImplicitConst { .. } => {
+                debug_assert!(false, "implicit const forms have a known size")
+            }
+            AttributeSpecificationKind::Form {
+                ref mut unknown_form_size,
+                ..
+            } => *unknown_form_size = Some(size),
         }
     }
 
@@ -523,7 +1089,13 @@ impl AttributeSpecification {
     /// Note that because some attributes are variably sized, the size cannot
     /// always be known without parsing, in which case we return `None`.
     pub fn size<R: Reader>(&self, header: &UnitHeader<R>) -> Option<usize> {
-        get_attribute_size(self.form, header.encoding()).map(usize::from)
+        match get_attribute_size(self.form(), header.encoding()) {
+            Some(size) => Some(usize::from(size)),
+            None => match self.unknown_form_size() {
+                Some(UnknownFormSize::Fixed(size)) => Some(usize::from(size)),
+                _ => None,
+            },
+        }
     }
 
     /// Parse an attribute's form.
@@ -630,6 +1202,238 @@ pub(crate) fn get_attribute_size(form: constants::DwForm, encoding: Encoding) ->
     }
 }
 
+/// A raw abbreviation declaration in the `.debug_abbrev` section.
+///
+/// This is returned by [`DebugAbbrev::raw_abbreviations`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawAbbreviation<R: Reader> {
+    offset: DebugAbbrevOffset<R::Offset>,
+    length: R::Offset,
+    code: u64,
+    tag: constants::DwTag,
+    has_children: constants::DwChildren,
+    attributes: R,
+}
+
+impl<R: Reader> RawAbbreviation<R> {
+    /// Return the offset of this declaration within the `.debug_abbrev` section.
+    #[inline]
+    pub fn offset(&self) -> DebugAbbrevOffset<R::Offset> {
+        self.offset
+    }
+
+    /// Return the length in bytes of this declaration, including the null
+    /// attribute specification that terminates it.
+    #[inline]
+    pub fn length(&self) -> R::Offset {
+        self.length
+    }
+
+    /// Return the abbreviation code.
+    #[inline]
+    pub fn code(&self) -> u64 {
+        self.code
+    }
+
+    /// Return the tag.
+    #[inline]
+    pub fn tag(&self) -> constants::DwTag {
+        self.tag
+    }
+
+    /// Return true if entries using this abbreviation have children.
+    #[inline]
+    pub fn has_children(&self) -> bool {
+        self.has_children == constants::DW_CHILDREN_yes
+    }
+
+    /// Iterate the attribute specifications of this declaration.
+    ///
+    /// Each specification gives the attribute name, the form, and the value
+    /// for `DW_FORM_implicit_const`.
+    pub fn attributes(&self) -> RawAbbreviationAttributeIter<R> {
+        RawAbbreviationAttributeIter {
+            input: self.attributes.clone(),
+        }
+    }
+}
+
+/// An iterator over the raw abbreviation declarations in a table in the
+/// `.debug_abbrev` section.
+///
+/// This is returned by [`DebugAbbrev::raw_abbreviations`].
+#[derive(Debug, Clone)]
+pub struct RawAbbreviationIter<R: Reader> {
+    section: R,
+    input: R,
+}
+
+impl<R: Reader> RawAbbreviationIter<R> {
+    /// Return the offset within the `.debug_abbrev` section of the next
+    /// declaration.
+    ///
+    /// After the iterator has returned `None`, this is the offset following the
+    /// null abbreviation that terminates the table, which is usually the start of
+    /// the next table.
+    pub fn offset(&self) -> DebugAbbrevOffset<R::Offset> {
+        DebugAbbrevOffset(self.input.offset_from(&self.section))
+    }
+
+    /// Advance the iterator to the next declaration.
+    ///
+    /// Returns `None` when the null abbreviation that terminates the table is reached,
+    /// or at the end of the section.
+    pub fn next(&mut self) -> Result<Option<RawAbbreviation<R>>> {
+        if self.input.is_empty() {
+            return Ok(None);
+        }
+        let offset = self.offset();
+        let (code, tag, has_children) = match Abbreviation::parse_header(&mut self.input) {
+            Ok(Some(header)) => header,
+            Ok(None) => return Ok(None),
+            Err(e) => {
+                self.input.empty();
+                return Err(e);
+            }
+        };
+        let mut rest = self.input.clone();
+        if let Err(e) = Self::skip_attributes(&mut rest) {
+            self.input.empty();
+            return Err(e);
+        }
+        let attributes = self.input.split(rest.offset_from(&self.input))?;
+        self.input = rest;
+        Ok(Some(RawAbbreviation {
+            offset,
+            length: self.offset().0 - offset.0,
+            code,
+            tag,
+            has_children,
+            attributes,
+        }))
+    }
+
+    fn skip_attributes(input: &mut R) -> Result<()> {
+        while AttributeSpecification::parse(input)?.is_some() {}
+        Ok(())
+    }
+}
+
+#[cfg(feature = "fallible-iterator")]
+impl<R: Reader> fallible_iterator::FallibleIterator for RawAbbreviationIter<R> {
+    type Item = RawAbbreviation<R>;
+    type Error = Error;
+
+    fn next(&mut self) -> ::core::result::Result<Option<Self::Item>, Self::Error> {
+        RawAbbreviationIter::next(self)
+    }
+}
+
+/// An iterator over the attribute specifications of a [`RawAbbreviation`].
+#[derive(Debug, Clone)]
+pub struct RawAbbreviationAttributeIter<R: Reader> {
+    input: R,
+}
+
+impl<R: Reader> RawAbbreviationAttributeIter<R> {
+    /// Advance the iterator to the next attribute specification.
+    pub fn next(&mut self) -> Result<Option<AttributeSpecification>> {
+        if self.input.is_empty() {
+            return Ok(None);
+        }
+        match AttributeSpecification::parse(&mut self.input) {
+            Ok(spec) => Ok(spec),
+            Err(e) => {
+                self.input.empty();
+                Err(e)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "fallible-iterator")]
+impl<R: Reader> fallible_iterator::FallibleIterator for RawAbbreviationAttributeIter<R> {
+    type Item = AttributeSpecification;
+    type Error = Error;
+
+    fn next(&mut self) -> ::core::result::Result<Option<Self::Item>, Self::Error> {
+        RawAbbreviationAttributeIter::next(self)
+    }
+}
+
+/// The number of entries in a unit that use each abbreviation.
+///
+/// ```rust,no_run
+/// # fn example<R: gimli::Reader>(dwarf: &gimli::Dwarf<R>) -> gimli::Result<()> {
+/// let mut headers = dwarf.units();
+/// while let Some(header) = headers.next()? {
+///     let abbreviations = dwarf.abbreviations(&header)?;
+///     let usage = gimli::AbbreviationUsage::new(&header, &abbreviations)?;
+///     for (code, count) in usage.iter() {
+///         println!("{}: {}", code, count);
+///     }
+///     println!("unused: {}", usage.unused(&abbreviations).count());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct AbbreviationUsage {
+    counts: btree_map::BTreeMap<u64, usize>,
+    null_entries: usize,
+}
+
+impl AbbreviationUsage {
+    /// Count the entries in the given unit.
+    ///
+    /// `abbreviations` must be the abbreviations for the unit.
+    pub fn new<R: Reader>(header: &UnitHeader<R>, abbreviations: &Abbreviations) -> Result<Self> {
+        let mut usage = AbbreviationUsage::default();
+        let mut entries = header.entries_raw(abbreviations, None)?;
+        while !entries.is_empty() {
+            match entries.read_abbreviation()? {
+                Some(abbrev) => {
+                    *usage.counts.entry(abbrev.code()).or_insert(0) += 1;
+                    entries.skip_attributes(abbrev.attributes())?;
+                }
+                None => usage.null_entries += 1,
+            }
+        }
+        Ok(usage)
+    }
+
+    /// Return the number of entries that use the abbreviation with the given code.
+    pub fn count(&self, code: u64) -> usize {
+        self.counts.get(&code).copied().unwrap_or(0)
+    }
+
+    /// Return the total number of entries, excluding null entries.
+    pub fn entries(&self) -> usize {
+        self.counts.values().sum()
+    }
+
+    /// Return the number of null entries.
+    pub fn null_entries(&self) -> usize {
+        self.null_entries
+    }
+
+    /// Iterate the abbreviation codes that are used, and the number of entries
+    /// using each code, in order of code.
+    pub fn iter(&self) -> impl Iterator<Item = (u64, usize)> + '_ {
+        self.counts.iter().map(|(code, count)| (*code, *count))
+    }
+
+    /// Iterate the abbreviations that are not used by any entry.
+    pub fn unused<'a>(
+        &'a self,
+        abbreviations: &'a Abbreviations,
+    ) -> impl Iterator<Item = &'a Abbreviation> + 'a {
+        abbreviations
+            .iter()
+            .filter(move |abbrev| !self.counts.contains_key(&abbrev.code()))
+    }
+}
+
 #[cfg(test)]
 pub(crate) mod tests {
     use super::*;
@@ -637,6 +1441,7 @@ pub(crate) mod tests {
     use crate::endianity::LittleEndian;
     use crate::read::{EndianSlice, Error};
     use crate::test_util::GimliSectionMethods;
+    use core::mem;
     #[cfg(target_pointer_width = "32")]
     use core::u32;
     use test_assembler::Section;
@@ -882,6 +1687,43 @@ pub(crate) mod tests {
         assert_eq!(*rest, EndianSlice::new(&expected_rest, LittleEndian));
     }
 
+    #[test]
+    fn test_parse_abbreviations_arena() {
+        #[rustfmt::skip]
+        let buf = Section::new()
+            .abbrev(1, constants::DW_TAG_compile_unit, constants::DW_CHILDREN_yes)
+                .abbrev_attr(constants::DW_AT_producer, constants::DW_FORM_strp)
+                .abbrev_attr_null()
+            .abbrev(2, constants::DW_TAG_base_type, constants::DW_CHILDREN_no)
+                .abbrev_attr_null()
+            .abbrev(3, constants::DW_TAG_variable, constants::DW_CHILDREN_no)
+                .abbrev_attr(constants::DW_AT_name, constants::DW_FORM_string)
+                .abbrev_attr_implicit_const(constants::DW_AT_decl_line, -0x1234_5678_9abc)
+                .abbrev_attr_null()
+            .abbrev_null()
+            .get_contents()
+            .unwrap();
+        let abbrevs = Abbreviations::parse(&mut EndianSlice::new(&buf, LittleEndian)).unwrap();
+
+        let abbrev1 = abbrevs.get(1).unwrap();
+        let abbrev2 = abbrevs.get(2).unwrap();
+        let abbrev3 = abbrevs.get(3).unwrap();
+        assert!(Arc::ptr_eq(
+            &abbrev1.attributes.arena,
+            &abbrev3.attributes.arena
+        ));
+        assert_eq!(abbrev1.attributes().len(), 1);
+        assert!(abbrev2.attributes().is_empty());
+        assert_eq!(abbrev3.attributes().len(), 2);
+        assert_eq!(abbrev3.attributes()[0].name(), constants::DW_AT_name);
+        assert_eq!(
+            abbrev3.attributes()[1].implicit_const_value(),
+            Some(-0x1234_5678_9abc)
+        );
+
+        assert_eq!(mem::size_of::<AttributeSpecification>(), 12);
+    }
+
     #[test]
     fn test_parse_abbreviations_duplicate() {
         let expected_rest = [1, 2, 3, 4];
@@ -1089,4 +1931,308 @@ pub(crate) mod tests {
             .unwrap();
         assert!(abbrevs.get(0).is_none());
     }
+
+    fn abbreviations_cache_section() -> Vec<u8> {
+        #[rustfmt::skip]
+        let buf = Section::new()
+            .abbrev(1, constants::DW_TAG_compile_unit, constants::DW_CHILDREN_no)
+                .abbrev_attr_null()
+            .abbrev_null()
+            .abbrev(1, constants::DW_TAG_type_unit, constants::DW_CHILDREN_no)
+                .abbrev_attr_null()
+            .abbrev_null()
+            .abbrev(1, constants::DW_TAG_partial_unit, constants::DW_CHILDREN_no)
+                .abbrev_attr_null()
+            .abbrev_null()
+            .get_contents()
+            .unwrap();
+        buf
+    }
+
+    #[test]
+    fn test_abbreviations_cache_lru() {
+        let buf = abbreviations_cache_section();
+        let debug_abbrev = DebugAbbrev::new(&buf, LittleEndian);
+        let mut cache = AbbreviationsCache::with_policy(AbbreviationsCachePolicy::Lru(2));
+
+        let tag = |cache: &mut AbbreviationsCache, offset| {
+            cache
+                .get(&debug_abbrev, DebugAbbrevOffset(offset))
+                .unwrap()
+                .get(1)
+                .unwrap()
+                .tag()
+        };
+        assert_eq!(tag(&mut cache, 0), constants::DW_TAG_compile_unit);
+        assert_eq!(tag(&mut cache, 6), constants::DW_TAG_type_unit);
+        // Make offset 0 the most recently used.
+        assert_eq!(tag(&mut cache, 0), constants::DW_TAG_compile_unit);
+        // Evicts offset 6.
+        assert_eq!(tag(&mut cache, 12), constants::DW_TAG_partial_unit);
+        assert_eq!(
+            cache.stats(),
+            AbbreviationsCacheStats {
+                hits: 1,
+                misses: 3,
+                insertions: 3,
+                evictions: 1,
+                entries: 2,
+            }
+        );
+        assert_eq!(tag(&mut cache, 0), constants::DW_TAG_compile_unit);
+        assert_eq!(tag(&mut cache, 6), constants::DW_TAG_type_unit);
+        assert_eq!(cache.stats().hits, 2);
+        assert_eq!(cache.stats().evictions, 2);
+
+        cache.set_policy(AbbreviationsCachePolicy::Lru(1));
+        assert_eq!(cache.stats().entries, 1);
+        cache.set_policy(AbbreviationsCachePolicy::Off);
+        assert_eq!(cache.stats().entries, 0);
+        assert_eq!(tag(&mut cache, 0), constants::DW_TAG_compile_unit);
+        assert_eq!(cache.stats().entries, 0);
+
+        cache.reset_stats();
+        assert_eq!(cache.stats(), AbbreviationsCacheStats::default());
+    }
+
+    #[test]
+    fn test_abbreviations_cache_populated() {
+        let buf = abbreviations_cache_section();
+        let debug_abbrev = DebugAbbrev::new(&buf, LittleEndian);
+        let mut cache = AbbreviationsCache::new();
+        assert_eq!(cache.policy(), AbbreviationsCachePolicy::Populated);
+
+        // Lookups are not stored by the default policy.
+        cache.get(&debug_abbrev, DebugAbbrevOffset(0)).unwrap();
+        cache.get(&debug_abbrev, DebugAbbrevOffset(0)).unwrap();
+        assert_eq!(cache.stats().misses, 2);
+        assert_eq!(cache.stats().entries, 0);
+
+        cache.populate_offsets(
+            AbbreviationsCacheStrategy::All,
+            &debug_abbrev,
+            vec![6],
+            None,
+        );
+        let abbrevs = cache.get(&debug_abbrev, DebugAbbrevOffset(6)).unwrap();
+        assert_eq!(abbrevs.get(1).unwrap().tag(), constants::DW_TAG_type_unit);
+        assert_eq!(cache.stats().hits, 1);
+        assert_eq!(cache.stats().entries, 1);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_abbreviations_cache_contention() {
+        static BUF: [u8; 18] = [
+            1, 0x11, 0, 0, 0, 0, // DW_TAG_compile_unit
+            1, 0x41, 0, 0, 0, 0, // DW_TAG_type_unit
+            1, 0x3c, 0, 0, 0, 0, // DW_TAG_partial_unit
+        ];
+        const THREADS: usize = 4;
+        const LOOKUPS: usize = 100;
+
+        let cache = Arc::new(AbbreviationsCache::with_policy(
+            AbbreviationsCachePolicy::All,
+        ));
+        let threads: Vec<_> = (0..THREADS)
+            .map(|_| {
+                let cache = Arc::clone(&cache);
+                std::thread::spawn(move || {
+                    let debug_abbrev = DebugAbbrev::new(&BUF, LittleEndian);
+                    for i in 0..LOOKUPS {
+                        let offset = DebugAbbrevOffset(i % 3 * 6);
+                        cache.get(&debug_abbrev, offset).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        // Every lookup waits for the lock, so each thread misses each offset
+        // at most once, and every miss is stored.
+        let stats = cache.stats();
+        assert_eq!(stats.hits + stats.misses, THREADS * LOOKUPS);
+        assert!(stats.misses >= 3 && stats.misses <= 3 * THREADS);
+        assert_eq!(stats.insertions, stats.misses);
+        assert_eq!(stats.entries, 3);
+    }
+
+    #[test]
+    fn test_abbreviations_cache_file_type() {
+        let buf = abbreviations_cache_section();
+        let debug_abbrev = DebugAbbrev::new(&buf, LittleEndian);
+        let mut cache = AbbreviationsCache::with_policy(AbbreviationsCachePolicy::All);
+
+        let abbrevs = cache.get(&debug_abbrev, DebugAbbrevOffset(0)).unwrap();
+        assert!(Arc::ptr_eq(
+            &abbrevs,
+            &cache.get(&debug_abbrev, DebugAbbrevOffset(0)).unwrap()
+        ));
+
+        cache.set_file_type(DwarfFileType::Dwo);
+        assert!(!Arc::ptr_eq(
+            &abbrevs,
+            &cache.get(&debug_abbrev, DebugAbbrevOffset(0)).unwrap()
+        ));
+
+        cache.set_file_type(DwarfFileType::Main);
+        assert!(Arc::ptr_eq(
+            &abbrevs,
+            &cache.get(&debug_abbrev, DebugAbbrevOffset(0)).unwrap()
+        ));
+    }
+
+    #[test]
+    fn test_abbreviations_cache_copy_range() {
+        let buf = abbreviations_cache_section();
+        let debug_abbrev = DebugAbbrev::new(&buf, LittleEndian);
+        let mut package = AbbreviationsCache::new();
+        package.set_file_type(DwarfFileType::Dwo);
+        package.populate_offsets(
+            AbbreviationsCacheStrategy::All,
+            &debug_abbrev,
+            vec![12, 0, 6, 6],
+            None,
+        );
+        assert_eq!(package.stats().entries, 3);
+
+        let mut unit = AbbreviationsCache::new();
+        unit.set_file_type(DwarfFileType::Dwo);
+        unit.copy_range(&package, 6, 6);
+        assert_eq!(unit.stats().entries, 1);
+        let dwo_abbrev = DebugAbbrev::new(&buf[6..12], LittleEndian);
+        let abbrevs = unit.get(&dwo_abbrev, DebugAbbrevOffset(0)).unwrap();
+        assert_eq!(abbrevs.get(1).unwrap().tag(), constants::DW_TAG_type_unit);
+        assert_eq!(unit.stats().hits, 1);
+    }
+
+    #[test]
+    fn test_raw_abbreviations() {
+        #[rustfmt::skip]
+        let buf = Section::new()
+            .abbrev(2, constants::DW_TAG_subprogram, constants::DW_CHILDREN_no)
+                .abbrev_attr(constants::DW_AT_name, constants::DW_FORM_string)
+                .abbrev_attr_implicit_const(constants::DW_AT_decl_file, -1)
+                .abbrev_attr_null()
+            .abbrev(1, constants::DW_TAG_compile_unit, constants::DW_CHILDREN_yes)
+                .abbrev_attr_null()
+            // A duplicate code.
+            .abbrev(2, constants::DW_TAG_variable, constants::DW_CHILDREN_no)
+                .abbrev_attr_null()
+            .abbrev_null()
+            .abbrev(1, constants::DW_TAG_type_unit, constants::DW_CHILDREN_no)
+                .abbrev_attr_null()
+            .abbrev_null()
+            .get_contents()
+            .unwrap();
+        let debug_abbrev = DebugAbbrev::new(&buf, LittleEndian);
+
+        let mut abbrevs = debug_abbrev
+            .raw_abbreviations(DebugAbbrevOffset(0))
+            .unwrap();
+        let abbrev = abbrevs.next().unwrap().unwrap();
+        assert_eq!(abbrev.offset(), DebugAbbrevOffset(0));
+        assert_eq!(abbrev.length(), 10);
+        assert_eq!(abbrev.code(), 2);
+        assert_eq!(abbrev.tag(), constants::DW_TAG_subprogram);
+        assert!(!abbrev.has_children());
+        let mut attrs = abbrev.attributes();
+        assert_eq!(
+            attrs.next(),
+            Ok(Some(AttributeSpecification::new(
+                constants::DW_AT_name,
+                constants::DW_FORM_string,
+                None
+            )))
+        );
+        assert_eq!(
+            attrs.next(),
+            Ok(Some(AttributeSpecification::new(
+                constants::DW_AT_decl_file,
+                constants::DW_FORM_implicit_const,
+                Some(-1)
+            )))
+        );
+        assert_eq!(attrs.next(), Ok(None));
+
+        let abbrev = abbrevs.next().unwrap().unwrap();
+        assert_eq!(abbrev.offset(), DebugAbbrevOffset(10));
+        assert_eq!(abbrev.length(), 5);
+        assert!(abbrev.has_children());
+        assert_eq!(abbrev.attributes().next(), Ok(None));
+        let abbrev = abbrevs.next().unwrap().unwrap();
+        assert_eq!(abbrev.code(), 2);
+        assert_eq!(abbrev.tag(), constants::DW_TAG_variable);
+        assert_eq!(abbrevs.next(), Ok(None));
+        assert_eq!(abbrevs.offset(), DebugAbbrevOffset(21));
+
+        let mut abbrevs = debug_abbrev.raw_abbreviations(abbrevs.offset()).unwrap();
+        let abbrev = abbrevs.next().unwrap().unwrap();
+        assert_eq!(abbrev.tag(), constants::DW_TAG_type_unit);
+        assert_eq!(abbrevs.next(), Ok(None));
+        assert_eq!(abbrevs.next(), Ok(None));
+        assert_eq!(abbrevs.offset(), DebugAbbrevOffset(buf.len()));
+    }
+
+    #[test]
+    fn test_abbreviation_usage() {
+        use crate::read::DebugInfo;
+
+        #[rustfmt::skip]
+        let abbrev = Section::new()
+            .abbrev(1, constants::DW_TAG_compile_unit, constants::DW_CHILDREN_yes)
+                .abbrev_attr_null()
+            .abbrev(2, constants::DW_TAG_subprogram, constants::DW_CHILDREN_no)
+                .abbrev_attr(constants::DW_AT_name, constants::DW_FORM_string)
+                .abbrev_attr_null()
+            .abbrev(3, constants::DW_TAG_variable, constants::DW_CHILDREN_no)
+                .abbrev_attr_null()
+            .abbrev(10, constants::DW_TAG_base_type, constants::DW_CHILDREN_no)
+                .abbrev_attr_null()
+            .abbrev_null()
+            .get_contents()
+            .unwrap();
+        let info = Section::with_endian(test_assembler::Endian::Little)
+            .L32(16)
+            .L16(4)
+            .L32(0)
+            .D8(8)
+            .uleb(1)
+            .uleb(2)
+            .append_bytes(b"a\0")
+            .uleb(2)
+            .append_bytes(b"b\0")
+            .uleb(10)
+            .uleb(0)
+            .get_contents()
+            .unwrap();
+
+        let debug_abbrev = DebugAbbrev::new(&abbrev, LittleEndian);
+        let header = DebugInfo::new(&info, LittleEndian)
+            .units()
+            .next()
+            .unwrap()
+            .unwrap();
+        let abbrevs = header.abbreviations(&debug_abbrev).unwrap();
+        assert_eq!(
+            abbrevs.iter().map(Abbreviation::code).collect::<Vec<_>>(),
+            [1, 2, 3, 10]
+        );
+
+        let usage = AbbreviationUsage::new(&header, &abbrevs).unwrap();
+        assert_eq!(usage.iter().collect::<Vec<_>>(), [(1, 1), (2, 2), (10, 1)]);
+        assert_eq!(usage.count(2), 2);
+        assert_eq!(usage.count(3), 0);
+        assert_eq!(usage.entries(), 4);
+        assert_eq!(usage.null_entries(), 1);
+        assert_eq!(
+            usage
+                .unused(&abbrevs)
+                .map(Abbreviation::code)
+                .collect::<Vec<_>>(),
+            [3]
+        );
+    }
 }
@@ -0,0 +1,439 @@
+use alloc::vec::Vec;
+
+use crate::common::{DebugAbbrevOffset, DebugInfoOffset};
+use crate::constants;
+use crate::endianity::LittleEndian;
+use crate::read::range_index::{RangeIndex, RangeIndexEntry};
+use crate::read::{
+    AbbreviationsCacheStrategy, Dwarf, EndianSlice, Error, Range, Reader, ReaderOffset, Result,
+    Section, UnitHeader, UnitOffset, UnitType,
+};
+
+const MAGIC: &[u8; 8] = b"gimliacc";
+const VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct CachedUnit {
+    offset: u64,
+    length: u64,
+    abbrev_offset: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct CachedRange {
+    begin: u64,
+    end: u64,
+    unit: u32,
+    /// The offset of the entry within the unit, or 0 for line sequences.
+    entry: u64,
+}
+
+impl RangeIndexEntry for CachedRange {
+    fn range(&self) -> Range {
+        Range {
+            begin: self.begin,
+            end: self.end,
+        }
+    }
+}
+
+/// The sizes of the sections that the cache depends on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct SectionSizes {
+    debug_abbrev: u64,
+    debug_info: u64,
+    debug_line: u64,
+}
+
+impl SectionSizes {
+    fn new<R: Reader>(dwarf: &Dwarf<R>) -> Self {
+        SectionSizes {
+            debug_abbrev: dwarf.debug_abbrev.reader().len().into_u64(),
+            debug_info: dwarf.debug_info.reader().len().into_u64(),
+            debug_line: dwarf.debug_line.reader().len().into_u64(),
+        }
+    }
+}
+
+/// A cache of indexes derived from the DWARF sections of a file.
+///
+/// Building indexes for a large file can be slow. This cache can be serialized
+/// to a byte buffer with [`AcceleratorCache::to_bytes`], and then stored by the
+/// caller and reloaded with [`AcceleratorCache::load`] the next time the same
+/// file is used.
+///
+/// The cache contains:
+/// - the offsets of the compilation unit headers and their abbreviations,
+/// - the address ranges of the top level and nested subprograms,
+/// - the address ranges of the line sequences in each unit's line program.
+///
+/// The cache is keyed by the build ID of the file. The sizes of the
+/// `.debug_abbrev`, `.debug_info` and `.debug_line` sections are also
+/// recorded, and a cache that does not match is treated as stale.
+///
+/// ```rust,no_run
+/// # fn example<R: gimli::Reader>(
+/// #     dwarf: &gimli::Dwarf<R>,
+/// #     build_id: &[u8],
+/// #     stored: Option<Vec<u8>>,
+/// # ) -> gimli::Result<()> {
+/// let cached = match stored {
+///     Some(data) => gimli::AcceleratorCache::load(&data, build_id, dwarf).ok().flatten(),
+///     None => None,
+/// };
+/// let cache = match cached {
+///     Some(cache) => cache,
+///     None => {
+///         let cache = gimli::AcceleratorCache::build(dwarf, build_id)?;
+///         // Store `cache.to_bytes()` for the next run.
+///         cache
+///     }
+/// };
+/// if let Some((index, offset)) = cache.find_function(0x1234) {
+///     let unit = dwarf.unit(cache.unit_header(dwarf, index)?)?;
+///     let entry = unit.entry(offset)?;
+///     // ...
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AcceleratorCache {
+    build_id: Vec<u8>,
+    sizes: SectionSizes,
+    units: Vec<CachedUnit>,
+    functions: RangeIndex<CachedRange>,
+    sequences: RangeIndex<CachedRange>,
+}
+
+impl AcceleratorCache {
+    /// Build the cache for the compilation units in the `.debug_info` section.
+    ///
+    /// Type units are not included.
+    pub fn build<R: Reader>(dwarf: &Dwarf<R>, build_id: &[u8]) -> Result<Self> {
+        let mut units = Vec::new();
+        let mut functions = Vec::new();
+        let mut sequences = Vec::new();
+        let mut headers = dwarf.units();
+        while let Some(header) = headers.next()? {
+            match header.type_() {
+                UnitType::Type { .. } | UnitType::SplitType { .. } => continue,
+                _ => {}
+            }
+            let offset = match header.offset().as_debug_info_offset() {
+                Some(offset) => offset,
+                None => continue,
+            };
+            let index = units.len() as u32;
+            units.push(CachedUnit {
+                offset: offset.0.into_u64(),
+                length: header.length_including_self().into_u64(),
+                abbrev_offset: header.debug_abbrev_offset().0.into_u64(),
+            });
+
+            let unit = dwarf.unit(header)?;
+            let mut entries = unit.entries();
+            while let Some((_, entry)) = entries.next_dfs()? {
+                if entry.tag() != constants::DW_TAG_subprogram {
+                    continue;
+                }
+                let mut ranges = dwarf.die_ranges(&unit, entry)?;
+                while let Some(range) = ranges.next()? {
+                    functions.push(CachedRange {
+                        begin: range.begin,
+                        end: range.end,
+                        unit: index,
+                        entry: entry.offset().0.into_u64(),
+                    });
+                }
+            }
+
+            if let Some(program) = unit.line_program.clone() {
+                let (_, unit_sequences) = program.sequences()?;
+                for sequence in unit_sequences {
+                    sequences.push(CachedRange {
+                        begin: sequence.start,
+                        end: sequence.end,
+                        unit: index,
+                        entry: 0,
+                    });
+                }
+            }
+        }
+        Ok(AcceleratorCache {
+            build_id: build_id.to_vec(),
+            sizes: SectionSizes::new(dwarf),
+            units,
+            functions: RangeIndex::new(functions),
+            sequences: RangeIndex::new(sequences),
+        })
+    }
+
+    /// Load a cache that was serialized with [`AcceleratorCache::to_bytes`].
+    ///
+    /// Returns `Ok(None)` if the cache is for a different build ID, or if
+    /// the sizes of the sections in `dwarf` do not match those that were
+    /// used to build the cache.
+    ///
+    /// Returns `Error::InvalidAcceleratorCache` if the data is not a valid cache.
+    pub fn load<R: Reader>(data: &[u8], build_id: &[u8], dwarf: &Dwarf<R>) -> Result<Option<Self>> {
+        let cache = Self::parse(data)?;
+        if cache.build_id != build_id || cache.sizes != SectionSizes::new(dwarf) {
+            return Ok(None);
+        }
+        Ok(Some(cache))
+    }
+
+    fn parse(data: &[u8]) -> Result<Self> {
+        let mut input = EndianSlice::new(data, LittleEndian);
+        if input.split(MAGIC.len()).ok().map(|magic| magic.slice()) != Some(&MAGIC[..])
+            || input.read_u32().ok() != Some(VERSION)
+        {
+            return Err(Error::InvalidAcceleratorCache);
+        }
+        Self::parse_contents(&mut input).map_err(|_| Error::InvalidAcceleratorCache)
+    }
+
+    fn parse_contents(input: &mut EndianSlice<'_, LittleEndian>) -> Result<Self> {
+        let build_id_len = input.read_u32()? as usize;
+        let build_id = input.split(build_id_len)?.slice().to_vec();
+        let sizes = SectionSizes {
+            debug_abbrev: input.read_u64()?,
+            debug_info: input.read_u64()?,
+            debug_line: input.read_u64()?,
+        };
+
+        let count = input.read_u32()?;
+        let mut units = Vec::new();
+        for _ in 0..count {
+            units.push(CachedUnit {
+                offset: input.read_u64()?,
+                length: input.read_u64()?,
+                abbrev_offset: input.read_u64()?,
+            });
+        }
+        let functions = parse_ranges(input, units.len())?;
+        let sequences = parse_ranges(input, units.len())?;
+        if !input.is_empty() {
+            return Err(Error::InvalidAcceleratorCache);
+        }
+        Ok(AcceleratorCache {
+            build_id,
+            sizes,
+            units,
+            functions,
+            sequences,
+        })
+    }
+
+    /// Serialize the cache to a byte buffer.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut w = Vec::new();
+        w.extend_from_slice(MAGIC);
+        w.extend_from_slice(&VERSION.to_le_bytes());
+        w.extend_from_slice(&(self.build_id.len() as u32).to_le_bytes());
+        w.extend_from_slice(&self.build_id);
+        w.extend_from_slice(&self.sizes.debug_abbrev.to_le_bytes());
+        w.extend_from_slice(&self.sizes.debug_info.to_le_bytes());
+        w.extend_from_slice(&self.sizes.debug_line.to_le_bytes());
+        w.extend_from_slice(&(self.units.len() as u32).to_le_bytes());
+        for unit in &self.units {
+            w.extend_from_slice(&unit.offset.to_le_bytes());
+            w.extend_from_slice(&unit.length.to_le_bytes());
+            w.extend_from_slice(&unit.abbrev_offset.to_le_bytes());
+        }
+        for ranges in [&self.functions, &self.sequences] {
+            let ranges = ranges.entries();
+            w.extend_from_slice(&(ranges.len() as u32).to_le_bytes());
+            for range in ranges {
+                w.extend_from_slice(&range.begin.to_le_bytes());
+                w.extend_from_slice(&range.end.to_le_bytes());
+                w.extend_from_slice(&range.unit.to_le_bytes());
+                w.extend_from_slice(&range.entry.to_le_bytes());
+            }
+        }
+        w
+    }
+
+    /// Return the build ID that the cache was built for.
+    pub fn build_id(&self) -> &[u8] {
+        &self.build_id
+    }
+
+    /// Return the number of units in the cache.
+    pub fn unit_count(&self) -> usize {
+        self.units.len()
+    }
+
+    /// Return the offset of the unit header for the unit at the given index.
+    pub fn unit_offset<T: ReaderOffset>(&self, index: usize) -> Option<DebugInfoOffset<T>> {
+        let offset = self.units.get(index)?.offset;
+        T::from_u64(offset).ok().map(DebugInfoOffset)
+    }
+
+    /// Parse the unit header for the unit at the given index.
+    ///
+    /// This avoids the need to iterate over all of the preceding unit headers.
+    pub fn unit_header<R: Reader>(&self, dwarf: &Dwarf<R>, index: usize) -> Result<UnitHeader<R>> {
+        let offset = self
+            .unit_offset(index)
+            .ok_or(Error::InvalidAcceleratorCache)?;
+        let header = dwarf.debug_info.header_from_offset(offset)?;
+        if header.length_including_self().into_u64() != self.units[index].length {
+            return Err(Error::InvalidAcceleratorCache);
+        }
+        Ok(header)
+    }
+
+    /// Parse the abbreviations for the cached units and store them in the
+    /// abbreviations cache of `dwarf`.
+    ///
+    /// This is equivalent to `Dwarf::populate_abbreviations_cache`, except
+    /// that it uses the cached abbreviation offsets instead of iterating over
+    /// the unit headers.
+    pub fn populate_abbreviations_cache<R: Reader>(
+        &self,
+        dwarf: &mut Dwarf<R>,
+        strategy: AbbreviationsCacheStrategy,
+    ) {
+        let offsets = self.units.iter().map(|unit| unit.abbrev_offset).collect();
+        let max_abbreviations = dwarf.max_abbreviations();
+        dwarf.abbreviations_cache.populate_offsets(
+            strategy,
+            &dwarf.debug_abbrev,
+            offsets,
+            max_abbreviations,
+        );
+    }
+
+    /// Return the offset of the abbreviations for the unit at the given index.
+    pub fn unit_abbrev_offset<T: ReaderOffset>(
+        &self,
+        index: usize,
+    ) -> Option<DebugAbbrevOffset<T>> {
+        let offset = self.units.get(index)?.abbrev_offset;
+        T::from_u64(offset).ok().map(DebugAbbrevOffset)
+    }
+
+    /// Find the subprogram containing the given address.
+    ///
+    /// Returns the index of the unit and the offset of the subprogram entry
+    /// within the unit. If multiple subprograms contain the address, then the
+    /// subprogram whose range starts closest to the address is returned.
+    pub fn find_function<T: ReaderOffset>(&self, address: u64) -> Option<(usize, UnitOffset<T>)> {
+        let range = self.functions.find(address)?;
+        let entry = T::from_u64(range.entry).ok()?;
+        Some((range.unit as usize, UnitOffset(entry)))
+    }
+
+    /// Find the unit with a line sequence containing the given address.
+    ///
+    /// Returns the index of the unit.
+    pub fn find_line_unit(&self, address: u64) -> Option<usize> {
+        self.sequences
+            .find(address)
+            .map(|range| range.unit as usize)
+    }
+}
+
+fn parse_ranges(
+    input: &mut EndianSlice<'_, LittleEndian>,
+    unit_count: usize,
+) -> Result<RangeIndex<CachedRange>> {
+    let count = input.read_u32()?;
+    let mut ranges = Vec::new();
+    for _ in 0..count {
+        let begin = input.read_u64()?;
+        let end = input.read_u64()?;
+        let unit = input.read_u32()?;
+        let entry = input.read_u64()?;
+        if unit as usize >= unit_count {
+            return Err(Error::InvalidAcceleratorCache);
+        }
+        ranges.push(CachedRange {
+            begin,
+            end,
+            unit,
+            entry,
+        });
+    }
+    Ok(RangeIndex::new(ranges))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::*;
+    use crate::read::abbrev::tests::AbbrevSectionMethods;
+    use crate::read::{DebugAbbrev, DebugInfo};
+    use crate::test_util::{compile_unit, test_dwarf, GimliSectionMethods};
+    use test_assembler::{Endian, Label, LabelMaker, Section};
+
+    #[test]
+    fn test_accelerator_cache() {
+        #[rustfmt::skip]
+        let abbrev = Section::with_endian(Endian::Little)
+            .abbrev(1, DW_TAG_compile_unit, DW_CHILDREN_yes)
+                .abbrev_attr_null()
+            .abbrev(2, DW_TAG_subprogram, DW_CHILDREN_yes)
+                .abbrev_attr(DW_AT_low_pc, DW_FORM_addr)
+                .abbrev_attr(DW_AT_high_pc, DW_FORM_udata)
+                .abbrev_attr_null()
+            .abbrev_null();
+        let abbrev = abbrev.get_contents().unwrap();
+
+        let outer = Label::new();
+        let inner = Label::new();
+        #[rustfmt::skip]
+        let entries = Section::with_endian(Endian::Little)
+            .uleb(1)
+                .mark(&outer)
+                .uleb(2).L64(0x1000).uleb(0x100)
+                    .mark(&inner)
+                    .uleb(2).L64(0x1040).uleb(0x10)
+                    .D8(0)
+                .D8(0);
+        let info = compile_unit(entries);
+        let offset = |label: &Label| UnitOffset(label.value().unwrap() as usize);
+
+        let mut dwarf = test_dwarf(&abbrev, &info);
+        let cache = AcceleratorCache::build(&dwarf, b"id").unwrap();
+        assert_eq!(cache.unit_count(), 1);
+        assert_eq!(cache.find_function(0x1000), Some((0, offset(&outer))));
+        assert_eq!(cache.find_function(0x1044), Some((0, offset(&inner))));
+        assert_eq!(cache.find_function(0x1050), Some((0, offset(&outer))));
+        assert_eq!(cache.find_function::<usize>(0x1100), None);
+        assert_eq!(cache.find_line_unit(0x1000), None);
+
+        let bytes = cache.to_bytes();
+        let loaded = AcceleratorCache::load(&bytes, b"id", &dwarf).unwrap();
+        assert_eq!(loaded.as_ref(), Some(&cache));
+        let loaded = loaded.unwrap();
+        let header = loaded.unit_header(&dwarf, 0).unwrap();
+        assert_eq!(
+            header.offset().as_debug_info_offset(),
+            Some(DebugInfoOffset(0))
+        );
+        loaded.populate_abbreviations_cache(&mut dwarf, AbbreviationsCacheStrategy::All);
+        assert_eq!(dwarf.abbreviations_cache.stats().entries, 1);
+
+        // Stale caches are rejected.
+        assert_eq!(AcceleratorCache::load(&bytes, b"other", &dwarf), Ok(None));
+        let truncated = Dwarf {
+            debug_abbrev: DebugAbbrev::new(&abbrev, crate::LittleEndian),
+            debug_info: DebugInfo::new(&info[..info.len() - 1], crate::LittleEndian),
+            ..Default::default()
+        };
+        assert_eq!(AcceleratorCache::load(&bytes, b"id", &truncated), Ok(None));
+
+        // Invalid caches are errors.
+        assert_eq!(
+            AcceleratorCache::load(&bytes[..bytes.len() - 1], b"id", &dwarf),
+            Err(Error::InvalidAcceleratorCache)
+        );
+        assert_eq!(
+            AcceleratorCache::load(&[], b"id", &dwarf),
+            Err(Error::InvalidAcceleratorCache)
+        );
+    }
+}
@@ -38,6 +38,34 @@ impl<R: Reader> DebugAddr<R> {
         )?)?;
         input.read_address(address_size)
     }
+
+    /// Returns the segment selector and address at the given `base` and `index`.
+    ///
+    /// This is the same as `get_address`, except that each entry in the set of
+    /// addresses is a segment selector followed by an address. This is used for
+    /// segmented architectures, where the header has a nonzero
+    /// `segment_selector_size`. If `segment_selector_size` is 0, then the
+    /// returned segment selector is 0.
+    pub fn get_segment_address(
+        &self,
+        address_size: u8,
+        segment_selector_size: u8,
+        base: DebugAddrBase<R::Offset>,
+        index: DebugAddrIndex<R::Offset>,
+    ) -> Result<(u64, u64)> {
+        let input = &mut self.section.clone();
+        input.skip(base.0)?;
+        input.skip(R::Offset::from_u64(
+            index.0.into_u64() * (u64::from(segment_selector_size) + u64::from(address_size)),
+        )?)?;
+        let segment = if segment_selector_size != 0 {
+            input.read_address(segment_selector_size)?
+        } else {
+            0
+        };
+        let address = input.read_address(address_size)?;
+        Ok((segment, address))
+    }
 }
 
 impl<T> DebugAddr<T> {
@@ -115,4 +143,43 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_get_segment_address() {
+        let zero = Label::new();
+        let length = Label::new();
+        let start = Label::new();
+        let first = Label::new();
+        let end = Label::new();
+        let mut section = Section::with_endian(Endian::Little)
+            .mark(&zero)
+            .initial_length(Format::Dwarf32, &length, &start)
+            .D16(5)
+            .D8(4)
+            .D8(2)
+            .mark(&first);
+        for i in 0..20 {
+            section = section.D16(i).D32(1000 + u32::from(i));
+        }
+        section = section.mark(&end);
+        length.set_const((&end - &start) as u64);
+
+        let section = section.get_contents().unwrap();
+        let debug_addr = DebugAddr::from(EndianSlice::new(&section, LittleEndian));
+        let base = DebugAddrBase((&first - &zero) as usize);
+        assert_eq!(
+            debug_addr.get_segment_address(4, 2, base, DebugAddrIndex(0)),
+            Ok((0, 1000))
+        );
+        assert_eq!(
+            debug_addr.get_segment_address(4, 2, base, DebugAddrIndex(19)),
+            Ok((19, 1019))
+        );
+        assert_eq!(
+            debug_addr.get_segment_address(4, 0, base, DebugAddrIndex(1)),
+            debug_addr
+                .get_address(4, base, DebugAddrIndex(1))
+                .map(|address| (0, address))
+        );
+    }
 }
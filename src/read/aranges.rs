@@ -1,6 +1,15 @@
+#[cfg(feature = "read")]
+use alloc::vec::Vec;
+
 use crate::common::{DebugArangesOffset, DebugInfoOffset, Encoding, SectionId};
 use crate::endianity::Endianity;
-use crate::read::{EndianSlice, Error, Range, Reader, ReaderOffset, Result, Section};
+#[cfg(feature = "read")]
+use crate::read::range_index::{RangeIndex, RangeIndexEntry};
+#[cfg(feature = "read")]
+use crate::read::{Dwarf, UnitType};
+use crate::read::{
+    EndianSlice, Error, Range, Reader, ReaderOffset, Result, ResultIterator, Section,
+};
 
 /// The `DebugAranges` struct represents the DWARF address range information
 /// found in the `.debug_aranges` section.
@@ -122,6 +131,24 @@ impl<R: Reader> fallible_iterator::FallibleIterator for ArangeHeaderIter<R> {
     }
 }
 
+impl<R: Reader> ArangeHeaderIter<R> {
+    /// Return an adapter that implements `Iterator`.
+    ///
+    /// See [`ResultIterator`] for more information.
+    pub fn iterator(self) -> ResultIterator<Self> {
+        ResultIterator::new(self)
+    }
+}
+
+impl<R: Reader> Iterator for ResultIterator<ArangeHeaderIter<R>> {
+    type Item = Result<ArangeHeader<R>>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_with(ArangeHeaderIter::next)
+    }
+}
+
 /// A header for a set of entries in the `.debug_arange` section.
 ///
 /// These entries all belong to a single unit.
@@ -285,8 +312,27 @@ impl<R: Reader> fallible_iterator::FallibleIterator for ArangeEntryIter<R> {
     }
 }
 
+impl<R: Reader> ArangeEntryIter<R> {
+    /// Return an adapter that implements `Iterator`.
+    ///
+    /// See [`ResultIterator`] for more information.
+    pub fn iterator(self) -> ResultIterator<Self> {
+        ResultIterator::new(self)
+    }
+}
+
+impl<R: Reader> Iterator for ResultIterator<ArangeEntryIter<R>> {
+    type Item = Result<ArangeEntry>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_with(ArangeEntryIter::next)
+    }
+}
+
 /// A single parsed arange.
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ArangeEntry {
     segment: Option<u64>,
     address: u64,
@@ -361,6 +407,216 @@ impl ArangeEntry {
     }
 }
 
+/// An address range from the `.debug_aranges` section, and the unit it belongs to.
+#[cfg(feature = "read")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ArangeLookupEntry<T = usize> {
+    /// The address range.
+    pub range: Range,
+    /// The offset of the header of the set containing this range.
+    pub header_offset: DebugArangesOffset<T>,
+    /// The offset of the unit in the `.debug_info` section.
+    pub debug_info_offset: DebugInfoOffset<T>,
+}
+
+#[cfg(feature = "read")]
+impl<T> RangeIndexEntry for ArangeLookupEntry<T> {
+    fn range(&self) -> Range {
+        self.range
+    }
+}
+
+/// A pair of address ranges in the `.debug_aranges` section that overlap.
+///
+/// These are commonly caused by linkers that do not remove the ranges of
+/// discarded or folded code.
+#[cfg(feature = "read")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ArangeOverlap<T = usize> {
+    /// The range that starts first.
+    pub first: ArangeLookupEntry<T>,
+    /// The range that starts within `first`.
+    pub second: ArangeLookupEntry<T>,
+}
+
+#[cfg(feature = "read")]
+impl<T: PartialEq> ArangeOverlap<T> {
+    /// Return true if both ranges are identical and belong to the same unit.
+    pub fn is_duplicate(&self) -> bool {
+        self.first.range == self.second.range
+            && self.first.debug_info_offset == self.second.debug_info_offset
+    }
+}
+
+/// The units in the `.debug_info` section that are not covered by the
+/// `.debug_aranges` section.
+///
+/// Consumers must scan the `.debug_info` section to find the addresses
+/// of the units in `missing_units`.
+#[cfg(feature = "read")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArangeCoverage<T = usize> {
+    /// The number of compilation units in the `.debug_info` section.
+    pub units: usize,
+    /// The compilation units that have no address ranges in the `.debug_aranges` section.
+    pub missing_units: Vec<DebugInfoOffset<T>>,
+    /// The offsets in sets in the `.debug_aranges` section that do not refer to the
+    /// start of a unit in the `.debug_info` section.
+    pub unknown_units: Vec<DebugInfoOffset<T>>,
+}
+
+/// A lookup table for all of the address ranges in the `.debug_aranges` section.
+///
+/// The ranges from all sets are sorted so that the unit containing an address
+/// can be found using a binary search. Empty ranges are ignored.
+/// Segment selectors are ignored.
+///
+/// ```rust,no_run
+/// # fn example<R: gimli::Reader>(dwarf: &gimli::Dwarf<R>) -> gimli::Result<()> {
+/// let lookup = dwarf.debug_aranges.lookup()?;
+/// if let Some(entry) = lookup.find(0x1234) {
+///     println!("{:?}", entry.debug_info_offset);
+/// }
+/// let coverage = lookup.coverage(dwarf)?;
+/// for offset in &coverage.missing_units {
+///     // Scan the unit in `.debug_info` instead.
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(feature = "read")]
+#[derive(Debug, Clone)]
+pub struct ArangeLookup<T = usize> {
+    entries: RangeIndex<ArangeLookupEntry<T>>,
+    overlaps: Vec<ArangeOverlap<T>>,
+    /// The sorted offsets of the units that have at least one range.
+    units: Vec<DebugInfoOffset<T>>,
+}
+
+#[cfg(feature = "read")]
+impl<R: Reader> DebugAranges<R> {
+    /// Build a lookup table for all of the address ranges in the section.
+    pub fn lookup(&self) -> Result<ArangeLookup<R::Offset>> {
+        let mut entries = Vec::new();
+        let mut units = Vec::new();
+        let mut headers = self.headers();
+        while let Some(header) = headers.next()? {
+            let mut header_entries = header.entries();
+            while let Some(entry) = header_entries.next()? {
+                let range = entry.range();
+                if range.begin >= range.end {
+                    continue;
+                }
+                entries.push(ArangeLookupEntry {
+                    range,
+                    header_offset: header.offset(),
+                    debug_info_offset: header.debug_info_offset(),
+                });
+                units.push(header.debug_info_offset());
+            }
+        }
+        units.sort_unstable();
+        units.dedup();
+
+        let entries = RangeIndex::new(entries);
+        let mut overlaps = Vec::new();
+        let mut furthest: Option<&ArangeLookupEntry<_>> = None;
+        for entry in entries.entries() {
+            match furthest {
+                Some(prev) => {
+                    if entry.range.begin < prev.range.end {
+                        overlaps.push(ArangeOverlap {
+                            first: *prev,
+                            second: *entry,
+                        });
+                    }
+                    if entry.range.end > prev.range.end {
+                        furthest = Some(entry);
+                    }
+                }
+                None => furthest = Some(entry),
+            }
+        }
+
+        Ok(ArangeLookup {
+            entries,
+            overlaps,
+            units,
+        })
+    }
+}
+
+#[cfg(feature = "read")]
+impl<T: ReaderOffset> ArangeLookup<T> {
+    /// Return all of the non-empty ranges, sorted by address.
+    pub fn entries(&self) -> &[ArangeLookupEntry<T>] {
+        self.entries.entries()
+    }
+
+    /// Return the range containing the given address.
+    ///
+    /// If multiple ranges contain the address, then the range that
+    /// starts closest to the address is returned. Of the ranges that start
+    /// at the same address, the smallest range is returned.
+    pub fn find(&self, address: u64) -> Option<&ArangeLookupEntry<T>> {
+        self.find_all(address).next()
+    }
+
+    /// Return all of the ranges containing the given address.
+    ///
+    /// The ranges are returned in decreasing order of their start address.
+    pub fn find_all(&self, address: u64) -> impl Iterator<Item = &ArangeLookupEntry<T>> {
+        self.entries.find_all(address)
+    }
+
+    /// Return the pairs of ranges that overlap.
+    ///
+    /// Each range that starts within an earlier range is reported once, paired with
+    /// the earlier range that extends the furthest.
+    pub fn overlaps(&self) -> &[ArangeOverlap<T>] {
+        &self.overlaps
+    }
+
+    /// Return true if the given unit has at least one range.
+    pub fn has_unit(&self, debug_info_offset: DebugInfoOffset<T>) -> bool {
+        self.units.binary_search(&debug_info_offset).is_ok()
+    }
+
+    /// Determine which compilation units in `dwarf.debug_info` have no ranges.
+    ///
+    /// Type units are not included.
+    pub fn coverage<R: Reader<Offset = T>>(&self, dwarf: &Dwarf<R>) -> Result<ArangeCoverage<T>> {
+        let mut coverage = ArangeCoverage {
+            units: 0,
+            missing_units: Vec::new(),
+            unknown_units: Vec::new(),
+        };
+        let mut offsets = Vec::new();
+        let mut headers = dwarf.debug_info.units();
+        while let Some(header) = headers.next()? {
+            match header.type_() {
+                UnitType::Type { .. } | UnitType::SplitType { .. } => continue,
+                _ => {}
+            }
+            let offset = match header.offset().as_debug_info_offset() {
+                Some(offset) => offset,
+                None => continue,
+            };
+            coverage.units += 1;
+            if !self.has_unit(offset) {
+                coverage.missing_units.push(offset);
+            }
+            offsets.push(offset);
+        }
+        for &offset in &self.units {
+            if offsets.binary_search(&offset).is_err() {
+                coverage.unknown_units.push(offset);
+            }
+        }
+        Ok(coverage)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -582,6 +838,28 @@ mod tests {
         );
     }
 
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_serde() {
+        let entries = [
+            ArangeEntry {
+                segment: None,
+                address: 0x0403_0201,
+                length: 0x0807_0605,
+            },
+            ArangeEntry {
+                segment: Some(0x1817_1615_1413_1211),
+                address: 0x0403_0201,
+                length: 0x0807_0605,
+            },
+        ];
+        let json = serde_json::to_string(&entries).unwrap();
+        assert_eq!(
+            serde_json::from_str::<[ArangeEntry; 2]>(&json).unwrap(),
+            entries
+        );
+    }
+
     #[test]
     fn test_parse_entry_segment() {
         let encoding = Encoding {
@@ -647,4 +925,110 @@ mod tests {
             })
         );
     }
+
+    #[test]
+    #[cfg(feature = "read")]
+    fn test_lookup() {
+        use crate::constants::*;
+        use crate::read::abbrev::tests::AbbrevSectionMethods;
+        use crate::read::{DebugAbbrev, DebugInfo};
+        use crate::test_util::GimliSectionMethods;
+        use test_assembler::{Endian, Label, LabelMaker, Section};
+
+        fn set(section: Section, debug_info_offset: u32, ranges: &[(u32, u32)]) -> Section {
+            let length = Label::new();
+            let start = Label::new();
+            let end = Label::new();
+            let mut section = section
+                .initial_length(Format::Dwarf32, &length, &start)
+                .L16(2)
+                .L32(debug_info_offset)
+                .D8(4)
+                .D8(0)
+                .L32(0);
+            for &(address, length) in ranges {
+                section = section.L32(address).L32(length);
+            }
+            section = section.L32(0).L32(0).mark(&end);
+            length.set_const((&end - &start) as u64);
+            section
+        }
+
+        let aranges = Section::with_endian(Endian::Little);
+        let aranges = set(
+            aranges,
+            0,
+            &[(0x1000, 0x100), (0x2000, 0x10), (0x1000, 0x100)],
+        );
+        let aranges = set(aranges, 0x40, &[(0x1080, 0x10), (0x3000, 0)]);
+        let aranges = aranges.get_contents().unwrap();
+        let debug_aranges = DebugAranges::new(&aranges, LittleEndian);
+        let lookup = debug_aranges.lookup().unwrap();
+
+        let range = |begin, end| Range { begin, end };
+        let entry0 = ArangeLookupEntry {
+            range: range(0x1000, 0x1100),
+            header_offset: DebugArangesOffset(0),
+            debug_info_offset: DebugInfoOffset(0),
+        };
+        let entry1 = ArangeLookupEntry {
+            range: range(0x1080, 0x1090),
+            header_offset: DebugArangesOffset(0x30),
+            debug_info_offset: DebugInfoOffset(0x40),
+        };
+        let entry2 = ArangeLookupEntry {
+            range: range(0x2000, 0x2010),
+            ..entry0
+        };
+        assert_eq!(lookup.entries(), [entry0, entry0, entry1, entry2]);
+
+        assert_eq!(lookup.find(0xfff), None);
+        assert_eq!(lookup.find(0x1000), Some(&entry0));
+        assert_eq!(lookup.find(0x1088), Some(&entry1));
+        assert_eq!(
+            lookup.find_all(0x1088).collect::<Vec<_>>(),
+            [&entry1, &entry0, &entry0]
+        );
+        assert_eq!(lookup.find(0x10a0), Some(&entry0));
+        assert_eq!(lookup.find(0x1100), None);
+        assert_eq!(lookup.find(0x200f), Some(&entry2));
+        assert_eq!(lookup.find(0x3000), None);
+
+        let overlaps = lookup.overlaps();
+        assert_eq!(overlaps.len(), 2);
+        assert!(overlaps[0].is_duplicate());
+        assert_eq!(
+            overlaps[1],
+            ArangeOverlap {
+                first: entry0,
+                second: entry1,
+            }
+        );
+        assert!(!overlaps[1].is_duplicate());
+
+        #[rustfmt::skip]
+        let abbrev = Section::with_endian(Endian::Little)
+            .abbrev(1, DW_TAG_compile_unit, DW_CHILDREN_no)
+                .abbrev_attr_null()
+            .abbrev_null();
+        let abbrev = abbrev.get_contents().unwrap();
+        let mut info = Section::with_endian(Endian::Little);
+        for _ in 0..2 {
+            info = info.L32(8).L16(4).L32(0).D8(4).uleb(1);
+        }
+        let info = info.get_contents().unwrap();
+        let dwarf = Dwarf {
+            debug_abbrev: DebugAbbrev::new(&abbrev, LittleEndian),
+            debug_info: DebugInfo::new(&info, LittleEndian),
+            ..Default::default()
+        };
+        assert_eq!(
+            lookup.coverage(&dwarf),
+            Ok(ArangeCoverage {
+                units: 2,
+                missing_units: vec![DebugInfoOffset(12)],
+                unknown_units: vec![DebugInfoOffset(0x40)],
+            })
+        );
+    }
 }
@@ -0,0 +1,367 @@
+use alloc::vec::Vec;
+
+use crate::common::UnitSectionOffset;
+use crate::constants;
+use crate::read::{
+    Attribute, AttributeValue, DebuggingInformationEntry, EntriesTreeNode, Expression, Reader,
+    Result, UnitOffset, UnitRef,
+};
+
+/// A call site within a subprogram.
+///
+/// This is parsed from a `DW_TAG_call_site` entry, or from the
+/// `DW_TAG_GNU_call_site` entry that was used prior to DWARF 5.
+#[derive(Debug, Clone)]
+pub struct CallSite<R: Reader> {
+    /// The offset of the call site entry.
+    pub offset: UnitOffset<R::Offset>,
+    /// The return address after the call.
+    ///
+    /// This is `DW_AT_call_return_pc`, or `DW_AT_low_pc` for GNU call sites.
+    pub return_pc: Option<u64>,
+    /// The address of the call instruction.
+    pub call_pc: Option<u64>,
+    /// Whether the call is a tail call.
+    pub tail_call: bool,
+    /// The entry for the called subprogram.
+    ///
+    /// This is `DW_AT_call_origin`, or `DW_AT_abstract_origin` for GNU call sites.
+    pub origin: Option<UnitSectionOffset<R::Offset>>,
+    /// The name of the called subprogram.
+    ///
+    /// This is only set if the origin is within the same unit.
+    pub origin_name: Option<R>,
+    /// The address of the called subprogram.
+    ///
+    /// This is only set if the origin is within the same unit.
+    pub origin_pc: Option<u64>,
+    /// An expression that computes the address of the called subprogram,
+    /// for indirect calls.
+    pub target: Option<Expression<R>>,
+    /// Whether the target expression uses registers that are clobbered by the call.
+    pub target_clobbered: bool,
+    /// The parameters that are passed to the called subprogram.
+    pub parameters: Vec<CallSiteParameter<R>>,
+}
+
+/// A parameter at a call site.
+///
+/// This is parsed from a `DW_TAG_call_site_parameter` entry, or from the
+/// `DW_TAG_GNU_call_site_parameter` entry that was used prior to DWARF 5.
+#[derive(Debug, Clone)]
+pub struct CallSiteParameter<R: Reader> {
+    /// The offset of the call site parameter entry.
+    pub offset: UnitOffset<R::Offset>,
+    /// The name of the parameter.
+    pub name: Option<R>,
+    /// The location where the parameter is passed.
+    pub location: Option<Expression<R>>,
+    /// An expression for the value of the parameter at the time of the call.
+    pub value: Option<Expression<R>>,
+    /// The location of the data referenced by the parameter, if the parameter
+    /// is a reference.
+    pub data_location: Option<Expression<R>>,
+    /// An expression for the value of the data referenced by the parameter.
+    pub data_value: Option<Expression<R>>,
+    /// The entry for the formal parameter of the called subprogram.
+    pub parameter: Option<UnitSectionOffset<R::Offset>>,
+}
+
+impl<'a, R: Reader> UnitRef<'a, R> {
+    /// Return the call sites within the given subprogram.
+    ///
+    /// This includes call sites within nested lexical blocks and inlined
+    /// subroutines, but excludes call sites within nested subprograms.
+    pub fn call_sites(&self, subprogram: UnitOffset<R::Offset>) -> Result<Vec<CallSite<R>>> {
+        let mut call_sites = Vec::new();
+        let mut tree = self.entries_tree(Some(subprogram))?;
+        self.collect_call_sites(tree.root()?, &mut call_sites)?;
+        Ok(call_sites)
+    }
+
+    fn collect_call_sites(
+        &self,
+        node: EntriesTreeNode<'_, '_, '_, R>,
+        call_sites: &mut Vec<CallSite<R>>,
+    ) -> Result<()> {
+        let mut children = node.children();
+        while let Some(child) = children.next()? {
+            match child.entry().tag() {
+                constants::DW_TAG_call_site | constants::DW_TAG_GNU_call_site => {
+                    call_sites.push(self.call_site(child)?);
+                }
+                constants::DW_TAG_subprogram => {}
+                _ => self.collect_call_sites(child, call_sites)?,
+            }
+        }
+        Ok(())
+    }
+
+    fn call_site(&self, node: EntriesTreeNode<'_, '_, '_, R>) -> Result<CallSite<R>> {
+        let entry = node.entry();
+        let mut call_site = CallSite {
+            offset: entry.offset(),
+            return_pc: None,
+            call_pc: None,
+            tail_call: false,
+            origin: None,
+            origin_name: None,
+            origin_pc: None,
+            target: None,
+            target_clobbered: false,
+            parameters: Vec::new(),
+        };
+        let mut origin = None;
+        let mut attrs = entry.attrs();
+        while let Some(attr) = attrs.next()? {
+            match attr.name() {
+                constants::DW_AT_call_return_pc | constants::DW_AT_low_pc => {
+                    call_site.return_pc = self.attr_address(attr.value())?;
+                }
+                constants::DW_AT_call_pc => {
+                    call_site.call_pc = self.attr_address(attr.value())?;
+                }
+                constants::DW_AT_call_tail_call | constants::DW_AT_GNU_tail_call => {
+                    call_site.tail_call = flag_value(&attr);
+                }
+                constants::DW_AT_call_origin | constants::DW_AT_abstract_origin => {
+                    origin = Some(attr.value());
+                }
+                constants::DW_AT_call_target | constants::DW_AT_GNU_call_site_target => {
+                    call_site.target = attr.exprloc_value();
+                }
+                constants::DW_AT_call_target_clobbered
+                | constants::DW_AT_GNU_call_site_target_clobbered => {
+                    call_site.target = attr.exprloc_value();
+                    call_site.target_clobbered = true;
+                }
+                _ => {}
+            }
+        }
+
+        if let Some(origin) = origin {
+            call_site.origin = self.reference_offset(&origin);
+            if let Some(origin) = self.local_reference(&origin) {
+                let entry = self.entry(origin)?;
+                if let Some(name) = entry.attr_value(constants::DW_AT_name)? {
+                    call_site.origin_name = Some(self.attr_string(name)?);
+                }
+                if let Some(low_pc) = entry.attr_value(constants::DW_AT_low_pc)? {
+                    call_site.origin_pc = self.attr_address(low_pc)?;
+                }
+            }
+        }
+
+        let mut children = node.children();
+        while let Some(child) = children.next()? {
+            match child.entry().tag() {
+                constants::DW_TAG_call_site_parameter
+                | constants::DW_TAG_GNU_call_site_parameter => {
+                    call_site
+                        .parameters
+                        .push(self.call_site_parameter(child.entry())?);
+                }
+                _ => {}
+            }
+        }
+        Ok(call_site)
+    }
+
+    fn call_site_parameter(
+        &self,
+        entry: &DebuggingInformationEntry<'_, '_, R>,
+    ) -> Result<CallSiteParameter<R>> {
+        let mut parameter = CallSiteParameter {
+            offset: entry.offset(),
+            name: None,
+            location: None,
+            value: None,
+            data_location: None,
+            data_value: None,
+            parameter: None,
+        };
+        let mut attrs = entry.attrs();
+        while let Some(attr) = attrs.next()? {
+            match attr.name() {
+                constants::DW_AT_name => {
+                    parameter.name = Some(self.attr_string(attr.value())?);
+                }
+                constants::DW_AT_location => {
+                    parameter.location = attr.exprloc_value();
+                }
+                constants::DW_AT_call_value | constants::DW_AT_GNU_call_site_value => {
+                    parameter.value = attr.exprloc_value();
+                }
+                constants::DW_AT_call_data_location => {
+                    parameter.data_location = attr.exprloc_value();
+                }
+                constants::DW_AT_call_data_value | constants::DW_AT_GNU_call_site_data_value => {
+                    parameter.data_value = attr.exprloc_value();
+                }
+                constants::DW_AT_call_parameter | constants::DW_AT_abstract_origin => {
+                    parameter.parameter = self.reference_offset(&attr.value());
+                }
+                _ => {}
+            }
+        }
+        Ok(parameter)
+    }
+
+    /// Convert a reference attribute value to an offset in the `.debug_info`
+    /// or `.debug_types` section.
+    fn reference_offset(&self, value: &AttributeValue<R>) -> Option<UnitSectionOffset<R::Offset>> {
+        match *value {
+            AttributeValue::UnitRef(offset) => Some(offset.to_unit_section_offset(self.unit)),
+            AttributeValue::DebugInfoRef(offset) => Some(offset.into()),
+            _ => None,
+        }
+    }
+
+    /// Convert a reference attribute value to an offset within this unit,
+    /// if it refers to an entry in this unit.
+    pub(crate) fn local_reference(
+        &self,
+        value: &AttributeValue<R>,
+    ) -> Option<UnitOffset<R::Offset>> {
+        match *value {
+            AttributeValue::UnitRef(offset) => Some(offset),
+            AttributeValue::DebugInfoRef(offset) => offset.to_unit_offset(&self.header),
+            _ => None,
+        }
+    }
+}
+
+fn flag_value<R: Reader>(attr: &Attribute<R>) -> bool {
+    match attr.value() {
+        AttributeValue::Flag(flag) => flag,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::DebugInfoOffset;
+    use crate::constants::*;
+    use crate::endianity::LittleEndian;
+    use crate::read::abbrev::tests::AbbrevSectionMethods;
+    use crate::read::{EndianSlice, Unit};
+    use crate::test_util::{compile_unit, test_dwarf, GimliSectionMethods};
+    use test_assembler::{Endian, Label, LabelMaker, Section};
+
+    #[test]
+    fn test_call_sites() {
+        #[rustfmt::skip]
+        let abbrev = Section::with_endian(Endian::Little)
+            .abbrev(1, DW_TAG_compile_unit, DW_CHILDREN_yes)
+                .abbrev_attr_null()
+            .abbrev(2, DW_TAG_subprogram, DW_CHILDREN_yes)
+                .abbrev_attr(DW_AT_name, DW_FORM_string)
+                .abbrev_attr(DW_AT_low_pc, DW_FORM_addr)
+                .abbrev_attr_null()
+            .abbrev(3, DW_TAG_lexical_block, DW_CHILDREN_yes)
+                .abbrev_attr_null()
+            .abbrev(4, DW_TAG_GNU_call_site, DW_CHILDREN_yes)
+                .abbrev_attr(DW_AT_low_pc, DW_FORM_addr)
+                .abbrev_attr(DW_AT_abstract_origin, DW_FORM_ref4)
+                .abbrev_attr_null()
+            .abbrev(5, DW_TAG_GNU_call_site_parameter, DW_CHILDREN_no)
+                .abbrev_attr(DW_AT_location, DW_FORM_exprloc)
+                .abbrev_attr(DW_AT_GNU_call_site_value, DW_FORM_exprloc)
+                .abbrev_attr_null()
+            .abbrev(6, DW_TAG_call_site, DW_CHILDREN_no)
+                .abbrev_attr(DW_AT_call_return_pc, DW_FORM_addr)
+                .abbrev_attr(DW_AT_call_tail_call, DW_FORM_flag_present)
+                .abbrev_attr(DW_AT_call_target, DW_FORM_exprloc)
+                .abbrev_attr_null()
+            .abbrev_null();
+        let abbrev = abbrev.get_contents().unwrap();
+
+        let callee = Label::new();
+        let caller = Label::new();
+        let call_site = Label::new();
+        let tail_call = Label::new();
+        let nested = Label::new();
+        let nested_call = Label::new();
+        #[rustfmt::skip]
+        let entries = Section::with_endian(Endian::Little)
+            .uleb(1)
+                .mark(&callee)
+                .uleb(2).append_bytes(b"callee\0").L64(0x1000)
+                    .D8(0)
+                .mark(&caller)
+                .uleb(2).append_bytes(b"caller\0").L64(0x2000)
+                    .uleb(3)
+                        .mark(&call_site)
+                        .uleb(4).L64(0x2010).L32(&callee)
+                            .uleb(5)
+                                .uleb(1).D8(DW_OP_reg5.0)
+                                .uleb(1).D8(DW_OP_lit1.0)
+                            .D8(0)
+                        .D8(0)
+                    .mark(&tail_call)
+                    .uleb(6).L64(0x2020).uleb(1).D8(DW_OP_reg0.0)
+                    .mark(&nested)
+                    .uleb(2).append_bytes(b"nested\0").L64(0x3000)
+                        .mark(&nested_call)
+                        .uleb(6).L64(0x3010).uleb(0)
+                        .D8(0)
+                    .D8(0)
+                .D8(0);
+        let info = compile_unit(entries);
+        let offset = |label: &Label| UnitOffset(label.value().unwrap() as usize);
+
+        let dwarf = test_dwarf(&abbrev, &info);
+        let header = dwarf.units().next().unwrap().unwrap();
+        let unit = Unit::new(&dwarf, header).unwrap();
+        let unit = unit.unit_ref(&dwarf);
+
+        let call_sites = unit.call_sites(offset(&caller)).unwrap();
+        assert_eq!(call_sites.len(), 2);
+
+        let site = &call_sites[0];
+        assert_eq!(site.offset, offset(&call_site));
+        assert_eq!(site.return_pc, Some(0x2010));
+        assert!(!site.tail_call);
+        assert_eq!(
+            site.origin,
+            Some(UnitSectionOffset::DebugInfoOffset(DebugInfoOffset(
+                offset(&callee).0
+            )))
+        );
+        assert_eq!(
+            site.origin_name,
+            Some(EndianSlice::new(b"callee", LittleEndian))
+        );
+        assert_eq!(site.origin_pc, Some(0x1000));
+        assert_eq!(site.target, None);
+        assert_eq!(site.parameters.len(), 1);
+        let parameter = &site.parameters[0];
+        assert_eq!(
+            parameter.location,
+            Some(Expression(EndianSlice::new(&[DW_OP_reg5.0], LittleEndian)))
+        );
+        assert_eq!(
+            parameter.value,
+            Some(Expression(EndianSlice::new(&[DW_OP_lit1.0], LittleEndian)))
+        );
+
+        let site = &call_sites[1];
+        assert_eq!(site.offset, offset(&tail_call));
+        assert_eq!(site.return_pc, Some(0x2020));
+        assert!(site.tail_call);
+        assert_eq!(site.origin, None);
+        assert_eq!(
+            site.target,
+            Some(Expression(EndianSlice::new(&[DW_OP_reg0.0], LittleEndian)))
+        );
+        assert!(site.parameters.is_empty());
+
+        let call_sites = unit.call_sites(offset(&callee)).unwrap();
+        assert!(call_sites.is_empty());
+
+        let nested = unit.call_sites(offset(&nested)).unwrap();
+        assert_eq!(nested.len(), 1);
+        assert_eq!(nested[0].offset, offset(&nested_call));
+    }
+}
@@ -14,8 +14,11 @@ use crate::common::{
 use crate::constants::{self, DwEhPe};
 use crate::endianity::Endianity;
 use crate::read::{
-    EndianSlice, Error, Expression, Reader, ReaderOffset, Result, Section, StoreOnHeap,
+    EndianSlice, Error, Expression, Reader, ReaderOffset, Result, ResultIterator, Section,
+    StoreOnHeap,
 };
+#[cfg(feature = "read")]
+use crate::read::{EvaluationResult, Location, Piece, Value};
 
 /// `DebugFrame` contains the `.debug_frame` section's frame unwinding
 /// information required to unwind to and recover registers from older frames on
@@ -37,6 +40,7 @@ pub struct DebugFrame<R: Reader> {
     address_size: u8,
     segment_size: u8,
     vendor: Vendor,
+    augmentation_data_size: Option<AugmentationDataSize>,
 }
 
 impl<R: Reader> DebugFrame<R> {
@@ -62,6 +66,14 @@ impl<R: Reader> DebugFrame<R> {
     pub fn set_vendor(&mut self, vendor: Vendor) {
         self.vendor = vendor;
     }
+
+    /// Set the function to use for vendor augmentation characters in CIEs.
+    ///
+    /// By default, CIEs with unknown augmentation characters fail to parse with
+    /// `Error::UnknownAugmentation`. See `AugmentationDataSize` for more information.
+    pub fn set_augmentation_data_size(&mut self, augmentation_data_size: fn(u8) -> Option<usize>) {
+        self.augmentation_data_size = Some(AugmentationDataSize(augmentation_data_size));
+    }
 }
 
 impl<'input, Endian> DebugFrame<EndianSlice<'input, Endian>>
@@ -106,6 +118,7 @@ impl<R: Reader> From<R> for DebugFrame<R> {
             address_size: mem::size_of::<usize>() as u8,
             segment_size: 0,
             vendor: Vendor::Default,
+            augmentation_data_size: None,
         }
     }
 }
@@ -300,6 +313,24 @@ impl<'a, 'bases, R: Reader> fallible_iterator::FallibleIterator for EhHdrTableIt
     }
 }
 
+impl<'a, 'bases, R: Reader> EhHdrTableIter<'a, 'bases, R> {
+    /// Return an adapter that implements `Iterator`.
+    ///
+    /// See [`ResultIterator`] for more information.
+    pub fn iterator(self) -> ResultIterator<Self> {
+        ResultIterator::new(self)
+    }
+}
+
+impl<'a, 'bases, R: Reader> Iterator for ResultIterator<EhHdrTableIter<'a, 'bases, R>> {
+    type Item = Result<(Pointer, Pointer)>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_with(EhHdrTableIter::next)
+    }
+}
+
 /// The CFI binary search table that is an optional part of the `.eh_frame_hdr` section.
 #[derive(Debug, Clone)]
 pub struct EhHdrTable<'a, R: Reader> {
@@ -494,6 +525,7 @@ pub struct EhFrame<R: Reader> {
     section: R,
     address_size: u8,
     vendor: Vendor,
+    augmentation_data_size: Option<AugmentationDataSize>,
 }
 
 impl<R: Reader> EhFrame<R> {
@@ -510,6 +542,14 @@ impl<R: Reader> EhFrame<R> {
     pub fn set_vendor(&mut self, vendor: Vendor) {
         self.vendor = vendor;
     }
+
+    /// Set the function to use for vendor augmentation characters in CIEs.
+    ///
+    /// By default, CIEs with unknown augmentation characters fail to parse with
+    /// `Error::UnknownAugmentation`. See `AugmentationDataSize` for more information.
+    pub fn set_augmentation_data_size(&mut self, augmentation_data_size: fn(u8) -> Option<usize>) {
+        self.augmentation_data_size = Some(AugmentationDataSize(augmentation_data_size));
+    }
 }
 
 impl<'input, Endian> EhFrame<EndianSlice<'input, Endian>>
@@ -553,6 +593,7 @@ impl<R: Reader> From<R> for EhFrame<R> {
             section,
             address_size: mem::size_of::<usize>() as u8,
             vendor: Vendor::Default,
+            augmentation_data_size: None,
         }
     }
 }
@@ -636,6 +677,9 @@ pub trait _UnwindSectionPrivate<R: Reader> {
 
     /// The vendor extensions to use.
     fn vendor(&self) -> Vendor;
+
+    /// The function to use for vendor augmentation characters.
+    fn augmentation_data_size(&self) -> Option<AugmentationDataSize>;
 }
 
 /// A section holding unwind information: either `.debug_frame` or
@@ -835,6 +879,10 @@ impl<R: Reader> _UnwindSectionPrivate<R> for DebugFrame<R> {
     fn vendor(&self) -> Vendor {
         self.vendor
     }
+
+    fn augmentation_data_size(&self) -> Option<AugmentationDataSize> {
+        self.augmentation_data_size
+    }
 }
 
 impl<R: Reader> UnwindSection<R> for DebugFrame<R> {
@@ -879,6 +927,10 @@ impl<R: Reader> _UnwindSectionPrivate<R> for EhFrame<R> {
     fn vendor(&self) -> Vendor {
         self.vendor
     }
+
+    fn augmentation_data_size(&self) -> Option<AugmentationDataSize> {
+        self.augmentation_data_size
+    }
 }
 
 impl<R: Reader> UnwindSection<R> for EhFrame<R> {
@@ -971,6 +1023,181 @@ impl BaseAddresses {
     }
 }
 
+/// The order in which `CombinedUnwindInfo` searches its sections.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnwindSectionPriority {
+    /// Search the `.eh_frame` section before the `.debug_frame` section.
+    EhFrameFirst,
+    /// Search the `.debug_frame` section before the `.eh_frame` section.
+    DebugFrameFirst,
+}
+
+impl Default for UnwindSectionPriority {
+    fn default() -> Self {
+        UnwindSectionPriority::EhFrameFirst
+    }
+}
+
+/// Unwind information from both the `.eh_frame` and `.debug_frame` sections.
+///
+/// Binaries often contain partial unwind information in each section. For example,
+/// `.eh_frame` may only describe functions that can unwind for exceptions, while
+/// `.debug_frame` is only present for objects compiled with debugging information.
+/// This searches both sections in the order given by `priority`, and uses the
+/// first FDE that contains the address.
+///
+/// Either section may be empty.
+///
+/// ```
+/// use gimli::{BaseAddresses, CombinedUnwindInfo, DebugFrame, EhFrame, NativeEndian,
+///             UnwindContext, UnwindSectionPriority};
+///
+/// # fn foo() -> gimli::Result<()> {
+/// # let read_eh_frame_section = || unimplemented!();
+/// # let read_debug_frame_section = || unimplemented!();
+/// # let address_of_eh_frame_section_in_memory = unimplemented!();
+/// # let address_of_text_section_in_memory = unimplemented!();
+/// let eh_frame_bases = BaseAddresses::default()
+///     .set_eh_frame(address_of_eh_frame_section_in_memory)
+///     .set_text(address_of_text_section_in_memory);
+/// let mut unwind_info = CombinedUnwindInfo::new(
+///     EhFrame::new(read_eh_frame_section(), NativeEndian),
+///     eh_frame_bases,
+///     DebugFrame::new(read_debug_frame_section(), NativeEndian),
+///     BaseAddresses::default(),
+/// );
+/// unwind_info.priority = UnwindSectionPriority::DebugFrameFirst;
+///
+/// # let address = unimplemented!();
+/// let mut ctx = UnwindContext::new();
+/// let row = unwind_info.unwind_info_for_address(&mut ctx, address)?;
+/// # let do_stuff_with = |_| unimplemented!();
+/// do_stuff_with(row);
+/// # unreachable!()
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct CombinedUnwindInfo<R: Reader> {
+    /// The `.eh_frame` section.
+    pub eh_frame: EhFrame<R>,
+
+    /// The base addresses to use for pointers in the `.eh_frame` and
+    /// `.eh_frame_hdr` sections.
+    pub eh_frame_bases: BaseAddresses,
+
+    /// The parsed `.eh_frame_hdr` section, if any.
+    ///
+    /// If this has a binary search table, then it is used to find FDEs in
+    /// `.eh_frame` instead of iterating over all of them.
+    pub eh_frame_hdr: Option<ParsedEhFrameHdr<R>>,
+
+    /// The `.debug_frame` section.
+    pub debug_frame: DebugFrame<R>,
+
+    /// The base addresses to use for pointers in the `.debug_frame` section.
+    pub debug_frame_bases: BaseAddresses,
+
+    /// The order in which the sections are searched.
+    pub priority: UnwindSectionPriority,
+}
+
+impl<R: Reader> CombinedUnwindInfo<R> {
+    /// Construct a new `CombinedUnwindInfo` from the given sections.
+    ///
+    /// `.eh_frame` is searched first by default.
+    pub fn new(
+        eh_frame: EhFrame<R>,
+        eh_frame_bases: BaseAddresses,
+        debug_frame: DebugFrame<R>,
+        debug_frame_bases: BaseAddresses,
+    ) -> Self {
+        CombinedUnwindInfo {
+            eh_frame,
+            eh_frame_bases,
+            eh_frame_hdr: None,
+            debug_frame,
+            debug_frame_bases,
+            priority: UnwindSectionPriority::default(),
+        }
+    }
+
+    /// Find the `FrameDescriptionEntry` for the given address.
+    ///
+    /// Returns the FDE and the section containing it, which is either
+    /// `SectionId::EhFrame` or `SectionId::DebugFrame`.
+    ///
+    /// If neither section contains the address, then
+    /// `Err(gimli::Error::NoUnwindInfoForAddress)` is returned.
+    /// If parsing one section fails, then the other section is still searched,
+    /// and the parsing error is only returned if the address is not found there.
+    pub fn fde_for_address(&self, address: u64) -> Result<(SectionId, FrameDescriptionEntry<R>)> {
+        let order = match self.priority {
+            UnwindSectionPriority::EhFrameFirst => [SectionId::EhFrame, SectionId::DebugFrame],
+            UnwindSectionPriority::DebugFrameFirst => [SectionId::DebugFrame, SectionId::EhFrame],
+        };
+        let mut error = Error::NoUnwindInfoForAddress;
+        for id in order {
+            let fde = if id == SectionId::EhFrame {
+                self.eh_frame_fde_for_address(address)
+            } else {
+                self.debug_frame.fde_for_address(
+                    &self.debug_frame_bases,
+                    address,
+                    DebugFrame::cie_from_offset,
+                )
+            };
+            match fde {
+                Ok(fde) => return Ok((id, fde)),
+                Err(Error::NoUnwindInfoForAddress) => {}
+                Err(e) => {
+                    if error == Error::NoUnwindInfoForAddress {
+                        error = e;
+                    }
+                }
+            }
+        }
+        Err(error)
+    }
+
+    fn eh_frame_fde_for_address(&self, address: u64) -> Result<FrameDescriptionEntry<R>> {
+        match self.eh_frame_hdr.as_ref().and_then(|hdr| hdr.table()) {
+            Some(table) => table.fde_for_address(
+                &self.eh_frame,
+                &self.eh_frame_bases,
+                address,
+                EhFrame::cie_from_offset,
+            ),
+            None => self.eh_frame.fde_for_address(
+                &self.eh_frame_bases,
+                address,
+                EhFrame::cie_from_offset,
+            ),
+        }
+    }
+
+    /// Find the frame unwind information for the given address.
+    ///
+    /// The FDE is found using `fde_for_address`, and then evaluated using the
+    /// section that contains it.
+    pub fn unwind_info_for_address<'ctx, A: UnwindContextStorage<R::Offset>>(
+        &self,
+        ctx: &'ctx mut UnwindContext<R::Offset, A>,
+        address: u64,
+    ) -> Result<&'ctx UnwindTableRow<R::Offset, A>> {
+        match self.fde_for_address(address)? {
+            (SectionId::EhFrame, fde) => {
+                fde.unwind_info_for_address(&self.eh_frame, &self.eh_frame_bases, ctx, address)
+            }
+            (_, fde) => fde.unwind_info_for_address(
+                &self.debug_frame,
+                &self.debug_frame_bases,
+                ctx,
+                address,
+            ),
+        }
+    }
+}
+
 /// An iterator over CIE and FDE entries in a `.debug_frame` or `.eh_frame`
 /// section.
 ///
@@ -1060,6 +1287,32 @@ where
     }
 }
 
+impl<'bases, Section, R> CfiEntriesIter<'bases, Section, R>
+where
+    R: Reader,
+    Section: UnwindSection<R>,
+{
+    /// Return an adapter that implements `Iterator`.
+    ///
+    /// See [`ResultIterator`] for more information.
+    pub fn iterator(self) -> ResultIterator<Self> {
+        ResultIterator::new(self)
+    }
+}
+
+impl<'bases, Section, R> Iterator for ResultIterator<CfiEntriesIter<'bases, Section, R>>
+where
+    R: Reader,
+    Section: UnwindSection<R>,
+{
+    type Item = Result<CieOrFde<'bases, Section, R>>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_with(CfiEntriesIter::next)
+    }
+}
+
 /// Either a `CommonInformationEntry` (CIE) or a `FrameDescriptionEntry` (FDE).
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum CieOrFde<'bases, Section, R>
@@ -1133,10 +1386,57 @@ where
     }
 }
 
+/// A function that returns the size of the CIE augmentation data for a vendor
+/// augmentation character, or `None` if the character is not known.
+///
+/// This is set using `EhFrame::set_augmentation_data_size` or
+/// `DebugFrame::set_augmentation_data_size`. The function is called for each
+/// character in a CIE augmentation string that is not defined by `.eh_frame`.
+/// The data is skipped, so that the remaining augmentation characters and the
+/// CIE's initial instructions can still be parsed. Use
+/// `CommonInformationEntry::raw_augmentation` to interpret the data.
+///
+/// Only the `L` character adds data to the FDE augmentation data, so vendor
+/// characters must not add data that precedes it.
+///
+/// ```
+/// use gimli::{EhFrame, NativeEndian};
+///
+/// # let buf = [];
+/// let mut eh_frame = EhFrame::new(&buf, NativeEndian);
+/// // The vendor 'V' character has 4 bytes of augmentation data.
+/// eh_frame.set_augmentation_data_size(|ch| if ch == b'V' { Some(4) } else { None });
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct AugmentationDataSize(pub fn(u8) -> Option<usize>);
+
+impl PartialEq for AugmentationDataSize {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 as usize == other.0 as usize
+    }
+}
+
+impl Eq for AugmentationDataSize {}
+
+/// The raw augmentation string and data of a CIE.
+///
+/// See `CommonInformationEntry::raw_augmentation`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RawAugmentation<R: Reader> {
+    /// The augmentation string, without the null terminator.
+    pub string: R,
+
+    /// The augmentation data, if the augmentation string begins with `z`.
+    ///
+    /// This does not include the length of the data.
+    pub data: Option<R>,
+}
+
 /// We support the z-style augmentation [defined by `.eh_frame`][ehframe].
 ///
 /// [ehframe]: https://refspecs.linuxfoundation.org/LSB_3.0.0/LSB-Core-generic/LSB-Core-generic/ehframechpt.html
 #[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Augmentation {
     /// > A 'L' may be present at any position after the first character of the
     /// > string. This character may only be present if 'z' is the first character
@@ -1226,7 +1526,19 @@ impl Augmentation {
                     augmentation.fde_address_encoding = Some(encoding);
                 }
                 b'S' => augmentation.is_signal_trampoline = true,
-                _ => return Err(Error::UnknownAugmentation),
+                _ => {
+                    let size = section
+                        .augmentation_data_size()
+                        .and_then(|augmentation_data_size| (augmentation_data_size.0)(ch))
+                        .ok_or(Error::UnknownAugmentation)?;
+                    let size = R::Offset::from_u64(size as u64)?;
+                    match data.as_mut() {
+                        Some(rest) => rest.skip(size)?,
+                        // Without 'z', the data is not delimited, so it must
+                        // be read from the input.
+                        None => input.skip(size)?,
+                    }
+                }
             }
 
             parsed_first = true;
@@ -1238,6 +1550,7 @@ impl Augmentation {
 
 /// Parsed augmentation data for a `FrameDescriptEntry`.
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 struct AugmentationData {
     lsda: Option<Pointer>,
 }
@@ -1269,6 +1582,7 @@ impl AugmentationData {
 /// > Frame Description Entries. There is at least one CIE in every non-empty
 /// > `.debug_frame` section.
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CommonInformationEntry<R, Offset = <R as Reader>::Offset>
 where
     R: Reader<Offset = Offset>,
@@ -1478,6 +1792,43 @@ impl<R: Reader> CommonInformationEntry<R> {
         self.augmentation.as_ref()
     }
 
+    /// Return the raw augmentation string and augmentation data of this CIE.
+    ///
+    /// This is useful for interpreting vendor augmentations. `section` must be
+    /// the section that this CIE was parsed from.
+    pub fn raw_augmentation<Section: UnwindSection<R>>(
+        &self,
+        section: &Section,
+    ) -> Result<RawAugmentation<R>> {
+        let mut input = section.section().clone();
+        input.skip(self.offset)?;
+        let (length, format) = input.read_initial_length()?;
+        let mut rest = input.split(length)?;
+        match Section::cie_offset_encoding(format) {
+            CieOffsetEncoding::U32 => rest.skip(R::Offset::from_u8(4))?,
+            CieOffsetEncoding::U64 => rest.skip(R::Offset::from_u8(8))?,
+        }
+        let version = rest.read_u8()?;
+        let string = rest.read_null_terminated_slice()?;
+        if Section::has_address_and_segment_sizes(version) {
+            rest.skip(R::Offset::from_u8(2))?;
+        }
+        rest.read_uleb128()?;
+        rest.read_sleb128()?;
+        if version == 1 {
+            rest.read_u8()?;
+        } else {
+            rest.read_uleb128()?;
+        }
+        let data = if string.clone().read_u8() == Ok(b'z') {
+            let length = rest.read_uleb128().and_then(R::Offset::from_u64)?;
+            Some(rest.split(length)?)
+        } else {
+            None
+        };
+        Ok(RawAugmentation { string, data })
+    }
+
     /// True if this CIE's FDEs have a LSDA.
     pub fn has_lsda(&self) -> bool {
         self.augmentation.map_or(false, |a| a.lsda.is_some())
@@ -1610,6 +1961,7 @@ where
 
 /// A `FrameDescriptionEntry` is a set of CFA instructions for an address range.
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FrameDescriptionEntry<R, Offset = <R as Reader>::Offset>
 where
     R: Reader<Offset = Offset>,
@@ -2819,6 +3171,7 @@ impl<T: ReaderOffset, S: UnwindContextStorage<T>> UnwindTableRow<T, S> {
 
 /// The canonical frame address (CFA) recovery rules.
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum CfaRule<T: ReaderOffset> {
     /// The CFA is given offset from the given register's value.
     RegisterAndOffset {
@@ -2859,6 +3212,7 @@ impl<T: ReaderOffset> CfaRule<T> {
 /// has been saved and the rule to find the value for the register in the
 /// previous frame."
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[non_exhaustive]
 pub enum RegisterRule<T: ReaderOffset> {
     /// > A register that has this rule has no recoverable value in the previous
@@ -2903,8 +3257,205 @@ impl<T: ReaderOffset> RegisterRule<T> {
     }
 }
 
+/// Access to the registers and memory of a frame, for use when evaluating
+/// CFA and register rules.
+///
+/// See [`CfaRule::evaluate`] and [`RegisterRule::evaluate`].
+#[cfg(feature = "read")]
+pub trait UnwindFrameAccess {
+    /// Return the value of the given register in the current frame.
+    fn register(&mut self, register: Register) -> Result<u64>;
+
+    /// Read `size` bytes of memory at the given address.
+    ///
+    /// `size` is at most the address size of the target.
+    fn read_memory(&mut self, address: u64, size: u8) -> Result<u64>;
+}
+
+/// The result of evaluating a [`RegisterRule`].
+#[cfg(feature = "read")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RegisterRuleValue {
+    /// The register has no recoverable value in the previous frame.
+    Undefined,
+    /// The previous value of the register is saved in memory at this address.
+    Address(u64),
+    /// The previous value of the register.
+    Value(u64),
+}
+
+#[cfg(feature = "read")]
+impl<T: ReaderOffset> CfaRule<T> {
+    /// Evaluate the rule to find the CFA.
+    ///
+    /// `address_size` is the address size of the CIE for the row,
+    /// which is given by [`CommonInformationEntry::address_size`].
+    pub fn evaluate<R, S, A>(&self, section: &S, address_size: u8, access: &mut A) -> Result<u64>
+    where
+        R: Reader<Offset = T>,
+        S: UnwindSection<R>,
+        A: UnwindFrameAccess + ?Sized,
+    {
+        match *self {
+            CfaRule::RegisterAndOffset { register, offset } => {
+                let value = access.register(register)?;
+                Ok(value.wrapping_add(offset as u64) & address_mask(address_size))
+            }
+            CfaRule::Expression(ref expression) => {
+                let expression = expression.get(section)?;
+                match evaluate_unwind_expression(expression, address_size, None, access)? {
+                    RegisterRuleValue::Address(address) => Ok(address),
+                    _ => Err(Error::UnsupportedEvaluation),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(feature = "read")]
+impl<T: ReaderOffset> RegisterRule<T> {
+    /// Evaluate the rule to find the value of `register` in the previous frame.
+    ///
+    /// `cfa` is the CFA of the row, as returned by [`CfaRule::evaluate`].
+    /// `address_size` is the address size of the CIE for the row,
+    /// which is given by [`CommonInformationEntry::address_size`].
+    ///
+    /// Returns `Error::UnsupportedEvaluation` for `RegisterRule::Architectural`.
+    pub fn evaluate<R, S, A>(
+        &self,
+        section: &S,
+        address_size: u8,
+        register: Register,
+        cfa: u64,
+        access: &mut A,
+    ) -> Result<RegisterRuleValue>
+    where
+        R: Reader<Offset = T>,
+        S: UnwindSection<R>,
+        A: UnwindFrameAccess + ?Sized,
+    {
+        let mask = address_mask(address_size);
+        let value = match *self {
+            RegisterRule::Undefined => RegisterRuleValue::Undefined,
+            RegisterRule::SameValue => RegisterRuleValue::Value(access.register(register)?),
+            RegisterRule::Register(register) => {
+                RegisterRuleValue::Value(access.register(register)?)
+            }
+            RegisterRule::Offset(offset) => {
+                RegisterRuleValue::Address(cfa.wrapping_add(offset as u64) & mask)
+            }
+            RegisterRule::ValOffset(offset) => {
+                RegisterRuleValue::Value(cfa.wrapping_add(offset as u64) & mask)
+            }
+            RegisterRule::Expression(ref expression) => {
+                let expression = expression.get(section)?;
+                match evaluate_unwind_expression(expression, address_size, Some(cfa), access)? {
+                    RegisterRuleValue::Address(address) => RegisterRuleValue::Address(address),
+                    _ => return Err(Error::UnsupportedEvaluation),
+                }
+            }
+            RegisterRule::ValExpression(ref expression) => {
+                let expression = expression.get(section)?;
+                match evaluate_unwind_expression(expression, address_size, Some(cfa), access)? {
+                    RegisterRuleValue::Address(value) | RegisterRuleValue::Value(value) => {
+                        RegisterRuleValue::Value(value)
+                    }
+                    RegisterRuleValue::Undefined => return Err(Error::UnsupportedEvaluation),
+                }
+            }
+            RegisterRule::Constant(value) => RegisterRuleValue::Value(value),
+            RegisterRule::Architectural => return Err(Error::UnsupportedEvaluation),
+        };
+        Ok(value)
+    }
+}
+
+#[cfg(feature = "read")]
+fn address_mask(address_size: u8) -> u64 {
+    if address_size >= 8 {
+        !0
+    } else {
+        (1 << (address_size * 8)) - 1
+    }
+}
+
+/// Evaluate a CFI expression.
+///
+/// If `cfa` is given, it is pushed on the stack before evaluation and is also
+/// used for `DW_OP_call_frame_cfa`.
+///
+/// A location result is returned as `RegisterRuleValue::Address`, and a value
+/// result (from `DW_OP_stack_value`) is returned as `RegisterRuleValue::Value`.
+#[cfg(feature = "read")]
+fn evaluate_unwind_expression<R, A>(
+    expression: Expression<R>,
+    address_size: u8,
+    cfa: Option<u64>,
+    access: &mut A,
+) -> Result<RegisterRuleValue>
+where
+    R: Reader,
+    A: UnwindFrameAccess + ?Sized,
+{
+    let encoding = Encoding {
+        format: Format::Dwarf32,
+        version: 4,
+        address_size,
+    };
+    let mut evaluation = expression.evaluation(encoding);
+    if let Some(cfa) = cfa {
+        evaluation.set_initial_value(cfa);
+    }
+    let mut result = evaluation.evaluate()?;
+    loop {
+        result = match result {
+            EvaluationResult::Complete => break,
+            EvaluationResult::RequiresMemory {
+                address,
+                size,
+                space: None,
+                base_type,
+            } if base_type.0 == R::Offset::from_u8(0) => {
+                let value = access.read_memory(address, size)?;
+                evaluation.resume_with_memory(Value::Generic(value))?
+            }
+            EvaluationResult::RequiresRegister {
+                register,
+                base_type,
+            } if base_type.0 == R::Offset::from_u8(0) => {
+                let value = access.register(register)?;
+                evaluation.resume_with_register(Value::Generic(value))?
+            }
+            EvaluationResult::RequiresCallFrameCfa => match cfa {
+                Some(cfa) => evaluation.resume_with_call_frame_cfa(cfa)?,
+                None => return Err(Error::UnsupportedEvaluation),
+            },
+            EvaluationResult::RequiresRelocatedAddress(address) => {
+                evaluation.resume_with_relocated_address(address)?
+            }
+            _ => return Err(Error::UnsupportedEvaluation),
+        };
+    }
+    match evaluation.as_result() {
+        [Piece {
+            size_in_bits: None,
+            bit_offset: None,
+            location: Location::Address { address },
+        }] => Ok(RegisterRuleValue::Address(*address)),
+        [Piece {
+            size_in_bits: None,
+            bit_offset: None,
+            location: Location::Value { value },
+        }] => Ok(RegisterRuleValue::Value(
+            value.to_u64(address_mask(address_size))?,
+        )),
+        _ => Err(Error::UnsupportedEvaluation),
+    }
+}
+
 /// A parsed call frame instruction.
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[non_exhaustive]
 pub enum CallFrameInstruction<T: ReaderOffset> {
     // 6.4.2.1 Row Creation Methods
@@ -3443,6 +3994,43 @@ impl<'a, R: Reader> CallFrameInstructionIter<'a, R> {
             }
         }
     }
+
+    /// Parse the next call frame instruction, and also return its offset
+    /// within the section and its length in bytes.
+    ///
+    /// This is useful for tools that need to display or rewrite the
+    /// encoded instructions.
+    pub fn next_with_offset(
+        &mut self,
+    ) -> Result<Option<CallFrameInstructionWithOffset<R::Offset>>> {
+        if self.input.is_empty() {
+            return Ok(None);
+        }
+        let offset = self.input.offset_from(self.parameters.section);
+        let start_len = self.input.len();
+        let instruction = match self.next()? {
+            Some(instruction) => instruction,
+            None => return Ok(None),
+        };
+        Ok(Some(CallFrameInstructionWithOffset {
+            offset,
+            length: start_len - self.input.len(),
+            instruction,
+        }))
+    }
+}
+
+/// A call frame instruction and its location within the section.
+///
+/// This is returned by [`CallFrameInstructionIter::next_with_offset`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CallFrameInstructionWithOffset<T: ReaderOffset> {
+    /// The offset of the instruction within the section.
+    pub offset: T,
+    /// The length in bytes of the encoded instruction.
+    pub length: T,
+    /// The parsed instruction.
+    pub instruction: CallFrameInstruction<T>,
 }
 
 #[cfg(feature = "fallible-iterator")]
@@ -3455,11 +4043,30 @@ impl<'a, R: Reader> fallible_iterator::FallibleIterator for CallFrameInstruction
     }
 }
 
+impl<'a, R: Reader> CallFrameInstructionIter<'a, R> {
+    /// Return an adapter that implements `Iterator`.
+    ///
+    /// See [`ResultIterator`] for more information.
+    pub fn iterator(self) -> ResultIterator<Self> {
+        ResultIterator::new(self)
+    }
+}
+
+impl<'a, R: Reader> Iterator for ResultIterator<CallFrameInstructionIter<'a, R>> {
+    type Item = Result<CallFrameInstruction<R::Offset>>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_with(CallFrameInstructionIter::next)
+    }
+}
+
 /// The location of a DWARF expression within an unwind section.
 ///
 /// This is stored as an offset and length within the section instead of as a
 /// `Reader` to avoid lifetime issues when reusing [`UnwindContext`].
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct UnwindExpression<T: ReaderOffset> {
     /// The offset of the expression within the section.
     pub offset: T,
@@ -3499,6 +4106,7 @@ fn parse_pointer_encoding<R: Reader>(input: &mut R) -> Result<constants::DwEhPe>
 
 /// A decoded pointer.
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Pointer {
     /// This value is the decoded pointer value.
     Direct(u64),
@@ -5267,6 +5875,56 @@ mod tests {
         assert_eq!(iter.next(), Ok(None));
     }
 
+    #[test]
+    fn test_call_frame_instruction_iter_offsets() {
+        let section = Section::with_endian(Endian::Little)
+            .D8(0)
+            .D8(constants::DW_CFA_def_cfa.0)
+            .uleb(7)
+            .uleb(0x100)
+            .D8(constants::DW_CFA_nop.0)
+            .D8(constants::DW_CFA_advance_loc1.0);
+
+        let contents = section.get_contents().unwrap();
+        let section = EndianSlice::new(&contents, LittleEndian);
+        let mut input = section;
+        input.skip(1).unwrap();
+        let parameters = PointerEncodingParameters {
+            bases: &SectionBaseAddresses::default(),
+            func_base: None,
+            address_size: 8,
+            section: &section,
+        };
+        let mut iter = CallFrameInstructionIter {
+            input,
+            address_encoding: None,
+            parameters,
+            vendor: Vendor::Default,
+        };
+
+        assert_eq!(
+            iter.next_with_offset(),
+            Ok(Some(CallFrameInstructionWithOffset {
+                offset: 1,
+                length: 4,
+                instruction: CallFrameInstruction::DefCfa {
+                    register: Register(7),
+                    offset: 0x100,
+                },
+            }))
+        );
+        assert_eq!(
+            iter.next_with_offset(),
+            Ok(Some(CallFrameInstructionWithOffset {
+                offset: 5,
+                length: 1,
+                instruction: CallFrameInstruction::Nop,
+            }))
+        );
+        assert!(iter.next_with_offset().is_err());
+        assert_eq!(iter.next_with_offset(), Ok(None));
+    }
+
     #[test]
     fn test_call_frame_instruction_iter_err() {
         // DW_CFA_advance_loc1 without an operand.
@@ -6362,6 +7020,144 @@ mod tests {
         assert_eq!(result.unwrap_err(), Error::NoUnwindInfoForAddress);
     }
 
+    #[test]
+    fn test_combined_unwind_info() {
+        fn fde<'a>(
+            cie: &CommonInformationEntry<EndianSlice<'a, LittleEndian>>,
+            initial_address: u64,
+        ) -> FrameDescriptionEntry<EndianSlice<'a, LittleEndian>> {
+            FrameDescriptionEntry {
+                offset: 0,
+                length: 0,
+                format: Format::Dwarf32,
+                cie: cie.clone(),
+                initial_segment: 0,
+                initial_address,
+                address_range: 0x100,
+                augmentation: None,
+                instructions: EndianSlice::new(&[], LittleEndian),
+            }
+        }
+
+        // The CFA is 8 from register 4 in `.eh_frame`, and 16 from register 5
+        // in `.debug_frame`.
+        let eh_instrs = [constants::DW_CFA_def_cfa.0, 4, 8];
+        let debug_instrs = [constants::DW_CFA_def_cfa.0, 5, 16];
+
+        let mut cie = make_test_cie();
+        cie.format = Format::Dwarf32;
+        cie.version = 1;
+        cie.address_size = 8;
+        cie.initial_instructions = EndianSlice::new(&eh_instrs, LittleEndian);
+        let kind = eh_frame_le();
+        // The `cie` helper always writes a `.debug_frame` CIE identifier.
+        let start_of_cie = Label::new();
+        let cie_length = Label::new();
+        let cie_start = Label::new();
+        let cie_end = Label::new();
+        let section = Section::with_endian(kind.endian())
+            .mark(&start_of_cie)
+            .D32(&cie_length)
+            .mark(&cie_start)
+            .D32(0)
+            .D8(cie.version)
+            .D8(0)
+            .uleb(cie.code_alignment_factor)
+            .sleb(cie.data_alignment_factor)
+            .D8(cie.return_address_register.0 as u8)
+            .append_bytes(&eh_instrs)
+            .mark(&cie_end);
+        cie_length.set_const((&cie_end - &cie_start) as u64);
+        let mut fde1 = fde(&cie, 0x1000);
+        let mut fde2 = fde(&cie, 0x2000);
+        let start_of_fde1 = Label::new();
+        let start_of_fde2 = Label::new();
+        let section = section
+            .mark(&start_of_fde1)
+            .fde(kind, (&start_of_fde1 - &start_of_cie + 4) as u64, &mut fde1)
+            .mark(&start_of_fde2)
+            .fde(kind, (&start_of_fde2 - &start_of_cie + 4) as u64, &mut fde2);
+        section.start().set_const(0);
+        let eh_frame = section.get_contents().unwrap();
+        let eh_frame = kind.section(&eh_frame);
+
+        let mut cie = make_test_cie();
+        cie.format = Format::Dwarf32;
+        cie.initial_instructions = EndianSlice::new(&debug_instrs, LittleEndian);
+        let kind = debug_frame_le();
+        let cie_location = Label::new();
+        let section = Section::with_endian(kind.endian())
+            .mark(&cie_location)
+            .cie(kind, None, &mut cie);
+        let mut fde1 = fde(&cie, 0x1000);
+        let mut fde3 = fde(&cie, 0x3000);
+        let section =
+            section
+                .fde(kind, &cie_location, &mut fde1)
+                .fde(kind, &cie_location, &mut fde3);
+        section.start().set_const(0);
+        let debug_frame = section.get_contents().unwrap();
+        let debug_frame = kind.section(&debug_frame);
+
+        let mut unwind_info = CombinedUnwindInfo::new(
+            eh_frame,
+            BaseAddresses::default(),
+            debug_frame,
+            BaseAddresses::default(),
+        );
+        let section = |unwind_info: &CombinedUnwindInfo<_>, address| {
+            unwind_info.fde_for_address(address).map(|(id, _)| id)
+        };
+        assert_eq!(section(&unwind_info, 0x1010), Ok(SectionId::EhFrame));
+        assert_eq!(section(&unwind_info, 0x2010), Ok(SectionId::EhFrame));
+        assert_eq!(section(&unwind_info, 0x3010), Ok(SectionId::DebugFrame));
+        assert_eq!(
+            section(&unwind_info, 0x4010),
+            Err(Error::NoUnwindInfoForAddress)
+        );
+
+        let mut ctx = UnwindContext::new();
+        let row = unwind_info
+            .unwind_info_for_address(&mut ctx, 0x1010)
+            .unwrap();
+        assert_eq!(
+            *row.cfa(),
+            CfaRule::RegisterAndOffset {
+                register: Register(4),
+                offset: 8,
+            }
+        );
+
+        unwind_info.priority = UnwindSectionPriority::DebugFrameFirst;
+        assert_eq!(section(&unwind_info, 0x1010), Ok(SectionId::DebugFrame));
+        assert_eq!(section(&unwind_info, 0x2010), Ok(SectionId::EhFrame));
+        let row = unwind_info
+            .unwind_info_for_address(&mut ctx, 0x1010)
+            .unwrap();
+        assert_eq!(
+            *row.cfa(),
+            CfaRule::RegisterAndOffset {
+                register: Register(5),
+                offset: 16,
+            }
+        );
+        let row = unwind_info
+            .unwind_info_for_address(&mut ctx, 0x2010)
+            .unwrap();
+        assert_eq!(
+            *row.cfa(),
+            CfaRule::RegisterAndOffset {
+                register: Register(4),
+                offset: 8,
+            }
+        );
+
+        // A parse error in one section doesn't prevent using the other.
+        unwind_info.eh_frame = EhFrame::new(&[0xff, 0xff, 0xff], LittleEndian);
+        assert_eq!(section(&unwind_info, 0x3010), Ok(SectionId::DebugFrame));
+        assert!(section(&unwind_info, 0x2010).is_err());
+    }
+
     #[test]
     fn test_eh_frame_hdr_unknown_version() {
         let bases = BaseAddresses::default();
@@ -6891,6 +7687,112 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_augmentation_parse_custom() {
+        fn data_size(ch: u8) -> Option<usize> {
+            if ch == b'V' {
+                Some(2)
+            } else {
+                None
+            }
+        }
+
+        let bases = Default::default();
+        let address_size = 8;
+        let rest = [9, 8, 7, 6, 5, 4, 3, 2, 1];
+
+        let section = Section::with_endian(Endian::Little)
+            .uleb(3)
+            .D8(0xaa)
+            .D8(0xbb)
+            .D8(constants::DW_EH_PE_udata4.0)
+            .append_bytes(&rest)
+            .get_contents()
+            .unwrap();
+        let mut section = EhFrame::new(&section, LittleEndian);
+        let input = &mut section.section().clone();
+        let aug_str = &mut EndianSlice::new(b"zVR", LittleEndian);
+        assert_eq!(
+            Augmentation::parse(aug_str, &bases, address_size, &section, input),
+            Err(Error::UnknownAugmentation)
+        );
+
+        section.set_augmentation_data_size(data_size);
+        let input = &mut section.section().clone();
+        let aug_str = &mut EndianSlice::new(b"zVR", LittleEndian);
+        let augmentation = Augmentation {
+            fde_address_encoding: Some(constants::DW_EH_PE_udata4),
+            ..Default::default()
+        };
+        assert_eq!(
+            Augmentation::parse(aug_str, &bases, address_size, &section, input),
+            Ok(augmentation)
+        );
+        assert_eq!(*input, EndianSlice::new(&rest, LittleEndian));
+
+        // Without 'z', the data is read from the input.
+        let input = &mut EndianSlice::new(&[0xaa, 0xbb, 9, 8], LittleEndian);
+        let aug_str = &mut EndianSlice::new(b"V", LittleEndian);
+        assert_eq!(
+            Augmentation::parse(aug_str, &bases, address_size, &section, input),
+            Ok(Augmentation::default())
+        );
+        assert_eq!(*input, EndianSlice::new(&[9, 8], LittleEndian));
+    }
+
+    #[test]
+    fn test_cie_raw_augmentation() {
+        let instructions = [constants::DW_CFA_nop.0; 4];
+        let length = Label::new();
+        let start = Label::new();
+        let end = Label::new();
+        let section = Section::with_endian(Endian::Little)
+            .L32(&length)
+            .mark(&start)
+            // CIE Id
+            .L32(0)
+            // Version
+            .D8(1)
+            .append_bytes(b"zVR\0")
+            // Code alignment factor, data alignment factor, return address register.
+            .uleb(1)
+            .sleb(-8)
+            .D8(16)
+            // Augmentation data.
+            .uleb(3)
+            .D8(0xaa)
+            .D8(0xbb)
+            .D8(constants::DW_EH_PE_udata4.0)
+            .append_bytes(&instructions)
+            .mark(&end);
+        length.set_const((&end - &start) as u64);
+        let section = section.get_contents().unwrap();
+        let mut eh_frame = EhFrame::new(&section, LittleEndian);
+        let bases = BaseAddresses::default();
+        assert_eq!(
+            eh_frame.cie_from_offset(&bases, EhFrameOffset(0)),
+            Err(Error::UnknownAugmentation)
+        );
+
+        eh_frame.set_augmentation_data_size(|ch| if ch == b'V' { Some(2) } else { None });
+        let cie = eh_frame.cie_from_offset(&bases, EhFrameOffset(0)).unwrap();
+        assert_eq!(cie.fde_address_encoding(), Some(constants::DW_EH_PE_udata4));
+        assert_eq!(
+            cie.initial_instructions,
+            EndianSlice::new(&instructions, LittleEndian)
+        );
+        assert_eq!(
+            cie.raw_augmentation(&eh_frame),
+            Ok(RawAugmentation {
+                string: EndianSlice::new(b"zVR", LittleEndian),
+                data: Some(EndianSlice::new(
+                    &[0xaa, 0xbb, constants::DW_EH_PE_udata4.0],
+                    LittleEndian
+                )),
+            })
+        );
+    }
+
     #[test]
     #[allow(non_snake_case)]
     fn test_augmentation_parse_L() {
@@ -7918,4 +8820,169 @@ mod tests {
             unwind_one(&mut ctx, &data2);
         }
     }
+
+    #[test]
+    fn test_evaluate_rules() {
+        struct Frame;
+
+        impl UnwindFrameAccess for Frame {
+            fn register(&mut self, register: Register) -> Result<u64> {
+                match register {
+                    Register(6) => Ok(0x1234),
+                    Register(7) => Ok(0x7000),
+                    _ => Err(Error::UnsupportedEvaluation),
+                }
+            }
+
+            fn read_memory(&mut self, address: u64, size: u8) -> Result<u64> {
+                assert_eq!(size, 8);
+                Ok(address + 0x1_0000)
+            }
+        }
+
+        #[rustfmt::skip]
+        let section = Section::with_endian(Endian::Little)
+            // DW_OP_breg7 8
+            .D8(constants::DW_OP_breg7.0).sleb(8)
+            // DW_OP_lit16, DW_OP_minus
+            .D8(constants::DW_OP_lit16.0).D8(constants::DW_OP_minus.0)
+            // DW_OP_call_frame_cfa, DW_OP_deref, DW_OP_stack_value
+            .D8(constants::DW_OP_call_frame_cfa.0)
+            .D8(constants::DW_OP_deref.0)
+            .D8(constants::DW_OP_stack_value.0);
+        let contents = section.get_contents().unwrap();
+        let debug_frame = DebugFrame::new(&contents, LittleEndian);
+        let expression = |offset, length| UnwindExpression { offset, length };
+        let frame = &mut Frame;
+
+        let cfa = CfaRule::RegisterAndOffset {
+            register: Register(7),
+            offset: -16,
+        };
+        assert_eq!(cfa.evaluate(&debug_frame, 8, frame), Ok(0x6ff0));
+        let cfa = CfaRule::Expression(expression(0, 2));
+        assert_eq!(cfa.evaluate(&debug_frame, 8, frame), Ok(0x7008));
+        assert_eq!(cfa.evaluate(&debug_frame, 4, frame), Ok(0x7008));
+
+        let cfa = 0x7008;
+        let evaluate = |rule: RegisterRule<usize>, frame: &mut Frame| {
+            rule.evaluate(&debug_frame, 8, Register(6), cfa, frame)
+        };
+        assert_eq!(
+            evaluate(RegisterRule::Undefined, frame),
+            Ok(RegisterRuleValue::Undefined)
+        );
+        assert_eq!(
+            evaluate(RegisterRule::SameValue, frame),
+            Ok(RegisterRuleValue::Value(0x1234))
+        );
+        assert_eq!(
+            evaluate(RegisterRule::Register(Register(7)), frame),
+            Ok(RegisterRuleValue::Value(0x7000))
+        );
+        assert_eq!(
+            evaluate(RegisterRule::Offset(-8), frame),
+            Ok(RegisterRuleValue::Address(0x7000))
+        );
+        assert_eq!(
+            evaluate(RegisterRule::ValOffset(-8), frame),
+            Ok(RegisterRuleValue::Value(0x7000))
+        );
+        assert_eq!(
+            evaluate(RegisterRule::Expression(expression(2, 2)), frame),
+            Ok(RegisterRuleValue::Address(0x6ff8))
+        );
+        assert_eq!(
+            evaluate(RegisterRule::ValExpression(expression(4, 3)), frame),
+            Ok(RegisterRuleValue::Value(0x1_7008))
+        );
+        assert_eq!(
+            evaluate(RegisterRule::Expression(expression(4, 3)), frame),
+            Err(Error::UnsupportedEvaluation)
+        );
+        assert_eq!(
+            evaluate(RegisterRule::Constant(5), frame),
+            Ok(RegisterRuleValue::Value(5))
+        );
+        assert_eq!(
+            evaluate(RegisterRule::Architectural, frame),
+            Err(Error::UnsupportedEvaluation)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_serde() {
+        let instructions: [CallFrameInstruction<usize>; 3] = [
+            CallFrameInstruction::DefCfa {
+                register: Register(7),
+                offset: 8,
+            },
+            CallFrameInstruction::Offset {
+                register: Register(16),
+                factored_offset: 1,
+            },
+            CallFrameInstruction::DefCfaExpression {
+                expression: UnwindExpression {
+                    offset: 0x20,
+                    length: 2,
+                },
+            },
+        ];
+        let json = serde_json::to_string(&instructions).unwrap();
+        assert_eq!(
+            serde_json::from_str::<[CallFrameInstruction<usize>; 3]>(&json).unwrap(),
+            instructions
+        );
+
+        let rules: [RegisterRule<usize>; 2] = [RegisterRule::Offset(-8), RegisterRule::SameValue];
+        let json = serde_json::to_string(&rules).unwrap();
+        assert_eq!(
+            serde_json::from_str::<[RegisterRule<usize>; 2]>(&json).unwrap(),
+            rules
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "serde", feature = "endian-reader"))]
+    fn test_serde_fde() {
+        use crate::read::EndianRcSlice;
+        use alloc::rc::Rc;
+
+        let reader = |buf: &[u8]| EndianRcSlice::new(Rc::from(buf), LittleEndian);
+        let mut augmentation = Augmentation::default();
+        augmentation.lsda = Some(constants::DW_EH_PE_absptr);
+        let cie = CommonInformationEntry {
+            offset: 0,
+            format: Format::Dwarf32,
+            length: 0x14,
+            return_address_register: Register(16),
+            version: 1,
+            address_size: 8,
+            initial_instructions: reader(&[0x0c, 0x07, 0x08]),
+            augmentation: Some(augmentation),
+            segment_size: 0,
+            data_alignment_factor: -8,
+            code_alignment_factor: 1,
+        };
+        let fde = FrameDescriptionEntry {
+            offset: 0x18,
+            length: 0x1c,
+            format: Format::Dwarf32,
+            cie,
+            initial_segment: 0,
+            initial_address: 0xfeed_face,
+            address_range: 9000,
+            augmentation: Some(AugmentationData {
+                lsda: Some(Pointer::Direct(0x1122_3344)),
+            }),
+            instructions: reader(&[0x41, 0x0e, 0x10]),
+        };
+        let json = serde_json::to_string(&fde).unwrap();
+        assert_eq!(
+            serde_json::from_str::<FrameDescriptionEntry<EndianRcSlice<LittleEndian>>>(&json)
+                .unwrap(),
+            fde
+        );
+    }
 }
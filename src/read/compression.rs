@@ -0,0 +1,340 @@
+use alloc::borrow::Cow;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use std::io::Read;
+
+#[cfg(feature = "endian-reader")]
+use crate::common::SectionId;
+use crate::endianity::Endianity;
+#[cfg(feature = "endian-reader")]
+use crate::read::{Dwarf, EndianArcSlice};
+use crate::read::{EndianSlice, Error, Reader, Result};
+
+/// The `ch_type` value for zlib compression in an ELF compression header.
+const ELFCOMPRESS_ZLIB: u32 = 1;
+/// The `ch_type` value for Zstandard compression in an ELF compression header.
+const ELFCOMPRESS_ZSTD: u32 = 2;
+
+/// The format used to compress the data of a section.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompressionFormat {
+    /// The data is not compressed.
+    None,
+    /// The data is compressed with zlib.
+    Zlib,
+    /// The data is compressed with Zstandard.
+    Zstandard,
+}
+
+/// The data of a section that may be compressed.
+///
+/// Sections may be compressed in one of two ways:
+///
+/// * ELF sections with the `SHF_COMPRESSED` flag start with an ELF compression
+///   header that gives the compression format and uncompressed size.
+///   Use [`CompressedSection::from_elf`] for these.
+///
+/// * GNU `.zdebug_*` sections start with the bytes `ZLIB` followed by the
+///   uncompressed size as a big-endian 64-bit value.
+///   Use [`CompressedSection::from_zdebug`] for these.
+///
+/// The data is only decompressed when [`CompressedSection::decompress`] is called,
+/// so this can be used in the loader function passed to `DwarfSections::load`
+/// to only decompress the sections that are needed. Alternatively, use
+/// [`Dwarf::load_compressed`] to load all of the sections into owned readers.
+///
+/// ```rust,no_run
+/// # fn example(compressed: &[u8]) -> gimli::Result<()> {
+/// use gimli::{CompressedSection, LittleEndian};
+///
+/// let section = CompressedSection::from_elf(compressed, LittleEndian, true)?;
+/// let data = section.decompress()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CompressedSection<'data> {
+    format: CompressionFormat,
+    data: &'data [u8],
+    uncompressed_size: u64,
+}
+
+impl<'data> CompressedSection<'data> {
+    /// Create a `CompressedSection` for data that is not compressed.
+    pub fn uncompressed(data: &'data [u8]) -> Self {
+        CompressedSection {
+            format: CompressionFormat::None,
+            data,
+            uncompressed_size: data.len() as u64,
+        }
+    }
+
+    /// Parse the ELF compression header at the start of the data of a section
+    /// with the `SHF_COMPRESSED` flag.
+    ///
+    /// `endian` is the endianity of the ELF file, and `is_64` is whether it is
+    /// a 64-bit ELF file.
+    pub fn from_elf<Endian: Endianity>(
+        data: &'data [u8],
+        endian: Endian,
+        is_64: bool,
+    ) -> Result<Self> {
+        let mut input = EndianSlice::new(data, endian);
+        let ch_type = input.read_u32()?;
+        let uncompressed_size = if is_64 {
+            // ch_reserved
+            input.read_u32()?;
+            let size = input.read_u64()?;
+            // ch_addralign
+            input.read_u64()?;
+            size
+        } else {
+            let size = input.read_u32()?;
+            // ch_addralign
+            input.read_u32()?;
+            u64::from(size)
+        };
+        let format = match ch_type {
+            ELFCOMPRESS_ZLIB => CompressionFormat::Zlib,
+            ELFCOMPRESS_ZSTD => CompressionFormat::Zstandard,
+            _ => return Err(Error::UnsupportedCompressionFormat),
+        };
+        Ok(CompressedSection {
+            format,
+            data: input.slice(),
+            uncompressed_size,
+        })
+    }
+
+    /// Parse the header at the start of the data of a GNU `.zdebug_*` section.
+    ///
+    /// If the data does not start with the `ZLIB` header, then it is assumed
+    /// to be uncompressed.
+    pub fn from_zdebug(data: &'data [u8]) -> Result<Self> {
+        if !data.starts_with(b"ZLIB") {
+            return Ok(Self::uncompressed(data));
+        }
+        let mut input = EndianSlice::new(&data[4..], crate::BigEndian);
+        let uncompressed_size = input.read_u64()?;
+        Ok(CompressedSection {
+            format: CompressionFormat::Zlib,
+            data: input.slice(),
+            uncompressed_size,
+        })
+    }
+
+    /// Return the compression format.
+    pub fn format(&self) -> CompressionFormat {
+        self.format
+    }
+
+    /// Return the compressed data, excluding any compression header.
+    pub fn data(&self) -> &'data [u8] {
+        self.data
+    }
+
+    /// Return the size of the data after decompression.
+    pub fn uncompressed_size(&self) -> u64 {
+        self.uncompressed_size
+    }
+
+    /// Decompress the data.
+    ///
+    /// Returns the data without copying if it is not compressed.
+    ///
+    /// Returns an error if the decompressed data does not have the size
+    /// given in the compression header. Decompression stops once that size
+    /// is exceeded, so corrupt data can't use an unbounded amount of memory.
+    pub fn decompress(&self) -> Result<Cow<'data, [u8]>> {
+        if self.format == CompressionFormat::None {
+            return Ok(Cow::Borrowed(self.data));
+        }
+        let size =
+            usize::try_from(self.uncompressed_size).map_err(|_| Error::InvalidCompressedData)?;
+        // Don't trust the size too much when reserving memory, in case the data is corrupt.
+        let mut out = Vec::new();
+        out.try_reserve_exact(size.min(self.data.len().saturating_mul(64)))
+            .map_err(|_| Error::InvalidCompressedData)?;
+        // Read at most one byte more than the expected size, which is enough
+        // to detect data that is too large.
+        let limit = size as u64 + 1;
+        match self.format {
+            CompressionFormat::None => {}
+            CompressionFormat::Zlib => {
+                let decoder = flate2::read::ZlibDecoder::new(self.data);
+                decoder
+                    .take(limit)
+                    .read_to_end(&mut out)
+                    .map_err(|_| Error::InvalidCompressedData)?;
+            }
+            CompressionFormat::Zstandard => {
+                let mut input = self.data;
+                while !input.is_empty() && out.len() <= size {
+                    let decoder = ruzstd::StreamingDecoder::new(&mut input)
+                        .map_err(|_| Error::InvalidCompressedData)?;
+                    decoder
+                        .take(limit - out.len() as u64)
+                        .read_to_end(&mut out)
+                        .map_err(|_| Error::InvalidCompressedData)?;
+                }
+            }
+        }
+        if out.len() != size {
+            return Err(Error::InvalidCompressedData);
+        }
+        Ok(Cow::Owned(out))
+    }
+}
+
+#[cfg(feature = "endian-reader")]
+impl<Endian: Endianity> Dwarf<EndianArcSlice<Endian>> {
+    /// Try to load the DWARF sections, decompressing them if required.
+    ///
+    /// `section` is a function that returns the possibly compressed data of
+    /// the section with the given id. Each section is decompressed into a
+    /// shared, owned reader as in `Dwarf::load_owned`.
+    ///
+    /// ```rust,no_run
+    /// # fn example(object: &std::collections::HashMap<&str, Vec<u8>>) {
+    /// use gimli::{CompressedSection, LittleEndian};
+    ///
+    /// let dwarf = gimli::Dwarf::load_compressed(
+    ///     |id| match object.get(id.name()) {
+    ///         Some(data) => CompressedSection::from_elf(data, LittleEndian, true),
+    ///         None => Ok(CompressedSection::uncompressed(&[])),
+    ///     },
+    ///     LittleEndian,
+    /// )
+    /// .unwrap();
+    /// # }
+    /// ```
+    pub fn load_compressed<'data, F, E>(
+        mut section: F,
+        endian: Endian,
+    ) -> core::result::Result<Self, E>
+    where
+        F: FnMut(SectionId) -> core::result::Result<CompressedSection<'data>, E>,
+        E: From<Error>,
+    {
+        Self::load_owned(|id| Ok(section(id)?.decompress()?), endian)
+    }
+
+    /// Try to load the DWARF sections from the supplementary object file,
+    /// decompressing them if required.
+    ///
+    /// `section` operates the same as for `load_compressed`.
+    ///
+    /// Sets `self.sup`, replacing any previous value.
+    pub fn load_sup_compressed<'data, F, E>(
+        &mut self,
+        mut section: F,
+        endian: Endian,
+    ) -> core::result::Result<(), E>
+    where
+        F: FnMut(SectionId) -> core::result::Result<CompressedSection<'data>, E>,
+        E: From<Error>,
+    {
+        self.load_sup_owned(|id| Ok(section(id)?.decompress()?), endian)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::endianity::LittleEndian;
+    use std::io::Write;
+    use test_assembler::{Endian, Section};
+
+    fn zlib(data: &[u8]) -> Vec<u8> {
+        let mut encoder = flate2::write::ZlibEncoder::new(Vec::new(), Default::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[test]
+    fn test_from_elf() {
+        let data = b"uncompressed data";
+        let compressed = zlib(data);
+        let section = Section::with_endian(Endian::Little)
+            .L32(ELFCOMPRESS_ZLIB)
+            .L32(0)
+            .L64(data.len() as u64)
+            .L64(1)
+            .append_bytes(&compressed);
+        let buf = section.get_contents().unwrap();
+        let section = CompressedSection::from_elf(&buf, LittleEndian, true).unwrap();
+        assert_eq!(section.format(), CompressionFormat::Zlib);
+        assert_eq!(section.uncompressed_size(), data.len() as u64);
+        assert_eq!(&*section.decompress().unwrap(), &data[..]);
+
+        let section = Section::with_endian(Endian::Little)
+            .L32(ELFCOMPRESS_ZLIB)
+            .L32(data.len() as u32 + 1)
+            .L32(1)
+            .append_bytes(&compressed);
+        let buf = section.get_contents().unwrap();
+        let section = CompressedSection::from_elf(&buf, LittleEndian, false).unwrap();
+        assert_eq!(section.decompress(), Err(Error::InvalidCompressedData));
+
+        // The header size is smaller than the decompressed data.
+        let section = Section::with_endian(Endian::Little)
+            .L32(ELFCOMPRESS_ZLIB)
+            .L32(4)
+            .L32(1)
+            .append_bytes(&compressed);
+        let buf = section.get_contents().unwrap();
+        let section = CompressedSection::from_elf(&buf, LittleEndian, false).unwrap();
+        assert_eq!(section.decompress(), Err(Error::InvalidCompressedData));
+
+        let section = Section::with_endian(Endian::Little).L32(3).L32(0).L32(1);
+        let buf = section.get_contents().unwrap();
+        assert_eq!(
+            CompressedSection::from_elf(&buf, LittleEndian, false),
+            Err(Error::UnsupportedCompressionFormat)
+        );
+    }
+
+    #[test]
+    fn test_from_zdebug() {
+        let data = b"uncompressed data";
+        let section = Section::with_endian(Endian::Big)
+            .append_bytes(b"ZLIB")
+            .D64(data.len() as u64)
+            .append_bytes(&zlib(data));
+        let buf = section.get_contents().unwrap();
+        let section = CompressedSection::from_zdebug(&buf).unwrap();
+        assert_eq!(section.format(), CompressionFormat::Zlib);
+        assert_eq!(&*section.decompress().unwrap(), &data[..]);
+
+        let section = CompressedSection::from_zdebug(data).unwrap();
+        assert_eq!(section.format(), CompressionFormat::None);
+        assert_eq!(section.decompress(), Ok(Cow::Borrowed(&data[..])));
+    }
+
+    #[cfg(feature = "endian-reader")]
+    #[test]
+    fn test_load_compressed() {
+        let data = b"string\0";
+        let section = Section::with_endian(Endian::Big)
+            .append_bytes(b"ZLIB")
+            .D64(data.len() as u64)
+            .append_bytes(&zlib(data));
+        let buf = section.get_contents().unwrap();
+        let dwarf = Dwarf::load_compressed(
+            |id| match id {
+                SectionId::DebugStr => CompressedSection::from_zdebug(&buf),
+                _ => Ok(CompressedSection::uncompressed(&[])),
+            },
+            LittleEndian,
+        )
+        .unwrap();
+        let string = dwarf.debug_str.get_str(crate::DebugStrOffset(0)).unwrap();
+        assert_eq!(&*string, b"string");
+
+        let result = Dwarf::load_compressed(
+            |_| CompressedSection::from_zdebug(&buf[..buf.len() - 1]),
+            LittleEndian,
+        );
+        assert_eq!(result.err(), Some(Error::InvalidCompressedData));
+    }
+}
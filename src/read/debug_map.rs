@@ -0,0 +1,422 @@
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::read::range_index::{RangeIndex, RangeIndexEntry};
+use crate::read::{Dwarf, Range, Reader, Result, SymbolInfo, Symbolizer};
+
+/// A symbol in a Mach-O debug map.
+///
+/// This is usually obtained from the `N_FUN` and `N_STSYM` STABS entries that
+/// follow an `N_OSO` entry in the symbol table of the executable, combined with
+/// the address of the symbol of the same name in the object file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DebugMapSymbol {
+    /// The name of the symbol.
+    pub name: String,
+    /// The address of the symbol in the object file.
+    pub object_address: u64,
+    /// The address of the symbol in the executable.
+    pub address: u64,
+    /// The size of the symbol in bytes.
+    ///
+    /// Symbols with a size of 0 are ignored when translating addresses.
+    pub size: u64,
+}
+
+/// An object file in a Mach-O debug map.
+///
+/// This is usually obtained from an `N_OSO` STABS entry in the symbol table
+/// of the executable.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DebugMapObject {
+    /// The path of the object file.
+    ///
+    /// This may refer to a member of a static archive, using the
+    /// `archive.a(member.o)` syntax.
+    pub path: String,
+    /// The modification time of the object file recorded in the executable,
+    /// or 0 if unknown.
+    ///
+    /// A loader may compare this to the modification time of the file to
+    /// detect stale object files.
+    pub timestamp: u64,
+    /// The symbols that the executable contains from the object file.
+    pub symbols: Vec<DebugMapSymbol>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct DebugMapRange {
+    begin: u64,
+    end: u64,
+    object: usize,
+    symbol: usize,
+}
+
+impl RangeIndexEntry for DebugMapRange {
+    fn range(&self) -> Range {
+        Range {
+            begin: self.begin,
+            end: self.end,
+        }
+    }
+}
+
+/// A Mach-O debug map.
+///
+/// On macOS, the linker does not copy the DWARF sections into the executable.
+/// Instead, the symbol table of the executable contains STABS entries that
+/// reference the original object files, and the addresses of the symbols in
+/// the executable. The debug map is used to translate between the addresses
+/// in the executable and the addresses in each object file.
+///
+/// Parsing the symbol table is the responsibility of the caller.
+#[derive(Debug, Clone, Default)]
+pub struct DebugMap {
+    objects: Vec<DebugMapObject>,
+    /// The ranges of the symbols in the executable.
+    ranges: RangeIndex<DebugMapRange>,
+    /// The ranges of the symbols in each object file.
+    object_ranges: Vec<RangeIndex<DebugMapRange>>,
+}
+
+impl DebugMap {
+    /// Create a debug map for the given object files.
+    pub fn new(objects: Vec<DebugMapObject>) -> Self {
+        let mut ranges = Vec::new();
+        let mut object_ranges = Vec::with_capacity(objects.len());
+        for (object_index, object) in objects.iter().enumerate() {
+            let mut symbol_ranges = Vec::new();
+            for (symbol_index, symbol) in object.symbols.iter().enumerate() {
+                if symbol.size == 0 {
+                    continue;
+                }
+                ranges.push(DebugMapRange {
+                    begin: symbol.address,
+                    end: symbol.address.wrapping_add(symbol.size),
+                    object: object_index,
+                    symbol: symbol_index,
+                });
+                symbol_ranges.push(DebugMapRange {
+                    begin: symbol.object_address,
+                    end: symbol.object_address.wrapping_add(symbol.size),
+                    object: object_index,
+                    symbol: symbol_index,
+                });
+            }
+            object_ranges.push(RangeIndex::new(symbol_ranges));
+        }
+        DebugMap {
+            objects,
+            ranges: RangeIndex::new(ranges),
+            object_ranges,
+        }
+    }
+
+    /// Return the object files in the debug map.
+    pub fn objects(&self) -> &[DebugMapObject] {
+        &self.objects
+    }
+
+    /// Find the symbol containing the given address in the executable.
+    ///
+    /// Returns the index of the object file and the symbol.
+    pub fn find_symbol(&self, address: u64) -> Option<(usize, &DebugMapSymbol)> {
+        let range = self.ranges.find(address)?;
+        Some((
+            range.object,
+            &self.objects[range.object].symbols[range.symbol],
+        ))
+    }
+
+    /// Translate an address in the executable to an address in an object file.
+    ///
+    /// Returns the index of the object file and the address within it.
+    pub fn object_address(&self, address: u64) -> Option<(usize, u64)> {
+        let range = self.ranges.find(address)?;
+        let symbol = &self.objects[range.object].symbols[range.symbol];
+        Some((
+            range.object,
+            symbol.object_address.wrapping_add(address - symbol.address),
+        ))
+    }
+
+    /// Translate an address in the object file with the given index to an
+    /// address in the executable.
+    ///
+    /// Returns `None` if the address is not within a symbol that was included in
+    /// the executable. This can be used as the `convert_address` function when
+    /// converting the DWARF of an object file with `write::Dwarf::from`.
+    pub fn executable_address(&self, object: usize, object_address: u64) -> Option<u64> {
+        let range = self.object_ranges.get(object)?.find(object_address)?;
+        let symbol = &self.objects[range.object].symbols[range.symbol];
+        Some(
+            symbol
+                .address
+                .wrapping_add(object_address - symbol.object_address),
+        )
+    }
+}
+
+/// A loader for the object files referenced by a [`DebugMap`].
+///
+/// It is implemented for closures with a matching signature.
+pub trait DebugMapLoader<R: Reader> {
+    /// Load the DWARF sections from an object file.
+    ///
+    /// The addresses in the sections must be the addresses in the object file.
+    ///
+    /// Returns `None` if the object file could not be found.
+    fn load_object(&mut self, object: &DebugMapObject) -> Result<Option<Dwarf<R>>>;
+}
+
+impl<R, F> DebugMapLoader<R> for F
+where
+    R: Reader,
+    F: FnMut(&DebugMapObject) -> Result<Option<Dwarf<R>>>,
+{
+    fn load_object(&mut self, object: &DebugMapObject) -> Result<Option<Dwarf<R>>> {
+        self(object)
+    }
+}
+
+#[derive(Debug)]
+enum DebugMapObjectState<R: Reader> {
+    NotLoaded,
+    Missing,
+    Loaded(Box<(Dwarf<R>, Symbolizer<R>)>),
+}
+
+/// The function and source location for an address in an executable
+/// with a debug map.
+///
+/// See [`DebugMapSymbolizer::lookup`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DebugMapSymbolInfo<R: Reader> {
+    /// The index of the object file in the debug map.
+    pub object: usize,
+    /// The address within the object file.
+    pub object_address: u64,
+    /// The function and source location, found using the DWARF of the object file.
+    pub info: SymbolInfo<R>,
+}
+
+/// A cache for finding the function and source location of addresses in an
+/// executable with a debug map.
+///
+/// Addresses in the executable are translated to addresses in an object file
+/// using the [`DebugMap`], and then looked up in the DWARF of the object file
+/// with a [`Symbolizer`]. The object files are loaded on first use using a
+/// [`DebugMapLoader`], and then cached.
+///
+/// ```rust,no_run
+/// # fn example<R: gimli::Reader>(
+/// #     map: gimli::DebugMap,
+/// #     loader: &mut dyn gimli::DebugMapLoader<R>,
+/// # ) -> gimli::Result<()> {
+/// let mut symbolizer = gimli::DebugMapSymbolizer::new(map);
+/// if let Some(found) = symbolizer.lookup(0x1_0000_1234, loader)? {
+///     let path = &symbolizer.debug_map().objects()[found.object].path;
+///     println!("{}: {:?}:{:?}", path, found.info.file, found.info.line);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct DebugMapSymbolizer<R: Reader> {
+    map: DebugMap,
+    objects: Vec<DebugMapObjectState<R>>,
+}
+
+impl<R: Reader> DebugMapSymbolizer<R> {
+    /// Create a symbolizer for the given debug map.
+    pub fn new(map: DebugMap) -> Self {
+        let mut objects = Vec::with_capacity(map.objects.len());
+        objects.resize_with(map.objects.len(), || DebugMapObjectState::NotLoaded);
+        DebugMapSymbolizer { map, objects }
+    }
+
+    /// Return the debug map.
+    pub fn debug_map(&self) -> &DebugMap {
+        &self.map
+    }
+
+    /// Return the DWARF sections of the object file with the given index.
+    ///
+    /// The object file is loaded using `loader` if it has not been loaded yet.
+    /// Returns `None` if the index is invalid or if the loader could not find
+    /// the object file. Errors are not cached.
+    pub fn object_dwarf(
+        &mut self,
+        object: usize,
+        loader: &mut dyn DebugMapLoader<R>,
+    ) -> Result<Option<&Dwarf<R>>> {
+        Ok(self.load(object, loader)?.map(|loaded| &loaded.0))
+    }
+
+    /// Find the function and source location for the given address in the executable.
+    ///
+    /// Returns `None` if the address is not in the debug map, if the object file
+    /// could not be found, or if no compilation unit in the object file contains
+    /// the address.
+    pub fn lookup(
+        &mut self,
+        address: u64,
+        loader: &mut dyn DebugMapLoader<R>,
+    ) -> Result<Option<DebugMapSymbolInfo<R>>> {
+        let (object, object_address) = match self.map.object_address(address) {
+            Some(found) => found,
+            None => return Ok(None),
+        };
+        let (dwarf, symbolizer) = match self.load(object, loader)? {
+            Some(loaded) => loaded,
+            None => return Ok(None),
+        };
+        let info = match symbolizer.lookup(dwarf, object_address)? {
+            Some(info) => info,
+            None => return Ok(None),
+        };
+        Ok(Some(DebugMapSymbolInfo {
+            object,
+            object_address,
+            info,
+        }))
+    }
+
+    fn load(
+        &mut self,
+        object: usize,
+        loader: &mut dyn DebugMapLoader<R>,
+    ) -> Result<Option<&(Dwarf<R>, Symbolizer<R>)>> {
+        let state = match self.objects.get_mut(object) {
+            Some(state) => state,
+            None => return Ok(None),
+        };
+        if let DebugMapObjectState::NotLoaded = state {
+            *state = match loader.load_object(&self.map.objects[object])? {
+                Some(dwarf) => {
+                    let symbolizer = Symbolizer::new(&dwarf);
+                    DebugMapObjectState::Loaded(Box::new((dwarf, symbolizer)))
+                }
+                None => DebugMapObjectState::Missing,
+            };
+        }
+        match state {
+            DebugMapObjectState::Loaded(loaded) => Ok(Some(loaded)),
+            _ => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::{DebugInfoOffset, UnitSectionOffset};
+    use crate::constants::*;
+    use crate::endianity::LittleEndian;
+    use crate::read::abbrev::tests::AbbrevSectionMethods;
+    use crate::read::{DebugAbbrev, DebugInfo, EndianSlice, Error};
+    use test_assembler::{Endian, Section};
+
+    fn debug_map() -> DebugMap {
+        let symbol = |name: &str, object_address, address, size| DebugMapSymbol {
+            name: name.into(),
+            object_address,
+            address,
+            size,
+        };
+        DebugMap::new(vec![
+            DebugMapObject {
+                path: "/work/a.o".into(),
+                timestamp: 0,
+                symbols: vec![
+                    symbol("_b", 0x20, 0x2000, 0x8),
+                    symbol("_a", 0, 0x1000, 0x10),
+                ],
+            },
+            DebugMapObject {
+                path: "/work/lib.a(b.o)".into(),
+                timestamp: 0,
+                symbols: vec![symbol("_c", 0, 0x1010, 0x10), symbol("_d", 0x10, 0, 0)],
+            },
+        ])
+    }
+
+    #[test]
+    fn test_debug_map() {
+        let map = debug_map();
+        assert_eq!(map.find_symbol(0xfff), None);
+        assert_eq!(
+            map.find_symbol(0x1004)
+                .map(|(object, symbol)| (object, &*symbol.name)),
+            Some((0, "_a"))
+        );
+        assert_eq!(map.object_address(0x1004), Some((0, 0x4)));
+        assert_eq!(map.object_address(0x1014), Some((1, 0x4)));
+        assert_eq!(map.object_address(0x2004), Some((0, 0x24)));
+        assert_eq!(map.object_address(0x2008), None);
+
+        assert_eq!(map.executable_address(0, 0x4), Some(0x1004));
+        assert_eq!(map.executable_address(0, 0x10), None);
+        assert_eq!(map.executable_address(0, 0x27), Some(0x2007));
+        assert_eq!(map.executable_address(1, 0x4), Some(0x1014));
+        assert_eq!(map.executable_address(1, 0x14), None);
+        assert_eq!(map.executable_address(2, 0x4), None);
+    }
+
+    #[test]
+    fn test_debug_map_symbolizer() {
+        #[rustfmt::skip]
+        let abbrev = Section::with_endian(Endian::Little)
+            .abbrev(1, DW_TAG_compile_unit, DW_CHILDREN_no)
+                .abbrev_attr(DW_AT_low_pc, DW_FORM_addr)
+                .abbrev_attr(DW_AT_high_pc, DW_FORM_udata)
+                .abbrev_attr_null()
+            .abbrev_null();
+        let abbrev = abbrev.get_contents().unwrap();
+        // A unit covering the object addresses 0 to 0x10.
+        let info = Section::with_endian(Endian::Little)
+            .L32(17)
+            .L16(4)
+            .L32(0)
+            .D8(8)
+            .D8(1)
+            .L64(0)
+            .D8(0x10);
+        let info = info.get_contents().unwrap();
+
+        let mut loaded = Vec::new();
+        let mut loader = |object: &DebugMapObject| {
+            loaded.push(object.path.clone());
+            if object.path.ends_with("(b.o)") {
+                return Ok(None);
+            }
+            Ok(Some(Dwarf {
+                debug_abbrev: DebugAbbrev::new(&abbrev, LittleEndian),
+                debug_info: DebugInfo::new(&info, LittleEndian),
+                ..Default::default()
+            }))
+        };
+
+        let mut symbolizer = DebugMapSymbolizer::<EndianSlice<'_, LittleEndian>>::new(debug_map());
+        let found = symbolizer.lookup(0x1004, &mut loader).unwrap().unwrap();
+        assert_eq!(found.object, 0);
+        assert_eq!(found.object_address, 0x4);
+        assert_eq!(
+            found.info.unit_offset,
+            UnitSectionOffset::DebugInfoOffset(DebugInfoOffset(0))
+        );
+        assert!(symbolizer.lookup(0x100f, &mut loader).unwrap().is_some());
+        // The object address 0x20 is not in the unit.
+        assert_eq!(symbolizer.lookup(0x2000, &mut loader), Ok(None));
+        assert_eq!(symbolizer.lookup(0x1014, &mut loader), Ok(None));
+        assert_eq!(symbolizer.lookup(0x1018, &mut loader), Ok(None));
+        assert!(symbolizer.object_dwarf(0, &mut loader).unwrap().is_some());
+        assert!(symbolizer.object_dwarf(1, &mut loader).unwrap().is_none());
+        assert_eq!(loaded, ["/work/a.o", "/work/lib.a(b.o)"]);
+
+        let mut failing = |_: &DebugMapObject| Err(Error::Io);
+        let mut symbolizer = DebugMapSymbolizer::<EndianSlice<'_, LittleEndian>>::new(debug_map());
+        assert_eq!(symbolizer.lookup(0x1004, &mut failing), Err(Error::Io));
+    }
+}
@@ -0,0 +1,841 @@
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::common::{DebugTypeSignature, DwoId, Encoding, Register, SectionId, UnitSectionOffset};
+use crate::constants;
+use crate::read::symbolize::file_path;
+use crate::read::{
+    AttributeValue, Augmentation, BaseAddresses, CallFrameInstruction, CieOrFde,
+    DebuggingInformationEntry, Dwarf, Error, IncompleteLineProgram, LineRow, Pointer, Range,
+    Reader, ReaderOffset, Result, Unit, UnwindExpression, UnwindSection,
+};
+
+/// A structured representation of the units in a `Dwarf`.
+///
+/// This contains the same information that is printed by a tool such as
+/// `dwarfdump`, but in a form that is easier to process. Attribute values are
+/// resolved where possible: strings are read from their sections, references
+/// are converted to section offsets, and location and range lists are parsed.
+///
+/// If the `serde` feature is enabled, then this can be serialized.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct DwarfDump {
+    /// The units in the `.debug_info` section, followed by the units in the
+    /// `.debug_types` section.
+    pub units: Vec<UnitDump>,
+}
+
+impl DwarfDump {
+    /// Parse all of the units in the given `Dwarf`.
+    pub fn new<R: Reader>(dwarf: &Dwarf<R>) -> Result<Self> {
+        let mut units = Vec::new();
+        let mut headers = dwarf.units();
+        while let Some(header) = headers.next()? {
+            let unit = dwarf.unit(header)?;
+            units.push(UnitDump::new(dwarf, &unit)?);
+        }
+        let mut headers = dwarf.type_units();
+        while let Some(header) = headers.next()? {
+            let unit = dwarf.unit(header)?;
+            units.push(UnitDump::new(dwarf, &unit)?);
+        }
+        Ok(DwarfDump { units })
+    }
+}
+
+/// A structured representation of a unit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct UnitDump {
+    /// The section containing the unit.
+    pub section: SectionId,
+    /// The offset of the unit header within `section`.
+    pub offset: u64,
+    /// The type of the unit.
+    pub unit_type: constants::DwUt,
+    /// The encoding parameters of the unit.
+    pub encoding: Encoding,
+    /// The DWO ID of a skeleton unit or split compilation unit.
+    pub dwo_id: Option<DwoId>,
+    /// The root entry of the unit, including all of its children.
+    pub root: EntryDump,
+    /// The line number program of the unit.
+    pub line_program: Option<LineProgramDump>,
+}
+
+impl UnitDump {
+    /// Parse the entries and line number program of the given unit.
+    pub fn new<R: Reader>(dwarf: &Dwarf<R>, unit: &Unit<R>) -> Result<Self> {
+        let (section, offset) = section_offset(unit.header.offset());
+        let root = EntryDump::tree(dwarf, unit)?;
+        let line_program = match unit.line_program {
+            Some(ref program) => Some(LineProgramDump::new(dwarf, unit, program.clone())?),
+            None => None,
+        };
+        Ok(UnitDump {
+            section,
+            offset,
+            unit_type: unit.header.type_().dw_ut(),
+            encoding: unit.encoding(),
+            dwo_id: unit.dwo_id,
+            root,
+            line_program,
+        })
+    }
+}
+
+/// A structured representation of a debugging information entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct EntryDump {
+    /// The offset of the entry within the section containing its unit.
+    pub offset: u64,
+    /// The tag of the entry.
+    pub tag: constants::DwTag,
+    /// The attributes of the entry.
+    pub attributes: Vec<AttributeDump>,
+    /// The children of the entry.
+    pub children: Vec<EntryDump>,
+}
+
+impl EntryDump {
+    /// Parse the root entry of the unit and all of its children.
+    ///
+    /// This uses an explicit stack instead of recursion, so that deeply nested
+    /// entries can't overflow the call stack. The depth is limited by
+    /// `Limits::max_die_depth`.
+    fn tree<R: Reader>(dwarf: &Dwarf<R>, unit: &Unit<R>) -> Result<Self> {
+        let mut entries = unit.entries();
+        let mut stack = match entries.next_dfs()? {
+            Some((_, root)) => vec![EntryDump::new(dwarf, unit, root)?],
+            None => return Err(Error::UnexpectedNull),
+        };
+        let mut depth = 0;
+        while let Some((delta_depth, entry)) = entries.next_dfs()? {
+            depth += delta_depth;
+            if depth <= 0 {
+                break;
+            }
+            // Move any finished entries into their parents.
+            while stack.len() > depth as usize {
+                finish_entry(&mut stack);
+            }
+            stack.push(EntryDump::new(dwarf, unit, entry)?);
+        }
+        while stack.len() > 1 {
+            finish_entry(&mut stack);
+        }
+        Ok(stack.pop().unwrap())
+    }
+
+    /// Parse a single entry, without its children.
+    fn new<R: Reader>(
+        dwarf: &Dwarf<R>,
+        unit: &Unit<R>,
+        entry: &DebuggingInformationEntry<'_, '_, R>,
+    ) -> Result<Self> {
+        let (_, offset) = section_offset(entry.offset().to_unit_section_offset(unit));
+        let mut attributes = Vec::new();
+        let mut attrs = entry.attrs();
+        while let Some(attr) = attrs.next()? {
+            attributes.push(AttributeDump {
+                name: attr.name(),
+                value: DumpValue::new(dwarf, unit, attr.value())?,
+            });
+        }
+        Ok(EntryDump {
+            offset,
+            tag: entry.tag(),
+            attributes,
+            children: Vec::new(),
+        })
+    }
+}
+
+/// Pop the last entry from the stack and add it to the children of its parent.
+fn finish_entry(stack: &mut Vec<EntryDump>) {
+    let entry = stack.pop().unwrap();
+    stack.last_mut().unwrap().children.push(entry);
+}
+
+/// A structured representation of an attribute.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct AttributeDump {
+    /// The name of the attribute.
+    pub name: constants::DwAt,
+    /// The resolved value of the attribute.
+    pub value: DumpValue,
+}
+
+/// The resolved value of an attribute.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[non_exhaustive]
+pub enum DumpValue {
+    /// An address, including addresses read from `.debug_addr`.
+    Address(u64),
+    /// A block of bytes.
+    Block(Vec<u8>),
+    /// An unsigned constant.
+    Unsigned(u64),
+    /// A signed constant.
+    Signed(i64),
+    /// A boolean flag.
+    Flag(bool),
+    /// A string, including strings read from the string sections.
+    ///
+    /// Invalid UTF-8 is replaced with U+FFFD.
+    String(String),
+    /// The bytes of a DWARF expression.
+    Expression(Vec<u8>),
+    /// A reference to an entry in the `.debug_info` or `.debug_types` section.
+    Reference {
+        /// The section containing the entry.
+        section: SectionId,
+        /// The offset of the entry within the section.
+        offset: u64,
+    },
+    /// A reference to an entry in the `.debug_info` section of the
+    /// supplementary object file.
+    SupReference(u64),
+    /// A type signature.
+    TypeSignature(DebugTypeSignature),
+    /// An offset into a section that is not otherwise resolved.
+    SectionOffset {
+        /// The section that the offset refers to, if known.
+        section: Option<SectionId>,
+        /// The offset.
+        offset: u64,
+    },
+    /// A location list.
+    LocationList(Vec<LocationDump>),
+    /// A range list.
+    RangeList(Vec<Range>),
+    /// A named constant, such as `DW_ATE_signed` or `DW_LANG_Rust`.
+    Constant(String),
+    /// The path of a file in the unit's line number program, or `None` if
+    /// the file index is invalid.
+    File(Option<String>),
+    /// A DWO ID.
+    DwoId(DwoId),
+}
+
+impl DumpValue {
+    pub(crate) fn new<R: Reader>(
+        dwarf: &Dwarf<R>,
+        unit: &Unit<R>,
+        value: AttributeValue<R>,
+    ) -> Result<Self> {
+        Ok(match value {
+            AttributeValue::Addr(address) => DumpValue::Address(address),
+            AttributeValue::DebugAddrIndex(index) => {
+                DumpValue::Address(dwarf.address(unit, index)?)
+            }
+            AttributeValue::Block(data) => DumpValue::Block(data.to_slice()?.to_vec()),
+            AttributeValue::Data1(data) => DumpValue::Unsigned(data.into()),
+            AttributeValue::Data2(data) => DumpValue::Unsigned(data.into()),
+            AttributeValue::Data4(data) => DumpValue::Unsigned(data.into()),
+            AttributeValue::Data8(data) | AttributeValue::Udata(data) => DumpValue::Unsigned(data),
+            AttributeValue::Sdata(data) => DumpValue::Signed(data),
+            AttributeValue::Flag(flag) => DumpValue::Flag(flag),
+            AttributeValue::Exprloc(expression) => {
+                DumpValue::Expression(expression.0.to_slice()?.to_vec())
+            }
+            AttributeValue::UnitRef(offset) => {
+                let (section, offset) = section_offset(offset.to_unit_section_offset(unit));
+                DumpValue::Reference { section, offset }
+            }
+            AttributeValue::DebugInfoRef(offset) => DumpValue::Reference {
+                section: SectionId::DebugInfo,
+                offset: offset.0.into_u64(),
+            },
+            AttributeValue::DebugInfoRefSup(offset) => DumpValue::SupReference(offset.0.into_u64()),
+            AttributeValue::DebugTypesRef(signature) => DumpValue::TypeSignature(signature),
+            AttributeValue::SecOffset(offset) => DumpValue::SectionOffset {
+                section: None,
+                offset: offset.into_u64(),
+            },
+            AttributeValue::DebugAddrBase(base) => {
+                section_value(SectionId::DebugAddr, base.0.into_u64())
+            }
+            AttributeValue::DebugLineRef(offset) => {
+                section_value(SectionId::DebugLine, offset.0.into_u64())
+            }
+            AttributeValue::DebugLocListsBase(base) => {
+                section_value(SectionId::DebugLocLists, base.0.into_u64())
+            }
+            AttributeValue::DebugMacinfoRef(offset) => {
+                section_value(SectionId::DebugMacinfo, offset.0.into_u64())
+            }
+            AttributeValue::DebugMacroRef(offset) => {
+                section_value(SectionId::DebugMacro, offset.0.into_u64())
+            }
+            AttributeValue::DebugRngListsBase(base) => {
+                section_value(SectionId::DebugRngLists, base.0.into_u64())
+            }
+            AttributeValue::DebugStrOffsetsBase(base) => {
+                section_value(SectionId::DebugStrOffsets, base.0.into_u64())
+            }
+            AttributeValue::LocationListsRef(_) | AttributeValue::DebugLocListsIndex(_) => {
+                let mut locations = Vec::new();
+                if let Some(mut iter) = dwarf.attr_locations(unit, value)? {
+                    while let Some(location) = iter.next()? {
+                        locations.push(LocationDump {
+                            range: location.range,
+                            expression: location.data.0.to_slice()?.to_vec(),
+                        });
+                    }
+                }
+                DumpValue::LocationList(locations)
+            }
+            AttributeValue::RangeListsRef(_) | AttributeValue::DebugRngListsIndex(_) => {
+                let mut ranges = Vec::new();
+                if let Some(mut iter) = dwarf.attr_ranges(unit, value)? {
+                    while let Some(range) = iter.next()? {
+                        ranges.push(range);
+                    }
+                }
+                DumpValue::RangeList(ranges)
+            }
+            AttributeValue::DebugStrRef(_)
+            | AttributeValue::DebugStrRefSup(_)
+            | AttributeValue::DebugStrOffsetsIndex(_)
+            | AttributeValue::DebugLineStrRef(_)
+            | AttributeValue::String(_) => {
+                let string = dwarf.attr_string(unit, value)?;
+                DumpValue::String(string.to_string_lossy()?.into_owned())
+            }
+            AttributeValue::Encoding(value) => DumpValue::Constant(value.to_string()),
+            AttributeValue::DecimalSign(value) => DumpValue::Constant(value.to_string()),
+            AttributeValue::Endianity(value) => DumpValue::Constant(value.to_string()),
+            AttributeValue::Accessibility(value) => DumpValue::Constant(value.to_string()),
+            AttributeValue::Visibility(value) => DumpValue::Constant(value.to_string()),
+            AttributeValue::Virtuality(value) => DumpValue::Constant(value.to_string()),
+            AttributeValue::Language(value) => DumpValue::Constant(value.to_string()),
+            AttributeValue::AddressClass(value) => DumpValue::Constant(value.to_string()),
+            AttributeValue::IdentifierCase(value) => DumpValue::Constant(value.to_string()),
+            AttributeValue::CallingConvention(value) => DumpValue::Constant(value.to_string()),
+            AttributeValue::Inline(value) => DumpValue::Constant(value.to_string()),
+            AttributeValue::Ordering(value) => DumpValue::Constant(value.to_string()),
+            AttributeValue::FileIndex(index) => {
+                let path = match unit.line_program {
+                    Some(ref program) => file_path(dwarf, unit, program.header(), index)?,
+                    None => None,
+                };
+                DumpValue::File(path)
+            }
+            AttributeValue::DwoId(dwo_id) => DumpValue::DwoId(dwo_id),
+            AttributeValue::Unknown { bytes, .. } => DumpValue::Block(bytes.to_slice()?.to_vec()),
+        })
+    }
+}
+
+impl fmt::Display for DumpValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn hex(f: &mut fmt::Formatter<'_>, data: &[u8]) -> fmt::Result {
+            f.write_str("0x")?;
+            for byte in data {
+                write!(f, "{:02x}", byte)?;
+            }
+            Ok(())
+        }
+
+        match self {
+            DumpValue::Address(address) => write!(f, "0x{:x}", address),
+            DumpValue::Block(data) | DumpValue::Expression(data) => hex(f, data),
+            DumpValue::Unsigned(value) => write!(f, "{}", value),
+            DumpValue::Signed(value) => write!(f, "{}", value),
+            DumpValue::Flag(flag) => write!(f, "{}", flag),
+            DumpValue::String(s) | DumpValue::Constant(s) | DumpValue::File(Some(s)) => {
+                f.write_str(s)
+            }
+            DumpValue::Reference { section, offset } => {
+                write!(f, "<{}+0x{:x}>", section.name(), offset)
+            }
+            DumpValue::SupReference(offset) => write!(f, "<sup .debug_info+0x{:x}>", offset),
+            DumpValue::TypeSignature(signature) => write!(f, "signature 0x{:016x}", signature.0),
+            DumpValue::SectionOffset {
+                section: Some(section),
+                offset,
+            } => write!(f, "<{}+0x{:x}>", section.name(), offset),
+            DumpValue::SectionOffset {
+                section: None,
+                offset,
+            } => write!(f, "0x{:x}", offset),
+            DumpValue::LocationList(locations) => {
+                for (i, location) in locations.iter().enumerate() {
+                    if i != 0 {
+                        f.write_str("; ")?;
+                    }
+                    write!(
+                        f,
+                        "[0x{:x}, 0x{:x}): ",
+                        location.range.begin, location.range.end
+                    )?;
+                    hex(f, &location.expression)?;
+                }
+                Ok(())
+            }
+            DumpValue::RangeList(ranges) => {
+                for (i, range) in ranges.iter().enumerate() {
+                    if i != 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "[0x{:x}, 0x{:x})", range.begin, range.end)?;
+                }
+                Ok(())
+            }
+            DumpValue::File(None) => f.write_str("<invalid file index>"),
+            DumpValue::DwoId(dwo_id) => write!(f, "0x{:016x}", dwo_id.0),
+        }
+    }
+}
+
+/// An entry in a location list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct LocationDump {
+    /// The address range of the location.
+    pub range: Range,
+    /// The bytes of the DWARF expression for the location.
+    pub expression: Vec<u8>,
+}
+
+/// A structured representation of a line number program.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct LineProgramDump {
+    /// The offset of the line number program within the `.debug_line` section.
+    pub offset: u64,
+    /// The files defined by the line number program, including any files
+    /// that are defined by `DW_LNE_define_file` instructions.
+    pub files: Vec<FileDump>,
+    /// The rows of the line number matrix.
+    pub rows: Vec<LineRow>,
+}
+
+impl LineProgramDump {
+    fn new<R: Reader>(
+        dwarf: &Dwarf<R>,
+        unit: &Unit<R>,
+        program: IncompleteLineProgram<R>,
+    ) -> Result<Self> {
+        let offset = program.header().offset().0.into_u64();
+        let mut rows = Vec::new();
+        let mut program_rows = program.rows();
+        while let Some((_, row)) = program_rows.next_row()? {
+            rows.push(*row);
+        }
+
+        let header = program_rows.header();
+        // Before DWARF 5, file indices start at 1.
+        let base = if header.version() >= 5 { 0 } else { 1 };
+        let mut files = Vec::new();
+        for index in 0..header.file_names().len() as u64 {
+            let index = index + base;
+            files.push(FileDump {
+                index,
+                path: file_path(dwarf, unit, header, index)?.unwrap_or_default(),
+            });
+        }
+        Ok(LineProgramDump {
+            offset,
+            files,
+            rows,
+        })
+    }
+}
+
+/// A file defined by a line number program.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct FileDump {
+    /// The index that is used to refer to this file.
+    pub index: u64,
+    /// The path of the file, including its directory and the compilation
+    /// directory if the path is relative.
+    pub path: String,
+}
+
+/// A structured representation of the entries in a `.debug_frame` or
+/// `.eh_frame` section.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct FrameDump {
+    /// The CIEs and FDEs in the order they occur in the section.
+    pub entries: Vec<FrameEntryDump>,
+}
+
+impl FrameDump {
+    /// Parse all of the entries in the given section.
+    pub fn new<R, Section>(section: &Section, bases: &BaseAddresses) -> Result<Self>
+    where
+        R: Reader,
+        Section: UnwindSection<R>,
+    {
+        let mut entries = Vec::new();
+        let mut iter = section.entries(bases);
+        while let Some(entry) = iter.next()? {
+            match entry {
+                CieOrFde::Cie(cie) => {
+                    let mut instructions = Vec::new();
+                    let mut iter = cie.instructions(section, bases);
+                    while let Some(instruction) = iter.next()? {
+                        instructions.push(instruction_u64(instruction));
+                    }
+                    entries.push(FrameEntryDump::Cie(CieDump {
+                        offset: cie.offset().into_u64(),
+                        version: cie.version(),
+                        augmentation: cie.augmentation().cloned(),
+                        address_size: cie.address_size(),
+                        code_alignment_factor: cie.code_alignment_factor(),
+                        data_alignment_factor: cie.data_alignment_factor(),
+                        return_address_register: cie.return_address_register(),
+                        instructions,
+                    }));
+                }
+                CieOrFde::Fde(partial) => {
+                    let fde = partial.parse(Section::cie_from_offset)?;
+                    let mut instructions = Vec::new();
+                    let mut iter = fde.instructions(section, bases);
+                    while let Some(instruction) = iter.next()? {
+                        instructions.push(instruction_u64(instruction));
+                    }
+                    entries.push(FrameEntryDump::Fde(FdeDump {
+                        offset: fde.offset().into_u64(),
+                        cie_offset: fde.cie().offset().into_u64(),
+                        initial_address: fde.initial_address(),
+                        length: fde.len(),
+                        lsda: fde.lsda(),
+                        instructions,
+                    }));
+                }
+            }
+        }
+        Ok(FrameDump { entries })
+    }
+}
+
+/// A structured representation of an entry in a `.debug_frame` or
+/// `.eh_frame` section.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum FrameEntryDump {
+    /// A common information entry.
+    Cie(CieDump),
+    /// A frame description entry.
+    Fde(FdeDump),
+}
+
+/// A structured representation of a common information entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct CieDump {
+    /// The offset of the CIE within its section.
+    pub offset: u64,
+    /// The version of the CIE.
+    pub version: u8,
+    /// The parsed augmentation, if any.
+    pub augmentation: Option<Augmentation>,
+    /// The size of a target address.
+    pub address_size: u8,
+    /// The code alignment factor.
+    pub code_alignment_factor: u64,
+    /// The data alignment factor.
+    pub data_alignment_factor: i64,
+    /// The register that holds the return address.
+    pub return_address_register: Register,
+    /// The initial instructions.
+    ///
+    /// The offsets of any expressions are relative to the start of the section.
+    pub instructions: Vec<CallFrameInstruction<u64>>,
+}
+
+/// A structured representation of a frame description entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct FdeDump {
+    /// The offset of the FDE within its section.
+    pub offset: u64,
+    /// The offset of the FDE's CIE within the section.
+    pub cie_offset: u64,
+    /// The first address covered by the FDE.
+    pub initial_address: u64,
+    /// The number of bytes of instructions covered by the FDE.
+    pub length: u64,
+    /// The address of the FDE's language-specific data area, if any.
+    pub lsda: Option<Pointer>,
+    /// The instructions.
+    ///
+    /// The offsets of any expressions are relative to the start of the section.
+    pub instructions: Vec<CallFrameInstruction<u64>>,
+}
+
+fn section_offset<T: ReaderOffset>(offset: UnitSectionOffset<T>) -> (SectionId, u64) {
+    match offset {
+        UnitSectionOffset::DebugInfoOffset(o) => (SectionId::DebugInfo, o.0.into_u64()),
+        UnitSectionOffset::DebugTypesOffset(o) => (SectionId::DebugTypes, o.0.into_u64()),
+    }
+}
+
+fn section_value(section: SectionId, offset: u64) -> DumpValue {
+    DumpValue::SectionOffset {
+        section: Some(section),
+        offset,
+    }
+}
+
+fn instruction_u64<T: ReaderOffset>(
+    instruction: CallFrameInstruction<T>,
+) -> CallFrameInstruction<u64> {
+    fn expression<T: ReaderOffset>(expression: UnwindExpression<T>) -> UnwindExpression<u64> {
+        UnwindExpression {
+            offset: expression.offset.into_u64(),
+            length: expression.length.into_u64(),
+        }
+    }
+    match instruction {
+        CallFrameInstruction::SetLoc { address } => CallFrameInstruction::SetLoc { address },
+        CallFrameInstruction::AdvanceLoc { delta } => CallFrameInstruction::AdvanceLoc { delta },
+        CallFrameInstruction::DefCfa { register, offset } => {
+            CallFrameInstruction::DefCfa { register, offset }
+        }
+        CallFrameInstruction::DefCfaSf {
+            register,
+            factored_offset,
+        } => CallFrameInstruction::DefCfaSf {
+            register,
+            factored_offset,
+        },
+        CallFrameInstruction::DefCfaRegister { register } => {
+            CallFrameInstruction::DefCfaRegister { register }
+        }
+        CallFrameInstruction::DefCfaOffset { offset } => {
+            CallFrameInstruction::DefCfaOffset { offset }
+        }
+        CallFrameInstruction::DefCfaOffsetSf { factored_offset } => {
+            CallFrameInstruction::DefCfaOffsetSf { factored_offset }
+        }
+        CallFrameInstruction::DefCfaExpression { expression: e } => {
+            CallFrameInstruction::DefCfaExpression {
+                expression: expression(e),
+            }
+        }
+        CallFrameInstruction::Undefined { register } => {
+            CallFrameInstruction::Undefined { register }
+        }
+        CallFrameInstruction::SameValue { register } => {
+            CallFrameInstruction::SameValue { register }
+        }
+        CallFrameInstruction::Offset {
+            register,
+            factored_offset,
+        } => CallFrameInstruction::Offset {
+            register,
+            factored_offset,
+        },
+        CallFrameInstruction::OffsetExtendedSf {
+            register,
+            factored_offset,
+        } => CallFrameInstruction::OffsetExtendedSf {
+            register,
+            factored_offset,
+        },
+        CallFrameInstruction::ValOffset {
+            register,
+            factored_offset,
+        } => CallFrameInstruction::ValOffset {
+            register,
+            factored_offset,
+        },
+        CallFrameInstruction::ValOffsetSf {
+            register,
+            factored_offset,
+        } => CallFrameInstruction::ValOffsetSf {
+            register,
+            factored_offset,
+        },
+        CallFrameInstruction::Register {
+            dest_register,
+            src_register,
+        } => CallFrameInstruction::Register {
+            dest_register,
+            src_register,
+        },
+        CallFrameInstruction::Expression {
+            register,
+            expression: e,
+        } => CallFrameInstruction::Expression {
+            register,
+            expression: expression(e),
+        },
+        CallFrameInstruction::ValExpression {
+            register,
+            expression: e,
+        } => CallFrameInstruction::ValExpression {
+            register,
+            expression: expression(e),
+        },
+        CallFrameInstruction::Restore { register } => CallFrameInstruction::Restore { register },
+        CallFrameInstruction::RememberState => CallFrameInstruction::RememberState,
+        CallFrameInstruction::RestoreState => CallFrameInstruction::RestoreState,
+        CallFrameInstruction::ArgsSize { size } => CallFrameInstruction::ArgsSize { size },
+        CallFrameInstruction::NegateRaState => CallFrameInstruction::NegateRaState,
+        CallFrameInstruction::Nop => CallFrameInstruction::Nop,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::Format;
+    use crate::constants::*;
+    use crate::read::abbrev::tests::AbbrevSectionMethods;
+    use crate::test_util::{compile_unit, test_dwarf, GimliSectionMethods};
+    use alloc::vec;
+    use test_assembler::{Endian, Section};
+
+    #[test]
+    fn test_dwarf_dump() {
+        #[rustfmt::skip]
+        let abbrev = Section::with_endian(Endian::Little)
+            .abbrev(1, DW_TAG_compile_unit, DW_CHILDREN_yes)
+                .abbrev_attr(DW_AT_name, DW_FORM_string)
+                .abbrev_attr(DW_AT_language, DW_FORM_data2)
+                .abbrev_attr_null()
+            .abbrev(2, DW_TAG_base_type, DW_CHILDREN_no)
+                .abbrev_attr(DW_AT_encoding, DW_FORM_data1)
+                .abbrev_attr(DW_AT_byte_size, DW_FORM_data1)
+                .abbrev_attr_null()
+            .abbrev(3, DW_TAG_variable, DW_CHILDREN_no)
+                .abbrev_attr(DW_AT_type, DW_FORM_ref4)
+                .abbrev_attr(DW_AT_low_pc, DW_FORM_addr)
+                .abbrev_attr_null()
+            .abbrev_null();
+        let abbrev = abbrev.get_contents().unwrap();
+
+        #[rustfmt::skip]
+        let entries = Section::with_endian(Endian::Little)
+            .uleb(1).append_bytes(b"test.c\0").L16(DW_LANG_C99.0)
+                .uleb(2).D8(DW_ATE_signed.0).D8(4)
+                .uleb(3).L32(0x15).L64(0x1000)
+                .D8(0);
+        let info = compile_unit(entries);
+
+        let dwarf = test_dwarf(&abbrev, &info);
+        let dump = DwarfDump::new(&dwarf).unwrap();
+        assert_eq!(
+            dump,
+            DwarfDump {
+                units: vec![UnitDump {
+                    section: SectionId::DebugInfo,
+                    offset: 0,
+                    unit_type: DW_UT_compile,
+                    encoding: Encoding {
+                        format: Format::Dwarf32,
+                        version: 4,
+                        address_size: 8,
+                    },
+                    dwo_id: None,
+                    root: EntryDump {
+                        offset: 0xb,
+                        tag: DW_TAG_compile_unit,
+                        attributes: vec![
+                            AttributeDump {
+                                name: DW_AT_name,
+                                value: DumpValue::String("test.c".into()),
+                            },
+                            AttributeDump {
+                                name: DW_AT_language,
+                                value: DumpValue::Constant("DW_LANG_C99".into()),
+                            },
+                        ],
+                        children: vec![
+                            EntryDump {
+                                offset: 0x15,
+                                tag: DW_TAG_base_type,
+                                attributes: vec![
+                                    AttributeDump {
+                                        name: DW_AT_encoding,
+                                        value: DumpValue::Constant("DW_ATE_signed".into()),
+                                    },
+                                    AttributeDump {
+                                        name: DW_AT_byte_size,
+                                        value: DumpValue::Unsigned(4),
+                                    },
+                                ],
+                                children: vec![],
+                            },
+                            EntryDump {
+                                offset: 0x18,
+                                tag: DW_TAG_variable,
+                                attributes: vec![
+                                    AttributeDump {
+                                        name: DW_AT_type,
+                                        value: DumpValue::Reference {
+                                            section: SectionId::DebugInfo,
+                                            offset: 0x15,
+                                        },
+                                    },
+                                    AttributeDump {
+                                        name: DW_AT_low_pc,
+                                        value: DumpValue::Address(0x1000),
+                                    },
+                                ],
+                                children: vec![],
+                            },
+                        ],
+                    },
+                    line_program: None,
+                }],
+            }
+        );
+
+        #[cfg(feature = "serde")]
+        {
+            let json = serde_json::to_string(&dump.units[0].root.attributes[0]).unwrap();
+            assert_eq!(json, r#"{"name":3,"value":{"String":"test.c"}}"#);
+        }
+    }
+
+    #[test]
+    fn test_dump_nested() {
+        const DEPTH: usize = 1000;
+
+        #[rustfmt::skip]
+        let abbrev = Section::with_endian(Endian::Little)
+            .abbrev(1, DW_TAG_compile_unit, DW_CHILDREN_yes)
+                .abbrev_attr_null()
+            .abbrev(2, DW_TAG_lexical_block, DW_CHILDREN_yes)
+                .abbrev_attr_null()
+            .abbrev_null();
+        let abbrev = abbrev.get_contents().unwrap();
+
+        let mut entries = Section::with_endian(Endian::Little).uleb(1);
+        for _ in 0..DEPTH {
+            entries = entries.uleb(2);
+        }
+        for _ in 0..=DEPTH {
+            entries = entries.D8(0);
+        }
+        let info = compile_unit(entries);
+
+        let mut dwarf = test_dwarf(&abbrev, &info);
+        let dump = DwarfDump::new(&dwarf).unwrap();
+        let mut entry = &dump.units[0].root;
+        let mut depth = 0;
+        while let Some(child) = entry.children.first() {
+            assert_eq!(entry.children.len(), 1);
+            assert_eq!(child.tag, DW_TAG_lexical_block);
+            entry = child;
+            depth += 1;
+        }
+        assert_eq!(depth, DEPTH);
+
+        dwarf.limits.max_die_depth = Some(DEPTH - 1);
+        assert_eq!(DwarfDump::new(&dwarf), Err(Error::EntryTooDeep));
+    }
+}
@@ -1,5 +1,8 @@
+use alloc::collections::{BTreeMap, BTreeSet};
 use alloc::string::String;
 use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::fmt;
 
 use crate::common::{
     DebugAddrBase, DebugAddrIndex, DebugInfoOffset, DebugLineStrOffset, DebugLocListsBase,
@@ -8,14 +11,21 @@ use crate::common::{
     LocationListsOffset, RangeListsOffset, RawRangeListsOffset, SectionId, UnitSectionOffset,
 };
 use crate::constants;
+#[cfg(all(feature = "endian-reader", feature = "std"))]
+use crate::endianity::Endianity;
+use crate::read::lazy::LazyArc;
+#[cfg(all(feature = "endian-reader", feature = "std"))]
+use crate::read::EndianArcSlice;
 use crate::read::{
-    Abbreviations, AbbreviationsCache, AbbreviationsCacheStrategy, AttributeValue, DebugAbbrev,
-    DebugAddr, DebugAranges, DebugCuIndex, DebugInfo, DebugInfoUnitHeadersIter, DebugLine,
-    DebugLineStr, DebugLoc, DebugLocLists, DebugRanges, DebugRngLists, DebugStr, DebugStrOffsets,
-    DebugTuIndex, DebugTypes, DebugTypesUnitHeadersIter, DebuggingInformationEntry, EntriesCursor,
-    EntriesRaw, EntriesTree, Error, IncompleteLineProgram, LocListIter, LocationLists, Range,
-    RangeLists, RawLocListIter, RawRngListIter, Reader, ReaderOffset, ReaderOffsetId, Result,
-    RngListIter, Section, UnitHeader, UnitIndex, UnitIndexSectionIterator, UnitOffset, UnitType,
+    Abbreviations, AbbreviationsCache, AbbreviationsCacheStrategy, AttributeValue, ContextError,
+    DebugAbbrev, DebugAddr, DebugAranges, DebugCuIndex, DebugInfo, DebugInfoUnitHeadersIter,
+    DebugLine, DebugLineStr, DebugLoc, DebugLocLists, DebugRanges, DebugRngLists, DebugStr,
+    DebugStrOffsets, DebugTuIndex, DebugTypes, DebugTypesUnitHeadersIter,
+    DebuggingInformationEntry, EntriesCursor, EntriesRaw, EntriesTree, Error, ErrorOperation,
+    Evaluation, Expression, IncompleteLineProgram, LocListIter, LocationLists, Range, RangeLists,
+    RawLocListIter, RawRngListIter, Reader, ReaderOffset, ReaderOffsetId, Result, ResultIterator,
+    RngListIter, Section, TombstoneFilter, UnitHeader, UnitIndex, UnitIndexSectionIterator,
+    UnitOffset, UnitType, WarningSink,
 };
 
 /// All of the commonly used DWARF sections.
@@ -102,6 +112,41 @@ impl<T> DwarfSections<T> {
         })
     }
 
+    /// Load the DWARF sections using the given loader function, continuing
+    /// after errors.
+    ///
+    /// This is like `load`, except that a section that fails to load is
+    /// replaced with an empty section, and the error is passed to `errors`.
+    /// This allows a missing or corrupt optional section to be ignored.
+    pub fn load_lenient<F, E, W>(mut section: F, mut errors: W) -> Self
+    where
+        T: Default,
+        F: FnMut(SectionId) -> core::result::Result<T, E>,
+        W: FnMut(SectionLoadError<E>),
+    {
+        let mut section = |id| {
+            section(id).unwrap_or_else(|error| {
+                errors(SectionLoadError { section: id, error });
+                T::default()
+            })
+        };
+        DwarfSections {
+            debug_abbrev: DebugAbbrev::from(section(SectionId::DebugAbbrev)),
+            debug_addr: DebugAddr::from(section(SectionId::DebugAddr)),
+            debug_aranges: DebugAranges::from(section(SectionId::DebugAranges)),
+            debug_info: DebugInfo::from(section(SectionId::DebugInfo)),
+            debug_line: DebugLine::from(section(SectionId::DebugLine)),
+            debug_line_str: DebugLineStr::from(section(SectionId::DebugLineStr)),
+            debug_str: DebugStr::from(section(SectionId::DebugStr)),
+            debug_str_offsets: DebugStrOffsets::from(section(SectionId::DebugStrOffsets)),
+            debug_types: DebugTypes::from(section(SectionId::DebugTypes)),
+            debug_loc: DebugLoc::from(section(SectionId::DebugLoc)),
+            debug_loclists: DebugLocLists::from(section(SectionId::DebugLocLists)),
+            debug_ranges: DebugRanges::from(section(SectionId::DebugRanges)),
+            debug_rnglists: DebugRngLists::from(section(SectionId::DebugRngLists)),
+        }
+    }
+
     /// Create a `Dwarf` structure that references the data in `self`.
     pub fn borrow<'a, F, R>(&'a self, mut borrow: F) -> Dwarf<R>
     where
@@ -155,6 +200,68 @@ impl<T> DwarfSections<T> {
     }
 }
 
+/// An error that occurred while loading a DWARF section.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SectionLoadError<E> {
+    /// The section that failed to load.
+    pub section: SectionId,
+    /// The error returned by the loader.
+    pub error: E,
+}
+
+impl<E: fmt::Display> fmt::Display for SectionLoadError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.section.name(), self.error)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<E: fmt::Debug + fmt::Display> std::error::Error for SectionLoadError<E> {}
+
+/// Limits on the resources used when parsing DWARF from untrusted input.
+///
+/// Each limit is `None` by default, which means there is no limit.
+/// Exceeding a limit fails with a specific error instead of using unbounded
+/// memory or stack in the consumer.
+///
+/// ```
+/// # fn example<R: gimli::Reader>(dwarf: &mut gimli::Dwarf<R>) {
+/// dwarf.limits = gimli::Limits {
+///     max_die_depth: Some(256),
+///     max_abbreviations: Some(65536),
+///     ..Default::default()
+/// };
+/// # }
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Limits {
+    /// The maximum depth of the DIE tree in a unit, where the root entry is at depth 0.
+    ///
+    /// This applies to the cursors and trees returned by `Unit::entries`,
+    /// `Unit::entries_at_offset`, `Unit::entries_tree` and `Unit::entries_raw`,
+    /// which fail with `Error::EntryTooDeep`.
+    pub max_die_depth: Option<usize>,
+
+    /// The maximum number of abbreviations in a single abbreviations table.
+    ///
+    /// This applies to `Dwarf::abbreviations`, which fails with
+    /// `Error::TooManyAbbreviations`.
+    pub max_abbreviations: Option<usize>,
+
+    /// The maximum nesting depth of expressions for `DW_OP_call*` operations.
+    ///
+    /// This applies to the evaluations returned by `Dwarf::evaluation`, which
+    /// fail with `Error::ExpressionTooDeep`.
+    pub max_expression_depth: Option<usize>,
+
+    /// The maximum length in bytes of a line number program, including its header.
+    ///
+    /// This applies to the line program of units returned by `Dwarf::unit`,
+    /// which fail with `Error::LineProgramTooLong`.
+    pub max_line_program_length: Option<u64>,
+}
+
+/// Return `debug_abbrev` with the abbreviation limit from `limits` applied.
 /// All of the commonly used DWARF sections, and other common information.
 #[derive(Debug, Default)]
 pub struct Dwarf<R> {
@@ -199,6 +306,16 @@ pub struct Dwarf<R> {
 
     /// A cache of previously parsed abbreviations for units in this file.
     pub abbreviations_cache: AbbreviationsCache,
+
+    /// An index of the unit headers in this file, used by `Dwarf::entry_at`.
+    pub unit_header_index: UnitHeaderIndex,
+
+    /// Additional tombstone addresses to ignore in range lists, location lists
+    /// and line programs.
+    pub tombstone_filter: TombstoneFilter,
+
+    /// Limits on the resources used when parsing.
+    pub limits: Limits,
 }
 
 impl<T> Dwarf<T> {
@@ -217,6 +334,19 @@ impl<T> Dwarf<T> {
         Ok(Self::from_sections(sections))
     }
 
+    /// Load the DWARF sections using the given loader function, continuing
+    /// after errors.
+    ///
+    /// See `DwarfSections::load_lenient` for more information.
+    pub fn load_lenient<F, E, W>(section: F, errors: W) -> Self
+    where
+        T: Default,
+        F: FnMut(SectionId) -> core::result::Result<T, E>,
+        W: FnMut(SectionLoadError<E>),
+    {
+        Self::from_sections(DwarfSections::load_lenient(section, errors))
+    }
+
     /// Load the DWARF sections from the supplementary object file.
     ///
     /// `section` operates the same as for `load`.
@@ -249,6 +379,9 @@ impl<T> Dwarf<T> {
             file_type: DwarfFileType::Main,
             sup: None,
             abbreviations_cache: AbbreviationsCache::new(),
+            unit_header_index: UnitHeaderIndex::new(),
+            tombstone_filter: TombstoneFilter::none(),
+            limits: Limits::default(),
         }
     }
 
@@ -299,6 +432,9 @@ impl<T> Dwarf<T> {
             file_type: self.file_type,
             sup: self.sup().map(|sup| Arc::new(sup.borrow(borrow))),
             abbreviations_cache: AbbreviationsCache::new(),
+            unit_header_index: UnitHeaderIndex::new(),
+            tombstone_filter: self.tombstone_filter,
+            limits: self.limits,
         }
     }
 
@@ -322,8 +458,13 @@ impl<R: Reader> Dwarf<R> {
     /// Errors during parsing abbreviations are also stored in the cache.
     /// Errors during iterating over the units are ignored.
     pub fn populate_abbreviations_cache(&mut self, strategy: AbbreviationsCacheStrategy) {
-        self.abbreviations_cache
-            .populate(strategy, &self.debug_abbrev, self.debug_info.units());
+        let max_abbreviations = self.max_abbreviations();
+        self.abbreviations_cache.populate_with_limit(
+            strategy,
+            &self.debug_abbrev,
+            self.debug_info.units(),
+            max_abbreviations,
+        );
     }
 
     /// Iterate the unit headers in the `.debug_info` section.
@@ -341,6 +482,18 @@ impl<R: Reader> Dwarf<R> {
         Unit::new(self, header)
     }
 
+    /// Construct a new `Unit` from the given unit header, adding the location
+    /// of the failure to any error.
+    ///
+    /// See `Unit::new_with_context` for more information.
+    #[inline]
+    pub fn unit_with_context(
+        &self,
+        header: UnitHeader<R>,
+    ) -> ::core::result::Result<Unit<R>, ContextError> {
+        Unit::new_with_context(self, header)
+    }
+
     /// Iterate the type-unit headers in the `.debug_types` section.
     ///
     /// Can be [used with
@@ -351,10 +504,38 @@ impl<R: Reader> Dwarf<R> {
     }
 
     /// Parse the abbreviations for a compilation unit.
+    ///
+    /// This uses `self.abbreviations_cache`, and stores the parsed abbreviations
+    /// in it according to the cache policy.
     #[inline]
     pub fn abbreviations(&self, unit: &UnitHeader<R>) -> Result<Arc<Abbreviations>> {
-        self.abbreviations_cache
-            .get(&self.debug_abbrev, unit.debug_abbrev_offset())
+        self.abbreviations_cache.get_with_limit(
+            &self.debug_abbrev,
+            unit.debug_abbrev_offset(),
+            self.max_abbreviations(),
+        )
+    }
+
+    /// Return the maximum number of abbreviations in a single abbreviations table.
+    ///
+    /// This is `Limits::max_abbreviations` if set, and otherwise the limit set
+    /// for `self.debug_abbrev`.
+    pub(crate) fn max_abbreviations(&self) -> Option<usize> {
+        self.limits
+            .max_abbreviations
+            .or_else(|| self.debug_abbrev.max_abbreviations())
+    }
+
+    /// Create an evaluation for an expression in the given unit.
+    ///
+    /// This applies `self.limits` to the evaluation.
+    #[inline]
+    pub fn evaluation(&self, unit: &Unit<R>, expression: Expression<R>) -> Evaluation<R> {
+        let mut evaluation = expression.evaluation(unit.encoding());
+        if let Some(max_expression_depth) = self.limits.max_expression_depth {
+            evaluation.set_max_expression_depth(max_expression_depth);
+        }
+        evaluation
     }
 
     /// Return the string offset at the given index.
@@ -473,13 +654,15 @@ impl<R: Reader> Dwarf<R> {
         unit: &Unit<R>,
         offset: RangeListsOffset<R::Offset>,
     ) -> Result<RngListIter<R>> {
-        self.ranges.ranges(
+        let mut ranges = self.ranges.ranges(
             offset,
             unit.encoding(),
             unit.low_pc,
             &self.debug_addr,
             unit.addr_base,
-        )
+        )?;
+        ranges.set_tombstone_filter(self.tombstone_filter);
+        Ok(ranges)
     }
 
     /// Iterate over the `RawRngListEntry`ies starting at the given offset.
@@ -571,14 +754,171 @@ impl<R: Reader> Dwarf<R> {
                 _ => {}
             }
         }
-        let range = low_pc.and_then(|begin| {
-            let end = size.map(|size| begin + size).or(high_pc);
-            // TODO: perhaps return an error if `end` is `None`
-            end.map(|end| Range { begin, end })
-        });
+        let range = low_pc
+            .filter(|&begin| {
+                !self
+                    .tombstone_filter
+                    .is_tombstone(begin, unit.encoding().address_size)
+            })
+            .and_then(|begin| {
+                let end = size.map(|size| begin + size).or(high_pc);
+                // TODO: perhaps return an error if `end` is `None`
+                end.map(|end| Range { begin, end })
+            });
         Ok(RangeIter(RangeIterInner::Single(range)))
     }
 
+    /// Find the unit containing the given offset in the `.debug_info` or
+    /// `.debug_types` section.
+    ///
+    /// This can be used to follow a `DW_FORM_ref_addr` reference, or an offset
+    /// obtained from an accelerator table.
+    ///
+    /// Returns the unit and the offset of the entry within the unit.
+    /// The entry can be parsed with `Unit::entry`.
+    ///
+    /// An index of the unit headers is built on the first call for each section,
+    /// and is reused for later calls. Units with headers that fail to parse are
+    /// left out of the index, so offsets within them are not found.
+    pub fn entry_at(
+        &self,
+        offset: UnitSectionOffset<R::Offset>,
+    ) -> Result<(Unit<R>, UnitOffset<R::Offset>)> {
+        let header = self.unit_header_at(offset)?;
+        let unit_offset = match offset {
+            UnitSectionOffset::DebugInfoOffset(o) => o.to_unit_offset(&header),
+            UnitSectionOffset::DebugTypesOffset(o) => o.to_unit_offset(&header),
+        }
+        .ok_or(Error::NoEntryAtGivenOffset)?;
+        let unit = self.unit(header)?;
+        Ok((unit, unit_offset))
+    }
+
+    /// Find the header of the unit containing the given offset in the `.debug_info`
+    /// or `.debug_types` section.
+    ///
+    /// See `Dwarf::entry_at` for more information.
+    pub fn unit_header_at(&self, offset: UnitSectionOffset<R::Offset>) -> Result<UnitHeader<R>> {
+        let header = match offset {
+            UnitSectionOffset::DebugInfoOffset(offset) => {
+                let starts = self.unit_header_index.debug_info.get(|| -> Result<_> {
+                    let mut starts = Vec::new();
+                    let mut units = self.units();
+                    while let Some(header) = units.next_lenient(&mut |_: ContextError| {}) {
+                        if let Some(offset) = header.offset().as_debug_info_offset() {
+                            starts.push(offset.0.into_u64());
+                        }
+                    }
+                    Ok(starts)
+                })?;
+                let start = UnitHeaderIndex::find(&starts, offset.0.into_u64())?;
+                self.debug_info
+                    .header_from_offset(DebugInfoOffset(R::Offset::from_u64(start)?))?
+            }
+            UnitSectionOffset::DebugTypesOffset(offset) => {
+                let starts = self.unit_header_index.debug_types.get(|| -> Result<_> {
+                    let mut starts = Vec::new();
+                    let mut units = self.type_units();
+                    while let Some(header) = units.next_lenient(&mut |_: ContextError| {}) {
+                        if let Some(offset) = header.offset().as_debug_types_offset() {
+                            starts.push(offset.0.into_u64());
+                        }
+                    }
+                    Ok(starts)
+                })?;
+                let start = UnitHeaderIndex::find(&starts, offset.0.into_u64())?;
+                self.debug_types
+                    .header_from_offset(DebugTypesOffset(R::Offset::from_u64(start)?))?
+            }
+        };
+        // Units that fail to parse are not in the index, so the offset may be
+        // past the end of the preceding unit.
+        let unit_offset = match offset {
+            UnitSectionOffset::DebugInfoOffset(o) => o.to_unit_offset(&header),
+            UnitSectionOffset::DebugTypesOffset(o) => o.to_unit_offset(&header),
+        };
+        if unit_offset.is_none() {
+            return Err(Error::NoEntryAtGivenOffset);
+        }
+        Ok(header)
+    }
+
+    /// Find the type unit with the given type signature.
+    ///
+    /// This can be used to follow a `DW_FORM_ref_sig8` reference. Type units are
+    /// searched for in both the `.debug_types` section (for DWARF 4) and the
+    /// `.debug_info` section (for DWARF 5).
+    ///
+    /// Returns the type unit and the offset of the type entry within the unit.
+    /// The entry can be parsed with `Unit::entry`. Returns
+    /// `Error::UnknownTypeSignature` if there is no type unit with the signature.
+    ///
+    /// A map of the type signatures is built on the first call, and is reused
+    /// for later calls. Units with headers that fail to parse are skipped.
+    ///
+    /// For DWARF packages, use `DwarfPackage::resolve_type_signature` instead,
+    /// which looks up the signature in the `.debug_tu_index` section.
+    pub fn resolve_type_signature(
+        &self,
+        signature: DebugTypeSignature,
+    ) -> Result<(Unit<R>, UnitOffset<R::Offset>)> {
+        let signatures = self
+            .unit_header_index
+            .type_signatures
+            .get(|| -> Result<_> {
+                let mut signatures = BTreeMap::new();
+                let mut add = |header: &UnitHeader<R>| {
+                    if let UnitType::Type {
+                        type_signature,
+                        type_offset,
+                    }
+                    | UnitType::SplitType {
+                        type_signature,
+                        type_offset,
+                    } = header.type_()
+                    {
+                        let offset = match header.offset() {
+                            UnitSectionOffset::DebugInfoOffset(o) => {
+                                UnitSectionOffset::DebugInfoOffset(DebugInfoOffset(o.0.into_u64()))
+                            }
+                            UnitSectionOffset::DebugTypesOffset(o) => {
+                                UnitSectionOffset::DebugTypesOffset(DebugTypesOffset(
+                                    o.0.into_u64(),
+                                ))
+                            }
+                        };
+                        // Keep the first unit if there are duplicates.
+                        signatures
+                            .entry(type_signature.0)
+                            .or_insert((offset, type_offset.0.into_u64()));
+                    }
+                };
+                let mut units = self.type_units();
+                while let Some(header) = units.next_lenient(&mut |_: ContextError| {}) {
+                    add(&header);
+                }
+                let mut units = self.units();
+                while let Some(header) = units.next_lenient(&mut |_: ContextError| {}) {
+                    add(&header);
+                }
+                Ok(signatures)
+            })?;
+        let (offset, type_offset) = match signatures.get(&signature.0) {
+            Some(value) => *value,
+            None => return Err(Error::UnknownTypeSignature),
+        };
+        let header = match offset {
+            UnitSectionOffset::DebugInfoOffset(o) => self
+                .debug_info
+                .header_from_offset(DebugInfoOffset(R::Offset::from_u64(o.0)?))?,
+            UnitSectionOffset::DebugTypesOffset(o) => self
+                .debug_types
+                .header_from_offset(DebugTypesOffset(R::Offset::from_u64(o.0)?))?,
+        };
+        let unit = self.unit(header)?;
+        Ok((unit, UnitOffset(R::Offset::from_u64(type_offset)?)))
+    }
+
     /// Return an iterator for the address ranges of a `Unit`.
     ///
     /// This uses `DW_AT_low_pc`, `DW_AT_high_pc` and `DW_AT_ranges` of the
@@ -606,22 +946,24 @@ impl<R: Reader> Dwarf<R> {
         unit: &Unit<R>,
         offset: LocationListsOffset<R::Offset>,
     ) -> Result<LocListIter<R>> {
-        match self.file_type {
+        let mut locations = match self.file_type {
             DwarfFileType::Main => self.locations.locations(
                 offset,
                 unit.encoding(),
                 unit.low_pc,
                 &self.debug_addr,
                 unit.addr_base,
-            ),
+            )?,
             DwarfFileType::Dwo => self.locations.locations_dwo(
                 offset,
                 unit.encoding(),
                 unit.low_pc,
                 &self.debug_addr,
                 unit.addr_base,
-            ),
-        }
+            )?,
+        };
+        locations.set_tombstone_filter(self.tombstone_filter);
+        Ok(locations)
     }
 
     /// Iterate over the raw `LocationListEntry`s starting at the given offset.
@@ -720,10 +1062,49 @@ impl<R: Reader> Dwarf<R> {
                 }
                 None => {}
             },
+            Error::MismatchedDwoId(skeleton, split) => {
+                return format!(
+                    "{} (skeleton 0x{:016x}, split 0x{:016x})",
+                    err, skeleton.0, split.0
+                );
+            }
             _ => {}
         }
         err.description().into()
     }
+
+    /// Returns a string representation of an error for a skeleton unit's split unit,
+    /// including the name of the DWO file.
+    ///
+    /// The error is formatted using `format_error`.
+    pub fn format_dwo_error(&self, skeleton: &Unit<R>, err: Error) -> String {
+        let dwo_name = skeleton
+            .dwo_name()
+            .ok()
+            .flatten()
+            .and_then(|dwo_name| self.attr_string(skeleton, dwo_name).ok());
+        match dwo_name {
+            Some(dwo_name) => match dwo_name.to_string_lossy() {
+                Ok(dwo_name) => format!("{}: {}", dwo_name, self.format_error(err)),
+                Err(_) => self.format_error(err),
+            },
+            None => self.format_error(err),
+        }
+    }
+
+    /// Returns a string representation of the given error, including its context.
+    ///
+    /// This is the same as the `Display` implementation of `ContextError`, except
+    /// that the underlying error is formatted using `format_error`.
+    pub fn format_context_error(&self, err: &ContextError) -> String {
+        struct Context<'a>(&'a ContextError);
+        impl<'a> fmt::Display for Context<'a> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                self.0.fmt_context(f)
+            }
+        }
+        format!("{}{}", Context(err), self.format_error(err.error()))
+    }
 }
 
 impl<R: Clone> Dwarf<R> {
@@ -739,6 +1120,120 @@ impl<R: Clone> Dwarf<R> {
         self.ranges
             .set_debug_ranges(parent.ranges.debug_ranges().clone());
         self.sup = parent.sup.clone();
+        self.abbreviations_cache.set_file_type(DwarfFileType::Dwo);
+        self.tombstone_filter = parent.tombstone_filter;
+        self.limits = parent.limits;
+    }
+}
+
+impl<R: Reader> Dwarf<R> {
+    /// Load the split unit for a skeleton unit.
+    ///
+    /// This uses `loader` to load the DWARF object (DWO) file named by the
+    /// skeleton unit's `DW_AT_dwo_name` or `DW_AT_GNU_dwo_name` attribute.
+    /// The loaded sections are merged with `self` using `Dwarf::make_dwo`,
+    /// and the split unit with a matching DWO ID is returned. Attributes
+    /// that are subject to relocation are copied from the skeleton unit.
+    ///
+    /// Returns `None` if `skeleton` is not a skeleton unit, or if the loader
+    /// could not find the DWO file. Returns `Error::MismatchedDwoId` if the DWO
+    /// file only contains split units with other DWO IDs, which usually means
+    /// that the DWO file is stale. Use `Dwarf::format_dwo_error` to include the
+    /// name of the DWO file when reporting errors.
+    pub fn split_unit(
+        &self,
+        skeleton: &Unit<R>,
+        loader: &mut dyn DwoLoader<R>,
+    ) -> Result<Option<SplitUnit<R>>> {
+        let dwo_id = match skeleton.dwo_id {
+            Some(dwo_id) => dwo_id,
+            None => return Ok(None),
+        };
+        let dwo_name = match skeleton.dwo_name()? {
+            Some(dwo_name) => self.attr_string(skeleton, dwo_name)?,
+            None => return Ok(None),
+        };
+        let mut dwarf = match loader.load_dwo(&dwo_name, skeleton.comp_dir.as_ref(), dwo_id)? {
+            Some(dwarf) => dwarf,
+            None => return Ok(None),
+        };
+        dwarf.make_dwo(self);
+
+        let mut split = None;
+        let mut other_dwo_id = None;
+        let mut units = dwarf.units();
+        while let Some(header) = units.next()? {
+            let unit = dwarf.unit(header)?;
+            if unit.dwo_id == Some(dwo_id) {
+                split = Some(unit);
+                break;
+            }
+            other_dwo_id = other_dwo_id.or(unit.dwo_id);
+        }
+        let mut unit = match (split, other_dwo_id) {
+            (Some(unit), _) => unit,
+            (None, Some(other_dwo_id)) => {
+                return Err(Error::MismatchedDwoId(dwo_id, other_dwo_id));
+            }
+            (None, None) => return Err(Error::MissingSplitUnit),
+        };
+        unit.copy_relocated_attributes(skeleton);
+        Ok(Some(SplitUnit { dwarf, unit }))
+    }
+}
+
+/// A loader for DWARF object (DWO) files.
+///
+/// This is used by `Dwarf::split_unit` to load the sections for a skeleton unit.
+/// It is implemented for closures with a matching signature.
+pub trait DwoLoader<R: Reader> {
+    /// Load the DWARF sections from a DWO file.
+    ///
+    /// `dwo_name` is the name of the DWO file, which may be relative to `comp_dir`.
+    /// `dwo_id` is the DWO ID of the skeleton unit, which can be used to find the
+    /// unit in a DWARF package instead.
+    ///
+    /// Returns `None` if the DWO file could not be found.
+    fn load_dwo(
+        &mut self,
+        dwo_name: &R,
+        comp_dir: Option<&R>,
+        dwo_id: DwoId,
+    ) -> Result<Option<Dwarf<R>>>;
+}
+
+impl<R, F> DwoLoader<R> for F
+where
+    R: Reader,
+    F: FnMut(&R, Option<&R>, DwoId) -> Result<Option<Dwarf<R>>>,
+{
+    fn load_dwo(
+        &mut self,
+        dwo_name: &R,
+        comp_dir: Option<&R>,
+        dwo_id: DwoId,
+    ) -> Result<Option<Dwarf<R>>> {
+        self(dwo_name, comp_dir, dwo_id)
+    }
+}
+
+/// A split unit that was loaded for a skeleton unit, together with the DWARF
+/// sections of its DWO file.
+///
+/// This is returned by `Dwarf::split_unit`.
+#[derive(Debug)]
+pub struct SplitUnit<R: Reader> {
+    /// The DWARF sections of the DWO file, merged with the parent file.
+    pub dwarf: Dwarf<R>,
+
+    /// The split unit.
+    pub unit: Unit<R>,
+}
+
+impl<R: Reader> SplitUnit<R> {
+    /// Return a reference to the split unit and its DWARF sections.
+    pub fn unit_ref(&self) -> UnitRef<'_, R> {
+        UnitRef::new(&self.dwarf, &self.unit)
     }
 }
 
@@ -851,6 +1346,63 @@ impl<T> DwarfPackageSections<T> {
     }
 }
 
+#[cfg(all(feature = "endian-reader", feature = "std"))]
+impl<Endian: Endianity> Dwarf<EndianArcSlice<Endian>> {
+    /// Try to load the DWARF sections into shared, owned readers.
+    ///
+    /// `section` operates the same as for `load`, except that it may return
+    /// anything that can be converted into an `Arc<[u8]>`, such as a `Vec<u8>`
+    /// or a `Cow<[u8]>`.
+    ///
+    /// The resulting `Dwarf` does not borrow from the object file, and is
+    /// `Send + Sync`, so it can be stored in an `Arc` and shared between threads.
+    ///
+    /// ```rust,no_run
+    /// # fn example(object: &std::collections::HashMap<&str, Vec<u8>>) {
+    /// use std::sync::Arc;
+    ///
+    /// let dwarf = gimli::Dwarf::load_owned(
+    ///     |id| -> Result<_, ()> { Ok(object.get(id.name()).cloned().unwrap_or_default()) },
+    ///     gimli::LittleEndian,
+    /// )
+    /// .unwrap();
+    /// let dwarf = Arc::new(dwarf);
+    /// let thread_dwarf = dwarf.clone();
+    /// std::thread::spawn(move || {
+    ///     let mut units = thread_dwarf.units();
+    ///     while let Ok(Some(header)) = units.next() {
+    ///         // ...
+    ///     }
+    /// });
+    /// # }
+    /// ```
+    pub fn load_owned<F, B, E>(mut section: F, endian: Endian) -> core::result::Result<Self, E>
+    where
+        F: FnMut(SectionId) -> core::result::Result<B, E>,
+        B: Into<Arc<[u8]>>,
+    {
+        Self::load(|id| section(id).map(|data| EndianArcSlice::from_bytes(data, endian)))
+    }
+
+    /// Load the DWARF sections from the supplementary object file into shared,
+    /// owned readers.
+    ///
+    /// `section` operates the same as for `load_owned`.
+    ///
+    /// Sets `self.sup`, replacing any previous value.
+    pub fn load_sup_owned<F, B, E>(
+        &mut self,
+        mut section: F,
+        endian: Endian,
+    ) -> core::result::Result<(), E>
+    where
+        F: FnMut(SectionId) -> core::result::Result<B, E>,
+        B: Into<Arc<[u8]>>,
+    {
+        self.load_sup(|id| section(id).map(|data| EndianArcSlice::from_bytes(data, endian)))
+    }
+}
+
 /// The sections from a `.dwp` file, with parsed indices.
 #[derive(Debug)]
 pub struct DwarfPackage<R: Reader> {
@@ -895,6 +1447,12 @@ pub struct DwarfPackage<R: Reader> {
     ///
     /// Used when creating `Dwarf<R>`.
     pub empty: R,
+
+    /// A cache of previously parsed abbreviations for units in this package.
+    ///
+    /// The entries are keyed by their offset in the `.debug_abbrev.dwo` section,
+    /// and are shared with the `Dwarf` returned for each unit.
+    pub abbreviations_cache: AbbreviationsCache,
 }
 
 impl<R: Reader> DwarfPackage<R> {
@@ -926,12 +1484,50 @@ impl<R: Reader> DwarfPackage<R> {
             debug_rnglists: sections.debug_rnglists,
             debug_types: sections.debug_types,
             empty,
+            abbreviations_cache: {
+                let mut cache = AbbreviationsCache::new();
+                cache.set_file_type(DwarfFileType::Dwo);
+                cache
+            },
         })
     }
 
+    /// Parse abbreviations and store them in the cache.
+    ///
+    /// This will iterate over the rows in the unit indices to determine the
+    /// abbreviations offsets. Units that share a `.debug_abbrev.dwo` contribution
+    /// will then share the parsed abbreviations.
+    ///
+    /// Errors during parsing abbreviations are also stored in the cache.
+    /// Errors during iterating over the unit indices are ignored.
+    pub fn populate_abbreviations_cache(&mut self, strategy: AbbreviationsCacheStrategy) {
+        let mut offsets = Vec::new();
+        for index in [&self.cu_index, &self.tu_index] {
+            for row in 1..=index.unit_count() {
+                if let Ok(sections) = index.sections(row) {
+                    for section in sections {
+                        if section.section == SectionId::DebugAbbrev {
+                            offsets.push(u64::from(section.offset));
+                        }
+                    }
+                }
+            }
+        }
+        self.abbreviations_cache.populate_offsets(
+            strategy,
+            &self.debug_abbrev,
+            offsets,
+            self.debug_abbrev.max_abbreviations(),
+        );
+    }
+
     /// Find the compilation unit with the given DWO identifier and return its section
     /// contributions.
     ///
+    /// Returns `Error::MismatchedDwoId` if the compilation unit in the contributions
+    /// has a different DWO ID, which means that the package index is inconsistent
+    /// with the units that it refers to.
+    ///
     /// ## Example Usage
     ///
     /// ```rust,no_run
@@ -952,7 +1548,12 @@ impl<R: Reader> DwarfPackage<R> {
             Some(row) => row,
             None => return Ok(None),
         };
-        self.cu_sections(row, parent).map(Some)
+        let dwo = self.cu_sections(row, parent)?;
+        if let Some(header) = dwo.units().next()? {
+            let unit = dwo.unit(header)?;
+            check_dwo_id(Some(id), unit.dwo_id)?;
+        }
+        Ok(Some(dwo))
     }
 
     /// Find the type unit with the given type signature and return its section
@@ -969,6 +1570,29 @@ impl<R: Reader> DwarfPackage<R> {
         self.tu_sections(row, parent).map(Some)
     }
 
+    /// Find the type unit with the given type signature.
+    ///
+    /// This is the same as `Dwarf::resolve_type_signature`, except that the
+    /// signature is looked up in the `.debug_tu_index` section. Since the unit's
+    /// attributes refer to its own section contributions, this also returns the
+    /// `Dwarf` for the contributions, which is the same as `DwarfPackage::find_tu`.
+    ///
+    /// Returns `Error::UnknownTypeSignature` if there is no type unit with the
+    /// signature.
+    #[allow(clippy::type_complexity)]
+    pub fn resolve_type_signature(
+        &self,
+        signature: DebugTypeSignature,
+        parent: &Dwarf<R>,
+    ) -> Result<(Dwarf<R>, Unit<R>, UnitOffset<R::Offset>)> {
+        let dwo = match self.find_tu(signature, parent)? {
+            Some(dwo) => dwo,
+            None => return Err(Error::UnknownTypeSignature),
+        };
+        let (unit, offset) = dwo.resolve_type_signature(signature)?;
+        Ok((dwo, unit, offset))
+    }
+
     /// Return the section contributions of the compilation unit at the given index.
     ///
     /// The index must be in the range `1..cu_index.unit_count`.
@@ -1075,6 +1699,14 @@ impl<R: Reader> DwarfPackage<R> {
         let debug_aranges = self.empty.clone().into();
         let debug_line_str = self.empty.clone().into();
 
+        let mut abbreviations_cache = AbbreviationsCache::new();
+        abbreviations_cache.set_file_type(DwarfFileType::Dwo);
+        abbreviations_cache.copy_range(
+            &self.abbreviations_cache,
+            u64::from(abbrev_offset),
+            u64::from(abbrev_size),
+        );
+
         Ok(Dwarf {
             debug_abbrev,
             debug_addr,
@@ -1089,11 +1721,60 @@ impl<R: Reader> DwarfPackage<R> {
             ranges: RangeLists::new(debug_ranges, debug_rnglists),
             file_type: DwarfFileType::Dwo,
             sup: parent.sup.clone(),
-            abbreviations_cache: AbbreviationsCache::new(),
+            abbreviations_cache,
+            unit_header_index: UnitHeaderIndex::new(),
+            tombstone_filter: parent.tombstone_filter,
+            limits: parent.limits,
         })
     }
 }
 
+#[cfg(all(feature = "endian-reader", feature = "std"))]
+impl<Endian: Endianity> DwarfPackage<EndianArcSlice<Endian>> {
+    /// Try to load the `.dwp` sections into shared, owned readers.
+    ///
+    /// `section` operates the same as for `Dwarf::load_owned`.
+    pub fn load_owned<F, B, E>(mut section: F, endian: Endian) -> core::result::Result<Self, E>
+    where
+        F: FnMut(SectionId) -> core::result::Result<B, E>,
+        B: Into<Arc<[u8]>>,
+        E: From<Error>,
+    {
+        Self::load(
+            |id| section(id).map(|data| EndianArcSlice::from_bytes(data, endian)),
+            EndianArcSlice::from_bytes(&[][..], endian),
+        )
+    }
+}
+
+/// An index of the unit headers in the `.debug_info` and `.debug_types` sections.
+///
+/// The index for each section is built on first use by `Dwarf::entry_at` or
+/// `Dwarf::unit_header_at`. The map of type signatures is built on first use
+/// by `Dwarf::resolve_type_signature`.
+#[derive(Debug, Default)]
+pub struct UnitHeaderIndex {
+    debug_info: LazyArc<Vec<u64>>,
+    debug_types: LazyArc<Vec<u64>>,
+    type_signatures: LazyArc<BTreeMap<u64, (UnitSectionOffset<u64>, u64)>>,
+}
+
+impl UnitHeaderIndex {
+    /// Create an empty unit header index.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return the start of the last unit that starts at or before `offset`.
+    fn find(starts: &[u64], offset: u64) -> Result<u64> {
+        let index = starts.partition_point(|&start| start <= offset);
+        if index == 0 {
+            return Err(Error::NoEntryAtGivenOffset);
+        }
+        Ok(starts[index - 1])
+    }
+}
+
 /// All of the commonly used information for a unit in the `.debug_info` or `.debug_types`
 /// sections.
 #[derive(Debug)]
@@ -1115,6 +1796,9 @@ where
     pub comp_dir: Option<R>,
 
     /// The `DW_AT_low_pc` attribute of the unit. Defaults to 0.
+    ///
+    /// For a split unit, this is only set if the attribute is not an address index.
+    /// Use `Unit::copy_relocated_attributes` to copy it from the skeleton unit.
     pub low_pc: u64,
 
     /// The `DW_AT_str_offsets_base` attribute of the unit. Defaults to 0.
@@ -1144,6 +1828,27 @@ impl<R: Reader> Unit<R> {
         Self::new_with_abbreviations(dwarf, header, abbreviations)
     }
 
+    /// Construct a new `Unit` from the given unit header, adding the location
+    /// of the failure to any error.
+    ///
+    /// This is the same as `new`, except that an error is returned as a
+    /// `ContextError` containing the operation and the section offset of the
+    /// abbreviations, entry, attribute or line program that failed to parse.
+    pub fn new_with_context(
+        dwarf: &Dwarf<R>,
+        header: UnitHeader<R>,
+    ) -> ::core::result::Result<Self, ContextError> {
+        let abbreviations = dwarf.abbreviations(&header).map_err(|e| {
+            ContextError::new(e)
+                .with_operation(ErrorOperation::Abbreviations)
+                .with_location(
+                    SectionId::DebugAbbrev,
+                    header.debug_abbrev_offset().0.into_u64(),
+                )
+        })?;
+        Self::new_impl(dwarf, header, abbreviations, None)
+    }
+
     /// Construct a new `Unit` from the given unit header and abbreviations.
     ///
     /// The abbreviations for this call can be obtained using `dwarf.abbreviations(&header)`.
@@ -1155,6 +1860,33 @@ impl<R: Reader> Unit<R> {
         header: UnitHeader<R>,
         abbreviations: Arc<Abbreviations>,
     ) -> Result<Self> {
+        Self::new_impl(dwarf, header, abbreviations, None).map_err(Error::from)
+    }
+
+    /// Construct a new `Unit` from the given unit header, ignoring errors in
+    /// optional information.
+    ///
+    /// This is the same as `new`, except that errors when parsing the line
+    /// number program header or the `DW_AT_low_pc` attribute are reported to
+    /// `warnings`, and the corresponding field of the unit is left unset.
+    /// Errors when parsing the abbreviations or the unit's root entry are
+    /// still returned.
+    pub fn new_lenient(
+        dwarf: &Dwarf<R>,
+        header: UnitHeader<R>,
+        warnings: &mut dyn WarningSink,
+    ) -> Result<Self> {
+        let abbreviations = dwarf.abbreviations(&header)?;
+        Self::new_impl(dwarf, header, abbreviations, Some(warnings)).map_err(Error::from)
+    }
+
+    fn new_impl(
+        dwarf: &Dwarf<R>,
+        mut header: UnitHeader<R>,
+        abbreviations: Arc<Abbreviations>,
+        mut warnings: Option<&mut dyn WarningSink>,
+    ) -> ::core::result::Result<Self, ContextError> {
+        header.set_max_die_depth(dwarf.limits.max_die_depth);
         let mut unit = Unit {
             abbreviations,
             name: None,
@@ -1185,13 +1917,18 @@ impl<R: Reader> Unit<R> {
         let mut comp_dir = None;
         let mut line_program_offset = None;
         let mut low_pc_attr = None;
+        let root_offset;
 
         {
             let mut cursor = unit.header.entries(&unit.abbreviations);
-            cursor.next_dfs()?;
-            let root = cursor.current().ok_or(Error::MissingUnitDie)?;
+            cursor.next_dfs_with_context()?;
+            let root = cursor.current().ok_or_else(|| {
+                unit.header
+                    .error_context(ErrorOperation::Entry, Error::MissingUnitDie)
+            })?;
+            root_offset = root.offset();
             let mut attrs = root.attrs();
-            while let Some(attr) = attrs.next()? {
+            while let Some(attr) = attrs.next_with_context()? {
                 match attr.name() {
                     constants::DW_AT_name => {
                         name = Some(attr.value());
@@ -1247,18 +1984,65 @@ impl<R: Reader> Unit<R> {
             Some(val) => dwarf.attr_string(&unit, val).ok(),
             None => None,
         };
-        unit.line_program = match line_program_offset {
-            Some(offset) => Some(dwarf.debug_line.program(
+        if let Some(offset) = line_program_offset {
+            match dwarf.debug_line.program(
                 offset,
                 unit.header.address_size(),
                 unit.comp_dir.clone(),
                 unit.name.clone(),
-            )?),
-            None => None,
-        };
+            ) {
+                Ok(program)
+                    if dwarf
+                        .limits
+                        .max_line_program_length
+                        .map_or(false, |max| program.header().unit_length().into_u64() > max) =>
+                {
+                    let e = ContextError::new(Error::LineProgramTooLong)
+                        .with_operation(ErrorOperation::LineProgram)
+                        .with_location(SectionId::DebugLine, offset.0.into_u64());
+                    match warnings {
+                        Some(ref mut warnings) => warnings.warning(e),
+                        None => return Err(e),
+                    }
+                }
+                Ok(mut program) => {
+                    program.set_tombstone_filter(dwarf.tombstone_filter);
+                    unit.line_program = Some(program);
+                }
+                Err(e) => {
+                    let e = ContextError::new(e)
+                        .with_operation(ErrorOperation::LineProgram)
+                        .with_location(SectionId::DebugLine, offset.0.into_u64());
+                    match warnings {
+                        Some(ref mut warnings) => warnings.warning(e),
+                        None => return Err(e),
+                    }
+                }
+            }
+        }
+        // A split unit doesn't have a `DW_AT_addr_base` (or `DW_AT_GNU_addr_base`
+        // for the GNU DWARF 4 extensions), so an address index can't be resolved
+        // until the attributes of the skeleton unit have been copied.
+        if dwarf.file_type == DwarfFileType::Dwo {
+            if let Some(AttributeValue::DebugAddrIndex(_)) = low_pc_attr {
+                low_pc_attr = None;
+            }
+        }
         if let Some(low_pc_attr) = low_pc_attr {
-            if let Some(addr) = dwarf.attr_address(&unit, low_pc_attr)? {
-                unit.low_pc = addr;
+            match dwarf.attr_address(&unit, low_pc_attr) {
+                Ok(Some(addr)) => unit.low_pc = addr,
+                Ok(None) => {}
+                Err(e) => {
+                    let e = unit.header.entry_error_context(
+                        root_offset,
+                        ErrorOperation::Attribute(constants::DW_AT_low_pc),
+                        e,
+                    );
+                    match warnings {
+                        Some(ref mut warnings) => warnings.warning(e),
+                        None => return Err(e),
+                    }
+                }
             }
         }
         Ok(unit)
@@ -1329,6 +2113,17 @@ impl<R: Reader> Unit<R> {
         }
     }
 
+    /// Verify that the DWO ID of this split unit matches the DWO ID of its skeleton unit.
+    ///
+    /// This should be used when the split unit was found by some means other than
+    /// its DWO ID, such as when loading a `.dwo` file that contains a single unit,
+    /// to detect stale DWO files. Units without a DWO ID are not checked.
+    ///
+    /// Returns `Error::MismatchedDwoId` if the DWO IDs do not match.
+    pub fn verify_dwo_id(&self, skeleton: &Unit<R>) -> Result<()> {
+        check_dwo_id(skeleton.dwo_id, self.dwo_id)
+    }
+
     /// Find the dwo name (if any) for this unit, automatically handling the differences
     /// between the standardized DWARF 5 split DWARF format and the pre-DWARF 5 GNU
     /// extension.
@@ -1346,6 +2141,18 @@ impl<R: Reader> Unit<R> {
     }
 }
 
+/// Check that the DWO ID of a split unit matches the DWO ID of its skeleton unit.
+///
+/// Units without a DWO ID are not checked.
+fn check_dwo_id(skeleton_id: Option<DwoId>, split_id: Option<DwoId>) -> Result<()> {
+    match (skeleton_id, split_id) {
+        (Some(skeleton_id), Some(split_id)) if skeleton_id != split_id => {
+            Err(Error::MismatchedDwoId(skeleton_id, split_id))
+        }
+        _ => Ok(()),
+    }
+}
+
 /// A reference to a `Unit` and its associated `Dwarf`.
 ///
 /// These often need to be passed around together, so this struct makes that easier.
@@ -1529,6 +2336,134 @@ impl<'a, R: Reader> UnitRef<'a, R> {
     }
 }
 
+/// A cache of strings read from the DWARF string sections.
+///
+/// Each string is validated as UTF-8 only once, and identical strings share the
+/// same allocation. This is useful when the same strings are read many times,
+/// such as when building a symbol index.
+///
+/// A `StringCache` must only be used with a single `Dwarf`, since the cache
+/// entries are keyed by section offsets.
+#[derive(Debug, Default)]
+pub struct StringCache {
+    offsets: BTreeMap<(StringCacheSection, u64), Arc<str>>,
+    strings: BTreeSet<Arc<str>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum StringCacheSection {
+    Str,
+    StrSup,
+    LineStr,
+}
+
+impl StringCache {
+    /// Create an empty string cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return the number of distinct strings in the cache.
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+
+    /// Return true if the cache contains no strings.
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+
+    /// Remove all strings from the cache.
+    pub fn clear(&mut self) {
+        self.offsets.clear();
+        self.strings.clear();
+    }
+
+    /// Return the string at the given offset in `.debug_str`.
+    pub fn string<R: Reader>(
+        &mut self,
+        dwarf: &Dwarf<R>,
+        offset: DebugStrOffset<R::Offset>,
+    ) -> Result<Arc<str>> {
+        self.get_or_insert(StringCacheSection::Str, offset.0, || dwarf.string(offset))
+    }
+
+    /// Return the string at the given offset in `.debug_line_str`.
+    pub fn line_string<R: Reader>(
+        &mut self,
+        dwarf: &Dwarf<R>,
+        offset: DebugLineStrOffset<R::Offset>,
+    ) -> Result<Arc<str>> {
+        self.get_or_insert(StringCacheSection::LineStr, offset.0, || {
+            dwarf.line_string(offset)
+        })
+    }
+
+    /// Return the string at the given offset in the `.debug_str`
+    /// in the supplementary object file.
+    pub fn sup_string<R: Reader>(
+        &mut self,
+        dwarf: &Dwarf<R>,
+        offset: DebugStrOffset<R::Offset>,
+    ) -> Result<Arc<str>> {
+        self.get_or_insert(StringCacheSection::StrSup, offset.0, || {
+            dwarf.sup_string(offset)
+        })
+    }
+
+    /// Return an attribute value as a string.
+    ///
+    /// This supports the same forms as [`Dwarf::attr_string`]. Strings that are
+    /// stored inline in the DIE are interned, but are validated each time.
+    pub fn attr_string<R: Reader>(
+        &mut self,
+        dwarf: &Dwarf<R>,
+        unit: &Unit<R>,
+        attr: AttributeValue<R>,
+    ) -> Result<Arc<str>> {
+        match attr {
+            AttributeValue::String(string) => Ok(self.intern(&string.to_string()?)),
+            AttributeValue::DebugStrRef(offset) => self.string(dwarf, offset),
+            AttributeValue::DebugStrRefSup(offset) => self.sup_string(dwarf, offset),
+            AttributeValue::DebugLineStrRef(offset) => self.line_string(dwarf, offset),
+            AttributeValue::DebugStrOffsetsIndex(index) => {
+                let offset = dwarf.string_offset(unit, index)?;
+                self.string(dwarf, offset)
+            }
+            _ => Err(Error::ExpectedStringAttributeValue),
+        }
+    }
+
+    fn get_or_insert<R, O, F>(
+        &mut self,
+        section: StringCacheSection,
+        offset: O,
+        read: F,
+    ) -> Result<Arc<str>>
+    where
+        R: Reader,
+        O: ReaderOffset,
+        F: FnOnce() -> Result<R>,
+    {
+        let key = (section, offset.into_u64());
+        if let Some(string) = self.offsets.get(&key) {
+            return Ok(string.clone());
+        }
+        let string = self.intern(&read()?.to_string()?);
+        self.offsets.insert(key, string.clone());
+        Ok(string)
+    }
+
+    fn intern(&mut self, string: &str) -> Arc<str> {
+        if let Some(string) = self.strings.get(string) {
+            return string.clone();
+        }
+        let string: Arc<str> = Arc::from(string);
+        self.strings.insert(string.clone());
+        string
+    }
+}
+
 impl<T: ReaderOffset> UnitSectionOffset<T> {
     /// Convert an offset to be relative to the start of the given unit,
     /// instead of relative to the start of the section.
@@ -1619,6 +2554,24 @@ impl<R: Reader> fallible_iterator::FallibleIterator for RangeIter<R> {
     }
 }
 
+impl<R: Reader> RangeIter<R> {
+    /// Return an adapter that implements `Iterator`.
+    ///
+    /// See [`ResultIterator`] for more information.
+    pub fn iterator(self) -> ResultIterator<Self> {
+        ResultIterator::new(self)
+    }
+}
+
+impl<R: Reader> Iterator for ResultIterator<RangeIter<R>> {
+    type Item = Result<Range>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_with(RangeIter::next)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1650,6 +2603,100 @@ mod tests {
         assert_is_send::<Unit<EndianSlice<'_, LittleEndian>>>();
     }
 
+    #[test]
+    #[cfg(all(feature = "endian-reader", feature = "std"))]
+    fn test_send_sync_owned() {
+        use crate::read::*;
+        fn assert_is_send_sync<T: Send + Sync + 'static>() {}
+        type R = EndianArcSlice<LittleEndian>;
+        assert_is_send_sync::<Dwarf<R>>();
+        assert_is_send_sync::<DwarfPackage<R>>();
+        assert_is_send_sync::<Unit<R>>();
+        assert_is_send_sync::<UnitHeader<R>>();
+        assert_is_send_sync::<Abbreviations>();
+        assert_is_send_sync::<IncompleteLineProgram<R>>();
+        assert_is_send_sync::<CompleteLineProgram<R>>();
+        assert_is_send_sync::<LineRows<R, IncompleteLineProgram<R>>>();
+        assert_is_send_sync::<LineSequence<R>>();
+        assert_is_send_sync::<RangeLists<R>>();
+        assert_is_send_sync::<LocationLists<R>>();
+        assert_is_send_sync::<DebugFrame<R>>();
+        assert_is_send_sync::<EhFrame<R>>();
+        assert_is_send_sync::<EhFrameHdr<R>>();
+        assert_is_send_sync::<CommonInformationEntry<R>>();
+        assert_is_send_sync::<FrameDescriptionEntry<R>>();
+        assert_is_send_sync::<UnwindContext<usize>>();
+        assert_is_send_sync::<Evaluation<R>>();
+        assert_is_send_sync::<DebugAranges<R>>();
+        assert_is_send_sync::<GdbIndex<R>>();
+        assert_is_send_sync::<AcceleratorCache>();
+        assert_is_send_sync::<Symbolizer<R>>();
+    }
+
+    #[test]
+    #[cfg(all(feature = "endian-reader", feature = "std"))]
+    fn test_load_owned() {
+        use alloc::borrow::Cow;
+
+        let dwarf = Dwarf::load_owned(
+            |id| -> core::result::Result<_, ()> {
+                Ok(match id {
+                    SectionId::DebugStr => Cow::Borrowed(&b"foo\0"[..]),
+                    _ => Cow::Owned(Vec::new()),
+                })
+            },
+            LittleEndian,
+        )
+        .unwrap();
+        assert_eq!(
+            dwarf.debug_str.get_str(DebugStrOffset(0)).unwrap().bytes(),
+            b"foo"
+        );
+        assert!(dwarf.debug_info.reader().is_empty());
+    }
+
+    #[test]
+    fn test_string_cache() {
+        let debug_str = b"foo\0bar\0foo\0";
+        let debug_line_str = b"bar\0\xff\0";
+        let dwarf = Dwarf {
+            debug_str: DebugStr::new(debug_str, LittleEndian),
+            debug_line_str: DebugLineStr::new(debug_line_str, LittleEndian),
+            ..Default::default()
+        };
+
+        let mut cache = StringCache::new();
+        let foo = cache.string(&dwarf, DebugStrOffset(0)).unwrap();
+        assert_eq!(&*foo, "foo");
+        assert!(Arc::ptr_eq(
+            &foo,
+            &cache.string(&dwarf, DebugStrOffset(0)).unwrap()
+        ));
+        // Identical strings at different offsets are interned.
+        assert!(Arc::ptr_eq(
+            &foo,
+            &cache.string(&dwarf, DebugStrOffset(8)).unwrap()
+        ));
+        let bar = cache.string(&dwarf, DebugStrOffset(4)).unwrap();
+        assert!(Arc::ptr_eq(
+            &bar,
+            &cache.line_string(&dwarf, DebugLineStrOffset(0)).unwrap()
+        ));
+        assert_eq!(cache.len(), 2);
+
+        assert_eq!(
+            cache.line_string(&dwarf, DebugLineStrOffset(4)),
+            Err(Error::BadUtf8)
+        );
+        assert_eq!(
+            cache.sup_string(&dwarf, DebugStrOffset(0)),
+            Err(Error::ExpectedStringAttributeValue)
+        );
+
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+
     #[test]
     fn test_format_error() {
         let dwarf_sections = DwarfSections::load(|_| -> Result<_> { Ok(vec![1, 2]) }).unwrap();
@@ -1678,4 +2725,853 @@ mod tests {
         }
         assert_eq!(dwarf.format_error(Error::Io), Error::Io.description());
     }
+
+    #[test]
+    fn test_load_lenient() {
+        let loader = |id| -> Result<Vec<u8>> {
+            match id {
+                SectionId::DebugRanges => Err(Error::Io),
+                _ => Ok(vec![id as u8]),
+            }
+        };
+        let mut errors = Vec::new();
+        let sections = DwarfSections::load_lenient(loader, |e| errors.push(e));
+        assert_eq!(
+            errors,
+            [SectionLoadError {
+                section: SectionId::DebugRanges,
+                error: Error::Io,
+            }]
+        );
+        let dwarf = sections.borrow(|section| EndianSlice::new(section, LittleEndian));
+        assert_eq!(
+            dwarf.debug_line.reader().slice(),
+            &[SectionId::DebugLine as u8]
+        );
+        assert!(dwarf.ranges.debug_ranges().reader().is_empty());
+    }
+
+    #[test]
+    fn test_entry_at() {
+        use crate::constants::*;
+        use crate::read::abbrev::tests::AbbrevSectionMethods;
+        use crate::test_util::{test_dwarf, GimliSectionMethods};
+        use test_assembler::{Endian, Label, LabelMaker, Section};
+
+        #[rustfmt::skip]
+        let abbrev = Section::with_endian(Endian::Little)
+            .abbrev(1, DW_TAG_compile_unit, DW_CHILDREN_no)
+                .abbrev_attr(DW_AT_name, DW_FORM_string)
+                .abbrev_attr_null()
+            .abbrev_null();
+        let abbrev = abbrev.get_contents().unwrap();
+
+        let mut info = Section::with_endian(Endian::Little);
+        // The unit with an invalid version is left out of the index.
+        for (version, name) in [(4, &b"a.c\0"[..]), (1, &b"x.c\0"[..]), (4, &b"b.c\0"[..])] {
+            let length = Label::new();
+            let start = Label::new();
+            let end = Label::new();
+            info = info
+                .initial_length(crate::Format::Dwarf32, &length, &start)
+                .L16(version)
+                .L32(0)
+                .D8(8)
+                .uleb(1)
+                .append_bytes(name)
+                .mark(&end);
+            length.set_const((&end - &start) as u64);
+        }
+        let info = info.get_contents().unwrap();
+
+        let dwarf = test_dwarf(&abbrev, &info);
+
+        let (unit, offset) = dwarf.entry_at(DebugInfoOffset(0x2b).into()).unwrap();
+        assert_eq!(unit.header.offset(), DebugInfoOffset(0x20).into());
+        assert_eq!(offset, UnitOffset(0xb));
+        let entry = unit.entry(offset).unwrap();
+        let name = entry.attr_value(DW_AT_name).unwrap().unwrap();
+        assert_eq!(dwarf.attr_string(&unit, name).unwrap().slice(), b"b.c");
+
+        let (unit, offset) = dwarf.entry_at(DebugInfoOffset(0xb).into()).unwrap();
+        assert_eq!(unit.header.offset(), DebugInfoOffset(0).into());
+        assert_eq!(offset, UnitOffset(0xb));
+
+        // Offsets within a unit header, within an invalid unit, or after the
+        // last unit, are invalid.
+        assert!(dwarf.entry_at(DebugInfoOffset(0x4).into()).is_err());
+        assert_eq!(
+            dwarf.unit_header_at(DebugInfoOffset(0x1b).into()).err(),
+            Some(Error::NoEntryAtGivenOffset)
+        );
+        assert!(dwarf.entry_at(DebugInfoOffset(0x100).into()).is_err());
+    }
+
+    #[test]
+    fn test_abbreviations_cache_policy() {
+        use crate::constants::*;
+        use crate::read::abbrev::tests::AbbrevSectionMethods;
+        use crate::read::{AbbreviationsCachePolicy, AbbreviationsCacheStats};
+        use crate::test_util::{test_dwarf, GimliSectionMethods};
+        use test_assembler::{Endian, Label, LabelMaker, Section};
+
+        #[rustfmt::skip]
+        let abbrev = Section::with_endian(Endian::Little)
+            .abbrev(1, DW_TAG_compile_unit, DW_CHILDREN_no)
+                .abbrev_attr_null()
+            .abbrev_null()
+            .abbrev(1, DW_TAG_partial_unit, DW_CHILDREN_no)
+                .abbrev_attr_null()
+            .abbrev_null();
+        let abbrev = abbrev.get_contents().unwrap();
+
+        let mut info = Section::with_endian(Endian::Little);
+        for abbrev_offset in [0, 6, 0] {
+            let length = Label::new();
+            let start = Label::new();
+            let end = Label::new();
+            info = info
+                .initial_length(crate::Format::Dwarf32, &length, &start)
+                .L16(4)
+                .L32(abbrev_offset)
+                .D8(8)
+                .uleb(1)
+                .mark(&end);
+            length.set_const((&end - &start) as u64);
+        }
+        let info = info.get_contents().unwrap();
+
+        let mut dwarf = test_dwarf(&abbrev, &info);
+        let parse_units = |dwarf: &Dwarf<_>| {
+            let mut units = dwarf.units();
+            while let Some(header) = units.next().unwrap() {
+                dwarf.unit(header).unwrap();
+            }
+            dwarf.abbreviations_cache.stats()
+        };
+
+        // The default policy only caches what was populated.
+        assert_eq!(
+            parse_units(&dwarf),
+            AbbreviationsCacheStats {
+                hits: 0,
+                misses: 3,
+                insertions: 0,
+                evictions: 0,
+                entries: 0,
+            }
+        );
+
+        dwarf.abbreviations_cache = AbbreviationsCache::new();
+        dwarf.populate_abbreviations_cache(AbbreviationsCacheStrategy::Duplicates);
+        assert_eq!(
+            parse_units(&dwarf),
+            AbbreviationsCacheStats {
+                hits: 2,
+                misses: 1,
+                insertions: 1,
+                evictions: 0,
+                entries: 1,
+            }
+        );
+
+        dwarf.abbreviations_cache = AbbreviationsCache::with_policy(AbbreviationsCachePolicy::All);
+        assert_eq!(
+            parse_units(&dwarf),
+            AbbreviationsCacheStats {
+                hits: 1,
+                misses: 2,
+                insertions: 2,
+                evictions: 0,
+                entries: 2,
+            }
+        );
+
+        dwarf.abbreviations_cache = AbbreviationsCache::with_policy(AbbreviationsCachePolicy::Off);
+        assert_eq!(
+            parse_units(&dwarf),
+            AbbreviationsCacheStats {
+                hits: 0,
+                misses: 3,
+                insertions: 0,
+                evictions: 0,
+                entries: 0,
+            }
+        );
+
+        dwarf.abbreviations_cache =
+            AbbreviationsCache::with_policy(AbbreviationsCachePolicy::Lru(1));
+        assert_eq!(
+            parse_units(&dwarf),
+            AbbreviationsCacheStats {
+                hits: 0,
+                misses: 3,
+                insertions: 3,
+                evictions: 2,
+                entries: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn test_unit_with_context() {
+        use crate::constants::*;
+        use crate::read::abbrev::tests::AbbrevSectionMethods;
+        use crate::test_util::{test_dwarf, GimliSectionMethods};
+        use test_assembler::{Endian, Label, LabelMaker, Section};
+
+        #[rustfmt::skip]
+        let abbrev = Section::with_endian(Endian::Little)
+            .abbrev(1, DW_TAG_compile_unit, DW_CHILDREN_no)
+                .abbrev_attr(DW_AT_name, DW_FORM_string)
+                .abbrev_attr_null()
+            .abbrev(2, DW_TAG_compile_unit, DW_CHILDREN_no)
+                .abbrev_attr(DW_AT_stmt_list, DW_FORM_sec_offset)
+                .abbrev_attr_null()
+            .abbrev_null();
+        let abbrev = abbrev.get_contents().unwrap();
+
+        let mut info = Section::with_endian(Endian::Little);
+        let mut starts = Vec::new();
+        for (abbrev_offset, body) in [
+            // Invalid abbreviations offset.
+            (0x100, &[1, b'a', 0][..]),
+            // Unterminated name.
+            (0, &[1, b'a'][..]),
+            // Missing line program.
+            (0, &[2, 0, 0, 0, 0][..]),
+        ] {
+            let length = Label::new();
+            let start = Label::new();
+            let end = Label::new();
+            starts.push(info.size());
+            info = info
+                .initial_length(crate::Format::Dwarf32, &length, &start)
+                .L16(4)
+                .L32(abbrev_offset)
+                .D8(8)
+                .append_bytes(body)
+                .mark(&end);
+            length.set_const((&end - &start) as u64);
+        }
+        let info = info.get_contents().unwrap();
+
+        let dwarf = test_dwarf(&abbrev, &info);
+        let mut units = dwarf.units();
+        let mut next = || {
+            let header = units.next().unwrap().unwrap();
+            let error = dwarf.unit(header).err().unwrap();
+            let context = dwarf.unit_with_context(header).err().unwrap();
+            assert_eq!(context.error(), error);
+            context
+        };
+
+        let err = next();
+        assert_eq!(err.operation(), Some(ErrorOperation::Abbreviations));
+        assert_eq!(err.section(), Some(SectionId::DebugAbbrev));
+        assert_eq!(err.offset(), Some(0x100));
+
+        let err = next();
+        assert_eq!(err.operation(), Some(ErrorOperation::Attribute(DW_AT_name)));
+        assert_eq!(err.section(), Some(SectionId::DebugInfo));
+        assert_eq!(err.offset(), Some(starts[1] + 0xb));
+        assert_eq!(
+            dwarf.format_context_error(&err),
+            format!(
+                "parsing DW_AT_name of DIE at .debug_info+0x{:x}: \
+                 Hit the end of input before it was expected at .debug_info+0x{:x}",
+                starts[1] + 0xb,
+                starts[1] + 0xc,
+            )
+        );
+
+        let err = next();
+        assert_eq!(err.operation(), Some(ErrorOperation::LineProgram));
+        assert_eq!(err.section(), Some(SectionId::DebugLine));
+        assert_eq!(err.offset(), Some(0));
+    }
+
+    #[test]
+    fn test_resolve_type_signature() {
+        use crate::constants::*;
+        use crate::read::abbrev::tests::AbbrevSectionMethods;
+        use crate::test_util::{test_dwarf, GimliSectionMethods};
+        use test_assembler::{Endian, Label, LabelMaker, Section};
+
+        #[rustfmt::skip]
+        let abbrev = Section::with_endian(Endian::Little)
+            .abbrev(1, DW_TAG_type_unit, DW_CHILDREN_yes)
+                .abbrev_attr_null()
+            .abbrev(2, DW_TAG_base_type, DW_CHILDREN_no)
+                .abbrev_attr(DW_AT_name, DW_FORM_string)
+                .abbrev_attr_null()
+            .abbrev_null();
+        let abbrev = abbrev.get_contents().unwrap();
+
+        let length = Label::new();
+        let start = Label::new();
+        let end = Label::new();
+        #[rustfmt::skip]
+        let types = Section::with_endian(Endian::Little)
+            .initial_length(crate::Format::Dwarf32, &length, &start)
+            .L16(4)
+            .L32(0)
+            .D8(8)
+            .L64(0x1111)
+            .L32(0x18)
+            .uleb(1)
+                .uleb(2).append_bytes(b"int\0")
+                .D8(0)
+            .mark(&end);
+        length.set_const((&end - &start) as u64);
+        let types = types.get_contents().unwrap();
+
+        let length = Label::new();
+        let start = Label::new();
+        let end = Label::new();
+        #[rustfmt::skip]
+        let info = Section::with_endian(Endian::Little)
+            .initial_length(crate::Format::Dwarf32, &length, &start)
+            .L16(5)
+            .D8(DW_UT_type.0)
+            .D8(8)
+            .L32(0)
+            .L64(0x2222)
+            .L32(0x19)
+            .uleb(1)
+                .uleb(2).append_bytes(b"long\0")
+                .D8(0)
+            .mark(&end);
+        length.set_const((&end - &start) as u64);
+        let info = info.get_contents().unwrap();
+
+        let dwarf = Dwarf {
+            debug_types: DebugTypes::new(&types, LittleEndian),
+            ..test_dwarf(&abbrev, &info)
+        };
+
+        for (signature, name) in [(0x1111, &b"int"[..]), (0x2222, &b"long"[..])] {
+            let (unit, offset) = dwarf
+                .resolve_type_signature(DebugTypeSignature(signature))
+                .unwrap();
+            let entry = unit.entry(offset).unwrap();
+            assert_eq!(entry.tag(), DW_TAG_base_type);
+            let value = entry.attr_value(DW_AT_name).unwrap().unwrap();
+            assert_eq!(dwarf.attr_string(&unit, value).unwrap().slice(), name);
+        }
+        assert_eq!(
+            dwarf
+                .resolve_type_signature(DebugTypeSignature(0x3333))
+                .err(),
+            Some(Error::UnknownTypeSignature)
+        );
+    }
+
+    #[test]
+    fn test_dwp_resolve_type_signature() {
+        use crate::constants::*;
+        use crate::read::abbrev::tests::AbbrevSectionMethods;
+        use crate::test_util::GimliSectionMethods;
+        use test_assembler::{Endian, Label, LabelMaker, Section};
+
+        #[rustfmt::skip]
+        let abbrev = Section::with_endian(Endian::Little)
+            .abbrev(1, DW_TAG_type_unit, DW_CHILDREN_yes)
+                .abbrev_attr_null()
+            .abbrev(2, DW_TAG_base_type, DW_CHILDREN_no)
+                .abbrev_attr(DW_AT_name, DW_FORM_string)
+                .abbrev_attr_null()
+            .abbrev_null();
+        let abbrev = abbrev.get_contents().unwrap();
+
+        fn unit(signature: u64, name: &[u8]) -> Vec<u8> {
+            let length = Label::new();
+            let start = Label::new();
+            let end = Label::new();
+            #[rustfmt::skip]
+            let unit = Section::with_endian(Endian::Little)
+                .initial_length(crate::Format::Dwarf32, &length, &start)
+                .L16(5)
+                .D8(DW_UT_split_type.0)
+                .D8(8)
+                .L32(0)
+                .L64(signature)
+                .L32(0x19)
+                .uleb(1)
+                    .uleb(2).append_bytes(name)
+                    .D8(0)
+                .mark(&end);
+            length.set_const((&end - &start) as u64);
+            unit.get_contents().unwrap()
+        }
+        let unit1 = unit(0x1111, b"int\0");
+        let unit2 = unit(0x2222, b"long\0");
+        let info = [&unit1[..], &unit2[..]].concat();
+
+        #[rustfmt::skip]
+        let tu_index = Section::with_endian(Endian::Little)
+            // Header.
+            .L16(5).L16(0).L32(2).L32(2).L32(4)
+            // Slots.
+            .L64(0).L64(0x1111).L64(0x2222).L64(0)
+            .L32(0).L32(1).L32(2).L32(0)
+            // Sections.
+            .L32(DW_SECT_INFO.0).L32(DW_SECT_ABBREV.0)
+            // Offsets.
+            .L32(0).L32(0)
+            .L32(unit1.len() as u32).L32(0)
+            // Sizes.
+            .L32(unit1.len() as u32).L32(abbrev.len() as u32)
+            .L32(unit2.len() as u32).L32(abbrev.len() as u32);
+        let tu_index = tu_index.get_contents().unwrap();
+
+        let empty = EndianSlice::new(&[], LittleEndian);
+        let dwp = DwarfPackage::load(
+            |id| -> Result<_> {
+                Ok(EndianSlice::new(
+                    match id {
+                        SectionId::DebugTuIndex => &tu_index,
+                        SectionId::DebugInfo => &info,
+                        SectionId::DebugAbbrev => &abbrev,
+                        _ => &[][..],
+                    },
+                    LittleEndian,
+                ))
+            },
+            empty,
+        )
+        .unwrap();
+        let parent = Dwarf::default();
+
+        for (signature, name) in [(0x1111, &b"int"[..]), (0x2222, &b"long"[..])] {
+            let (dwo, unit, offset) = dwp
+                .resolve_type_signature(DebugTypeSignature(signature), &parent)
+                .unwrap();
+            let entry = unit.entry(offset).unwrap();
+            assert_eq!(entry.tag(), DW_TAG_base_type);
+            let value = entry.attr_value(DW_AT_name).unwrap().unwrap();
+            assert_eq!(dwo.attr_string(&unit, value).unwrap().slice(), name);
+        }
+        assert_eq!(
+            dwp.resolve_type_signature(DebugTypeSignature(0x3333), &parent)
+                .err(),
+            Some(Error::UnknownTypeSignature)
+        );
+    }
+
+    #[test]
+    fn test_split_unit() {
+        use crate::constants::*;
+        use crate::read::abbrev::tests::AbbrevSectionMethods;
+        use crate::test_util::GimliSectionMethods;
+        use test_assembler::{Endian, Label, LabelMaker, Section};
+
+        fn unit(unit_type: DwUt, dwo_id: u64, name: &[u8]) -> Vec<u8> {
+            let length = Label::new();
+            let start = Label::new();
+            let end = Label::new();
+            let section = Section::with_endian(Endian::Little)
+                .initial_length(crate::Format::Dwarf32, &length, &start)
+                .L16(5)
+                .D8(unit_type.0)
+                .D8(8)
+                .L32(0)
+                .L64(dwo_id)
+                .uleb(1)
+                .append_bytes(name)
+                .mark(&end);
+            length.set_const((&end - &start) as u64);
+            section.get_contents().unwrap()
+        }
+
+        #[rustfmt::skip]
+        let skeleton_abbrev = Section::with_endian(Endian::Little)
+            .abbrev(1, DW_TAG_skeleton_unit, DW_CHILDREN_no)
+                .abbrev_attr(DW_AT_dwo_name, DW_FORM_string)
+                .abbrev_attr_null()
+            .abbrev_null();
+        let skeleton_abbrev = skeleton_abbrev.get_contents().unwrap();
+        let skeleton_info = unit(DW_UT_skeleton, 0x1234, b"a.dwo\0");
+
+        #[rustfmt::skip]
+        let split_abbrev = Section::with_endian(Endian::Little)
+            .abbrev(1, DW_TAG_compile_unit, DW_CHILDREN_no)
+                .abbrev_attr(DW_AT_name, DW_FORM_string)
+                .abbrev_attr_null()
+            .abbrev_null();
+        let split_abbrev = split_abbrev.get_contents().unwrap();
+        let mut split_info = unit(DW_UT_split_compile, 0x5678, b"b.c\0");
+        split_info.extend(unit(DW_UT_split_compile, 0x1234, b"a.c\0"));
+
+        let dwarf = Dwarf {
+            debug_abbrev: DebugAbbrev::new(&skeleton_abbrev, LittleEndian),
+            debug_info: DebugInfo::new(&skeleton_info, LittleEndian),
+            ..Default::default()
+        };
+        let header = dwarf.units().next().unwrap().unwrap();
+        let skeleton = dwarf.unit(header).unwrap();
+        assert_eq!(skeleton.dwo_id, Some(DwoId(0x1234)));
+
+        let mut loader = |dwo_name: &EndianSlice<'_, LittleEndian>, _: Option<&_>, _| {
+            if dwo_name.slice() != b"a.dwo" {
+                return Ok(None);
+            }
+            Ok(Some(Dwarf {
+                debug_abbrev: DebugAbbrev::new(&split_abbrev, LittleEndian),
+                debug_info: DebugInfo::new(&split_info, LittleEndian),
+                ..Default::default()
+            }))
+        };
+        let split = dwarf.split_unit(&skeleton, &mut loader).unwrap().unwrap();
+        assert_eq!(split.dwarf.file_type, DwarfFileType::Dwo);
+        assert_eq!(split.unit.dwo_id, Some(DwoId(0x1234)));
+        assert_eq!(split.unit.name.unwrap().slice(), b"a.c");
+
+        assert_eq!(split.unit.verify_dwo_id(&skeleton), Ok(()));
+
+        let mut missing = |_: &_, _: Option<&_>, _| Ok(None);
+        assert!(dwarf.split_unit(&skeleton, &mut missing).unwrap().is_none());
+
+        let stale_info = unit(DW_UT_split_compile, 0x5678, b"b.c\0");
+        let mut stale = |_: &_, _: Option<&_>, _| {
+            Ok(Some(Dwarf {
+                debug_abbrev: DebugAbbrev::new(&split_abbrev, LittleEndian),
+                debug_info: DebugInfo::new(&stale_info, LittleEndian),
+                ..Default::default()
+            }))
+        };
+        let err = dwarf.split_unit(&skeleton, &mut stale).unwrap_err();
+        assert_eq!(err, Error::MismatchedDwoId(DwoId(0x1234), DwoId(0x5678)));
+        assert_eq!(
+            dwarf.format_dwo_error(&skeleton, err),
+            "a.dwo: The DWO ID of a split unit does not match the DWO ID of its skeleton unit. \
+             (skeleton 0x0000000000001234, split 0x0000000000005678)"
+        );
+
+        let header = split.dwarf.units().next().unwrap().unwrap();
+        let unit = split.dwarf.unit(header).unwrap();
+        assert!(split
+            .dwarf
+            .split_unit(&unit, &mut missing)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_verify_dwo_id() {
+        use crate::constants::*;
+        use crate::read::abbrev::tests::AbbrevSectionMethods;
+        use crate::test_util::{test_dwarf, GimliSectionMethods};
+        use test_assembler::{Endian, Label, LabelMaker, Section};
+
+        fn unit(dwo_id: Option<u64>) -> Vec<u8> {
+            let length = Label::new();
+            let start = Label::new();
+            let end = Label::new();
+            let mut section = Section::with_endian(Endian::Little)
+                .initial_length(crate::Format::Dwarf32, &length, &start)
+                .L16(4)
+                .L32(0)
+                .D8(8);
+            section = match dwo_id {
+                Some(dwo_id) => section.uleb(1).L64(dwo_id),
+                None => section.uleb(2),
+            };
+            section = section.mark(&end);
+            length.set_const((&end - &start) as u64);
+            section.get_contents().unwrap()
+        }
+
+        #[rustfmt::skip]
+        let abbrev = Section::with_endian(Endian::Little)
+            .abbrev(1, DW_TAG_compile_unit, DW_CHILDREN_no)
+                .abbrev_attr(DW_AT_GNU_dwo_id, DW_FORM_data8)
+                .abbrev_attr_null()
+            .abbrev(2, DW_TAG_compile_unit, DW_CHILDREN_no)
+                .abbrev_attr_null()
+            .abbrev_null();
+        let abbrev = abbrev.get_contents().unwrap();
+        let info = [unit(Some(0x1234)), unit(Some(0x5678)), unit(None)].concat();
+        let dwarf = test_dwarf(&abbrev, &info);
+        let mut units = Vec::new();
+        let mut headers = dwarf.units();
+        while let Some(header) = headers.next().unwrap() {
+            units.push(dwarf.unit(header).unwrap());
+        }
+        let (a, b, none) = (&units[0], &units[1], &units[2]);
+        assert_eq!(none.dwo_id, None);
+
+        assert_eq!(a.verify_dwo_id(a), Ok(()));
+        assert_eq!(
+            a.verify_dwo_id(b),
+            Err(Error::MismatchedDwoId(DwoId(0x5678), DwoId(0x1234)))
+        );
+        assert_eq!(
+            b.verify_dwo_id(a),
+            Err(Error::MismatchedDwoId(DwoId(0x1234), DwoId(0x5678)))
+        );
+        // Units without a DWO ID are not checked.
+        assert_eq!(a.verify_dwo_id(none), Ok(()));
+        assert_eq!(none.verify_dwo_id(a), Ok(()));
+        assert_eq!(none.verify_dwo_id(none), Ok(()));
+    }
+
+    #[test]
+    fn test_dwp_find_cu() {
+        use crate::constants::*;
+        use crate::read::abbrev::tests::AbbrevSectionMethods;
+        use crate::test_util::GimliSectionMethods;
+        use test_assembler::{Endian, Label, LabelMaker, Section};
+
+        fn unit(dwo_id: u64, name: &[u8]) -> Vec<u8> {
+            let length = Label::new();
+            let start = Label::new();
+            let end = Label::new();
+            let section = Section::with_endian(Endian::Little)
+                .initial_length(crate::Format::Dwarf32, &length, &start)
+                .L16(5)
+                .D8(DW_UT_split_compile.0)
+                .D8(8)
+                .L32(0)
+                .L64(dwo_id)
+                .uleb(1)
+                .append_bytes(name)
+                .mark(&end);
+            length.set_const((&end - &start) as u64);
+            section.get_contents().unwrap()
+        }
+
+        #[rustfmt::skip]
+        let abbrev = Section::with_endian(Endian::Little)
+            .abbrev(1, DW_TAG_compile_unit, DW_CHILDREN_no)
+                .abbrev_attr(DW_AT_name, DW_FORM_string)
+                .abbrev_attr_null()
+            .abbrev_null();
+        let abbrev = abbrev.get_contents().unwrap();
+        let unit1 = unit(0x1111, b"a.c\0");
+        let unit2 = unit(0x2222, b"b.c\0");
+        let info = [&unit1[..], &unit2[..]].concat();
+
+        // The second row of the index has the wrong DWO ID for its unit.
+        #[rustfmt::skip]
+        let cu_index = Section::with_endian(Endian::Little)
+            // Header.
+            .L16(5).L16(0).L32(2).L32(2).L32(4)
+            // Slots.
+            .L64(0).L64(0x1111).L64(0x3332).L64(0)
+            .L32(0).L32(1).L32(2).L32(0)
+            // Sections.
+            .L32(DW_SECT_INFO.0).L32(DW_SECT_ABBREV.0)
+            // Offsets.
+            .L32(0).L32(0)
+            .L32(unit1.len() as u32).L32(0)
+            // Sizes.
+            .L32(unit1.len() as u32).L32(abbrev.len() as u32)
+            .L32(unit2.len() as u32).L32(abbrev.len() as u32);
+        let cu_index = cu_index.get_contents().unwrap();
+
+        let empty = EndianSlice::new(&[], LittleEndian);
+        let dwp = DwarfPackage::load(
+            |id| -> Result<_> {
+                Ok(EndianSlice::new(
+                    match id {
+                        SectionId::DebugCuIndex => &cu_index,
+                        SectionId::DebugInfo => &info,
+                        SectionId::DebugAbbrev => &abbrev,
+                        _ => &[][..],
+                    },
+                    LittleEndian,
+                ))
+            },
+            empty,
+        )
+        .unwrap();
+        let parent = Dwarf::default();
+
+        let dwo = dwp.find_cu(DwoId(0x1111), &parent).unwrap().unwrap();
+        let header = dwo.units().next().unwrap().unwrap();
+        let unit = dwo.unit(header).unwrap();
+        assert_eq!(unit.dwo_id, Some(DwoId(0x1111)));
+        assert_eq!(unit.name.unwrap().slice(), b"a.c");
+
+        assert_eq!(
+            dwp.find_cu(DwoId(0x3332), &parent).err(),
+            Some(Error::MismatchedDwoId(DwoId(0x3332), DwoId(0x2222)))
+        );
+        assert!(dwp.find_cu(DwoId(0x2222), &parent).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_split_unit_gnu() {
+        use crate::constants::*;
+        use crate::read::abbrev::tests::AbbrevSectionMethods;
+        use crate::test_util::GimliSectionMethods;
+        use test_assembler::{Endian, Label, LabelMaker, Section};
+
+        fn unit(entries: Section) -> Vec<u8> {
+            let length = Label::new();
+            let start = Label::new();
+            let end = Label::new();
+            let section = Section::with_endian(Endian::Little)
+                .initial_length(crate::Format::Dwarf32, &length, &start)
+                .L16(4)
+                .L32(0)
+                .D8(8)
+                .append_section(entries)
+                .mark(&end);
+            length.set_const((&end - &start) as u64);
+            section.get_contents().unwrap()
+        }
+
+        #[rustfmt::skip]
+        let skeleton_abbrev = Section::with_endian(Endian::Little)
+            .abbrev(1, DW_TAG_compile_unit, DW_CHILDREN_no)
+                .abbrev_attr(DW_AT_GNU_dwo_name, DW_FORM_string)
+                .abbrev_attr(DW_AT_GNU_dwo_id, DW_FORM_data8)
+                .abbrev_attr(DW_AT_low_pc, DW_FORM_addr)
+                .abbrev_attr(DW_AT_GNU_addr_base, DW_FORM_sec_offset)
+                .abbrev_attr_null()
+            .abbrev_null();
+        let skeleton_abbrev = skeleton_abbrev.get_contents().unwrap();
+        let skeleton_info = unit(
+            Section::with_endian(Endian::Little)
+                .uleb(1)
+                .append_bytes(b"a.dwo\0")
+                .L64(0x1234)
+                .L64(0x1000)
+                .L32(8),
+        );
+        // The GNU extensions don't have a header for `.debug_addr`.
+        let addr = Section::with_endian(Endian::Little)
+            .L64(0)
+            .L64(0x1000)
+            .L64(0x2000)
+            .get_contents()
+            .unwrap();
+
+        #[rustfmt::skip]
+        let split_abbrev = Section::with_endian(Endian::Little)
+            .abbrev(1, DW_TAG_compile_unit, DW_CHILDREN_no)
+                .abbrev_attr(DW_AT_name, DW_FORM_GNU_str_index)
+                .abbrev_attr(DW_AT_low_pc, DW_FORM_GNU_addr_index)
+                .abbrev_attr(DW_AT_GNU_dwo_id, DW_FORM_data8)
+                .abbrev_attr_null()
+            .abbrev_null();
+        let split_abbrev = split_abbrev.get_contents().unwrap();
+        let split_info = unit(
+            Section::with_endian(Endian::Little)
+                .uleb(1)
+                .uleb(0)
+                .uleb(1)
+                .L64(0x1234),
+        );
+        // The GNU extensions don't have a header for `.debug_str_offsets.dwo`.
+        let split_str_offsets = Section::with_endian(Endian::Little)
+            .L32(0)
+            .get_contents()
+            .unwrap();
+        let split_str = b"a.c\0";
+        let split_dwarf = || Dwarf {
+            debug_abbrev: DebugAbbrev::new(&split_abbrev, LittleEndian),
+            debug_info: DebugInfo::new(&split_info, LittleEndian),
+            debug_str: DebugStr::new(split_str, LittleEndian),
+            debug_str_offsets: DebugStrOffsets::from(EndianSlice::new(
+                &split_str_offsets,
+                LittleEndian,
+            )),
+            ..Default::default()
+        };
+
+        let dwarf = Dwarf {
+            debug_abbrev: DebugAbbrev::new(&skeleton_abbrev, LittleEndian),
+            debug_info: DebugInfo::new(&skeleton_info, LittleEndian),
+            debug_addr: DebugAddr::from(EndianSlice::new(&addr, LittleEndian)),
+            ..Default::default()
+        };
+        let header = dwarf.units().next().unwrap().unwrap();
+        let skeleton = dwarf.unit(header).unwrap();
+        assert_eq!(skeleton.dwo_id, Some(DwoId(0x1234)));
+        assert_eq!(skeleton.addr_base, DebugAddrBase(8));
+
+        // The address index can't be resolved without the skeleton unit.
+        let mut dwo = split_dwarf();
+        dwo.file_type = DwarfFileType::Dwo;
+        let header = dwo.units().next().unwrap().unwrap();
+        let unit = dwo.unit(header).unwrap();
+        assert_eq!(unit.name.unwrap().slice(), b"a.c");
+        assert_eq!(unit.low_pc, 0);
+
+        let mut loader = |_: &_, _: Option<&_>, _| Ok(Some(split_dwarf()));
+        let split = dwarf.split_unit(&skeleton, &mut loader).unwrap().unwrap();
+        assert_eq!(split.unit.name.unwrap().slice(), b"a.c");
+        assert_eq!(split.unit.low_pc, 0x1000);
+        assert_eq!(
+            split.dwarf.attr_address(
+                &split.unit,
+                AttributeValue::DebugAddrIndex(DebugAddrIndex(1))
+            ),
+            Ok(Some(0x2000))
+        );
+    }
+
+    #[test]
+    fn test_limits() {
+        use crate::constants::*;
+        use crate::read::abbrev::tests::AbbrevSectionMethods;
+        use crate::test_util::{compile_unit, test_dwarf, GimliSectionMethods};
+        use test_assembler::{Endian, Section};
+
+        #[rustfmt::skip]
+        let abbrev = Section::with_endian(Endian::Little)
+            .abbrev(1, DW_TAG_compile_unit, DW_CHILDREN_yes)
+                .abbrev_attr_null()
+            .abbrev(2, DW_TAG_namespace, DW_CHILDREN_yes)
+                .abbrev_attr_null()
+            .abbrev_null();
+        let abbrev = abbrev.get_contents().unwrap();
+
+        #[rustfmt::skip]
+        let entries = Section::with_endian(Endian::Little)
+            // Entries at depths 0, 1 and 2.
+            .uleb(1).uleb(2).uleb(2)
+            .D8(0).D8(0).D8(0);
+        let info = compile_unit(entries);
+
+        let mut dwarf = test_dwarf(&abbrev, &info);
+
+        dwarf.limits.max_die_depth = Some(1);
+        let header = dwarf.units().next().unwrap().unwrap();
+        let unit = dwarf.unit(header).unwrap();
+
+        let mut entries = unit.entries();
+        assert_eq!(entries.next_dfs().unwrap().unwrap().0, 0);
+        assert_eq!(entries.next_dfs().unwrap().unwrap().0, 1);
+        assert_eq!(entries.next_dfs().err(), Some(Error::EntryTooDeep));
+
+        let mut tree = unit.entries_tree(None).unwrap();
+        let root = tree.root().unwrap();
+        let mut children = root.children();
+        let child = children.next().unwrap().unwrap();
+        let mut grandchildren = child.children();
+        assert_eq!(grandchildren.next().err(), Some(Error::EntryTooDeep));
+
+        let mut raw = unit.entries_raw(None).unwrap();
+        assert!(raw.read_abbreviation().unwrap().is_some());
+        assert!(raw.read_abbreviation().unwrap().is_some());
+        assert_eq!(raw.read_abbreviation().err(), Some(Error::EntryTooDeep));
+
+        dwarf.limits.max_die_depth = Some(2);
+        let unit = dwarf.unit(header).unwrap();
+        let mut entries = unit.entries();
+        let mut count = 0;
+        while entries.next_dfs().unwrap().is_some() {
+            count += 1;
+        }
+        assert_eq!(count, 3);
+
+        dwarf.limits.max_abbreviations = Some(1);
+        assert_eq!(dwarf.unit(header).err(), Some(Error::TooManyAbbreviations));
+        dwarf.limits.max_abbreviations = Some(2);
+        assert!(dwarf.unit(header).is_ok());
+    }
 }
@@ -11,6 +11,7 @@ use core::str;
 use stable_deref_trait::CloneStableDeref;
 
 use crate::endianity::Endianity;
+use crate::leb128;
 use crate::read::{Error, Reader, ReaderOffsetId, Result};
 
 /// A reference counted, non-thread-safe slice of bytes and associated
@@ -240,6 +241,39 @@ where
     }
 }
 
+#[cfg(feature = "std")]
+impl<Endian> EndianArcSlice<Endian>
+where
+    Endian: Endianity,
+{
+    /// Construct a new `EndianArcSlice` from any owned or borrowed bytes
+    /// that can be converted into an `Arc<[u8]>`.
+    ///
+    /// This accepts a `Vec<u8>`, a `Cow<[u8]>`, a `Box<[u8]>` or a `&[u8]`.
+    /// The resulting reader has no lifetime and is `Send + Sync`, so it can be
+    /// shared between threads.
+    ///
+    /// ```
+    /// # #[cfg(feature = "std")] {
+    /// use std::borrow::Cow;
+    ///
+    /// let reader = gimli::EndianArcSlice::from_bytes(vec![1, 2, 3, 4], gimli::NativeEndian);
+    /// assert_eq!(reader.bytes(), [1, 2, 3, 4]);
+    ///
+    /// let data: Cow<[u8]> = Cow::Borrowed(&[1, 2]);
+    /// let reader = gimli::EndianArcSlice::from_bytes(data, gimli::NativeEndian);
+    /// assert_eq!(reader.bytes(), [1, 2]);
+    /// # }
+    /// ```
+    #[inline]
+    pub fn from_bytes<B>(bytes: B, endian: Endian) -> Self
+    where
+        B: Into<Arc<[u8]>>,
+    {
+        EndianReader::new(bytes.into(), endian)
+    }
+}
+
 /// # Range Methods
 ///
 /// Unfortunately, `std::ops::Index` *must* return a reference, so we can't
@@ -475,6 +509,93 @@ where
             None => Err(Error::UnexpectedEof(self.offset_id())),
         }
     }
+
+    #[inline]
+    fn skip_leb128(&mut self) -> Result<()> {
+        match leb128::read::skip_fast(self.bytes()) {
+            Some(len) => {
+                self.range.skip(len);
+                Ok(())
+            }
+            None => leb128::read::skip(self),
+        }
+    }
+
+    #[inline]
+    fn read_uleb128(&mut self) -> Result<u64> {
+        match leb128::read::unsigned_fast(self.bytes()) {
+            Some((value, len)) => {
+                self.range.skip(len);
+                Ok(value)
+            }
+            None => leb128::read::unsigned(self),
+        }
+    }
+
+    #[inline]
+    fn read_uleb128_u16(&mut self) -> Result<u16> {
+        match leb128::read::unsigned_fast(self.bytes()) {
+            Some((value, len)) if len <= 3 && value <= u64::from(u16::MAX) => {
+                self.range.skip(len);
+                Ok(value as u16)
+            }
+            _ => leb128::read::u16(self),
+        }
+    }
+
+    #[inline]
+    fn read_sleb128(&mut self) -> Result<i64> {
+        match leb128::read::signed_fast(self.bytes()) {
+            Some((value, len)) => {
+                self.range.skip(len);
+                Ok(value)
+            }
+            None => leb128::read::signed(self),
+        }
+    }
+}
+
+/// Serialized in the same form as `EndianSlice`, containing only the bytes
+/// of the current range.
+#[cfg(feature = "serde")]
+impl<Endian, T> serde::Serialize for EndianReader<Endian, T>
+where
+    Endian: Endianity + serde::Serialize,
+    T: CloneStableDeref<Target = [u8]> + Debug,
+{
+    fn serialize<S>(&self, serializer: S) -> core::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        use serde::ser::SerializeStruct;
+        let mut state = serializer.serialize_struct("EndianReader", 2)?;
+        state.serialize_field("slice", self.bytes())?;
+        state.serialize_field("endian", &self.endian)?;
+        state.end()
+    }
+}
+
+/// Deserialized into a new allocation containing the serialized bytes.
+#[cfg(feature = "serde")]
+impl<'de, Endian, T> serde::Deserialize<'de> for EndianReader<Endian, T>
+where
+    Endian: Endianity + serde::Deserialize<'de>,
+    T: CloneStableDeref<Target = [u8]> + Debug + From<alloc::vec::Vec<u8>>,
+{
+    fn deserialize<D>(deserializer: D) -> core::result::Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        #[derive(serde::Deserialize)]
+        #[serde(rename = "EndianReader")]
+        struct Data<Endian> {
+            slice: alloc::vec::Vec<u8>,
+            endian: Endian,
+        }
+
+        let data = Data::deserialize(deserializer)?;
+        Ok(EndianReader::new(T::from(data.slice), data.endian))
+    }
 }
 
 #[cfg(test)]
@@ -636,4 +757,16 @@ mod tests {
         assert_eq!(arr, &BAD_UTF8[1..3]);
         assert_eq!(reader.bytes(), &BAD_UTF8[3..]);
     }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_serde() {
+        let buf = Rc::from(&[1, 2, 3, 4][..]);
+        let mut reader = native_reader(buf);
+        reader.skip(1).unwrap();
+        let json = serde_json::to_string(&reader).unwrap();
+        let de = serde_json::from_str::<EndianRcSlice<NativeEndian>>(&json).unwrap();
+        assert_eq!(de, reader);
+        assert_eq!(de.bytes(), &[2, 3, 4]);
+    }
 }
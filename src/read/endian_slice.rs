@@ -9,12 +9,14 @@ use core::ops::{Deref, Range, RangeFrom, RangeTo};
 use core::str;
 
 use crate::endianity::Endianity;
+use crate::leb128;
 use crate::read::{Error, Reader, ReaderOffsetId, Result};
 
 /// A `&[u8]` slice with endianity metadata.
 ///
 /// This implements the `Reader` trait, which is used for all reading of DWARF sections.
 #[derive(Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EndianSlice<'input, Endian>
 where
     Endian: Endianity,
@@ -329,6 +331,50 @@ where
         buf.copy_from_slice(slice);
         Ok(())
     }
+
+    #[inline]
+    fn skip_leb128(&mut self) -> Result<()> {
+        match leb128::read::skip_fast(self.slice) {
+            Some(len) => {
+                self.slice = &self.slice[len..];
+                Ok(())
+            }
+            None => leb128::read::skip(self),
+        }
+    }
+
+    #[inline]
+    fn read_uleb128(&mut self) -> Result<u64> {
+        match leb128::read::unsigned_fast(self.slice) {
+            Some((value, len)) => {
+                self.slice = &self.slice[len..];
+                Ok(value)
+            }
+            None => leb128::read::unsigned(self),
+        }
+    }
+
+    #[inline]
+    fn read_uleb128_u16(&mut self) -> Result<u16> {
+        match leb128::read::unsigned_fast(self.slice) {
+            Some((value, len)) if len <= 3 && value <= u64::from(u16::MAX) => {
+                self.slice = &self.slice[len..];
+                Ok(value as u16)
+            }
+            _ => leb128::read::u16(self),
+        }
+    }
+
+    #[inline]
+    fn read_sleb128(&mut self) -> Result<i64> {
+        match leb128::read::signed_fast(self.slice) {
+            Some((value, len)) => {
+                self.slice = &self.slice[len..];
+                Ok(value)
+            }
+            None => leb128::read::signed(self),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,637 @@
+use alloc::vec::Vec;
+
+use crate::common::{DebugInfoOffset, DebugTypeSignature, DebugTypesOffset};
+use crate::endianity::Endianity;
+use crate::read::range_index::{RangeIndex, RangeIndexEntry};
+use crate::read::{EndianSlice, Error, Range, Reader, ReaderOffset, Result, UnitOffset};
+
+/// The parsed contents of a `.gdb_index` section.
+///
+/// This section is generated by GDB or by linkers such as `gold` and `lld`,
+/// and provides an index of the names and addresses in the `.debug_info` and
+/// `.debug_types` sections. Versions 7, 8 and 9 of the format are supported.
+///
+/// All values in this section are little-endian, regardless of the target, so
+/// the reader should normally use `LittleEndian`.
+///
+/// Units are identified by an index, which covers the compilation units
+/// followed by the type units. Use [`GdbIndex::unit`] to find the unit
+/// for an index.
+#[derive(Debug, Clone)]
+pub struct GdbIndex<R: Reader> {
+    version: u32,
+    cu_list: R,
+    tu_list: R,
+    address_area: R,
+    symbol_table: R,
+    constant_pool: R,
+    /// The address ranges, indexed for `find_address`.
+    sorted_addresses: RangeIndex<GdbIndexAddress>,
+}
+
+impl<'input, Endian> GdbIndex<EndianSlice<'input, Endian>>
+where
+    Endian: Endianity,
+{
+    /// Parse the `.gdb_index` section from the given data.
+    pub fn new(section: &'input [u8], endian: Endian) -> Result<Self> {
+        Self::parse(EndianSlice::new(section, endian))
+    }
+}
+
+impl<R: Reader> GdbIndex<R> {
+    /// Parse the header of the given `.gdb_index` section.
+    ///
+    /// This also reads the address area so that it can be searched
+    /// by `find_address`.
+    pub fn parse(section: R) -> Result<Self> {
+        let mut input = section.clone();
+        let version = input.read_u32()?;
+        if !(7..=9).contains(&version) {
+            return Err(Error::UnknownVersion(version.into()));
+        }
+        let cu_list_offset = input.read_u32()?;
+        let tu_list_offset = input.read_u32()?;
+        let address_area_offset = input.read_u32()?;
+        let symbol_table_offset = input.read_u32()?;
+        let shortcut_table_offset = if version >= 9 {
+            Some(input.read_u32()?)
+        } else {
+            None
+        };
+        let constant_pool_offset = input.read_u32()?;
+
+        let area = |start: u32, end: u32| -> Result<R> {
+            if end < start {
+                return Err(Error::InvalidGdbIndex);
+            }
+            let mut area = section.clone();
+            area.skip(R::Offset::from_u32(start))?;
+            area.truncate(R::Offset::from_u32(end - start))?;
+            Ok(area)
+        };
+        let cu_list = area(cu_list_offset, tu_list_offset)?;
+        let tu_list = area(tu_list_offset, address_area_offset)?;
+        let address_area = area(address_area_offset, symbol_table_offset)?;
+        // For version 9, the shortcut table is between the symbol table and
+        // the constant pool.
+        let symbol_table_end = shortcut_table_offset.unwrap_or(constant_pool_offset);
+        if constant_pool_offset < symbol_table_end {
+            return Err(Error::InvalidGdbIndex);
+        }
+        let symbol_table = area(symbol_table_offset, symbol_table_end)?;
+        let size = symbol_table.len().into_u64() / 8;
+        if size & size.wrapping_sub(1) != 0 {
+            return Err(Error::InvalidGdbIndex);
+        }
+        let mut constant_pool = section;
+        constant_pool.skip(R::Offset::from_u32(constant_pool_offset))?;
+
+        let mut sorted_addresses = Vec::new();
+        let mut addresses = GdbIndexAddressIter {
+            input: address_area.clone(),
+        };
+        while let Some(address) = addresses.next()? {
+            sorted_addresses.push(address);
+        }
+
+        Ok(GdbIndex {
+            version,
+            cu_list,
+            tu_list,
+            address_area,
+            symbol_table,
+            constant_pool,
+            sorted_addresses: RangeIndex::new(sorted_addresses),
+        })
+    }
+
+    /// Return the version of the index.
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Return the number of compilation units in the index.
+    pub fn cu_count(&self) -> u32 {
+        (self.cu_list.len().into_u64() / 16) as u32
+    }
+
+    /// Return the number of type units in the index.
+    pub fn tu_count(&self) -> u32 {
+        (self.tu_list.len().into_u64() / 24) as u32
+    }
+
+    /// Return the unit for the given index.
+    ///
+    /// Indices less than `cu_count` refer to compilation units, and the
+    /// following indices refer to type units.
+    pub fn unit(&self, index: u32) -> Result<GdbIndexUnit<R::Offset>> {
+        let cu_count = self.cu_count();
+        if index < cu_count {
+            let mut input = self.cu_list.clone();
+            input.skip(R::Offset::from_u64(u64::from(index) * 16)?)?;
+            let offset = R::Offset::from_u64(input.read_u64()?)?;
+            let length = input.read_u64()?;
+            Ok(GdbIndexUnit::Compilation {
+                offset: DebugInfoOffset(offset),
+                length,
+            })
+        } else if index - cu_count < self.tu_count() {
+            let mut input = self.tu_list.clone();
+            input.skip(R::Offset::from_u64(u64::from(index - cu_count) * 24)?)?;
+            let offset = R::Offset::from_u64(input.read_u64()?)?;
+            let type_offset = R::Offset::from_u64(input.read_u64()?)?;
+            let signature = input.read_u64()?;
+            Ok(GdbIndexUnit::Type {
+                offset: DebugTypesOffset(offset),
+                type_offset: UnitOffset(type_offset),
+                signature: DebugTypeSignature(signature),
+            })
+        } else {
+            Err(Error::InvalidGdbIndex)
+        }
+    }
+
+    /// Iterate over the address ranges in the index.
+    pub fn addresses(&self) -> GdbIndexAddressIter<R> {
+        GdbIndexAddressIter {
+            input: self.address_area.clone(),
+        }
+    }
+
+    /// Find the index of the unit containing the given address.
+    ///
+    /// If more than one range contains the address, then the range with the
+    /// greatest start address is used.
+    pub fn find_address(&self, address: u64) -> Result<Option<u32>> {
+        Ok(self
+            .sorted_addresses
+            .find(address)
+            .map(|address| address.unit_index))
+    }
+
+    /// Find the units that define the given name.
+    ///
+    /// Returns an empty iterator if the name is not in the index.
+    pub fn find_name(&self, name: &[u8]) -> Result<GdbIndexSymbolIter<R>> {
+        let size = (self.symbol_table.len().into_u64() / 8) as u32;
+        if size == 0 {
+            return Ok(self.symbol_iter(None));
+        }
+        let hash = gdb_index_hash(name);
+        let mask = size - 1;
+        let mut slot = hash & mask;
+        let step = (hash.wrapping_mul(17) & mask) | 1;
+        for _ in 0..size {
+            let mut input = self.symbol_table.clone();
+            input.skip(R::Offset::from_u32(slot * 8))?;
+            let name_offset = input.read_u32()?;
+            let vector_offset = input.read_u32()?;
+            if name_offset == 0 && vector_offset == 0 {
+                break;
+            }
+            if self.name_matches(name_offset, name)? {
+                let vector = self.symbol_vector(vector_offset)?;
+                return Ok(self.symbol_iter(Some(vector)));
+            }
+            slot = (slot + step) & mask;
+        }
+        Ok(self.symbol_iter(None))
+    }
+
+    /// Iterate over all of the names in the index.
+    pub fn names(&self) -> GdbIndexNameIter<'_, R> {
+        GdbIndexNameIter {
+            index: self,
+            input: self.symbol_table.clone(),
+        }
+    }
+
+    fn name_matches(&self, offset: u32, name: &[u8]) -> Result<bool> {
+        let mut input = self.constant_pool.clone();
+        input.skip(R::Offset::from_u32(offset))?;
+        for c in name {
+            if input.read_u8()? != *c {
+                return Ok(false);
+            }
+        }
+        Ok(input.read_u8()? == 0)
+    }
+
+    fn symbol_vector(&self, offset: u32) -> Result<R> {
+        let mut input = self.constant_pool.clone();
+        input.skip(R::Offset::from_u32(offset))?;
+        let count = input.read_u32()?;
+        input.truncate(R::Offset::from_u64(u64::from(count) * 4)?)?;
+        Ok(input)
+    }
+
+    fn symbol_iter(&self, input: Option<R>) -> GdbIndexSymbolIter<R> {
+        GdbIndexSymbolIter {
+            input: input.unwrap_or_else(|| {
+                let mut input = self.constant_pool.clone();
+                input.empty();
+                input
+            }),
+        }
+    }
+}
+
+/// The hash function used for the symbol table of a `.gdb_index` section.
+fn gdb_index_hash(name: &[u8]) -> u32 {
+    let mut hash = 0u32;
+    for c in name {
+        hash = hash
+            .wrapping_mul(67)
+            .wrapping_add(u32::from(c.to_ascii_lowercase()))
+            .wrapping_sub(113);
+    }
+    hash
+}
+
+/// A unit referenced by a `.gdb_index` section.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GdbIndexUnit<T = usize> {
+    /// A compilation unit in the `.debug_info` section.
+    Compilation {
+        /// The offset of the unit header.
+        offset: DebugInfoOffset<T>,
+        /// The length of the unit, including its header.
+        length: u64,
+    },
+    /// A type unit in the `.debug_types` section.
+    Type {
+        /// The offset of the unit header.
+        offset: DebugTypesOffset<T>,
+        /// The offset of the type within the unit.
+        type_offset: UnitOffset<T>,
+        /// The type signature.
+        signature: DebugTypeSignature,
+    },
+}
+
+/// An address range in a `.gdb_index` section.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GdbIndexAddress {
+    /// The address range.
+    pub range: Range,
+    /// The index of the compilation unit containing the range.
+    pub unit_index: u32,
+}
+
+impl RangeIndexEntry for GdbIndexAddress {
+    fn range(&self) -> Range {
+        self.range
+    }
+}
+
+/// An iterator over the address ranges in a `.gdb_index` section.
+#[derive(Debug, Clone)]
+pub struct GdbIndexAddressIter<R: Reader> {
+    input: R,
+}
+
+impl<R: Reader> GdbIndexAddressIter<R> {
+    /// Advance the iterator to the next address range.
+    pub fn next(&mut self) -> Result<Option<GdbIndexAddress>> {
+        if self.input.is_empty() {
+            return Ok(None);
+        }
+        let result = (|| {
+            let begin = self.input.read_u64()?;
+            let end = self.input.read_u64()?;
+            let unit_index = self.input.read_u32()?;
+            Ok(GdbIndexAddress {
+                range: Range { begin, end },
+                unit_index,
+            })
+        })();
+        match result {
+            Ok(address) => Ok(Some(address)),
+            Err(e) => {
+                self.input.empty();
+                Err(e)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "fallible-iterator")]
+impl<R: Reader> fallible_iterator::FallibleIterator for GdbIndexAddressIter<R> {
+    type Item = GdbIndexAddress;
+    type Error = Error;
+
+    fn next(&mut self) -> ::core::result::Result<Option<Self::Item>, Self::Error> {
+        GdbIndexAddressIter::next(self)
+    }
+}
+
+/// The kind of a symbol in a `.gdb_index` section.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GdbIndexSymbolKind {
+    /// The kind is not specified.
+    None,
+    /// A type.
+    Type,
+    /// A variable or enumerator.
+    Variable,
+    /// A function.
+    Function,
+    /// Some other kind of symbol.
+    Other,
+    /// A reserved value.
+    Unknown(u8),
+}
+
+/// A reference from a name in a `.gdb_index` section to a unit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GdbIndexSymbol {
+    /// The index of the unit that defines the symbol.
+    pub unit_index: u32,
+    /// The kind of the symbol.
+    pub kind: GdbIndexSymbolKind,
+    /// Whether the symbol is static (has internal linkage).
+    pub is_static: bool,
+}
+
+impl GdbIndexSymbolKind {
+    pub(crate) fn from_u8(value: u8) -> Self {
+        match value {
+            0 => GdbIndexSymbolKind::None,
+            1 => GdbIndexSymbolKind::Type,
+            2 => GdbIndexSymbolKind::Variable,
+            3 => GdbIndexSymbolKind::Function,
+            4 => GdbIndexSymbolKind::Other,
+            x => GdbIndexSymbolKind::Unknown(x),
+        }
+    }
+}
+
+impl GdbIndexSymbol {
+    fn from_u32(value: u32) -> Self {
+        GdbIndexSymbol {
+            unit_index: value & 0x00ff_ffff,
+            kind: GdbIndexSymbolKind::from_u8(((value >> 28) & 7) as u8),
+            is_static: value & 0x8000_0000 != 0,
+        }
+    }
+}
+
+/// An iterator over the units that define a name in a `.gdb_index` section.
+#[derive(Debug, Clone)]
+pub struct GdbIndexSymbolIter<R: Reader> {
+    input: R,
+}
+
+impl<R: Reader> GdbIndexSymbolIter<R> {
+    /// Advance the iterator to the next unit.
+    pub fn next(&mut self) -> Result<Option<GdbIndexSymbol>> {
+        if self.input.is_empty() {
+            return Ok(None);
+        }
+        match self.input.read_u32() {
+            Ok(value) => Ok(Some(GdbIndexSymbol::from_u32(value))),
+            Err(e) => {
+                self.input.empty();
+                Err(e)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "fallible-iterator")]
+impl<R: Reader> fallible_iterator::FallibleIterator for GdbIndexSymbolIter<R> {
+    type Item = GdbIndexSymbol;
+    type Error = Error;
+
+    fn next(&mut self) -> ::core::result::Result<Option<Self::Item>, Self::Error> {
+        GdbIndexSymbolIter::next(self)
+    }
+}
+
+/// An iterator over the names in a `.gdb_index` section.
+#[derive(Debug, Clone)]
+pub struct GdbIndexNameIter<'index, R: Reader> {
+    index: &'index GdbIndex<R>,
+    input: R,
+}
+
+impl<'index, R: Reader> GdbIndexNameIter<'index, R> {
+    /// Advance the iterator to the next name.
+    ///
+    /// Returns the name and an iterator over the units that define it.
+    pub fn next(&mut self) -> Result<Option<(R, GdbIndexSymbolIter<R>)>> {
+        while !self.input.is_empty() {
+            let result = (|| {
+                let name_offset = self.input.read_u32()?;
+                let vector_offset = self.input.read_u32()?;
+                if name_offset == 0 && vector_offset == 0 {
+                    return Ok(None);
+                }
+                let mut name = self.index.constant_pool.clone();
+                name.skip(R::Offset::from_u32(name_offset))?;
+                let name = name.read_null_terminated_slice()?;
+                let vector = self.index.symbol_vector(vector_offset)?;
+                Ok(Some((name, self.index.symbol_iter(Some(vector)))))
+            })();
+            match result {
+                Ok(Some(name)) => return Ok(Some(name)),
+                Ok(None) => {}
+                Err(e) => {
+                    self.input.empty();
+                    return Err(e);
+                }
+            }
+        }
+        Ok(None)
+    }
+}
+
+#[cfg(feature = "fallible-iterator")]
+impl<'index, R: Reader> fallible_iterator::FallibleIterator for GdbIndexNameIter<'index, R> {
+    type Item = (R, GdbIndexSymbolIter<R>);
+    type Error = Error;
+
+    fn next(&mut self) -> ::core::result::Result<Option<Self::Item>, Self::Error> {
+        GdbIndexNameIter::next(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::endianity::LittleEndian;
+    use alloc::vec;
+    use alloc::vec::Vec;
+    use test_assembler::{Endian, Label, LabelMaker, Section};
+
+    fn symbol_slots(names: &[&[u8]], size: u32) -> Vec<Option<usize>> {
+        let mut slots = vec![None; size as usize];
+        for (i, name) in names.iter().enumerate() {
+            let hash = gdb_index_hash(name);
+            let mut slot = hash & (size - 1);
+            let step = (hash.wrapping_mul(17) & (size - 1)) | 1;
+            while slots[slot as usize].is_some() {
+                slot = (slot + step) & (size - 1);
+            }
+            slots[slot as usize] = Some(i);
+        }
+        slots
+    }
+
+    #[test]
+    fn test_unknown_version() {
+        let buf = [6, 0, 0, 0, 0, 0, 0, 0];
+        match GdbIndex::new(&buf, LittleEndian) {
+            Err(Error::UnknownVersion(6)) => {}
+            otherwise => panic!("Unexpected result: {:?}", otherwise),
+        }
+    }
+
+    #[test]
+    fn test_gdb_index() {
+        for version in [8, 9] {
+            test_gdb_index_version(version);
+        }
+    }
+
+    fn test_gdb_index_version(version: u32) {
+        let cu_list = Label::new();
+        let tu_list = Label::new();
+        let address_area = Label::new();
+        let symbol_table = Label::new();
+        let shortcut_table = Label::new();
+        let constant_pool = Label::new();
+
+        let names: [&[u8]; 2] = [b"main", b"foo"];
+        // Offsets within the constant pool.
+        let vectors = [0, 8];
+        let name_offsets = [20, 25];
+
+        #[rustfmt::skip]
+        let mut section = Section::with_endian(Endian::Little)
+            // Header.
+            .D32(version)
+            .D32(&cu_list)
+            .D32(&tu_list)
+            .D32(&address_area)
+            .D32(&symbol_table);
+        if version >= 9 {
+            section = section.D32(&shortcut_table);
+        }
+        #[rustfmt::skip]
+        let mut section = section
+            .D32(&constant_pool)
+            // CU list.
+            .mark(&cu_list)
+            .D64(0).D64(0x30)
+            .D64(0x30).D64(0x40)
+            // TU list.
+            .mark(&tu_list)
+            .D64(0x10).D64(0x1d).D64(0x0123_4567_89ab_cdef)
+            // Address area.
+            .mark(&address_area)
+            .D64(0x2000).D64(0x2080).D32(1)
+            .D64(0x1000).D64(0x1100).D32(0)
+            .D64(0x1040).D64(0x1080).D32(1)
+            // Symbol table.
+            .mark(&symbol_table);
+        for slot in symbol_slots(&names, 4) {
+            section = match slot {
+                Some(i) => section.D32(name_offsets[i]).D32(vectors[i]),
+                None => section.D32(0).D32(0),
+            };
+        }
+        if version >= 9 {
+            // The shortcut table is larger than the symbol table, so the size
+            // of the symbol table can't be derived from the constant pool offset.
+            section = section.mark(&shortcut_table).append_repeated(0, 40);
+        }
+        #[rustfmt::skip]
+        let section = section
+            // Constant pool.
+            .mark(&constant_pool)
+            .D32(1)
+            .D32(0x3000_0000)
+            .D32(2)
+            .D32(0xa000_0001)
+            .D32(0x1000_0002)
+            .append_bytes(b"main\0")
+            .append_bytes(b"foo\0");
+        section.start().set_const(0);
+        let buf = section.get_contents().unwrap();
+        let index = GdbIndex::new(&buf, LittleEndian).unwrap();
+
+        assert_eq!(index.version(), version);
+        assert_eq!(index.cu_count(), 2);
+        assert_eq!(index.tu_count(), 1);
+        assert_eq!(
+            index.unit(1),
+            Ok(GdbIndexUnit::Compilation {
+                offset: DebugInfoOffset(0x30),
+                length: 0x40,
+            })
+        );
+        assert_eq!(
+            index.unit(2),
+            Ok(GdbIndexUnit::Type {
+                offset: DebugTypesOffset(0x10),
+                type_offset: UnitOffset(0x1d),
+                signature: DebugTypeSignature(0x0123_4567_89ab_cdef),
+            })
+        );
+        assert_eq!(index.unit(3), Err(Error::InvalidGdbIndex));
+
+        assert_eq!(index.find_address(0x1000), Ok(Some(0)));
+        assert_eq!(index.find_address(0x1040), Ok(Some(1)));
+        assert_eq!(index.find_address(0x1080), Ok(Some(0)));
+        assert_eq!(index.find_address(0x207f), Ok(Some(1)));
+        assert_eq!(index.find_address(0x1100), Ok(None));
+        assert_eq!(index.find_address(0xfff), Ok(None));
+
+        let mut symbols = index.find_name(b"main").unwrap();
+        assert_eq!(
+            symbols.next(),
+            Ok(Some(GdbIndexSymbol {
+                unit_index: 0,
+                kind: GdbIndexSymbolKind::Function,
+                is_static: false,
+            }))
+        );
+        assert_eq!(symbols.next(), Ok(None));
+
+        // The hash is case insensitive, but the name comparison is not.
+        let mut symbols = index.find_name(b"FOO").unwrap();
+        assert_eq!(symbols.next(), Ok(None));
+
+        let mut symbols = index.find_name(b"foo").unwrap();
+        assert_eq!(
+            symbols.next(),
+            Ok(Some(GdbIndexSymbol {
+                unit_index: 1,
+                kind: GdbIndexSymbolKind::Variable,
+                is_static: true,
+            }))
+        );
+        assert_eq!(
+            symbols.next(),
+            Ok(Some(GdbIndexSymbol {
+                unit_index: 2,
+                kind: GdbIndexSymbolKind::Type,
+                is_static: false,
+            }))
+        );
+        assert_eq!(symbols.next(), Ok(None));
+
+        let mut symbols = index.find_name(b"missing").unwrap();
+        assert_eq!(symbols.next(), Ok(None));
+
+        let mut count = 0;
+        let mut all = index.names();
+        while let Some((name, _)) = all.next().unwrap() {
+            assert!(names.contains(&name.slice()));
+            count += 1;
+        }
+        assert_eq!(count, 2);
+    }
+}
@@ -0,0 +1,312 @@
+use core::marker::PhantomData;
+
+use crate::common::{DebugInfoOffset, SectionId};
+use crate::endianity::Endianity;
+use crate::read::lookup::{
+    DebugLookup, LookupEntryIter, LookupParser, PubStuffHeader, PubStuffParser,
+};
+use crate::read::{
+    EndianSlice, GdbIndexSymbolKind, PubNamesEntry, Reader, ReaderOffset, Result, ResultIterator,
+    Section, UnitOffset,
+};
+
+/// A single parsed entry from a `.debug_gnu_pubnames` or `.debug_gnu_pubtypes` section.
+///
+/// These entries are the same as those in the `.debug_pubnames` and `.debug_pubtypes`
+/// sections, except that they also contain a byte of flags that describe the
+/// kind of the name. These flags use the same encoding as the `.gdb_index` section.
+#[derive(Debug, Clone)]
+pub struct GnuPubEntry<R: Reader> {
+    unit_header_offset: DebugInfoOffset<R::Offset>,
+    die_offset: UnitOffset<R::Offset>,
+    flags: u8,
+    name: R,
+}
+
+impl<R: Reader> GnuPubEntry<R> {
+    /// Returns the name this entry refers to.
+    pub fn name(&self) -> &R {
+        &self.name
+    }
+
+    /// Returns the offset into the .debug_info section for the header of the compilation unit
+    /// which contains this name.
+    ///
+    /// When using split DWARF, this is the skeleton compilation unit, and the
+    /// DIE offset refers to a DIE in the corresponding split compilation unit.
+    pub fn unit_header_offset(&self) -> DebugInfoOffset<R::Offset> {
+        self.unit_header_offset
+    }
+
+    /// Returns the offset into the compilation unit for the debugging information entry which
+    /// has this name.
+    pub fn die_offset(&self) -> UnitOffset<R::Offset> {
+        self.die_offset
+    }
+
+    /// Returns the raw flags byte for this entry.
+    pub fn flags(&self) -> u8 {
+        self.flags
+    }
+
+    /// Returns the kind of the entity that has this name.
+    pub fn kind(&self) -> GdbIndexSymbolKind {
+        GdbIndexSymbolKind::from_u8((self.flags >> 4) & 7)
+    }
+
+    /// Returns true if the entity that has this name is static (has internal linkage).
+    pub fn is_static(&self) -> bool {
+        self.flags & 0x80 != 0
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct GnuPubStuffParser<R: Reader> {
+    // This struct is never instantiated.
+    phantom: PhantomData<R>,
+}
+
+impl<R: Reader> LookupParser<R> for GnuPubStuffParser<R> {
+    type Header = PubStuffHeader<R::Offset>;
+    type Entry = GnuPubEntry<R>;
+
+    fn parse_header(input: &mut R) -> Result<(R, Self::Header)> {
+        PubStuffParser::<R, PubNamesEntry<R>>::parse_header(input)
+    }
+
+    /// Parse a single entry. Return `None` for the null entry, `Some` for an actual entry.
+    fn parse_entry(input: &mut R, header: &Self::Header) -> Result<Option<Self::Entry>> {
+        let offset = input.read_offset(header.format())?;
+        if offset.into_u64() == 0 {
+            input.empty();
+            Ok(None)
+        } else {
+            let flags = input.read_u8()?;
+            let name = input.read_null_terminated_slice()?;
+            Ok(Some(GnuPubEntry {
+                unit_header_offset: header.unit_offset(),
+                die_offset: UnitOffset(offset),
+                flags,
+                name,
+            }))
+        }
+    }
+}
+
+/// The `DebugGnuPubNames` struct represents the GNU public names information
+/// found in the `.debug_gnu_pubnames` section.
+///
+/// This section is generated instead of `.debug_pubnames` when using split DWARF
+/// with GCC's `-ggnu-pubnames` option, or Clang's `-gsplit-dwarf`.
+#[derive(Debug, Clone)]
+pub struct DebugGnuPubNames<R: Reader>(DebugLookup<R, GnuPubStuffParser<R>>);
+
+impl<'input, Endian> DebugGnuPubNames<EndianSlice<'input, Endian>>
+where
+    Endian: Endianity,
+{
+    /// Construct a new `DebugGnuPubNames` instance from the data in the
+    /// `.debug_gnu_pubnames` section.
+    ///
+    /// It is the caller's responsibility to read the `.debug_gnu_pubnames` section and
+    /// present it as a `&[u8]` slice. That means using some ELF loader on
+    /// Linux, a Mach-O loader on macOS, etc.
+    ///
+    /// ```
+    /// use gimli::{DebugGnuPubNames, LittleEndian};
+    ///
+    /// # let buf = [];
+    /// # let read_debug_gnu_pubnames_section_somehow = || &buf;
+    /// let debug_gnu_pubnames =
+    ///     DebugGnuPubNames::new(read_debug_gnu_pubnames_section_somehow(), LittleEndian);
+    /// ```
+    pub fn new(debug_gnu_pubnames_section: &'input [u8], endian: Endian) -> Self {
+        Self::from(EndianSlice::new(debug_gnu_pubnames_section, endian))
+    }
+}
+
+impl<R: Reader> DebugGnuPubNames<R> {
+    /// Iterate the entries in the `.debug_gnu_pubnames` section.
+    ///
+    /// ```
+    /// use gimli::{DebugGnuPubNames, EndianSlice, LittleEndian};
+    ///
+    /// # let buf = [];
+    /// # let read_debug_gnu_pubnames_section_somehow = || &buf;
+    /// let debug_gnu_pubnames =
+    ///     DebugGnuPubNames::new(read_debug_gnu_pubnames_section_somehow(), LittleEndian);
+    ///
+    /// let mut iter = debug_gnu_pubnames.items();
+    /// while let Some(pubname) = iter.next().unwrap() {
+    ///   println!("pubname {} found!", pubname.name().to_string_lossy());
+    /// }
+    /// ```
+    pub fn items(&self) -> GnuPubEntryIter<R> {
+        GnuPubEntryIter(self.0.items())
+    }
+}
+
+impl<R: Reader> Section<R> for DebugGnuPubNames<R> {
+    fn id() -> SectionId {
+        SectionId::DebugGnuPubNames
+    }
+
+    fn reader(&self) -> &R {
+        self.0.reader()
+    }
+}
+
+impl<R: Reader> From<R> for DebugGnuPubNames<R> {
+    fn from(debug_gnu_pubnames_section: R) -> Self {
+        DebugGnuPubNames(DebugLookup::from(debug_gnu_pubnames_section))
+    }
+}
+
+/// The `DebugGnuPubTypes` struct represents the GNU public types information
+/// found in the `.debug_gnu_pubtypes` section.
+///
+/// This section is generated instead of `.debug_pubtypes` when using split DWARF
+/// with GCC's `-ggnu-pubnames` option, or Clang's `-gsplit-dwarf`.
+#[derive(Debug, Clone)]
+pub struct DebugGnuPubTypes<R: Reader>(DebugLookup<R, GnuPubStuffParser<R>>);
+
+impl<'input, Endian> DebugGnuPubTypes<EndianSlice<'input, Endian>>
+where
+    Endian: Endianity,
+{
+    /// Construct a new `DebugGnuPubTypes` instance from the data in the
+    /// `.debug_gnu_pubtypes` section.
+    ///
+    /// It is the caller's responsibility to read the `.debug_gnu_pubtypes` section and
+    /// present it as a `&[u8]` slice. That means using some ELF loader on
+    /// Linux, a Mach-O loader on macOS, etc.
+    ///
+    /// ```
+    /// use gimli::{DebugGnuPubTypes, LittleEndian};
+    ///
+    /// # let buf = [];
+    /// # let read_debug_gnu_pubtypes_section_somehow = || &buf;
+    /// let debug_gnu_pubtypes =
+    ///     DebugGnuPubTypes::new(read_debug_gnu_pubtypes_section_somehow(), LittleEndian);
+    /// ```
+    pub fn new(debug_gnu_pubtypes_section: &'input [u8], endian: Endian) -> Self {
+        Self::from(EndianSlice::new(debug_gnu_pubtypes_section, endian))
+    }
+}
+
+impl<R: Reader> DebugGnuPubTypes<R> {
+    /// Iterate the entries in the `.debug_gnu_pubtypes` section.
+    pub fn items(&self) -> GnuPubEntryIter<R> {
+        GnuPubEntryIter(self.0.items())
+    }
+}
+
+impl<R: Reader> Section<R> for DebugGnuPubTypes<R> {
+    fn id() -> SectionId {
+        SectionId::DebugGnuPubTypes
+    }
+
+    fn reader(&self) -> &R {
+        self.0.reader()
+    }
+}
+
+impl<R: Reader> From<R> for DebugGnuPubTypes<R> {
+    fn from(debug_gnu_pubtypes_section: R) -> Self {
+        DebugGnuPubTypes(DebugLookup::from(debug_gnu_pubtypes_section))
+    }
+}
+
+/// An iterator over the entries from a `.debug_gnu_pubnames` or `.debug_gnu_pubtypes`
+/// section.
+///
+/// Can be [used with
+/// `FallibleIterator`](./index.html#using-with-fallibleiterator).
+#[derive(Debug, Clone)]
+pub struct GnuPubEntryIter<R: Reader>(LookupEntryIter<R, GnuPubStuffParser<R>>);
+
+impl<R: Reader> GnuPubEntryIter<R> {
+    /// Advance the iterator and return the next entry.
+    ///
+    /// Returns the newly parsed entry as `Ok(Some(entry))`. Returns
+    /// `Ok(None)` when iteration is complete and all entries have already been
+    /// parsed and yielded. If an error occurs while parsing the next entry,
+    /// then this error is returned as `Err(e)`, and all subsequent calls return
+    /// `Ok(None)`.
+    pub fn next(&mut self) -> Result<Option<GnuPubEntry<R>>> {
+        self.0.next()
+    }
+
+    /// Return an adapter that implements `Iterator`.
+    ///
+    /// See [`ResultIterator`] for more information.
+    pub fn iterator(self) -> ResultIterator<Self> {
+        ResultIterator::new(self)
+    }
+}
+
+#[cfg(feature = "fallible-iterator")]
+impl<R: Reader> fallible_iterator::FallibleIterator for GnuPubEntryIter<R> {
+    type Item = GnuPubEntry<R>;
+    type Error = crate::read::Error;
+
+    fn next(&mut self) -> ::core::result::Result<Option<Self::Item>, Self::Error> {
+        self.0.next()
+    }
+}
+
+impl<R: Reader> Iterator for ResultIterator<GnuPubEntryIter<R>> {
+    type Item = Result<GnuPubEntry<R>>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_with(GnuPubEntryIter::next)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::Format;
+    use crate::endianity::LittleEndian;
+    use crate::test_util::GimliSectionMethods;
+    use test_assembler::{Endian, Label, LabelMaker, Section};
+
+    #[test]
+    fn test_gnu_pubnames() {
+        let length = Label::new();
+        let start = Label::new();
+        let end = Label::new();
+        #[rustfmt::skip]
+        let section = Section::with_endian(Endian::Little)
+            .initial_length(Format::Dwarf32, &length, &start)
+            .D16(2)
+            .D32(0x40)
+            .D32(0x30)
+            .D32(0x1c).D8(0x30).append_bytes(b"main\0")
+            .D32(0x2a).D8(0xa0).append_bytes(b"counter\0")
+            .D32(0)
+            .mark(&end);
+        length.set_const((&end - &start) as u64);
+        let buf = section.get_contents().unwrap();
+
+        let debug_gnu_pubnames = DebugGnuPubNames::new(&buf, LittleEndian);
+        let mut items = debug_gnu_pubnames.items();
+
+        let entry = items.next().unwrap().unwrap();
+        assert_eq!(entry.name().slice(), b"main");
+        assert_eq!(entry.unit_header_offset(), DebugInfoOffset(0x40));
+        assert_eq!(entry.die_offset(), UnitOffset(0x1c));
+        assert_eq!(entry.kind(), GdbIndexSymbolKind::Function);
+        assert!(!entry.is_static());
+
+        let entry = items.next().unwrap().unwrap();
+        assert_eq!(entry.name().slice(), b"counter");
+        assert_eq!(entry.die_offset(), UnitOffset(0x2a));
+        assert_eq!(entry.flags(), 0xa0);
+        assert_eq!(entry.kind(), GdbIndexSymbolKind::Variable);
+        assert!(entry.is_static());
+
+        assert!(items.next().unwrap().is_none());
+    }
+}
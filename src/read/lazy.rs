@@ -0,0 +1,309 @@
+use alloc::sync::Arc;
+#[cfg(any(test, not(feature = "std")))]
+use core::cell::UnsafeCell;
+use core::fmt;
+use core::marker::PhantomData;
+use core::mem;
+#[cfg(any(test, not(feature = "std")))]
+use core::ops::{Deref, DerefMut};
+use core::ptr;
+#[cfg(any(test, not(feature = "std")))]
+use core::sync::atomic::AtomicBool;
+use core::sync::atomic::{AtomicPtr, Ordering};
+
+/// A value that is computed on first use, and then shared.
+///
+/// This is similar to `OnceLock<Arc<T>>`, but is available in `no_std`.
+pub(crate) struct LazyArc<T> {
+    // Only written once with a value obtained from `Arc<T>::into_raw`.
+    // This holds a ref count for the `Arc`, so it is always safe to
+    // clone the `Arc` given a reference to the `LazyArc`.
+    value: AtomicPtr<T>,
+    phantom: PhantomData<Arc<T>>,
+}
+
+impl<T> Default for LazyArc<T> {
+    fn default() -> Self {
+        LazyArc {
+            value: AtomicPtr::new(ptr::null_mut()),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<T> fmt::Debug for LazyArc<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let initialized = !self.value.load(Ordering::Acquire).is_null();
+        f.debug_struct("LazyArc")
+            .field("initialized", &initialized)
+            .finish()
+    }
+}
+
+impl<T> Drop for LazyArc<T> {
+    fn drop(&mut self) {
+        let value_ptr = self.value.load(Ordering::Acquire);
+        if !value_ptr.is_null() {
+            // SAFETY: all writes to `self.value` are pointers obtained from `Arc::into_raw`.
+            drop(unsafe { Arc::from_raw(value_ptr) });
+        }
+    }
+}
+
+impl<T> LazyArc<T> {
+    /// Return the value, computing it with `f` if it has not been computed yet.
+    ///
+    /// If multiple threads race to compute the value, then only one of the
+    /// computed values is kept. Errors are not cached.
+    pub(crate) fn get<E, F: FnOnce() -> Result<T, E>>(&self, f: F) -> Result<Arc<T>, E> {
+        // Clone an `Arc` given a pointer obtained from `Arc::into_raw`.
+        // SAFETY: `value_ptr` must be a valid pointer obtained from `Arc<T>::into_raw`.
+        unsafe fn clone_arc_ptr<T>(value_ptr: *const T) -> Arc<T> {
+            let value = Arc::from_raw(value_ptr);
+            let clone = Arc::clone(&value);
+            mem::forget(value);
+            clone
+        }
+
+        // Return the existing value if already computed.
+        // `Ordering::Acquire` is needed so that the content of the loaded `Arc` is
+        // visible to this thread.
+        let value_ptr = self.value.load(Ordering::Acquire);
+        if !value_ptr.is_null() {
+            // SAFETY: all writes to `self.value` are pointers obtained from `Arc::into_raw`.
+            return Ok(unsafe { clone_arc_ptr(value_ptr) });
+        }
+
+        // Race to compute and set the value.
+        let value = f().map(Arc::new)?;
+        let value_ptr = Arc::into_raw(value);
+        match self.value.compare_exchange(
+            ptr::null_mut(),
+            value_ptr as *mut T,
+            Ordering::AcqRel,
+            Ordering::Acquire,
+        ) {
+            Ok(_) => {
+                // Return the value we computed.
+                // SAFETY: `value_ptr` was obtained from `Arc::into_raw`.
+                Ok(unsafe { clone_arc_ptr(value_ptr) })
+            }
+            Err(existing_value_ptr) => {
+                // We lost the race, drop unneeded `value_ptr`.
+                // SAFETY: `value_ptr` was obtained from `Arc::into_raw`.
+                drop(unsafe { Arc::from_raw(value_ptr) });
+                // Return the existing value.
+                // SAFETY: all writes to `self.value` are pointers obtained from `Arc::into_raw`.
+                Ok(unsafe { clone_arc_ptr(existing_value_ptr) })
+            }
+        }
+    }
+
+    /// Return a reference to the value, computing it with `f` if it has not been computed yet.
+    ///
+    /// See `get` for more information.
+    pub(crate) fn get_ref<E, F: FnOnce() -> Result<T, E>>(&self, f: F) -> Result<&T, E> {
+        let value = self.get(f)?;
+        // SAFETY: `self.value` is never changed after it is set, and it holds a
+        // ref count for the `Arc` until `self` is dropped.
+        Ok(unsafe { &*Arc::as_ptr(&value) })
+    }
+
+    /// Return a reference to the value if it has been computed.
+    pub(crate) fn peek(&self) -> Option<&T> {
+        let value_ptr = self.value.load(Ordering::Acquire);
+        if value_ptr.is_null() {
+            return None;
+        }
+        // SAFETY: all writes to `self.value` are pointers obtained from `Arc::into_raw`,
+        // and it holds a ref count for the `Arc` until `self` is dropped.
+        Some(unsafe { &*value_ptr })
+    }
+}
+
+/// A lock for a cache that is shared between threads.
+///
+/// With the `std` feature, this is a `std::sync::Mutex`, and `lock` waits for
+/// other threads to release the lock.
+///
+/// Without the `std` feature, there is no way to wait for the lock, so this is
+/// a `TryLock`, and `lock` returns `None` while another thread holds the lock.
+/// Callers must handle this, for example by skipping an optional cache update.
+#[derive(Default)]
+pub(crate) struct CacheLock<T> {
+    #[cfg(feature = "std")]
+    value: std::sync::Mutex<T>,
+    #[cfg(not(feature = "std"))]
+    value: TryLock<T>,
+}
+
+/// The guard returned by `CacheLock::lock`.
+#[cfg(feature = "std")]
+pub(crate) type CacheLockGuard<'a, T> = std::sync::MutexGuard<'a, T>;
+/// The guard returned by `CacheLock::lock`.
+#[cfg(not(feature = "std"))]
+pub(crate) type CacheLockGuard<'a, T> = TryLockGuard<'a, T>;
+
+impl<T: fmt::Debug> fmt::Debug for CacheLock<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.value.fmt(f)
+    }
+}
+
+impl<T> CacheLock<T> {
+    /// Acquire the lock.
+    ///
+    /// This only returns `None` if the `std` feature is disabled and another
+    /// thread holds the lock.
+    #[cfg(feature = "std")]
+    pub(crate) fn lock(&self) -> Option<CacheLockGuard<'_, T>> {
+        // A panic while the lock was held can't leave the cache in a state
+        // that is unsafe to use, so ignore poisoning.
+        Some(
+            self.value
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner),
+        )
+    }
+
+    /// Acquire the lock.
+    ///
+    /// This only returns `None` if the `std` feature is disabled and another
+    /// thread holds the lock.
+    #[cfg(not(feature = "std"))]
+    pub(crate) fn lock(&self) -> Option<CacheLockGuard<'_, T>> {
+        self.value.try_lock()
+    }
+
+    /// Return a mutable reference to the value.
+    ///
+    /// This never fails, since the mutable borrow means the lock can't be held.
+    pub(crate) fn get_mut(&mut self) -> &mut T {
+        #[cfg(feature = "std")]
+        let value = self
+            .value
+            .get_mut()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        #[cfg(not(feature = "std"))]
+        let value = self.value.get_mut();
+        value
+    }
+}
+
+/// A lock that never blocks.
+///
+/// This is similar to `Mutex<T>`, but is available in `no_std`. Callers must
+/// handle failing to acquire the lock, for example by skipping an optional cache
+/// update, since there is no way to wait for the lock to be released.
+#[cfg(any(test, not(feature = "std")))]
+#[derive(Default)]
+pub(crate) struct TryLock<T> {
+    locked: AtomicBool,
+    value: UnsafeCell<T>,
+}
+
+// SAFETY: the lock ensures that only one thread can access the value at a time.
+#[cfg(any(test, not(feature = "std")))]
+unsafe impl<T: Send> Send for TryLock<T> {}
+#[cfg(any(test, not(feature = "std")))]
+unsafe impl<T: Send> Sync for TryLock<T> {}
+
+#[cfg(any(test, not(feature = "std")))]
+impl<T: fmt::Debug> fmt::Debug for TryLock<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut f = f.debug_struct("TryLock");
+        match self.try_lock() {
+            Some(value) => f.field("value", &*value),
+            None => f.field("value", &"<locked>"),
+        };
+        f.finish()
+    }
+}
+
+#[cfg(any(test, not(feature = "std")))]
+impl<T> TryLock<T> {
+    /// Acquire the lock, or return `None` if it is already held.
+    pub(crate) fn try_lock(&self) -> Option<TryLockGuard<'_, T>> {
+        self.locked
+            .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .ok()
+            .map(|_| TryLockGuard { lock: self })
+    }
+
+    /// Return a mutable reference to the value.
+    ///
+    /// This never fails, since the mutable borrow means the lock can't be held.
+    pub(crate) fn get_mut(&mut self) -> &mut T {
+        self.value.get_mut()
+    }
+}
+
+/// The guard returned by `TryLock::try_lock`.
+#[cfg(any(test, not(feature = "std")))]
+pub(crate) struct TryLockGuard<'a, T> {
+    lock: &'a TryLock<T>,
+}
+
+#[cfg(any(test, not(feature = "std")))]
+impl<'a, T> Deref for TryLockGuard<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // SAFETY: the guard holds the lock.
+        unsafe { &*self.lock.value.get() }
+    }
+}
+
+#[cfg(any(test, not(feature = "std")))]
+impl<'a, T> DerefMut for TryLockGuard<'a, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        // SAFETY: the guard holds the lock.
+        unsafe { &mut *self.lock.value.get() }
+    }
+}
+
+#[cfg(any(test, not(feature = "std")))]
+impl<'a, T> Drop for TryLockGuard<'a, T> {
+    fn drop(&mut self) {
+        // `Ordering::Release` is needed so that writes to the value are visible
+        // to the next thread that acquires the lock.
+        self.lock.locked.store(false, Ordering::Release);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lazy_arc() {
+        let lazy = LazyArc::default();
+        assert_eq!(lazy.get(|| Err::<u32, ()>(())), Err(()));
+        assert_eq!(lazy.peek(), None);
+        assert_eq!(*lazy.get(|| Ok::<_, ()>(1)).unwrap(), 1);
+        assert_eq!(*lazy.get(|| Ok::<_, ()>(2)).unwrap(), 1);
+        assert_eq!(lazy.peek(), Some(&1));
+    }
+
+    #[test]
+    fn test_try_lock() {
+        let mut lock = TryLock::<u32>::default();
+        {
+            let mut guard = lock.try_lock().unwrap();
+            *guard += 1;
+            assert!(lock.try_lock().is_none());
+            assert_eq!(format!("{:?}", lock), "TryLock { value: \"<locked>\" }");
+        }
+        assert_eq!(*lock.try_lock().unwrap(), 1);
+        *lock.get_mut() += 1;
+        assert_eq!(format!("{:?}", lock), "TryLock { value: 2 }");
+    }
+
+    #[test]
+    fn test_cache_lock() {
+        let mut lock = CacheLock::<u32>::default();
+        *lock.lock().unwrap() += 1;
+        *lock.get_mut() += 1;
+        assert_eq!(*lock.lock().unwrap(), 2);
+    }
+}
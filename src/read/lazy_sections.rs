@@ -0,0 +1,299 @@
+use alloc::borrow::Cow;
+use core::convert::Infallible;
+use core::fmt;
+use core::ops::Deref;
+
+use crate::common::SectionId;
+use crate::endianity::Endianity;
+use crate::read::lazy::LazyArc;
+use crate::read::{Dwarf, EndianSlice, Reader, ReaderOffsetId, Result, SectionLoadError};
+
+/// DWARF sections that are loaded on first use.
+///
+/// The loader function is only called for a section when a reader for the
+/// section is first used, and the result is then cached. A section that fails
+/// to load is empty, and the error can be obtained with `errors`.
+///
+/// ```rust,no_run
+/// # fn example() {
+/// # let loader = |id: gimli::SectionId| -> Result<Vec<u8>, gimli::Error> { unimplemented!() };
+/// let sections = gimli::LazyDwarfSections::new(loader);
+/// let dwarf = sections.borrow(gimli::LittleEndian);
+/// // Only the sections that are needed for the units are loaded.
+/// let mut units = dwarf.units();
+/// while let Ok(Some(header)) = units.next() {
+///     // ...
+/// }
+/// for error in sections.errors() {
+///     eprintln!("{}", error);
+/// }
+/// # }
+/// ```
+pub struct LazyDwarfSections<T, E, F> {
+    loader: F,
+    sections: [LazyArc<core::result::Result<T, E>>; SectionId::ALL.len()],
+}
+
+impl<T, E, F> fmt::Debug for LazyDwarfSections<T, E, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LazyDwarfSections")
+            .field("sections", &self.sections)
+            .finish()
+    }
+}
+
+impl<T, E, F> LazyDwarfSections<T, E, F>
+where
+    F: Fn(SectionId) -> core::result::Result<T, E>,
+{
+    /// Create a new `LazyDwarfSections` using the given loader function.
+    ///
+    /// `loader` loads a DWARF section from the object file.
+    /// It should return an empty section if the section does not exist.
+    pub fn new(loader: F) -> Self {
+        LazyDwarfSections {
+            loader,
+            sections: Default::default(),
+        }
+    }
+
+    /// Return the data for the given section, loading it if needed.
+    pub fn section(&self, id: SectionId) -> core::result::Result<&T, SectionLoadError<&E>> {
+        let result = self.sections[id as usize]
+            .get_ref(|| Ok::<_, Infallible>((self.loader)(id)))
+            .unwrap_or_else(|never| match never {});
+        result
+            .as_ref()
+            .map_err(|error| SectionLoadError { section: id, error })
+    }
+
+    /// Return the errors for the sections that have failed to load so far.
+    pub fn errors(&self) -> impl Iterator<Item = SectionLoadError<&E>> {
+        SectionId::ALL
+            .iter()
+            .filter_map(move |&id| match self.sections[id as usize].peek() {
+                Some(Err(error)) => Some(SectionLoadError { section: id, error }),
+                _ => None,
+            })
+    }
+}
+
+impl<T, E, F> LazyDwarfSections<T, E, F>
+where
+    T: Deref<Target = [u8]>,
+    F: Fn(SectionId) -> core::result::Result<T, E>,
+{
+    /// Create a `Dwarf` structure with readers that load their section on
+    /// first use.
+    pub fn borrow<Endian: Endianity>(
+        &self,
+        endian: Endian,
+    ) -> Dwarf<LazySectionReader<'_, Endian>> {
+        let sections = Dwarf::load(|id| -> core::result::Result<_, Infallible> {
+            Ok(LazySectionReader {
+                sections: self,
+                id,
+                endian,
+                slice: None,
+            })
+        });
+        match sections {
+            Ok(sections) => sections,
+            Err(never) => match never {},
+        }
+    }
+}
+
+/// The data for a section of `LazyDwarfSections`.
+trait LazySectionData {
+    fn data(&self, id: SectionId) -> &[u8];
+}
+
+impl<T, E, F> LazySectionData for LazyDwarfSections<T, E, F>
+where
+    T: Deref<Target = [u8]>,
+    F: Fn(SectionId) -> core::result::Result<T, E>,
+{
+    fn data(&self, id: SectionId) -> &[u8] {
+        match self.section(id) {
+            Ok(data) => data,
+            Err(_) => &[],
+        }
+    }
+}
+
+/// A `Reader` for a section of `LazyDwarfSections`.
+///
+/// The section is loaded when the reader is first used. After that, this is
+/// the same as an `EndianSlice` of the section data.
+#[derive(Clone, Copy)]
+pub struct LazySectionReader<'a, Endian: Endianity> {
+    sections: &'a dyn LazySectionData,
+    id: SectionId,
+    endian: Endian,
+    slice: Option<EndianSlice<'a, Endian>>,
+}
+
+impl<'a, Endian: Endianity> fmt::Debug for LazySectionReader<'a, Endian> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LazySectionReader")
+            .field("section", &self.id)
+            .field("slice", &self.slice)
+            .finish()
+    }
+}
+
+impl<'a, Endian: Endianity> LazySectionReader<'a, Endian> {
+    /// Return the identifier of the section.
+    #[inline]
+    pub fn section_id(&self) -> SectionId {
+        self.id
+    }
+
+    /// Return the remaining data, loading the section if needed.
+    #[inline]
+    pub fn slice(&self) -> EndianSlice<'a, Endian> {
+        match self.slice {
+            Some(slice) => slice,
+            None => EndianSlice::new(self.sections.data(self.id), self.endian),
+        }
+    }
+
+    #[inline]
+    fn slice_mut(&mut self) -> &mut EndianSlice<'a, Endian> {
+        let slice = self.slice();
+        self.slice.get_or_insert(slice)
+    }
+}
+
+impl<'a, Endian: Endianity> Reader for LazySectionReader<'a, Endian> {
+    type Endian = Endian;
+    type Offset = usize;
+
+    #[inline]
+    fn endian(&self) -> Endian {
+        self.endian
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.slice().len()
+    }
+
+    #[inline]
+    fn empty(&mut self) {
+        self.slice_mut().empty();
+    }
+
+    #[inline]
+    fn truncate(&mut self, len: usize) -> Result<()> {
+        self.slice_mut().truncate(len)
+    }
+
+    #[inline]
+    fn offset_from(&self, base: &Self) -> usize {
+        Reader::offset_from(&self.slice(), &base.slice())
+    }
+
+    #[inline]
+    fn offset_id(&self) -> ReaderOffsetId {
+        self.slice().offset_id()
+    }
+
+    #[inline]
+    fn lookup_offset_id(&self, id: ReaderOffsetId) -> Option<usize> {
+        self.slice().lookup_offset_id(id)
+    }
+
+    #[inline]
+    fn find(&self, byte: u8) -> Result<usize> {
+        Reader::find(&self.slice(), byte)
+    }
+
+    #[inline]
+    fn skip(&mut self, len: usize) -> Result<()> {
+        self.slice_mut().skip(len)
+    }
+
+    #[inline]
+    fn split(&mut self, len: usize) -> Result<Self> {
+        let slice = self.slice_mut().split(len)?;
+        Ok(LazySectionReader {
+            slice: Some(slice),
+            ..*self
+        })
+    }
+
+    #[inline]
+    fn to_slice(&self) -> Result<Cow<'_, [u8]>> {
+        Ok(self.slice().slice().into())
+    }
+
+    #[inline]
+    fn to_string(&self) -> Result<Cow<'_, str>> {
+        Ok(self.slice().to_string()?.into())
+    }
+
+    #[inline]
+    fn to_string_lossy(&self) -> Result<Cow<'_, str>> {
+        Ok(self.slice().to_string_lossy())
+    }
+
+    #[inline]
+    fn read_slice(&mut self, buf: &mut [u8]) -> Result<()> {
+        self.slice_mut().read_slice(buf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::endianity::LittleEndian;
+    use crate::read::{Error, Section};
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn test_lazy_dwarf_sections() {
+        let loads = core::cell::Cell::new(0);
+        let sections = LazyDwarfSections::new(|id| -> core::result::Result<Vec<u8>, Error> {
+            loads.set(loads.get() + 1);
+            match id {
+                SectionId::DebugRanges => Err(Error::Io),
+                _ => Ok(vec![id as u8]),
+            }
+        });
+        let dwarf = sections.borrow(LittleEndian);
+        assert_eq!(loads.get(), 0);
+
+        let debug_line = dwarf.debug_line.reader();
+        assert_eq!(debug_line.section_id(), SectionId::DebugLine);
+        assert_eq!(
+            debug_line.to_slice().unwrap(),
+            &[SectionId::DebugLine as u8][..]
+        );
+        assert_eq!(loads.get(), 1);
+        assert!(dwarf.ranges.debug_ranges().reader().is_empty());
+        assert_eq!(loads.get(), 2);
+
+        // Results are cached, including errors.
+        let mut debug_line = *dwarf.debug_line.reader();
+        assert_eq!(debug_line.read_u8(), Ok(SectionId::DebugLine as u8));
+        assert!(debug_line.is_empty());
+        assert!(dwarf.ranges.debug_ranges().reader().is_empty());
+        assert_eq!(loads.get(), 2);
+
+        assert_eq!(
+            sections.errors().collect::<Vec<_>>(),
+            [SectionLoadError {
+                section: SectionId::DebugRanges,
+                error: &Error::Io,
+            }]
+        );
+        assert_eq!(
+            sections.section(SectionId::DebugInfo),
+            Ok(&vec![SectionId::DebugInfo as u8])
+        );
+        assert_eq!(loads.get(), 3);
+    }
+}
@@ -9,7 +9,10 @@ use crate::common::{
 };
 use crate::constants;
 use crate::endianity::Endianity;
-use crate::read::{AttributeValue, EndianSlice, Error, Reader, ReaderOffset, Result, Section};
+use crate::read::{
+    AttributeValue, ContextError, EndianSlice, Error, ErrorOperation, Reader, ReaderOffset, Result,
+    ResultIterator, Section, TombstoneFilter, WarningSink,
+};
 
 /// The `DebugLine` struct contains the source location to instruction mapping
 /// found in the `.debug_line` section.
@@ -174,6 +177,9 @@ where
     program: Program,
     row: LineRow,
     instructions: LineInstructions<R>,
+    special_opcodes: SpecialOpcodeTable,
+    // The row that starts the next range returned by `next_range`.
+    pending: Option<LineRow>,
 }
 
 type OneShotLineRows<R, Offset = <R as Reader>::Offset> =
@@ -193,10 +199,13 @@ where
         let instructions = LineInstructions {
             input: program.header().program_buf.clone(),
         };
+        let special_opcodes = SpecialOpcodeTable::new(program.header());
         LineRows {
             program,
             row,
             instructions,
+            special_opcodes,
+            pending: None,
         }
     }
 
@@ -206,10 +215,13 @@ where
     ) -> ResumedLineRows<'program, R, Offset> {
         let row = LineRow::new(program.header());
         let instructions = sequence.instructions.clone();
+        let special_opcodes = SpecialOpcodeTable::new(program.header());
         LineRows {
             program,
             row,
             instructions,
+            special_opcodes,
+            pending: None,
         }
     }
 
@@ -231,31 +243,281 @@ where
     /// Unfortunately, the references mean that this cannot be a
     /// `FallibleIterator`.
     pub fn next_row(&mut self) -> Result<Option<(&LineProgramHeader<R, Offset>, &LineRow)>> {
+        if self.advance()? {
+            Ok(Some((self.header(), &self.row)))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Parse and execute the line number program until up to `max_rows` rows
+    /// have been computed, and append the rows to `rows`.
+    ///
+    /// Returns the number of rows that were appended. If this is less than
+    /// `max_rows`, then the matrix is complete. If there was an error parsing
+    /// an instruction, then `Err(e)` is returned, and the rows before the error
+    /// have been appended.
+    ///
+    /// This is more efficient than calling `next_row` for each row.
+    pub fn next_rows(&mut self, rows: &mut Vec<LineRow>, max_rows: usize) -> Result<usize> {
+        let mut count = 0;
+        while count < max_rows && self.advance()? {
+            rows.push(self.row);
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// Parse and execute the line number program until the next row for which
+    /// `filter` returns true is computed.
+    ///
+    /// Rows that end a sequence are always returned, because they are needed
+    /// to determine the end address of the preceding row. The addresses of
+    /// the skipped rows are effectively included in the range of the
+    /// preceding returned row.
+    pub fn next_row_filtered<F>(
+        &mut self,
+        mut filter: F,
+    ) -> Result<Option<(&LineProgramHeader<R, Offset>, &LineRow)>>
+    where
+        F: FnMut(&LineRow) -> bool,
+    {
+        while self.advance()? {
+            if self.row.end_sequence() || filter(&self.row) {
+                return Ok(Some((self.header(), &self.row)));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Parse and execute the line number program until the next row that is
+    /// a recommended breakpoint location (`is_stmt`) is computed.
+    ///
+    /// See `next_row_filtered` for more information.
+    pub fn next_stmt_row(&mut self) -> Result<Option<(&LineProgramHeader<R, Offset>, &LineRow)>> {
+        self.next_row_filtered(LineRow::is_stmt)
+    }
+
+    /// Return the next range of addresses that have the same file, line and column.
+    ///
+    /// Consecutive rows within a sequence that have the same file, line and
+    /// column are merged into a single range. The row for the range is the
+    /// first of these rows. Empty ranges are skipped, as are rows at the end
+    /// of the program that are not terminated by a `DW_LNE_end_sequence`.
+    ///
+    /// This method should not be mixed with calls to the other methods that
+    /// advance the rows.
+    pub fn next_range(&mut self) -> Result<Option<LineRowRange>> {
+        self.next_range_filtered(|_| true)
+    }
+
+    /// Return the next range of addresses for which the row matches `filter`.
+    ///
+    /// This is the same as `next_range`, except that ranges for which `filter`
+    /// returns false are skipped. The filter is applied to the row for
+    /// the range.
+    pub fn next_range_filtered<F>(&mut self, mut filter: F) -> Result<Option<LineRowRange>>
+    where
+        F: FnMut(&LineRow) -> bool,
+    {
+        let mut first = self.pending.take();
+        while self.advance()? {
+            let row = self.row;
+            let start = match first {
+                Some(start) => start,
+                None => {
+                    if !row.end_sequence() {
+                        first = Some(row);
+                    }
+                    continue;
+                }
+            };
+            if !row.end_sequence()
+                && row.file_index() == start.file_index()
+                && row.line() == start.line()
+                && row.column() == start.column()
+            {
+                continue;
+            }
+            first = if row.end_sequence() { None } else { Some(row) };
+            if start.address() < row.address() && filter(&start) {
+                self.pending = first;
+                return Ok(Some(LineRowRange {
+                    begin: start.address(),
+                    end: row.address(),
+                    row: start,
+                }));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Execute instructions until the next row is computed.
+    ///
+    /// Returns false if there are no more rows.
+    #[inline]
+    fn advance(&mut self) -> Result<bool> {
+        match self.advance_inner() {
+            Ok(is_row) => Ok(is_row),
+            Err((error, _)) => {
+                self.instructions.input.empty();
+                Err(error)
+            }
+        }
+    }
+
+    /// Execute instructions until the next row is computed.
+    ///
+    /// On error, also returns the remaining length of the input at the start
+    /// of the instruction that failed, and leaves the input wherever parsing stopped.
+    #[inline]
+    fn advance_inner(&mut self) -> core::result::Result<bool, (Error, Offset)> {
         // Perform any reset that was required after copying the previous row.
         self.row.reset(self.program.header());
 
         loop {
             // Split the borrow here, rather than calling `self.header()`.
-            match self.instructions.next_instruction(self.program.header()) {
-                Err(err) => return Err(err),
-                Ok(None) => return Ok(None),
-                Ok(Some(instruction)) => {
-                    if self.row.execute(instruction, &mut self.program) {
-                        if self.row.tombstone {
-                            // Perform any reset that was required for the tombstone row.
-                            // Normally this is done when `next_row` is called again, but for
-                            // tombstones we loop immediately.
-                            self.row.reset(self.program.header());
-                        } else {
-                            return Ok(Some((self.header(), &self.row)));
-                        }
+            let header = self.program.header();
+            let input = &mut self.instructions.input;
+            if input.is_empty() {
+                return Ok(false);
+            }
+            let start_len = input.len();
+            let is_row = match input.read_u8() {
+                // Special opcodes are the most common, so handle them without
+                // constructing a `LineInstruction`.
+                Ok(opcode) if opcode >= header.opcode_base => {
+                    self.row
+                        .exec_special(self.special_opcodes.get(opcode), header);
+                    true
+                }
+                Ok(opcode) => match LineInstruction::parse_opcode(header, opcode, input) {
+                    Ok(instruction) => self.row.execute(instruction, &mut self.program),
+                    Err(e) => return Err((e, start_len)),
+                },
+                Err(e) => return Err((e, start_len)),
+            };
+            if is_row {
+                if self.row.tombstone {
+                    // Perform any reset that was required for the tombstone row.
+                    // Normally this is done when `next_row` is called again, but for
+                    // tombstones we loop immediately.
+                    self.row.reset(self.program.header());
+                } else {
+                    return Ok(true);
+                }
+            }
+            // Fall through, parse the next instruction, and see if that
+            // yields a row.
+        }
+    }
+
+    /// Parse and execute the next instructions in the line number program until
+    /// another row in the line number matrix is computed, skipping the remainder
+    /// of any sequence that contains a damaged instruction.
+    ///
+    /// This is the same as `next_row`, except that if an instruction fails to
+    /// parse then the error is reported to `warnings`, and decoding resumes
+    /// after the next `DW_LNE_end_sequence` instruction, or at the end of the
+    /// program if there is none. The state machine is reset, so that the
+    /// following sequences are decoded normally. The location of the reported
+    /// error is the offset of the damaged instruction in `.debug_line`.
+    ///
+    /// The search for `DW_LNE_end_sequence` is done by looking for its encoding,
+    /// because the instructions following the damaged instruction may not be
+    /// able to be parsed. This may occasionally match an operand of another
+    /// instruction instead.
+    pub fn next_row_lenient(
+        &mut self,
+        warnings: &mut dyn WarningSink,
+    ) -> Option<(&LineProgramHeader<R, Offset>, &LineRow)> {
+        loop {
+            match self.advance_inner() {
+                Ok(true) => return Some((self.header(), &self.row)),
+                Ok(false) => return None,
+                Err((error, start_len)) => {
+                    warnings.warning(self.instruction_error(error, start_len));
+                    let header = self.program.header();
+                    let input = &mut self.instructions.input;
+                    // Skip to the end of `0, 1, DW_LNE_end_sequence`, or the end of
+                    // the program.
+                    let len = input
+                        .to_slice()
+                        .ok()
+                        .and_then(|data| data.windows(3).position(|x| x == [0, 1, 1]))
+                        .and_then(|pos| R::Offset::from_u64(pos as u64 + 3).ok());
+                    if len.map_or(true, |len| input.skip(len).is_err()) {
+                        input.empty();
                     }
-                    // Fall through, parse the next instruction, and see if that
-                    // yields a row.
+                    self.row = LineRow::new(header);
                 }
             }
         }
     }
+
+    /// Parse and execute the next instructions in the line number program until
+    /// another row in the line number matrix is computed, adding the location
+    /// of the instruction to any error.
+    ///
+    /// This is the same as `next_row`, except that an error is returned as a
+    /// `ContextError` containing the offset in `.debug_line` of the instruction
+    /// that failed to parse.
+    #[allow(clippy::type_complexity)]
+    pub fn next_row_with_context(
+        &mut self,
+    ) -> core::result::Result<Option<(&LineProgramHeader<R, Offset>, &LineRow)>, ContextError> {
+        match self.advance_inner() {
+            Ok(true) => Ok(Some((self.header(), &self.row))),
+            Ok(false) => Ok(None),
+            Err((error, start_len)) => {
+                let error = self.instruction_error(error, start_len);
+                self.instructions.input.empty();
+                Err(error)
+            }
+        }
+    }
+
+    /// Wrap an error returned by `advance_inner` with the offset of the
+    /// instruction that failed.
+    fn instruction_error(&self, error: Error, start_len: Offset) -> ContextError {
+        let input = &self.instructions.input;
+        let consumed = start_len - input.len();
+        let begin = self.program.header().instruction_offset(input).0 - consumed;
+        ContextError::new(error)
+            .with_operation(ErrorOperation::LineProgram)
+            .with_location(SectionId::DebugLine, begin.into_u64())
+    }
+}
+
+impl<R, Program, Offset> LineRows<R, Program, Offset>
+where
+    Program: LineProgram<R, Offset>,
+    R: Reader<Offset = Offset>,
+    Offset: ReaderOffset,
+{
+    /// Return an adapter that implements `Iterator`.
+    ///
+    /// The adapter yields a copy of each row. Use `ResultIterator::get_ref`
+    /// to access the line program header.
+    ///
+    /// See [`ResultIterator`] for more information.
+    pub fn iterator(self) -> ResultIterator<Self> {
+        ResultIterator::new(self)
+    }
+}
+
+impl<R, Program, Offset> Iterator for ResultIterator<LineRows<R, Program, Offset>>
+where
+    Program: LineProgram<R, Offset>,
+    R: Reader<Offset = Offset>,
+    Offset: ReaderOffset,
+{
+    type Item = Result<LineRow>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_with(|rows| Ok(rows.next_row()?.map(|(_, row)| *row)))
+    }
 }
 
 /// Deprecated. `Opcode` has been renamed to `LineInstruction`.
@@ -385,6 +647,18 @@ where
     /// > into it instead.
     SetAddress(u64),
 
+    /// A `DW_LNE_set_address` instruction for a line number program with a
+    /// nonzero `segment_selector_size`.
+    ///
+    /// The operand is a segment selector followed by the address. This sets
+    /// the segment register in addition to the address register.
+    SetSegmentedAddress {
+        /// The segment selector.
+        segment: u64,
+        /// The address within the segment.
+        address: u64,
+    },
+
     /// Defines a new source file in the line number program and appends it to
     /// the line number program header's list of source files.
     DefineFile(FileEntry<R, Offset>),
@@ -411,6 +685,17 @@ where
         R: 'header,
     {
         let opcode = input.read_u8()?;
+        Self::parse_opcode(header, opcode, input)
+    }
+
+    fn parse_opcode<'header>(
+        header: &'header LineProgramHeader<R>,
+        opcode: u8,
+        input: &mut R,
+    ) -> Result<LineInstruction<R>>
+    where
+        R: 'header,
+    {
         if opcode == 0 {
             let length = input.read_uleb128().and_then(R::Offset::from_u64)?;
             let mut instr_rest = input.split(length)?;
@@ -420,8 +705,15 @@ where
                 constants::DW_LNE_end_sequence => Ok(LineInstruction::EndSequence),
 
                 constants::DW_LNE_set_address => {
-                    let address = instr_rest.read_address(header.address_size())?;
-                    Ok(LineInstruction::SetAddress(address))
+                    let segment_size = header.segment_selector_size();
+                    if segment_size != 0 {
+                        let segment = instr_rest.read_address(segment_size)?;
+                        let address = instr_rest.read_address(header.address_size())?;
+                        Ok(LineInstruction::SetSegmentedAddress { segment, address })
+                    } else {
+                        let address = instr_rest.read_address(header.address_size())?;
+                        Ok(LineInstruction::SetAddress(address))
+                    }
                 }
 
                 constants::DW_LNE_define_file => {
@@ -559,6 +851,13 @@ where
             LineInstruction::SetAddress(address) => {
                 write!(f, "{} to {}", constants::DW_LNE_set_address, address)
             }
+            LineInstruction::SetSegmentedAddress { segment, address } => write!(
+                f,
+                "{} to {}:{}",
+                constants::DW_LNE_set_address,
+                segment,
+                address
+            ),
             LineInstruction::DefineFile(_) => write!(f, "{}", constants::DW_LNE_define_file),
             LineInstruction::SetDiscriminator(discr) => {
                 write!(f, "{} to {}", constants::DW_LNE_set_discriminator, discr)
@@ -629,8 +928,10 @@ pub type LineNumberRow = LineRow;
 ///
 /// Each row is a copy of the registers of the state machine, as defined in section 6.2.2.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LineRow {
     tombstone: bool,
+    segment: u64,
     address: Wrapping<u64>,
     op_index: Wrapping<u64>,
     file: u64,
@@ -652,6 +953,7 @@ impl LineRow {
             // "At the beginning of each sequence within a line number program, the
             // state of the registers is:" -- Section 6.2.2
             tombstone: false,
+            segment: 0,
             address: Wrapping(0),
             op_index: Wrapping(0),
             file: 1,
@@ -679,6 +981,15 @@ impl LineRow {
         self.address.0
     }
 
+    /// The segment selector for the address.
+    ///
+    /// This is only set by programs with a nonzero `segment_selector_size`,
+    /// and is 0 otherwise.
+    #[inline]
+    pub fn segment(&self) -> u64 {
+        self.segment
+    }
+
     /// > An unsigned integer representing the index of an operation within a VLIW
     /// > instruction. The index of the first operation is 0. For non-VLIW
     /// > architectures, this register will always be 0.
@@ -877,8 +1188,15 @@ impl LineRow {
             }
 
             LineInstruction::SetAddress(address) => {
-                let tombstone_address = !0 >> (64 - program.header().encoding.address_size * 8);
-                self.tombstone = address == tombstone_address;
+                self.tombstone = program.header().is_tombstone(address);
+                self.address.0 = address;
+                self.op_index.0 = 0;
+                false
+            }
+
+            LineInstruction::SetSegmentedAddress { segment, address } => {
+                self.tombstone = program.header().is_tombstone(address);
+                self.segment = segment;
                 self.address.0 = address;
                 self.op_index.0 = 0;
                 false
@@ -968,23 +1286,94 @@ impl LineRow {
 
     /// Section 6.2.5.1
     fn exec_special_opcode<R: Reader>(&mut self, opcode: u8, header: &LineProgramHeader<R>) {
-        let adjusted_opcode = self.adjust_opcode(opcode, header);
+        self.exec_special(SpecialOpcode::new(opcode, header), header);
+    }
+
+    /// Section 6.2.5.1, using a precomputed special opcode.
+    #[inline]
+    fn exec_special<R: Reader>(&mut self, special: SpecialOpcode, header: &LineProgramHeader<R>) {
+        // Step 1
+        self.apply_line_advance(i64::from(special.line_advance));
+
+        // Step 2
+        if header.line_encoding.maximum_operations_per_instruction == 1 {
+            self.address += Wrapping(u64::from(special.address_advance));
+            self.op_index.0 = 0;
+        } else {
+            self.apply_operation_advance(u64::from(special.operation_advance), header);
+        }
+    }
+}
+
+/// The effect of a special opcode, computed from the line program header.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct SpecialOpcode {
+    line_advance: i16,
+    operation_advance: u8,
+    /// The address advance when `maximum_operations_per_instruction` is 1.
+    address_advance: u16,
+}
 
+impl SpecialOpcode {
+    fn new<R: Reader>(opcode: u8, header: &LineProgramHeader<R>) -> Self {
+        let adjusted_opcode = opcode - header.opcode_base;
         let line_range = header.line_encoding.line_range;
         let line_advance = adjusted_opcode % line_range;
         let operation_advance = adjusted_opcode / line_range;
+        SpecialOpcode {
+            line_advance: i16::from(header.line_encoding.line_base) + i16::from(line_advance),
+            operation_advance,
+            address_advance: u16::from(header.line_encoding.minimum_instruction_length)
+                * u16::from(operation_advance),
+        }
+    }
+}
 
-        // Step 1
-        let line_base = i64::from(header.line_encoding.line_base);
-        self.apply_line_advance(line_base + i64::from(line_advance));
+/// The effects of all special opcodes for a line program header, so that
+/// they don't need to be recomputed for each instruction.
+#[derive(Clone)]
+struct SpecialOpcodeTable {
+    opcodes: [SpecialOpcode; 256],
+}
 
-        // Step 2
-        self.apply_operation_advance(u64::from(operation_advance), header);
+impl fmt::Debug for SpecialOpcodeTable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SpecialOpcodeTable").finish()
+    }
+}
+
+impl SpecialOpcodeTable {
+    fn new<R: Reader>(header: &LineProgramHeader<R>) -> Self {
+        let mut opcodes = [SpecialOpcode::default(); 256];
+        for opcode in header.opcode_base..=u8::MAX {
+            opcodes[usize::from(opcode)] = SpecialOpcode::new(opcode, header);
+        }
+        SpecialOpcodeTable { opcodes }
+    }
+
+    /// Get the special opcode. `opcode` must be at least `opcode_base`.
+    #[inline]
+    fn get(&self, opcode: u8) -> SpecialOpcode {
+        self.opcodes[usize::from(opcode)]
     }
 }
 
+/// A range of addresses that have the same source location.
+///
+/// This is returned by `LineRows::next_range`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineRowRange {
+    /// The first address in the range.
+    pub begin: u64,
+    /// The first address that is not in the range.
+    pub end: u64,
+    /// The first row in the range.
+    pub row: LineRow,
+}
+
 /// The type of column that a row is referring to.
 #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ColumnType {
     /// The `LeftEdge` means that the statement begins at the start of the new
     /// line.
@@ -1031,6 +1420,13 @@ where
 
     header_length: Offset,
 
+    /// The size in bytes of a segment selector, or 0 if the target
+    /// architecture is not segmented.
+    segment_selector_size: u8,
+
+    /// Additional tombstone addresses for `DW_LNE_set_address`.
+    tombstone_filter: TombstoneFilter,
+
     line_encoding: LineEncoding,
 
     /// "The number assigned to the first special opcode."
@@ -1110,6 +1506,39 @@ where
         self.encoding.address_size
     }
 
+    /// Get the size in bytes of a segment selector.
+    ///
+    /// This is always 0 for version 4 and earlier.
+    pub fn segment_selector_size(&self) -> u8 {
+        self.segment_selector_size
+    }
+
+    /// Get the filter for additional tombstone addresses.
+    pub fn tombstone_filter(&self) -> TombstoneFilter {
+        self.tombstone_filter
+    }
+
+    pub(crate) fn set_tombstone_filter(&mut self, filter: TombstoneFilter) {
+        self.tombstone_filter = filter;
+    }
+
+    /// Return the offset in the `.debug_line` section of the given position
+    /// within the instructions of this program.
+    fn instruction_offset(&self, input: &R) -> DebugLineOffset<Offset> {
+        let unit_end = self.offset.0
+            + Offset::from_u8(self.encoding.format.initial_length_size())
+            + self.unit_length;
+        DebugLineOffset(unit_end - self.program_buf.len() + input.offset_from(&self.program_buf))
+    }
+
+    fn is_tombstone(&self, address: u64) -> bool {
+        let tombstone_address = !0 >> (64 - self.encoding.address_size * 8);
+        address == tombstone_address
+            || self
+                .tombstone_filter
+                .is_tombstone(address, self.encoding.address_size)
+    }
+
     /// Whether this line program is encoded in 64- or 32-bit DWARF.
     pub fn format(&self) -> Format {
         self.encoding.format
@@ -1288,14 +1717,15 @@ where
         let rest = &mut input.split(unit_length)?;
 
         let version = rest.read_u16()?;
-        if version < 2 || version > 5 {
+        if (version < 2 || version > 5) && !crate::read::is_draft_version(version) {
             return Err(Error::UnknownVersion(u64::from(version)));
         }
 
+        let mut segment_selector_size = 0;
         if version >= 5 {
             address_size = rest.read_u8()?;
-            let segment_selector_size = rest.read_u8()?;
-            if segment_selector_size != 0 {
+            segment_selector_size = rest.read_u8()?;
+            if segment_selector_size > 8 {
                 return Err(Error::UnsupportedSegmentSize);
             }
         }
@@ -1404,6 +1834,8 @@ where
             offset,
             unit_length,
             header_length,
+            segment_selector_size,
+            tombstone_filter: TombstoneFilter::none(),
             line_encoding,
             opcode_base,
             standard_opcode_lengths,
@@ -1445,6 +1877,17 @@ where
         &self.header
     }
 
+    /// Set the filter for additional tombstone addresses.
+    ///
+    /// Sequences that begin with a `DW_LNE_set_address` instruction for a
+    /// matching address are skipped by `rows` and `sequences`, in the same way
+    /// as sequences for the -1 tombstone.
+    ///
+    /// `Dwarf::unit` sets this to `Dwarf::tombstone_filter`.
+    pub fn set_tombstone_filter(&mut self, filter: TombstoneFilter) {
+        self.header.set_tombstone_filter(filter);
+    }
+
     /// Construct a new `LineRows` for executing this program to iterate
     /// over rows in the line information matrix.
     pub fn rows(self) -> OneShotLineRows<R, Offset> {
@@ -1671,6 +2114,7 @@ where
 
 /// The format of a component of an include directory or file name entry.
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FileEntryFormat {
     /// The type of information that is represented by the component.
     pub content_type: constants::DwLnct,
@@ -1778,8 +2222,11 @@ fn parse_file_v5<R: Reader>(
 fn parse_attribute<R: Reader>(
     input: &mut R,
     encoding: Encoding,
-    form: constants::DwForm,
+    mut form: constants::DwForm,
 ) -> Result<AttributeValue<R>> {
+    while form == constants::DW_FORM_indirect {
+        form = constants::DwForm(input.read_uleb128_u16()?);
+    }
     Ok(match form {
         constants::DW_FORM_block1 => {
             let len = input.read_u8().map(R::Offset::from_u8)?;
@@ -2141,6 +2588,8 @@ mod tests {
             offset: DebugLineOffset(0),
             unit_length: 1,
             header_length: 1,
+            segment_selector_size: 0,
+            tombstone_filter: TombstoneFilter::none(),
             line_encoding,
             opcode_base: OPCODE_BASE,
             standard_opcode_lengths: EndianSlice::new(STANDARD_OPCODE_LENGTHS, LittleEndian),
@@ -2333,7 +2782,7 @@ mod tests {
             opcode,
             LineInstruction::UnknownStandardN(constants::DwLns(OPCODE_BASE), args)
         );
-        assert_eq!(*rest, []);
+        assert!(rest.is_empty());
     }
 
     #[test]
@@ -2844,6 +3293,200 @@ mod tests {
         assert_exec_opcode(header, initial_registers, opcode, expected_registers, false);
     }
 
+    #[test]
+    fn test_exec_set_address_tombstone_filter() {
+        let mut header = make_test_header(EndianSlice::new(&[], LittleEndian));
+        header.set_tombstone_filter(TombstoneFilter::none().zero(true));
+        let initial_registers = LineRow::new(&header);
+        let opcode = LineInstruction::SetAddress(0);
+
+        let mut expected_registers = initial_registers;
+        expected_registers.tombstone = true;
+
+        assert_exec_opcode(header, initial_registers, opcode, expected_registers, false);
+    }
+
+    #[test]
+    fn test_parse_attribute_indirect() {
+        let encoding = Encoding {
+            format: Format::Dwarf32,
+            version: 5,
+            address_size: 4,
+        };
+        let buf = [
+            constants::DW_FORM_indirect.0 as u8,
+            constants::DW_FORM_data2.0 as u8,
+            0x34,
+            0x12,
+        ];
+        let input = &mut EndianSlice::new(&buf, LittleEndian);
+        assert_eq!(
+            parse_attribute(input, encoding, constants::DW_FORM_indirect),
+            Ok(AttributeValue::Data2(0x1234))
+        );
+        assert!(input.is_empty());
+    }
+
+    #[test]
+    fn test_next_row_lenient() {
+        #[rustfmt::skip]
+        let bytes = [
+            constants::DW_LNS_copy.0,
+            OPCODE_BASE + 12,
+            // Truncated address.
+            0, 3, constants::DW_LNE_set_address.0, 1, 2,
+            OPCODE_BASE,
+            0, 1, constants::DW_LNE_end_sequence.0,
+            constants::DW_LNS_copy.0,
+            // Truncated operand.
+            constants::DW_LNS_advance_pc.0, 0x80,
+        ];
+        let mut program = make_test_program(EndianSlice::new(&bytes, LittleEndian));
+        // Pretend that the header is 10 bytes long.
+        program.header.unit_length = bytes.len() + 10;
+        let mut rows = program.clone().rows();
+        let mut warnings = Vec::new();
+        let mut next = || {
+            rows.next_row_lenient(&mut warnings)
+                .map(|(_, row)| row.address())
+        };
+        assert_eq!(next(), Some(0));
+        assert_eq!(next(), Some(1));
+        assert_eq!(next(), Some(0));
+        assert_eq!(next(), None);
+        assert_eq!(next(), None);
+
+        assert_eq!(warnings.len(), 2);
+        for (warning, offset) in warnings.iter().zip([16, 26]) {
+            assert!(matches!(warning.error(), Error::UnexpectedEof(_)));
+            assert_eq!(warning.operation(), Some(ErrorOperation::LineProgram));
+            assert_eq!(warning.section(), Some(SectionId::DebugLine));
+            assert_eq!(warning.offset(), Some(offset));
+        }
+
+        // The strict parser stops at the first damaged instruction.
+        let mut rows = program.rows();
+        assert_eq!(
+            rows.next_row_with_context()
+                .map(|row| row.map(|(_, row)| row.address())),
+            Ok(Some(0))
+        );
+        assert!(rows.next_row_with_context().unwrap().is_some());
+        assert_eq!(rows.next_row_with_context().err(), Some(warnings[0]));
+        assert!(rows.next_row_with_context().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_next_range() {
+        use constants::*;
+        #[rustfmt::skip]
+        let bytes = [
+            DW_LNS_copy.0,
+            // Same location, but not a statement.
+            DW_LNS_negate_stmt.0,
+            DW_LNS_advance_pc.0, 2,
+            DW_LNS_copy.0,
+            DW_LNS_negate_stmt.0,
+            DW_LNS_advance_pc.0, 2,
+            DW_LNS_advance_line.0, 1,
+            DW_LNS_copy.0,
+            // Same address, so the previous row is empty.
+            DW_LNS_advance_line.0, 1,
+            DW_LNS_copy.0,
+            DW_LNS_advance_pc.0, 2,
+            DW_LNS_set_file.0, 2,
+            DW_LNS_copy.0,
+            DW_LNS_advance_pc.0, 2,
+            0, 1, DW_LNE_end_sequence.0,
+            // Unterminated sequence.
+            DW_LNS_copy.0,
+        ];
+        let program = make_test_program(EndianSlice::new(&bytes, LittleEndian));
+
+        let mut rows = program.clone().rows();
+        let mut addresses = Vec::new();
+        while let Some((_, row)) = rows.next_stmt_row().unwrap() {
+            addresses.push(row.address());
+        }
+        assert_eq!(addresses, [0, 4, 4, 6, 8, 0]);
+
+        let range = |rows: &mut OneShotLineRows<_>, filter: Option<u64>| {
+            let range = match filter {
+                Some(file) => rows.next_range_filtered(|row| row.file_index() == file),
+                None => rows.next_range(),
+            };
+            range.unwrap().map(|range| {
+                (
+                    range.begin,
+                    range.end,
+                    range.row.file_index(),
+                    range.row.line(),
+                )
+            })
+        };
+        let line = NonZeroU64::new;
+        let mut rows = program.clone().rows();
+        assert_eq!(range(&mut rows, None), Some((0, 4, 1, line(1))));
+        assert_eq!(range(&mut rows, None), Some((4, 6, 1, line(3))));
+        assert_eq!(range(&mut rows, None), Some((6, 8, 2, line(3))));
+        assert_eq!(range(&mut rows, None), None);
+
+        let mut rows = program.rows();
+        assert_eq!(range(&mut rows, Some(2)), Some((6, 8, 2, line(3))));
+        assert_eq!(range(&mut rows, Some(2)), None);
+    }
+
+    #[test]
+    fn test_next_rows() {
+        let bytes = [
+            OPCODE_BASE,
+            OPCODE_BASE + 25,
+            constants::DW_LNS_advance_line.0,
+            10,
+            constants::DW_LNS_copy.0,
+            0xff,
+            OPCODE_BASE + 1,
+            0,
+            1,
+            constants::DW_LNE_end_sequence.0,
+            OPCODE_BASE + 40,
+        ];
+        for maximum_operations_per_instruction in [1, 3] {
+            let mut program = make_test_program(EndianSlice::new(&bytes, LittleEndian));
+            program.header.line_encoding.minimum_instruction_length = 2;
+            program
+                .header
+                .line_encoding
+                .maximum_operations_per_instruction = maximum_operations_per_instruction;
+
+            // Compute the expected rows without using the special opcode table.
+            let mut expected = Vec::new();
+            let mut instructions = program.header().instructions();
+            let mut row = LineRow::new(program.header());
+            let mut copy = program.clone();
+            while let Some(instruction) = instructions.next_instruction(program.header()).unwrap() {
+                if row.execute(instruction, &mut copy) {
+                    expected.push(row);
+                    row.reset(program.header());
+                }
+            }
+            assert_eq!(expected.len(), 7);
+
+            let mut rows = program.clone().rows();
+            let mut actual = Vec::new();
+            assert_eq!(rows.next_rows(&mut actual, 4), Ok(4));
+            assert_eq!(rows.next_rows(&mut actual, 4), Ok(3));
+            assert_eq!(rows.next_rows(&mut actual, 4), Ok(0));
+            assert_eq!(actual, expected);
+
+            let mut rows = program.rows();
+            for expected in &expected {
+                assert_eq!(rows.next_row().unwrap().map(|(_, row)| row), Some(expected));
+            }
+            assert_eq!(rows.next_row().unwrap(), None);
+        }
+    }
+
     #[test]
     fn test_exec_define_file() {
         let mut program = make_test_program(EndianSlice::new(&[], LittleEndian));
@@ -3118,4 +3761,26 @@ mod tests {
         assert_eq!(sequences[1].start, 11);
         assert_eq!(sequences[1].end, 14);
     }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_serde() {
+        let header = make_test_header(EndianSlice::new(&[], LittleEndian));
+        let mut row = LineRow::new(&header);
+        let json = serde_json::to_string(&row).unwrap();
+        assert!(json.contains(r#""address":0,"#));
+        assert!(json.contains(r#""file":1,"#));
+        assert_eq!(serde_json::from_str::<LineRow>(&json).unwrap(), row);
+
+        row.apply_line_advance(5);
+        row.apply_operation_advance(3, &header);
+        row.is_stmt = false;
+        row.discriminator = 2;
+        let json = serde_json::to_string(&row).unwrap();
+        assert_eq!(serde_json::from_str::<LineRow>(&json).unwrap(), row);
+
+        let column = ColumnType::Column(NonZeroU64::new(3).unwrap());
+        let json = serde_json::to_string(&column).unwrap();
+        assert_eq!(serde_json::from_str::<ColumnType>(&json).unwrap(), column);
+    }
 }
@@ -5,6 +5,8 @@ use crate::read::{Error, Reader, Result};
 pub(crate) struct ListsHeader {
     encoding: Encoding,
     #[allow(dead_code)]
+    segment_selector_size: u8,
+    #[allow(dead_code)]
     offset_entry_count: u32,
 }
 
@@ -16,6 +18,7 @@ impl Default for ListsHeader {
                 version: 5,
                 address_size: 0,
             },
+            segment_selector_size: 0,
             offset_entry_count: 0,
         }
     }
@@ -45,15 +48,14 @@ fn parse_header<R: Reader>(input: &mut R) -> Result<ListsHeader> {
     input.truncate(length)?;
 
     let version = input.read_u16()?;
-    if version != 5 {
+    if version != 5 && !crate::read::is_draft_version(version) {
         return Err(Error::UnknownVersion(u64::from(version)));
     }
 
     let address_size = input.read_u8()?;
+    // The entries don't contain segment selectors. Segment selectors for
+    // indexed addresses are stored in `.debug_addr` instead.
     let segment_selector_size = input.read_u8()?;
-    if segment_selector_size != 0 {
-        return Err(Error::UnsupportedSegmentSize);
-    }
     let offset_entry_count = input.read_u32()?;
 
     let encoding = Encoding {
@@ -63,6 +65,7 @@ fn parse_header<R: Reader>(input: &mut R) -> Result<ListsHeader> {
     };
     Ok(ListsHeader {
         encoding,
+        segment_selector_size,
         offset_entry_count,
     })
 }
@@ -6,7 +6,7 @@ use crate::constants;
 use crate::endianity::Endianity;
 use crate::read::{
     lists::ListsHeader, DebugAddr, EndianSlice, Error, Expression, Range, RawRange, Reader,
-    ReaderOffset, ReaderOffsetId, Result, Section,
+    ReaderOffset, ReaderOffsetId, Result, ResultIterator, Section, TombstoneFilter,
 };
 
 /// The raw contents of the `.debug_loc` section.
@@ -334,6 +334,11 @@ pub struct RawLocListIter<R: Reader> {
 
 /// A raw entry in .debug_loclists.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound = "R: serde::Serialize, R::Offset: serde::Serialize")
+)]
 pub enum RawLocListEntry<R: Reader> {
     /// A location from DWARF version <= 4.
     AddressOrOffsetPair {
@@ -527,6 +532,24 @@ impl<R: Reader> fallible_iterator::FallibleIterator for RawLocListIter<R> {
     }
 }
 
+impl<R: Reader> RawLocListIter<R> {
+    /// Return an adapter that implements `Iterator`.
+    ///
+    /// See [`ResultIterator`] for more information.
+    pub fn iterator(self) -> ResultIterator<Self> {
+        ResultIterator::new(self)
+    }
+}
+
+impl<R: Reader> Iterator for ResultIterator<RawLocListIter<R>> {
+    type Item = Result<RawLocListEntry<R>>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_with(RawLocListIter::next)
+    }
+}
+
 /// An iterator over a location list.
 ///
 /// This iterator internally handles processing of base address selection entries
@@ -538,6 +561,11 @@ pub struct LocListIter<R: Reader> {
     base_address: u64,
     debug_addr: DebugAddr<R>,
     debug_addr_base: DebugAddrBase<R::Offset>,
+    tombstone_filter: TombstoneFilter,
+    base_address_tombstone: bool,
+    segment_selector_size: u8,
+    base_segment: u64,
+    segment: u64,
 }
 
 impl<R: Reader> LocListIter<R> {
@@ -553,13 +581,60 @@ impl<R: Reader> LocListIter<R> {
             base_address,
             debug_addr,
             debug_addr_base,
+            tombstone_filter: TombstoneFilter::none(),
+            base_address_tombstone: false,
+            segment_selector_size: 0,
+            base_segment: 0,
+            segment: 0,
         }
     }
 
+    /// Set the filter for additional tombstone addresses to ignore.
+    ///
+    /// Locations with a range that begins at a matching address are skipped,
+    /// as are offset pairs that follow a base address entry with a matching
+    /// address. The filter is not applied to the base address of the unit,
+    /// since a `DW_AT_low_pc` of 0 is commonly used with offset pairs.
+    pub fn set_tombstone_filter(&mut self, filter: TombstoneFilter) {
+        self.tombstone_filter = filter;
+    }
+
+    /// Set the size of the segment selectors in the `.debug_addr` entries
+    /// that are used by this list.
+    ///
+    /// This must be the `segment_selector_size` from the header of the set of
+    /// addresses in `.debug_addr`. The default is 0.
+    pub fn set_segment_selector_size(&mut self, segment_selector_size: u8) {
+        self.segment_selector_size = segment_selector_size;
+    }
+
+    /// The segment selector for the location most recently returned by `next`.
+    ///
+    /// This is only set for entries that use addresses from `.debug_addr` with
+    /// a nonzero segment selector size, and is 0 otherwise. For other entries,
+    /// the segment is given by the `DW_AT_segment` attribute of the entry that
+    /// refers to the list.
+    #[inline]
+    pub fn segment(&self) -> u64 {
+        self.segment
+    }
+
+    fn set_base_address(&mut self, segment: u64, addr: u64) {
+        self.base_segment = segment;
+        self.base_address = addr;
+        self.base_address_tombstone = self
+            .tombstone_filter
+            .is_tombstone(addr, self.raw.encoding.address_size);
+    }
+
     #[inline]
-    fn get_address(&self, index: DebugAddrIndex<R::Offset>) -> Result<u64> {
-        self.debug_addr
-            .get_address(self.raw.encoding.address_size, self.debug_addr_base, index)
+    fn get_address(&self, index: DebugAddrIndex<R::Offset>) -> Result<(u64, u64)> {
+        self.debug_addr.get_segment_address(
+            self.raw.encoding.address_size,
+            self.segment_selector_size,
+            self.debug_addr_base,
+            index,
+        )
     }
 
     /// Advance the iterator to the next location.
@@ -600,57 +675,64 @@ impl<R: Reader> LocListIter<R> {
             mask
         };
 
-        let (range, data) = match raw_loc {
+        let (segment, range, data) = match raw_loc {
             RawLocListEntry::BaseAddress { addr } => {
-                self.base_address = addr;
+                self.set_base_address(0, addr);
                 return Ok(None);
             }
             RawLocListEntry::BaseAddressx { addr } => {
-                self.base_address = self.get_address(addr)?;
+                let (segment, addr) = self.get_address(addr)?;
+                self.set_base_address(segment, addr);
                 return Ok(None);
             }
             RawLocListEntry::StartxEndx { begin, end, data } => {
-                let begin = self.get_address(begin)?;
-                let end = self.get_address(end)?;
-                (Range { begin, end }, data)
+                let (segment, begin) = self.get_address(begin)?;
+                let (_, end) = self.get_address(end)?;
+                (segment, Range { begin, end }, data)
             }
             RawLocListEntry::StartxLength {
                 begin,
                 length,
                 data,
             } => {
-                let begin = self.get_address(begin)?;
+                let (segment, begin) = self.get_address(begin)?;
                 let end = begin.wrapping_add(length) & mask;
-                (Range { begin, end }, data)
+                (segment, Range { begin, end }, data)
             }
-            RawLocListEntry::DefaultLocation { data } => (
-                Range {
+            RawLocListEntry::DefaultLocation { data } => {
+                // This isn't affected by tombstones.
+                let range = Range {
                     begin: 0,
                     end: u64::max_value(),
-                },
-                data,
-            ),
+                };
+                self.segment = 0;
+                return Ok(Some(LocationListEntry { range, data }));
+            }
             RawLocListEntry::AddressOrOffsetPair { begin, end, data }
             | RawLocListEntry::OffsetPair { begin, end, data } => {
-                if self.base_address == tombstone {
+                if self.base_address == tombstone || self.base_address_tombstone {
                     return Ok(None);
                 }
                 let mut range = Range { begin, end };
                 range.add_base_address(self.base_address, self.raw.encoding.address_size);
-                (range, data)
+                (self.base_segment, range, data)
             }
-            RawLocListEntry::StartEnd { begin, end, data } => (Range { begin, end }, data),
+            RawLocListEntry::StartEnd { begin, end, data } => (0, Range { begin, end }, data),
             RawLocListEntry::StartLength {
                 begin,
                 length,
                 data,
             } => {
                 let end = begin.wrapping_add(length) & mask;
-                (Range { begin, end }, data)
+                (0, Range { begin, end }, data)
             }
         };
 
-        if range.begin == tombstone {
+        if range.begin == tombstone
+            || self
+                .tombstone_filter
+                .is_tombstone(range.begin, self.raw.encoding.address_size)
+        {
             return Ok(None);
         }
 
@@ -659,6 +741,8 @@ impl<R: Reader> LocListIter<R> {
             return Err(Error::InvalidLocationAddressRange);
         }
 
+        self.segment = segment;
+
         Ok(Some(LocationListEntry { range, data }))
     }
 }
@@ -673,8 +757,31 @@ impl<R: Reader> fallible_iterator::FallibleIterator for LocListIter<R> {
     }
 }
 
+impl<R: Reader> LocListIter<R> {
+    /// Return an adapter that implements `Iterator`.
+    ///
+    /// See [`ResultIterator`] for more information.
+    pub fn iterator(self) -> ResultIterator<Self> {
+        ResultIterator::new(self)
+    }
+}
+
+impl<R: Reader> Iterator for ResultIterator<LocListIter<R>> {
+    type Item = Result<LocationListEntry<R>>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_with(LocListIter::next)
+    }
+}
+
 /// A location list entry from the `.debug_loc` or `.debug_loclists` sections.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound = "R: serde::Serialize, R::Offset: serde::Serialize")
+)]
 pub struct LocationListEntry<R: Reader> {
     /// The address range that this location is valid for.
     pub range: Range,
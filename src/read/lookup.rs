@@ -134,6 +134,16 @@ pub struct PubStuffHeader<T = usize> {
     unit_length: T,
 }
 
+impl<T: Copy> PubStuffHeader<T> {
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
+    pub fn unit_offset(&self) -> DebugInfoOffset<T> {
+        self.unit_offset
+    }
+}
+
 pub trait PubStuffEntry<R: Reader> {
     fn new(
         die_offset: UnitOffset<R::Offset>,
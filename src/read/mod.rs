@@ -3,6 +3,7 @@
 //! * [Example Usage](#example-usage)
 //! * [API Structure](#api-structure)
 //! * [Using with `FallibleIterator`](#using-with-fallibleiterator)
+//! * [Using with `Iterator`](#using-with-iterator)
 //!
 //! ## Example Usage
 //!
@@ -83,6 +84,10 @@
 //!
 //!   * [`DebugFrame`](./struct.DebugFrame.html): The `.debug_frame` section.
 //!
+//!   * [`DebugGnuPubNames`](./struct.DebugGnuPubNames.html): The `.debug_gnu_pubnames` section.
+//!
+//!   * [`DebugGnuPubTypes`](./struct.DebugGnuPubTypes.html): The `.debug_gnu_pubtypes` section.
+//!
 //!   * [`DebugInfo`](./struct.DebugInfo.html): The `.debug_info` section.
 //!
 //!   * [`DebugLine`](./struct.DebugLine.html): The `.debug_line` section.
@@ -93,6 +98,8 @@
 //!
 //!   * [`DebugLocLists`](./struct.DebugLocLists.html): The `.debug_loclists` section.
 //!
+//!   * [`DebugNames`](./struct.DebugNames.html): The `.debug_names` section.
+//!
 //!   * [`DebugPubNames`](./struct.DebugPubNames.html): The `.debug_pubnames`
 //!   section.
 //!
@@ -172,29 +179,78 @@
 //! # }
 //! # fn main() {}
 //! ```
+//!
+//! ## Using with `Iterator`
+//!
+//! If you do not want to depend on the `fallible-iterator` crate, the
+//! iterators also provide an `iterator` method that returns a
+//! [`ResultIterator`](./struct.ResultIterator.html). This adapter implements
+//! the standard library's `Iterator` trait with an `Item` type of
+//! `Result<T, Error>`, and stops after the first error.
+//!
+//! ```
+//! use gimli::{DebugAranges, EndianSlice, LittleEndian};
+//!
+//! fn find_sum_of_address_range_lengths(aranges: DebugAranges<EndianSlice<LittleEndian>>)
+//!     -> gimli::Result<u64>
+//! {
+//!     let mut sum = 0;
+//!     for header in aranges.headers().iterator() {
+//!         for arange in header?.entries().iterator() {
+//!             sum += arange?.length();
+//!         }
+//!     }
+//!     Ok(sum)
+//! }
+//! ```
 
 use core::fmt::{self, Debug};
 use core::result;
 #[cfg(feature = "std")]
 use std::{error, io};
 
-use crate::common::{Register, SectionId};
+use crate::common::{DwoId, Register, SectionId};
 use crate::constants;
 
 mod util;
 pub use util::*;
 
+#[cfg(feature = "read")]
+mod accelerator_cache;
+#[cfg(feature = "read")]
+pub use self::accelerator_cache::*;
+
 mod addr;
 pub use self::addr::*;
 
 mod cfi;
 pub use self::cfi::*;
 
+#[cfg(feature = "compression")]
+mod compression;
+#[cfg(feature = "compression")]
+pub use self::compression::*;
+
+#[cfg(feature = "read")]
+mod call_site;
+#[cfg(feature = "read")]
+pub use self::call_site::*;
+
+#[cfg(feature = "read")]
+mod debug_map;
+#[cfg(feature = "read")]
+pub use self::debug_map::*;
+
 #[cfg(feature = "read")]
 mod dwarf;
 #[cfg(feature = "read")]
 pub use self::dwarf::*;
 
+#[cfg(feature = "read")]
+mod dump;
+#[cfg(feature = "read")]
+pub use self::dump::*;
+
 mod endian_slice;
 pub use self::endian_slice::*;
 
@@ -217,9 +273,27 @@ pub use self::abbrev::*;
 mod aranges;
 pub use self::aranges::*;
 
+#[cfg(feature = "read")]
+mod gdb_index;
+#[cfg(feature = "read")]
+pub use self::gdb_index::*;
+
+#[cfg(feature = "read")]
+mod gnu_pubnames;
+#[cfg(feature = "read")]
+pub use self::gnu_pubnames::*;
+
 mod index;
 pub use self::index::*;
 
+#[cfg(feature = "read")]
+mod lazy;
+
+#[cfg(feature = "read")]
+mod lazy_sections;
+#[cfg(feature = "read")]
+pub use self::lazy_sections::*;
+
 #[cfg(feature = "read")]
 mod line;
 #[cfg(feature = "read")]
@@ -233,6 +307,16 @@ pub use self::loclists::*;
 #[cfg(feature = "read")]
 mod lookup;
 
+#[cfg(feature = "read")]
+mod name_lookup;
+#[cfg(feature = "read")]
+pub use self::name_lookup::*;
+
+#[cfg(feature = "read")]
+mod names;
+#[cfg(feature = "read")]
+pub use self::names::*;
+
 mod op;
 pub use self::op::*;
 
@@ -246,14 +330,38 @@ mod pubtypes;
 #[cfg(feature = "read")]
 pub use self::pubtypes::*;
 
+#[cfg(feature = "read")]
+mod range_index;
+
 mod rnglists;
 pub use self::rnglists::*;
 
+#[cfg(feature = "read")]
+mod render;
+#[cfg(feature = "read")]
+pub use self::render::*;
+
+#[cfg(feature = "read")]
+mod scope;
+#[cfg(feature = "read")]
+pub use self::scope::*;
+
+#[cfg(all(feature = "read", feature = "std"))]
+mod seek_reader;
+#[cfg(all(feature = "read", feature = "std"))]
+pub use self::seek_reader::*;
+
 mod str;
 pub use self::str::*;
 
+#[cfg(feature = "read")]
+mod symbolize;
+#[cfg(feature = "read")]
+pub use self::symbolize::*;
+
 /// An offset into the current compilation or type unit.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct UnitOffset<T = usize>(pub T);
 
 #[cfg(feature = "read")]
@@ -261,9 +369,24 @@ mod unit;
 #[cfg(feature = "read")]
 pub use self::unit::*;
 
+#[cfg(feature = "read")]
+mod unit_file_map;
+#[cfg(feature = "read")]
+pub use self::unit_file_map::*;
+
+#[cfg(feature = "read")]
+mod unit_map;
+#[cfg(feature = "read")]
+pub use self::unit_map::*;
+
 mod value;
 pub use self::value::*;
 
+#[cfg(feature = "read")]
+mod variant;
+#[cfg(feature = "read")]
+pub use self::variant::*;
+
 /// Indicates that storage should be allocated on heap.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct StoreOnHeap;
@@ -438,6 +561,34 @@ pub enum Error {
     InvalidIndexRow,
     /// Unknown section type in `.dwp` index.
     UnknownIndexSection,
+    /// Invalid header or table offsets in `.gdb_index` section.
+    InvalidGdbIndex,
+    /// Invalid header or tables in a name index in the `.debug_names` section.
+    InvalidNameIndex,
+    /// There is no type unit with the given type signature.
+    UnknownTypeSignature,
+    /// A DWO file did not contain a split unit with the skeleton unit's DWO ID.
+    MissingSplitUnit,
+    /// The DWO ID of a split unit does not match the DWO ID of its skeleton unit.
+    ///
+    /// The values are the DWO IDs of the skeleton unit and the split unit.
+    MismatchedDwoId(DwoId, DwoId),
+    /// The compression format of a section is not supported.
+    UnsupportedCompressionFormat,
+    /// The compressed data of a section is invalid.
+    InvalidCompressedData,
+    /// Found an unknown descriptor in a `DW_AT_discr_list` attribute.
+    UnknownDiscriminantDescriptor(constants::DwDsc),
+    /// The data for an `AcceleratorCache` is invalid.
+    InvalidAcceleratorCache,
+    /// A DIE was nested deeper than the maximum depth in `Limits`.
+    EntryTooDeep,
+    /// An abbreviations table contained more entries than the maximum in `Limits`.
+    TooManyAbbreviations,
+    /// An expression was nested deeper than the maximum depth in `Limits`.
+    ExpressionTooDeep,
+    /// A line program was longer than the maximum length in `Limits`.
+    LineProgramTooLong,
 }
 
 impl fmt::Display for Error {
@@ -587,6 +738,29 @@ impl Error {
             Error::InvalidIndexSlotCount => "Invalid slot count in `.dwp` index.",
             Error::InvalidIndexRow => "Invalid hash row in `.dwp` index.",
             Error::UnknownIndexSection => "Unknown section type in `.dwp` index.",
+            Error::InvalidGdbIndex => "Invalid header or table offsets in `.gdb_index` section.",
+            Error::InvalidNameIndex => "Invalid header or tables in `.debug_names` section.",
+            Error::UnknownTypeSignature => "There is no type unit with the given type signature.",
+            Error::MissingSplitUnit => {
+                "A DWO file did not contain a split unit with the skeleton unit's DWO ID."
+            }
+            Error::MismatchedDwoId(..) => {
+                "The DWO ID of a split unit does not match the DWO ID of its skeleton unit."
+            }
+            Error::UnsupportedCompressionFormat => {
+                "The compression format of a section is not supported."
+            }
+            Error::InvalidCompressedData => "The compressed data of a section is invalid.",
+            Error::UnknownDiscriminantDescriptor(_) => {
+                "Found an unknown descriptor in a `DW_AT_discr_list` attribute."
+            }
+            Error::InvalidAcceleratorCache => "The data for an `AcceleratorCache` is invalid.",
+            Error::EntryTooDeep => "A DIE was nested deeper than the maximum depth.",
+            Error::TooManyAbbreviations => {
+                "An abbreviations table contained more than the maximum number of entries."
+            }
+            Error::ExpressionTooDeep => "An expression was nested deeper than the maximum depth.",
+            Error::LineProgramTooLong => "A line program was longer than the maximum length.",
         }
     }
 }
@@ -604,6 +778,207 @@ impl From<io::Error> for Error {
 /// The result of a parse.
 pub type Result<T> = result::Result<T, Error>;
 
+/// Return true if `version` is a draft DWARF version that is accepted because
+/// the `dwarf-6-draft` feature is enabled.
+///
+/// Draft versions are parsed using the same layout as DWARF version 5.
+#[inline]
+pub(crate) fn is_draft_version(version: u16) -> bool {
+    cfg!(feature = "dwarf-6-draft") && version == 6
+}
+
+/// The operation that was being performed when an error occurred.
+///
+/// This is used by [`ContextError`] to describe the error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ErrorOperation {
+    /// Parsing a unit header.
+    UnitHeader,
+    /// Parsing an abbreviations table.
+    Abbreviations,
+    /// Parsing a debugging information entry.
+    Entry,
+    /// Parsing an attribute of a debugging information entry.
+    Attribute(constants::DwAt),
+    /// Parsing a line number program.
+    LineProgram,
+    /// Parsing a location list.
+    LocationList,
+    /// Parsing a range list.
+    RangeList,
+    /// Parsing a call frame information entry.
+    FrameEntry,
+    /// Evaluating a DWARF expression.
+    Expression,
+}
+
+impl fmt::Display for ErrorOperation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> ::core::result::Result<(), fmt::Error> {
+        match *self {
+            ErrorOperation::UnitHeader => write!(f, "parsing unit header"),
+            ErrorOperation::Abbreviations => write!(f, "parsing abbreviations"),
+            ErrorOperation::Entry => write!(f, "parsing DIE"),
+            ErrorOperation::Attribute(name) => write!(f, "parsing {} of DIE", name),
+            ErrorOperation::LineProgram => write!(f, "parsing line program"),
+            ErrorOperation::LocationList => write!(f, "parsing location list"),
+            ErrorOperation::RangeList => write!(f, "parsing range list"),
+            ErrorOperation::FrameEntry => write!(f, "parsing frame entry"),
+            ErrorOperation::Expression => write!(f, "evaluating expression"),
+        }
+    }
+}
+
+/// An `Error` together with information about where it occurred.
+///
+/// The location is the section and offset of the item that was being parsed
+/// (for example, the DIE that contains an attribute), which is not
+/// necessarily the position at which the error was detected.
+///
+/// This is returned by methods such as [`Dwarf::unit_with_context`],
+/// [`EntriesCursor::next_dfs_with_context`], [`AttrsIter::next_with_context`]
+/// and [`LineRows::next_row_with_context`], and is reported to a [`WarningSink`]
+/// by the lenient parsing methods.
+///
+/// ```
+/// use gimli::{ContextError, ErrorOperation, SectionId};
+///
+/// let err = ContextError::new(gimli::Error::UnexpectedNull)
+///     .with_operation(ErrorOperation::Attribute(gimli::DW_AT_location))
+///     .with_location(SectionId::DebugInfo, 0x1234);
+/// assert_eq!(
+///     err.to_string(),
+///     "parsing DW_AT_location of DIE at .debug_info+0x1234: \
+///      Read a null entry before it was expected."
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContextError {
+    error: Error,
+    operation: Option<ErrorOperation>,
+    location: Option<(SectionId, u64)>,
+}
+
+impl ContextError {
+    /// Create a new `ContextError` without any context.
+    #[inline]
+    pub fn new(error: Error) -> Self {
+        ContextError {
+            error,
+            operation: None,
+            location: None,
+        }
+    }
+
+    /// Set the operation that was being performed.
+    #[inline]
+    pub fn with_operation(mut self, operation: ErrorOperation) -> Self {
+        self.operation = Some(operation);
+        self
+    }
+
+    /// Set the section and offset of the item that was being parsed.
+    #[inline]
+    pub fn with_location(mut self, section: SectionId, offset: u64) -> Self {
+        self.location = Some((section, offset));
+        self
+    }
+
+    /// The underlying error.
+    #[inline]
+    pub fn error(&self) -> Error {
+        self.error
+    }
+
+    /// The operation that was being performed, if known.
+    #[inline]
+    pub fn operation(&self) -> Option<ErrorOperation> {
+        self.operation
+    }
+
+    /// The section containing the item that was being parsed, if known.
+    #[inline]
+    pub fn section(&self) -> Option<SectionId> {
+        self.location.map(|(section, _)| section)
+    }
+
+    /// The offset within `section` of the item that was being parsed, if known.
+    #[inline]
+    pub fn offset(&self) -> Option<u64> {
+        self.location.map(|(_, offset)| offset)
+    }
+
+    pub(crate) fn fmt_context(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.operation, self.location) {
+            (Some(operation), Some((section, offset))) => {
+                write!(f, "{} at {}+0x{:x}: ", operation, section.name(), offset)
+            }
+            (Some(operation), None) => write!(f, "{}: ", operation),
+            (None, Some((section, offset))) => {
+                write!(f, "at {}+0x{:x}: ", section.name(), offset)
+            }
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+impl fmt::Display for ContextError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> ::core::result::Result<(), fmt::Error> {
+        self.fmt_context(f)?;
+        write!(f, "{}", self.error)
+    }
+}
+
+#[cfg(feature = "std")]
+impl error::Error for ContextError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+impl From<Error> for ContextError {
+    #[inline]
+    fn from(error: Error) -> Self {
+        ContextError::new(error)
+    }
+}
+
+impl From<ContextError> for Error {
+    #[inline]
+    fn from(error: ContextError) -> Self {
+        error.error
+    }
+}
+
+/// A receiver for errors that were recovered from while parsing in lenient mode.
+///
+/// The lenient parsing methods, such as
+/// [`DebugInfoUnitHeadersIter::next_lenient`], skip past damaged items
+/// instead of returning an error, and report the skipped errors to a
+/// `WarningSink`. This is implemented for closures and for `Vec<ContextError>`.
+pub trait WarningSink {
+    /// Report an error that was recovered from.
+    fn warning(&mut self, error: ContextError);
+}
+
+impl<F> WarningSink for F
+where
+    F: FnMut(ContextError),
+{
+    #[inline]
+    fn warning(&mut self, error: ContextError) {
+        self(error)
+    }
+}
+
+#[cfg(feature = "read")]
+impl WarningSink for alloc::vec::Vec<ContextError> {
+    #[inline]
+    fn warning(&mut self, error: ContextError) {
+        self.push(error)
+    }
+}
+
 /// A convenience trait for loading DWARF sections from object files.  To be
 /// used like:
 ///
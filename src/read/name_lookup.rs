@@ -0,0 +1,427 @@
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+use crate::common::{DebugInfoOffset, DebugTypesOffset, UnitSectionOffset};
+use crate::constants;
+use crate::read::{
+    DebugGnuPubNames, DebugGnuPubTypes, DebugNames, DebugPubNames, DebugPubTypes, Dwarf, Error,
+    GdbIndex, GdbIndexUnit, NameIndex, Reader, ReaderOffset, Result, Section, UnitHeader,
+    UnitOffset,
+};
+
+/// A location found by a [`NameLookup`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NameLookupEntry<T = usize> {
+    /// The offset of the unit header.
+    pub unit_offset: UnitSectionOffset<T>,
+    /// The offset of the entry within the unit.
+    ///
+    /// This is `None` if the table only identifies the unit that defines the name.
+    pub die_offset: Option<UnitOffset<T>>,
+}
+
+impl<T: ReaderOffset> NameLookupEntry<T> {
+    /// Return the offset of the entry within the `.debug_info` or `.debug_types`
+    /// section.
+    ///
+    /// This is the offset to pass to [`Dwarf::entry_at`]. Returns `None` if
+    /// the entry offset is unknown, or if the offset overflows.
+    pub fn die_section_offset(&self) -> Option<UnitSectionOffset<T>> {
+        let die_offset = self.die_offset?.0.into_u64();
+        let offset = |unit_offset: T| {
+            let offset = unit_offset.into_u64().checked_add(die_offset)?;
+            T::from_u64(offset).ok()
+        };
+        Some(match self.unit_offset {
+            UnitSectionOffset::DebugInfoOffset(o) => DebugInfoOffset(offset(o.0)?).into(),
+            UnitSectionOffset::DebugTypesOffset(o) => DebugTypesOffset(offset(o.0)?).into(),
+        })
+    }
+}
+
+/// A table that can be used to find the debugging information entries for a name.
+///
+/// This is implemented by `DebugNames`, `DebugPubNames`, `DebugPubTypes`,
+/// `DebugGnuPubNames`, `DebugGnuPubTypes` and `GdbIndex`.
+///
+/// Use [`Dwarf::lookup_name`] to search all of the tables that are available.
+pub trait NameLookup<R: Reader> {
+    /// Return true if the table contains no names.
+    fn is_empty(&self) -> bool;
+
+    /// Append the entries for the given name to `entries`.
+    ///
+    /// `dwarf` is used for tables that refer to other sections, such as the
+    /// `.debug_str` section.
+    fn lookup_name(
+        &self,
+        dwarf: &Dwarf<R>,
+        name: &[u8],
+        entries: &mut Vec<NameLookupEntry<R::Offset>>,
+    ) -> Result<()>;
+}
+
+macro_rules! pub_name_lookup {
+    ($($table:ident),*) => {
+        $(
+            impl<R: Reader> NameLookup<R> for $table<R> {
+                fn is_empty(&self) -> bool {
+                    self.reader().is_empty()
+                }
+
+                fn lookup_name(
+                    &self,
+                    _dwarf: &Dwarf<R>,
+                    name: &[u8],
+                    entries: &mut Vec<NameLookupEntry<R::Offset>>,
+                ) -> Result<()> {
+                    let mut items = self.items();
+                    while let Some(item) = items.next()? {
+                        if item.name().to_slice()? == name {
+                            entries.push(NameLookupEntry {
+                                unit_offset: item.unit_header_offset().into(),
+                                die_offset: Some(item.die_offset()),
+                            });
+                        }
+                    }
+                    Ok(())
+                }
+            }
+        )*
+    };
+}
+
+pub_name_lookup!(
+    DebugPubNames,
+    DebugPubTypes,
+    DebugGnuPubNames,
+    DebugGnuPubTypes
+);
+
+impl<R: Reader> NameLookup<R> for GdbIndex<R> {
+    fn is_empty(&self) -> bool {
+        self.cu_count() == 0 && self.tu_count() == 0
+    }
+
+    /// The entries only identify the units, except for type units, which
+    /// also give the offset of the type.
+    fn lookup_name(
+        &self,
+        _dwarf: &Dwarf<R>,
+        name: &[u8],
+        entries: &mut Vec<NameLookupEntry<R::Offset>>,
+    ) -> Result<()> {
+        let mut symbols = self.find_name(name)?;
+        while let Some(symbol) = symbols.next()? {
+            let entry = match self.unit(symbol.unit_index)? {
+                GdbIndexUnit::Compilation { offset, .. } => NameLookupEntry {
+                    unit_offset: offset.into(),
+                    die_offset: None,
+                },
+                GdbIndexUnit::Type {
+                    offset,
+                    type_offset,
+                    ..
+                } => NameLookupEntry {
+                    unit_offset: offset.into(),
+                    die_offset: Some(type_offset),
+                },
+            };
+            if !entries.contains(&entry) {
+                entries.push(entry);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<R: Reader> NameLookup<R> for DebugNames<R> {
+    fn is_empty(&self) -> bool {
+        self.reader().is_empty()
+    }
+
+    /// Entries in foreign type units are resolved using
+    /// `Dwarf::resolve_type_signature`, and are skipped if the type unit
+    /// is not found.
+    fn lookup_name(
+        &self,
+        dwarf: &Dwarf<R>,
+        name: &[u8],
+        entries: &mut Vec<NameLookupEntry<R::Offset>>,
+    ) -> Result<()> {
+        let mut indexes = self.indexes();
+        while let Some(index) = indexes.next()? {
+            let mut names = index.find_name(name, &dwarf.debug_str)?;
+            while let Some(entry) = names.next()? {
+                let unit = match entry.type_unit {
+                    Some(type_unit) => name_index_type_unit(dwarf, &index, type_unit)?,
+                    None => {
+                        // The compilation unit may be omitted if there is only one.
+                        let comp_unit = match entry.compile_unit {
+                            Some(comp_unit) => comp_unit,
+                            None if index.comp_unit_count() == 1 => 0,
+                            None => return Err(Error::InvalidNameIndex),
+                        };
+                        let comp_unit =
+                            u32::try_from(comp_unit).map_err(|_| Error::InvalidNameIndex)?;
+                        Some((index.comp_unit(comp_unit)?.into(), None))
+                    }
+                };
+                if let Some((unit_offset, type_offset)) = unit {
+                    entries.push(NameLookupEntry {
+                        unit_offset,
+                        die_offset: entry.die_offset.or(type_offset),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Find the unit header offset for a type unit in a name index.
+///
+/// Also returns the offset of the type for foreign type units.
+#[allow(clippy::type_complexity)]
+fn name_index_type_unit<R: Reader>(
+    dwarf: &Dwarf<R>,
+    index: &NameIndex<R>,
+    type_unit: u64,
+) -> Result<Option<(UnitSectionOffset<R::Offset>, Option<UnitOffset<R::Offset>>)>> {
+    let type_unit = u32::try_from(type_unit).map_err(|_| Error::InvalidNameIndex)?;
+    let local_count = index.local_type_unit_count();
+    if type_unit < local_count {
+        return Ok(Some((index.local_type_unit(type_unit)?.into(), None)));
+    }
+    let signature = index.foreign_type_unit(type_unit - local_count)?;
+    match dwarf.resolve_type_signature(signature) {
+        Ok((unit, type_offset)) => Ok(Some((unit.header.offset(), Some(type_offset)))),
+        Err(Error::UnknownTypeSignature) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+impl<R: Reader> Dwarf<R> {
+    /// Find the debugging information entries with the given name.
+    ///
+    /// All of `tables` that are not empty are searched, and the results are
+    /// merged with duplicates removed. Entries for which a table only
+    /// identifies the unit are resolved by searching that unit for entries
+    /// with a matching `DW_AT_name`.
+    ///
+    /// If all of the tables are empty, then every unit in the `.debug_info`
+    /// section is searched instead. Note that this only compares `DW_AT_name`,
+    /// so it will not find qualified names such as those in a `.gdb_index`.
+    ///
+    /// ```rust,no_run
+    /// # fn example<R: gimli::Reader>(
+    /// #     dwarf: &gimli::Dwarf<R>,
+    /// #     debug_names: &gimli::DebugNames<R>,
+    /// #     debug_pubnames: &gimli::DebugPubNames<R>,
+    /// #     debug_pubtypes: &gimli::DebugPubTypes<R>,
+    /// # ) -> gimli::Result<()> {
+    /// let tables: [&dyn gimli::NameLookup<R>; 3] = [debug_names, debug_pubnames, debug_pubtypes];
+    /// for entry in dwarf.lookup_name(&tables, b"main")? {
+    ///     if let Some(offset) = entry.die_section_offset() {
+    ///         let (unit, offset) = dwarf.entry_at(offset)?;
+    ///         let entry = unit.entry(offset)?;
+    ///         // ...
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn lookup_name(
+        &self,
+        tables: &[&dyn NameLookup<R>],
+        name: &[u8],
+    ) -> Result<Vec<NameLookupEntry<R::Offset>>> {
+        let mut found = Vec::new();
+        let mut entries = Vec::new();
+        let mut searched = false;
+        for table in tables.iter().filter(|table| !table.is_empty()) {
+            table.lookup_name(self, name, &mut found)?;
+            searched = true;
+        }
+        if !searched {
+            let mut units = self.units();
+            while let Some(header) = units.next()? {
+                self.find_name_in_unit(header, name, &mut entries)?;
+            }
+        }
+        for entry in found {
+            if entry.die_offset.is_some() {
+                entries.push(entry);
+            } else {
+                let header = self.unit_header_at(entry.unit_offset)?;
+                self.find_name_in_unit(header, name, &mut entries)?;
+            }
+        }
+        let mut unique = Vec::with_capacity(entries.len());
+        for entry in entries {
+            if !unique.contains(&entry) {
+                unique.push(entry);
+            }
+        }
+        Ok(unique)
+    }
+
+    fn find_name_in_unit(
+        &self,
+        header: UnitHeader<R>,
+        name: &[u8],
+        entries: &mut Vec<NameLookupEntry<R::Offset>>,
+    ) -> Result<()> {
+        let unit_offset = header.offset();
+        let unit = self.unit(header)?;
+        let mut cursor = unit.entries();
+        while let Some((_, entry)) = cursor.next_dfs()? {
+            if let Some(value) = entry.attr_value(constants::DW_AT_name)? {
+                if self.attr_string(&unit, value)?.to_slice()? == name {
+                    entries.push(NameLookupEntry {
+                        unit_offset,
+                        die_offset: Some(entry.offset()),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::*;
+    use crate::endianity::LittleEndian;
+    use crate::read::abbrev::tests::AbbrevSectionMethods;
+    use crate::read::names::tests::debug_names;
+    use crate::read::DebugStr;
+    use crate::test_util::{compile_unit, test_dwarf, GimliSectionMethods};
+    use crate::Format;
+    use test_assembler::{Endian, Label, LabelMaker, Section};
+
+    #[test]
+    fn test_lookup_name() {
+        #[rustfmt::skip]
+        let abbrev = Section::with_endian(Endian::Little)
+            .abbrev(1, DW_TAG_compile_unit, DW_CHILDREN_yes)
+                .abbrev_attr(DW_AT_name, DW_FORM_string)
+                .abbrev_attr_null()
+            .abbrev(2, DW_TAG_subprogram, DW_CHILDREN_no)
+                .abbrev_attr(DW_AT_name, DW_FORM_string)
+                .abbrev_attr_null()
+            .abbrev(3, DW_TAG_base_type, DW_CHILDREN_no)
+                .abbrev_attr(DW_AT_name, DW_FORM_string)
+                .abbrev_attr_null()
+            .abbrev_null();
+        let abbrev = abbrev.get_contents().unwrap();
+
+        let main = Label::new();
+        let helper = Label::new();
+        let int = Label::new();
+        #[rustfmt::skip]
+        let entries = Section::with_endian(Endian::Little)
+            .uleb(1).append_bytes(b"a.c\0")
+            .mark(&main)
+            .uleb(2).append_bytes(b"main\0")
+            .mark(&helper)
+            .uleb(2).append_bytes(b"helper\0")
+            .mark(&int)
+            .uleb(3).append_bytes(b"int\0")
+            .D8(0);
+        let info = compile_unit(entries);
+        let main = main.value().unwrap() as usize;
+        let helper = helper.value().unwrap() as usize;
+        let int = int.value().unwrap() as usize;
+
+        let length = Label::new();
+        let start = Label::new();
+        let end = Label::new();
+        #[rustfmt::skip]
+        let pubnames = Section::with_endian(Endian::Little)
+            .initial_length(Format::Dwarf32, &length, &start)
+            .L16(2)
+            .L32(0)
+            .L32(info.len() as u32)
+            .L32(main as u32).append_bytes(b"main\0")
+            .L32(0)
+            .mark(&end);
+        length.set_const((&end - &start) as u64);
+        let pubnames = pubnames.get_contents().unwrap();
+
+        let length = Label::new();
+        let start = Label::new();
+        let end = Label::new();
+        #[rustfmt::skip]
+        let pubtypes = Section::with_endian(Endian::Little)
+            .initial_length(Format::Dwarf32, &length, &start)
+            .L16(2)
+            .L32(0)
+            .L32(info.len() as u32)
+            .L32(int as u32).append_bytes(b"int\0")
+            .L32(0)
+            .mark(&end);
+        length.set_const((&end - &start) as u64);
+        let pubtypes = pubtypes.get_contents().unwrap();
+
+        let (names, debug_str) = debug_names(
+            &[0],
+            &[
+                (b"main", DW_TAG_subprogram, main as u32),
+                (b"helper", DW_TAG_subprogram, helper as u32),
+            ],
+            true,
+        );
+
+        let dwarf = Dwarf {
+            debug_str: DebugStr::new(&debug_str, LittleEndian),
+            ..test_dwarf(&abbrev, &info)
+        };
+        let debug_pubnames = DebugPubNames::new(&pubnames, LittleEndian);
+        let debug_pubtypes = DebugPubTypes::new(&pubtypes, LittleEndian);
+        let debug_names = DebugNames::new(&names, LittleEndian);
+        let empty = DebugPubNames::new(&[], LittleEndian);
+        let entry = |offset| NameLookupEntry {
+            unit_offset: UnitSectionOffset::DebugInfoOffset(DebugInfoOffset(0)),
+            die_offset: Some(UnitOffset(offset)),
+        };
+
+        // All non-empty tables are searched.
+        let pub_tables: [&dyn NameLookup<_>; 3] = [&empty, &debug_pubnames, &debug_pubtypes];
+        assert_eq!(
+            dwarf.lookup_name(&pub_tables, b"main"),
+            Ok(vec![entry(main)])
+        );
+        assert_eq!(dwarf.lookup_name(&pub_tables, b"int"), Ok(vec![entry(int)]));
+        assert_eq!(dwarf.lookup_name(&pub_tables, b"helper"), Ok(vec![]));
+
+        // Duplicate entries are removed.
+        let tables: [&dyn NameLookup<_>; 3] = [&debug_names, &debug_pubnames, &debug_pubtypes];
+        assert_eq!(dwarf.lookup_name(&tables, b"main"), Ok(vec![entry(main)]));
+        assert_eq!(
+            dwarf.lookup_name(&tables, b"helper"),
+            Ok(vec![entry(helper)])
+        );
+        assert_eq!(dwarf.lookup_name(&tables, b"int"), Ok(vec![entry(int)]));
+
+        // The entry can be found using its section offset.
+        let found = dwarf.lookup_name(&tables, b"helper").unwrap();
+        let offset = found[0].die_section_offset().unwrap();
+        assert_eq!(
+            offset,
+            UnitSectionOffset::DebugInfoOffset(DebugInfoOffset(helper))
+        );
+        let (unit, offset) = dwarf.entry_at(offset).unwrap();
+        assert_eq!(offset, UnitOffset(helper));
+        assert_eq!(unit.entry(offset).unwrap().tag(), DW_TAG_subprogram);
+
+        // Otherwise the units are searched.
+        assert_eq!(
+            dwarf.lookup_name(&[&empty], b"helper"),
+            Ok(vec![entry(helper)])
+        );
+        assert_eq!(dwarf.lookup_name(&[], b"main"), Ok(vec![entry(main)]));
+        assert_eq!(dwarf.lookup_name(&[], b"missing"), Ok(vec![]));
+    }
+}
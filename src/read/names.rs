@@ -0,0 +1,591 @@
+use alloc::vec::Vec;
+
+use crate::common::{DebugInfoOffset, DebugStrOffset, DebugTypeSignature, Format, SectionId};
+use crate::constants;
+use crate::endianity::Endianity;
+use crate::read::{
+    DebugStr, EndianSlice, Error, Reader, ReaderOffset, Result, Section, UnitOffset,
+};
+
+/// The `DebugNames` struct represents the DWARF name index information
+/// found in the `.debug_names` section.
+///
+/// The section contains one or more name indexes. Each name index covers
+/// a list of compilation units and type units.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct DebugNames<R> {
+    section: R,
+}
+
+impl<'input, Endian> DebugNames<EndianSlice<'input, Endian>>
+where
+    Endian: Endianity,
+{
+    /// Construct a new `DebugNames` instance from the data in the `.debug_names`
+    /// section.
+    ///
+    /// It is the caller's responsibility to read the `.debug_names` section and
+    /// present it as a `&[u8]` slice. That means using some ELF loader on
+    /// Linux, a Mach-O loader on macOS, etc.
+    ///
+    /// ```
+    /// use gimli::{DebugNames, LittleEndian};
+    ///
+    /// # let buf = [];
+    /// # let read_debug_names_section_somehow = || &buf;
+    /// let debug_names = DebugNames::new(read_debug_names_section_somehow(), LittleEndian);
+    /// ```
+    pub fn new(section: &'input [u8], endian: Endian) -> Self {
+        Self::from(EndianSlice::new(section, endian))
+    }
+}
+
+impl<R: Reader> DebugNames<R> {
+    /// Iterate the name indexes in the `.debug_names` section.
+    pub fn indexes(&self) -> NameIndexIter<R> {
+        NameIndexIter {
+            input: self.section.clone(),
+        }
+    }
+}
+
+impl<R> Section<R> for DebugNames<R> {
+    fn id() -> SectionId {
+        SectionId::DebugNames
+    }
+
+    fn reader(&self) -> &R {
+        &self.section
+    }
+}
+
+impl<R> From<R> for DebugNames<R> {
+    fn from(section: R) -> Self {
+        DebugNames { section }
+    }
+}
+
+/// An iterator over the name indexes in a `.debug_names` section.
+#[derive(Debug, Clone)]
+pub struct NameIndexIter<R: Reader> {
+    input: R,
+}
+
+impl<R: Reader> NameIndexIter<R> {
+    /// Advance the iterator and return the next name index.
+    ///
+    /// Returns `Ok(None)` when iteration is complete. If an error occurs while
+    /// parsing the next name index, then this error is returned as `Err(e)`,
+    /// and all subsequent calls return `Ok(None)`.
+    pub fn next(&mut self) -> Result<Option<NameIndex<R>>> {
+        if self.input.is_empty() {
+            return Ok(None);
+        }
+        match NameIndex::parse(&mut self.input) {
+            Ok(index) => Ok(Some(index)),
+            Err(e) => {
+                self.input.empty();
+                Err(e)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "fallible-iterator")]
+impl<R: Reader> fallible_iterator::FallibleIterator for NameIndexIter<R> {
+    type Item = NameIndex<R>;
+    type Error = Error;
+
+    fn next(&mut self) -> ::core::result::Result<Option<Self::Item>, Self::Error> {
+        NameIndexIter::next(self)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct NameAbbreviation {
+    code: u64,
+    tag: constants::DwTag,
+    attributes: Vec<(constants::DwIdx, constants::DwForm)>,
+}
+
+/// A name index in a `.debug_names` section.
+#[derive(Debug, Clone)]
+pub struct NameIndex<R: Reader> {
+    format: Format,
+    comp_unit_count: u32,
+    local_type_unit_count: u32,
+    foreign_type_unit_count: u32,
+    bucket_count: u32,
+    name_count: u32,
+    comp_units: R,
+    local_type_units: R,
+    foreign_type_units: R,
+    buckets: R,
+    hashes: R,
+    string_offsets: R,
+    entry_offsets: R,
+    abbreviations: Vec<NameAbbreviation>,
+    entry_pool: R,
+}
+
+impl<R: Reader> NameIndex<R> {
+    fn parse(input: &mut R) -> Result<Self> {
+        let (length, format) = input.read_initial_length()?;
+        let mut rest = input.split(length)?;
+        let version = rest.read_u16()?;
+        if version != 5 {
+            return Err(Error::UnknownVersion(version.into()));
+        }
+        let _padding = rest.read_u16()?;
+        let comp_unit_count = rest.read_u32()?;
+        let local_type_unit_count = rest.read_u32()?;
+        let foreign_type_unit_count = rest.read_u32()?;
+        let bucket_count = rest.read_u32()?;
+        let name_count = rest.read_u32()?;
+        let abbrev_table_size = rest.read_u32()?;
+        let augmentation_string_size = rest.read_u32()?;
+        rest.skip(R::Offset::from_u32(augmentation_string_size))?;
+
+        let offset_size = u64::from(format.word_size());
+        let mut table = |count: u32, size: u64| -> Result<R> {
+            let len = u64::from(count)
+                .checked_mul(size)
+                .ok_or(Error::InvalidNameIndex)?;
+            rest.split(R::Offset::from_u64(len)?)
+        };
+        let comp_units = table(comp_unit_count, offset_size)?;
+        let local_type_units = table(local_type_unit_count, offset_size)?;
+        let foreign_type_units = table(foreign_type_unit_count, 8)?;
+        let buckets = table(bucket_count, 4)?;
+        let hashes = table(if bucket_count == 0 { 0 } else { name_count }, 4)?;
+        let string_offsets = table(name_count, offset_size)?;
+        let entry_offsets = table(name_count, offset_size)?;
+        let mut abbrev_table = table(abbrev_table_size, 1)?;
+
+        let mut abbreviations = Vec::new();
+        loop {
+            let code = abbrev_table.read_uleb128()?;
+            if code == 0 {
+                break;
+            }
+            let tag = constants::DwTag(abbrev_table.read_uleb128_u16()?);
+            let mut attributes = Vec::new();
+            loop {
+                let index = constants::DwIdx(abbrev_table.read_uleb128_u16()?);
+                let form = constants::DwForm(abbrev_table.read_uleb128_u16()?);
+                if index.0 == 0 && form.0 == 0 {
+                    break;
+                }
+                attributes.push((index, form));
+            }
+            abbreviations.push(NameAbbreviation {
+                code,
+                tag,
+                attributes,
+            });
+        }
+
+        Ok(NameIndex {
+            format,
+            comp_unit_count,
+            local_type_unit_count,
+            foreign_type_unit_count,
+            bucket_count,
+            name_count,
+            comp_units,
+            local_type_units,
+            foreign_type_units,
+            buckets,
+            hashes,
+            string_offsets,
+            entry_offsets,
+            abbreviations,
+            entry_pool: rest,
+        })
+    }
+
+    /// Return the number of compilation units in the index.
+    pub fn comp_unit_count(&self) -> u32 {
+        self.comp_unit_count
+    }
+
+    /// Return the number of type units in the index that are in the same
+    /// object file.
+    pub fn local_type_unit_count(&self) -> u32 {
+        self.local_type_unit_count
+    }
+
+    /// Return the number of type units in the index that are in other
+    /// object files.
+    pub fn foreign_type_unit_count(&self) -> u32 {
+        self.foreign_type_unit_count
+    }
+
+    /// Return the number of names in the index.
+    pub fn name_count(&self) -> u32 {
+        self.name_count
+    }
+
+    /// Return the offset of the compilation unit at the given index.
+    pub fn comp_unit(&self, index: u32) -> Result<DebugInfoOffset<R::Offset>> {
+        self.unit_offset(&self.comp_units, self.comp_unit_count, index)
+    }
+
+    /// Return the offset of the local type unit at the given index.
+    ///
+    /// The index is relative to the local type units, and does not include
+    /// the compilation units.
+    pub fn local_type_unit(&self, index: u32) -> Result<DebugInfoOffset<R::Offset>> {
+        self.unit_offset(&self.local_type_units, self.local_type_unit_count, index)
+    }
+
+    /// Return the signature of the foreign type unit at the given index.
+    ///
+    /// The index is relative to the foreign type units, and does not include
+    /// the compilation units or local type units.
+    pub fn foreign_type_unit(&self, index: u32) -> Result<DebugTypeSignature> {
+        if index >= self.foreign_type_unit_count {
+            return Err(Error::InvalidNameIndex);
+        }
+        let mut input = self.foreign_type_units.clone();
+        input.skip(R::Offset::from_u64(u64::from(index) * 8)?)?;
+        Ok(DebugTypeSignature(input.read_u64()?))
+    }
+
+    fn unit_offset(&self, list: &R, count: u32, index: u32) -> Result<DebugInfoOffset<R::Offset>> {
+        if index >= count {
+            return Err(Error::InvalidNameIndex);
+        }
+        let mut input = list.clone();
+        let size = u64::from(self.format.word_size());
+        input.skip(R::Offset::from_u64(u64::from(index) * size)?)?;
+        input.read_offset(self.format).map(DebugInfoOffset)
+    }
+
+    /// Find the entries for the given name.
+    ///
+    /// The names in the index are offsets into `debug_str`. If the index has
+    /// a hash table then it is used for the lookup, otherwise every name is
+    /// compared.
+    ///
+    /// Returns an empty iterator if the name is not in the index.
+    pub fn find_name(&self, name: &[u8], debug_str: &DebugStr<R>) -> Result<NameEntryIter<'_, R>> {
+        if self.bucket_count == 0 {
+            for index in 0..self.name_count {
+                if self.name_matches(index, name, debug_str)? {
+                    return self.entries(index);
+                }
+            }
+            return Ok(self.entry_iter(None));
+        }
+
+        let hash = debug_names_hash(name);
+        let bucket = hash % self.bucket_count;
+        let mut buckets = self.buckets.clone();
+        buckets.skip(R::Offset::from_u64(u64::from(bucket) * 4)?)?;
+        // The bucket contains the 1-based index of the first name in the bucket.
+        let start = buckets.read_u32()?;
+        if start == 0 {
+            return Ok(self.entry_iter(None));
+        }
+        let mut hashes = self.hashes.clone();
+        hashes.skip(R::Offset::from_u64(u64::from(start - 1) * 4)?)?;
+        for index in start - 1..self.name_count {
+            let name_hash = hashes.read_u32()?;
+            if name_hash % self.bucket_count != bucket {
+                break;
+            }
+            if name_hash == hash && self.name_matches(index, name, debug_str)? {
+                return self.entries(index);
+            }
+        }
+        Ok(self.entry_iter(None))
+    }
+
+    fn name_matches(&self, index: u32, name: &[u8], debug_str: &DebugStr<R>) -> Result<bool> {
+        let mut input = self.string_offsets.clone();
+        let size = u64::from(self.format.word_size());
+        input.skip(R::Offset::from_u64(u64::from(index) * size)?)?;
+        let offset = input.read_offset(self.format)?;
+        Ok(debug_str.get_str(DebugStrOffset(offset))?.to_slice()? == name)
+    }
+
+    fn entries(&self, index: u32) -> Result<NameEntryIter<'_, R>> {
+        let mut input = self.entry_offsets.clone();
+        let size = u64::from(self.format.word_size());
+        input.skip(R::Offset::from_u64(u64::from(index) * size)?)?;
+        let offset = input.read_offset(self.format)?;
+        let mut entries = self.entry_pool.clone();
+        entries.skip(offset)?;
+        Ok(self.entry_iter(Some(entries)))
+    }
+
+    fn entry_iter(&self, input: Option<R>) -> NameEntryIter<'_, R> {
+        NameEntryIter {
+            index: self,
+            input: input.unwrap_or_else(|| {
+                let mut input = self.entry_pool.clone();
+                input.empty();
+                input
+            }),
+        }
+    }
+}
+
+/// The hash function used for the hash table of a `.debug_names` section.
+fn debug_names_hash(name: &[u8]) -> u32 {
+    let mut hash = 5381u32;
+    for c in name {
+        hash = hash.wrapping_mul(33).wrapping_add(u32::from(*c));
+    }
+    hash
+}
+
+/// An entry for a name in a `.debug_names` section.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NameEntry<T = usize> {
+    /// The tag of the debugging information entry.
+    pub tag: constants::DwTag,
+    /// The `DW_IDX_compile_unit` index of the compilation unit.
+    ///
+    /// This is omitted by producers if there is only one compilation unit
+    /// in the name index.
+    pub compile_unit: Option<u64>,
+    /// The `DW_IDX_type_unit` index of the type unit.
+    ///
+    /// This indexes the local type units followed by the foreign type units.
+    pub type_unit: Option<u64>,
+    /// The `DW_IDX_die_offset` offset of the debugging information entry
+    /// within its unit.
+    pub die_offset: Option<UnitOffset<T>>,
+}
+
+/// An iterator over the entries for a name in a `.debug_names` section.
+#[derive(Debug, Clone)]
+pub struct NameEntryIter<'index, R: Reader> {
+    index: &'index NameIndex<R>,
+    input: R,
+}
+
+impl<'index, R: Reader> NameEntryIter<'index, R> {
+    /// Advance the iterator and return the next entry.
+    ///
+    /// Returns `Ok(None)` when iteration is complete. If an error occurs while
+    /// parsing the next entry, then this error is returned as `Err(e)`, and all
+    /// subsequent calls return `Ok(None)`.
+    pub fn next(&mut self) -> Result<Option<NameEntry<R::Offset>>> {
+        if self.input.is_empty() {
+            return Ok(None);
+        }
+        match self.parse() {
+            Ok(Some(entry)) => Ok(Some(entry)),
+            Ok(None) => {
+                self.input.empty();
+                Ok(None)
+            }
+            Err(e) => {
+                self.input.empty();
+                Err(e)
+            }
+        }
+    }
+
+    fn parse(&mut self) -> Result<Option<NameEntry<R::Offset>>> {
+        let code = self.input.read_uleb128()?;
+        if code == 0 {
+            return Ok(None);
+        }
+        let abbrev = self
+            .index
+            .abbreviations
+            .iter()
+            .find(|abbrev| abbrev.code == code)
+            .ok_or(Error::UnknownAbbreviation)?;
+        let mut entry = NameEntry {
+            tag: abbrev.tag,
+            compile_unit: None,
+            type_unit: None,
+            die_offset: None,
+        };
+        for &(index, form) in &abbrev.attributes {
+            let value = self.read_value(form)?;
+            match index {
+                constants::DW_IDX_compile_unit => entry.compile_unit = Some(value),
+                constants::DW_IDX_type_unit => entry.type_unit = Some(value),
+                constants::DW_IDX_die_offset => {
+                    entry.die_offset = Some(UnitOffset(R::Offset::from_u64(value)?));
+                }
+                _ => {}
+            }
+        }
+        Ok(Some(entry))
+    }
+
+    fn read_value(&mut self, form: constants::DwForm) -> Result<u64> {
+        let input = &mut self.input;
+        match form {
+            constants::DW_FORM_flag_present => Ok(1),
+            constants::DW_FORM_data1 | constants::DW_FORM_ref1 | constants::DW_FORM_flag => {
+                input.read_u8().map(u64::from)
+            }
+            constants::DW_FORM_data2 | constants::DW_FORM_ref2 => input.read_u16().map(u64::from),
+            constants::DW_FORM_data4 | constants::DW_FORM_ref4 => input.read_u32().map(u64::from),
+            constants::DW_FORM_data8 | constants::DW_FORM_ref8 | constants::DW_FORM_ref_sig8 => {
+                input.read_u64()
+            }
+            constants::DW_FORM_udata | constants::DW_FORM_ref_udata => input.read_uleb128(),
+            _ => Err(Error::UnknownForm),
+        }
+    }
+}
+
+#[cfg(feature = "fallible-iterator")]
+impl<'index, R: Reader> fallible_iterator::FallibleIterator for NameEntryIter<'index, R> {
+    type Item = NameEntry<R::Offset>;
+    type Error = Error;
+
+    fn next(&mut self) -> ::core::result::Result<Option<Self::Item>, Self::Error> {
+        NameEntryIter::next(self)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::constants::*;
+    use crate::endianity::LittleEndian;
+    use crate::test_util::GimliSectionMethods;
+    use test_assembler::{Endian, Label, LabelMaker, Section};
+
+    /// Build a `.debug_names` section and its `.debug_str` section.
+    ///
+    /// Each name has a single entry with the given tag and DIE offset
+    /// in the first compilation unit.
+    pub(crate) fn debug_names(
+        comp_units: &[u32],
+        names: &[(&[u8], DwTag, u32)],
+        hashed: bool,
+    ) -> (Vec<u8>, Vec<u8>) {
+        let mut debug_str = Vec::new();
+        let mut names: Vec<_> = names
+            .iter()
+            .map(|&(name, tag, die_offset)| {
+                let offset = debug_str.len() as u32;
+                debug_str.extend_from_slice(name);
+                debug_str.push(0);
+                (debug_names_hash(name), offset, tag, die_offset)
+            })
+            .collect();
+        let bucket_count = if hashed { 2 } else { 0 };
+        if hashed {
+            names.sort_by_key(|name| name.0 % bucket_count);
+        }
+
+        let length = Label::new();
+        let start = Label::new();
+        let end = Label::new();
+        let mut section = Section::with_endian(Endian::Little)
+            .initial_length(Format::Dwarf32, &length, &start)
+            .L16(5)
+            .L16(0)
+            .L32(comp_units.len() as u32)
+            .L32(0)
+            .L32(0)
+            .L32(bucket_count)
+            .L32(names.len() as u32);
+        let abbrev_size = Label::new();
+        section = section.L32(&abbrev_size).L32(4).append_bytes(b"test");
+        for offset in comp_units {
+            section = section.L32(*offset);
+        }
+        if hashed {
+            for bucket in 0..bucket_count {
+                let index = names
+                    .iter()
+                    .position(|name| name.0 % bucket_count == bucket);
+                section = section.L32(index.map_or(0, |index| index as u32 + 1));
+            }
+            for name in &names {
+                section = section.L32(name.0);
+            }
+        }
+        for name in &names {
+            section = section.L32(name.1);
+        }
+        // Each entry is 6 bytes: abbreviation code, DIE offset and terminator.
+        for index in 0..names.len() {
+            section = section.L32(index as u32 * 6);
+        }
+        let abbrev_start = Label::new();
+        let abbrev_end = Label::new();
+        #[rustfmt::skip]
+        let mut section = section
+            .mark(&abbrev_start)
+            .uleb(1).uleb(DW_TAG_subprogram.0.into())
+                .uleb(DW_IDX_die_offset.0.into()).uleb(DW_FORM_ref4.0.into())
+                .uleb(0).uleb(0)
+            .uleb(2).uleb(DW_TAG_base_type.0.into())
+                .uleb(DW_IDX_die_offset.0.into()).uleb(DW_FORM_ref4.0.into())
+                .uleb(0).uleb(0)
+            .uleb(0)
+            .mark(&abbrev_end);
+        abbrev_size.set_const((&abbrev_end - &abbrev_start) as u64);
+        for name in &names {
+            let code = if name.2 == DW_TAG_subprogram { 1 } else { 2 };
+            section = section.uleb(code).L32(name.3).D8(0);
+        }
+        let section = section.mark(&end);
+        length.set_const((&end - &start) as u64);
+        (section.get_contents().unwrap(), debug_str)
+    }
+
+    #[test]
+    fn test_find_name() {
+        let names: &[(&[u8], _, _)] = &[
+            (b"main", DW_TAG_subprogram, 0x20),
+            (b"int", DW_TAG_base_type, 0x30),
+            (b"helper", DW_TAG_subprogram, 0x40),
+        ];
+        for hashed in [false, true] {
+            let (section, debug_str) = debug_names(&[0x100], names, hashed);
+            let debug_names = DebugNames::new(&section, LittleEndian);
+            let debug_str = DebugStr::new(&debug_str, LittleEndian);
+            let mut indexes = debug_names.indexes();
+            let index = indexes.next().unwrap().unwrap();
+            assert!(indexes.next().unwrap().is_none());
+            assert_eq!(index.comp_unit_count(), 1);
+            assert_eq!(index.name_count(), 3);
+            assert_eq!(index.comp_unit(0), Ok(DebugInfoOffset(0x100)));
+            assert_eq!(index.comp_unit(1), Err(Error::InvalidNameIndex));
+
+            for (name, tag, die_offset) in names {
+                let mut entries = index.find_name(name, &debug_str).unwrap();
+                assert_eq!(
+                    entries.next(),
+                    Ok(Some(NameEntry {
+                        tag: *tag,
+                        compile_unit: None,
+                        type_unit: None,
+                        die_offset: Some(UnitOffset(*die_offset as usize)),
+                    }))
+                );
+                assert_eq!(entries.next(), Ok(None));
+            }
+            let mut entries = index.find_name(b"missing", &debug_str).unwrap();
+            assert_eq!(entries.next(), Ok(None));
+        }
+    }
+
+    #[test]
+    fn test_unknown_version() {
+        let section = Section::with_endian(Endian::Little)
+            .L32(2)
+            .L16(4)
+            .get_contents()
+            .unwrap();
+        let debug_names = DebugNames::new(&section, LittleEndian);
+        let mut indexes = debug_names.indexes();
+        assert_eq!(indexes.next().err(), Some(Error::UnknownVersion(4)));
+        assert!(indexes.next().unwrap().is_none());
+    }
+}
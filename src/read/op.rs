@@ -7,7 +7,9 @@ use core::mem;
 use super::util::{ArrayLike, ArrayVec};
 use crate::common::{DebugAddrIndex, DebugInfoOffset, Encoding, Register};
 use crate::constants;
-use crate::read::{Error, Reader, ReaderOffset, Result, StoreOnHeap, UnitOffset, Value, ValueType};
+use crate::read::{
+    Error, Reader, ReaderOffset, Result, ResultIterator, StoreOnHeap, UnitOffset, Value, ValueType,
+};
 
 /// A reference to a DIE, either relative to the current CU or
 /// relative to the section.
@@ -290,6 +292,24 @@ where
         /// The index of the stack item. 0 is the bottom of the operand stack.
         index: u32,
     },
+
+    /// Pop the top stack entry as an address space identifier, and the next
+    /// entry as an address, and push the address as a memory location in
+    /// that address space.
+    ///
+    /// Represents `DW_OP_LLVM_user DW_OP_LLVM_form_aspace_address`.
+    FormAspaceAddress,
+    /// Pop the top stack entry as an address space identifier, and push the
+    /// contents of the register plus the offset as a memory location in that
+    /// address space.
+    ///
+    /// Represents `DW_OP_LLVM_user DW_OP_LLVM_aspace_bregx`.
+    AspaceRegisterOffset {
+        /// The register number.
+        register: Register,
+        /// The offset to add.
+        offset: i64,
+    },
 }
 
 #[derive(Debug)]
@@ -320,6 +340,13 @@ where
         /// The address.
         address: u64,
     },
+    /// The piece is found in memory in a target-specific address space.
+    AddressSpace {
+        /// The address.
+        address: u64,
+        /// The address space.
+        space: u64,
+    },
     /// The piece has no location but its value is known.
     Value {
         /// The value.
@@ -807,6 +834,18 @@ where
                 }
                 _ => Err(Error::InvalidExpression(name)),
             },
+            constants::DW_OP_LLVM_user => {
+                let opcode = bytes.read_uleb128_u16()?;
+                match constants::DwOpLlvmUser(opcode) {
+                    constants::DW_OP_LLVM_form_aspace_address => Ok(Operation::FormAspaceAddress),
+                    constants::DW_OP_LLVM_aspace_bregx => {
+                        let register = bytes.read_uleb128().and_then(Register::from_u64)?;
+                        let offset = bytes.read_sleb128()?;
+                        Ok(Operation::AspaceRegisterOffset { register, offset })
+                    }
+                    _ => Err(Error::InvalidExpression(name)),
+                }
+            }
             _ => Err(Error::InvalidExpression(name)),
         }
     }
@@ -825,6 +864,7 @@ enum EvaluationState<R: Reader> {
 enum EvaluationWaiting<R: Reader> {
     Memory,
     Register { offset: i64 },
+    AspaceRegister { offset: i64, space: u64 },
     FrameBase { offset: i64 },
     Tls,
     Cfa,
@@ -921,6 +961,7 @@ pub enum EvaluationResult<R: Reader> {
 
 /// The bytecode for a DWARF expression or location description.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Expression<R: Reader>(pub R);
 
 impl<R: Reader> Expression<R> {
@@ -995,6 +1036,24 @@ impl<R: Reader> fallible_iterator::FallibleIterator for OperationIter<R> {
     }
 }
 
+impl<R: Reader> OperationIter<R> {
+    /// Return an adapter that implements `Iterator`.
+    ///
+    /// See [`ResultIterator`] for more information.
+    pub fn iterator(self) -> ResultIterator<Self> {
+        ResultIterator::new(self)
+    }
+}
+
+impl<R: Reader> Iterator for ResultIterator<OperationIter<R>> {
+    type Item = Result<Operation<R>>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_with(OperationIter::next)
+    }
+}
+
 /// Specification of what storage should be used for [`Evaluation`].
 ///
 #[cfg_attr(
@@ -1018,6 +1077,7 @@ on the heap using [`Vec`]. This is the default storage type parameter for [`Eval
 ///
 /// impl<R: Reader> EvaluationStorage<R> for StoreOnStack {
 ///     type Stack = [Value; 64];
+///     type AddressSpaces = [Option<u64>; 64];
 ///     type ExpressionStack = [(R, R); 4];
 ///     type Result = [Piece<R>; 1];
 /// }
@@ -1044,6 +1104,10 @@ on the heap using [`Vec`]. This is the default storage type parameter for [`Eval
 pub trait EvaluationStorage<R: Reader> {
     /// The storage used for the evaluation stack.
     type Stack: ArrayLike<Item = Value>;
+    /// The storage used for the address space of each entry in the evaluation stack.
+    ///
+    /// This should have the same capacity as `Stack`.
+    type AddressSpaces: ArrayLike<Item = Option<u64>>;
     /// The storage used for the expression stack.
     type ExpressionStack: ArrayLike<Item = (R, R)>;
     /// The storage used for the results.
@@ -1053,6 +1117,7 @@ pub trait EvaluationStorage<R: Reader> {
 #[cfg(feature = "read")]
 impl<R: Reader> EvaluationStorage<R> for StoreOnHeap {
     type Stack = Vec<Value>;
+    type AddressSpaces = Vec<Option<u64>>;
     type ExpressionStack = Vec<(R, R)>;
     type Result = Vec<Piece<R>>;
 }
@@ -1108,6 +1173,7 @@ pub struct Evaluation<R: Reader, S: EvaluationStorage<R> = StoreOnHeap> {
     encoding: Encoding,
     object_address: Option<u64>,
     max_iterations: Option<u32>,
+    max_expression_depth: Option<usize>,
     iteration: u32,
     state: EvaluationState<R>,
 
@@ -1126,10 +1192,144 @@ pub struct Evaluation<R: Reader, S: EvaluationStorage<R> = StoreOnHeap> {
     // is stored here while evaluating the subroutine.
     expression_stack: ArrayVec<S::ExpressionStack>,
 
+    // The address space of each entry in `stack`, if the entry is an
+    // address in a target-specific address space. Stack operations move
+    // the address space with the entry, and adding an offset to an address
+    // keeps its address space. Other operations discard it.
+    address_spaces: ArrayVec<S::AddressSpaces>,
+
     value_result: Option<Value>,
     result: ArrayVec<S::Result>,
 }
 
+/// The state of an `Evaluation` before an operation is evaluated.
+///
+/// This is passed to the callback given to `Evaluation::with_trace`.
+#[derive(Debug)]
+pub struct EvaluationTrace<'a, R: Reader> {
+    /// The offset of the operation within the expression that contains it.
+    ///
+    /// This is relative to the expression for a `DW_OP_call*` subroutine
+    /// while it is being evaluated.
+    pub offset: u64,
+    /// The operation, including its operands.
+    pub operation: &'a Operation<R>,
+    /// The stack before the operation is evaluated. The top of the stack is last.
+    pub stack: &'a [Value],
+}
+
+/// The type of the callback given to `Evaluation::with_trace`.
+///
+/// The callback is invoked with the state of the evaluation before each
+/// operation is evaluated.
+pub type EvaluationTraceFn<'t, R> = dyn FnMut(&EvaluationTrace<'_, R>) + 't;
+
+/// An `Evaluation` that invokes a callback before each operation is evaluated.
+///
+/// This is created by `Evaluation::with_trace`. The callback may borrow
+/// local variables, since it is only used while this value exists.
+///
+/// ```rust,no_run
+/// # fn example<R: gimli::Reader>(bytecode: R, encoding: gimli::Encoding) -> gimli::Result<()> {
+/// let mut evaluation = gimli::Evaluation::new(bytecode, encoding);
+/// let mut offsets = Vec::new();
+/// let mut trace = |trace: &gimli::EvaluationTrace<'_, R>| offsets.push(trace.offset);
+/// let mut result = evaluation.with_trace(&mut trace).evaluate()?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct TracedEvaluation<'a, 't, R: Reader, S: EvaluationStorage<R> = StoreOnHeap> {
+    evaluation: &'a mut Evaluation<R, S>,
+    trace: &'t mut EvaluationTraceFn<'t, R>,
+}
+
+impl<'a, 't, R: Reader, S: EvaluationStorage<R>> core::fmt::Debug for TracedEvaluation<'a, 't, R, S>
+where
+    Evaluation<R, S>: core::fmt::Debug,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("TracedEvaluation")
+            .field("evaluation", &self.evaluation)
+            .finish()
+    }
+}
+
+impl<'a, 't, R: Reader, S: EvaluationStorage<R>> TracedEvaluation<'a, 't, R, S> {
+    /// See `Evaluation::evaluate`.
+    pub fn evaluate(&mut self) -> Result<EvaluationResult<R>> {
+        self.evaluation.evaluate_with(Some(self.trace))
+    }
+
+    /// See `Evaluation::resume_with_memory`.
+    pub fn resume_with_memory(&mut self, value: Value) -> Result<EvaluationResult<R>> {
+        self.evaluation.apply_memory(value)?;
+        self.evaluation.evaluate_internal(Some(self.trace))
+    }
+
+    /// See `Evaluation::resume_with_register`.
+    pub fn resume_with_register(&mut self, value: Value) -> Result<EvaluationResult<R>> {
+        self.evaluation.apply_register(value)?;
+        self.evaluation.evaluate_internal(Some(self.trace))
+    }
+
+    /// See `Evaluation::resume_with_frame_base`.
+    pub fn resume_with_frame_base(&mut self, frame_base: u64) -> Result<EvaluationResult<R>> {
+        self.evaluation.apply_frame_base(frame_base)?;
+        self.evaluation.evaluate_internal(Some(self.trace))
+    }
+
+    /// See `Evaluation::resume_with_tls`.
+    pub fn resume_with_tls(&mut self, value: u64) -> Result<EvaluationResult<R>> {
+        self.evaluation.apply_tls(value)?;
+        self.evaluation.evaluate_internal(Some(self.trace))
+    }
+
+    /// See `Evaluation::resume_with_call_frame_cfa`.
+    pub fn resume_with_call_frame_cfa(&mut self, cfa: u64) -> Result<EvaluationResult<R>> {
+        self.evaluation.apply_call_frame_cfa(cfa)?;
+        self.evaluation.evaluate_internal(Some(self.trace))
+    }
+
+    /// See `Evaluation::resume_with_at_location`.
+    pub fn resume_with_at_location(&mut self, bytes: R) -> Result<EvaluationResult<R>> {
+        self.evaluation.apply_at_location(bytes)?;
+        self.evaluation.evaluate_internal(Some(self.trace))
+    }
+
+    /// See `Evaluation::resume_with_entry_value`.
+    pub fn resume_with_entry_value(&mut self, entry_value: Value) -> Result<EvaluationResult<R>> {
+        self.evaluation.apply_entry_value(entry_value)?;
+        self.evaluation.evaluate_internal(Some(self.trace))
+    }
+
+    /// See `Evaluation::resume_with_parameter_ref`.
+    pub fn resume_with_parameter_ref(
+        &mut self,
+        parameter_value: u64,
+    ) -> Result<EvaluationResult<R>> {
+        self.evaluation.apply_parameter_ref(parameter_value)?;
+        self.evaluation.evaluate_internal(Some(self.trace))
+    }
+
+    /// See `Evaluation::resume_with_relocated_address`.
+    pub fn resume_with_relocated_address(&mut self, address: u64) -> Result<EvaluationResult<R>> {
+        self.evaluation.apply_relocated_address(address)?;
+        self.evaluation.evaluate_internal(Some(self.trace))
+    }
+
+    /// See `Evaluation::resume_with_indexed_address`.
+    pub fn resume_with_indexed_address(&mut self, address: u64) -> Result<EvaluationResult<R>> {
+        self.evaluation.apply_indexed_address(address)?;
+        self.evaluation.evaluate_internal(Some(self.trace))
+    }
+
+    /// See `Evaluation::resume_with_base_type`.
+    pub fn resume_with_base_type(&mut self, base_type: ValueType) -> Result<EvaluationResult<R>> {
+        self.evaluation.apply_base_type(base_type)?;
+        self.evaluation.evaluate_internal(Some(self.trace))
+    }
+}
+
 #[cfg(feature = "read")]
 impl<R: Reader> Evaluation<R> {
     /// Create a new DWARF expression evaluator.
@@ -1166,6 +1366,7 @@ impl<R: Reader, S: EvaluationStorage<R>> Evaluation<R, S> {
             encoding,
             object_address: None,
             max_iterations: None,
+            max_expression_depth: None,
             iteration: 0,
             state: EvaluationState::Start(None),
             addr_mask: if encoding.address_size == 8 {
@@ -1176,6 +1377,7 @@ impl<R: Reader, S: EvaluationStorage<R>> Evaluation<R, S> {
             stack: Default::default(),
             expression_stack: Default::default(),
             pc,
+            address_spaces: Default::default(),
             value_result: None,
             result: Default::default(),
         }
@@ -1223,19 +1425,73 @@ impl<R: Reader, S: EvaluationStorage<R>> Evaluation<R, S> {
         self.max_iterations = Some(value);
     }
 
-    fn pop(&mut self) -> Result<Value> {
-        match self.stack.pop() {
-            Some(value) => Ok(value),
-            None => Err(Error::NotEnoughStackItems),
+    /// Set the maximum nesting depth of the expressions provided by
+    /// `resume_with_at_location` for `DW_OP_call*` operations.
+    ///
+    /// Exceeding this depth fails with `Error::ExpressionTooDeep`.
+    /// The default is not to have a maximum, other than the capacity
+    /// of the expression stack in the `EvaluationStorage`.
+    pub fn set_max_expression_depth(&mut self, value: usize) {
+        self.max_expression_depth = Some(value);
+    }
+
+    /// Return an evaluation that invokes `trace` before each operation is evaluated.
+    ///
+    /// The callback is given the operation and the current stack. This can
+    /// be used to log or step through the evaluation of an expression.
+    ///
+    /// The returned value has the same methods as `Evaluation` for evaluating
+    /// the expression and resuming the evaluation. Operations evaluated by
+    /// calling these methods on the `Evaluation` directly are not traced.
+    pub fn with_trace<'a, 't>(
+        &'a mut self,
+        trace: &'t mut EvaluationTraceFn<'t, R>,
+    ) -> TracedEvaluation<'a, 't, R, S> {
+        TracedEvaluation {
+            evaluation: self,
+            trace,
         }
     }
 
+    fn pop(&mut self) -> Result<Value> {
+        self.pop_entry().map(|(value, _)| value)
+    }
+
     fn push(&mut self, value: Value) -> Result<()> {
-        self.stack.try_push(value).map_err(|_| Error::StackFull)
+        self.push_entry(value, None)
+    }
+
+    // Pop a stack entry and its address space.
+    fn pop_entry(&mut self) -> Result<(Value, Option<u64>)> {
+        match (self.stack.pop(), self.address_spaces.pop()) {
+            (Some(value), Some(space)) => Ok((value, space)),
+            _ => Err(Error::NotEnoughStackItems),
+        }
     }
 
-    fn evaluate_one_operation(&mut self) -> Result<OperationEvaluationResult<R>> {
+    // Push a stack entry and its address space.
+    fn push_entry(&mut self, value: Value, space: Option<u64>) -> Result<()> {
+        self.stack.try_push(value).map_err(|_| Error::StackFull)?;
+        if self.address_spaces.try_push(space).is_err() {
+            self.stack.pop();
+            return Err(Error::StackFull);
+        }
+        Ok(())
+    }
+
+    fn evaluate_one_operation(
+        &mut self,
+        trace: Option<&mut EvaluationTraceFn<'_, R>>,
+    ) -> Result<OperationEvaluationResult<R>> {
+        let offset = self.pc.offset_from(&self.bytecode).into_u64();
         let operation = Operation::parse(&mut self.pc, self.encoding)?;
+        if let Some(trace) = trace {
+            trace(&EvaluationTrace {
+                offset,
+                operation: &operation,
+                stack: &self.stack,
+            });
+        }
 
         match operation {
             Operation::Deref {
@@ -1243,14 +1499,14 @@ impl<R: Reader, S: EvaluationStorage<R>> Evaluation<R, S> {
                 size,
                 space,
             } => {
-                let entry = self.pop()?;
+                let (entry, tagged_space) = self.pop_entry()?;
                 let addr = entry.to_u64(self.addr_mask)?;
                 let addr_space = if space {
                     let entry = self.pop()?;
                     let value = entry.to_u64(self.addr_mask)?;
                     Some(value)
                 } else {
-                    None
+                    tagged_space
                 };
                 return Ok(OperationEvaluationResult::Waiting(
                     EvaluationWaiting::Memory,
@@ -1273,21 +1529,22 @@ impl<R: Reader, S: EvaluationStorage<R>> Evaluation<R, S> {
                     return Err(Error::NotEnoughStackItems);
                 }
                 let value = self.stack[len - index - 1];
-                self.push(value)?;
+                let space = self.address_spaces[len - index - 1];
+                self.push_entry(value, space)?;
             }
             Operation::Swap => {
-                let top = self.pop()?;
-                let next = self.pop()?;
-                self.push(top)?;
-                self.push(next)?;
+                let top = self.pop_entry()?;
+                let next = self.pop_entry()?;
+                self.push_entry(top.0, top.1)?;
+                self.push_entry(next.0, next.1)?;
             }
             Operation::Rot => {
-                let one = self.pop()?;
-                let two = self.pop()?;
-                let three = self.pop()?;
-                self.push(one)?;
-                self.push(three)?;
-                self.push(two)?;
+                let one = self.pop_entry()?;
+                let two = self.pop_entry()?;
+                let three = self.pop_entry()?;
+                self.push_entry(one.0, one.1)?;
+                self.push_entry(three.0, three.1)?;
+                self.push_entry(two.0, two.1)?;
             }
 
             Operation::Abs => {
@@ -1309,9 +1566,9 @@ impl<R: Reader, S: EvaluationStorage<R>> Evaluation<R, S> {
             }
             Operation::Minus => {
                 let rhs = self.pop()?;
-                let lhs = self.pop()?;
+                let (lhs, space) = self.pop_entry()?;
                 let result = lhs.sub(rhs, self.addr_mask)?;
-                self.push(result)?;
+                self.push_entry(result, space)?;
             }
             Operation::Mod => {
                 let rhs = self.pop()?;
@@ -1342,16 +1599,16 @@ impl<R: Reader, S: EvaluationStorage<R>> Evaluation<R, S> {
                 self.push(result)?;
             }
             Operation::Plus => {
-                let rhs = self.pop()?;
-                let lhs = self.pop()?;
+                let (rhs, rhs_space) = self.pop_entry()?;
+                let (lhs, lhs_space) = self.pop_entry()?;
                 let result = lhs.add(rhs, self.addr_mask)?;
-                self.push(result)?;
+                self.push_entry(result, lhs_space.or(rhs_space))?;
             }
             Operation::PlusConstant { value } => {
-                let lhs = self.pop()?;
+                let (lhs, space) = self.pop_entry()?;
                 let rhs = Value::from_u64(lhs.value_type(), value)?;
                 let result = lhs.add(rhs, self.addr_mask)?;
-                self.push(result)?;
+                self.push_entry(result, space)?;
             }
             Operation::Shl => {
                 let rhs = self.pop()?;
@@ -1560,9 +1817,12 @@ impl<R: Reader, S: EvaluationStorage<R>> Evaluation<R, S> {
                 let location = if self.stack.is_empty() {
                     Location::Empty
                 } else {
-                    let entry = self.pop()?;
+                    let (entry, space) = self.pop_entry()?;
                     let address = entry.to_u64(self.addr_mask)?;
-                    Location::Address { address }
+                    match space {
+                        Some(space) => Location::AddressSpace { address, space },
+                        None => Location::Address { address },
+                    }
                 };
                 self.result
                     .try_push(Piece {
@@ -1597,6 +1857,22 @@ impl<R: Reader, S: EvaluationStorage<R>> Evaluation<R, S> {
             | Operation::WasmStack { .. } => {
                 return Err(Error::UnsupportedEvaluation);
             }
+
+            Operation::FormAspaceAddress => {
+                let space = self.pop()?.to_u64(self.addr_mask)?;
+                let address = self.pop()?;
+                self.push_entry(address, Some(space))?;
+            }
+            Operation::AspaceRegisterOffset { register, offset } => {
+                let space = self.pop()?.to_u64(self.addr_mask)?;
+                return Ok(OperationEvaluationResult::Waiting(
+                    EvaluationWaiting::AspaceRegister { offset, space },
+                    EvaluationResult::RequiresRegister {
+                        register,
+                        base_type: generic_type(),
+                    },
+                ));
+            }
         }
 
         Ok(OperationEvaluationResult::Incomplete)
@@ -1639,6 +1915,13 @@ impl<R: Reader, S: EvaluationStorage<R>> Evaluation<R, S> {
     /// value and resume the evaluation by calling the appropriate resume_with
     /// method on `Evaluation`.
     pub fn evaluate(&mut self) -> Result<EvaluationResult<R>> {
+        self.evaluate_with(None)
+    }
+
+    fn evaluate_with(
+        &mut self,
+        trace: Option<&mut EvaluationTraceFn<'_, R>>,
+    ) -> Result<EvaluationResult<R>> {
         match self.state {
             EvaluationState::Start(initial_value) => {
                 if let Some(value) = initial_value {
@@ -1652,7 +1935,7 @@ impl<R: Reader, S: EvaluationStorage<R>> Evaluation<R, S> {
             EvaluationState::Waiting(_) => panic!(),
         };
 
-        match self.evaluate_internal() {
+        match self.evaluate_internal(trace) {
             Ok(r) => Ok(r),
             Err(e) => {
                 self.state = EvaluationState::Error(e);
@@ -1669,6 +1952,11 @@ impl<R: Reader, S: EvaluationStorage<R>> Evaluation<R, S> {
     /// # Panics
     /// Panics if this `Evaluation` did not previously stop with `EvaluationResult::RequiresMemory`.
     pub fn resume_with_memory(&mut self, value: Value) -> Result<EvaluationResult<R>> {
+        self.apply_memory(value)?;
+        self.evaluate_internal(None)
+    }
+
+    fn apply_memory(&mut self, value: Value) -> Result<()> {
         match self.state {
             EvaluationState::Error(err) => return Err(err),
             EvaluationState::Waiting(EvaluationWaiting::Memory) => {
@@ -1677,9 +1965,8 @@ impl<R: Reader, S: EvaluationStorage<R>> Evaluation<R, S> {
             _ => panic!(
                 "Called `Evaluation::resume_with_memory` without a preceding `EvaluationResult::RequiresMemory`"
             ),
-        };
-
-        self.evaluate_internal()
+        }
+        Ok(())
     }
 
     /// Resume the `Evaluation` with the provided `register` value.  This will apply
@@ -1690,6 +1977,11 @@ impl<R: Reader, S: EvaluationStorage<R>> Evaluation<R, S> {
     /// # Panics
     /// Panics if this `Evaluation` did not previously stop with `EvaluationResult::RequiresRegister`.
     pub fn resume_with_register(&mut self, value: Value) -> Result<EvaluationResult<R>> {
+        self.apply_register(value)?;
+        self.evaluate_internal(None)
+    }
+
+    fn apply_register(&mut self, value: Value) -> Result<()> {
         match self.state {
             EvaluationState::Error(err) => return Err(err),
             EvaluationState::Waiting(EvaluationWaiting::Register { offset }) => {
@@ -1697,12 +1989,16 @@ impl<R: Reader, S: EvaluationStorage<R>> Evaluation<R, S> {
                 let value = value.add(offset, self.addr_mask)?;
                 self.push(value)?;
             }
+            EvaluationState::Waiting(EvaluationWaiting::AspaceRegister { offset, space }) => {
+                let offset = Value::from_u64(value.value_type(), offset as u64)?;
+                let value = value.add(offset, self.addr_mask)?;
+                self.push_entry(value, Some(space))?;
+            }
             _ => panic!(
                 "Called `Evaluation::resume_with_register` without a preceding `EvaluationResult::RequiresRegister`"
             ),
-        };
-
-        self.evaluate_internal()
+        }
+        Ok(())
     }
 
     /// Resume the `Evaluation` with the provided `frame_base`.  This will
@@ -1713,6 +2009,11 @@ impl<R: Reader, S: EvaluationStorage<R>> Evaluation<R, S> {
     /// # Panics
     /// Panics if this `Evaluation` did not previously stop with `EvaluationResult::RequiresFrameBase`.
     pub fn resume_with_frame_base(&mut self, frame_base: u64) -> Result<EvaluationResult<R>> {
+        self.apply_frame_base(frame_base)?;
+        self.evaluate_internal(None)
+    }
+
+    fn apply_frame_base(&mut self, frame_base: u64) -> Result<()> {
         match self.state {
             EvaluationState::Error(err) => return Err(err),
             EvaluationState::Waiting(EvaluationWaiting::FrameBase { offset }) => {
@@ -1721,9 +2022,8 @@ impl<R: Reader, S: EvaluationStorage<R>> Evaluation<R, S> {
             _ => panic!(
                 "Called `Evaluation::resume_with_frame_base` without a preceding `EvaluationResult::RequiresFrameBase`"
             ),
-        };
-
-        self.evaluate_internal()
+        }
+        Ok(())
     }
 
     /// Resume the `Evaluation` with the provided `value`.  This will apply
@@ -1734,6 +2034,11 @@ impl<R: Reader, S: EvaluationStorage<R>> Evaluation<R, S> {
     /// # Panics
     /// Panics if this `Evaluation` did not previously stop with `EvaluationResult::RequiresTls`.
     pub fn resume_with_tls(&mut self, value: u64) -> Result<EvaluationResult<R>> {
+        self.apply_tls(value)?;
+        self.evaluate_internal(None)
+    }
+
+    fn apply_tls(&mut self, value: u64) -> Result<()> {
         match self.state {
             EvaluationState::Error(err) => return Err(err),
             EvaluationState::Waiting(EvaluationWaiting::Tls) => {
@@ -1742,9 +2047,8 @@ impl<R: Reader, S: EvaluationStorage<R>> Evaluation<R, S> {
             _ => panic!(
                 "Called `Evaluation::resume_with_tls` without a preceding `EvaluationResult::RequiresTls`"
             ),
-        };
-
-        self.evaluate_internal()
+        }
+        Ok(())
     }
 
     /// Resume the `Evaluation` with the provided `cfa`.  This will
@@ -1755,6 +2059,11 @@ impl<R: Reader, S: EvaluationStorage<R>> Evaluation<R, S> {
     /// # Panics
     /// Panics if this `Evaluation` did not previously stop with `EvaluationResult::RequiresCallFrameCfa`.
     pub fn resume_with_call_frame_cfa(&mut self, cfa: u64) -> Result<EvaluationResult<R>> {
+        self.apply_call_frame_cfa(cfa)?;
+        self.evaluate_internal(None)
+    }
+
+    fn apply_call_frame_cfa(&mut self, cfa: u64) -> Result<()> {
         match self.state {
             EvaluationState::Error(err) => return Err(err),
             EvaluationState::Waiting(EvaluationWaiting::Cfa) => {
@@ -1763,9 +2072,8 @@ impl<R: Reader, S: EvaluationStorage<R>> Evaluation<R, S> {
             _ => panic!(
                 "Called `Evaluation::resume_with_call_frame_cfa` without a preceding `EvaluationResult::RequiresCallFrameCfa`"
             ),
-        };
-
-        self.evaluate_internal()
+        }
+        Ok(())
     }
 
     /// Resume the `Evaluation` with the provided `bytes`.  This will
@@ -1775,11 +2083,21 @@ impl<R: Reader, S: EvaluationStorage<R>> Evaluation<R, S> {
     ///
     /// # Panics
     /// Panics if this `Evaluation` did not previously stop with `EvaluationResult::RequiresAtLocation`.
-    pub fn resume_with_at_location(&mut self, mut bytes: R) -> Result<EvaluationResult<R>> {
+    pub fn resume_with_at_location(&mut self, bytes: R) -> Result<EvaluationResult<R>> {
+        self.apply_at_location(bytes)?;
+        self.evaluate_internal(None)
+    }
+
+    fn apply_at_location(&mut self, mut bytes: R) -> Result<()> {
         match self.state {
             EvaluationState::Error(err) => return Err(err),
             EvaluationState::Waiting(EvaluationWaiting::AtLocation) => {
                 if !bytes.is_empty() {
+                    if let Some(max_expression_depth) = self.max_expression_depth {
+                        if self.expression_stack.len() >= max_expression_depth {
+                            return Err(Error::ExpressionTooDeep);
+                        }
+                    }
                     let mut pc = bytes.clone();
                     mem::swap(&mut pc, &mut self.pc);
                     mem::swap(&mut bytes, &mut self.bytecode);
@@ -1789,9 +2107,8 @@ impl<R: Reader, S: EvaluationStorage<R>> Evaluation<R, S> {
             _ => panic!(
                 "Called `Evaluation::resume_with_at_location` without a precedeing `EvaluationResult::RequiresAtLocation`"
             ),
-        };
-
-        self.evaluate_internal()
+        }
+        Ok(())
     }
 
     /// Resume the `Evaluation` with the provided `entry_value`.  This will
@@ -1802,6 +2119,11 @@ impl<R: Reader, S: EvaluationStorage<R>> Evaluation<R, S> {
     /// # Panics
     /// Panics if this `Evaluation` did not previously stop with `EvaluationResult::RequiresEntryValue`.
     pub fn resume_with_entry_value(&mut self, entry_value: Value) -> Result<EvaluationResult<R>> {
+        self.apply_entry_value(entry_value)?;
+        self.evaluate_internal(None)
+    }
+
+    fn apply_entry_value(&mut self, entry_value: Value) -> Result<()> {
         match self.state {
             EvaluationState::Error(err) => return Err(err),
             EvaluationState::Waiting(EvaluationWaiting::EntryValue) => {
@@ -1810,9 +2132,8 @@ impl<R: Reader, S: EvaluationStorage<R>> Evaluation<R, S> {
             _ => panic!(
                 "Called `Evaluation::resume_with_entry_value` without a preceding `EvaluationResult::RequiresEntryValue`"
             ),
-        };
-
-        self.evaluate_internal()
+        }
+        Ok(())
     }
 
     /// Resume the `Evaluation` with the provided `parameter_value`.  This will
@@ -1826,6 +2147,11 @@ impl<R: Reader, S: EvaluationStorage<R>> Evaluation<R, S> {
         &mut self,
         parameter_value: u64,
     ) -> Result<EvaluationResult<R>> {
+        self.apply_parameter_ref(parameter_value)?;
+        self.evaluate_internal(None)
+    }
+
+    fn apply_parameter_ref(&mut self, parameter_value: u64) -> Result<()> {
         match self.state {
             EvaluationState::Error(err) => return Err(err),
             EvaluationState::Waiting(EvaluationWaiting::ParameterRef) => {
@@ -1834,9 +2160,8 @@ impl<R: Reader, S: EvaluationStorage<R>> Evaluation<R, S> {
             _ => panic!(
                 "Called `Evaluation::resume_with_parameter_ref` without a preceding `EvaluationResult::RequiresParameterRef`"
             ),
-        };
-
-        self.evaluate_internal()
+        }
+        Ok(())
     }
 
     /// Resume the `Evaluation` with the provided relocated `address`.  This will use the
@@ -1848,6 +2173,11 @@ impl<R: Reader, S: EvaluationStorage<R>> Evaluation<R, S> {
     /// Panics if this `Evaluation` did not previously stop with
     /// `EvaluationResult::RequiresRelocatedAddress`.
     pub fn resume_with_relocated_address(&mut self, address: u64) -> Result<EvaluationResult<R>> {
+        self.apply_relocated_address(address)?;
+        self.evaluate_internal(None)
+    }
+
+    fn apply_relocated_address(&mut self, address: u64) -> Result<()> {
         match self.state {
             EvaluationState::Error(err) => return Err(err),
             EvaluationState::Waiting(EvaluationWaiting::RelocatedAddress) => {
@@ -1856,9 +2186,8 @@ impl<R: Reader, S: EvaluationStorage<R>> Evaluation<R, S> {
             _ => panic!(
                 "Called `Evaluation::resume_with_relocated_address` without a preceding `EvaluationResult::RequiresRelocatedAddress`"
             ),
-        };
-
-        self.evaluate_internal()
+        }
+        Ok(())
     }
 
     /// Resume the `Evaluation` with the provided indexed `address`.  This will use the
@@ -1870,6 +2199,11 @@ impl<R: Reader, S: EvaluationStorage<R>> Evaluation<R, S> {
     /// Panics if this `Evaluation` did not previously stop with
     /// `EvaluationResult::RequiresIndexedAddress`.
     pub fn resume_with_indexed_address(&mut self, address: u64) -> Result<EvaluationResult<R>> {
+        self.apply_indexed_address(address)?;
+        self.evaluate_internal(None)
+    }
+
+    fn apply_indexed_address(&mut self, address: u64) -> Result<()> {
         match self.state {
             EvaluationState::Error(err) => return Err(err),
             EvaluationState::Waiting(EvaluationWaiting::IndexedAddress) => {
@@ -1878,9 +2212,8 @@ impl<R: Reader, S: EvaluationStorage<R>> Evaluation<R, S> {
             _ => panic!(
                 "Called `Evaluation::resume_with_indexed_address` without a preceding `EvaluationResult::RequiresIndexedAddress`"
             ),
-        };
-
-        self.evaluate_internal()
+        }
+        Ok(())
     }
 
     /// Resume the `Evaluation` with the provided `base_type`.  This will use the
@@ -1891,6 +2224,11 @@ impl<R: Reader, S: EvaluationStorage<R>> Evaluation<R, S> {
     /// # Panics
     /// Panics if this `Evaluation` did not previously stop with `EvaluationResult::RequiresBaseType`.
     pub fn resume_with_base_type(&mut self, base_type: ValueType) -> Result<EvaluationResult<R>> {
+        self.apply_base_type(base_type)?;
+        self.evaluate_internal(None)
+    }
+
+    fn apply_base_type(&mut self, base_type: ValueType) -> Result<()> {
         let value = match self.state {
             EvaluationState::Error(err) => return Err(err),
             EvaluationState::Waiting(EvaluationWaiting::TypedLiteral { ref value }) => {
@@ -1908,8 +2246,7 @@ impl<R: Reader, S: EvaluationStorage<R>> Evaluation<R, S> {
                 "Called `Evaluation::resume_with_base_type` without a preceding `EvaluationResult::RequiresBaseType`"
             ),
         };
-        self.push(value)?;
-        self.evaluate_internal()
+        self.push(value)
     }
 
     fn end_of_expression(&mut self) -> bool {
@@ -1925,7 +2262,10 @@ impl<R: Reader, S: EvaluationStorage<R>> Evaluation<R, S> {
         false
     }
 
-    fn evaluate_internal(&mut self) -> Result<EvaluationResult<R>> {
+    fn evaluate_internal(
+        &mut self,
+        mut trace: Option<&mut EvaluationTraceFn<'_, R>>,
+    ) -> Result<EvaluationResult<R>> {
         while !self.end_of_expression() {
             self.iteration += 1;
             if let Some(max_iterations) = self.max_iterations {
@@ -1934,7 +2274,7 @@ impl<R: Reader, S: EvaluationStorage<R>> Evaluation<R, S> {
                 }
             }
 
-            let op_result = self.evaluate_one_operation()?;
+            let op_result = self.evaluate_one_operation(trace.as_deref_mut())?;
             match op_result {
                 OperationEvaluationResult::Piece => {}
                 OperationEvaluationResult::Incomplete => {
@@ -1994,14 +2334,21 @@ impl<R: Reader, S: EvaluationStorage<R>> Evaluation<R, S> {
         // If no pieces have been seen, use the stack top as the
         // result.
         if self.result.is_empty() {
-            let entry = self.pop()?;
+            let (entry, space) = self.pop_entry()?;
             self.value_result = Some(entry);
             let addr = entry.to_u64(self.addr_mask)?;
+            let location = match space {
+                Some(space) => Location::AddressSpace {
+                    address: addr,
+                    space,
+                },
+                None => Location::Address { address: addr },
+            };
             self.result
                 .try_push(Piece {
                     size_in_bits: None,
                     bit_offset: None,
-                    location: Location::Address { address: addr },
+                    location,
                 })
                 .map_err(|_| Error::StackFull)?;
         }
@@ -2815,6 +3162,33 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_op_parse_llvm_user() {
+        // Doesn't matter for this test.
+        let encoding = encoding4();
+
+        check_op_parse(
+            |s| s.D8(constants::DW_OP_LLVM_user.0).uleb(2),
+            &Operation::FormAspaceAddress,
+            encoding,
+        );
+        check_op_parse(
+            |s| s.D8(constants::DW_OP_LLVM_user.0).uleb(9).uleb(3).sleb(-8),
+            &Operation::AspaceRegisterOffset {
+                register: Register(3),
+                offset: -8,
+            },
+            encoding,
+        );
+
+        let buf = [constants::DW_OP_LLVM_user.0, 0x7f];
+        let mut bytes = EndianSlice::new(&buf, LittleEndian);
+        assert_eq!(
+            Operation::parse(&mut bytes, encoding),
+            Err(Error::InvalidExpression(constants::DW_OP_LLVM_user))
+        );
+    }
+
     #[test]
     fn test_op_wasm() {
         // Doesn't matter for this test.
@@ -3510,6 +3884,200 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_eval_trace() {
+        use self::AssemblerEntry::*;
+        use crate::constants::*;
+
+        let program = [
+            Op(DW_OP_lit1),
+            Op(DW_OP_const1u),
+            U8(2),
+            Op(DW_OP_plus),
+            Op(DW_OP_fbreg),
+            Sleb(0),
+            Op(DW_OP_plus),
+        ];
+        let bytes = assemble(&program);
+        let bytes = EndianSlice::new(&bytes, LittleEndian);
+
+        let mut log = Vec::new();
+        let mut trace = |trace: &EvaluationTrace<'_, _>| {
+            log.push((
+                trace.offset,
+                format!("{:?}", trace.operation),
+                trace.stack.to_vec(),
+            ));
+        };
+        let mut eval = Evaluation::new(bytes, encoding4());
+        let mut traced = eval.with_trace(&mut trace);
+        assert_eq!(traced.evaluate(), Ok(EvaluationResult::RequiresFrameBase));
+        // Resuming continues to trace.
+        assert_eq!(
+            traced.resume_with_frame_base(4),
+            Ok(EvaluationResult::Complete)
+        );
+        assert_eq!(eval.value_result(), Some(Value::Generic(7)));
+
+        assert_eq!(
+            log,
+            [
+                (0, "UnsignedConstant { value: 1 }".into(), vec![]),
+                (
+                    1,
+                    "UnsignedConstant { value: 2 }".into(),
+                    vec![Value::Generic(1)]
+                ),
+                (3, "Plus".into(), vec![Value::Generic(1), Value::Generic(2)]),
+                (
+                    4,
+                    "FrameOffset { offset: 0 }".into(),
+                    vec![Value::Generic(3)]
+                ),
+                (6, "Plus".into(), vec![Value::Generic(3), Value::Generic(4)]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_eval_address_space() {
+        // It's nice if an operation and its arguments can fit on a single
+        // line in the test program.
+        use self::AssemblerEntry::*;
+        use crate::constants::*;
+
+        #[rustfmt::skip]
+        let program = [
+            Op(DW_OP_addr), U32(0x1000),
+            Op(DW_OP_lit5),
+            Op(DW_OP_LLVM_user), Uleb(DW_OP_LLVM_form_aspace_address.0.into()),
+            Op(DW_OP_deref),
+            Op(DW_OP_lit6),
+            Op(DW_OP_LLVM_user), Uleb(DW_OP_LLVM_aspace_bregx.0.into()), Uleb(1), Sleb(8),
+            Op(DW_OP_plus_uconst), Uleb(4),
+        ];
+
+        let result = [Piece {
+            size_in_bits: None,
+            bit_offset: None,
+            location: Location::AddressSpace {
+                address: 0x210c,
+                space: 6,
+            },
+        }];
+
+        check_eval_with_args(
+            &program,
+            Ok(&result),
+            encoding4(),
+            None,
+            None,
+            None,
+            |eval, mut result| {
+                while result != EvaluationResult::Complete {
+                    result = match result {
+                        EvaluationResult::RequiresMemory {
+                            address,
+                            size,
+                            space,
+                            base_type,
+                        } => {
+                            assert_eq!(address, 0x1000);
+                            assert_eq!(size, 4);
+                            assert_eq!(space, Some(5));
+                            assert_eq!(base_type, UnitOffset(0));
+                            eval.resume_with_memory(Value::Generic(0x2000))?
+                        }
+                        EvaluationResult::RequiresRegister {
+                            register,
+                            base_type,
+                        } => {
+                            assert_eq!(register, Register(1));
+                            assert_eq!(base_type, UnitOffset(0));
+                            eval.resume_with_register(Value::Generic(0x2100))?
+                        }
+                        EvaluationResult::RequiresRelocatedAddress(address) => {
+                            eval.resume_with_relocated_address(address)?
+                        }
+                        _ => panic!(),
+                    };
+                }
+                Ok(result)
+            },
+        );
+    }
+
+    #[test]
+    fn test_eval_address_space_stack() {
+        // It's nice if an operation and its arguments can fit on a single
+        // line in the test program.
+        use self::AssemblerEntry::*;
+        use crate::constants::*;
+
+        #[rustfmt::skip]
+        let program = [
+            Op(DW_OP_const2u), U16(0x1000),
+            Op(DW_OP_lit5),
+            Op(DW_OP_LLVM_user), Uleb(DW_OP_LLVM_form_aspace_address.0.into()),
+                                            // -- 0x1000@5
+            Op(DW_OP_const2u), U16(0x2000),
+            Op(DW_OP_lit6),
+            Op(DW_OP_LLVM_user), Uleb(DW_OP_LLVM_form_aspace_address.0.into()),
+                                            // -- 0x1000@5 0x2000@6
+            Op(DW_OP_swap),                 // -- 0x2000@6 0x1000@5
+            Op(DW_OP_lit0),                 // -- 0x2000@6 0x1000@5 0
+            Op(DW_OP_rot),                  // -- 0 0x2000@6 0x1000@5
+            Op(DW_OP_deref),                // -- 0 0x2000@6 0x3000
+            Op(DW_OP_drop),                 // -- 0 0x2000@6
+            Op(DW_OP_over),                 // -- 0 0x2000@6 0
+            Op(DW_OP_drop),                 // -- 0 0x2000@6
+            Op(DW_OP_pick), U8(0),          // -- 0 0x2000@6 0x2000@6
+            Op(DW_OP_piece), Uleb(4),       // -- 0 0x2000@6
+            Op(DW_OP_swap),                 // -- 0x2000@6 0
+            Op(DW_OP_piece), Uleb(4),       // -- 0x2000@6
+            Op(DW_OP_piece), Uleb(4),
+        ];
+
+        let piece = |location| Piece {
+            size_in_bits: Some(32),
+            bit_offset: None,
+            location,
+        };
+        let result = [
+            piece(Location::AddressSpace {
+                address: 0x2000,
+                space: 6,
+            }),
+            piece(Location::Address { address: 0 }),
+            piece(Location::AddressSpace {
+                address: 0x2000,
+                space: 6,
+            }),
+        ];
+
+        check_eval_with_args(
+            &program,
+            Ok(&result),
+            encoding4(),
+            None,
+            None,
+            None,
+            |eval, mut result| {
+                while result != EvaluationResult::Complete {
+                    result = match result {
+                        EvaluationResult::RequiresMemory { address, space, .. } => {
+                            assert_eq!(address, 0x1000);
+                            assert_eq!(space, Some(5));
+                            eval.resume_with_memory(Value::Generic(0x3000))?
+                        }
+                        _ => panic!(),
+                    };
+                }
+                Ok(result)
+            },
+        );
+    }
+
     #[test]
     fn test_eval_register() {
         // It's nice if an operation and its arguments can fit on a single
@@ -3815,6 +4383,26 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_eval_max_expression_depth() {
+        // DW_OP_call2 0
+        const CALL: &[u8] = &[0x98, 0x00, 0x00];
+
+        let bytecode = EndianSlice::new(CALL, LittleEndian);
+        let mut eval = Evaluation::new(bytecode, encoding4());
+        eval.set_max_expression_depth(2);
+        let mut result = eval.evaluate().unwrap();
+        for _ in 0..2 {
+            assert!(matches!(result, EvaluationResult::RequiresAtLocation(_)));
+            result = eval.resume_with_at_location(bytecode).unwrap();
+        }
+        assert!(matches!(result, EvaluationResult::RequiresAtLocation(_)));
+        assert_eq!(
+            eval.resume_with_at_location(bytecode),
+            Err(Error::ExpressionTooDeep)
+        );
+    }
+
     #[test]
     fn test_eval_pieces() {
         // It's nice if an operation and its arguments can fit on a single
@@ -1,7 +1,7 @@
 use crate::common::{DebugInfoOffset, SectionId};
 use crate::endianity::Endianity;
 use crate::read::lookup::{DebugLookup, LookupEntryIter, PubStuffEntry, PubStuffParser};
-use crate::read::{EndianSlice, Reader, Result, Section, UnitOffset};
+use crate::read::{EndianSlice, Reader, Result, ResultIterator, Section, UnitOffset};
 
 /// A single parsed pubname.
 #[derive(Debug, Clone)]
@@ -139,3 +139,21 @@ impl<R: Reader> fallible_iterator::FallibleIterator for PubNamesEntryIter<R> {
         self.0.next()
     }
 }
+
+impl<R: Reader> PubNamesEntryIter<R> {
+    /// Return an adapter that implements `Iterator`.
+    ///
+    /// See [`ResultIterator`] for more information.
+    pub fn iterator(self) -> ResultIterator<Self> {
+        ResultIterator::new(self)
+    }
+}
+
+impl<R: Reader> Iterator for ResultIterator<PubNamesEntryIter<R>> {
+    type Item = Result<PubNamesEntry<R>>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_with(PubNamesEntryIter::next)
+    }
+}
@@ -0,0 +1,129 @@
+use alloc::vec::Vec;
+use core::cmp::Reverse;
+
+use crate::read::Range;
+
+/// A value that covers an address range, and which can be stored in a `RangeIndex`.
+pub(crate) trait RangeIndexEntry {
+    /// Return the address range covered by this value.
+    fn range(&self) -> Range;
+}
+
+/// A list of address ranges that supports finding the ranges containing an address.
+///
+/// The ranges may overlap.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct RangeIndex<T> {
+    /// The entries, sorted by the start of their range, and then by decreasing
+    /// end, so that a range comes after any ranges that contain it.
+    entries: Vec<T>,
+    /// The maximum end address of each entry and all entries before it.
+    max_ends: Vec<u64>,
+}
+
+impl<T> Default for RangeIndex<T> {
+    fn default() -> Self {
+        RangeIndex {
+            entries: Vec::new(),
+            max_ends: Vec::new(),
+        }
+    }
+}
+
+impl<T: RangeIndexEntry> RangeIndex<T> {
+    /// Create an index for the given entries.
+    pub(crate) fn new(mut entries: Vec<T>) -> Self {
+        // Ignore empty ranges, which are often caused by linkers discarding code.
+        entries.retain(|entry| {
+            let range = entry.range();
+            range.begin < range.end
+        });
+        entries.sort_by_key(|entry| {
+            let range = entry.range();
+            (range.begin, Reverse(range.end))
+        });
+        let mut max_end = 0;
+        let max_ends = entries
+            .iter()
+            .map(|entry| {
+                max_end = max_end.max(entry.range().end);
+                max_end
+            })
+            .collect();
+        RangeIndex { entries, max_ends }
+    }
+
+    /// Return the entries, sorted by the start of their range, and then by
+    /// decreasing end.
+    pub(crate) fn entries(&self) -> &[T] {
+        &self.entries
+    }
+
+    /// Return the entry containing the given address.
+    ///
+    /// If multiple entries contain the address, then the entry whose range
+    /// starts closest to the address is returned. Of the entries that start
+    /// at the same address, the entry with the smallest range is returned.
+    pub(crate) fn find(&self, address: u64) -> Option<&T> {
+        self.find_all(address).next()
+    }
+
+    /// Return all of the entries containing the given address.
+    ///
+    /// The entries are returned in decreasing order of their start address,
+    /// and then in increasing order of their end address.
+    pub(crate) fn find_all(&self, address: u64) -> impl Iterator<Item = &T> {
+        let index = self
+            .entries
+            .partition_point(|entry| entry.range().begin <= address);
+        self.entries[..index]
+            .iter()
+            .zip(&self.max_ends[..index])
+            .rev()
+            .take_while(move |(_, max_end)| **max_end > address)
+            .map(|(entry, _)| entry)
+            .filter(move |entry| address < entry.range().end)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    impl RangeIndexEntry for (Range, u32) {
+        fn range(&self) -> Range {
+            self.0
+        }
+    }
+
+    #[test]
+    fn test_range_index() {
+        let range = |begin, end| Range { begin, end };
+        let index = RangeIndex::new(vec![
+            (range(0x30, 0x40), 3),
+            (range(0x10, 0x50), 1),
+            (range(0x20, 0x20), 2),
+            (range(0x20, 0x28), 4),
+            (range(0x30, 0x38), 5),
+        ]);
+        let values = |address| {
+            index
+                .find_all(address)
+                .map(|entry| entry.1)
+                .collect::<Vec<_>>()
+        };
+
+        // The empty range is ignored.
+        assert_eq!(index.entries().len(), 4);
+        assert!(values(0x0f).is_empty());
+        assert_eq!(values(0x10), [1]);
+        assert_eq!(values(0x20), [4, 1]);
+        assert_eq!(values(0x2c), [1]);
+        assert_eq!(values(0x30), [5, 3, 1]);
+        assert_eq!(values(0x38), [3, 1]);
+        assert_eq!(values(0x4f), [1]);
+        assert!(values(0x50).is_empty());
+        assert_eq!(index.find(0x24).map(|entry| entry.1), Some(4));
+        assert_eq!(index.find(0x50), None);
+    }
+}
@@ -418,7 +418,7 @@ pub trait Reader: Debug + Clone {
 
     /// Read an unsigned LEB128 encoded u32.
     fn read_uleb128_u32(&mut self) -> Result<u32> {
-        leb128::read::unsigned(self)?
+        self.read_uleb128()?
             .try_into()
             .map_err(|_| Error::BadUnsignedLeb128)
     }
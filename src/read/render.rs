@@ -0,0 +1,346 @@
+use alloc::string::{String, ToString};
+use core::fmt::{self, Write};
+
+use crate::common::{DebugInfoOffset, Encoding};
+use crate::constants;
+use crate::read::{
+    AttributeValue, DieReference, DumpValue, Expression, Operation, Reader, ReaderOffset, Result,
+    UnitOffset, UnitRef,
+};
+
+/// A helper for displaying a DWARF expression.
+///
+/// Each operation is displayed using its name followed by its operands, in the
+/// style of `llvm-dwarfdump`. Operations are separated by a space.
+///
+/// Returned by [`Expression::display`].
+#[derive(Debug, Clone)]
+pub struct ExpressionDisplay<R: Reader> {
+    expression: Expression<R>,
+    encoding: Encoding,
+}
+
+impl<R: Reader> Expression<R> {
+    /// Return a value that displays the operations of this expression.
+    ///
+    /// If the expression cannot be parsed, then the operations before the
+    /// error are displayed, followed by a description of the error.
+    pub fn display(&self, encoding: Encoding) -> ExpressionDisplay<R> {
+        ExpressionDisplay {
+            expression: self.clone(),
+            encoding,
+        }
+    }
+}
+
+impl<R: Reader> fmt::Display for ExpressionDisplay<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut bytes = self.expression.0.clone();
+        let mut space = false;
+        while !bytes.is_empty() {
+            if space {
+                f.write_char(' ')?;
+            }
+            space = true;
+            let op_bytes = bytes.clone();
+            let result = Operation::parse(&mut bytes, self.encoding)
+                .and_then(|op| write_op(f, self.encoding, op_bytes, op));
+            match result {
+                Ok(Ok(())) => {}
+                Ok(Err(e)) => return Err(e),
+                Err(e) => return write!(f, "<{}>", e),
+            }
+        }
+        Ok(())
+    }
+}
+
+fn write_op<R: Reader>(
+    f: &mut fmt::Formatter<'_>,
+    encoding: Encoding,
+    mut bytes: R,
+    op: Operation<R>,
+) -> Result<fmt::Result> {
+    let dwop = constants::DwOp(bytes.read_u8()?);
+    let mut operands = String::new();
+    let w = &mut operands;
+    // Writing to a `String` can't fail, so ignore the results.
+    match op {
+        Operation::Deref {
+            base_type, size, ..
+        } => {
+            if dwop == constants::DW_OP_deref_size || dwop == constants::DW_OP_xderef_size {
+                let _ = write!(w, " {}", size);
+            }
+            if base_type != UnitOffset(R::Offset::from_u8(0)) {
+                let _ = write!(w, " type 0x{:08x}", base_type.0.into_u64());
+            }
+        }
+        Operation::Pick { index } if dwop == constants::DW_OP_pick => {
+            let _ = write!(w, " {}", index);
+        }
+        Operation::PlusConstant { value } => {
+            let _ = write!(w, " {}", value);
+        }
+        Operation::Bra { target } | Operation::Skip { target } => {
+            let _ = write!(w, " {}", target);
+        }
+        Operation::SignedConstant { value } => match dwop {
+            constants::DW_OP_const1s
+            | constants::DW_OP_const2s
+            | constants::DW_OP_const4s
+            | constants::DW_OP_const8s
+            | constants::DW_OP_consts => {
+                let _ = write!(w, " {}", value);
+            }
+            _ => {}
+        },
+        Operation::UnsignedConstant { value } => match dwop {
+            constants::DW_OP_const1u
+            | constants::DW_OP_const2u
+            | constants::DW_OP_const4u
+            | constants::DW_OP_const8u
+            | constants::DW_OP_constu => {
+                let _ = write!(w, " {}", value);
+            }
+            // Other operations encode the value in the opcode, such as `DW_OP_lit0`.
+            _ => {}
+        },
+        Operation::Register { register } if dwop == constants::DW_OP_regx => {
+            let _ = write!(w, " {}", register.0);
+        }
+        Operation::RegisterOffset {
+            register,
+            offset,
+            base_type,
+        } => {
+            if dwop >= constants::DW_OP_breg0 && dwop <= constants::DW_OP_breg31 {
+                let _ = write!(w, " {:+}", offset);
+            } else {
+                let _ = write!(w, " {}", register.0);
+                if offset != 0 {
+                    let _ = write!(w, " {:+}", offset);
+                }
+                if base_type != UnitOffset(R::Offset::from_u8(0)) {
+                    let _ = write!(w, " type 0x{:08x}", base_type.0.into_u64());
+                }
+            }
+        }
+        Operation::FrameOffset { offset } => {
+            let _ = write!(w, " {}", offset);
+        }
+        Operation::Call { offset } => {
+            let offset = match offset {
+                DieReference::UnitRef(UnitOffset(offset)) => offset,
+                DieReference::DebugInfoRef(DebugInfoOffset(offset)) => offset,
+            };
+            let _ = write!(w, " 0x{:08x}", offset.into_u64());
+        }
+        Operation::Piece {
+            size_in_bits,
+            bit_offset: None,
+        } => {
+            let _ = write!(w, " {}", size_in_bits / 8);
+        }
+        Operation::Piece {
+            size_in_bits,
+            bit_offset: Some(bit_offset),
+        } => {
+            let _ = write!(w, " {} {}", size_in_bits, bit_offset);
+        }
+        Operation::ImplicitValue { data } => {
+            let data = data.to_slice()?;
+            let _ = write!(w, " {} 0x", data.len());
+            for byte in data.iter() {
+                let _ = write!(w, "{:02x}", byte);
+            }
+        }
+        Operation::ImplicitPointer { value, byte_offset } => {
+            let _ = write!(w, " 0x{:08x} {}", value.0.into_u64(), byte_offset);
+        }
+        Operation::EntryValue { expression } => {
+            let _ = write!(w, "({})", Expression(expression).display(encoding));
+        }
+        Operation::ParameterRef { offset } => {
+            let _ = write!(w, " 0x{:08x}", offset.0.into_u64());
+        }
+        Operation::Address { address } => {
+            let _ = write!(w, " 0x{:x}", address);
+        }
+        Operation::AddressIndex { index } => {
+            let _ = write!(w, " {}", index.0.into_u64());
+        }
+        Operation::ConstantIndex { index } => {
+            let _ = write!(w, " {}", index.0.into_u64());
+        }
+        Operation::TypedLiteral { base_type, value } => {
+            let _ = write!(w, " type 0x{:08x} 0x", base_type.0.into_u64());
+            for byte in value.to_slice()?.iter() {
+                let _ = write!(w, "{:02x}", byte);
+            }
+        }
+        Operation::Convert { base_type } | Operation::Reinterpret { base_type } => {
+            let _ = write!(w, " type 0x{:08x}", base_type.0.into_u64());
+        }
+        Operation::WasmLocal { index }
+        | Operation::WasmGlobal { index }
+        | Operation::WasmStack { index } => {
+            let kind = bytes.read_u8()?;
+            let _ = write!(w, " 0x{:x} {}", kind, index);
+        }
+        Operation::FormAspaceAddress => {
+            let _ = write!(w, " {}", constants::DW_OP_LLVM_form_aspace_address);
+        }
+        Operation::AspaceRegisterOffset { register, offset } => {
+            let _ = write!(
+                w,
+                " {} {} {:+}",
+                constants::DW_OP_LLVM_aspace_bregx,
+                register.0,
+                offset
+            );
+        }
+        _ => {}
+    }
+    Ok(write!(f, "{}{}", dwop, operands))
+}
+
+impl<'a, R: Reader> UnitRef<'a, R> {
+    /// Return a human-readable representation of an attribute value.
+    ///
+    /// This resolves the value using the unit:
+    ///
+    /// * strings are read from the string sections,
+    /// * constants such as `DW_AT_language` are displayed using their names,
+    /// * expressions are displayed using [`Expression::display`],
+    /// * location and range lists are read and each entry is displayed,
+    /// * file indices are displayed as the path of the file,
+    /// * references are displayed as section offsets.
+    pub fn attr_value_string(&self, value: AttributeValue<R>) -> Result<String> {
+        let encoding = self.encoding();
+        Ok(match value {
+            AttributeValue::Exprloc(expression) => expression.display(encoding).to_string(),
+            AttributeValue::LocationListsRef(_) | AttributeValue::DebugLocListsIndex(_) => {
+                let mut s = String::new();
+                if let Some(mut locations) = self.attr_locations(value)? {
+                    while let Some(location) = locations.next()? {
+                        if !s.is_empty() {
+                            s.push_str("; ");
+                        }
+                        let _ = write!(
+                            s,
+                            "[0x{:x}, 0x{:x}): {}",
+                            location.range.begin,
+                            location.range.end,
+                            location.data.display(encoding)
+                        );
+                    }
+                }
+                s
+            }
+            _ => DumpValue::new(self.dwarf, self.unit, value)?.to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::Format;
+    use crate::constants::*;
+    use crate::endianity::LittleEndian;
+    use crate::read::abbrev::tests::AbbrevSectionMethods;
+    use crate::read::{DebugStr, Dwarf, EndianSlice, Unit};
+    use crate::test_util::{compile_unit, test_dwarf, GimliSectionMethods};
+    use test_assembler::{Endian, Section};
+
+    #[test]
+    fn test_expression_display() {
+        let encoding = Encoding {
+            format: Format::Dwarf32,
+            version: 4,
+            address_size: 8,
+        };
+        #[rustfmt::skip]
+        let bytes = [
+            DW_OP_breg7.0, 0x78,
+            DW_OP_deref.0,
+            DW_OP_lit3.0,
+            DW_OP_constu.0, 0x80, 0x01,
+            DW_OP_plus_uconst.0, 0x10,
+            DW_OP_fbreg.0, 0x70,
+            DW_OP_piece.0, 0x04,
+            DW_OP_addr.0, 0x00, 0x10, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            DW_OP_entry_value.0, 0x01, DW_OP_reg5.0,
+            DW_OP_stack_value.0,
+        ];
+        let expression = Expression(EndianSlice::new(&bytes, LittleEndian));
+        assert_eq!(
+            expression.display(encoding).to_string(),
+            "DW_OP_breg7 -8 DW_OP_deref DW_OP_lit3 DW_OP_constu 128 DW_OP_plus_uconst 16 \
+             DW_OP_fbreg -16 DW_OP_piece 4 DW_OP_addr 0x1000 DW_OP_entry_value(DW_OP_reg5) \
+             DW_OP_stack_value"
+        );
+
+        let bytes = [DW_OP_lit1.0, DW_OP_const2u.0, 0x01];
+        let expression = Expression(EndianSlice::new(&bytes, LittleEndian));
+        assert_eq!(
+            expression.display(encoding).to_string(),
+            "DW_OP_lit1 <Hit the end of input before it was expected>"
+        );
+    }
+
+    #[test]
+    fn test_attr_value_string() {
+        #[rustfmt::skip]
+        let abbrev = Section::with_endian(Endian::Little)
+            .abbrev(1, DW_TAG_compile_unit, DW_CHILDREN_yes)
+                .abbrev_attr(DW_AT_name, DW_FORM_strp)
+                .abbrev_attr(DW_AT_language, DW_FORM_data2)
+                .abbrev_attr(DW_AT_low_pc, DW_FORM_addr)
+                .abbrev_attr_null()
+            .abbrev(2, DW_TAG_variable, DW_CHILDREN_no)
+                .abbrev_attr(DW_AT_type, DW_FORM_ref4)
+                .abbrev_attr(DW_AT_location, DW_FORM_exprloc)
+                .abbrev_attr(DW_AT_external, DW_FORM_flag_present)
+                .abbrev_attr_null()
+            .abbrev_null();
+        let abbrev = abbrev.get_contents().unwrap();
+        let debug_str = b"\0main.c\0";
+
+        #[rustfmt::skip]
+        let entries = Section::with_endian(Endian::Little)
+            .uleb(1).L32(1).L16(DW_LANG_C99.0).L64(0x1000)
+                .uleb(2).L32(0x10).uleb(1).D8(DW_OP_reg5.0)
+                .D8(0);
+        let info = compile_unit(entries);
+
+        let dwarf = Dwarf {
+            debug_str: DebugStr::new(debug_str, LittleEndian),
+            ..test_dwarf(&abbrev, &info)
+        };
+        let header = dwarf.units().next().unwrap().unwrap();
+        let unit = Unit::new(&dwarf, header).unwrap();
+        let unit = unit.unit_ref(&dwarf);
+
+        let mut values = alloc::vec::Vec::new();
+        let mut entries = unit.entries();
+        while let Some((_, entry)) = entries.next_dfs().unwrap() {
+            let mut attrs = entry.attrs();
+            while let Some(attr) = attrs.next().unwrap() {
+                values.push(unit.attr_value_string(attr.value()).unwrap());
+            }
+        }
+        assert_eq!(
+            values,
+            [
+                "main.c",
+                "DW_LANG_C99",
+                "0x1000",
+                "<.debug_info+0x10>",
+                "DW_OP_reg5",
+                "true",
+            ]
+        );
+    }
+}
@@ -6,7 +6,7 @@ use crate::constants;
 use crate::endianity::Endianity;
 use crate::read::{
     lists::ListsHeader, DebugAddr, EndianSlice, Error, Reader, ReaderOffset, ReaderOffsetId,
-    Result, Section,
+    Result, ResultIterator, Section,
 };
 
 /// The raw contents of the `.debug_ranges` section.
@@ -311,6 +311,7 @@ pub struct RawRngListIter<R: Reader> {
 
 /// A raw entry in .debug_rnglists
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum RawRngListEntry<T> {
     /// A range from DWARF version <= 4.
     AddressOrOffsetPair {
@@ -464,6 +465,24 @@ impl<R: Reader> fallible_iterator::FallibleIterator for RawRngListIter<R> {
     }
 }
 
+impl<R: Reader> RawRngListIter<R> {
+    /// Return an adapter that implements `Iterator`.
+    ///
+    /// See [`ResultIterator`] for more information.
+    pub fn iterator(self) -> ResultIterator<Self> {
+        ResultIterator::new(self)
+    }
+}
+
+impl<R: Reader> Iterator for ResultIterator<RawRngListIter<R>> {
+    type Item = Result<RawRngListEntry<R::Offset>>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_with(RawRngListIter::next)
+    }
+}
+
 /// An iterator over an address range list.
 ///
 /// This iterator internally handles processing of base addresses and different
@@ -475,6 +494,11 @@ pub struct RngListIter<R: Reader> {
     base_address: u64,
     debug_addr: DebugAddr<R>,
     debug_addr_base: DebugAddrBase<R::Offset>,
+    tombstone_filter: TombstoneFilter,
+    base_address_tombstone: bool,
+    segment_selector_size: u8,
+    base_segment: u64,
+    segment: u64,
 }
 
 impl<R: Reader> RngListIter<R> {
@@ -490,13 +514,60 @@ impl<R: Reader> RngListIter<R> {
             base_address,
             debug_addr,
             debug_addr_base,
+            tombstone_filter: TombstoneFilter::none(),
+            base_address_tombstone: false,
+            segment_selector_size: 0,
+            base_segment: 0,
+            segment: 0,
         }
     }
 
+    /// Set the filter for additional tombstone addresses to ignore.
+    ///
+    /// Ranges that begin at a matching address are skipped, as are offset
+    /// pairs that follow a base address entry with a matching address.
+    /// The filter is not applied to the base address of the unit, since a
+    /// `DW_AT_low_pc` of 0 is commonly used with offset pairs.
+    pub fn set_tombstone_filter(&mut self, filter: TombstoneFilter) {
+        self.tombstone_filter = filter;
+    }
+
+    /// Set the size of the segment selectors in the `.debug_addr` entries
+    /// that are used by this list.
+    ///
+    /// This must be the `segment_selector_size` from the header of the set of
+    /// addresses in `.debug_addr`. The default is 0.
+    pub fn set_segment_selector_size(&mut self, segment_selector_size: u8) {
+        self.segment_selector_size = segment_selector_size;
+    }
+
+    /// The segment selector for the range most recently returned by `next`.
+    ///
+    /// This is only set for entries that use addresses from `.debug_addr` with
+    /// a nonzero segment selector size, and is 0 otherwise. For other entries,
+    /// the segment is given by the `DW_AT_segment` attribute of the entry that
+    /// refers to the list.
     #[inline]
-    fn get_address(&self, index: DebugAddrIndex<R::Offset>) -> Result<u64> {
-        self.debug_addr
-            .get_address(self.raw.encoding.address_size, self.debug_addr_base, index)
+    pub fn segment(&self) -> u64 {
+        self.segment
+    }
+
+    fn set_base_address(&mut self, segment: u64, addr: u64) {
+        self.base_segment = segment;
+        self.base_address = addr;
+        self.base_address_tombstone = self
+            .tombstone_filter
+            .is_tombstone(addr, self.raw.encoding.address_size);
+    }
+
+    #[inline]
+    fn get_address(&self, index: DebugAddrIndex<R::Offset>) -> Result<(u64, u64)> {
+        self.debug_addr.get_segment_address(
+            self.raw.encoding.address_size,
+            self.segment_selector_size,
+            self.debug_addr_base,
+            index,
+        )
     }
 
     /// Advance the iterator to the next range.
@@ -534,42 +605,47 @@ impl<R: Reader> RngListIter<R> {
             mask
         };
 
-        let range = match raw_range {
+        let (segment, range) = match raw_range {
             RawRngListEntry::BaseAddress { addr } => {
-                self.base_address = addr;
+                self.set_base_address(0, addr);
                 return Ok(None);
             }
             RawRngListEntry::BaseAddressx { addr } => {
-                self.base_address = self.get_address(addr)?;
+                let (segment, addr) = self.get_address(addr)?;
+                self.set_base_address(segment, addr);
                 return Ok(None);
             }
             RawRngListEntry::StartxEndx { begin, end } => {
-                let begin = self.get_address(begin)?;
-                let end = self.get_address(end)?;
-                Range { begin, end }
+                let (segment, begin) = self.get_address(begin)?;
+                let (_, end) = self.get_address(end)?;
+                (segment, Range { begin, end })
             }
             RawRngListEntry::StartxLength { begin, length } => {
-                let begin = self.get_address(begin)?;
+                let (segment, begin) = self.get_address(begin)?;
                 let end = begin.wrapping_add(length) & mask;
-                Range { begin, end }
+                (segment, Range { begin, end })
             }
             RawRngListEntry::AddressOrOffsetPair { begin, end }
             | RawRngListEntry::OffsetPair { begin, end } => {
-                if self.base_address == tombstone {
+                if self.base_address == tombstone || self.base_address_tombstone {
                     return Ok(None);
                 }
                 let mut range = Range { begin, end };
                 range.add_base_address(self.base_address, self.raw.encoding.address_size);
-                range
+                (self.base_segment, range)
             }
-            RawRngListEntry::StartEnd { begin, end } => Range { begin, end },
+            RawRngListEntry::StartEnd { begin, end } => (0, Range { begin, end }),
             RawRngListEntry::StartLength { begin, length } => {
                 let end = begin.wrapping_add(length) & mask;
-                Range { begin, end }
+                (0, Range { begin, end })
             }
         };
 
-        if range.begin == tombstone {
+        if range.begin == tombstone
+            || self
+                .tombstone_filter
+                .is_tombstone(range.begin, self.raw.encoding.address_size)
+        {
             return Ok(None);
         }
 
@@ -578,6 +654,7 @@ impl<R: Reader> RngListIter<R> {
             return Err(Error::InvalidAddressRange);
         }
 
+        self.segment = segment;
         Ok(Some(range))
     }
 }
@@ -592,6 +669,24 @@ impl<R: Reader> fallible_iterator::FallibleIterator for RngListIter<R> {
     }
 }
 
+impl<R: Reader> RngListIter<R> {
+    /// Return an adapter that implements `Iterator`.
+    ///
+    /// See [`ResultIterator`] for more information.
+    pub fn iterator(self) -> ResultIterator<Self> {
+        ResultIterator::new(self)
+    }
+}
+
+impl<R: Reader> Iterator for ResultIterator<RngListIter<R>> {
+    type Item = Result<Range>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_with(RngListIter::next)
+    }
+}
+
 /// A raw address range from the `.debug_ranges` section.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub(crate) struct RawRange {
@@ -630,6 +725,7 @@ impl RawRange {
 
 /// An address range from the `.debug_ranges`, `.debug_rnglists`, or `.debug_aranges` sections.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Range {
     /// The beginning address of the range.
     pub begin: u64,
@@ -648,6 +744,71 @@ impl Range {
     }
 }
 
+/// A set of addresses that linkers use to mark discarded code.
+///
+/// When a linker discards the code that a range or line sequence refers to,
+/// it may resolve the relocation to a tombstone value instead of a real address.
+/// The iterators for range lists, location lists and line programs always
+/// ignore the tombstones defined for the DWARF version, which are -1 for DWARF 5
+/// and line programs, and -2 for DWARF 4 range lists and location lists.
+/// This filter can be used to ignore additional tombstones, such as the value 0
+/// used by some older linkers.
+///
+/// The values -1 and -2 are relative to the address size, so for 4 byte
+/// addresses they are `0xffff_ffff` and `0xffff_fffe`.
+///
+/// The default filter does not match any addresses.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct TombstoneFilter {
+    zero: bool,
+    minus_one: bool,
+    minus_two: bool,
+}
+
+impl TombstoneFilter {
+    /// Return a filter that does not match any addresses.
+    pub fn none() -> Self {
+        TombstoneFilter::default()
+    }
+
+    /// Return a filter that matches all of the tombstones commonly used by linkers.
+    ///
+    /// These are 0, -1 and -2.
+    pub fn all() -> Self {
+        TombstoneFilter {
+            zero: true,
+            minus_one: true,
+            minus_two: true,
+        }
+    }
+
+    /// Set whether the filter matches the address 0.
+    pub fn zero(mut self, enabled: bool) -> Self {
+        self.zero = enabled;
+        self
+    }
+
+    /// Set whether the filter matches the address -1.
+    pub fn minus_one(mut self, enabled: bool) -> Self {
+        self.minus_one = enabled;
+        self
+    }
+
+    /// Set whether the filter matches the address -2.
+    pub fn minus_two(mut self, enabled: bool) -> Self {
+        self.minus_two = enabled;
+        self
+    }
+
+    /// Return true if the address is a tombstone for the given address size.
+    pub fn is_tombstone(&self, address: u64, address_size: u8) -> bool {
+        let mask = !0 >> (64 - 8 * u32::from(address_size.clamp(1, 8)));
+        (self.zero && address == 0)
+            || (self.minus_one && address == mask)
+            || (self.minus_two && address == mask - 1)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1371,6 +1532,118 @@ mod tests {
         assert_eq!(ranges.next(), Ok(None));
     }
 
+    #[test]
+    fn test_rnglists_segment() {
+        // A `.debug_addr` set with 2 byte segment selectors and 4 byte addresses.
+        #[rustfmt::skip]
+        let addr = Section::with_endian(Endian::Little)
+            .L16(1).L32(0x1000)
+            .L16(2).L32(0x2000);
+        let addr = addr.get_contents().unwrap();
+
+        #[rustfmt::skip]
+        let section = Section::with_endian(Endian::Little)
+            // A StartxLength using the second address.
+            .L8(3).uleb(1).uleb(0x10)
+            // A BaseAddressx using the first address followed by an OffsetPair.
+            .L8(1).uleb(0)
+            .L8(4).uleb(0x10).uleb(0x20)
+            // A StartLength, which has no segment.
+            .L8(7).L32(0x3000).uleb(0x10)
+            // A RangeList end.
+            .L8(0);
+        let buf = section.get_contents().unwrap();
+        let debug_ranges = DebugRanges::new(&[], LittleEndian);
+        let debug_rnglists = DebugRngLists::new(&buf, LittleEndian);
+        let rnglists = RangeLists::new(debug_ranges, debug_rnglists);
+        let debug_addr = &DebugAddr::from(EndianSlice::new(&addr, LittleEndian));
+        let encoding = Encoding {
+            format: Format::Dwarf32,
+            version: 5,
+            address_size: 4,
+        };
+        let mut ranges = rnglists
+            .ranges(
+                RangeListsOffset(0),
+                encoding,
+                0,
+                debug_addr,
+                DebugAddrBase(0),
+            )
+            .unwrap();
+        ranges.set_segment_selector_size(2);
+        let mut result = alloc::vec::Vec::new();
+        while let Some(range) = ranges.next().unwrap() {
+            result.push((ranges.segment(), range.begin, range.end));
+        }
+        assert_eq!(
+            result,
+            [
+                (2, 0x2000, 0x2010),
+                (1, 0x1010, 0x1020),
+                (0, 0x3000, 0x3010)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tombstone_filter() {
+        let filter = TombstoneFilter::all();
+        assert!(filter.is_tombstone(0, 4));
+        assert!(filter.is_tombstone(0xffff_ffff, 4));
+        assert!(filter.is_tombstone(0xffff_fffe, 4));
+        assert!(!filter.is_tombstone(0xffff_fffe, 8));
+        assert!(filter.is_tombstone(!0 - 1, 8));
+        assert!(!filter.is_tombstone(1, 4));
+        let filter = filter.minus_one(false);
+        assert!(!filter.is_tombstone(0xffff_ffff, 4));
+        assert!(!TombstoneFilter::none().is_tombstone(0, 4));
+
+        #[rustfmt::skip]
+        let section = Section::with_endian(Endian::Little)
+            // A normal range.
+            .L32(0x10).L32(0x20)
+            // A base address selection of 0 followed by a normal range.
+            .L32(0xffff_ffff).L32(0)
+            .L32(0x10).L32(0x20)
+            // A base address selection followed by a normal range.
+            .L32(0xffff_ffff).L32(0x1000)
+            .L32(0x10).L32(0x20)
+            // A range end.
+            .L32(0).L32(0);
+
+        let buf = section.get_contents().unwrap();
+        let debug_ranges = DebugRanges::new(&buf, LittleEndian);
+        let debug_rnglists = DebugRngLists::new(&[], LittleEndian);
+        let rnglists = RangeLists::new(debug_ranges, debug_rnglists);
+        let debug_addr = &DebugAddr::from(EndianSlice::new(&[], LittleEndian));
+        let encoding = Encoding {
+            format: Format::Dwarf32,
+            version: 4,
+            address_size: 4,
+        };
+        let ranges = |filter| {
+            let mut result = alloc::vec::Vec::new();
+            let mut ranges = rnglists
+                .ranges(
+                    RangeListsOffset(0),
+                    encoding,
+                    0,
+                    debug_addr,
+                    DebugAddrBase(0),
+                )
+                .unwrap();
+            ranges.set_tombstone_filter(filter);
+            while let Some(range) = ranges.next().unwrap() {
+                result.push(range.begin);
+            }
+            result
+        };
+        assert_eq!(ranges(TombstoneFilter::none()), [0x10, 0x10, 0x1010]);
+        // Offset pairs using the base address of 0 from the unit are kept.
+        assert_eq!(ranges(TombstoneFilter::none().zero(true)), [0x10, 0x1010]);
+    }
+
     #[test]
     fn test_ranges_invalid() {
         #[rustfmt::skip]
@@ -1473,4 +1746,29 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_serde() {
+        let range = Range {
+            begin: 0x1000,
+            end: 0x1010,
+        };
+        let json = serde_json::to_string(&range).unwrap();
+        assert_eq!(json, r#"{"begin":4096,"end":4112}"#);
+        assert_eq!(serde_json::from_str::<Range>(&json).unwrap(), range);
+
+        let entry = RawRngListEntry::StartxLength {
+            begin: DebugAddrIndex(1),
+            length: 0x10,
+        };
+        let json = serde_json::to_string(&entry).unwrap();
+        assert!(matches!(
+            serde_json::from_str::<RawRngListEntry<usize>>(&json).unwrap(),
+            RawRngListEntry::StartxLength {
+                begin: DebugAddrIndex(1),
+                length: 0x10,
+            }
+        ));
+    }
 }
@@ -0,0 +1,396 @@
+use alloc::vec::Vec;
+
+use crate::constants;
+use crate::read::symbolize::for_each_origin;
+use crate::read::{
+    AttributeValue, DebuggingInformationEntry, Error, Expression, Reader, Result, Unit, UnitOffset,
+    UnitRef,
+};
+
+/// A variable or parameter that is in scope at an address.
+///
+/// See [`UnitRef::variables_at`].
+#[derive(Debug, Clone)]
+pub struct ScopeVariable<R: Reader> {
+    /// The offset of the `DW_TAG_variable` or `DW_TAG_formal_parameter` entry.
+    pub offset: UnitOffset<R::Offset>,
+    /// The offset of the subprogram, lexical block, or inlined subroutine
+    /// entry that contains the variable.
+    pub scope: UnitOffset<R::Offset>,
+    /// Whether the entry is a `DW_TAG_formal_parameter`.
+    pub is_parameter: bool,
+    /// The name of the variable.
+    pub name: Option<R>,
+    /// The offset of the type entry for the variable.
+    pub type_offset: Option<UnitOffset<R::Offset>>,
+    /// The location of the variable that is valid at the address.
+    ///
+    /// This is `None` if the variable has no location, or if its location
+    /// list has no entry for the address.
+    pub location: Option<Expression<R>>,
+}
+
+impl<'a, R: Reader> UnitRef<'a, R> {
+    /// Return the variables and parameters that are in scope at the given address.
+    ///
+    /// This finds the subprogram containing the address, and the lexical blocks and
+    /// inlined subroutines within it that contain the address. The variables are
+    /// returned in order from the outermost scope to the innermost scope.
+    ///
+    /// Names and types are taken from `DW_AT_abstract_origin` or `DW_AT_specification`
+    /// if they are not present on the variable entry itself. Types are only taken
+    /// from referenced entries in the same unit.
+    ///
+    /// Variables at the compilation unit or namespace level are not included.
+    pub fn variables_at(&self, address: u64) -> Result<Vec<ScopeVariable<R>>> {
+        let mut entries = self.entries();
+        let root = match entries.next_dfs()? {
+            Some((_, root)) => root.offset(),
+            None => return Err(Error::MissingUnitDie),
+        };
+        let mut root = Scope::new(root, 0, false);
+        // The scopes within the root that contain the current entry.
+        let mut scopes: Vec<Scope<R>> = Vec::new();
+        let mut depth = 0;
+        while let Some((delta_depth, entry)) = entries.next_dfs()? {
+            depth += delta_depth;
+            while scopes.last().map_or(false, |scope| scope.depth >= depth) {
+                let scope = scopes.pop().unwrap();
+                scope.finish(&mut scopes.last_mut().unwrap_or(&mut root).inner);
+            }
+            let parent = scopes.last_mut().unwrap_or(&mut root);
+            if parent.depth + 1 != depth {
+                // The entry is in a subtree that doesn't contain the address.
+                continue;
+            }
+            let in_subprogram = parent.in_subprogram;
+            match entry.tag() {
+                constants::DW_TAG_variable | constants::DW_TAG_formal_parameter
+                    if in_subprogram =>
+                {
+                    let variable = self.scope_variable(entry, parent.offset, address)?;
+                    parent.variables.push(variable);
+                }
+                constants::DW_TAG_subprogram
+                | constants::DW_TAG_lexical_block
+                | constants::DW_TAG_inlined_subroutine => {
+                    // Lexical blocks without any ranges have the same scope as their parent.
+                    let default = in_subprogram && entry.tag() == constants::DW_TAG_lexical_block;
+                    if self.scope_contains(entry, address, default)? {
+                        scopes.push(Scope::new(entry.offset(), depth, true));
+                    }
+                }
+                constants::DW_TAG_namespace
+                | constants::DW_TAG_module
+                | constants::DW_TAG_class_type
+                | constants::DW_TAG_structure_type
+                | constants::DW_TAG_union_type
+                    if !in_subprogram =>
+                {
+                    // Subprograms may be nested within these.
+                    scopes.push(Scope::new(entry.offset(), depth, false));
+                }
+                _ => {}
+            }
+        }
+        while let Some(scope) = scopes.pop() {
+            scope.finish(&mut scopes.last_mut().unwrap_or(&mut root).inner);
+        }
+        let mut variables = Vec::new();
+        root.finish(&mut variables);
+        Ok(variables)
+    }
+
+    fn scope_contains(
+        &self,
+        entry: &DebuggingInformationEntry<'_, '_, R>,
+        address: u64,
+        default: bool,
+    ) -> Result<bool> {
+        let mut ranges = self.die_ranges(entry)?;
+        let mut empty = true;
+        while let Some(range) = ranges.next()? {
+            if range.begin <= address && address < range.end {
+                return Ok(true);
+            }
+            empty = false;
+        }
+        Ok(empty && default)
+    }
+
+    fn scope_variable(
+        &self,
+        entry: &DebuggingInformationEntry<'_, '_, R>,
+        scope: UnitOffset<R::Offset>,
+        address: u64,
+    ) -> Result<ScopeVariable<R>> {
+        let mut variable = ScopeVariable {
+            offset: entry.offset(),
+            scope,
+            is_parameter: entry.tag() == constants::DW_TAG_formal_parameter,
+            name: None,
+            type_offset: None,
+            location: None,
+        };
+        if let Some(location) = entry.attr_value(constants::DW_AT_location)? {
+            variable.location = self.location_at(location, address)?;
+        }
+
+        let same_unit = |unit: &Unit<R>| unit.header.offset() == self.header.offset();
+        for_each_origin(self.dwarf, self.unit, entry.offset(), |unit, entry| {
+            if variable.name.is_none() {
+                if let Some(value) = entry.attr_value(constants::DW_AT_name)? {
+                    variable.name = Some(self.dwarf.attr_string(unit, value)?);
+                }
+            }
+            if variable.type_offset.is_none() && same_unit(unit) {
+                if let Some(AttributeValue::UnitRef(offset)) =
+                    entry.attr_value(constants::DW_AT_type)?
+                {
+                    variable.type_offset = Some(offset);
+                }
+            }
+            Ok(variable.name.is_none() || variable.type_offset.is_none())
+        })?;
+        Ok(variable)
+    }
+
+    fn location_at(&self, value: AttributeValue<R>, address: u64) -> Result<Option<Expression<R>>> {
+        if let Some(expression) = value.exprloc_value() {
+            return Ok(Some(expression));
+        }
+        if let Some(mut locations) = self.attr_locations(value)? {
+            while let Some(location) = locations.next()? {
+                if location.range.begin <= address && address < location.range.end {
+                    return Ok(Some(location.data));
+                }
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// A scope that contains the address, and the variables found in it so far.
+struct Scope<R: Reader> {
+    offset: UnitOffset<R::Offset>,
+    depth: isize,
+    in_subprogram: bool,
+    /// The variables of this scope.
+    variables: Vec<ScopeVariable<R>>,
+    /// The variables of the scopes nested within this scope.
+    inner: Vec<ScopeVariable<R>>,
+}
+
+impl<R: Reader> Scope<R> {
+    fn new(offset: UnitOffset<R::Offset>, depth: isize, in_subprogram: bool) -> Self {
+        Scope {
+            offset,
+            depth,
+            in_subprogram,
+            variables: Vec::new(),
+            inner: Vec::new(),
+        }
+    }
+
+    /// Append the variables of this scope, followed by those of the nested scopes.
+    fn finish(mut self, variables: &mut Vec<ScopeVariable<R>>) {
+        variables.append(&mut self.variables);
+        variables.append(&mut self.inner);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::*;
+    use crate::endianity::LittleEndian;
+    use crate::read::abbrev::tests::AbbrevSectionMethods;
+    use crate::read::{DebugLoc, Dwarf, EndianSlice};
+    use crate::test_util::{compile_unit, test_dwarf, GimliSectionMethods};
+    use test_assembler::{Endian, Label, LabelMaker, Section};
+
+    #[test]
+    fn test_variables_at() {
+        #[rustfmt::skip]
+        let abbrev = Section::with_endian(Endian::Little)
+            .abbrev(1, DW_TAG_compile_unit, DW_CHILDREN_yes)
+                .abbrev_attr_null()
+            .abbrev(2, DW_TAG_base_type, DW_CHILDREN_no)
+                .abbrev_attr_null()
+            .abbrev(3, DW_TAG_variable, DW_CHILDREN_no)
+                .abbrev_attr(DW_AT_name, DW_FORM_string)
+                .abbrev_attr(DW_AT_type, DW_FORM_ref4)
+                .abbrev_attr_null()
+            .abbrev(4, DW_TAG_subprogram, DW_CHILDREN_yes)
+                .abbrev_attr(DW_AT_low_pc, DW_FORM_addr)
+                .abbrev_attr(DW_AT_high_pc, DW_FORM_udata)
+                .abbrev_attr_null()
+            .abbrev(5, DW_TAG_formal_parameter, DW_CHILDREN_no)
+                .abbrev_attr(DW_AT_name, DW_FORM_string)
+                .abbrev_attr(DW_AT_type, DW_FORM_ref4)
+                .abbrev_attr(DW_AT_location, DW_FORM_exprloc)
+                .abbrev_attr_null()
+            .abbrev(6, DW_TAG_lexical_block, DW_CHILDREN_yes)
+                .abbrev_attr(DW_AT_low_pc, DW_FORM_addr)
+                .abbrev_attr(DW_AT_high_pc, DW_FORM_udata)
+                .abbrev_attr_null()
+            .abbrev(7, DW_TAG_variable, DW_CHILDREN_no)
+                .abbrev_attr(DW_AT_name, DW_FORM_string)
+                .abbrev_attr(DW_AT_location, DW_FORM_sec_offset)
+                .abbrev_attr_null()
+            .abbrev(8, DW_TAG_variable, DW_CHILDREN_no)
+                .abbrev_attr(DW_AT_abstract_origin, DW_FORM_ref4)
+                .abbrev_attr_null()
+            .abbrev_null();
+        let abbrev = abbrev.get_contents().unwrap();
+
+        #[rustfmt::skip]
+        let loc = Section::with_endian(Endian::Little)
+            .L64(0x1020).L64(0x1030).L16(1).D8(DW_OP_reg1.0)
+            .L64(0x1030).L64(0x1040).L16(1).D8(DW_OP_reg2.0)
+            .L64(0).L64(0);
+        let loc = loc.get_contents().unwrap();
+
+        let int = Label::new();
+        let global = Label::new();
+        let function = Label::new();
+        let param = Label::new();
+        let origin = Label::new();
+        let cycle = Label::new();
+        let block = Label::new();
+        let local = Label::new();
+        #[rustfmt::skip]
+        let entries = Section::with_endian(Endian::Little)
+            .uleb(1)
+                .mark(&int)
+                .uleb(2)
+                .mark(&global)
+                .uleb(3).append_bytes(b"global\0").L32(&int)
+                .mark(&function)
+                .uleb(4).L64(0x1000).uleb(0x100)
+                    .mark(&param)
+                    .uleb(5).append_bytes(b"arg\0").L32(&int).uleb(1).D8(DW_OP_reg5.0)
+                    .mark(&origin)
+                    .uleb(8).L32(&global)
+                    // An origin reference to itself.
+                    .mark(&cycle)
+                    .uleb(8).L32(&cycle)
+                    .mark(&block)
+                    .uleb(6).L64(0x1020).uleb(0x20)
+                        .mark(&local)
+                        .uleb(7).append_bytes(b"local\0").L32(0)
+                        .D8(0)
+                    .D8(0)
+                .D8(0);
+        let info = compile_unit(entries);
+        let offset = |label: &Label| UnitOffset(label.value().unwrap() as usize);
+
+        let dwarf = Dwarf {
+            locations: crate::read::LocationLists::new(
+                DebugLoc::new(&loc, LittleEndian),
+                Default::default(),
+            ),
+            ..test_dwarf(&abbrev, &info)
+        };
+        let header = dwarf.units().next().unwrap().unwrap();
+        let unit = Unit::new(&dwarf, header).unwrap();
+        let unit = unit.unit_ref(&dwarf);
+
+        assert!(unit.variables_at(0x2000).unwrap().is_empty());
+
+        let variables = unit.variables_at(0x1000).unwrap();
+        assert_eq!(variables.len(), 3);
+        assert_eq!(variables[0].offset, offset(&param));
+        assert_eq!(variables[0].scope, offset(&function));
+        assert!(variables[0].is_parameter);
+        assert_eq!(variables[0].type_offset, Some(offset(&int)));
+        assert_eq!(
+            variables[0].name,
+            Some(EndianSlice::new(b"arg", LittleEndian))
+        );
+        assert_eq!(
+            variables[0].location,
+            Some(Expression(EndianSlice::new(&[DW_OP_reg5.0], LittleEndian)))
+        );
+        // The name and type are taken from the origin.
+        assert_eq!(variables[1].offset, offset(&origin));
+        assert_eq!(
+            variables[1].name,
+            Some(EndianSlice::new(b"global", LittleEndian))
+        );
+        assert_eq!(variables[1].type_offset, Some(offset(&int)));
+        assert_eq!(variables[1].location, None);
+        assert_eq!(variables[2].offset, offset(&cycle));
+        assert_eq!(variables[2].name, None);
+        assert_eq!(variables[2].type_offset, None);
+
+        let variables = unit.variables_at(0x1034).unwrap();
+        assert_eq!(variables.len(), 4);
+        assert_eq!(variables[0].offset, offset(&param));
+        assert_eq!(variables[3].offset, offset(&local));
+        assert_eq!(variables[3].scope, offset(&block));
+        assert!(!variables[3].is_parameter);
+        assert_eq!(
+            variables[3].name,
+            Some(EndianSlice::new(b"local", LittleEndian))
+        );
+        assert_eq!(
+            variables[3].location,
+            Some(Expression(EndianSlice::new(&[DW_OP_reg2.0], LittleEndian)))
+        );
+    }
+
+    #[test]
+    fn test_variables_at_nested() {
+        const DEPTH: usize = 1000;
+
+        #[rustfmt::skip]
+        let abbrev = Section::with_endian(Endian::Little)
+            .abbrev(1, DW_TAG_compile_unit, DW_CHILDREN_yes)
+                .abbrev_attr_null()
+            .abbrev(2, DW_TAG_subprogram, DW_CHILDREN_yes)
+                .abbrev_attr(DW_AT_low_pc, DW_FORM_addr)
+                .abbrev_attr(DW_AT_high_pc, DW_FORM_udata)
+                .abbrev_attr_null()
+            .abbrev(3, DW_TAG_lexical_block, DW_CHILDREN_yes)
+                .abbrev_attr_null()
+            .abbrev(4, DW_TAG_variable, DW_CHILDREN_no)
+                .abbrev_attr(DW_AT_name, DW_FORM_string)
+                .abbrev_attr_null()
+            .abbrev_null();
+        let abbrev = abbrev.get_contents().unwrap();
+
+        let innermost = Label::new();
+        let mut entries = Section::with_endian(Endian::Little)
+            .uleb(1)
+            .uleb(2)
+            .L64(0x1000)
+            .uleb(0x100);
+        // Lexical blocks without ranges have the scope of their parent.
+        for _ in 0..DEPTH - 1 {
+            entries = entries.uleb(3);
+        }
+        entries = entries
+            .mark(&innermost)
+            .uleb(3)
+            .uleb(4)
+            .append_bytes(b"x\0");
+        for _ in 0..DEPTH + 2 {
+            entries = entries.D8(0);
+        }
+        let info = compile_unit(entries);
+
+        let dwarf = test_dwarf(&abbrev, &info);
+        let header = dwarf.units().next().unwrap().unwrap();
+        let unit = Unit::new(&dwarf, header).unwrap();
+        let unit = unit.unit_ref(&dwarf);
+
+        let variables = unit.variables_at(0x1000).unwrap();
+        assert_eq!(variables.len(), 1);
+        assert_eq!(
+            variables[0].scope,
+            UnitOffset(innermost.value().unwrap() as usize)
+        );
+        assert!(unit.variables_at(0x2000).unwrap().is_empty());
+    }
+}
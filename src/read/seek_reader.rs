@@ -0,0 +1,433 @@
+//! A `Reader` that is backed by a `Read + Seek` source.
+
+use alloc::borrow::Cow;
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::fmt;
+use std::collections::HashMap;
+use std::io::{Read, Seek, SeekFrom};
+use std::sync::Mutex;
+
+use crate::endianity::Endianity;
+use crate::read::{Error, Reader, ReaderOffsetId, Result};
+
+/// The default size of a cached block, in bytes.
+const DEFAULT_BLOCK_SIZE: usize = 4096;
+
+/// The default maximum number of cached blocks.
+const DEFAULT_MAX_BLOCKS: usize = 256;
+
+trait ReadSeek: Read + Seek + Send {}
+
+impl<T: Read + Seek + Send> ReadSeek for T {}
+
+struct CachedBlock {
+    data: Vec<u8>,
+    last_used: u64,
+}
+
+struct SeekSourceInner {
+    source: Box<dyn ReadSeek>,
+    block_size: usize,
+    max_blocks: usize,
+    blocks: HashMap<u64, CachedBlock>,
+    tick: u64,
+}
+
+impl SeekSourceInner {
+    /// Return the cached data for the block with the given index, reading it
+    /// from the source if needed.
+    ///
+    /// The block may be shorter than `block_size` if it is at the end of the source.
+    fn block(&mut self, index: u64) -> Result<&[u8]> {
+        self.tick += 1;
+        let tick = self.tick;
+
+        if !self.blocks.contains_key(&index) {
+            if self.blocks.len() >= self.max_blocks {
+                let oldest = self
+                    .blocks
+                    .iter()
+                    .min_by_key(|(_, block)| block.last_used)
+                    .map(|(index, _)| *index);
+                if let Some(oldest) = oldest {
+                    self.blocks.remove(&oldest);
+                }
+            }
+
+            let start = index.checked_mul(self.block_size as u64).ok_or(Error::Io)?;
+            self.source.seek(SeekFrom::Start(start))?;
+            let mut data = vec![0; self.block_size];
+            let mut len = 0;
+            while len < data.len() {
+                match self.source.read(&mut data[len..]) {
+                    Ok(0) => break,
+                    Ok(n) => len += n,
+                    Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+                    Err(e) => return Err(e.into()),
+                }
+            }
+            data.truncate(len);
+            self.blocks.insert(
+                index,
+                CachedBlock {
+                    data,
+                    last_used: tick,
+                },
+            );
+        }
+
+        let block = self.blocks.get_mut(&index).unwrap();
+        block.last_used = tick;
+        Ok(&block.data)
+    }
+
+    /// Read exactly `buf.len()` bytes starting at `pos`.
+    fn read_at(&mut self, mut pos: u64, mut buf: &mut [u8]) -> Result<()> {
+        let block_size = self.block_size as u64;
+        while !buf.is_empty() {
+            let block = self.block(pos / block_size)?;
+            let offset = (pos % block_size) as usize;
+            if offset >= block.len() {
+                // The source is shorter than the reader.
+                return Err(Error::Io);
+            }
+            let len = core::cmp::min(block.len() - offset, buf.len());
+            let (head, tail) = buf.split_at_mut(len);
+            head.copy_from_slice(&block[offset..][..len]);
+            buf = tail;
+            pos += len as u64;
+        }
+        Ok(())
+    }
+
+    /// Return the position of the first occurrence of `byte` in the `len` bytes
+    /// starting at `pos`.
+    fn find_at(&mut self, mut pos: u64, mut len: u64, byte: u8) -> Result<Option<u64>> {
+        let block_size = self.block_size as u64;
+        while len != 0 {
+            let block = self.block(pos / block_size)?;
+            let offset = (pos % block_size) as usize;
+            if offset >= block.len() {
+                return Err(Error::Io);
+            }
+            let n = core::cmp::min((block.len() - offset) as u64, len);
+            let data = &block[offset..][..n as usize];
+            if let Some(index) = data.iter().position(|x| *x == byte) {
+                return Ok(Some(pos + index as u64));
+            }
+            pos += n;
+            len -= n;
+        }
+        Ok(None)
+    }
+}
+
+/// A shared handle to a `Read + Seek` source, such as a `std::fs::File`.
+///
+/// Data is read from the source in fixed size blocks, and a bounded number of
+/// these blocks are cached. This allows reading large debugging information
+/// sections without loading them into memory, at the cost of speed.
+///
+/// Use [`SeekSource::reader`] to create a [`SeekReader`] for each section.
+/// All readers created from the same source share the same cache.
+///
+/// The source is protected by a mutex, so readers may be sent between threads,
+/// but they will not read in parallel.
+#[derive(Clone)]
+pub struct SeekSource {
+    inner: Arc<Mutex<SeekSourceInner>>,
+}
+
+impl fmt::Debug for SeekSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SeekSource").finish_non_exhaustive()
+    }
+}
+
+impl SeekSource {
+    /// Construct a new `SeekSource` with the default cache size.
+    ///
+    /// This caches up to 256 blocks of 4096 bytes.
+    pub fn new<S>(source: S) -> Self
+    where
+        S: Read + Seek + Send + 'static,
+    {
+        Self::with_cache_size(source, DEFAULT_BLOCK_SIZE, DEFAULT_MAX_BLOCKS)
+    }
+
+    /// Construct a new `SeekSource` that caches up to `max_blocks` blocks
+    /// of `block_size` bytes.
+    ///
+    /// Both `block_size` and `max_blocks` are treated as being at least 1.
+    pub fn with_cache_size<S>(source: S, block_size: usize, max_blocks: usize) -> Self
+    where
+        S: Read + Seek + Send + 'static,
+    {
+        SeekSource {
+            inner: Arc::new(Mutex::new(SeekSourceInner {
+                source: Box::new(source),
+                block_size: core::cmp::max(block_size, 1),
+                max_blocks: core::cmp::max(max_blocks, 1),
+                blocks: HashMap::new(),
+                tick: 0,
+            })),
+        }
+    }
+
+    /// Return a reader for the `len` bytes starting at `offset` in the source.
+    ///
+    /// The range is not checked against the length of the source. Reading data
+    /// beyond the end of the source returns `Error::Io`.
+    ///
+    /// ```
+    /// use gimli::{LittleEndian, SeekSource};
+    /// use std::io::Cursor;
+    ///
+    /// # fn main() -> gimli::Result<()> {
+    /// // In practice, the section offsets and sizes are obtained from the
+    /// // object file headers, and the source is a `std::fs::File`.
+    /// let source = SeekSource::new(Cursor::new(vec![0u8; 0x100]));
+    /// let dwarf = gimli::Dwarf::load(|id| -> gimli::Result<_> {
+    ///     let (offset, len) = match id {
+    ///         gimli::SectionId::DebugInfo => (0x10, 0x20),
+    ///         _ => (0, 0),
+    ///     };
+    ///     Ok(source.reader(offset, len, LittleEndian))
+    /// })?;
+    /// # let _ = dwarf;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn reader<Endian>(&self, offset: u64, len: usize, endian: Endian) -> SeekReader<Endian>
+    where
+        Endian: Endianity,
+    {
+        SeekReader {
+            source: self.clone(),
+            endian,
+            offset,
+            len,
+        }
+    }
+
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, SeekSourceInner>> {
+        self.inner.lock().map_err(|_| Error::Io)
+    }
+}
+
+/// A `Reader` for a range of bytes in a [`SeekSource`].
+///
+/// Methods that return data, such as `to_slice`, always return owned data.
+#[derive(Debug, Clone)]
+pub struct SeekReader<Endian>
+where
+    Endian: Endianity,
+{
+    source: SeekSource,
+    endian: Endian,
+    offset: u64,
+    len: usize,
+}
+
+impl<Endian> SeekReader<Endian>
+where
+    Endian: Endianity,
+{
+    /// Return the offset of the start of this reader within the source.
+    #[inline]
+    pub fn source_offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Return all remaining data as a vector.
+    pub fn to_vec(&self) -> Result<Vec<u8>> {
+        let mut buf = vec![0; self.len];
+        self.source.lock()?.read_at(self.offset, &mut buf)?;
+        Ok(buf)
+    }
+}
+
+impl<Endian> Reader for SeekReader<Endian>
+where
+    Endian: Endianity,
+{
+    type Endian = Endian;
+    type Offset = usize;
+
+    #[inline]
+    fn endian(&self) -> Endian {
+        self.endian
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    fn empty(&mut self) {
+        self.len = 0;
+    }
+
+    #[inline]
+    fn truncate(&mut self, len: usize) -> Result<()> {
+        if self.len < len {
+            Err(Error::UnexpectedEof(self.offset_id()))
+        } else {
+            self.len = len;
+            Ok(())
+        }
+    }
+
+    #[inline]
+    fn offset_from(&self, base: &Self) -> usize {
+        (self.offset - base.offset) as usize
+    }
+
+    #[inline]
+    fn offset_id(&self) -> ReaderOffsetId {
+        ReaderOffsetId(self.offset)
+    }
+
+    #[inline]
+    fn lookup_offset_id(&self, id: ReaderOffsetId) -> Option<usize> {
+        let id = id.0;
+        if id >= self.offset && id - self.offset <= self.len as u64 {
+            Some((id - self.offset) as usize)
+        } else {
+            None
+        }
+    }
+
+    fn find(&self, byte: u8) -> Result<usize> {
+        match self
+            .source
+            .lock()?
+            .find_at(self.offset, self.len as u64, byte)?
+        {
+            Some(pos) => Ok((pos - self.offset) as usize),
+            None => Err(Error::UnexpectedEof(self.offset_id())),
+        }
+    }
+
+    #[inline]
+    fn skip(&mut self, len: usize) -> Result<()> {
+        if self.len < len {
+            Err(Error::UnexpectedEof(self.offset_id()))
+        } else {
+            self.offset += len as u64;
+            self.len -= len;
+            Ok(())
+        }
+    }
+
+    #[inline]
+    fn split(&mut self, len: usize) -> Result<Self> {
+        let mut head = self.clone();
+        head.truncate(len)?;
+        self.skip(len)?;
+        Ok(head)
+    }
+
+    fn to_slice(&self) -> Result<Cow<'_, [u8]>> {
+        self.to_vec().map(Cow::Owned)
+    }
+
+    fn to_string(&self) -> Result<Cow<'_, str>> {
+        match String::from_utf8(self.to_vec()?) {
+            Ok(s) => Ok(Cow::Owned(s)),
+            _ => Err(Error::BadUtf8),
+        }
+    }
+
+    fn to_string_lossy(&self) -> Result<Cow<'_, str>> {
+        let buf = self.to_vec()?;
+        Ok(Cow::Owned(String::from_utf8_lossy(&buf).into_owned()))
+    }
+
+    fn read_slice(&mut self, buf: &mut [u8]) -> Result<()> {
+        if self.len < buf.len() {
+            return Err(Error::UnexpectedEof(self.offset_id()));
+        }
+        self.source.lock()?.read_at(self.offset, buf)?;
+        self.skip(buf.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::DebugStrOffset;
+    use crate::endianity::LittleEndian;
+    use crate::read::DebugStr;
+    use std::io::Cursor;
+
+    fn source(block_size: usize, max_blocks: usize) -> SeekSource {
+        let data: Vec<u8> = (0..=255).collect();
+        SeekSource::with_cache_size(Cursor::new(data), block_size, max_blocks)
+    }
+
+    #[test]
+    fn test_seek_reader_read() {
+        // Use small blocks so that reads cross block boundaries.
+        let source = source(3, 2);
+        let mut reader = source.reader(0x10, 0x20, LittleEndian);
+        assert_eq!(reader.len(), 0x20);
+        assert_eq!(reader.read_u8(), Ok(0x10));
+        assert_eq!(reader.read_u32(), Ok(0x1413_1211));
+        assert_eq!(reader.read_u64(), Ok(0x1c1b_1a19_1817_1615));
+
+        let mut head = reader.split(4).unwrap();
+        assert_eq!(head.to_slice().unwrap(), &[0x1d, 0x1e, 0x1f, 0x20][..]);
+        assert_eq!(head.read_u16(), Ok(0x1e1d));
+        assert_eq!(reader.read_u8(), Ok(0x21));
+
+        let base = source.reader(0x10, 0x20, LittleEndian);
+        assert_eq!(reader.offset_from(&base), 0x12);
+        assert_eq!(reader.lookup_offset_id(base.offset_id()), None);
+        assert_eq!(base.lookup_offset_id(reader.offset_id()), Some(0x12));
+
+        assert_eq!(reader.find(0x28), Ok(6));
+        assert_eq!(
+            reader.find(0x01),
+            Err(Error::UnexpectedEof(reader.offset_id()))
+        );
+
+        let mut buf = [0; 0x20];
+        assert_eq!(
+            reader.read_slice(&mut buf),
+            Err(Error::UnexpectedEof(reader.offset_id()))
+        );
+        reader.skip(reader.len()).unwrap();
+        assert!(reader.is_empty());
+    }
+
+    #[test]
+    fn test_seek_reader_past_end() {
+        let source = source(16, 4);
+        let mut reader = source.reader(0xfe, 4, LittleEndian);
+        assert_eq!(reader.read_u16(), Ok(0xfffe));
+        assert_eq!(reader.read_u8(), Err(Error::Io));
+    }
+
+    #[test]
+    fn test_seek_reader_debug_str() {
+        let data = b"\0foo\0bar\0".to_vec();
+        let source = SeekSource::with_cache_size(Cursor::new(data), 2, 1);
+        let debug_str = DebugStr::from(source.reader(0, 9, LittleEndian));
+        let s = debug_str.get_str(DebugStrOffset(5)).unwrap();
+        assert_eq!(s.to_string().unwrap(), "bar");
+        let s = debug_str.get_str(DebugStrOffset(1)).unwrap();
+        assert_eq!(s.to_string_lossy().unwrap(), "foo");
+    }
+
+    #[test]
+    fn test_seek_reader_send_sync() {
+        fn assert_send_sync<T: Send + Sync + 'static>() {}
+        assert_send_sync::<SeekSource>();
+        assert_send_sync::<SeekReader<LittleEndian>>();
+    }
+}
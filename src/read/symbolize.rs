@@ -0,0 +1,508 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::mem;
+
+use crate::common::UnitSectionOffset;
+use crate::constants;
+use crate::read::lazy::LazyArc;
+use crate::read::range_index::{RangeIndex, RangeIndexEntry};
+use crate::read::{
+    AttributeValue, ColumnType, ContextError, DebuggingInformationEntry, Dwarf, LineProgramHeader,
+    Range, Reader, Result, Unit, UnitAddressMap, UnitOffset,
+};
+
+/// The maximum number of `DW_AT_abstract_origin` or `DW_AT_specification`
+/// references that are followed from an entry, to avoid loops.
+pub(crate) const MAX_ORIGIN_DEPTH: usize = 16;
+
+/// The function and source location for an address.
+///
+/// See [`Symbolizer::lookup`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymbolInfo<R: Reader> {
+    /// The offset of the header of the unit containing the address.
+    pub unit_offset: UnitSectionOffset<R::Offset>,
+    /// The offset of the innermost `DW_TAG_subprogram` entry containing the address.
+    pub function: Option<UnitOffset<R::Offset>>,
+    /// The `DW_AT_name` of the function.
+    pub name: Option<R>,
+    /// The `DW_AT_linkage_name` or `DW_AT_MIPS_linkage_name` of the function.
+    pub linkage_name: Option<R>,
+    /// The path of the source file, including the directory and the
+    /// compilation directory if they are known.
+    pub file: Option<String>,
+    /// The source line number, or `None` if the line is unknown.
+    pub line: Option<u64>,
+    /// The source column number, or `None` if the column is unknown.
+    pub column: Option<u64>,
+}
+
+#[derive(Debug)]
+struct UnitSymbols<R: Reader> {
+    line_header: Option<LineProgramHeader<R>>,
+    sequences: RangeIndex<SymbolSequence>,
+    functions: RangeIndex<FunctionRange<R::Offset>>,
+}
+
+#[derive(Debug)]
+struct SymbolSequence {
+    begin: u64,
+    end: u64,
+    rows: Vec<SymbolRow>,
+}
+
+impl RangeIndexEntry for SymbolSequence {
+    fn range(&self) -> Range {
+        Range {
+            begin: self.begin,
+            end: self.end,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct SymbolRow {
+    address: u64,
+    file_index: u64,
+    line: u64,
+    column: u64,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct FunctionRange<T> {
+    range: Range,
+    offset: UnitOffset<T>,
+}
+
+impl<T> RangeIndexEntry for FunctionRange<T> {
+    fn range(&self) -> Range {
+        self.range
+    }
+}
+
+/// A cache for finding the function and source location of addresses.
+///
+/// This combines a [`UnitAddressMap`] to find the compilation unit for an address,
+/// a search of the `DW_TAG_subprogram` entries in the unit, and the line
+/// program of the unit. The units, functions and line rows are parsed on first use
+/// and then cached, so that repeated lookups are cheap.
+///
+/// Inlined subroutines are not reported; the result gives the outermost function
+/// and the source location of the address within that function.
+///
+/// ```rust,no_run
+/// # fn example<R: gimli::Reader>(dwarf: &gimli::Dwarf<R>) -> gimli::Result<()> {
+/// let symbolizer = gimli::Symbolizer::new(dwarf);
+/// if let Some(info) = symbolizer.lookup(dwarf, 0x1234)? {
+///     if let Some(name) = info.name {
+///         println!("{}", name.to_string_lossy()?);
+///     }
+///     println!("{:?}:{:?}:{:?}", info.file, info.line, info.column);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Symbolizer<R: Reader> {
+    units: UnitAddressMap<R>,
+    symbols: Vec<LazyArc<UnitSymbols<R>>>,
+}
+
+impl<R: Reader> Symbolizer<R> {
+    /// Build the address map for the compilation units in the `.debug_info` section.
+    ///
+    /// See [`UnitAddressMap::new`].
+    pub fn new(dwarf: &Dwarf<R>) -> Self {
+        let units = UnitAddressMap::new(dwarf);
+        let mut symbols = Vec::with_capacity(units.len());
+        symbols.resize_with(units.len(), LazyArc::default);
+        Symbolizer { units, symbols }
+    }
+
+    /// Return the errors for the units that were skipped when building the address map.
+    ///
+    /// See [`UnitAddressMap::errors`].
+    pub fn errors(&self, dwarf: &Dwarf<R>) -> Vec<ContextError> {
+        self.units.errors(dwarf)
+    }
+
+    /// Find the function and source location for the given address.
+    ///
+    /// Returns `None` if no compilation unit contains the address. Otherwise,
+    /// the fields of the result that could not be determined are `None`.
+    ///
+    /// `dwarf` must be the same as the value given to `Symbolizer::new`.
+    pub fn lookup(&self, dwarf: &Dwarf<R>, address: u64) -> Result<Option<SymbolInfo<R>>> {
+        let index = match self.units.find_index(dwarf, address) {
+            Some(index) => index,
+            None => return Ok(None),
+        };
+        let (header, unit) = self.units.unit_at(dwarf, index)?;
+        let symbols = self.symbols[index].get(|| UnitSymbols::new(dwarf, &unit))?;
+
+        let mut info = SymbolInfo {
+            unit_offset: header.offset(),
+            function: None,
+            name: None,
+            linkage_name: None,
+            file: None,
+            line: None,
+            column: None,
+        };
+
+        if let Some(function) = symbols.find_function(address) {
+            info.function = Some(function);
+            let (name, linkage_name) = function_name(dwarf, &unit, function)?;
+            info.name = name;
+            info.linkage_name = linkage_name;
+        }
+
+        if let (Some(row), Some(line_header)) = (symbols.find_row(address), &symbols.line_header) {
+            info.file = file_path(dwarf, &unit, line_header, row.file_index)?;
+            info.line = if row.line != 0 { Some(row.line) } else { None };
+            info.column = if row.column != 0 {
+                Some(row.column)
+            } else {
+                None
+            };
+        }
+
+        Ok(Some(info))
+    }
+}
+
+impl<R: Reader> UnitSymbols<R> {
+    fn new(dwarf: &Dwarf<R>, unit: &Unit<R>) -> Result<Self> {
+        let mut functions = Vec::new();
+        let mut entries = unit.entries();
+        while let Some((_, entry)) = entries.next_dfs()? {
+            if entry.tag() != constants::DW_TAG_subprogram {
+                continue;
+            }
+            let mut ranges = dwarf.die_ranges(unit, entry)?;
+            while let Some(range) = ranges.next()? {
+                functions.push(FunctionRange {
+                    range,
+                    offset: entry.offset(),
+                });
+            }
+        }
+
+        let mut line_header = None;
+        let mut sequences = Vec::new();
+        if let Some(program) = unit.line_program.clone() {
+            line_header = Some(program.header().clone());
+            let mut rows = program.rows();
+            let mut sequence_rows = Vec::new();
+            while let Some((_, row)) = rows.next_row()? {
+                if row.end_sequence() {
+                    let begin = sequence_rows.first().map(|first: &SymbolRow| first.address);
+                    match begin {
+                        Some(begin) if begin < row.address() => {
+                            sequences.push(SymbolSequence {
+                                begin,
+                                end: row.address(),
+                                rows: mem::take(&mut sequence_rows),
+                            });
+                        }
+                        _ => sequence_rows.clear(),
+                    }
+                    continue;
+                }
+                let symbol_row = SymbolRow {
+                    address: row.address(),
+                    file_index: row.file_index(),
+                    line: row.line().map(|line| line.get()).unwrap_or(0),
+                    column: match row.column() {
+                        ColumnType::LeftEdge => 0,
+                        ColumnType::Column(column) => column.get(),
+                    },
+                };
+                // Only keep the last row for an address.
+                match sequence_rows.last_mut() {
+                    Some(last) if last.address == symbol_row.address => *last = symbol_row,
+                    _ => sequence_rows.push(symbol_row),
+                }
+            }
+        }
+
+        Ok(UnitSymbols {
+            line_header,
+            sequences: RangeIndex::new(sequences),
+            functions: RangeIndex::new(functions),
+        })
+    }
+
+    /// Return the innermost function containing the address.
+    fn find_function(&self, address: u64) -> Option<UnitOffset<R::Offset>> {
+        self.functions.find(address).map(|function| function.offset)
+    }
+
+    fn find_row(&self, address: u64) -> Option<&SymbolRow> {
+        let sequence = self.sequences.find(address)?;
+        let index = sequence.rows.partition_point(|row| row.address <= address);
+        sequence.rows.get(index.checked_sub(1)?)
+    }
+}
+
+/// Find the name and linkage name of a function, following `DW_AT_abstract_origin`
+/// and `DW_AT_specification` references if they are not present on the entry.
+fn function_name<R: Reader>(
+    dwarf: &Dwarf<R>,
+    unit: &Unit<R>,
+    offset: UnitOffset<R::Offset>,
+) -> Result<(Option<R>, Option<R>)> {
+    let mut name = None;
+    let mut linkage_name = None;
+    for_each_origin(dwarf, unit, offset, |unit, entry| {
+        if name.is_none() {
+            if let Some(value) = entry.attr_value(constants::DW_AT_name)? {
+                name = Some(dwarf.attr_string(unit, value)?);
+            }
+        }
+        if linkage_name.is_none() {
+            let value = match entry.attr_value(constants::DW_AT_linkage_name)? {
+                Some(value) => Some(value),
+                None => entry.attr_value(constants::DW_AT_MIPS_linkage_name)?,
+            };
+            if let Some(value) = value {
+                linkage_name = Some(dwarf.attr_string(unit, value)?);
+            }
+        }
+        Ok(name.is_none() || linkage_name.is_none())
+    })?;
+    Ok((name, linkage_name))
+}
+
+/// Call `f` for the entry at `offset`, and then for each entry that is
+/// referenced by the previous entry's `DW_AT_abstract_origin` or
+/// `DW_AT_specification` attribute, until `f` returns false.
+///
+/// At most `MAX_ORIGIN_DEPTH` references are followed. The references may be
+/// to entries in other units, in which case `f` is passed the other unit.
+pub(crate) fn for_each_origin<R, F>(
+    dwarf: &Dwarf<R>,
+    unit: &Unit<R>,
+    mut offset: UnitOffset<R::Offset>,
+    mut f: F,
+) -> Result<()>
+where
+    R: Reader,
+    F: FnMut(&Unit<R>, &DebuggingInformationEntry<'_, '_, R>) -> Result<bool>,
+{
+    let mut other_unit = None;
+    for _ in 0..=MAX_ORIGIN_DEPTH {
+        let unit = other_unit.as_ref().unwrap_or(unit);
+        let entry = unit.entry(offset)?;
+        if !f(unit, &entry)? {
+            break;
+        }
+        let next = match entry.attr_value(constants::DW_AT_abstract_origin)? {
+            Some(value) => Some(value),
+            None => entry.attr_value(constants::DW_AT_specification)?,
+        };
+        match next {
+            Some(AttributeValue::UnitRef(next)) => offset = next,
+            Some(AttributeValue::DebugInfoRef(next)) => match next.to_unit_offset(&unit.header) {
+                Some(next) => offset = next,
+                None => {
+                    let (next_unit, next) = dwarf.entry_at(next.into())?;
+                    other_unit = Some(next_unit);
+                    offset = next;
+                }
+            },
+            _ => break,
+        }
+    }
+    Ok(())
+}
+
+/// Return the full path of the given file in a line program.
+pub(crate) fn file_path<R: Reader>(
+    dwarf: &Dwarf<R>,
+    unit: &Unit<R>,
+    header: &LineProgramHeader<R>,
+    file_index: u64,
+) -> Result<Option<String>> {
+    let file = match header.file(file_index) {
+        Some(file) => file,
+        None => return Ok(None),
+    };
+    let mut path = String::new();
+    if let Some(comp_dir) = &unit.comp_dir {
+        path_push(&mut path, &comp_dir.to_string_lossy()?);
+    }
+    // For DWARF version <= 4, directory 0 is the compilation directory,
+    // which has already been added.
+    if file.directory_index() != 0 || header.version() >= 5 {
+        if let Some(directory) = file.directory(header) {
+            path_push(
+                &mut path,
+                &dwarf.attr_string(unit, directory)?.to_string_lossy()?,
+            );
+        }
+    }
+    path_push(
+        &mut path,
+        &dwarf
+            .attr_string(unit, file.path_name())?
+            .to_string_lossy()?,
+    );
+    Ok(Some(path))
+}
+
+pub(crate) fn path_push(path: &mut String, p: &str) {
+    if has_unix_root(p) || has_windows_root(p) || path.is_empty() {
+        *path = p.into();
+    } else if !p.is_empty() {
+        let separator = if has_windows_root(path) { '\\' } else { '/' };
+        if !path.ends_with(separator) {
+            path.push(separator);
+        }
+        path.push_str(p);
+    }
+}
+
+fn has_unix_root(p: &str) -> bool {
+    p.starts_with('/')
+}
+
+fn has_windows_root(p: &str) -> bool {
+    p.starts_with('\\') || p.get(1..3) == Some(":\\")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::*;
+    use crate::endianity::LittleEndian;
+    use crate::read::abbrev::tests::AbbrevSectionMethods;
+    use crate::read::{DebugLine, EndianSlice};
+    use crate::test_util::{compile_unit, test_dwarf, GimliSectionMethods};
+    use crate::Format;
+    use test_assembler::{Endian, Label, LabelMaker, Section};
+
+    #[test]
+    fn test_symbolizer() {
+        #[rustfmt::skip]
+        let abbrev = Section::with_endian(Endian::Little)
+            .abbrev(1, DW_TAG_compile_unit, DW_CHILDREN_yes)
+                .abbrev_attr(DW_AT_name, DW_FORM_string)
+                .abbrev_attr(DW_AT_comp_dir, DW_FORM_string)
+                .abbrev_attr(DW_AT_low_pc, DW_FORM_addr)
+                .abbrev_attr(DW_AT_high_pc, DW_FORM_udata)
+                .abbrev_attr(DW_AT_stmt_list, DW_FORM_sec_offset)
+                .abbrev_attr_null()
+            .abbrev(2, DW_TAG_subprogram, DW_CHILDREN_no)
+                .abbrev_attr(DW_AT_name, DW_FORM_string)
+                .abbrev_attr(DW_AT_linkage_name, DW_FORM_string)
+                .abbrev_attr(DW_AT_declaration, DW_FORM_flag_present)
+                .abbrev_attr_null()
+            .abbrev(3, DW_TAG_subprogram, DW_CHILDREN_no)
+                .abbrev_attr(DW_AT_specification, DW_FORM_ref4)
+                .abbrev_attr(DW_AT_low_pc, DW_FORM_addr)
+                .abbrev_attr(DW_AT_high_pc, DW_FORM_udata)
+                .abbrev_attr_null()
+            .abbrev(4, DW_TAG_subprogram, DW_CHILDREN_no)
+                .abbrev_attr(DW_AT_name, DW_FORM_string)
+                .abbrev_attr(DW_AT_low_pc, DW_FORM_addr)
+                .abbrev_attr(DW_AT_high_pc, DW_FORM_udata)
+                .abbrev_attr_null()
+            .abbrev_null();
+        let abbrev = abbrev.get_contents().unwrap();
+
+        let length = Label::new();
+        let start = Label::new();
+        let header_length = Label::new();
+        let header_start = Label::new();
+        let header_end = Label::new();
+        let end = Label::new();
+        #[rustfmt::skip]
+        let line = Section::with_endian(Endian::Little)
+            .initial_length(Format::Dwarf32, &length, &start)
+            .L16(4)
+            .L32(&header_length)
+            .mark(&header_start)
+            // Minimum instruction length, maximum operations per instruction,
+            // default is_stmt, line base, line range, opcode base.
+            .D8(1).D8(1).D8(1).D8(-5i8 as u8).D8(14).D8(13)
+            .append_bytes(&[0, 1, 1, 1, 1, 0, 0, 0, 1, 0, 0, 1])
+            // Include directories.
+            .append_bytes(b"src\0").D8(0)
+            // Files.
+            .append_bytes(b"a.c\0").uleb(1).uleb(0).uleb(0)
+            .D8(0)
+            .mark(&header_end)
+            .D8(0).uleb(9).D8(DW_LNE_set_address.0).L64(0x1000)
+            .D8(DW_LNS_advance_line.0).sleb(9)
+            .D8(DW_LNS_set_column.0).uleb(5)
+            .D8(DW_LNS_copy.0)
+            .D8(DW_LNS_advance_pc.0).uleb(0x10)
+            .D8(DW_LNS_advance_line.0).sleb(2)
+            .D8(DW_LNS_copy.0)
+            .D8(DW_LNS_advance_pc.0).uleb(0x10)
+            .D8(0).uleb(1).D8(DW_LNE_end_sequence.0)
+            .mark(&end);
+        length.set_const((&end - &start) as u64);
+        header_length.set_const((&header_end - &header_start) as u64);
+        let line = line.get_contents().unwrap();
+
+        let decl = Label::new();
+        #[rustfmt::skip]
+        let entries = Section::with_endian(Endian::Little)
+            .uleb(1)
+                .append_bytes(b"a.c\0")
+                .append_bytes(b"/comp\0")
+                .L64(0x1000)
+                .uleb(0x20)
+                .L32(0)
+            .mark(&decl)
+            .uleb(2).append_bytes(b"foo\0").append_bytes(b"_Z3foov\0")
+            .uleb(3).L32(&decl).L64(0x1000).uleb(0x10)
+            .uleb(4).append_bytes(b"bar\0").L64(0x1010).uleb(0x10)
+            .D8(0);
+        let info = compile_unit(entries);
+
+        let dwarf = Dwarf {
+            debug_line: DebugLine::new(&line, LittleEndian),
+            ..test_dwarf(&abbrev, &info)
+        };
+        let symbolizer = Symbolizer::new(&dwarf);
+        let string = |s: &'static [u8]| Some(EndianSlice::new(s, LittleEndian));
+
+        let foo = symbolizer.lookup(&dwarf, 0x1004).unwrap().unwrap();
+        assert_eq!(foo.name, string(b"foo"));
+        assert_eq!(foo.linkage_name, string(b"_Z3foov"));
+        assert_eq!(foo.file.as_deref(), Some("/comp/src/a.c"));
+        assert_eq!(foo.line, Some(10));
+        assert_eq!(foo.column, Some(5));
+
+        let bar = symbolizer.lookup(&dwarf, 0x101f).unwrap().unwrap();
+        assert_eq!(bar.name, string(b"bar"));
+        assert_eq!(bar.linkage_name, None);
+        assert_eq!(bar.file.as_deref(), Some("/comp/src/a.c"));
+        assert_eq!(bar.line, Some(12));
+        assert_eq!(bar.column, Some(5));
+
+        assert_eq!(symbolizer.lookup(&dwarf, 0x1020), Ok(None));
+    }
+
+    #[test]
+    fn test_path_push() {
+        let mut path = String::new();
+        path_push(&mut path, "/comp");
+        path_push(&mut path, "src");
+        path_push(&mut path, "a.c");
+        assert_eq!(path, "/comp/src/a.c");
+        path_push(&mut path, "/usr/include/b.h");
+        assert_eq!(path, "/usr/include/b.h");
+
+        let mut path = String::from("C:\\comp");
+        path_push(&mut path, "a.c");
+        assert_eq!(path, "C:\\comp\\a.c");
+        path_push(&mut path, "\\\\server\\b.h");
+        assert_eq!(path, "\\\\server\\b.h");
+        path_push(&mut path, "D:\\c.h");
+        assert_eq!(path, "D:\\c.h");
+    }
+}
@@ -15,8 +15,9 @@ use crate::constants;
 use crate::endianity::Endianity;
 use crate::read::abbrev::get_attribute_size;
 use crate::read::{
-    Abbreviation, Abbreviations, AttributeSpecification, DebugAbbrev, DebugStr, EndianSlice, Error,
-    Expression, Reader, ReaderOffset, Result, Section, UnitOffset,
+    is_draft_version, Abbreviation, Abbreviations, AttributeSpecification, ContextError,
+    DebugAbbrev, DebugStr, EndianSlice, Error, ErrorOperation, Expression, Reader, ReaderOffset,
+    Result, ResultIterator, Section, UnitOffset, UnknownFormSize, WarningSink,
 };
 
 impl<T: ReaderOffset> DebugTypesOffset<T> {
@@ -201,6 +202,21 @@ impl<R: Reader> DebugInfoUnitHeadersIter<R> {
             }
         }
     }
+
+    /// Advance the iterator to the next unit header, skipping any units
+    /// that fail to parse.
+    ///
+    /// Errors are reported to `warnings`. If the length of a damaged unit can
+    /// be read, then iteration continues with the following unit, otherwise
+    /// iteration stops.
+    pub fn next_lenient(&mut self, warnings: &mut dyn WarningSink) -> Option<UnitHeader<R>> {
+        next_unit_header_lenient(
+            &mut self.input,
+            &mut self.offset.0,
+            |offset| DebugInfoOffset(offset).into(),
+            warnings,
+        )
+    }
 }
 
 #[cfg(feature = "fallible-iterator")]
@@ -213,6 +229,72 @@ impl<R: Reader> fallible_iterator::FallibleIterator for DebugInfoUnitHeadersIter
     }
 }
 
+impl<R: Reader> DebugInfoUnitHeadersIter<R> {
+    /// Return an adapter that implements `Iterator`.
+    ///
+    /// See [`ResultIterator`] for more information.
+    pub fn iterator(self) -> ResultIterator<Self> {
+        ResultIterator::new(self)
+    }
+}
+
+impl<R: Reader> Iterator for ResultIterator<DebugInfoUnitHeadersIter<R>> {
+    type Item = Result<UnitHeader<R>>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_with(DebugInfoUnitHeadersIter::next)
+    }
+}
+
+/// Parse the next unit header, skipping over any units that fail to parse.
+fn next_unit_header_lenient<R: Reader>(
+    input: &mut R,
+    offset: &mut R::Offset,
+    unit_offset: fn(R::Offset) -> UnitSectionOffset<R::Offset>,
+    warnings: &mut dyn WarningSink,
+) -> Option<UnitHeader<R>> {
+    while !input.is_empty() {
+        let len = input.len();
+        let mut rest = input.clone();
+        let result = parse_unit_header(&mut rest, unit_offset(*offset));
+        match result {
+            Ok(header) => {
+                *offset += len - rest.len();
+                *input = rest;
+                return Some(header);
+            }
+            Err(e) => {
+                let (section, section_offset) = unit_section_location(unit_offset(*offset));
+                warnings.warning(
+                    ContextError::new(e)
+                        .with_operation(ErrorOperation::UnitHeader)
+                        .with_location(section, section_offset.into_u64()),
+                );
+                let mut rest = input.clone();
+                let skipped = rest
+                    .read_initial_length()
+                    .and_then(|(unit_length, _)| rest.skip(unit_length));
+                if skipped.is_err() {
+                    input.empty();
+                    break;
+                }
+                *offset += len - rest.len();
+                *input = rest;
+            }
+        }
+    }
+    None
+}
+
+/// Return the section and section offset for a unit offset.
+fn unit_section_location<T>(offset: UnitSectionOffset<T>) -> (SectionId, T) {
+    match offset {
+        UnitSectionOffset::DebugInfoOffset(o) => (SectionId::DebugInfo, o.0),
+        UnitSectionOffset::DebugTypesOffset(o) => (SectionId::DebugTypes, o.0),
+    }
+}
+
 /// Parse the unit type from the unit header.
 fn parse_unit_type<R: Reader>(input: &mut R) -> Result<constants::DwUt> {
     let val = input.read_u8()?;
@@ -239,6 +321,7 @@ pub(crate) fn parse_debug_info_offset<R: Reader>(
 /// specific data carried in the header (e.g. the type
 /// signature/type offset of a type unit).
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum UnitType<Offset>
 where
     Offset: ReaderOffset,
@@ -301,6 +384,7 @@ where
 /// The common fields for the headers of compilation units and
 /// type units.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct UnitHeader<R, Offset = <R as Reader>::Offset>
 where
     R: Reader<Offset = Offset>,
@@ -312,6 +396,8 @@ where
     debug_abbrev_offset: DebugAbbrevOffset<Offset>,
     unit_offset: UnitSectionOffset<Offset>,
     entries_buf: R,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    max_die_depth: Option<usize>,
 }
 
 /// Static methods.
@@ -336,6 +422,7 @@ where
             debug_abbrev_offset,
             unit_offset,
             entries_buf,
+            max_die_depth: None,
         }
     }
 }
@@ -351,6 +438,33 @@ where
         self.unit_offset
     }
 
+    /// Wrap an error that occurred while parsing this unit, adding the
+    /// section and offset of the unit header.
+    pub fn error_context(&self, operation: ErrorOperation, error: Error) -> ContextError {
+        self.context(Offset::from_u8(0), operation, error)
+    }
+
+    /// Wrap an error that occurred while parsing the entry at the given
+    /// offset in this unit, adding the section and offset of the entry.
+    pub fn entry_error_context(
+        &self,
+        offset: UnitOffset<Offset>,
+        operation: ErrorOperation,
+        error: Error,
+    ) -> ContextError {
+        self.context(offset.0, operation, error)
+    }
+
+    fn context(&self, offset: Offset, operation: ErrorOperation, error: Error) -> ContextError {
+        let (section, unit_offset) = unit_section_location(self.unit_offset);
+        let error = ContextError::new(error).with_operation(operation);
+        // The offset is only unknown if the unit and entry offsets are invalid.
+        match unit_offset.into_u64().checked_add(offset.into_u64()) {
+            Some(offset) => error.with_location(section, offset),
+            None => error,
+        }
+    }
+
     /// Return the serialized size of the common unit header for the given
     /// DWARF format.
     pub fn size_of_header(&self) -> usize {
@@ -358,7 +472,7 @@ where
         let version_size = 2;
         let debug_abbrev_offset_size = self.encoding.format.word_size() as usize;
         let address_size_size = 1;
-        let unit_type_size = if self.encoding.version == 5 { 1 } else { 0 };
+        let unit_type_size = if self.encoding.version >= 5 { 1 } else { 0 };
         let type_specific_size = match self.unit_type {
             UnitType::Compilation | UnitType::Partial => 0,
             UnitType::Type { .. } | UnitType::SplitType { .. } => {
@@ -486,6 +600,12 @@ where
         entry.ok_or(Error::NoEntryAtGivenOffset)
     }
 
+    /// Set the maximum depth of the DIE tree for the cursors and trees
+    /// returned by this header.
+    pub(crate) fn set_max_die_depth(&mut self, max_die_depth: Option<usize>) {
+        self.max_die_depth = max_die_depth;
+    }
+
     /// Navigate this unit's `DebuggingInformationEntry`s.
     pub fn entries<'me, 'abbrev>(
         &'me self,
@@ -497,6 +617,8 @@ where
             abbreviations,
             cached_current: None,
             delta_depth: 0,
+            depth: 0,
+            max_depth: self.max_die_depth,
         }
     }
 
@@ -514,6 +636,8 @@ where
             abbreviations,
             cached_current: None,
             delta_depth: 0,
+            depth: 0,
+            max_depth: self.max_die_depth,
         })
     }
 
@@ -528,7 +652,9 @@ where
             Some(offset) => self.range_from(offset..)?,
             None => self.entries_buf.clone(),
         };
-        Ok(EntriesTree::new(input, self, abbreviations))
+        let mut tree = EntriesTree::new(input, self, abbreviations);
+        tree.set_max_depth(self.max_die_depth);
+        Ok(tree)
     }
 
     /// Read the raw data that defines the Debugging Information Entries.
@@ -546,6 +672,7 @@ where
             unit: self,
             abbreviations,
             depth: 0,
+            max_depth: self.max_die_depth,
         })
     }
 
@@ -582,7 +709,7 @@ where
             UnitSectionOffset::DebugInfoOffset(_) => constants::DW_UT_compile,
             UnitSectionOffset::DebugTypesOffset(_) => constants::DW_UT_type,
         };
-    } else if version == 5 {
+    } else if version == 5 || is_draft_version(version) {
         unit_type = parse_unit_type(&mut rest)?;
         address_size = rest.read_u8()?;
         abbrev_offset = parse_debug_abbrev_offset(&mut rest, format)?;
@@ -929,6 +1056,7 @@ where
 // for their data.  This gives better code generation in `parse_attribute`.
 #[repr(u64)]
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum AttributeValue<R, Offset = <R as Reader>::Offset>
 where
     R: Reader<Offset = Offset>,
@@ -1102,11 +1230,30 @@ where
     /// An implementation-defined identifier uniquely identifying a compilation
     /// unit.
     DwoId(DwoId),
+
+    /// The raw bytes of a value with a form that is not known to `gimli`.
+    ///
+    /// This is only used if `DebugAbbrev::set_unknown_form_size` specified
+    /// the size of the form.
+    Unknown {
+        /// The form of the value.
+        form: constants::DwForm,
+        /// The raw bytes of the value.
+        bytes: R,
+    },
 }
 
 /// An attribute in a `DebuggingInformationEntry`, consisting of a name and
 /// associated value.
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "R: serde::Serialize, R::Offset: serde::Serialize",
+        deserialize = "R: serde::Deserialize<'de>, R::Offset: serde::Deserialize<'de>"
+    ))
+)]
 pub struct Attribute<R: Reader> {
     name: constants::DwAt,
     value: AttributeValue<R>,
@@ -1944,6 +2091,23 @@ fn length_uleb128_value<R: Reader>(input: &mut R) -> Result<R> {
     input.split(len)
 }
 
+fn unknown_form_value<R: Reader>(input: &mut R, size: UnknownFormSize) -> Result<R> {
+    match size {
+        UnknownFormSize::Fixed(size) => input.split(R::Offset::from_u8(size)),
+        UnknownFormSize::Leb128 => {
+            let mut rest = input.clone();
+            rest.skip_leb128()?;
+            let len = rest.offset_from(input);
+            input.split(len)
+        }
+        UnknownFormSize::Block1 => length_u8_value(input),
+        UnknownFormSize::Block2 => length_u16_value(input),
+        UnknownFormSize::Block4 => length_u32_value(input),
+        UnknownFormSize::Block => length_uleb128_value(input),
+        UnknownFormSize::String => input.read_null_terminated_slice(),
+    }
+}
+
 // Return true if the given `name` can be a section offset in DWARF version 2/3.
 // This is required to correctly handle relocations.
 fn allow_section_offset(name: constants::DwAt, version: u16) -> bool {
@@ -2179,9 +2343,15 @@ pub(crate) fn parse_attribute<R: Reader>(
                 let index = input.read_uleb128().and_then(R::Offset::from_u64)?;
                 AttributeValue::DebugRngListsIndex(DebugRngListsIndex(index))
             }
-            _ => {
-                return Err(Error::UnknownForm);
-            }
+            _ => match spec.unknown_form_size() {
+                // The size only applies to the form in the abbreviation, not to
+                // a form read from `DW_FORM_indirect`.
+                Some(size) if form == spec.form() => {
+                    let bytes = unknown_form_value(input, size)?;
+                    AttributeValue::Unknown { form, bytes }
+                }
+                _ => return Err(Error::UnknownForm),
+            },
         };
         let attr = Attribute {
             name: spec.name(),
@@ -2245,9 +2415,12 @@ pub(crate) fn skip_attributes<R: Reader>(
                 | constants::DW_FORM_rnglistx => {
                     input.skip_leb128()?;
                 }
-                _ => {
-                    return Err(Error::UnknownForm);
-                }
+                _ => match spec.unknown_form_size() {
+                    Some(size) if form == spec.form() => {
+                        unknown_form_value(input, size)?;
+                    }
+                    _ => return Err(Error::UnknownForm),
+                },
             };
             break;
         }
@@ -2311,6 +2484,25 @@ impl<'abbrev, 'entry, 'unit, R: Reader> AttrsIter<'abbrev, 'entry, 'unit, R> {
             }
         }
     }
+
+    /// Advance the iterator and return the next attribute, adding the
+    /// location of the entry to any error.
+    ///
+    /// This is the same as `next`, except that an error is returned as a
+    /// `ContextError` for the attribute that failed to parse.
+    pub fn next_with_context(
+        &mut self,
+    ) -> ::core::result::Result<Option<Attribute<R>>, ContextError> {
+        let operation = match self.attributes.first() {
+            Some(spec) => ErrorOperation::Attribute(spec.name()),
+            None => ErrorOperation::Entry,
+        };
+        self.next().map_err(|e| {
+            self.entry
+                .unit
+                .entry_error_context(self.entry.offset, operation, e)
+        })
+    }
 }
 
 #[cfg(feature = "fallible-iterator")]
@@ -2325,6 +2517,26 @@ impl<'abbrev, 'entry, 'unit, R: Reader> fallible_iterator::FallibleIterator
     }
 }
 
+impl<'abbrev, 'entry, 'unit, R: Reader> AttrsIter<'abbrev, 'entry, 'unit, R> {
+    /// Return an adapter that implements `Iterator`.
+    ///
+    /// See [`ResultIterator`] for more information.
+    pub fn iterator(self) -> ResultIterator<Self> {
+        ResultIterator::new(self)
+    }
+}
+
+impl<'abbrev, 'entry, 'unit, R: Reader> Iterator
+    for ResultIterator<AttrsIter<'abbrev, 'entry, 'unit, R>>
+{
+    type Item = Result<Attribute<R>>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_with(AttrsIter::next)
+    }
+}
+
 /// A raw reader of the data that defines the Debugging Information Entries.
 ///
 /// `EntriesRaw` provides primitives to read the components of Debugging Information
@@ -2386,9 +2598,19 @@ where
     unit: &'unit UnitHeader<R>,
     abbreviations: &'abbrev Abbreviations,
     depth: isize,
+    max_depth: Option<usize>,
 }
 
 impl<'abbrev, 'unit, R: Reader> EntriesRaw<'abbrev, 'unit, R> {
+    /// Set the maximum depth of the entries that may be read.
+    ///
+    /// Reading an entry that is nested deeper than this fails with
+    /// `Error::EntryTooDeep`. The first entry read is at depth 0.
+    /// `None` means there is no limit, which is the default.
+    pub fn set_max_depth(&mut self, max_depth: Option<usize>) {
+        self.max_depth = max_depth;
+    }
+
     /// Return true if there is no more input.
     #[inline]
     pub fn is_empty(&self) -> bool {
@@ -2426,6 +2648,9 @@ impl<'abbrev, 'unit, R: Reader> EntriesRaw<'abbrev, 'unit, R> {
             .abbreviations
             .get(code)
             .ok_or(Error::UnknownAbbreviation)?;
+        if exceeds_depth(self.depth, self.max_depth) {
+            return Err(Error::EntryTooDeep);
+        }
         if abbrev.has_children() {
             self.depth += 1;
         }
@@ -2443,6 +2668,61 @@ impl<'abbrev, 'unit, R: Reader> EntriesRaw<'abbrev, 'unit, R> {
     pub fn skip_attributes(&mut self, specs: &[AttributeSpecification]) -> Result<()> {
         skip_attributes(&mut self.input, self.unit.encoding(), specs)
     }
+
+    /// Skip the attributes and children of an entry.
+    ///
+    /// `abbrev` must be the abbreviation that was just returned by `read_abbreviation`,
+    /// and none of its attributes may have been read yet. After this returns, the
+    /// next entry read will be the sibling of the skipped entry, or the null entry
+    /// that terminates the list of siblings.
+    ///
+    /// This uses the `DW_AT_sibling` attribute to jump over the children of entries
+    /// that have it, and otherwise skips each child entry in turn.
+    pub fn skip_subtree(&mut self, abbrev: &Abbreviation) -> Result<()> {
+        let depth = if abbrev.has_children() {
+            self.depth - 1
+        } else {
+            self.depth
+        };
+        let mut abbrev = Some(abbrev);
+        loop {
+            if let Some(abbrev) = abbrev {
+                self.skip_entry(abbrev)?;
+            }
+            if self.depth <= depth {
+                return Ok(());
+            }
+            abbrev = self.read_abbreviation()?;
+        }
+    }
+
+    /// Skip the attributes of an entry, and its children if it has a valid
+    /// `DW_AT_sibling` attribute.
+    fn skip_entry(&mut self, abbrev: &Abbreviation) -> Result<()> {
+        let specs = abbrev.attributes();
+        if !abbrev.has_children() {
+            return self.skip_attributes(specs);
+        }
+        let index = match specs
+            .iter()
+            .position(|spec| spec.name() == constants::DW_AT_sibling)
+        {
+            Some(index) => index,
+            None => return self.skip_attributes(specs),
+        };
+        self.skip_attributes(&specs[..index])?;
+        let sibling = self.read_attribute(specs[index])?;
+        self.skip_attributes(&specs[index + 1..])?;
+        if let AttributeValue::UnitRef(offset) = sibling.value() {
+            if offset > self.next_offset() {
+                if let Ok(input) = self.unit.range_from(offset..) {
+                    self.input = input;
+                    self.depth -= 1;
+                }
+            }
+        }
+        Ok(())
+    }
 }
 
 /// A cursor into the Debugging Information Entries tree for a compilation unit.
@@ -2468,9 +2748,20 @@ where
     abbreviations: &'abbrev Abbreviations,
     cached_current: Option<DebuggingInformationEntry<'abbrev, 'unit, R>>,
     delta_depth: isize,
+    depth: isize,
+    max_depth: Option<usize>,
 }
 
 impl<'abbrev, 'unit, R: Reader> EntriesCursor<'abbrev, 'unit, R> {
+    /// Set the maximum depth of the entries that the cursor may move to.
+    ///
+    /// Moving to an entry that is nested deeper than this fails with
+    /// `Error::EntryTooDeep`. The first entry is at depth 0.
+    /// `None` means there is no limit, which is the default.
+    pub fn set_max_depth(&mut self, max_depth: Option<usize>) {
+        self.max_depth = max_depth;
+    }
+
     /// Get a reference to the entry that the cursor is currently pointing to.
     ///
     /// If the cursor is not pointing at an entry, or if the current entry is a
@@ -2495,13 +2786,22 @@ impl<'abbrev, 'unit, R: Reader> EntriesCursor<'abbrev, 'unit, R> {
             return Ok(None);
         }
 
+        let depth = self.depth + self.delta_depth;
         match DebuggingInformationEntry::parse(&mut self.input, self.unit, self.abbreviations) {
+            Ok(Some(_)) if exceeds_depth(depth, self.max_depth) => {
+                self.input.empty();
+                self.delta_depth = 0;
+                self.cached_current = None;
+                Err(Error::EntryTooDeep)
+            }
             Ok(Some(entry)) => {
+                self.depth = depth;
                 self.delta_depth = entry.has_children() as isize;
                 self.cached_current = Some(entry);
                 Ok(Some(()))
             }
             Ok(None) => {
+                self.depth = depth;
                 self.delta_depth = -1;
                 self.cached_current = None;
                 Ok(Some(()))
@@ -2651,6 +2951,98 @@ impl<'abbrev, 'unit, R: Reader> EntriesCursor<'abbrev, 'unit, R> {
         }
     }
 
+    /// Move the cursor to the next DIE in the tree in DFS order, stopping
+    /// at damaged entries instead of returning an error.
+    ///
+    /// This is the same as `next_dfs`, except that if an entry fails to parse
+    /// then the error is reported to `warnings` and `None` is returned. The
+    /// entries before the damaged entry are still returned, but since the
+    /// position of the following entries is unknown, the remaining entries in
+    /// the unit are skipped.
+    pub fn next_dfs_lenient(
+        &mut self,
+        warnings: &mut dyn WarningSink,
+    ) -> Option<(isize, &DebuggingInformationEntry<'abbrev, 'unit, R>)> {
+        let mut delta_depth = self.delta_depth;
+        loop {
+            self.next_entry_lenient(warnings)?;
+            if self.cached_current.is_some() {
+                return self
+                    .cached_current
+                    .as_ref()
+                    .map(|entry| (delta_depth, entry));
+            }
+
+            // next_entry_lenient() read a null entry.
+            delta_depth += self.delta_depth;
+        }
+    }
+
+    fn next_entry_lenient(&mut self, warnings: &mut dyn WarningSink) -> Option<()> {
+        match self.next_entry_with_context() {
+            Ok(next) => next,
+            Err(e) => {
+                warnings.warning(e);
+                None
+            }
+        }
+    }
+
+    /// Move the cursor to the next DIE in the tree in DFS order, adding the
+    /// location of the entry to any error.
+    ///
+    /// This is the same as `next_dfs`, except that an error is returned as a
+    /// `ContextError` containing the offset of the entry that failed to parse.
+    pub fn next_dfs_with_context(
+        &mut self,
+    ) -> ::core::result::Result<
+        Option<(isize, &DebuggingInformationEntry<'abbrev, 'unit, R>)>,
+        ContextError,
+    > {
+        let mut delta_depth = self.delta_depth;
+        loop {
+            if self.next_entry_with_context()?.is_some() {
+                if let Some(ref entry) = self.cached_current {
+                    return Ok(Some((delta_depth, entry)));
+                }
+
+                // next_entry_with_context() read a null entry.
+                delta_depth += self.delta_depth;
+            } else {
+                return Ok(None);
+            }
+        }
+    }
+
+    fn next_entry_with_context(&mut self) -> ::core::result::Result<Option<()>, ContextError> {
+        if let Some(ref current) = self.cached_current {
+            match current.after_attrs() {
+                Ok(input) => self.input = input,
+                Err(e) => {
+                    let error =
+                        self.unit
+                            .entry_error_context(current.offset(), ErrorOperation::Entry, e);
+                    self.input.empty();
+                    self.delta_depth = 0;
+                    self.cached_current = None;
+                    return Err(error);
+                }
+            }
+            self.cached_current = None;
+        }
+
+        if self.input.is_empty() {
+            self.delta_depth = 0;
+            return Ok(None);
+        }
+        let offset =
+            UnitOffset(self.unit.header_size() + self.input.offset_from(&self.unit.entries_buf));
+        self.next_entry().map_err(|e| {
+            self.unit
+                .entry_error_context(offset, ErrorOperation::Entry, e)
+        })
+    }
+
     /// Move the cursor to the next sibling DIE of the current one.
     ///
     /// Returns `Ok(Some(entry))` when the cursor has been moved to
@@ -2776,6 +3168,7 @@ impl<'abbrev, 'unit, R: Reader> EntriesCursor<'abbrev, 'unit, R> {
                     // to it (which keeps us at the same depth).
                     self.input = sibling_input;
                     self.cached_current = None;
+                    self.delta_depth = 0;
                 } else {
                     // This entry has children, so the next entry is
                     // down one level.
@@ -2802,6 +3195,30 @@ impl<'abbrev, 'unit, R: Reader> EntriesCursor<'abbrev, 'unit, R> {
     }
 }
 
+impl<'abbrev, 'unit, R: Reader> EntriesCursor<'abbrev, 'unit, R> {
+    /// Return an adapter that implements `Iterator`.
+    ///
+    /// The adapter traverses the entries in DFS order, in the same way as `next_dfs`,
+    /// and yields a copy of each entry along with its delta depth.
+    ///
+    /// See [`ResultIterator`] for more information.
+    pub fn iterator(self) -> ResultIterator<Self> {
+        ResultIterator::new(self)
+    }
+}
+
+impl<'abbrev, 'unit, R: Reader> Iterator for ResultIterator<EntriesCursor<'abbrev, 'unit, R>> {
+    type Item = Result<(isize, DebuggingInformationEntry<'abbrev, 'unit, R>)>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_with(|cursor| {
+            let entry = cursor.next_dfs()?;
+            Ok(entry.map(|(delta_depth, entry)| (delta_depth, entry.clone())))
+        })
+    }
+}
+
 /// The state information for a tree view of the Debugging Information Entries.
 ///
 /// The `EntriesTree` can be used to recursively iterate through the DIE
@@ -2853,6 +3270,7 @@ where
     input: R,
     entry: Option<DebuggingInformationEntry<'abbrev, 'unit, R>>,
     depth: isize,
+    max_depth: Option<usize>,
 }
 
 impl<'abbrev, 'unit, R: Reader> EntriesTree<'abbrev, 'unit, R> {
@@ -2865,9 +3283,19 @@ impl<'abbrev, 'unit, R: Reader> EntriesTree<'abbrev, 'unit, R> {
             input,
             entry: None,
             depth: 0,
+            max_depth: None,
         }
     }
 
+    /// Set the maximum depth of the nodes in the tree.
+    ///
+    /// Moving to a node that is nested deeper than this fails with
+    /// `Error::EntryTooDeep`. The root node is at depth 0.
+    /// `None` means there is no limit, which is the default.
+    pub fn set_max_depth(&mut self, max_depth: Option<usize>) {
+        self.max_depth = max_depth;
+    }
+
     /// Returns the root node of the tree.
     pub fn root<'me>(&'me mut self) -> Result<EntriesTreeNode<'abbrev, 'unit, 'me, R>> {
         self.input = self.root.clone();
@@ -2910,6 +3338,11 @@ impl<'abbrev, 'unit, R: Reader> EntriesTree<'abbrev, 'unit, R> {
                 self.unit,
                 self.abbreviations,
             ) {
+                Ok(Some(_)) if exceeds_depth(self.depth, self.max_depth) => {
+                    self.input.empty();
+                    self.entry = None;
+                    Err(Error::EntryTooDeep)
+                }
                 Ok(entry) => {
                     self.entry = entry;
                     Ok(self.entry.is_some())
@@ -2970,6 +3403,15 @@ impl<'abbrev, 'unit, R: Reader> EntriesTree<'abbrev, 'unit, R> {
     }
 }
 
+/// Return true if an entry at `depth` is deeper than `max_depth`.
+#[inline]
+fn exceeds_depth(depth: isize, max_depth: Option<usize>) -> bool {
+    match max_depth {
+        Some(max_depth) => depth > 0 && depth as usize > max_depth,
+        None => false,
+    }
+}
+
 /// A node in the Debugging Information Entry tree.
 ///
 /// The root node of a tree can be obtained
@@ -3140,6 +3582,13 @@ impl<R: Reader> DebugTypes<R> {
             offset: DebugTypesOffset(R::Offset::from_u8(0)),
         }
     }
+
+    /// Get the UnitHeader located at offset from this .debug_types section.
+    pub fn header_from_offset(&self, offset: DebugTypesOffset<R::Offset>) -> Result<UnitHeader<R>> {
+        let input = &mut self.debug_types_section.clone();
+        input.skip(offset.0)?;
+        parse_unit_header(input, offset.into())
+    }
 }
 
 /// An iterator over the type-units of this `.debug_types` section.
@@ -3172,6 +3621,21 @@ impl<R: Reader> DebugTypesUnitHeadersIter<R> {
             }
         }
     }
+
+    /// Advance the iterator to the next type unit header, skipping any units
+    /// that fail to parse.
+    ///
+    /// Errors are reported to `warnings`. If the length of a damaged unit can
+    /// be read, then iteration continues with the following unit, otherwise
+    /// iteration stops.
+    pub fn next_lenient(&mut self, warnings: &mut dyn WarningSink) -> Option<UnitHeader<R>> {
+        next_unit_header_lenient(
+            &mut self.input,
+            &mut self.offset.0,
+            |offset| DebugTypesOffset(offset).into(),
+            warnings,
+        )
+    }
 }
 
 #[cfg(feature = "fallible-iterator")]
@@ -3184,6 +3648,24 @@ impl<R: Reader> fallible_iterator::FallibleIterator for DebugTypesUnitHeadersIte
     }
 }
 
+impl<R: Reader> DebugTypesUnitHeadersIter<R> {
+    /// Return an adapter that implements `Iterator`.
+    ///
+    /// See [`ResultIterator`] for more information.
+    pub fn iterator(self) -> ResultIterator<Self> {
+        ResultIterator::new(self)
+    }
+}
+
+impl<R: Reader> Iterator for ResultIterator<DebugTypesUnitHeadersIter<R>> {
+    type Item = Result<UnitHeader<R>>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_with(DebugTypesUnitHeadersIter::next)
+    }
+}
+
 #[cfg(test)]
 // Tests require leb128::write.
 #[cfg(feature = "write")]
@@ -3238,7 +3720,7 @@ mod tests {
                     .L16(unit.version())
                     .offset(unit.debug_abbrev_offset.0, unit.format())
                     .D8(unit.address_size()),
-                5 => section
+                5 | 6 => section
                     .mark(&start)
                     .L16(unit.version())
                     .D8(unit.type_().dw_ut().0)
@@ -3431,6 +3913,7 @@ mod tests {
             debug_abbrev_offset: DebugAbbrevOffset(0x0102_0304_0506_0708),
             unit_offset: DebugInfoOffset(0).into(),
             entries_buf: EndianSlice::new(expected_rest, LittleEndian),
+            max_die_depth: None,
         };
         let mut unit32 = UnitHeader {
             encoding: Encoding {
@@ -3443,6 +3926,7 @@ mod tests {
             debug_abbrev_offset: DebugAbbrevOffset(0x0807_0605),
             unit_offset: DebugInfoOffset(0).into(),
             entries_buf: EndianSlice::new(expected_rest, LittleEndian),
+            max_die_depth: None,
         };
         let section = Section::with_endian(Endian::Little)
             .unit(&mut unit64)
@@ -3457,6 +3941,37 @@ mod tests {
         assert_eq!(units.next(), Ok(None));
     }
 
+    #[test]
+    fn test_units_iterator() {
+        let expected_rest = &[1, 2, 3, 4, 5, 6, 7, 8, 9];
+        let mut unit = UnitHeader {
+            encoding: Encoding {
+                format: Format::Dwarf32,
+                version: 4,
+                address_size: 4,
+            },
+            unit_length: 0,
+            unit_type: UnitType::Compilation,
+            debug_abbrev_offset: DebugAbbrevOffset(0x0807_0605),
+            unit_offset: DebugInfoOffset(0).into(),
+            entries_buf: EndianSlice::new(expected_rest, LittleEndian),
+            max_die_depth: None,
+        };
+        let section = Section::with_endian(Endian::Little).unit(&mut unit);
+        let buf = section.get_contents().unwrap();
+
+        let debug_info = DebugInfo::new(&buf, LittleEndian);
+        let units = debug_info.units().iterator().collect::<Vec<_>>();
+        assert_eq!(units, [Ok(unit)]);
+
+        // Truncated header. The error is returned once.
+        let debug_info = DebugInfo::new(&buf[..8], LittleEndian);
+        let mut units = debug_info.units().iterator();
+        assert!(matches!(units.next(), Some(Err(Error::UnexpectedEof(_)))));
+        assert!(units.next().is_none());
+        assert!(units.next().is_none());
+    }
+
     #[test]
     fn test_unit_version_unknown_version() {
         let buf = [0x02, 0x00, 0x00, 0x00, 0xab, 0xcd];
@@ -3502,6 +4017,7 @@ mod tests {
             debug_abbrev_offset: DebugAbbrevOffset(0x0807_0605),
             unit_offset: DebugInfoOffset(0).into(),
             entries_buf: EndianSlice::new(expected_rest, LittleEndian),
+            max_die_depth: None,
         };
         let section = Section::with_endian(Endian::Little)
             .unit(&mut expected_unit)
@@ -3532,6 +4048,7 @@ mod tests {
             debug_abbrev_offset: DebugAbbrevOffset(0x0102_0304_0506_0708),
             unit_offset: DebugInfoOffset(0).into(),
             entries_buf: EndianSlice::new(expected_rest, LittleEndian),
+            max_die_depth: None,
         };
         let section = Section::with_endian(Endian::Little)
             .unit(&mut expected_unit)
@@ -3561,6 +4078,7 @@ mod tests {
             debug_abbrev_offset: DebugAbbrevOffset(0x0807_0605),
             unit_offset: DebugInfoOffset(0).into(),
             entries_buf: EndianSlice::new(expected_rest, LittleEndian),
+            max_die_depth: None,
         };
         let section = Section::with_endian(Endian::Little)
             .unit(&mut expected_unit)
@@ -3575,6 +4093,38 @@ mod tests {
         assert_eq!(*rest, EndianSlice::new(expected_rest, LittleEndian));
     }
 
+    #[test]
+    fn test_parse_v6_draft_unit_header() {
+        let expected_rest = &[1, 2, 3, 4, 5, 6, 7, 8, 9];
+        let encoding = Encoding {
+            format: Format::Dwarf32,
+            version: 6,
+            address_size: 8,
+        };
+        let mut expected_unit = UnitHeader {
+            encoding,
+            unit_length: 0,
+            unit_type: UnitType::Compilation,
+            debug_abbrev_offset: DebugAbbrevOffset(0x0807_0605),
+            unit_offset: DebugInfoOffset(0).into(),
+            entries_buf: EndianSlice::new(expected_rest, LittleEndian),
+            max_die_depth: None,
+        };
+        let section = Section::with_endian(Endian::Little)
+            .unit(&mut expected_unit)
+            .append_bytes(expected_rest);
+        let buf = section.get_contents().unwrap();
+        let rest = &mut EndianSlice::new(&buf, LittleEndian);
+
+        let result = parse_unit_header(rest, DebugInfoOffset(0).into());
+        if cfg!(feature = "dwarf-6-draft") {
+            assert_eq!(result, Ok(expected_unit));
+            assert_eq!(*rest, EndianSlice::new(expected_rest, LittleEndian));
+        } else {
+            assert_eq!(result, Err(Error::UnknownVersion(6)));
+        }
+    }
+
     #[test]
     #[cfg(target_pointer_width = "64")]
     fn test_parse_v5_unit_header_64_ok() {
@@ -3591,6 +4141,7 @@ mod tests {
             debug_abbrev_offset: DebugAbbrevOffset(0x0102_0304_0506_0708),
             unit_offset: DebugInfoOffset(0).into(),
             entries_buf: EndianSlice::new(expected_rest, LittleEndian),
+            max_die_depth: None,
         };
         let section = Section::with_endian(Endian::Little)
             .unit(&mut expected_unit)
@@ -3620,6 +4171,7 @@ mod tests {
             debug_abbrev_offset: DebugAbbrevOffset(0x0807_0605),
             unit_offset: DebugInfoOffset(0).into(),
             entries_buf: EndianSlice::new(expected_rest, LittleEndian),
+            max_die_depth: None,
         };
         let section = Section::with_endian(Endian::Little)
             .unit(&mut expected_unit)
@@ -3650,6 +4202,7 @@ mod tests {
             debug_abbrev_offset: DebugAbbrevOffset(0x0102_0304_0506_0708),
             unit_offset: DebugInfoOffset(0).into(),
             entries_buf: EndianSlice::new(expected_rest, LittleEndian),
+            max_die_depth: None,
         };
         let section = Section::with_endian(Endian::Little)
             .unit(&mut expected_unit)
@@ -3679,6 +4232,7 @@ mod tests {
             debug_abbrev_offset: DebugAbbrevOffset(0x0807_0605),
             unit_offset: DebugInfoOffset(0).into(),
             entries_buf: EndianSlice::new(expected_rest, LittleEndian),
+            max_die_depth: None,
         };
         let section = Section::with_endian(Endian::Little)
             .unit(&mut expected_unit)
@@ -3709,6 +4263,7 @@ mod tests {
             debug_abbrev_offset: DebugAbbrevOffset(0x0102_0304_0506_0708),
             unit_offset: DebugInfoOffset(0).into(),
             entries_buf: EndianSlice::new(expected_rest, LittleEndian),
+            max_die_depth: None,
         };
         let section = Section::with_endian(Endian::Little)
             .unit(&mut expected_unit)
@@ -3738,6 +4293,7 @@ mod tests {
             debug_abbrev_offset: DebugAbbrevOffset(0x0807_0605),
             unit_offset: DebugInfoOffset(0).into(),
             entries_buf: EndianSlice::new(expected_rest, LittleEndian),
+            max_die_depth: None,
         };
         let section = Section::with_endian(Endian::Little)
             .unit(&mut expected_unit)
@@ -3768,6 +4324,7 @@ mod tests {
             debug_abbrev_offset: DebugAbbrevOffset(0x0102_0304_0506_0708),
             unit_offset: DebugInfoOffset(0).into(),
             entries_buf: EndianSlice::new(expected_rest, LittleEndian),
+            max_die_depth: None,
         };
         let section = Section::with_endian(Endian::Little)
             .unit(&mut expected_unit)
@@ -3841,6 +4398,7 @@ mod tests {
             debug_abbrev_offset: DebugAbbrevOffset(0x0807_0605),
             unit_offset: DebugTypesOffset(0).into(),
             entries_buf: EndianSlice::new(expected_rest, LittleEndian),
+            max_die_depth: None,
         };
         let section = Section::with_endian(Endian::Little)
             .unit(&mut expected_unit)
@@ -3874,6 +4432,7 @@ mod tests {
             debug_abbrev_offset: DebugAbbrevOffset(0x0807_0605),
             unit_offset: DebugTypesOffset(0).into(),
             entries_buf: EndianSlice::new(expected_rest, LittleEndian),
+            max_die_depth: None,
         };
         let section = Section::with_endian(Endian::Little)
             .unit(&mut expected_unit)
@@ -3906,6 +4465,7 @@ mod tests {
             debug_abbrev_offset: DebugAbbrevOffset(0x0807_0605),
             unit_offset: DebugInfoOffset(0).into(),
             entries_buf: EndianSlice::new(expected_rest, LittleEndian),
+            max_die_depth: None,
         };
         let section = Section::with_endian(Endian::Little)
             .unit(&mut expected_unit)
@@ -3939,6 +4499,7 @@ mod tests {
             debug_abbrev_offset: DebugAbbrevOffset(0x0807_0605),
             unit_offset: DebugInfoOffset(0).into(),
             entries_buf: EndianSlice::new(expected_rest, LittleEndian),
+            max_die_depth: None,
         };
         let section = Section::with_endian(Endian::Little)
             .unit(&mut expected_unit)
@@ -3971,6 +4532,7 @@ mod tests {
             debug_abbrev_offset: DebugAbbrevOffset(0x0807_0605),
             unit_offset: DebugInfoOffset(0).into(),
             entries_buf: EndianSlice::new(expected_rest, LittleEndian),
+            max_die_depth: None,
         };
         let section = Section::with_endian(Endian::Little)
             .unit(&mut expected_unit)
@@ -4004,6 +4566,7 @@ mod tests {
             debug_abbrev_offset: DebugAbbrevOffset(0x0807_0605),
             unit_offset: DebugInfoOffset(0).into(),
             entries_buf: EndianSlice::new(expected_rest, LittleEndian),
+            max_die_depth: None,
         };
         let section = Section::with_endian(Endian::Little)
             .unit(&mut expected_unit)
@@ -4847,6 +5410,125 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_skip_attributes_indirect() {
+        let encoding = Encoding {
+            format: Format::Dwarf32,
+            version: 4,
+            address_size: 4,
+        };
+        let specs = [
+            AttributeSpecification::new(constants::DW_AT_name, constants::DW_FORM_indirect, None),
+            AttributeSpecification::new(constants::DW_AT_low_pc, constants::DW_FORM_data1, None),
+            AttributeSpecification::new(
+                constants::DW_AT_high_pc,
+                constants::DW_FORM_indirect,
+                None,
+            ),
+        ];
+        let section = Section::with_endian(Endian::Little)
+            .uleb(constants::DW_FORM_string.0.into())
+            .append_bytes(b"foo\0")
+            .D8(1)
+            .uleb(constants::DW_FORM_indirect.0.into())
+            .uleb(constants::DW_FORM_data4.0.into())
+            .L32(2)
+            .D8(0xff);
+        let buf = section.get_contents().unwrap();
+
+        let input = &mut EndianSlice::new(&buf, LittleEndian);
+        skip_attributes(input, encoding, &specs).unwrap();
+        assert_eq!(input.slice(), &[0xff]);
+    }
+
+    #[test]
+    fn test_parse_attribute_unknown_form() {
+        let encoding = Encoding {
+            format: Format::Dwarf32,
+            version: 4,
+            address_size: 4,
+        };
+        let abbrev_section = Section::with_endian(Endian::Little)
+            .uleb(1)
+            .uleb(constants::DW_TAG_subprogram.0.into())
+            .D8(constants::DW_CHILDREN_no.0)
+            .uleb(constants::DW_AT_name.0.into())
+            .uleb(0x1f30)
+            .uleb(constants::DW_AT_low_pc.0.into())
+            .uleb(0x1f31)
+            .uleb(constants::DW_AT_high_pc.0.into())
+            .uleb(0x1f32)
+            .uleb(0)
+            .uleb(0)
+            .uleb(0);
+        let abbrev_buf = abbrev_section.get_contents().unwrap();
+        let section = Section::with_endian(Endian::Little)
+            .D8(2)
+            .append_bytes(b"ab")
+            .D16(0x1234)
+            .uleb(0x1_0000)
+            .D8(0xff);
+        let buf = section.get_contents().unwrap();
+
+        // Without a policy, unknown forms are an error.
+        let debug_abbrev = DebugAbbrev::new(&abbrev_buf, LittleEndian);
+        let abbrevs = debug_abbrev.abbreviations(DebugAbbrevOffset(0)).unwrap();
+        let specs = abbrevs.get(1).unwrap().attributes();
+        assert_eq!(specs[0].unknown_form_size(), None);
+        let input = &mut EndianSlice::new(&buf, LittleEndian);
+        assert_eq!(
+            parse_attribute(input, encoding, specs[0]),
+            Err(Error::UnknownForm)
+        );
+        let input = &mut EndianSlice::new(&buf, LittleEndian);
+        assert_eq!(
+            skip_attributes(input, encoding, specs),
+            Err(Error::UnknownForm)
+        );
+
+        let mut debug_abbrev = DebugAbbrev::new(&abbrev_buf, LittleEndian);
+        debug_abbrev.set_unknown_form_size(|form| match form.0 {
+            0x1f30 => Some(UnknownFormSize::Block1),
+            0x1f31 => Some(UnknownFormSize::Fixed(2)),
+            0x1f32 => Some(UnknownFormSize::Leb128),
+            _ => None,
+        });
+        let abbrevs = debug_abbrev.abbreviations(DebugAbbrevOffset(0)).unwrap();
+        let specs = abbrevs.get(1).unwrap().attributes();
+        assert_eq!(
+            specs[1].unknown_form_size(),
+            Some(UnknownFormSize::Fixed(2))
+        );
+
+        let input = &mut EndianSlice::new(&buf, LittleEndian);
+        let mut values = Vec::new();
+        for spec in specs {
+            values.push(parse_attribute(input, encoding, *spec).unwrap().value());
+        }
+        assert_eq!(input.slice(), &[0xff]);
+        assert_eq!(
+            values,
+            [
+                AttributeValue::Unknown {
+                    form: constants::DwForm(0x1f30),
+                    bytes: EndianSlice::new(&buf[1..3], LittleEndian),
+                },
+                AttributeValue::Unknown {
+                    form: constants::DwForm(0x1f31),
+                    bytes: EndianSlice::new(&buf[3..5], LittleEndian),
+                },
+                AttributeValue::Unknown {
+                    form: constants::DwForm(0x1f32),
+                    bytes: EndianSlice::new(&buf[5..8], LittleEndian),
+                },
+            ]
+        );
+
+        let input = &mut EndianSlice::new(&buf, LittleEndian);
+        skip_attributes(input, encoding, specs).unwrap();
+        assert_eq!(input.slice(), &[0xff]);
+    }
+
     #[test]
     fn test_attrs_iter() {
         let encoding = Encoding {
@@ -5187,6 +5869,7 @@ mod tests {
             debug_abbrev_offset: DebugAbbrevOffset(0),
             unit_offset: DebugInfoOffset(0).into(),
             entries_buf: EndianSlice::new(&entries_buf, LittleEndian),
+            max_die_depth: None,
         };
         let section = Section::with_endian(Endian::Little).unit(&mut unit);
         section.get_contents().unwrap()
@@ -5213,6 +5896,7 @@ mod tests {
             debug_abbrev_offset: DebugAbbrevOffset(0),
             unit_offset: DebugInfoOffset(0).into(),
             entries_buf: EndianSlice::new(&entries_buf, LittleEndian),
+            max_die_depth: None,
         };
         let section = Section::with_endian(Endian::Little).unit(&mut unit);
         let info_buf = &section.get_contents().unwrap();
@@ -5541,6 +6225,7 @@ mod tests {
             debug_abbrev_offset: DebugAbbrevOffset(0),
             unit_offset: DebugInfoOffset(0).into(),
             entries_buf: EndianSlice::new(&[], LittleEndian),
+            max_die_depth: None,
         };
         let header_size = unit.size_of_header();
         let entries_buf = entries_cursor_sibling_entries_buf(header_size);
@@ -5583,6 +6268,7 @@ mod tests {
             debug_abbrev_offset: DebugAbbrevOffset(0),
             unit_offset: DebugTypesOffset(0).into(),
             entries_buf: EndianSlice::new(&[], LittleEndian),
+            max_die_depth: None,
         };
         let header_size = unit.size_of_header();
         let entries_buf = entries_cursor_sibling_entries_buf(header_size);
@@ -5736,6 +6422,7 @@ mod tests {
             debug_abbrev_offset: DebugAbbrevOffset(0),
             unit_offset: DebugInfoOffset(0).into(),
             entries_buf: EndianSlice::new(&[], LittleEndian),
+            max_die_depth: None,
         };
         let header_size = unit.size_of_header();
         let (entries_buf, entry2) = entries_tree_tests_debug_info_buf(header_size);
@@ -5901,6 +6588,7 @@ mod tests {
             debug_abbrev_offset: DebugAbbrevOffset(0),
             unit_offset: DebugInfoOffset(0).into(),
             entries_buf: EndianSlice::new(&entries_buf, LittleEndian),
+            max_die_depth: None,
         };
         let section = Section::with_endian(Endian::Little).unit(&mut unit);
         let info_buf = section.get_contents().unwrap();
@@ -5956,6 +6644,196 @@ mod tests {
         assert!(entries.is_empty());
     }
 
+    #[test]
+    fn test_entries_cursor_next_dfs_lenient() {
+        let encoding = Encoding {
+            format: Format::Dwarf32,
+            version: 4,
+            address_size: 4,
+        };
+        let mut unit = UnitHeader {
+            encoding,
+            unit_length: 0,
+            unit_type: UnitType::Compilation,
+            debug_abbrev_offset: DebugAbbrevOffset(0),
+            unit_offset: DebugInfoOffset(0).into(),
+            entries_buf: EndianSlice::new(&[], LittleEndian),
+            max_die_depth: None,
+        };
+        let header_size = unit.size_of_header();
+        let entries_buf = entries_cursor_sibling_entries_buf(header_size);
+        unit.entries_buf = EndianSlice::new(&entries_buf, LittleEndian);
+        let section = Section::with_endian(Endian::Little).unit(&mut unit);
+        let info_buf = section.get_contents().unwrap();
+        let debug_info = DebugInfo::new(&info_buf, LittleEndian);
+        let unit = debug_info.units().next().unwrap().unwrap();
+
+        let abbrev_buf = entries_cursor_sibling_abbrev_buf();
+        let debug_abbrev = DebugAbbrev::new(&abbrev_buf, LittleEndian);
+        let abbrevs = unit.abbreviations(&debug_abbrev).unwrap();
+
+        let mut warnings = Vec::new();
+        let mut cursor = unit.entries(&abbrevs);
+        let (delta, entry) = cursor.next_dfs_lenient(&mut warnings).unwrap();
+        assert_eq!(delta, 0);
+        assert_entry_name(entry, "001");
+        let (delta, entry) = cursor.next_dfs_lenient(&mut warnings).unwrap();
+        assert_eq!(delta, 1);
+        assert_entry_name(entry, "002");
+        let offset = entry.offset().0 + 6;
+        assert!(warnings.is_empty());
+
+        // The child of "002" uses an invalid abbreviation code.
+        assert!(cursor.next_dfs_lenient(&mut warnings).is_none());
+        assert!(cursor.next_dfs_lenient(&mut warnings).is_none());
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].error(), Error::UnknownAbbreviation);
+        assert_eq!(warnings[0].operation(), Some(ErrorOperation::Entry));
+        assert_eq!(warnings[0].section(), Some(SectionId::DebugInfo));
+        assert_eq!(warnings[0].offset(), Some(offset as u64));
+
+        // The same error is returned with its location by the strict cursor.
+        let mut cursor = unit.entries(&abbrevs);
+        assert!(cursor.next_dfs_with_context().unwrap().is_some());
+        assert!(cursor.next_dfs_with_context().unwrap().is_some());
+        assert_eq!(cursor.next_dfs_with_context().err(), Some(warnings[0]));
+        assert!(cursor.next_dfs_with_context().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_units_next_lenient() {
+        let mut unit1 = UnitHeader {
+            encoding: Encoding {
+                format: Format::Dwarf32,
+                version: 4,
+                address_size: 4,
+            },
+            unit_length: 0,
+            unit_type: UnitType::Compilation,
+            debug_abbrev_offset: DebugAbbrevOffset(0),
+            unit_offset: DebugInfoOffset(0).into(),
+            entries_buf: EndianSlice::new(&[1, 2, 3], LittleEndian),
+            max_die_depth: None,
+        };
+        let mut unit2 = unit1;
+        let section = Section::with_endian(Endian::Little).unit(&mut unit1);
+        // A unit with an unknown version.
+        let bad_offset = section.size();
+        let section = section.L32(3).L16(99).D8(0).unit(&mut unit2);
+        // A truncated unit.
+        let section = section.L32(100).L16(4);
+        let buf = section.get_contents().unwrap();
+
+        let debug_info = DebugInfo::new(&buf, LittleEndian);
+        let mut units = debug_info.units();
+        let mut warnings = Vec::new();
+        assert_eq!(units.next_lenient(&mut warnings), Some(unit1));
+        assert_eq!(units.next_lenient(&mut warnings), Some(unit2));
+        assert_eq!(units.next_lenient(&mut warnings), None);
+        assert_eq!(units.next_lenient(&mut warnings), None);
+
+        assert_eq!(warnings.len(), 2);
+        assert_eq!(warnings[0].error(), Error::UnknownVersion(99));
+        assert_eq!(warnings[0].offset(), Some(bad_offset));
+        assert!(matches!(warnings[1].error(), Error::UnexpectedEof(_)));
+        assert_eq!(warnings[1].operation(), Some(ErrorOperation::UnitHeader));
+
+        // Unit offsets after a skipped unit are correct.
+        assert!(matches!(
+            unit2.offset(),
+            UnitSectionOffset::DebugInfoOffset(o) if o.0 as u64 == bad_offset + 7
+        ));
+    }
+
+    #[test]
+    fn test_entries_raw_skip_subtree() {
+        fn assert_entry<'abbrev, Endian>(
+            entries: &mut EntriesRaw<'abbrev, '_, EndianSlice<'_, Endian>>,
+            name: &str,
+        ) -> &'abbrev Abbreviation
+        where
+            Endian: Endianity,
+        {
+            let abbrev = entries
+                .read_abbreviation()
+                .expect("Should parse abbrev")
+                .expect("Should have abbrev");
+            let mut probe = entries.clone();
+            let attr = probe
+                .read_attribute(abbrev.attributes()[0])
+                .expect("Should parse attribute");
+            assert_eq!(
+                attr.value(),
+                AttributeValue::String(EndianSlice::new(name.as_bytes(), Endian::default()))
+            );
+            abbrev
+        }
+
+        let encoding = Encoding {
+            format: Format::Dwarf32,
+            version: 4,
+            address_size: 4,
+        };
+        let mut unit = UnitHeader {
+            encoding,
+            unit_length: 0,
+            unit_type: UnitType::Compilation,
+            debug_abbrev_offset: DebugAbbrevOffset(0),
+            unit_offset: DebugInfoOffset(0).into(),
+            entries_buf: EndianSlice::new(&[], LittleEndian),
+            max_die_depth: None,
+        };
+        let header_size = unit.size_of_header();
+        let entries_buf = entries_cursor_sibling_entries_buf(header_size);
+        unit.entries_buf = EndianSlice::new(&entries_buf, LittleEndian);
+        let section = Section::with_endian(Endian::Little).unit(&mut unit);
+        let info_buf = section.get_contents().unwrap();
+        let debug_info = DebugInfo::new(&info_buf, LittleEndian);
+
+        let unit = debug_info
+            .units()
+            .next()
+            .expect("should have a unit result")
+            .expect("and it should be ok");
+
+        let abbrev_buf = entries_cursor_sibling_abbrev_buf();
+        let debug_abbrev = DebugAbbrev::new(&abbrev_buf, LittleEndian);
+        let abbrevs = unit
+            .abbreviations(&debug_abbrev)
+            .expect("Should parse abbreviations");
+        let mut entries = unit
+            .entries_raw(&abbrevs, None)
+            .expect("Should have entries");
+
+        let abbrev = assert_entry(&mut entries, "001");
+        entries.skip_attributes(abbrev.attributes()).unwrap();
+        assert_eq!(entries.next_depth(), 1);
+
+        // Valid sibling attribute. The children contain an invalid code.
+        let abbrev = assert_entry(&mut entries, "002");
+        entries.skip_subtree(abbrev).unwrap();
+        assert_eq!(entries.next_depth(), 1);
+
+        // Invalid sibling attribute.
+        let abbrev = assert_entry(&mut entries, "004");
+        entries.skip_subtree(abbrev).unwrap();
+        assert_eq!(entries.next_depth(), 1);
+
+        // Sibling attribute in child only.
+        let abbrev = assert_entry(&mut entries, "006");
+        entries.skip_subtree(abbrev).unwrap();
+        assert_eq!(entries.next_depth(), 1);
+
+        // No sibling attribute.
+        let abbrev = assert_entry(&mut entries, "010");
+        entries.skip_subtree(abbrev).unwrap();
+        assert_eq!(entries.next_depth(), 1);
+
+        assert!(entries.read_abbreviation().unwrap().is_none());
+        assert_eq!(entries.next_depth(), 0);
+        assert!(entries.is_empty());
+    }
+
     #[test]
     fn test_debug_info_offset() {
         let padding = &[0; 10];
@@ -5972,6 +6850,7 @@ mod tests {
             debug_abbrev_offset: DebugAbbrevOffset(0),
             unit_offset: DebugInfoOffset(0).into(),
             entries_buf: EndianSlice::new(entries, LittleEndian),
+            max_die_depth: None,
         };
         Section::with_endian(Endian::Little)
             .append_bytes(padding)
@@ -6024,6 +6903,7 @@ mod tests {
             debug_abbrev_offset: DebugAbbrevOffset(0),
             unit_offset: DebugTypesOffset(0).into(),
             entries_buf: EndianSlice::new(entries, LittleEndian),
+            max_die_depth: None,
         };
         Section::with_endian(Endian::Little)
             .append_bytes(padding)
@@ -6074,6 +6954,7 @@ mod tests {
             debug_abbrev_offset: DebugAbbrevOffset(0),
             unit_offset: DebugInfoOffset(0).into(),
             entries_buf: EndianSlice::new(&[], LittleEndian),
+            max_die_depth: None,
         };
         unit.encoding.format = Format::Dwarf32;
         assert_eq!(unit.length_including_self(), 4);
@@ -6128,4 +7009,101 @@ mod tests {
         let debug_abbrev = DebugAbbrev::new(read_debug_abbrev_section_somehow(), LittleEndian);
         let _abbrevs_for_unit = unit.abbreviations(&debug_abbrev).unwrap();
     }
+
+    #[test]
+    fn test_entry_error_context() {
+        let encoding = Encoding {
+            format: Format::Dwarf32,
+            version: 4,
+            address_size: 4,
+        };
+        let unit = UnitHeader::new(
+            encoding,
+            7,
+            UnitType::Compilation,
+            DebugAbbrevOffset(0),
+            DebugTypesOffset(0x100).into(),
+            EndianSlice::new(&[], LittleEndian),
+        );
+
+        let err = unit.error_context(ErrorOperation::UnitHeader, Error::UnknownVersion(7));
+        assert_eq!(err.error(), Error::UnknownVersion(7));
+        assert_eq!(err.operation(), Some(ErrorOperation::UnitHeader));
+        assert_eq!(err.section(), Some(SectionId::DebugTypes));
+        assert_eq!(err.offset(), Some(0x100));
+
+        let err = unit.entry_error_context(
+            UnitOffset(0x34),
+            ErrorOperation::Attribute(constants::DW_AT_location),
+            Error::UnexpectedNull,
+        );
+        assert_eq!(err.section(), Some(SectionId::DebugTypes));
+        assert_eq!(err.offset(), Some(0x134));
+        assert_eq!(
+            format!("{}", err),
+            "parsing DW_AT_location of DIE at .debug_types+0x134: \
+             Read a null entry before it was expected."
+        );
+        assert_eq!(Error::from(err), Error::UnexpectedNull);
+
+        // An offset that overflows `u64` is omitted.
+        let unit = UnitHeader::new(
+            encoding,
+            7,
+            UnitType::Compilation,
+            DebugAbbrevOffset(0),
+            DebugTypesOffset(usize::MAX).into(),
+            EndianSlice::new(&[], LittleEndian),
+        );
+        let err =
+            unit.entry_error_context(UnitOffset(1), ErrorOperation::Entry, Error::UnexpectedNull);
+        assert_eq!(err.operation(), Some(ErrorOperation::Entry));
+        assert_eq!(err.offset(), (usize::MAX as u64).checked_add(1));
+    }
+
+    #[test]
+    #[cfg(all(feature = "serde", feature = "endian-reader"))]
+    fn test_serde() {
+        use crate::read::EndianRcSlice;
+        use alloc::rc::Rc;
+
+        type R = EndianRcSlice<LittleEndian>;
+        let reader = |buf: &[u8]| R::new(Rc::from(buf), LittleEndian);
+
+        let header = UnitHeader {
+            encoding: Encoding {
+                format: Format::Dwarf32,
+                version: 4,
+                address_size: 8,
+            },
+            unit_length: 0x10,
+            unit_type: UnitType::Type {
+                type_signature: DebugTypeSignature(0xdead_beef_dead_beef),
+                type_offset: UnitOffset(0x17),
+            },
+            debug_abbrev_offset: DebugAbbrevOffset(0x20),
+            unit_offset: DebugTypesOffset(0x30).into(),
+            entries_buf: reader(&[1, 2, 3]),
+            max_die_depth: None,
+        };
+        let json = serde_json::to_string(&header).unwrap();
+        assert_eq!(
+            serde_json::from_str::<UnitHeader<R>>(&json).unwrap(),
+            header
+        );
+
+        let values: [AttributeValue<R>; 6] = [
+            AttributeValue::Addr(0x1234),
+            AttributeValue::Block(reader(&[4, 5, 6])),
+            AttributeValue::Exprloc(Expression(reader(&[0x9c]))),
+            AttributeValue::UnitRef(UnitOffset(0x20)),
+            AttributeValue::DebugStrRef(DebugStrOffset(0x40)),
+            AttributeValue::String(reader(b"name")),
+        ];
+        let json = serde_json::to_string(&values).unwrap();
+        assert_eq!(
+            serde_json::from_str::<[AttributeValue<R>; 6]>(&json).unwrap(),
+            values
+        );
+    }
 }
@@ -0,0 +1,310 @@
+use alloc::collections::btree_map::{self, BTreeMap};
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::common::UnitSectionOffset;
+use crate::read::symbolize::{file_path, path_push};
+use crate::read::unit_map::unit_error;
+use crate::read::{ContextError, Dwarf, Reader, ReaderOffset, Result, Unit, UnitType};
+
+/// A reference to a source file by a unit, found by a [`UnitFileMap`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnitFileEntry<T = usize> {
+    /// The offset of the unit header.
+    pub unit_offset: UnitSectionOffset<T>,
+    /// The index of the file in the line program of the unit.
+    ///
+    /// This is `None` if the file is only referenced by the `DW_AT_name`
+    /// of the unit.
+    pub file_index: Option<u64>,
+}
+
+/// A map from source file paths to the compilation units that reference them.
+///
+/// The paths are built from the `DW_AT_name` and `DW_AT_comp_dir` attributes
+/// of the root entry of each unit, and from the file entries in the line
+/// program header of each unit. Relative paths are joined to the
+/// directory of the file entry and the compilation directory.
+///
+/// Type units are not included.
+///
+/// Units with invalid headers, entries or line programs are skipped, so that
+/// one damaged unit does not prevent lookups in the others. Use
+/// [`UnitFileMap::errors`] to find out which units were skipped.
+///
+/// ```rust,no_run
+/// # fn example<R: gimli::Reader>(dwarf: &gimli::Dwarf<R>) -> gimli::Result<()> {
+/// let map = gimli::UnitFileMap::new(dwarf);
+/// for (path, entry) in map.find_suffix("src/main.rs") {
+///     println!("{}: {:?} {:?}", path, entry.unit_offset, entry.file_index);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct UnitFileMap<T = usize> {
+    files: BTreeMap<String, Vec<UnitFileEntry<T>>>,
+    errors: Vec<ContextError>,
+}
+
+impl<T: ReaderOffset> UnitFileMap<T> {
+    /// Build the map for the compilation units in the `.debug_info` section.
+    pub fn new<R: Reader<Offset = T>>(dwarf: &Dwarf<R>) -> Self {
+        let mut files = BTreeMap::<String, Vec<UnitFileEntry<T>>>::new();
+        let mut errors = Vec::new();
+        let mut headers = dwarf.units();
+        while let Some(header) = headers.next_lenient(&mut errors) {
+            match header.type_() {
+                UnitType::Type { .. } | UnitType::SplitType { .. } => continue,
+                _ => {}
+            }
+            let unit_offset = header.offset();
+            let unit = match dwarf.unit(header.clone()) {
+                Ok(unit) => unit,
+                Err(e) => {
+                    errors.push(unit_error(e, &header));
+                    continue;
+                }
+            };
+
+            // Keep the files that were found before any error.
+            let mut unit_files = BTreeMap::new();
+            if let Err(e) = unit_file_paths(dwarf, &unit, &mut unit_files) {
+                errors.push(unit_error(e, &header));
+            }
+            for (path, file_index) in unit_files {
+                files.entry(path).or_default().push(UnitFileEntry {
+                    unit_offset,
+                    file_index,
+                });
+            }
+        }
+        UnitFileMap { files, errors }
+    }
+
+    /// Return the errors for the units that were skipped.
+    pub fn errors(&self) -> &[ContextError] {
+        &self.errors
+    }
+
+    /// Return the number of distinct file paths in the map.
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    /// Return true if the map contains no file paths.
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Return an iterator over the file paths in the map, in sorted order.
+    pub fn paths(&self) -> impl Iterator<Item = &str> {
+        self.files.keys().map(String::as_str)
+    }
+
+    /// Return the units that reference the file with the given path.
+    ///
+    /// The path must match exactly.
+    pub fn find(&self, path: &str) -> &[UnitFileEntry<T>] {
+        self.files.get(path).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// Return the paths and units for the files whose path ends with the given suffix.
+    ///
+    /// The suffix must match whole path components, so `"main.rs"` matches
+    /// `"/src/main.rs"` but not `"/src/domain.rs"`.
+    pub fn find_suffix<'a>(
+        &'a self,
+        suffix: &'a str,
+    ) -> impl Iterator<Item = (&'a str, &'a UnitFileEntry<T>)> + 'a {
+        self.files
+            .iter()
+            .filter(move |(path, _)| has_path_suffix(path, suffix))
+            .flat_map(|(path, entries)| entries.iter().map(move |entry| (path.as_str(), entry)))
+    }
+
+    /// Return an iterator over the file paths and the units that reference them.
+    pub fn iter(&self) -> btree_map::Iter<'_, String, Vec<UnitFileEntry<T>>> {
+        self.files.iter()
+    }
+}
+
+fn has_path_suffix(path: &str, suffix: &str) -> bool {
+    if suffix.is_empty() || !path.ends_with(suffix) {
+        return false;
+    }
+    let prefix = &path[..path.len() - suffix.len()];
+    prefix.is_empty()
+        || prefix.ends_with('/')
+        || prefix.ends_with('\\')
+        || suffix.starts_with('/')
+        || suffix.starts_with('\\')
+}
+
+/// Add the paths of the files referenced by the unit to `files`.
+fn unit_file_paths<R: Reader>(
+    dwarf: &Dwarf<R>,
+    unit: &Unit<R>,
+    files: &mut BTreeMap<String, Option<u64>>,
+) -> Result<()> {
+    if let Some(program) = &unit.line_program {
+        let header = program.header();
+        // File index 0 is only valid for DWARF version 5.
+        let first = if header.version() >= 5 { 0 } else { 1 };
+        for file_index in first..first + header.file_names().len() as u64 {
+            if let Some(path) = file_path(dwarf, unit, header, file_index)? {
+                files.entry(path).or_insert(Some(file_index));
+            }
+        }
+    }
+    if let Some(name) = &unit.name {
+        let mut path = String::new();
+        if let Some(comp_dir) = &unit.comp_dir {
+            path_push(&mut path, &comp_dir.to_string_lossy()?);
+        }
+        path_push(&mut path, &name.to_string_lossy()?);
+        files.entry(path).or_insert(None);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::{DebugInfoOffset, SectionId};
+    use crate::constants::*;
+    use crate::endianity::LittleEndian;
+    use crate::read::abbrev::tests::AbbrevSectionMethods;
+    use crate::read::{DebugLine, EndianSlice, Error};
+    use crate::test_util::{compile_unit, test_dwarf, GimliSectionMethods};
+    use crate::Format;
+    use test_assembler::{Endian, Label, LabelMaker, Section};
+
+    #[test]
+    fn test_has_path_suffix() {
+        assert!(has_path_suffix("/src/main.rs", "main.rs"));
+        assert!(has_path_suffix("/src/main.rs", "src/main.rs"));
+        assert!(has_path_suffix("/src/main.rs", "/src/main.rs"));
+        assert!(has_path_suffix("c:\\src\\main.rs", "main.rs"));
+        assert!(has_path_suffix("main.rs", "main.rs"));
+        assert!(!has_path_suffix("/src/domain.rs", "main.rs"));
+        assert!(!has_path_suffix("/src/main.rs", ""));
+    }
+
+    #[test]
+    fn test_unit_file_map() {
+        #[rustfmt::skip]
+        let abbrev = Section::with_endian(Endian::Little)
+            .abbrev(1, DW_TAG_compile_unit, DW_CHILDREN_no)
+                .abbrev_attr(DW_AT_name, DW_FORM_string)
+                .abbrev_attr(DW_AT_comp_dir, DW_FORM_string)
+                .abbrev_attr(DW_AT_stmt_list, DW_FORM_sec_offset)
+                .abbrev_attr_null()
+            .abbrev(2, DW_TAG_compile_unit, DW_CHILDREN_no)
+                .abbrev_attr(DW_AT_name, DW_FORM_string)
+                .abbrev_attr(DW_AT_comp_dir, DW_FORM_string)
+                .abbrev_attr_null()
+            .abbrev_null();
+        let abbrev = abbrev.get_contents().unwrap();
+
+        let length = Label::new();
+        let start = Label::new();
+        let header_length = Label::new();
+        let header_start = Label::new();
+        let header_end = Label::new();
+        let end = Label::new();
+        #[rustfmt::skip]
+        let line = Section::with_endian(Endian::Little)
+            .initial_length(Format::Dwarf32, &length, &start)
+            .L16(4)
+            .L32(&header_length)
+            .mark(&header_start)
+            // Minimum instruction length, maximum operations per instruction,
+            // default is_stmt, line base, line range, opcode base.
+            .D8(1).D8(1).D8(1).D8(-5i8 as u8).D8(14).D8(13)
+            .append_bytes(&[0, 1, 1, 1, 1, 0, 0, 0, 1, 0, 0, 1])
+            // Include directories.
+            .append_bytes(b"src\0").D8(0)
+            // Files.
+            .append_bytes(b"main.c\0").uleb(1).uleb(0).uleb(0)
+            .append_bytes(b"/usr/include/stdio.h\0").uleb(0).uleb(0).uleb(0)
+            .D8(0)
+            .mark(&header_end)
+            .mark(&end);
+        length.set_const((&end - &start) as u64);
+        header_length.set_const((&header_end - &header_start) as u64);
+        let line = line.get_contents().unwrap();
+
+        let mut info = Section::with_endian(Endian::Little);
+        let mut unit_offsets = Vec::new();
+        for unit in 0..3 {
+            let entries = Section::with_endian(Endian::Little);
+            let entries = if unit == 0 {
+                entries
+                    .uleb(1)
+                    .append_bytes(b"src/main.c\0")
+                    .append_bytes(b"/work\0")
+                    .L32(0)
+            } else if unit == 1 {
+                entries
+                    .uleb(2)
+                    .append_bytes(b"other.c\0")
+                    .append_bytes(b"/work\0")
+            } else {
+                // An invalid abbreviation code.
+                entries.uleb(9)
+            };
+            unit_offsets.push(info.size() as usize);
+            info = info.append_bytes(&compile_unit(entries));
+        }
+        let info = info.get_contents().unwrap();
+
+        let dwarf: Dwarf<EndianSlice<'_, LittleEndian>> = Dwarf {
+            debug_line: DebugLine::new(&line, LittleEndian),
+            ..test_dwarf(&abbrev, &info)
+        };
+
+        let map = UnitFileMap::new(&dwarf);
+        assert_eq!(
+            map.paths().collect::<Vec<_>>(),
+            ["/usr/include/stdio.h", "/work/other.c", "/work/src/main.c"]
+        );
+        // The invalid unit is skipped.
+        assert_eq!(map.errors().len(), 1);
+        assert_eq!(map.errors()[0].error(), Error::UnknownAbbreviation);
+        assert_eq!(map.errors()[0].section(), Some(SectionId::DebugInfo));
+        assert_eq!(map.errors()[0].offset(), Some(unit_offsets[2] as u64));
+
+        let unit0 = UnitSectionOffset::DebugInfoOffset(DebugInfoOffset(unit_offsets[0]));
+        let unit1 = UnitSectionOffset::DebugInfoOffset(DebugInfoOffset(unit_offsets[1]));
+        // The line program entry is preferred over the unit name.
+        assert_eq!(
+            map.find("/work/src/main.c"),
+            [UnitFileEntry {
+                unit_offset: unit0,
+                file_index: Some(1),
+            }]
+        );
+        assert_eq!(
+            map.find("/work/other.c"),
+            [UnitFileEntry {
+                unit_offset: unit1,
+                file_index: None,
+            }]
+        );
+        assert_eq!(map.find("main.c"), []);
+
+        let found = map.find_suffix("stdio.h").collect::<Vec<_>>();
+        assert_eq!(
+            found,
+            [(
+                "/usr/include/stdio.h",
+                &UnitFileEntry {
+                    unit_offset: unit0,
+                    file_index: Some(2),
+                }
+            )]
+        );
+        assert_eq!(map.find_suffix("c").count(), 0);
+    }
+}
@@ -0,0 +1,457 @@
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+use crate::common::{DebugInfoOffset, SectionId};
+use crate::constants;
+use crate::read::lazy::LazyArc;
+use crate::read::range_index::{RangeIndex, RangeIndexEntry};
+use crate::read::{
+    ContextError, Dwarf, ErrorOperation, Range, Reader, ReaderOffset, Result, Unit, UnitHeader,
+    UnitType,
+};
+
+#[derive(Debug)]
+struct UnitMapEntry<R: Reader> {
+    header: UnitHeader<R>,
+    unit: LazyArc<Unit<R>>,
+    has_aranges: bool,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct UnitMapRange {
+    range: Range,
+    unit: usize,
+}
+
+impl RangeIndexEntry for UnitMapRange {
+    fn range(&self) -> Range {
+        self.range
+    }
+}
+
+/// The ranges of the units that have no entries in `.debug_aranges`.
+#[derive(Debug, Default)]
+struct UnitRanges {
+    ranges: RangeIndex<UnitMapRange>,
+    errors: Vec<ContextError>,
+}
+
+/// A map from addresses to the compilation units that contain them.
+///
+/// The map is built from the `.debug_aranges` section when it contains entries
+/// for a unit. Otherwise, the ranges are determined from the `DW_AT_low_pc`,
+/// `DW_AT_high_pc` and `DW_AT_ranges` attributes of the root entry of the unit,
+/// or of its top level subprograms if the root entry has no ranges. This
+/// requires parsing the unit, so it is delayed until the first lookup.
+///
+/// Ranges that begin at an address matching `Dwarf::tombstone_filter` are ignored.
+///
+/// Units with invalid headers or ranges are skipped, so that one damaged unit
+/// does not prevent lookups in the others. Use [`UnitAddressMap::errors`] to
+/// find out which units were skipped.
+///
+/// Units are parsed on first use and then cached, so that repeated lookups
+/// for addresses in the same unit are cheap.
+///
+/// ```rust,no_run
+/// # fn example<R: gimli::Reader>(dwarf: &gimli::Dwarf<R>) -> gimli::Result<()> {
+/// let map = gimli::UnitAddressMap::new(dwarf);
+/// if let Some((header, unit)) = map.find_unit(dwarf, 0x1234)? {
+///     println!("{:?}", header.offset());
+///     let unit = unit.unit_ref(dwarf);
+///     // ...
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct UnitAddressMap<R: Reader> {
+    units: Vec<UnitMapEntry<R>>,
+    /// The ranges from the `.debug_aranges` section.
+    aranges: RangeIndex<UnitMapRange>,
+    /// The ranges of the other units, which are found on the first lookup.
+    unit_ranges: LazyArc<UnitRanges>,
+    errors: Vec<ContextError>,
+}
+
+impl<R: Reader> UnitAddressMap<R> {
+    /// Build the map for the compilation units in the `.debug_info` section.
+    ///
+    /// Type units are not included.
+    pub fn new(dwarf: &Dwarf<R>) -> Self {
+        let mut errors = Vec::new();
+        let mut units = Vec::new();
+        let mut headers = dwarf.units();
+        while let Some(header) = headers.next_lenient(&mut errors) {
+            match header.type_() {
+                UnitType::Type { .. } | UnitType::SplitType { .. } => continue,
+                _ => {}
+            }
+            units.push(UnitMapEntry {
+                header,
+                unit: LazyArc::default(),
+                has_aranges: false,
+            });
+        }
+
+        // Units are always in offset order, so we can use a binary search.
+        let find = |units: &[UnitMapEntry<R>], offset: DebugInfoOffset<R::Offset>| {
+            units
+                .binary_search_by_key(&Some(offset), |entry| {
+                    entry.header.offset().as_debug_info_offset()
+                })
+                .ok()
+        };
+
+        let mut aranges = Vec::new();
+        let mut headers = dwarf.debug_aranges.headers();
+        loop {
+            let header = match headers.next() {
+                Ok(Some(header)) => header,
+                Ok(None) => break,
+                Err(e) => {
+                    // The length of the set is unknown, so we can't continue.
+                    errors.push(ContextError::new(e));
+                    break;
+                }
+            };
+            let unit = match find(&units, header.debug_info_offset()) {
+                Some(unit) => unit,
+                None => continue,
+            };
+            let address_size = header.encoding().address_size;
+            let mut set = Vec::new();
+            let mut entries = header.entries();
+            let result = (|| {
+                while let Some(entry) = entries.next()? {
+                    units[unit].has_aranges = true;
+                    if dwarf
+                        .tombstone_filter
+                        .is_tombstone(entry.address(), address_size)
+                    {
+                        continue;
+                    }
+                    set.push(UnitMapRange {
+                        range: entry.range(),
+                        unit,
+                    });
+                }
+                Ok(())
+            })();
+            match result {
+                Ok(()) => aranges.append(&mut set),
+                Err(e) => {
+                    // Use the ranges from the unit instead.
+                    units[unit].has_aranges = false;
+                    errors.push(
+                        ContextError::new(e)
+                            .with_location(SectionId::DebugAranges, header.offset().0.into_u64()),
+                    );
+                }
+            }
+        }
+        UnitAddressMap {
+            units,
+            aranges: RangeIndex::new(aranges),
+            unit_ranges: LazyArc::default(),
+            errors,
+        }
+    }
+
+    /// Return the number of units in the map.
+    pub fn len(&self) -> usize {
+        self.units.len()
+    }
+
+    /// Return true if the map contains no units.
+    pub fn is_empty(&self) -> bool {
+        self.units.is_empty()
+    }
+
+    /// Return the errors for the units and address ranges that were skipped.
+    ///
+    /// This parses the units that have no entries in `.debug_aranges` if that
+    /// has not been done by a lookup yet.
+    /// `dwarf` must be the same as the value given to `UnitAddressMap::new`.
+    pub fn errors(&self, dwarf: &Dwarf<R>) -> Vec<ContextError> {
+        let mut errors = self.errors.clone();
+        errors.extend_from_slice(&self.unit_ranges(dwarf).errors);
+        errors
+    }
+
+    /// Return the header of the unit containing the given address.
+    ///
+    /// If multiple units contain the address, then the unit whose range
+    /// starts closest to the address is returned.
+    /// `dwarf` must be the same as the value given to `UnitAddressMap::new`.
+    pub fn find_header(&self, dwarf: &Dwarf<R>, address: u64) -> Option<&UnitHeader<R>> {
+        self.find_index(dwarf, address)
+            .map(|index| &self.units[index].header)
+    }
+
+    /// Return the header and the parsed unit for the unit containing the given address.
+    ///
+    /// The unit is parsed using `dwarf` the first time it is needed, and then cached.
+    /// `dwarf` must be the same as the value given to `UnitAddressMap::new`.
+    #[allow(clippy::type_complexity)]
+    pub fn find_unit(
+        &self,
+        dwarf: &Dwarf<R>,
+        address: u64,
+    ) -> Result<Option<(&UnitHeader<R>, Arc<Unit<R>>)>> {
+        match self.find_index(dwarf, address) {
+            Some(index) => self.unit_at(dwarf, index).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Return the header and the parsed unit for the unit at the given index.
+    pub(crate) fn unit_at(
+        &self,
+        dwarf: &Dwarf<R>,
+        index: usize,
+    ) -> Result<(&UnitHeader<R>, Arc<Unit<R>>)> {
+        let entry = &self.units[index];
+        let unit = entry.unit.get(|| dwarf.unit(entry.header.clone()))?;
+        Ok((&entry.header, unit))
+    }
+
+    /// Return the index of the unit containing the given address.
+    pub(crate) fn find_index(&self, dwarf: &Dwarf<R>, address: u64) -> Option<usize> {
+        let unit_ranges = self.unit_ranges(dwarf);
+        let range = match (self.aranges.find(address), unit_ranges.ranges.find(address)) {
+            (Some(a), Some(b)) if b.range.begin > a.range.begin => b,
+            (Some(a), _) => a,
+            (None, b) => b?,
+        };
+        Some(range.unit)
+    }
+
+    fn unit_ranges(&self, dwarf: &Dwarf<R>) -> Arc<UnitRanges> {
+        // Errors are recorded instead of returned, so this never fails.
+        self.unit_ranges
+            .get(|| Ok::<_, ()>(self.parse_unit_ranges(dwarf)))
+            .unwrap()
+    }
+
+    fn parse_unit_ranges(&self, dwarf: &Dwarf<R>) -> UnitRanges {
+        let mut ranges = Vec::new();
+        let mut errors = Vec::new();
+        for (index, entry) in self.units.iter().enumerate() {
+            if entry.has_aranges {
+                continue;
+            }
+            let unit = match dwarf.unit(entry.header.clone()) {
+                Ok(unit) => unit,
+                Err(e) => {
+                    errors.push(unit_error(e, &entry.header));
+                    continue;
+                }
+            };
+            // Keep the ranges that were found before any error.
+            if let Err(e) = push_unit_ranges(&mut ranges, dwarf, &unit, index) {
+                errors.push(unit_error(e, &entry.header).with_operation(ErrorOperation::RangeList));
+            }
+            // We've done the work of parsing the unit already, so cache it.
+            entry.unit.get(|| Ok::<_, ()>(unit)).ok();
+        }
+        UnitRanges {
+            ranges: RangeIndex::new(ranges),
+            errors,
+        }
+    }
+}
+
+/// Add the location of the unit header to an error for the unit.
+pub(crate) fn unit_error<R: Reader>(
+    error: crate::read::Error,
+    header: &UnitHeader<R>,
+) -> ContextError {
+    let error = ContextError::new(error);
+    match header.offset().as_debug_info_offset() {
+        Some(offset) => error.with_location(SectionId::DebugInfo, offset.0.into_u64()),
+        None => error,
+    }
+}
+
+fn push_unit_ranges<R: Reader>(
+    ranges: &mut Vec<UnitMapRange>,
+    dwarf: &Dwarf<R>,
+    unit: &Unit<R>,
+    index: usize,
+) -> Result<()> {
+    let mut found = false;
+    let mut unit_ranges = dwarf.unit_ranges(unit)?;
+    while let Some(range) = unit_ranges.next()? {
+        found = true;
+        ranges.push(UnitMapRange { range, unit: index });
+    }
+    if !found {
+        // Some producers only give ranges for the subprograms.
+        let mut cursor = unit.entries();
+        // Skip the root.
+        cursor.next_dfs()?;
+        let mut depth = 0;
+        while let Some((delta_depth, child)) = cursor.next_dfs()? {
+            depth += delta_depth;
+            if depth <= 0 {
+                break;
+            }
+            if depth == 1 && child.tag() == constants::DW_TAG_subprogram {
+                let mut child_ranges = dwarf.die_ranges(unit, child)?;
+                while let Some(range) = child_ranges.next()? {
+                    ranges.push(UnitMapRange { range, unit: index });
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::*;
+    use crate::endianity::LittleEndian;
+    use crate::read::abbrev::tests::AbbrevSectionMethods;
+    use crate::read::{DebugAranges, EndianSlice, Error};
+    use crate::test_util::{compile_unit, test_dwarf, GimliSectionMethods};
+    use crate::Format;
+    use test_assembler::{Endian, Label, LabelMaker, Section};
+
+    #[test]
+    fn test_unit_address_map() {
+        #[rustfmt::skip]
+        let abbrev = Section::with_endian(Endian::Little)
+            .abbrev(1, DW_TAG_compile_unit, DW_CHILDREN_yes)
+                .abbrev_attr(DW_AT_name, DW_FORM_string)
+                .abbrev_attr_null()
+            .abbrev(2, DW_TAG_compile_unit, DW_CHILDREN_yes)
+                .abbrev_attr(DW_AT_name, DW_FORM_string)
+                .abbrev_attr(DW_AT_low_pc, DW_FORM_addr)
+                .abbrev_attr(DW_AT_high_pc, DW_FORM_udata)
+                .abbrev_attr_null()
+            .abbrev(3, DW_TAG_subprogram, DW_CHILDREN_no)
+                .abbrev_attr(DW_AT_low_pc, DW_FORM_addr)
+                .abbrev_attr(DW_AT_high_pc, DW_FORM_udata)
+                .abbrev_attr_null()
+            .abbrev_null();
+        let abbrev = abbrev.get_contents().unwrap();
+
+        fn unit(info: Section, code: u64, name: &[u8], ranges: &[(u64, u64)]) -> Section {
+            let mut entries = Section::with_endian(Endian::Little)
+                .uleb(code)
+                .append_bytes(name)
+                .D8(0);
+            let mut ranges = ranges.iter();
+            if code == 2 {
+                let (begin, size) = ranges.next().unwrap();
+                entries = entries.L64(*begin).uleb(*size);
+            }
+            for (begin, size) in ranges {
+                entries = entries.uleb(3).L64(*begin).uleb(*size);
+            }
+            info.append_bytes(&compile_unit(entries.D8(0)))
+        }
+
+        // Unit 0 has aranges, unit 1 has root ranges, unit 2 only has subprogram ranges.
+        // Unit 1 also has aranges, but they are invalid.
+        let bad_unit = Label::new();
+        let unit1 = Label::new();
+        let unit2 = Label::new();
+        let info = Section::with_endian(Endian::Little);
+        let info = unit(info, 1, b"a", &[(0x100, 0x10)]);
+        // A unit with an unknown version is skipped.
+        let info = info.mark(&bad_unit).L32(2).L16(1);
+        let info = info.mark(&unit1);
+        let info = unit(info, 2, b"b", &[(0x200, 0x100)]);
+        let info = info.mark(&unit2);
+        let info = unit(info, 1, b"c", &[(0x280, 0x10), (0x400, 0x10)]);
+        info.start().set_const(0);
+        let info = info.get_contents().unwrap();
+
+        let length = Label::new();
+        let start = Label::new();
+        let end = Label::new();
+        #[rustfmt::skip]
+        let aranges = Section::with_endian(Endian::Little)
+            .initial_length(Format::Dwarf32, &length, &start)
+            .L16(2)
+            .L32(0)
+            .D8(8)
+            .D8(0)
+            .L32(0)
+            .L64(0x1000).L64(0x10)
+            .L64(0x1100).L64(0)
+            .L64(0).L64(0)
+            .mark(&end);
+        length.set_const((&end - &start) as u64);
+        let bad_aranges = Label::new();
+        let length = Label::new();
+        let start = Label::new();
+        let end = Label::new();
+        #[rustfmt::skip]
+        let aranges = aranges
+            .mark(&bad_aranges)
+            .initial_length(Format::Dwarf32, &length, &start)
+            .L16(2)
+            .L32(&unit1)
+            // Unsupported address size.
+            .D8(3)
+            .D8(0)
+            .append_repeated(1, 6)
+            .mark(&end);
+        length.set_const((&end - &start) as u64);
+        aranges.start().set_const(0);
+        let aranges = aranges.get_contents().unwrap();
+
+        let dwarf = Dwarf {
+            debug_aranges: DebugAranges::new(&aranges, LittleEndian),
+            ..test_dwarf(&abbrev, &info)
+        };
+        let map = UnitAddressMap::new(&dwarf);
+        assert_eq!(map.len(), 3);
+
+        let offset = |address| {
+            map.find_header(&dwarf, address)
+                .map(|header| header.offset().as_debug_info_offset().unwrap().0)
+        };
+        // The unit's own ranges are ignored when it has aranges.
+        assert_eq!(offset(0x100), None);
+        assert_eq!(offset(0x1000), Some(0));
+        assert_eq!(offset(0x100f), Some(0));
+        assert_eq!(offset(0x1010), None);
+        assert_eq!(offset(0x1100), None);
+        let unit1 = unit1.value().unwrap() as usize;
+        let unit2 = unit2.value().unwrap() as usize;
+        assert_eq!(offset(0x1ff), None);
+        assert_eq!(offset(0x200), Some(unit1));
+        // Overlapping ranges prefer the closest start.
+        assert_eq!(offset(0x284), Some(unit2));
+        assert_eq!(offset(0x2a0), Some(unit1));
+        assert_eq!(offset(0x2ff), Some(unit1));
+        assert_eq!(offset(0x300), None);
+        assert_eq!(offset(0x404), Some(unit2));
+
+        let errors = map.errors(&dwarf);
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].error(), Error::UnknownVersion(1));
+        assert_eq!(errors[0].section(), Some(SectionId::DebugInfo));
+        assert_eq!(errors[0].offset(), bad_unit.value());
+        assert_eq!(errors[1].error(), Error::UnsupportedAddressSize(3));
+        assert_eq!(errors[1].section(), Some(SectionId::DebugAranges));
+        assert_eq!(errors[1].offset(), bad_aranges.value());
+
+        let (header, unit) = map.find_unit(&dwarf, 0x404).unwrap().unwrap();
+        assert_eq!(
+            header.offset().as_debug_info_offset(),
+            Some(DebugInfoOffset(unit2))
+        );
+        assert_eq!(unit.name, Some(EndianSlice::new(b"c", LittleEndian)));
+        let (_, unit_again) = map.find_unit(&dwarf, 0x284).unwrap().unwrap();
+        assert!(Arc::ptr_eq(&unit, &unit_again));
+        let (_, unit) = map.find_unit(&dwarf, 0x1000).unwrap().unwrap();
+        assert_eq!(unit.name, Some(EndianSlice::new(b"a", LittleEndian)));
+        assert!(map.find_unit(&dwarf, 0).unwrap().is_none());
+    }
+}
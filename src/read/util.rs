@@ -3,11 +3,14 @@ use alloc::boxed::Box;
 #[cfg(feature = "read")]
 use alloc::vec::Vec;
 use core::fmt;
+use core::iter::FusedIterator;
 use core::mem::MaybeUninit;
 use core::ops;
 use core::ptr;
 use core::slice;
 
+use crate::read;
+
 mod sealed {
     /// # Safety
     /// Implementer must not modify the content in storage.
@@ -281,3 +284,71 @@ where
         fmt::Debug::fmt(&**self, f)
     }
 }
+
+/// An adapter that implements `Iterator` for a fallible iterator.
+///
+/// The iterators in this crate return `Result<Option<T>>` from their `next`
+/// method. This adapter instead yields `Result<T>` items, which allows them to
+/// be used with `for` loops and the standard `Iterator` combinators, without
+/// requiring the `fallible-iterator` crate.
+///
+/// The adapter stops iterating after the first error is returned.
+///
+/// Construct a `ResultIterator` using the `iterator` method of the fallible
+/// iterator.
+///
+/// ```rust,no_run
+/// # fn example() -> Result<(), gimli::Error> {
+/// # let debug_info = gimli::DebugInfo::new(&[], gimli::LittleEndian);
+/// let headers = debug_info
+///     .units()
+///     .iterator()
+///     .collect::<Result<Vec<_>, _>>()?;
+/// # unreachable!()
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ResultIterator<I> {
+    inner: I,
+    done: bool,
+}
+
+impl<I> ResultIterator<I> {
+    pub(crate) fn new(inner: I) -> Self {
+        ResultIterator { inner, done: false }
+    }
+
+    /// Return a reference to the underlying fallible iterator.
+    pub fn get_ref(&self) -> &I {
+        &self.inner
+    }
+
+    /// Return the underlying fallible iterator.
+    pub fn into_inner(self) -> I {
+        self.inner
+    }
+
+    /// Advance the fallible iterator using `next`, and convert the result.
+    #[inline]
+    pub(crate) fn next_with<T, F>(&mut self, next: F) -> Option<read::Result<T>>
+    where
+        F: FnOnce(&mut I) -> read::Result<Option<T>>,
+    {
+        if self.done {
+            return None;
+        }
+        match next(&mut self.inner) {
+            Ok(Some(item)) => Some(Ok(item)),
+            Ok(None) => {
+                self.done = true;
+                None
+            }
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+impl<I> FusedIterator for ResultIterator<I> where ResultIterator<I>: Iterator {}
@@ -23,6 +23,7 @@ fn mask_bit_size(addr_mask: u64) -> u32 {
 
 /// The type of an entry on the DWARF stack.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ValueType {
     /// The generic type, which is address-sized and of unspecified sign,
     /// as specified in the DWARF 5 standard, section 2.5.1.
@@ -52,6 +53,7 @@ pub enum ValueType {
 
 /// The value of an entry on the DWARF stack.
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Value {
     /// A generic value, which is address-sized and of unspecified sign.
     Generic(u64),
@@ -0,0 +1,519 @@
+use alloc::vec::Vec;
+
+use crate::constants;
+use crate::read::{
+    AttributeValue, EntriesTreeNode, Error, Reader, Result, ResultIterator, UnitOffset, UnitRef,
+};
+
+/// The value of a discriminant.
+///
+/// Discriminants are encoded as either signed or unsigned LEB128 numbers,
+/// depending on the type of the discriminant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Discriminant {
+    /// The value of a discriminant with an unsigned type.
+    Unsigned(u64),
+    /// The value of a discriminant with a signed type.
+    Signed(i64),
+}
+
+impl Discriminant {
+    fn to_i128(self) -> i128 {
+        match self {
+            Discriminant::Unsigned(value) => i128::from(value),
+            Discriminant::Signed(value) => i128::from(value),
+        }
+    }
+}
+
+/// An item in a `DW_AT_discr_list` attribute.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiscriminantValue {
+    /// A single discriminant value (`DW_DSC_label`).
+    Label(Discriminant),
+    /// An inclusive range of discriminant values (`DW_DSC_range`).
+    Range(Discriminant, Discriminant),
+}
+
+impl DiscriminantValue {
+    /// Return true if the given discriminant matches this item.
+    pub fn contains(&self, value: Discriminant) -> bool {
+        let value = value.to_i128();
+        match *self {
+            DiscriminantValue::Label(label) => label.to_i128() == value,
+            DiscriminantValue::Range(low, high) => {
+                low.to_i128() <= value && value <= high.to_i128()
+            }
+        }
+    }
+}
+
+/// An iterator over the items in a `DW_AT_discr_list` attribute.
+///
+/// Can be [used with
+/// `FallibleIterator`](./index.html#using-with-fallibleiterator).
+#[derive(Debug, Clone)]
+pub struct DiscrListIter<R: Reader> {
+    input: R,
+    signed: bool,
+}
+
+impl<R: Reader> DiscrListIter<R> {
+    /// Construct a new iterator for the block of a `DW_AT_discr_list` attribute.
+    ///
+    /// `encoding` is the `DW_AT_encoding` of the type of the discriminant,
+    /// which determines whether the values are signed.
+    pub fn new(block: R, encoding: constants::DwAte) -> Self {
+        DiscrListIter {
+            input: block,
+            signed: is_signed(encoding),
+        }
+    }
+
+    /// Advance the iterator to the next item.
+    pub fn next(&mut self) -> Result<Option<DiscriminantValue>> {
+        if self.input.is_empty() {
+            return Ok(None);
+        }
+        let result = self.parse();
+        if result.is_err() {
+            self.input.empty();
+        }
+        result.map(Some)
+    }
+
+    /// Return an adapter that implements `Iterator`.
+    ///
+    /// See [`ResultIterator`] for more information.
+    pub fn iterator(self) -> ResultIterator<Self> {
+        ResultIterator::new(self)
+    }
+
+    fn parse(&mut self) -> Result<DiscriminantValue> {
+        let descriptor = constants::DwDsc(self.input.read_u8()?);
+        match descriptor {
+            constants::DW_DSC_label => Ok(DiscriminantValue::Label(self.read_value()?)),
+            constants::DW_DSC_range => {
+                let low = self.read_value()?;
+                let high = self.read_value()?;
+                Ok(DiscriminantValue::Range(low, high))
+            }
+            _ => Err(Error::UnknownDiscriminantDescriptor(descriptor)),
+        }
+    }
+
+    fn read_value(&mut self) -> Result<Discriminant> {
+        if self.signed {
+            self.input.read_sleb128().map(Discriminant::Signed)
+        } else {
+            self.input.read_uleb128().map(Discriminant::Unsigned)
+        }
+    }
+}
+
+#[cfg(feature = "fallible-iterator")]
+impl<R: Reader> fallible_iterator::FallibleIterator for DiscrListIter<R> {
+    type Item = DiscriminantValue;
+    type Error = Error;
+
+    fn next(&mut self) -> ::core::result::Result<Option<Self::Item>, Self::Error> {
+        DiscrListIter::next(self)
+    }
+}
+
+impl<R: Reader> Iterator for ResultIterator<DiscrListIter<R>> {
+    type Item = Result<DiscriminantValue>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_with(DiscrListIter::next)
+    }
+}
+
+/// A variant part of a structure type.
+///
+/// This is parsed from a `DW_TAG_variant_part` entry. Rust enums and Ada
+/// variant records are encoded using these.
+#[derive(Debug, Clone)]
+pub struct VariantPart<R: Reader> {
+    /// The offset of the variant part entry.
+    pub offset: UnitOffset<R::Offset>,
+    /// The member that holds the discriminant.
+    ///
+    /// This is `DW_AT_discr`. It is `None` if the variant part does not
+    /// have a discriminant member, in which case the discriminant type may
+    /// be given by `DW_AT_type` instead.
+    pub discriminant: Option<UnitOffset<R::Offset>>,
+    /// The encoding of the type of the discriminant.
+    ///
+    /// This is `DW_ATE_unsigned` if the type could not be determined.
+    pub encoding: constants::DwAte,
+    /// The variants in this variant part.
+    pub variants: Vec<Variant<R>>,
+}
+
+impl<R: Reader> VariantPart<R> {
+    /// Return the variant that is selected by the given discriminant value.
+    ///
+    /// If no variant matches the value, then the default variant is returned, if any.
+    pub fn find_variant(&self, value: Discriminant) -> Option<&Variant<R>> {
+        self.variants
+            .iter()
+            .find(|variant| variant.values.iter().any(|v| v.contains(value)))
+            .or_else(|| self.variants.iter().find(|variant| variant.is_default()))
+    }
+}
+
+/// A variant within a variant part.
+///
+/// This is parsed from a `DW_TAG_variant` entry.
+#[derive(Debug, Clone)]
+pub struct Variant<R: Reader> {
+    /// The offset of the variant entry.
+    pub offset: UnitOffset<R::Offset>,
+    /// The discriminant values that select this variant.
+    ///
+    /// This is parsed from either `DW_AT_discr_value` or `DW_AT_discr_list`.
+    /// It is empty for the default variant.
+    pub values: Vec<DiscriminantValue>,
+    /// The `DW_TAG_member` entries of this variant.
+    pub members: Vec<UnitOffset<R::Offset>>,
+}
+
+impl<R: Reader> Variant<R> {
+    /// Return true if this is the default variant.
+    pub fn is_default(&self) -> bool {
+        self.values.is_empty()
+    }
+}
+
+impl<'a, R: Reader> UnitRef<'a, R> {
+    /// Return the variant parts of the given structure type.
+    ///
+    /// Only variant parts that are direct children of the entry are returned.
+    pub fn variant_parts(&self, offset: UnitOffset<R::Offset>) -> Result<Vec<VariantPart<R>>> {
+        let mut variant_parts = Vec::new();
+        let mut tree = self.entries_tree(Some(offset))?;
+        let mut children = tree.root()?.children();
+        while let Some(child) = children.next()? {
+            if child.entry().tag() == constants::DW_TAG_variant_part {
+                variant_parts.push(self.variant_part(child)?);
+            }
+        }
+        Ok(variant_parts)
+    }
+
+    fn variant_part(&self, node: EntriesTreeNode<'_, '_, '_, R>) -> Result<VariantPart<R>> {
+        let entry = node.entry();
+        let discriminant = entry
+            .attr_value(constants::DW_AT_discr)?
+            .and_then(|value| self.local_reference(&value));
+        let type_ = match discriminant {
+            Some(member) => self.entry(member)?.attr_value(constants::DW_AT_type)?,
+            None => entry.attr_value(constants::DW_AT_type)?,
+        };
+        let encoding = match type_.and_then(|value| self.local_reference(&value)) {
+            Some(type_) => self.discriminant_encoding(type_)?,
+            None => constants::DW_ATE_unsigned,
+        };
+
+        let mut variant_part = VariantPart {
+            offset: entry.offset(),
+            discriminant,
+            encoding,
+            variants: Vec::new(),
+        };
+        let mut children = node.children();
+        while let Some(child) = children.next()? {
+            if child.entry().tag() == constants::DW_TAG_variant {
+                let variant = self.variant(child, encoding)?;
+                variant_part.variants.push(variant);
+            }
+        }
+        Ok(variant_part)
+    }
+
+    fn variant(
+        &self,
+        node: EntriesTreeNode<'_, '_, '_, R>,
+        encoding: constants::DwAte,
+    ) -> Result<Variant<R>> {
+        let entry = node.entry();
+        let mut variant = Variant {
+            offset: entry.offset(),
+            values: Vec::new(),
+            members: Vec::new(),
+        };
+        let signed = is_signed(encoding);
+        let mut attrs = entry.attrs();
+        while let Some(attr) = attrs.next()? {
+            match attr.name() {
+                constants::DW_AT_discr_value => {
+                    if let Some(value) = discriminant_value(attr.value(), signed) {
+                        variant.values.push(DiscriminantValue::Label(value));
+                    }
+                }
+                constants::DW_AT_discr_list => {
+                    if let AttributeValue::Block(block) = attr.value() {
+                        let mut iter = DiscrListIter::new(block, encoding);
+                        while let Some(value) = iter.next()? {
+                            variant.values.push(value);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let mut children = node.children();
+        while let Some(child) = children.next()? {
+            if child.entry().tag() == constants::DW_TAG_member {
+                variant.members.push(child.entry().offset());
+            }
+        }
+        Ok(variant)
+    }
+
+    /// Find the encoding of a base type, skipping over typedefs and qualifiers.
+    fn discriminant_encoding(&self, mut offset: UnitOffset<R::Offset>) -> Result<constants::DwAte> {
+        // Limit the depth in case of cycles.
+        for _ in 0..16 {
+            let entry = self.entry(offset)?;
+            match entry.tag() {
+                constants::DW_TAG_base_type | constants::DW_TAG_enumeration_type => {
+                    if let Some(AttributeValue::Encoding(encoding)) =
+                        entry.attr_value(constants::DW_AT_encoding)?
+                    {
+                        return Ok(encoding);
+                    }
+                }
+                constants::DW_TAG_typedef
+                | constants::DW_TAG_const_type
+                | constants::DW_TAG_volatile_type => {}
+                _ => break,
+            }
+            match entry
+                .attr_value(constants::DW_AT_type)?
+                .and_then(|value| self.local_reference(&value))
+            {
+                Some(type_) => offset = type_,
+                None => break,
+            }
+        }
+        Ok(constants::DW_ATE_unsigned)
+    }
+}
+
+fn is_signed(encoding: constants::DwAte) -> bool {
+    matches!(
+        encoding,
+        constants::DW_ATE_signed | constants::DW_ATE_signed_char | constants::DW_ATE_signed_fixed
+    )
+}
+
+/// Convert a `DW_AT_discr_value`, sign extending fixed size constants if needed.
+fn discriminant_value<R: Reader>(value: AttributeValue<R>, signed: bool) -> Option<Discriminant> {
+    if signed {
+        let value = match value {
+            AttributeValue::Data1(v) => i64::from(v as i8),
+            AttributeValue::Data2(v) => i64::from(v as i16),
+            AttributeValue::Data4(v) => i64::from(v as i32),
+            AttributeValue::Data8(v) => v as i64,
+            AttributeValue::Sdata(v) => v,
+            AttributeValue::Udata(v) => v as i64,
+            _ => return None,
+        };
+        Some(Discriminant::Signed(value))
+    } else {
+        let value = match value {
+            AttributeValue::Sdata(v) => v as u64,
+            _ => value.udata_value()?,
+        };
+        Some(Discriminant::Unsigned(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::*;
+    use crate::endianity::LittleEndian;
+    use crate::read::abbrev::tests::AbbrevSectionMethods;
+    use crate::read::{EndianSlice, Unit};
+    use crate::test_util::{compile_unit, test_dwarf, GimliSectionMethods};
+    use test_assembler::{Endian, Label, LabelMaker, Section};
+
+    #[test]
+    fn test_discr_list() {
+        // DW_DSC_label 3, DW_DSC_range 0x7f..=0x80.
+        let buf = [0, 3, 1, 0x7f, 0x80, 0x01];
+
+        let mut iter = DiscrListIter::new(
+            EndianSlice::new(&buf, LittleEndian),
+            constants::DW_ATE_unsigned,
+        );
+        let label = iter.next().unwrap().unwrap();
+        assert_eq!(label, DiscriminantValue::Label(Discriminant::Unsigned(3)));
+        let range = iter.next().unwrap().unwrap();
+        assert_eq!(
+            range,
+            DiscriminantValue::Range(Discriminant::Unsigned(0x7f), Discriminant::Unsigned(0x80))
+        );
+        assert_eq!(iter.next(), Ok(None));
+        assert!(label.contains(Discriminant::Unsigned(3)));
+        assert!(!label.contains(Discriminant::Unsigned(4)));
+        assert!(range.contains(Discriminant::Unsigned(0x80)));
+        assert!(!range.contains(Discriminant::Unsigned(0x81)));
+
+        let mut iter = DiscrListIter::new(
+            EndianSlice::new(&buf, LittleEndian),
+            constants::DW_ATE_signed,
+        );
+        assert_eq!(
+            iter.next(),
+            Ok(Some(DiscriminantValue::Label(Discriminant::Signed(3))))
+        );
+        let range = iter.next().unwrap().unwrap();
+        assert_eq!(
+            range,
+            DiscriminantValue::Range(Discriminant::Signed(-1), Discriminant::Signed(0x80))
+        );
+        assert!(range.contains(Discriminant::Signed(0)));
+        assert!(!range.contains(Discriminant::Signed(-2)));
+
+        let buf = [2, 0];
+        let mut iter = DiscrListIter::new(
+            EndianSlice::new(&buf, LittleEndian),
+            constants::DW_ATE_unsigned,
+        );
+        assert_eq!(
+            iter.next(),
+            Err(Error::UnknownDiscriminantDescriptor(constants::DwDsc(2)))
+        );
+        assert_eq!(iter.next(), Ok(None));
+    }
+
+    #[test]
+    fn test_variant_parts() {
+        #[rustfmt::skip]
+        let abbrev = Section::with_endian(Endian::Little)
+            .abbrev(1, DW_TAG_compile_unit, DW_CHILDREN_yes)
+                .abbrev_attr_null()
+            .abbrev(2, DW_TAG_base_type, DW_CHILDREN_no)
+                .abbrev_attr(DW_AT_encoding, DW_FORM_data1)
+                .abbrev_attr_null()
+            .abbrev(3, DW_TAG_typedef, DW_CHILDREN_no)
+                .abbrev_attr(DW_AT_type, DW_FORM_ref4)
+                .abbrev_attr_null()
+            .abbrev(4, DW_TAG_structure_type, DW_CHILDREN_yes)
+                .abbrev_attr_null()
+            .abbrev(5, DW_TAG_variant_part, DW_CHILDREN_yes)
+                .abbrev_attr(DW_AT_discr, DW_FORM_ref4)
+                .abbrev_attr_null()
+            .abbrev(6, DW_TAG_member, DW_CHILDREN_no)
+                .abbrev_attr(DW_AT_type, DW_FORM_ref4)
+                .abbrev_attr(DW_AT_artificial, DW_FORM_flag_present)
+                .abbrev_attr_null()
+            .abbrev(7, DW_TAG_variant, DW_CHILDREN_yes)
+                .abbrev_attr(DW_AT_discr_value, DW_FORM_data1)
+                .abbrev_attr_null()
+            .abbrev(8, DW_TAG_variant, DW_CHILDREN_yes)
+                .abbrev_attr(DW_AT_discr_list, DW_FORM_block1)
+                .abbrev_attr_null()
+            .abbrev(9, DW_TAG_variant, DW_CHILDREN_yes)
+                .abbrev_attr_null()
+            .abbrev(10, DW_TAG_member, DW_CHILDREN_no)
+                .abbrev_attr_null()
+            .abbrev_null();
+        let abbrev = abbrev.get_contents().unwrap();
+
+        let base = Label::new();
+        let typedef = Label::new();
+        let structure = Label::new();
+        let part = Label::new();
+        let tag = Label::new();
+        let variant_a = Label::new();
+        let member_a = Label::new();
+        let variant_b = Label::new();
+        let member_b = Label::new();
+        let variant_c = Label::new();
+        #[rustfmt::skip]
+        let entries = Section::with_endian(Endian::Little)
+            .uleb(1)
+                .mark(&base)
+                .uleb(2).D8(DW_ATE_signed.0)
+                .mark(&typedef)
+                .uleb(3).L32(&base)
+                .mark(&structure)
+                .uleb(4)
+                    .mark(&part)
+                    .uleb(5).L32(&tag)
+                        .mark(&tag)
+                        .uleb(6).L32(&typedef)
+                        .mark(&variant_a)
+                        .uleb(7).D8(0xff)
+                            .mark(&member_a)
+                            .uleb(10)
+                            .D8(0)
+                        .mark(&variant_b)
+                        .uleb(8).D8(3).D8(DW_DSC_range.0).D8(0).D8(5)
+                            .mark(&member_b)
+                            .uleb(10)
+                            .D8(0)
+                        .mark(&variant_c)
+                        .uleb(9)
+                            .D8(0)
+                        .D8(0)
+                    .D8(0)
+                .D8(0);
+        let info = compile_unit(entries);
+        let offset = |label: &Label| UnitOffset(label.value().unwrap() as usize);
+
+        let dwarf = test_dwarf(&abbrev, &info);
+        let header = dwarf.units().next().unwrap().unwrap();
+        let unit = Unit::new(&dwarf, header).unwrap();
+        let unit = unit.unit_ref(&dwarf);
+
+        let parts = unit.variant_parts(offset(&structure)).unwrap();
+        assert_eq!(parts.len(), 1);
+        let variant_part = &parts[0];
+        assert_eq!(variant_part.offset, offset(&part));
+        assert_eq!(variant_part.discriminant, Some(offset(&tag)));
+        assert_eq!(variant_part.encoding, DW_ATE_signed);
+        assert_eq!(variant_part.variants.len(), 3);
+
+        let variant = &variant_part.variants[0];
+        assert_eq!(variant.offset, offset(&variant_a));
+        assert_eq!(
+            variant.values,
+            [DiscriminantValue::Label(Discriminant::Signed(-1))]
+        );
+        assert_eq!(variant.members, [offset(&member_a)]);
+
+        let variant = &variant_part.variants[1];
+        assert_eq!(variant.offset, offset(&variant_b));
+        assert_eq!(
+            variant.values,
+            [DiscriminantValue::Range(
+                Discriminant::Signed(0),
+                Discriminant::Signed(5)
+            )]
+        );
+        assert_eq!(variant.members, [offset(&member_b)]);
+
+        let variant = &variant_part.variants[2];
+        assert_eq!(variant.offset, offset(&variant_c));
+        assert!(variant.is_default());
+        assert!(variant.members.is_empty());
+
+        let find = |value| {
+            variant_part
+                .find_variant(Discriminant::Signed(value))
+                .unwrap()
+                .offset
+        };
+        assert_eq!(find(-1), offset(&variant_a));
+        assert_eq!(find(3), offset(&variant_b));
+        assert_eq!(find(6), offset(&variant_c));
+    }
+}
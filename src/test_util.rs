@@ -1,7 +1,11 @@
 #![allow(missing_docs)]
 
-use crate::Format;
-use test_assembler::{Label, Section};
+use alloc::vec::Vec;
+
+#[cfg(feature = "read")]
+use crate::read::{DebugAbbrev, DebugInfo, Dwarf, EndianSlice};
+use crate::{Format, LittleEndian};
+use test_assembler::{Endian, Label, LabelMaker, Section};
 
 pub trait GimliSectionMethods {
     fn sleb(self, val: i64) -> Self;
@@ -51,3 +55,34 @@ impl GimliSectionMethods for Section {
         }
     }
 }
+
+/// Build a DWARF 4 compilation unit with 8 byte addresses and an abbreviations
+/// offset of 0, containing the given entries.
+///
+/// Labels in `entries` are offsets within the unit.
+#[cfg(feature = "read")]
+pub fn compile_unit(entries: Section) -> Vec<u8> {
+    let length = Label::new();
+    let start = Label::new();
+    let end = Label::new();
+    let unit = Section::with_endian(Endian::Little)
+        .set_start_const(0)
+        .initial_length(Format::Dwarf32, &length, &start)
+        .L16(4)
+        .L32(0)
+        .D8(8);
+    entries.start().set(&unit.here());
+    let unit = unit.append_section(entries).mark(&end);
+    length.set_const((&end - &start) as u64);
+    unit.get_contents().unwrap()
+}
+
+/// Create a `Dwarf` for the given `.debug_abbrev` and `.debug_info` sections.
+#[cfg(feature = "read")]
+pub fn test_dwarf<'a>(abbrev: &'a [u8], info: &'a [u8]) -> Dwarf<EndianSlice<'a, LittleEndian>> {
+    Dwarf {
+        debug_abbrev: DebugAbbrev::new(abbrev, LittleEndian),
+        debug_info: DebugInfo::new(info, LittleEndian),
+        ..Default::default()
+    }
+}
@@ -24,6 +24,14 @@ impl AbbreviationTable {
         (code + 1) as u64
     }
 
+    /// Sort the abbreviations, so that their codes do not depend on the order
+    /// in which they were added.
+    ///
+    /// This must be called before the codes returned by `add` are used.
+    pub fn sort(&mut self) {
+        self.abbrevs.sort();
+    }
+
     /// Write the abbreviation table to the `.debug_abbrev` section.
     pub fn write<W: Writer>(&self, w: &mut DebugAbbrev<W>) -> Result<()> {
         for (code, abbrev) in self.abbrevs.iter().enumerate() {
@@ -37,7 +45,7 @@ impl AbbreviationTable {
 
 /// An abbreviation describes the shape of a `DebuggingInformationEntry`'s type:
 /// its tag type, whether it has children, and its set of attributes.
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub(crate) struct Abbreviation {
     tag: constants::DwTag,
     has_children: bool,
@@ -77,25 +85,44 @@ impl Abbreviation {
 }
 
 /// The description of an attribute in an abbreviated type.
-// TODO: support implicit const
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub(crate) struct AttributeSpecification {
     name: constants::DwAt,
     form: constants::DwForm,
+    implicit_const_value: i64,
 }
 
 impl AttributeSpecification {
     /// Construct a new `AttributeSpecification`.
     #[inline]
     pub fn new(name: constants::DwAt, form: constants::DwForm) -> AttributeSpecification {
-        AttributeSpecification { name, form }
+        AttributeSpecification {
+            name,
+            form,
+            implicit_const_value: 0,
+        }
+    }
+
+    /// Construct a new `AttributeSpecification` with a form of
+    /// `DW_FORM_implicit_const` and the given value.
+    #[inline]
+    pub fn new_implicit_const(name: constants::DwAt, value: i64) -> AttributeSpecification {
+        AttributeSpecification {
+            name,
+            form: constants::DW_FORM_implicit_const,
+            implicit_const_value: value,
+        }
     }
 
     /// Write the attribute specification to the `.debug_abbrev` section.
     #[inline]
     pub fn write<W: Writer>(&self, w: &mut DebugAbbrev<W>) -> Result<()> {
         w.write_uleb128(self.name.0.into())?;
-        w.write_uleb128(self.form.0.into())
+        w.write_uleb128(self.form.0.into())?;
+        if self.form == constants::DW_FORM_implicit_const {
+            w.write_sleb128(self.implicit_const_value)?;
+        }
+        Ok(())
     }
 }
 
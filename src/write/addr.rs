@@ -0,0 +1,212 @@
+use indexmap::IndexSet;
+use std::ops::{Deref, DerefMut};
+
+use crate::common::{DebugAddrBase, DebugAddrIndex, Encoding, SectionId};
+use crate::write::{Address, Error, Result, Section, Writer};
+
+define_section!(
+    DebugAddr,
+    DebugAddrBase,
+    "A writable `.debug_addr` section."
+);
+
+/// A table of addresses that will be stored in a `.debug_addr` section.
+///
+/// Entries refer to addresses in the table by index. The table must be written
+/// to the `.debug_addr` section of the object file that contains the addresses,
+/// which for split DWARF is the object file containing the skeleton unit.
+///
+/// For segmented architectures, each address may have a segment selector.
+/// The segment selectors are only written if the segment selector size is
+/// nonzero.
+#[derive(Debug, Default)]
+pub struct AddressTable {
+    segment_selector_size: u8,
+    addresses: IndexSet<(u64, Address)>,
+}
+
+impl AddressTable {
+    /// Add an address to the table, and return its index.
+    ///
+    /// Adding an address that is already in the table returns the existing index.
+    pub fn add(&mut self, address: Address) -> DebugAddrIndex {
+        self.add_with_segment(0, address)
+    }
+
+    /// Add an address with a segment selector to the table, and return its index.
+    ///
+    /// Adding an address that is already in the table returns the existing index.
+    pub fn add_with_segment(&mut self, segment: u64, address: Address) -> DebugAddrIndex {
+        let (index, _) = self.addresses.insert_full((segment, address));
+        DebugAddrIndex(index)
+    }
+
+    /// Set the size of the segment selectors. The default is 0.
+    ///
+    /// A nonzero size requires DWARF version 5.
+    pub fn set_segment_selector_size(&mut self, segment_selector_size: u8) {
+        self.segment_selector_size = segment_selector_size;
+    }
+
+    /// Return the number of addresses in the table.
+    #[inline]
+    pub fn count(&self) -> usize {
+        self.addresses.len()
+    }
+
+    /// Get the address at the given index.
+    ///
+    /// Panics if `index` is invalid.
+    #[inline]
+    pub fn get(&self, index: DebugAddrIndex) -> Address {
+        self.addresses[index.0].1
+    }
+
+    /// Get the segment selector of the address at the given index.
+    ///
+    /// Panics if `index` is invalid.
+    #[inline]
+    pub fn segment(&self, index: DebugAddrIndex) -> u64 {
+        self.addresses[index.0].0
+    }
+
+    /// Write the address table to the `.debug_addr` section.
+    ///
+    /// For DWARF version 5, the table is preceded by a header. For earlier
+    /// versions, the table uses the format of the GNU split DWARF extension,
+    /// which has no header.
+    ///
+    /// Returns the base offset of the table. This is the value for the
+    /// `DW_AT_addr_base` or `DW_AT_GNU_addr_base` attribute of the unit.
+    ///
+    /// Returns an error if a segment selector does not fit in the segment
+    /// selector size.
+    pub fn write<W: Writer>(
+        &self,
+        w: &mut DebugAddr<W>,
+        encoding: Encoding,
+    ) -> Result<DebugAddrBase> {
+        let length_offset = match encoding.version {
+            2..=4 if self.segment_selector_size != 0 => return Err(Error::NeedVersion(5)),
+            2..=4 => None,
+            5 => {
+                let length_offset = w.write_initial_length(encoding.format)?;
+                let length_base = w.len();
+                w.write_u16(encoding.version)?;
+                w.write_u8(encoding.address_size)?;
+                w.write_u8(self.segment_selector_size)?;
+                Some((length_offset, length_base))
+            }
+            _ => return Err(Error::UnsupportedVersion(encoding.version)),
+        };
+
+        let base = w.offset();
+        for &(segment, address) in self.addresses.iter() {
+            if self.segment_selector_size != 0 {
+                w.write_udata(segment, self.segment_selector_size)?;
+            } else if segment != 0 {
+                return Err(Error::ValueTooLarge);
+            }
+            w.write_address(address, encoding.address_size)?;
+        }
+
+        if let Some((length_offset, length_base)) = length_offset {
+            let length = (w.len() - length_base) as u64;
+            w.write_initial_length_at(length_offset, length, encoding.format)?;
+        }
+        Ok(base)
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "read")]
+mod tests {
+    use super::*;
+    use crate::common::Format;
+    use crate::read;
+    use crate::write::EndianVec;
+    use crate::LittleEndian;
+
+    #[test]
+    fn test_address_table() {
+        let mut addresses = AddressTable::default();
+        let index1 = addresses.add(Address::Constant(0x1000));
+        let index2 = addresses.add(Address::Constant(0x2000));
+        assert_eq!(addresses.add(Address::Constant(0x1000)), index1);
+        assert_eq!(addresses.count(), 2);
+        assert_eq!(addresses.get(index2), Address::Constant(0x2000));
+
+        for &version in &[4, 5] {
+            for &format in &[Format::Dwarf32, Format::Dwarf64] {
+                let encoding = Encoding {
+                    format,
+                    version,
+                    address_size: 8,
+                };
+                let mut debug_addr = DebugAddr::from(EndianVec::new(LittleEndian));
+                let base = addresses.write(&mut debug_addr, encoding).unwrap();
+                let header_size = match (version, format) {
+                    (4, _) => 0,
+                    (_, Format::Dwarf32) => 8,
+                    (_, Format::Dwarf64) => 16,
+                };
+                assert_eq!(base, DebugAddrBase(header_size));
+
+                let read_debug_addr =
+                    read::DebugAddr::from(read::EndianSlice::new(debug_addr.slice(), LittleEndian));
+                for (index, address) in [(index1, 0x1000), (index2, 0x2000)] {
+                    assert_eq!(read_debug_addr.get_address(8, base, index), Ok(address));
+                }
+                if version == 5 {
+                    use crate::read::Reader;
+                    let mut r = read::EndianSlice::new(debug_addr.slice(), LittleEndian);
+                    assert_eq!(r.read_initial_length(), Ok((20, format)));
+                    assert_eq!(r.read_u16(), Ok(5));
+                    assert_eq!(r.read_u8(), Ok(8));
+                    assert_eq!(r.read_u8(), Ok(0));
+                    assert_eq!(r.len(), 16);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_address_table_segment() {
+        let encoding = Encoding {
+            format: Format::Dwarf32,
+            version: 5,
+            address_size: 4,
+        };
+        let mut addresses = AddressTable::default();
+        let index1 = addresses.add_with_segment(1, Address::Constant(0x1000));
+        let index2 = addresses.add_with_segment(2, Address::Constant(0x1000));
+        assert_ne!(index1, index2);
+        assert_eq!(addresses.segment(index2), 2);
+
+        let mut debug_addr = DebugAddr::from(EndianVec::new(LittleEndian));
+        assert_eq!(
+            addresses.write(&mut debug_addr, encoding),
+            Err(Error::ValueTooLarge)
+        );
+
+        addresses.set_segment_selector_size(2);
+        let mut debug_addr = DebugAddr::from(EndianVec::new(LittleEndian));
+        let base = addresses.write(&mut debug_addr, encoding).unwrap();
+        assert_eq!(debug_addr.slice()[7], 2);
+        let read_debug_addr =
+            read::DebugAddr::from(read::EndianSlice::new(debug_addr.slice(), LittleEndian));
+        assert_eq!(
+            read_debug_addr.get_segment_address(4, 2, base, index2),
+            Ok((2, 0x1000))
+        );
+
+        let encoding = Encoding {
+            version: 4,
+            ..encoding
+        };
+        assert_eq!(
+            addresses.write(&mut debug_addr, encoding),
+            Err(Error::NeedVersion(5))
+        );
+    }
+}
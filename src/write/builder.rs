@@ -0,0 +1,580 @@
+use alloc::vec::Vec;
+
+use crate::common::{Encoding, LineEncoding, Register};
+use crate::constants;
+use crate::write::{
+    Address, AttributeValue, DwarfUnit, Error, Expression, FileId, LineProgram, LineString, Range,
+    RangeList, Result, Sections, UnitEntryId, Writer,
+};
+
+/// An identifier for a function in a `DebugInfoBuilder`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FunctionId(usize);
+
+/// The location of a parameter or local variable added with a `DebugInfoBuilder`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VariableLocation {
+    /// The value is stored in a register.
+    ///
+    /// This is written as `DW_OP_reg`.
+    Register(Register),
+    /// The value is stored in memory at an offset from the frame base of the function.
+    ///
+    /// This is written as `DW_OP_fbreg`.
+    FrameOffset(i64),
+    /// The value is stored in memory at an offset from the address in a register.
+    ///
+    /// This is written as `DW_OP_breg`.
+    RegisterOffset(Register, i64),
+    /// The value is stored in memory at a fixed address.
+    ///
+    /// This is written as `DW_OP_addr`.
+    Address(Address),
+    /// An arbitrary location expression.
+    Expression(Expression),
+}
+
+impl VariableLocation {
+    fn expression(self) -> Expression {
+        let mut expression = Expression::new();
+        match self {
+            VariableLocation::Register(register) => expression.op_reg(register),
+            VariableLocation::FrameOffset(offset) => expression.op_fbreg(offset),
+            VariableLocation::RegisterOffset(register, offset) => {
+                expression.op_breg(register, offset)
+            }
+            VariableLocation::Address(address) => expression.op_addr(address),
+            VariableLocation::Expression(e) => expression = e,
+        }
+        expression
+    }
+}
+
+#[derive(Debug)]
+struct Function {
+    entry: UnitEntryId,
+    address: Address,
+    size: u64,
+    file: FileId,
+    line: u64,
+    rows: Vec<(u64, FileId, u64)>,
+}
+
+/// A builder for the debugging information of a single compilation unit
+/// containing generated code.
+///
+/// This is intended for JIT compilers and other runtime code generators
+/// that only need enough debugging information for stack traces, breakpoints
+/// and stepping to work. It generates the DIE tree, line number program and
+/// abbreviations from a description of the functions and their variables.
+///
+/// The frame base of each function defaults to `DW_OP_call_frame_cfa`,
+/// so if you use [`VariableLocation::FrameOffset`] then you should also
+/// emit call frame information for the function, or use
+/// [`DebugInfoBuilder::set_frame_base`].
+///
+/// Use [`DebugInfoBuilder::finish`] to obtain a [`DwarfUnit`] if you need to
+/// add further information that is not supported by this builder.
+///
+/// ```rust
+/// use gimli::write::{Address, DebugInfoBuilder, EndianVec, Sections, VariableLocation};
+///
+/// # fn example() -> gimli::write::Result<()> {
+/// let encoding = gimli::Encoding {
+///     format: gimli::Format::Dwarf32,
+///     version: 4,
+///     address_size: 8,
+/// };
+/// let mut builder = DebugInfoBuilder::new(encoding, "/tmp", "script.js");
+/// let file = builder.add_file("", "script.js");
+/// let int = builder.add_base_type("int", gimli::DW_ATE_signed, 4);
+/// let function = builder.add_function("main", Address::Constant(0x1000), 0x40, file, 1);
+/// builder.set_return_type(function, int);
+/// builder.add_parameter(function, "argc", int, VariableLocation::Register(gimli::X86_64::RDI));
+/// builder.add_variable(function, "i", int, VariableLocation::FrameOffset(-8));
+/// builder.add_line(function, 0x10, file, 2);
+/// builder.add_line(function, 0x30, file, 3);
+///
+/// let mut sections = Sections::new(EndianVec::new(gimli::LittleEndian));
+/// builder.write(&mut sections)?;
+/// # Ok(())
+/// # }
+/// # example().unwrap();
+/// ```
+#[derive(Debug)]
+pub struct DebugInfoBuilder {
+    dwarf: DwarfUnit,
+    functions: Vec<Function>,
+}
+
+impl DebugInfoBuilder {
+    /// Create a new builder for a compilation unit with the given name and
+    /// working directory.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `comp_dir` or `comp_name` is empty or contains a null byte.
+    pub fn new(encoding: Encoding, comp_dir: &str, comp_name: &str) -> Self {
+        let mut dwarf = DwarfUnit::new(encoding);
+        let line_program = LineProgram::new(
+            encoding,
+            LineEncoding::default(),
+            LineString::new(comp_dir, encoding, &mut dwarf.line_strings),
+            LineString::new(comp_name, encoding, &mut dwarf.line_strings),
+            None,
+        );
+        dwarf.unit.line_program = line_program;
+        let root = dwarf.unit.root();
+        let name = dwarf.strings.add(comp_name);
+        let comp_dir = dwarf.strings.add(comp_dir);
+        let entry = dwarf.unit.get_mut(root);
+        entry.set(constants::DW_AT_name, AttributeValue::StringRef(name));
+        entry.set(
+            constants::DW_AT_comp_dir,
+            AttributeValue::StringRef(comp_dir),
+        );
+        DebugInfoBuilder {
+            dwarf,
+            functions: Vec::new(),
+        }
+    }
+
+    /// Set the `DW_AT_producer` attribute of the compilation unit.
+    pub fn set_producer(&mut self, producer: &str) {
+        let producer = self.dwarf.strings.add(producer);
+        let root = self.dwarf.unit.root();
+        self.dwarf.unit.get_mut(root).set(
+            constants::DW_AT_producer,
+            AttributeValue::StringRef(producer),
+        );
+    }
+
+    /// Set the `DW_AT_language` attribute of the compilation unit.
+    pub fn set_language(&mut self, language: constants::DwLang) {
+        let root = self.dwarf.unit.root();
+        self.dwarf.unit.get_mut(root).set(
+            constants::DW_AT_language,
+            AttributeValue::Language(language),
+        );
+    }
+
+    /// Add a source file, and return its id.
+    ///
+    /// If `directory` is empty then the file is relative to the working directory
+    /// of the compilation unit.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `name` is empty, or if `directory` or `name` contains a null byte.
+    pub fn add_file(&mut self, directory: &str, name: &str) -> FileId {
+        let encoding = self.dwarf.unit.encoding();
+        let line_program = &mut self.dwarf.unit.line_program;
+        let directory = if directory.is_empty() {
+            line_program.default_directory()
+        } else {
+            line_program.add_directory(LineString::new(
+                directory,
+                encoding,
+                &mut self.dwarf.line_strings,
+            ))
+        };
+        let name = LineString::new(name, encoding, &mut self.dwarf.line_strings);
+        line_program.add_file(name, directory, None)
+    }
+
+    /// Add a `DW_TAG_base_type` entry, and return its id.
+    ///
+    /// `byte_size` is the size of the type in bytes.
+    pub fn add_base_type(
+        &mut self,
+        name: &str,
+        encoding: constants::DwAte,
+        byte_size: u64,
+    ) -> UnitEntryId {
+        let root = self.dwarf.unit.root();
+        let id = self.dwarf.unit.add(root, constants::DW_TAG_base_type);
+        let name = self.dwarf.strings.add(name);
+        let entry = self.dwarf.unit.get_mut(id);
+        entry.set(constants::DW_AT_name, AttributeValue::StringRef(name));
+        entry.set(
+            constants::DW_AT_encoding,
+            AttributeValue::Encoding(encoding),
+        );
+        entry.set(constants::DW_AT_byte_size, AttributeValue::Udata(byte_size));
+        id
+    }
+
+    /// Add a `DW_TAG_pointer_type` entry, and return its id.
+    ///
+    /// If `pointee` is `None` then this is a pointer to `void`.
+    pub fn add_pointer_type(&mut self, pointee: Option<UnitEntryId>) -> UnitEntryId {
+        let address_size = self.dwarf.unit.address_size();
+        let root = self.dwarf.unit.root();
+        let id = self.dwarf.unit.add(root, constants::DW_TAG_pointer_type);
+        let entry = self.dwarf.unit.get_mut(id);
+        entry.set(
+            constants::DW_AT_byte_size,
+            AttributeValue::Data1(address_size),
+        );
+        if let Some(pointee) = pointee {
+            entry.set(constants::DW_AT_type, AttributeValue::UnitRef(pointee));
+        }
+        id
+    }
+
+    /// Add a function, and return its id.
+    ///
+    /// The function contains the code from `address` to `address + size`,
+    /// and is declared at the given line of `file`.
+    pub fn add_function(
+        &mut self,
+        name: &str,
+        address: Address,
+        size: u64,
+        file: FileId,
+        line: u64,
+    ) -> FunctionId {
+        let encoding = self.dwarf.unit.encoding();
+        let root = self.dwarf.unit.root();
+        let id = self.dwarf.unit.add(root, constants::DW_TAG_subprogram);
+        let name = self.dwarf.strings.add(name);
+        let mut frame_base = Expression::new();
+        frame_base.op(constants::DW_OP_call_frame_cfa);
+        let entry = self.dwarf.unit.get_mut(id);
+        entry.set(constants::DW_AT_name, AttributeValue::StringRef(name));
+        entry.set(constants::DW_AT_external, AttributeValue::Flag(true));
+        entry.set(
+            constants::DW_AT_decl_file,
+            AttributeValue::FileIndex(Some(file)),
+        );
+        entry.set(constants::DW_AT_decl_line, AttributeValue::Udata(line));
+        entry.set(constants::DW_AT_low_pc, AttributeValue::Address(address));
+        if encoding.version < 4 {
+            // A constant `DW_AT_high_pc` is an offset only since DWARF 4.
+            entry.set(
+                constants::DW_AT_high_pc,
+                AttributeValue::Address(address.add(size)),
+            );
+        } else {
+            entry.set(constants::DW_AT_high_pc, AttributeValue::Udata(size));
+        }
+        entry.set(
+            constants::DW_AT_frame_base,
+            AttributeValue::Exprloc(frame_base),
+        );
+        self.functions.push(Function {
+            entry: id,
+            address,
+            size,
+            file,
+            line,
+            rows: Vec::new(),
+        });
+        FunctionId(self.functions.len() - 1)
+    }
+
+    /// Return the id of the `DW_TAG_subprogram` entry for a function.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `function` is invalid.
+    pub fn function_entry(&self, function: FunctionId) -> UnitEntryId {
+        self.functions[function.0].entry
+    }
+
+    /// Set the return type of a function.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `function` is invalid.
+    pub fn set_return_type(&mut self, function: FunctionId, return_type: UnitEntryId) {
+        let entry = self.function_entry(function);
+        self.dwarf
+            .unit
+            .get_mut(entry)
+            .set(constants::DW_AT_type, AttributeValue::UnitRef(return_type));
+    }
+
+    /// Set the frame base of a function.
+    ///
+    /// This replaces the default of `DW_OP_call_frame_cfa`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `function` is invalid.
+    pub fn set_frame_base(&mut self, function: FunctionId, frame_base: VariableLocation) {
+        let entry = self.function_entry(function);
+        self.dwarf.unit.get_mut(entry).set(
+            constants::DW_AT_frame_base,
+            AttributeValue::Exprloc(frame_base.expression()),
+        );
+    }
+
+    /// Add a `DW_TAG_formal_parameter` entry to a function, and return its id.
+    ///
+    /// Parameters are listed in the order that they are added.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `function` is invalid.
+    pub fn add_parameter(
+        &mut self,
+        function: FunctionId,
+        name: &str,
+        type_: UnitEntryId,
+        location: VariableLocation,
+    ) -> UnitEntryId {
+        self.add_variable_entry(
+            function,
+            constants::DW_TAG_formal_parameter,
+            name,
+            type_,
+            location,
+        )
+    }
+
+    /// Add a `DW_TAG_variable` entry for a local variable to a function,
+    /// and return its id.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `function` is invalid.
+    pub fn add_variable(
+        &mut self,
+        function: FunctionId,
+        name: &str,
+        type_: UnitEntryId,
+        location: VariableLocation,
+    ) -> UnitEntryId {
+        self.add_variable_entry(function, constants::DW_TAG_variable, name, type_, location)
+    }
+
+    fn add_variable_entry(
+        &mut self,
+        function: FunctionId,
+        tag: constants::DwTag,
+        name: &str,
+        type_: UnitEntryId,
+        location: VariableLocation,
+    ) -> UnitEntryId {
+        let parent = self.function_entry(function);
+        let id = self.dwarf.unit.add(parent, tag);
+        let name = self.dwarf.strings.add(name);
+        let entry = self.dwarf.unit.get_mut(id);
+        entry.set(constants::DW_AT_name, AttributeValue::StringRef(name));
+        entry.set(constants::DW_AT_type, AttributeValue::UnitRef(type_));
+        entry.set(
+            constants::DW_AT_location,
+            AttributeValue::Exprloc(location.expression()),
+        );
+        id
+    }
+
+    /// Add a line number row for a function.
+    ///
+    /// The row applies to the code from `address_offset` bytes after the
+    /// start of the function until the next row. Rows may be added in any order.
+    ///
+    /// If no rows are added for a function, then a single row is generated
+    /// for the start of the function using its declaration file and line.
+    ///
+    /// `address_offset` must be less than the size of the function, otherwise
+    /// [`DebugInfoBuilder::finish`] returns an error.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `function` is invalid.
+    pub fn add_line(&mut self, function: FunctionId, address_offset: u64, file: FileId, line: u64) {
+        self.functions[function.0]
+            .rows
+            .push((address_offset, file, line));
+    }
+
+    /// Generate the line number program and address ranges, and return the
+    /// resulting unit.
+    ///
+    /// Returns `Error::InvalidAddress` if a line number row is not within its function.
+    pub fn finish(self) -> Result<DwarfUnit> {
+        let mut dwarf = self.dwarf;
+        let mut ranges = Vec::with_capacity(self.functions.len());
+        for mut function in self.functions {
+            ranges.push(Range::StartLength {
+                begin: function.address,
+                length: function.size,
+            });
+
+            if function.rows.is_empty() {
+                if function.size == 0 {
+                    continue;
+                }
+                function.rows.push((0, function.file, function.line));
+            }
+            if function.rows.iter().any(|row| row.0 >= function.size) {
+                return Err(Error::InvalidAddress);
+            }
+            function.rows.sort_by_key(|row| row.0);
+            let line_program = &mut dwarf.unit.line_program;
+            line_program.begin_sequence(Some(function.address));
+            for (address_offset, file, line) in function.rows {
+                let row = line_program.row();
+                row.address_offset = address_offset;
+                row.file = file;
+                row.line = line;
+                line_program.generate_row();
+            }
+            line_program.end_sequence(function.size);
+        }
+
+        if !ranges.is_empty() {
+            let ranges = dwarf.unit.ranges.add(RangeList(ranges));
+            let version = dwarf.unit.version();
+            let root = dwarf.unit.root();
+            let entry = dwarf.unit.get_mut(root);
+            if version <= 4 {
+                // The base address for the range list.
+                entry.set(
+                    constants::DW_AT_low_pc,
+                    AttributeValue::Address(Address::Constant(0)),
+                );
+            }
+            entry.set(
+                constants::DW_AT_ranges,
+                AttributeValue::RangeListRef(ranges),
+            );
+        }
+        Ok(dwarf)
+    }
+
+    /// Generate the debugging information and write it to the given sections.
+    pub fn write<W: Writer>(self, sections: &mut Sections<W>) -> Result<()> {
+        self.finish()?.write(sections)
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "read")]
+mod tests {
+    use super::*;
+    use crate::read;
+    use crate::write::EndianVec;
+    use crate::{Format, LittleEndian};
+
+    #[test]
+    fn test_debug_info_builder() {
+        for &version in &[3, 4, 5] {
+            let encoding = Encoding {
+                format: Format::Dwarf32,
+                version,
+                address_size: 8,
+            };
+            let mut builder = DebugInfoBuilder::new(encoding, "/tmp", "jit");
+            builder.set_producer("test");
+            let file = builder.add_file("src", "a.js");
+            let int = builder.add_base_type("int", constants::DW_ATE_signed, 4);
+            let f1 = builder.add_function("f1", Address::Constant(0x1000), 0x20, file, 10);
+            builder.set_return_type(f1, int);
+            builder.add_parameter(f1, "x", int, VariableLocation::Register(Register(5)));
+            builder.add_variable(f1, "y", int, VariableLocation::FrameOffset(-16));
+            builder.add_line(f1, 0x10, file, 12);
+            builder.add_line(f1, 0, file, 11);
+            builder.add_function("f2", Address::Constant(0x2000), 0x8, file, 20);
+
+            let mut sections = Sections::new(EndianVec::new(LittleEndian));
+            builder.write(&mut sections).unwrap();
+
+            let dwarf = read::Dwarf::load(|id| -> read::Result<_> {
+                Ok(read::EndianSlice::new(
+                    sections.get(id).map(|w| w.slice()).unwrap_or_default(),
+                    LittleEndian,
+                ))
+            })
+            .unwrap();
+            let header = dwarf.units().next().unwrap().unwrap();
+            let unit = dwarf.unit(header).unwrap();
+            let unit = unit.unit_ref(&dwarf);
+
+            let mut names = Vec::new();
+            let mut function_ranges = Vec::new();
+            let mut entries = unit.entries();
+            while let Some((_, entry)) = entries.next_dfs().unwrap() {
+                let name = match entry.attr_value(constants::DW_AT_name).unwrap() {
+                    Some(name) => unit.attr_string(name).unwrap().slice().to_vec(),
+                    None => Vec::new(),
+                };
+                names.push((entry.tag(), name));
+                if entry.tag() == constants::DW_TAG_subprogram {
+                    let high_pc = entry.attr_value(constants::DW_AT_high_pc).unwrap();
+                    if version < 4 {
+                        assert!(matches!(high_pc, Some(read::AttributeValue::Addr(_))));
+                    } else {
+                        assert!(matches!(high_pc, Some(read::AttributeValue::Udata(_))));
+                    }
+                    let mut iter = unit.die_ranges(entry).unwrap();
+                    while let Some(range) = iter.next().unwrap() {
+                        function_ranges.push((range.begin, range.end));
+                    }
+                }
+            }
+            assert_eq!(
+                names,
+                [
+                    (constants::DW_TAG_compile_unit, b"jit".to_vec()),
+                    (constants::DW_TAG_base_type, b"int".to_vec()),
+                    (constants::DW_TAG_subprogram, b"f1".to_vec()),
+                    (constants::DW_TAG_formal_parameter, b"x".to_vec()),
+                    (constants::DW_TAG_variable, b"y".to_vec()),
+                    (constants::DW_TAG_subprogram, b"f2".to_vec()),
+                ]
+            );
+
+            let mut ranges = Vec::new();
+            let mut iter = unit.unit_ranges().unwrap();
+            while let Some(range) = iter.next().unwrap() {
+                ranges.push((range.begin, range.end));
+            }
+            assert_eq!(ranges, [(0x1000, 0x1020), (0x2000, 0x2008)]);
+            assert_eq!(function_ranges, ranges);
+
+            let program = unit.line_program.clone().unwrap();
+            let mut rows = program.rows();
+            let mut lines = Vec::new();
+            while let Some((header, row)) = rows.next_row().unwrap() {
+                let file = row.file(header).unwrap();
+                let path = unit.attr_string(file.path_name()).unwrap();
+                assert_eq!(path.slice(), b"a.js");
+                lines.push((
+                    row.address(),
+                    row.line().map(|l| l.get()),
+                    row.end_sequence(),
+                ));
+            }
+            assert_eq!(
+                lines,
+                [
+                    (0x1000, Some(11), false),
+                    (0x1010, Some(12), false),
+                    (0x1020, Some(12), true),
+                    (0x2000, Some(20), false),
+                    (0x2008, Some(20), true),
+                ]
+            );
+        }
+    }
+
+    #[test]
+    fn test_debug_info_builder_invalid_line() {
+        let encoding = Encoding {
+            format: Format::Dwarf32,
+            version: 4,
+            address_size: 8,
+        };
+        for &offset in &[0x20, 0x30] {
+            let mut builder = DebugInfoBuilder::new(encoding, "/tmp", "jit");
+            let file = builder.add_file("", "a.js");
+            let f = builder.add_function("f", Address::Constant(0x1000), 0x20, file, 10);
+            builder.add_line(f, 0, file, 10);
+            builder.add_line(f, offset, file, 11);
+            assert_eq!(builder.finish().err(), Some(Error::InvalidAddress));
+        }
+    }
+}
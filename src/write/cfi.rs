@@ -64,11 +64,92 @@ impl FrameTable {
     }
 
     /// Write the frame table entries to the given `.eh_frame` section.
+    ///
+    /// This does not write the zero terminator that is required by some unwinders.
+    /// See [`EhFrame::write_terminator`].
     pub fn write_eh_frame<W: Writer>(&self, w: &mut EhFrame<W>) -> Result<()> {
         self.write(&mut w.0, true)
     }
 
+    /// Write the frame table entries to the given `.eh_frame` section, and
+    /// return the offsets of the FDEs that were written.
+    ///
+    /// The FDEs are returned in the order that they were added.
+    ///
+    /// This is intended for JIT compilers that write the `.eh_frame` section into
+    /// memory and register it with the unwinder at runtime:
+    ///
+    /// * The offsets can be used to patch the address of each FDE after the
+    ///   final address of the code is known. See [`EhFrame::set_fde_address`].
+    ///
+    /// * Unwinders based on libunwind expect `__register_frame` to be called
+    ///   for each FDE, instead of once for the whole section.
+    ///   The address to register is given by [`FdeOffset::offset`].
+    ///
+    /// * The offsets can be used to write a `.eh_frame_hdr` section.
+    ///   See [`FrameTable::write_eh_frame_hdr`].
+    pub fn write_eh_frame_with_offsets<W: Writer>(
+        &self,
+        w: &mut EhFrame<W>,
+    ) -> Result<Vec<FdeOffset>> {
+        let mut offsets = Vec::with_capacity(self.fdes.len());
+        self.write_fdes(&mut w.0, true, |fde| offsets.push(fde))?;
+        Ok(offsets)
+    }
+
+    /// Write a `.eh_frame_hdr` section containing a binary search table for
+    /// the given FDEs.
+    ///
+    /// `hdr_address` is the address that the `.eh_frame_hdr` section will be loaded at,
+    /// and `eh_frame_address` is the address that the `.eh_frame` section is loaded at.
+    /// The address of each FDE must be a constant address.
+    ///
+    /// The pointers in the header are encoded as 4 byte offsets, so all addresses
+    /// must be within 2GB of `hdr_address`.
+    pub fn write_eh_frame_hdr<W: Writer>(
+        w: &mut W,
+        hdr_address: u64,
+        eh_frame_address: u64,
+        fdes: &[FdeOffset],
+    ) -> Result<()> {
+        let mut table = Vec::with_capacity(fdes.len());
+        for fde in fdes {
+            let address = match fde.address {
+                Address::Constant(address) => address,
+                Address::Symbol { .. } => return Err(Error::InvalidAddress),
+            };
+            let fde_address = eh_frame_address.wrapping_add(fde.offset.0 as u64);
+            table.push((address, fde_address));
+        }
+        table.sort_unstable();
+
+        let start = w.len();
+        w.write_u8(1)?;
+        w.write_u8(constants::DW_EH_PE_pcrel.0 | constants::DW_EH_PE_sdata4.0)?;
+        w.write_u8(constants::DW_EH_PE_udata4.0)?;
+        w.write_u8(constants::DW_EH_PE_datarel.0 | constants::DW_EH_PE_sdata4.0)?;
+        // The `.eh_frame` pointer is relative to the address of the pointer itself.
+        let eh_frame_ptr = hdr_address + (w.len() - start) as u64;
+        w.write_sdata(eh_frame_address.wrapping_sub(eh_frame_ptr) as i64, 4)?;
+        w.write_udata(table.len() as u64, 4)?;
+        // The table entries are relative to the start of the `.eh_frame_hdr` section.
+        for (address, fde_address) in table {
+            w.write_sdata(address.wrapping_sub(hdr_address) as i64, 4)?;
+            w.write_sdata(fde_address.wrapping_sub(hdr_address) as i64, 4)?;
+        }
+        Ok(())
+    }
+
     fn write<W: Writer>(&self, w: &mut W, eh_frame: bool) -> Result<()> {
+        self.write_fdes(w, eh_frame, |_| {})
+    }
+
+    fn write_fdes<W: Writer, F: FnMut(FdeOffset)>(
+        &self,
+        w: &mut W,
+        eh_frame: bool,
+        mut f: F,
+    ) -> Result<()> {
         let mut cie_offsets = vec![None; self.cies.len()];
         for (cie_id, fde) in &self.fdes {
             let cie_index = cie_id.index;
@@ -83,13 +164,93 @@ impl FrameTable {
                 }
             };
 
-            fde.write(w, eh_frame, cie_offset, cie)?;
+            f(fde.write(w, eh_frame, cie_offset, cie)?);
         }
-        // TODO: write length 0 terminator for eh_frame?
         Ok(())
     }
 }
 
+impl<W: Writer> EhFrame<W> {
+    /// Write the zero length terminator that marks the end of the section.
+    ///
+    /// This is required when the section is registered with the unwinder at
+    /// runtime using libgcc's `__register_frame`, which is passed the start of
+    /// the section and reads entries until it finds the terminator.
+    pub fn write_terminator(&mut self) -> Result<()> {
+        self.write_u32(0)
+    }
+
+    /// Set the initial address of an FDE that has already been written.
+    ///
+    /// `fde` must have been returned by [`FrameTable::write_eh_frame_with_offsets`]
+    /// for this section, and `section_address` is the address that this section
+    /// will be loaded at. The address is only used for `DW_EH_PE_pcrel` encodings.
+    ///
+    /// This can be used by JIT compilers to write the FDEs before the final address
+    /// of the code is known.
+    ///
+    /// Only `DW_EH_PE_absptr` and `DW_EH_PE_pcrel` applications with a fixed
+    /// size format are supported.
+    pub fn set_fde_address(
+        &mut self,
+        fde: &mut FdeOffset,
+        address: u64,
+        section_address: u64,
+    ) -> Result<()> {
+        let eh_pe = fde.address_encoding;
+        let val = match eh_pe.application() {
+            constants::DW_EH_PE_absptr => address,
+            constants::DW_EH_PE_pcrel => {
+                address.wrapping_sub(section_address.wrapping_add(fde.address_offset as u64))
+            }
+            _ => return Err(Error::UnsupportedPointerEncoding(eh_pe)),
+        };
+        let (size, signed) = match eh_pe.format() {
+            constants::DW_EH_PE_absptr => (fde.address_size, false),
+            constants::DW_EH_PE_udata2 => (2, false),
+            constants::DW_EH_PE_udata4 => (4, false),
+            constants::DW_EH_PE_udata8 => (8, false),
+            constants::DW_EH_PE_sdata2 => (2, true),
+            constants::DW_EH_PE_sdata4 => (4, true),
+            constants::DW_EH_PE_sdata8 => (8, true),
+            _ => return Err(Error::UnsupportedPointerEncoding(eh_pe)),
+        };
+        let val = if signed && size < 8 {
+            // Check that the value fits, then truncate it to an unsigned value.
+            let bits = u32::from(size) * 8;
+            let signed_val = val as i64;
+            if (signed_val << (64 - bits)) >> (64 - bits) != signed_val {
+                return Err(Error::ValueTooLarge);
+            }
+            val & ((1 << bits) - 1)
+        } else {
+            val
+        };
+        self.write_udata_at(fde.address_offset, val, size)?;
+        fde.address = Address::Constant(address);
+        Ok(())
+    }
+}
+
+/// The location of an FDE that was written to a `.eh_frame` section.
+///
+/// See [`FrameTable::write_eh_frame_with_offsets`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FdeOffset {
+    /// The offset of the start of the FDE within the section.
+    pub offset: EhFrameOffset,
+    /// The offset of the initial address field of the FDE within the section.
+    pub address_offset: usize,
+    /// The pointer encoding of the initial address field.
+    pub address_encoding: constants::DwEhPe,
+    /// The address size used for `DW_EH_PE_absptr` encodings.
+    pub address_size: u8,
+    /// The initial address of the FDE.
+    pub address: Address,
+    /// The length in bytes of the code described by the FDE.
+    pub length: u32,
+}
+
 /// A common information entry. This contains information that is shared between FDEs.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct CommonInformationEntry {
@@ -124,6 +285,11 @@ pub struct CommonInformationEntry {
     /// True for signal trampolines.
     pub signal_trampoline: bool,
 
+    /// Vendor augmentation characters and their augmentation data.
+    ///
+    /// These are written after the augmentation characters defined by `.eh_frame`.
+    pub custom_augmentation: Vec<(u8, Vec<u8>)>,
+
     /// The initial instructions upon entry to this function.
     instructions: Vec<CallFrameInstruction>,
 }
@@ -147,6 +313,7 @@ impl CommonInformationEntry {
             lsda_encoding: None,
             fde_address_encoding: constants::DW_EH_PE_absptr,
             signal_trampoline: false,
+            custom_augmentation: Vec::new(),
             instructions: Vec::new(),
         }
     }
@@ -161,6 +328,7 @@ impl CommonInformationEntry {
             || self.lsda_encoding.is_some()
             || self.signal_trampoline
             || self.fde_address_encoding != constants::DW_EH_PE_absptr
+            || !self.custom_augmentation.is_empty()
     }
 
     /// Returns the section offset of the CIE.
@@ -207,6 +375,9 @@ impl CommonInformationEntry {
             if self.signal_trampoline {
                 w.write_u8(b'S')?;
             }
+            for (ch, _) in &self.custom_augmentation {
+                w.write_u8(*ch)?;
+            }
         }
         w.write_u8(0)?;
 
@@ -244,9 +415,14 @@ impl CommonInformationEntry {
             if self.fde_address_encoding != constants::DW_EH_PE_absptr {
                 w.write_u8(self.fde_address_encoding.0)?;
             }
+            for (_, data) in &self.custom_augmentation {
+                w.write(data)?;
+            }
 
             let augmentation_length = (w.len() - augmentation_length_base) as u64;
-            debug_assert!(augmentation_length < 0x80);
+            if augmentation_length >= 0x80 {
+                return Err(Error::ValueTooLarge);
+            }
             w.write_udata_at(augmentation_length_offset, augmentation_length, 1)?;
         }
 
@@ -279,6 +455,11 @@ pub struct FrameDescriptionEntry {
     /// The address of the LSDA.
     pub lsda: Option<Address>,
 
+    /// Augmentation data for vendor augmentations.
+    ///
+    /// This is written after the LSDA.
+    pub custom_augmentation_data: Vec<u8>,
+
     /// The instructions for this function, ordered by offset.
     instructions: Vec<(u32, CallFrameInstruction)>,
 }
@@ -290,6 +471,7 @@ impl FrameDescriptionEntry {
             address,
             length,
             lsda: None,
+            custom_augmentation_data: Vec::new(),
             instructions: Vec::new(),
         }
     }
@@ -308,8 +490,9 @@ impl FrameDescriptionEntry {
         eh_frame: bool,
         cie_offset: usize,
         cie: &CommonInformationEntry,
-    ) -> Result<()> {
+    ) -> Result<FdeOffset> {
         let encoding = cie.encoding;
+        let offset = w.len();
         let length_offset = w.write_initial_length(encoding.format)?;
         let length_base = w.len();
 
@@ -324,6 +507,7 @@ impl FrameDescriptionEntry {
             )?;
         }
 
+        let address_offset = w.len();
         if cie.fde_address_encoding != constants::DW_EH_PE_absptr {
             w.write_eh_pointer(
                 self.address,
@@ -349,9 +533,12 @@ impl FrameDescriptionEntry {
             if let (Some(lsda), Some(lsda_encoding)) = (self.lsda, cie.lsda_encoding) {
                 w.write_eh_pointer(lsda, lsda_encoding, encoding.address_size)?;
             }
+            w.write(&self.custom_augmentation_data)?;
 
             let augmentation_length = (w.len() - augmentation_length_base) as u64;
-            debug_assert!(augmentation_length < 0x80);
+            if augmentation_length >= 0x80 {
+                return Err(Error::ValueTooLarge);
+            }
             w.write_udata_at(augmentation_length_offset, augmentation_length, 1)?;
         }
 
@@ -371,7 +558,14 @@ impl FrameDescriptionEntry {
         let length = (w.len() - length_base) as u64;
         w.write_initial_length_at(length_offset, length, encoding.format)?;
 
-        Ok(())
+        Ok(FdeOffset {
+            offset: EhFrameOffset(offset),
+            address_offset,
+            address_encoding: cie.fde_address_encoding,
+            address_size: encoding.address_size,
+            address: self.address,
+            length: self.length,
+        })
     }
 }
 
@@ -969,6 +1163,151 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_eh_frame_jit() {
+        use crate::read::UnwindSection;
+
+        let encoding = Encoding {
+            format: Format::Dwarf32,
+            version: 1,
+            address_size: 8,
+        };
+        let mut frames = FrameTable::default();
+        let cie1 = CommonInformationEntry::new(encoding, 1, -8, X86_64::RA);
+        let cie1_id = frames.add_cie(cie1);
+        let mut cie2 = CommonInformationEntry::new(encoding, 1, -8, X86_64::RA);
+        cie2.fde_address_encoding = constants::DW_EH_PE_pcrel | constants::DW_EH_PE_sdata4;
+        let cie2_id = frames.add_cie(cie2);
+        frames.add_fde(
+            cie1_id,
+            FrameDescriptionEntry::new(Address::Constant(0), 0x10),
+        );
+        frames.add_fde(
+            cie2_id,
+            FrameDescriptionEntry::new(Address::Constant(0), 0x20),
+        );
+
+        let mut eh_frame = EhFrame::from(EndianVec::new(LittleEndian));
+        let mut fdes = frames.write_eh_frame_with_offsets(&mut eh_frame).unwrap();
+        eh_frame.write_terminator().unwrap();
+        assert_eq!(fdes.len(), 2);
+        assert_eq!(fdes[0].address_encoding, constants::DW_EH_PE_absptr);
+        assert_eq!(fdes[1].length, 0x20);
+
+        // Patch the addresses after the code has been placed.
+        let eh_frame_address = 0x10_0000;
+        let hdr_address = 0x20_0000;
+        eh_frame
+            .set_fde_address(&mut fdes[0], 0x30_0000, eh_frame_address)
+            .unwrap();
+        eh_frame
+            .set_fde_address(&mut fdes[1], 0x30_1000, eh_frame_address)
+            .unwrap();
+        assert_eq!(fdes[1].address, Address::Constant(0x30_1000));
+        assert_eq!(
+            eh_frame.set_fde_address(&mut fdes[1], 0x1_0000_0000, eh_frame_address),
+            Err(Error::ValueTooLarge)
+        );
+
+        let mut eh_frame_hdr = EndianVec::new(LittleEndian);
+        FrameTable::write_eh_frame_hdr(&mut eh_frame_hdr, hdr_address, eh_frame_address, &fdes)
+            .unwrap();
+
+        let bases = read::BaseAddresses::default()
+            .set_eh_frame(eh_frame_address)
+            .set_eh_frame_hdr(hdr_address);
+        let read_eh_frame = read::EhFrame::new(eh_frame.slice(), LittleEndian);
+        let mut entries = read_eh_frame.entries(&bases);
+        let mut addresses = Vec::new();
+        while let Some(entry) = entries.next().unwrap() {
+            if let read::CieOrFde::Fde(partial) = entry {
+                let fde = partial.parse(read::EhFrame::cie_from_offset).unwrap();
+                addresses.push((fde.offset(), fde.initial_address(), fde.len()));
+            }
+        }
+        assert_eq!(
+            addresses,
+            [
+                (fdes[0].offset.0, 0x30_0000, 0x10),
+                (fdes[1].offset.0, 0x30_1000, 0x20),
+            ]
+        );
+
+        let read_eh_frame_hdr = read::EhFrameHdr::new(eh_frame_hdr.slice(), LittleEndian)
+            .parse(&bases, 8)
+            .unwrap();
+        assert_eq!(
+            read_eh_frame_hdr.eh_frame_ptr(),
+            read::Pointer::Direct(eh_frame_address)
+        );
+        let table = read_eh_frame_hdr.table().unwrap();
+        let fde = table
+            .fde_for_address(
+                &read_eh_frame,
+                &bases,
+                0x30_1004,
+                read::EhFrame::cie_from_offset,
+            )
+            .unwrap();
+        assert_eq!(fde.initial_address(), 0x30_1000);
+        let fde = table
+            .fde_for_address(
+                &read_eh_frame,
+                &bases,
+                0x30_0004,
+                read::EhFrame::cie_from_offset,
+            )
+            .unwrap();
+        assert_eq!(fde.initial_address(), 0x30_0000);
+    }
+
+    #[test]
+    fn test_custom_augmentation() {
+        use crate::read::UnwindSection;
+
+        let encoding = Encoding {
+            format: Format::Dwarf32,
+            version: 1,
+            address_size: 8,
+        };
+        let mut frames = FrameTable::default();
+        let mut cie = CommonInformationEntry::new(encoding, 1, -8, X86_64::RA);
+        cie.lsda_encoding = Some(constants::DW_EH_PE_absptr);
+        cie.custom_augmentation = vec![(b'V', vec![0xaa, 0xbb])];
+        let cie_id = frames.add_cie(cie);
+        let mut fde = FrameDescriptionEntry::new(Address::Constant(0x1000), 0x10);
+        fde.lsda = Some(Address::Constant(0x3300));
+        fde.custom_augmentation_data = vec![0xcc];
+        frames.add_fde(cie_id, fde);
+
+        let mut eh_frame = EhFrame::from(EndianVec::new(LittleEndian));
+        frames.write_eh_frame(&mut eh_frame).unwrap();
+
+        let mut read_eh_frame = read::EhFrame::new(eh_frame.slice(), LittleEndian);
+        read_eh_frame.set_address_size(8);
+        read_eh_frame.set_augmentation_data_size(|ch| if ch == b'V' { Some(2) } else { None });
+        let bases = read::BaseAddresses::default();
+        let mut entries = read_eh_frame.entries(&bases);
+        let cie = match entries.next().unwrap() {
+            Some(read::CieOrFde::Cie(cie)) => cie,
+            _ => panic!("expected CIE"),
+        };
+        let raw = cie.raw_augmentation(&read_eh_frame).unwrap();
+        assert_eq!(raw.string.slice(), b"zLV");
+        assert_eq!(
+            raw.data.unwrap().slice(),
+            &[constants::DW_EH_PE_absptr.0, 0xaa, 0xbb]
+        );
+        let fde = match entries.next().unwrap() {
+            Some(read::CieOrFde::Fde(partial)) => partial
+                .parse(|_, bases, o| read_eh_frame.cie_from_offset(bases, o))
+                .unwrap(),
+            _ => panic!("expected FDE"),
+        };
+        assert_eq!(fde.lsda(), Some(read::Pointer::Direct(0x3300)));
+        assert_eq!(fde.len(), 0x10);
+    }
+
     #[test]
     fn test_frame_instruction() {
         let mut expression = Expression::new();
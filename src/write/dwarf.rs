@@ -1,11 +1,96 @@
 use alloc::vec::Vec;
 
-use crate::common::Encoding;
+use crate::common::{DebugLineOffset, Encoding};
 use crate::write::{
-    AbbreviationTable, LineProgram, LineStringTable, Result, Sections, StringTable, Unit,
-    UnitTable, Writer,
+    AbbreviationTable, DebugLineStrOffsets, DebugStrOffsets, LineProgram, LineStringTable, Result,
+    Sections, StringTable, Unit, UnitTable, Writer,
 };
 
+/// Options that control the layout of the written sections.
+///
+/// The output of the write module is always deterministic: writing the same
+/// DWARF information with the same options produces identical sections.
+/// By default, the layout depends on the order in which items were added:
+///
+/// * Units are written in the order they were added to the `UnitTable`, and
+///   entries are written in depth-first order.
+/// * Strings are written in the order they were first added to the string
+///   tables.
+/// * Each unit has its own abbreviation table, and abbreviation codes are
+///   assigned starting from 1 in order of first use by a depth-first
+///   traversal of the unit's entries.
+/// * Line program instructions are chosen based only on the program's
+///   `LineEncoding` and the difference between each row and the previous row.
+///
+/// These options make the layout of the string and abbreviation tables
+/// independent of insertion order.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct WriteOptions {
+    /// Write the `.debug_str` and `.debug_line_str` strings sorted in byte order.
+    pub sort_strings: bool,
+
+    /// Assign abbreviation codes in sorted order of the abbreviations, instead
+    /// of in order of first use.
+    pub sort_abbreviations: bool,
+
+    /// For DWARF version 4 and earlier, write range and location lists using
+    /// base address selection entries followed by offset pairs.
+    ///
+    /// By default, `StartEnd` and `StartLength` entries are written as
+    /// the begin and end addresses. This is only correct if the base address
+    /// of the unit is 0. With this option, the lists do not depend on the base
+    /// address of the unit, and need fewer relocations.
+    ///
+    /// Lists containing an `OffsetPair` entry that is relative to the base address
+    /// of the unit are written unchanged.
+    pub base_address_selection: bool,
+
+    /// For DWARF version 4 and earlier, write location lists to `.debug_loc`
+    /// using the `DW_LLE_GNU_*` entries of the GNU split DWARF extension.
+    ///
+    /// This should be used when writing a `.dwo` file for a unit that is
+    /// referenced by a skeleton unit with a `DW_AT_GNU_dwo_name` attribute.
+    /// These lists can be read with `read::LocationLists::locations_dwo`.
+    ///
+    /// The entries refer to addresses by index. The addresses are added to
+    /// `Unit::addresses`, which must be written to the `.debug_addr` section of
+    /// the skeleton unit using `AddressTable::write`, and the skeleton unit must
+    /// have a `DW_AT_GNU_addr_base` attribute containing the returned base.
+    ///
+    /// The extension has no offset pairs, so an `OffsetPair` is written as a
+    /// start and length entry using the preceding `BaseAddress` in the list.
+    /// Lists containing an `OffsetPair` that is relative to the base address of
+    /// the unit, or a `DefaultLocation`, can't be written.
+    ///
+    /// This option does not support split range lists. Range lists are written to
+    /// the `.debug_ranges` section as normal, relative to the start of the section.
+    /// Writing range lists for the skeleton unit's `.debug_ranges` section, to be
+    /// used with a `DW_AT_GNU_ranges_base` attribute, is not supported.
+    pub gnu_split_location_lists: bool,
+}
+
+impl WriteOptions {
+    /// Write the string tables using the options.
+    fn write_strings<W: Writer>(
+        &self,
+        sections: &mut Sections<W>,
+        line_strings: &LineStringTable,
+        strings: &StringTable,
+    ) -> Result<(DebugLineStrOffsets, DebugStrOffsets)> {
+        if self.sort_strings {
+            Ok((
+                line_strings.write_sorted(&mut sections.debug_line_str)?,
+                strings.write_sorted(&mut sections.debug_str)?,
+            ))
+        } else {
+            Ok((
+                line_strings.write(&mut sections.debug_line_str)?,
+                strings.write(&mut sections.debug_str)?,
+            ))
+        }
+    }
+}
+
 /// Writable DWARF information for more than one unit.
 #[derive(Debug, Default)]
 pub struct Dwarf {
@@ -34,19 +119,64 @@ impl Dwarf {
     }
 
     /// Write the DWARF information to the given sections.
+    ///
+    /// See [`WriteOptions`] for details of the layout of the sections.
     pub fn write<W: Writer>(&mut self, sections: &mut Sections<W>) -> Result<()> {
-        let line_strings = self.line_strings.write(&mut sections.debug_line_str)?;
-        let strings = self.strings.write(&mut sections.debug_str)?;
-        self.units.write(sections, &line_strings, &strings)?;
+        self.write_with_options(sections, &WriteOptions::default())
+    }
+
+    /// Write the DWARF information to the given sections, using the given options.
+    pub fn write_with_options<W: Writer>(
+        &mut self,
+        sections: &mut Sections<W>,
+        options: &WriteOptions,
+    ) -> Result<()> {
+        let (line_strings, strings) =
+            options.write_strings(sections, &self.line_strings, &self.strings)?;
+        self.units
+            .write_with_options(sections, &line_strings, &strings, options)?;
+        self.write_line_programs_only(sections, &line_strings, &strings)?;
+        Ok(())
+    }
+
+    /// Write only the line number programs in `self.line_programs`, and the
+    /// strings that they use, to the given sections.
+    ///
+    /// `self.units` is ignored, so only the `.debug_line`, `.debug_line_str`
+    /// and `.debug_str` sections are written. This is useful for producers
+    /// that only emit line number information for the code they generate.
+    ///
+    /// Returns the offsets of the line number programs within the `.debug_line`
+    /// section, in the same order as `self.line_programs`. If the sections
+    /// already contained data, then these offsets include it. These offsets
+    /// can be used to reference the programs from other debugging information,
+    /// such as a `DW_AT_stmt_list` attribute written by another producer.
+    pub fn write_line_programs<W: Writer>(
+        &self,
+        sections: &mut Sections<W>,
+        options: &WriteOptions,
+    ) -> Result<Vec<DebugLineOffset>> {
+        let (line_strings, strings) =
+            options.write_strings(sections, &self.line_strings, &self.strings)?;
+        self.write_line_programs_only(sections, &line_strings, &strings)
+    }
+
+    fn write_line_programs_only<W: Writer>(
+        &self,
+        sections: &mut Sections<W>,
+        line_strings: &DebugLineStrOffsets,
+        strings: &DebugStrOffsets,
+    ) -> Result<Vec<DebugLineOffset>> {
+        let mut offsets = Vec::with_capacity(self.line_programs.len());
         for line_program in &self.line_programs {
-            line_program.write(
+            offsets.push(line_program.write(
                 &mut sections.debug_line,
                 line_program.encoding(),
-                &line_strings,
-                &strings,
-            )?;
+                line_strings,
+                strings,
+            )?);
         }
-        Ok(())
+        Ok(offsets)
     }
 }
 
@@ -80,9 +210,20 @@ impl DwarfUnit {
     }
 
     /// Write the DWARf information to the given sections.
+    ///
+    /// See [`WriteOptions`] for details of the layout of the sections.
     pub fn write<W: Writer>(&mut self, sections: &mut Sections<W>) -> Result<()> {
-        let line_strings = self.line_strings.write(&mut sections.debug_line_str)?;
-        let strings = self.strings.write(&mut sections.debug_str)?;
+        self.write_with_options(sections, &WriteOptions::default())
+    }
+
+    /// Write the DWARF information to the given sections, using the given options.
+    pub fn write_with_options<W: Writer>(
+        &mut self,
+        sections: &mut Sections<W>,
+        options: &WriteOptions,
+    ) -> Result<()> {
+        let (line_strings, strings) =
+            options.write_strings(sections, &self.line_strings, &self.strings)?;
 
         let abbrev_offset = sections.debug_abbrev.offset();
         let mut abbrevs = AbbreviationTable::default();
@@ -93,6 +234,7 @@ impl DwarfUnit {
             &mut abbrevs,
             &line_strings,
             &strings,
+            options,
         )?;
         // None should exist because we didn't give out any UnitId.
         assert!(sections.debug_info_refs.is_empty());
@@ -108,7 +250,7 @@ impl DwarfUnit {
 pub(crate) mod convert {
     use super::*;
     use crate::read::{self, Reader};
-    use crate::write::{Address, ConvertResult};
+    use crate::write::{Address, ConvertOptions, ConvertResult};
 
     impl Dwarf {
         /// Create a `write::Dwarf` by converting a `read::Dwarf`.
@@ -121,10 +263,27 @@ pub(crate) mod convert {
         pub fn from<R: Reader<Offset = usize>>(
             dwarf: &read::Dwarf<R>,
             convert_address: &dyn Fn(u64) -> Option<Address>,
+        ) -> ConvertResult<Dwarf> {
+            Self::from_with_options(dwarf, convert_address, &ConvertOptions::default())
+        }
+
+        /// Create a `write::Dwarf` by converting a `read::Dwarf` using the given options.
+        ///
+        /// See [`Dwarf::from`] for details of `convert_address`.
+        pub fn from_with_options<R: Reader<Offset = usize>>(
+            dwarf: &read::Dwarf<R>,
+            convert_address: &dyn Fn(u64) -> Option<Address>,
+            options: &ConvertOptions,
         ) -> ConvertResult<Dwarf> {
             let mut line_strings = LineStringTable::default();
             let mut strings = StringTable::default();
-            let units = UnitTable::from(dwarf, &mut line_strings, &mut strings, convert_address)?;
+            let units = UnitTable::from_with_options(
+                dwarf,
+                &mut line_strings,
+                &mut strings,
+                convert_address,
+                options,
+            )?;
             // TODO: convert the line programs that were not referenced by a unit.
             let line_programs = Vec::new();
             Ok(Dwarf {
@@ -16,6 +16,10 @@ use crate::write::{
 // should be able to ignore instructions they don't support.
 const OPCODE_BASE: u8 = 13;
 
+/// The number of operands for each standard opcode, starting at opcode 1.
+const STANDARD_OPCODE_LENGTHS: [u8; OPCODE_BASE as usize - 1] =
+    [0, 1, 1, 1, 1, 0, 0, 0, 1, 0, 0, 1];
+
 /// A line number program.
 #[derive(Debug, Clone)]
 pub struct LineProgram {
@@ -23,6 +27,7 @@ pub struct LineProgram {
     none: bool,
     encoding: Encoding,
     line_encoding: LineEncoding,
+    segment_selector_size: u8,
 
     /// A list of source directory path names.
     ///
@@ -30,7 +35,10 @@ pub struct LineProgram {
     /// directory of the compilation unit.
     ///
     /// The first entry is for the working directory of the compilation unit.
-    directories: IndexSet<LineString>,
+    ///
+    /// The second element of the key is 0, except for duplicate entries added
+    /// during conversion, which use their index to keep the key unique.
+    directories: IndexSet<(LineString, usize)>,
 
     /// A list of source file entries.
     ///
@@ -40,7 +48,10 @@ pub struct LineProgram {
     /// directory. Otherwise the directory is meaningless.
     ///
     /// Does not include comp_file, even for version >= 5.
-    files: IndexMap<(LineString, DirectoryId), FileInfo>,
+    ///
+    /// The last element of the key is used for duplicates in the same way
+    /// as for `directories`.
+    files: IndexMap<(LineString, DirectoryId, usize), FileInfo>,
 
     /// The primary source file of the compilation unit.
     /// This is required for version >= 5, but we never reference it elsewhere
@@ -71,6 +82,8 @@ pub struct LineProgram {
     row: LineRow,
     // TODO: this probably should be either rows or sequences instead
     instructions: Vec<LineInstruction>,
+    /// The encoded bytes of raw instructions, referenced by `LineInstruction::Raw`.
+    raw_instructions: Vec<u8>,
     in_sequence: bool,
 }
 
@@ -109,12 +122,14 @@ impl LineProgram {
             none: false,
             encoding,
             line_encoding,
+            segment_selector_size: 0,
             directories: IndexSet::new(),
             files: IndexMap::new(),
             comp_file: (comp_file, comp_file_info.unwrap_or_default()),
             prev_row: LineRow::initial_state(line_encoding),
             row: LineRow::initial_state(line_encoding),
             instructions: Vec::new(),
+            raw_instructions: Vec::new(),
             in_sequence: false,
             file_has_timestamp: false,
             file_has_size: false,
@@ -143,12 +158,14 @@ impl LineProgram {
                 address_size: 0,
             },
             line_encoding,
+            segment_selector_size: 0,
             directories: IndexSet::new(),
             files: IndexMap::new(),
             comp_file: (LineString::String(Vec::new()), FileInfo::default()),
             prev_row: LineRow::initial_state(line_encoding),
             row: LineRow::initial_state(line_encoding),
             instructions: Vec::new(),
+            raw_instructions: Vec::new(),
             in_sequence: false,
             file_has_timestamp: false,
             file_has_size: false,
@@ -186,6 +203,21 @@ impl LineProgram {
         self.encoding.format
     }
 
+    /// Return the segment selector size in bytes for this line program.
+    #[inline]
+    pub fn segment_selector_size(&self) -> u8 {
+        self.segment_selector_size
+    }
+
+    /// Set the segment selector size in bytes for this line program.
+    ///
+    /// This must be nonzero in order to use `begin_segmented_sequence`.
+    /// A nonzero size requires version 5 or later.
+    #[inline]
+    pub fn set_segment_selector_size(&mut self, size: u8) {
+        self.segment_selector_size = size;
+    }
+
     /// Return the id for the working directory of the compilation unit.
     #[inline]
     pub fn default_directory(&self) -> DirectoryId {
@@ -203,7 +235,27 @@ impl LineProgram {
     ///
     /// Panics if `directory` is empty or contains a null byte.
     pub fn add_directory(&mut self, directory: LineString) -> DirectoryId {
-        if let LineString::String(ref val) = directory {
+        self.check_directory(&directory);
+        let (index, _) = self.directories.insert_full((directory, 0));
+        DirectoryId(index)
+    }
+
+    /// Add a directory entry, even if it is a duplicate of an existing entry.
+    #[cfg(feature = "read")]
+    fn push_directory(&mut self, directory: LineString) -> DirectoryId {
+        self.check_directory(&directory);
+        let index = self.directories.len();
+        let key = if self.directories.contains(&(directory.clone(), 0)) {
+            (directory, index)
+        } else {
+            (directory, 0)
+        };
+        self.directories.insert(key);
+        DirectoryId(index)
+    }
+
+    fn check_directory(&self, directory: &LineString) {
+        if let LineString::String(ref val) = *directory {
             // For DWARF version <= 4, directories must not be empty.
             // The first directory isn't emitted so skip the check for it.
             if self.encoding.version <= 4 && !self.directories.is_empty() {
@@ -211,8 +263,6 @@ impl LineProgram {
             }
             assert!(!val.contains(&0));
         }
-        let (index, _) = self.directories.insert_full(directory);
-        DirectoryId(index)
     }
 
     /// Get a reference to a directory entry.
@@ -221,7 +271,7 @@ impl LineProgram {
     ///
     /// Panics if `id` is invalid.
     pub fn get_directory(&self, id: DirectoryId) -> &LineString {
-        self.directories.get_index(id.0).unwrap()
+        &self.directories.get_index(id.0).unwrap().0
     }
 
     /// Add a file entry and return its id.
@@ -247,12 +297,8 @@ impl LineProgram {
         directory: DirectoryId,
         info: Option<FileInfo>,
     ) -> FileId {
-        if let LineString::String(ref val) = file {
-            assert!(!val.is_empty());
-            assert!(!val.contains(&0));
-        }
-
-        let key = (file, directory);
+        Self::check_file(&file);
+        let key = (file, directory, 0);
         let index = if let Some(info) = info {
             let (index, _) = self.files.insert_full(key, info);
             index
@@ -265,6 +311,27 @@ impl LineProgram {
         FileId::new(index)
     }
 
+    /// Add a file entry, even if it is a duplicate of an existing entry.
+    #[cfg(feature = "read")]
+    fn push_file(&mut self, file: LineString, directory: DirectoryId, info: FileInfo) -> FileId {
+        Self::check_file(&file);
+        let index = self.files.len();
+        let mut key = (file, directory, 0);
+        if self.files.contains_key(&key) {
+            // Use an index past the end so that it can't be confused with 0.
+            key.2 = index + 1;
+        }
+        self.files.insert(key, info);
+        FileId::new(index)
+    }
+
+    fn check_file(file: &LineString) {
+        if let LineString::String(ref val) = *file {
+            assert!(!val.is_empty());
+            assert!(!val.contains(&0));
+        }
+    }
+
     /// Get a reference to a file entry.
     ///
     /// # Panics
@@ -276,7 +343,7 @@ impl LineProgram {
             Some(index) => self
                 .files
                 .get_index(index)
-                .map(|entry| (&(entry.0).0, (entry.0).1))
+                .map(|(key, _)| (&key.0, key.1))
                 .unwrap(),
         }
     }
@@ -322,6 +389,20 @@ impl LineProgram {
         }
     }
 
+    /// Begin a new sequence and set its segment selector and base address.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a sequence has already begun, or if the segment selector size
+    /// of this line program is 0.
+    pub fn begin_segmented_sequence(&mut self, segment: u64, address: Address) {
+        assert!(!self.in_sequence);
+        assert!(self.segment_selector_size != 0);
+        self.in_sequence = true;
+        self.instructions
+            .push(LineInstruction::SetSegmentedAddress(segment, address));
+    }
+
     /// End the sequence, and reset the row to its default values.
     ///
     /// Only the `address_offset` and op_index` fields of the current row are used.
@@ -343,6 +424,70 @@ impl LineProgram {
         self.row = LineRow::initial_state(self.line_encoding);
     }
 
+    /// Add a raw standard opcode at the current position in the program.
+    ///
+    /// `operands` must be the encoded operands of the opcode. Standard
+    /// opcodes are written with the standard opcode lengths defined by
+    /// DWARF 5, so `operands` must contain that number of ULEB128 values,
+    /// or a single 2 byte value for `DW_LNS_fixed_advance_pc`.
+    ///
+    /// The effect of the opcode on the state machine registers is not
+    /// tracked, so this should not be used for opcodes that change the
+    /// registers used by `generate_row`.
+    ///
+    /// Returns an error if `opcode` is not a standard opcode, or if the
+    /// operands don't match the opcode.
+    pub fn add_raw_standard_opcode(
+        &mut self,
+        opcode: constants::DwLns,
+        operands: &[u8],
+    ) -> Result<()> {
+        if opcode.0 == 0 || opcode.0 >= OPCODE_BASE {
+            return Err(Error::InvalidLineInstruction);
+        }
+        let valid = if opcode == constants::DW_LNS_fixed_advance_pc {
+            operands.len() == 2
+        } else {
+            // Each ULEB128 value ends with a byte that has the high bit clear.
+            let count = operands.iter().filter(|&&byte| byte & 0x80 == 0).count();
+            count == usize::from(STANDARD_OPCODE_LENGTHS[opcode.0 as usize - 1])
+                && operands.last().map_or(true, |&byte| byte & 0x80 == 0)
+        };
+        if !valid {
+            return Err(Error::InvalidLineInstruction);
+        }
+        let start = self.raw_instructions.len();
+        self.raw_instructions.push(opcode.0);
+        self.raw_instructions.extend_from_slice(operands);
+        self.instructions.push(LineInstruction::Raw {
+            start,
+            end: self.raw_instructions.len(),
+        });
+        Ok(())
+    }
+
+    /// Add a raw extended opcode at the current position in the program.
+    ///
+    /// `data` is the payload that follows the opcode, and may be
+    /// of any length. This can be used for vendor extensions
+    /// in the range `DW_LNE_lo_user` to `DW_LNE_hi_user`.
+    ///
+    /// The effect of the opcode on the state machine registers is not
+    /// tracked, so this should not be used for opcodes that change the
+    /// registers used by `generate_row`.
+    pub fn add_raw_extended_opcode(&mut self, opcode: constants::DwLne, data: &[u8]) {
+        let start = self.raw_instructions.len();
+        self.raw_instructions.push(0);
+        // Writing to a `Vec` never fails.
+        leb128::write::unsigned(&mut self.raw_instructions, 1 + data.len() as u64).unwrap();
+        self.raw_instructions.push(opcode.0);
+        self.raw_instructions.extend_from_slice(data);
+        self.instructions.push(LineInstruction::Raw {
+            start,
+            end: self.raw_instructions.len(),
+        });
+    }
+
     /// Return true if a sequence has begun.
     #[inline]
     pub fn in_sequence(&self) -> bool {
@@ -519,8 +664,9 @@ impl LineProgram {
 
         if self.version() >= 5 {
             w.write_u8(self.address_size())?;
-            // Segment selector size.
-            w.write_u8(0)?;
+            w.write_u8(self.segment_selector_size)?;
+        } else if self.segment_selector_size != 0 {
+            return Err(Error::NeedVersion(5));
         }
 
         let header_length_offset = w.len();
@@ -541,11 +687,11 @@ impl LineProgram {
         w.write_u8(self.line_encoding.line_base as u8)?;
         w.write_u8(self.line_encoding.line_range)?;
         w.write_u8(OPCODE_BASE)?;
-        w.write(&[0, 1, 1, 1, 1, 0, 0, 0, 1, 0, 0, 1])?;
+        w.write(&STANDARD_OPCODE_LENGTHS)?;
 
         if self.version() <= 4 {
             // The first directory is stored as DW_AT_comp_dir.
-            for dir in self.directories.iter().skip(1) {
+            for (dir, _) in self.directories.iter().skip(1) {
                 dir.write(
                     w,
                     constants::DW_FORM_string,
@@ -556,7 +702,7 @@ impl LineProgram {
             }
             w.write_u8(0)?;
 
-            for ((file, dir), info) in self.files.iter() {
+            for ((file, dir, _), info) in self.files.iter() {
                 file.write(
                     w,
                     constants::DW_FORM_string,
@@ -573,12 +719,12 @@ impl LineProgram {
             // Directory entry formats (only ever 1).
             w.write_u8(1)?;
             w.write_uleb128(u64::from(constants::DW_LNCT_path.0))?;
-            let dir_form = self.directories.get_index(0).unwrap().form();
+            let dir_form = self.directories.get_index(0).unwrap().0.form();
             w.write_uleb128(dir_form.0.into())?;
 
             // Directory entries.
             w.write_uleb128(self.directories.len() as u64)?;
-            for dir in self.directories.iter() {
+            for (dir, _) in self.directories.iter() {
                 dir.write(
                     w,
                     dir_form,
@@ -635,7 +781,7 @@ impl LineProgram {
                 Ok(())
             };
             write_file(&self.comp_file.0, DirectoryId(0), &self.comp_file.1)?;
-            for ((file, dir), info) in self.files.iter() {
+            for ((file, dir, _), info) in self.files.iter() {
                 write_file(file, *dir, info)?;
             }
         }
@@ -648,7 +794,12 @@ impl LineProgram {
         )?;
 
         for instruction in &self.instructions {
-            instruction.write(w, self.address_size())?;
+            instruction.write(
+                w,
+                self.address_size(),
+                self.segment_selector_size,
+                &self.raw_instructions,
+            )?;
         }
 
         let length = (w.len() - length_base) as u64;
@@ -737,7 +888,9 @@ enum LineInstruction {
     NegateStatement,
     SetBasicBlock,
     ConstAddPc,
-    // DW_LNS_fixed_advance_pc is not supported.
+    // Only generated when converting with `ConvertOptions::exact_line_programs`.
+    #[cfg(feature = "read")]
+    FixedAddPc(u16),
     SetPrologueEnd,
     SetEpilogueBegin,
     SetIsa(u64),
@@ -746,13 +899,27 @@ enum LineInstruction {
     EndSequence,
     // TODO: this doubles the size of this enum.
     SetAddress(Address),
+    SetSegmentedAddress(u64, Address),
     // DW_LNE_define_file is not supported.
     SetDiscriminator(u64),
+
+    // An instruction added by `add_raw_*_opcode`, whose encoding is stored
+    // in `LineProgram::raw_instructions`.
+    Raw {
+        start: usize,
+        end: usize,
+    },
 }
 
 impl LineInstruction {
     /// Write the line number instruction to the given section.
-    fn write<W: Writer>(self, w: &mut DebugLine<W>, address_size: u8) -> Result<()> {
+    fn write<W: Writer>(
+        self,
+        w: &mut DebugLine<W>,
+        address_size: u8,
+        segment_selector_size: u8,
+        raw: &[u8],
+    ) -> Result<()> {
         use self::LineInstruction::*;
         match self {
             Special(val) => w.write_u8(val)?,
@@ -776,6 +943,11 @@ impl LineInstruction {
             NegateStatement => w.write_u8(constants::DW_LNS_negate_stmt.0)?,
             SetBasicBlock => w.write_u8(constants::DW_LNS_set_basic_block.0)?,
             ConstAddPc => w.write_u8(constants::DW_LNS_const_add_pc.0)?,
+            #[cfg(feature = "read")]
+            FixedAddPc(val) => {
+                w.write_u8(constants::DW_LNS_fixed_advance_pc.0)?;
+                w.write_u16(val)?;
+            }
             SetPrologueEnd => w.write_u8(constants::DW_LNS_set_prologue_end.0)?,
             SetEpilogueBegin => w.write_u8(constants::DW_LNS_set_epilogue_begin.0)?,
             SetIsa(val) => {
@@ -793,6 +965,13 @@ impl LineInstruction {
                 w.write_u8(constants::DW_LNE_set_address.0)?;
                 w.write_address(address, address_size)?;
             }
+            SetSegmentedAddress(segment, address) => {
+                w.write_u8(0)?;
+                w.write_uleb128(1 + u64::from(segment_selector_size) + u64::from(address_size))?;
+                w.write_u8(constants::DW_LNE_set_address.0)?;
+                w.write_udata(segment, segment_selector_size)?;
+                w.write_address(address, address_size)?;
+            }
             SetDiscriminator(val) => {
                 let mut bytes = [0u8; 10];
                 // bytes is long enough so this will never fail.
@@ -802,6 +981,7 @@ impl LineInstruction {
                 w.write_u8(constants::DW_LNE_set_discriminator.0)?;
                 w.write(&bytes[..len])?;
             }
+            Raw { start, end } => w.write(&raw[start..end])?,
         }
         Ok(())
     }
@@ -962,18 +1142,40 @@ define_section!(
 mod convert {
     use super::*;
     use crate::read::{self, Reader};
-    use crate::write::{self, ConvertError, ConvertResult};
+    use crate::write::{self, ConvertError, ConvertOptions, ConvertResult};
 
     impl LineProgram {
         /// Create a line number program by reading the data from the given program.
         ///
         /// Return the program and a mapping from file index to `FileId`.
         pub fn from<R: Reader<Offset = usize>>(
+            from_program: read::IncompleteLineProgram<R>,
+            dwarf: &read::Dwarf<R>,
+            line_strings: &mut write::LineStringTable,
+            strings: &mut write::StringTable,
+            convert_address: &dyn Fn(u64) -> Option<Address>,
+        ) -> ConvertResult<(LineProgram, Vec<FileId>)> {
+            Self::from_with_options(
+                from_program,
+                dwarf,
+                line_strings,
+                strings,
+                convert_address,
+                &ConvertOptions::default(),
+            )
+        }
+
+        /// Create a line number program by reading the data from the given program
+        /// using the given options.
+        ///
+        /// Return the program and a mapping from file index to `FileId`.
+        pub fn from_with_options<R: Reader<Offset = usize>>(
             mut from_program: read::IncompleteLineProgram<R>,
             dwarf: &read::Dwarf<R>,
             line_strings: &mut write::LineStringTable,
             strings: &mut write::StringTable,
             convert_address: &dyn Fn(u64) -> Option<Address>,
+            options: &ConvertOptions,
         ) -> ConvertResult<(LineProgram, Vec<FileId>)> {
             // Create mappings in case the source has duplicate files or directories.
             let mut dirs = Vec::new();
@@ -1031,10 +1233,15 @@ mod convert {
                     files.push(FileId::zero());
                 }
 
-                for from_dir in from_header.include_directories() {
+                for (i, from_dir) in from_header.include_directories().iter().enumerate() {
                     let from_dir =
                         LineString::from(from_dir.clone(), dwarf, line_strings, strings)?;
-                    dirs.push(program.add_directory(from_dir));
+                    // For version 5, the first directory is the existing comp_dir entry.
+                    if options.exact_line_programs && (i != 0 || from_header.version() <= 4) {
+                        dirs.push(program.push_directory(from_dir));
+                    } else {
+                        dirs.push(program.add_directory(from_dir));
+                    }
                 }
 
                 program.file_has_timestamp = from_header.file_has_timestamp();
@@ -1048,17 +1255,27 @@ mod convert {
                         return Err(ConvertError::InvalidDirectoryIndex);
                     }
                     let from_dir = dirs[from_dir as usize];
-                    let from_info = Some(FileInfo {
+                    let from_info = FileInfo {
                         timestamp: from_file.timestamp(),
                         size: from_file.size(),
                         md5: *from_file.md5(),
-                    });
-                    files.push(program.add_file(from_name, from_dir, from_info));
+                    };
+                    if options.exact_line_programs {
+                        // Keep duplicate entries so that the indices don't change.
+                        files.push(program.push_file(from_name, from_dir, from_info));
+                    } else {
+                        files.push(program.add_file(from_name, from_dir, Some(from_info)));
+                    }
                 }
 
                 program
             };
 
+            if options.exact_line_programs {
+                program.convert_instructions(from_program, &files, convert_address)?;
+                return Ok((program, files));
+            }
+
             // We can't use the `from_program.rows()` because that wouldn't let
             // us preserve address relocations.
             let mut from_row = read::LineRow::new(from_program.header());
@@ -1076,7 +1293,8 @@ mod convert {
                         }
                         from_row.execute(read::LineInstruction::SetAddress(0), &mut from_program);
                     }
-                    read::LineInstruction::DefineFile(_) => {
+                    read::LineInstruction::SetSegmentedAddress { .. }
+                    | read::LineInstruction::DefineFile(_) => {
                         return Err(ConvertError::UnsupportedLineInstruction);
                     }
                     _ => {
@@ -1123,6 +1341,72 @@ mod convert {
             }
             Ok((program, files))
         }
+
+        /// Convert each instruction of the program to an equivalent instruction.
+        fn convert_instructions<R: Reader<Offset = usize>>(
+            &mut self,
+            from_program: read::IncompleteLineProgram<R>,
+            files: &[FileId],
+            convert_address: &dyn Fn(u64) -> Option<Address>,
+        ) -> ConvertResult<()> {
+            let from_header = from_program.header();
+            let from_opcode_base = from_header.opcode_base();
+            let mut instructions = from_header.instructions();
+            while let Some(instruction) = instructions.next_instruction(from_header)? {
+                let instruction = match instruction {
+                    read::LineInstruction::Special(val) => {
+                        // The opcode base may differ, but the adjusted opcode must not.
+                        let val = u16::from(val - from_opcode_base) + u16::from(OPCODE_BASE);
+                        if val > 255 {
+                            return Err(ConvertError::InexactLineProgram);
+                        }
+                        LineInstruction::Special(val as u8)
+                    }
+                    read::LineInstruction::Copy => LineInstruction::Copy,
+                    read::LineInstruction::AdvancePc(val) => LineInstruction::AdvancePc(val),
+                    read::LineInstruction::AdvanceLine(val) => LineInstruction::AdvanceLine(val),
+                    read::LineInstruction::SetFile(val) => {
+                        if val >= files.len() as u64 {
+                            return Err(ConvertError::InvalidFileIndex);
+                        }
+                        LineInstruction::SetFile(files[val as usize])
+                    }
+                    read::LineInstruction::SetColumn(val) => LineInstruction::SetColumn(val),
+                    read::LineInstruction::NegateStatement => LineInstruction::NegateStatement,
+                    read::LineInstruction::SetBasicBlock => LineInstruction::SetBasicBlock,
+                    read::LineInstruction::ConstAddPc => LineInstruction::ConstAddPc,
+                    read::LineInstruction::FixedAddPc(val) => LineInstruction::FixedAddPc(val),
+                    read::LineInstruction::SetPrologueEnd => LineInstruction::SetPrologueEnd,
+                    read::LineInstruction::SetEpilogueBegin => LineInstruction::SetEpilogueBegin,
+                    read::LineInstruction::SetIsa(val) => LineInstruction::SetIsa(val),
+                    read::LineInstruction::EndSequence => LineInstruction::EndSequence,
+                    read::LineInstruction::SetAddress(val) => match convert_address(val) {
+                        Some(val) => LineInstruction::SetAddress(val),
+                        None => return Err(ConvertError::InvalidAddress),
+                    },
+                    read::LineInstruction::SetDiscriminator(val) => {
+                        LineInstruction::SetDiscriminator(val)
+                    }
+                    read::LineInstruction::UnknownExtended(opcode, data) => {
+                        self.add_raw_extended_opcode(opcode, &data.to_slice()?);
+                        self.in_sequence = true;
+                        continue;
+                    }
+                    read::LineInstruction::UnknownStandard0(_)
+                    | read::LineInstruction::UnknownStandard1(..)
+                    | read::LineInstruction::UnknownStandardN(..) => {
+                        return Err(ConvertError::InexactLineProgram);
+                    }
+                    read::LineInstruction::SetSegmentedAddress { .. }
+                    | read::LineInstruction::DefineFile(_) => {
+                        return Err(ConvertError::UnsupportedLineInstruction);
+                    }
+                };
+                self.in_sequence = instruction != LineInstruction::EndSequence;
+                self.instructions.push(instruction);
+            }
+            Ok(())
+        }
     }
 
     impl LineString {
@@ -1155,7 +1439,7 @@ mod convert {
 mod tests {
     use super::*;
     use crate::read;
-    use crate::write::{DebugLineStr, DebugStr, EndianVec, StringTable};
+    use crate::write::{ConvertOptions, DebugLineStr, DebugStr, EndianVec, StringTable};
     use crate::LittleEndian;
 
     #[test]
@@ -1827,6 +2111,261 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_raw_opcodes() {
+        let encoding = Encoding {
+            format: Format::Dwarf32,
+            version: 4,
+            address_size: 8,
+        };
+        let mut program = LineProgram::new(
+            encoding,
+            LineEncoding::default(),
+            LineString::String(b"dir1".to_vec()),
+            LineString::String(b"file1".to_vec()),
+            None,
+        );
+        program.begin_sequence(Some(Address::Constant(0x1000)));
+        program.add_raw_extended_opcode(constants::DW_LNE_lo_user, &[1, 2, 3]);
+        // A pair of opcodes that leaves the registers unchanged.
+        program
+            .add_raw_standard_opcode(constants::DW_LNS_negate_stmt, &[])
+            .unwrap();
+        program
+            .add_raw_standard_opcode(constants::DW_LNS_negate_stmt, &[])
+            .unwrap();
+        for (opcode, operands) in [
+            (constants::DwLns(0), &[][..]),
+            (constants::DwLns(OPCODE_BASE), &[]),
+            (constants::DW_LNS_copy, &[1]),
+            (constants::DW_LNS_advance_pc, &[]),
+            (constants::DW_LNS_advance_pc, &[0x80]),
+            (constants::DW_LNS_advance_pc, &[1, 2]),
+            (constants::DW_LNS_fixed_advance_pc, &[1]),
+        ] {
+            assert_eq!(
+                program.add_raw_standard_opcode(opcode, operands),
+                Err(Error::InvalidLineInstruction)
+            );
+        }
+        program.generate_row();
+        program.end_sequence(0x20);
+
+        let mut debug_line = DebugLine::from(EndianVec::new(LittleEndian));
+        let debug_line_offset = program
+            .write(
+                &mut debug_line,
+                encoding,
+                &DebugLineStrOffsets::none(),
+                &DebugStrOffsets::none(),
+            )
+            .unwrap();
+
+        let read_debug_line = read::DebugLine::new(debug_line.slice(), LittleEndian);
+        let read_program = read_debug_line
+            .program(debug_line_offset, 8, None, None)
+            .unwrap();
+        let read_header = read_program.header();
+        let mut read_insts = read_header.instructions();
+        let mut next = || read_insts.next_instruction(read_header).unwrap().unwrap();
+        assert_eq!(next(), read::LineInstruction::SetAddress(0x1000));
+        assert_eq!(
+            next(),
+            read::LineInstruction::UnknownExtended(
+                constants::DW_LNE_lo_user,
+                read::EndianSlice::new(&[1, 2, 3], LittleEndian)
+            )
+        );
+        assert_eq!(next(), read::LineInstruction::NegateStatement);
+        assert_eq!(next(), read::LineInstruction::NegateStatement);
+
+        let mut rows = read_program.rows();
+        let (_, row) = rows.next_row().unwrap().unwrap();
+        assert_eq!(row.address(), 0x1000);
+        assert!(row.is_stmt());
+        let (_, row) = rows.next_row().unwrap().unwrap();
+        assert!(row.end_sequence());
+        assert_eq!(row.address(), 0x1020);
+    }
+
+    #[test]
+    fn test_exact_line_program() {
+        let encoding = Encoding {
+            format: Format::Dwarf32,
+            version: 4,
+            address_size: 8,
+        };
+        let mut program = LineProgram::new(
+            encoding,
+            LineEncoding::default(),
+            LineString::String(b"dir1".to_vec()),
+            LineString::String(b"file1".to_vec()),
+            None,
+        );
+        let dir2 = program.add_directory(LineString::String(b"dir2".to_vec()));
+        program.add_file(LineString::String(b"file2".to_vec()), dir2, None);
+        // Duplicate entries must be preserved.
+        let dir3 = program.push_directory(LineString::String(b"dir2".to_vec()));
+        assert_ne!(dir2, dir3);
+        program.push_file(
+            LineString::String(b"file2".to_vec()),
+            dir3,
+            FileInfo::default(),
+        );
+        program.push_file(
+            LineString::String(b"file2".to_vec()),
+            dir3,
+            FileInfo::default(),
+        );
+        program.begin_sequence(Some(Address::Constant(0x1000)));
+        // Instructions that are not generated by `generate_row`.
+        // The address changes aren't tracked, so the following rows
+        // must keep an address offset of 0.
+        program
+            .add_raw_standard_opcode(constants::DW_LNS_advance_pc, &[4])
+            .unwrap();
+        program
+            .add_raw_standard_opcode(constants::DW_LNS_copy, &[])
+            .unwrap();
+        program
+            .add_raw_standard_opcode(constants::DW_LNS_negate_stmt, &[])
+            .unwrap();
+        program
+            .add_raw_standard_opcode(constants::DW_LNS_negate_stmt, &[])
+            .unwrap();
+        program.add_raw_extended_opcode(constants::DW_LNE_set_discriminator, &[0]);
+        program
+            .add_raw_standard_opcode(constants::DW_LNS_fixed_advance_pc, &[0x10, 0])
+            .unwrap();
+        program
+            .add_raw_standard_opcode(constants::DW_LNS_set_file, &[1])
+            .unwrap();
+        program.generate_row();
+        program.end_sequence(0);
+
+        let mut debug_line = DebugLine::from(EndianVec::new(LittleEndian));
+        let debug_line_offset = program
+            .write(
+                &mut debug_line,
+                encoding,
+                &DebugLineStrOffsets::none(),
+                &DebugStrOffsets::none(),
+            )
+            .unwrap();
+
+        let read_debug_line = read::DebugLine::new(debug_line.slice(), LittleEndian);
+        let read_program = read_debug_line
+            .program(debug_line_offset, 8, None, None)
+            .unwrap();
+        let dwarf = read::Dwarf::default();
+        let convert = |exact_line_programs| {
+            let (convert_program, convert_files) = LineProgram::from_with_options(
+                read_program.clone(),
+                &dwarf,
+                &mut LineStringTable::default(),
+                &mut StringTable::default(),
+                &|address| Some(Address::Constant(address)),
+                &ConvertOptions {
+                    exact_line_programs,
+                },
+            )
+            .unwrap();
+            let mut convert_line = DebugLine::from(EndianVec::new(LittleEndian));
+            convert_program
+                .write(
+                    &mut convert_line,
+                    encoding,
+                    &DebugLineStrOffsets::none(),
+                    &DebugStrOffsets::none(),
+                )
+                .unwrap();
+            (convert_line.slice().to_vec(), convert_files)
+        };
+
+        let (convert_line, convert_files) = convert(true);
+        assert_eq!(convert_line, debug_line.slice());
+        assert_eq!(
+            convert_files,
+            [
+                FileId::zero(),
+                FileId::new(0),
+                FileId::new(1),
+                FileId::new(2)
+            ]
+        );
+
+        let (convert_line, _) = convert(false);
+        assert_ne!(convert_line, debug_line.slice());
+    }
+
+    #[test]
+    fn test_segmented_address() {
+        let mut encoding = Encoding {
+            format: Format::Dwarf32,
+            version: 5,
+            address_size: 4,
+        };
+        let mut program = LineProgram::new(
+            encoding,
+            LineEncoding::default(),
+            LineString::String(b"dir1".to_vec()),
+            LineString::String(b"file1".to_vec()),
+            None,
+        );
+        program.set_segment_selector_size(2);
+        program.begin_segmented_sequence(3, Address::Constant(0x1000));
+        program.generate_row();
+        program.end_sequence(0x20);
+
+        let mut debug_line = DebugLine::from(EndianVec::new(LittleEndian));
+        let debug_line_offset = program
+            .write(
+                &mut debug_line,
+                encoding,
+                &DebugLineStrOffsets::none(),
+                &DebugStrOffsets::none(),
+            )
+            .unwrap();
+
+        let read_debug_line = read::DebugLine::new(debug_line.slice(), LittleEndian);
+        let read_program = read_debug_line
+            .program(debug_line_offset, 4, None, None)
+            .unwrap();
+        let read_header = read_program.header();
+        assert_eq!(read_header.segment_selector_size(), 2);
+        let mut read_insts = read_header.instructions();
+        assert_eq!(
+            read_insts.next_instruction(read_header).unwrap(),
+            Some(read::LineInstruction::SetSegmentedAddress {
+                segment: 3,
+                address: 0x1000
+            })
+        );
+
+        let mut rows = read_program.rows();
+        let (_, row) = rows.next_row().unwrap().unwrap();
+        assert_eq!(row.segment(), 3);
+        assert_eq!(row.address(), 0x1000);
+        let (_, row) = rows.next_row().unwrap().unwrap();
+        assert!(row.end_sequence());
+        assert_eq!(row.segment(), 3);
+        assert_eq!(row.address(), 0x1020);
+
+        // Segment selectors can't be encoded before version 5.
+        encoding.version = 4;
+        program.encoding.version = 4;
+        let mut debug_line = DebugLine::from(EndianVec::new(LittleEndian));
+        assert_eq!(
+            program.write(
+                &mut debug_line,
+                encoding,
+                &DebugLineStrOffsets::none(),
+                &DebugStrOffsets::none(),
+            ),
+            Err(Error::NeedVersion(5))
+        );
+    }
+
     #[test]
     fn test_line_string() {
         let version = 5;
@@ -1954,4 +2493,48 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_write_line_programs() {
+        let encoding = Encoding {
+            format: Format::Dwarf32,
+            version: 5,
+            address_size: 4,
+        };
+        let mut dwarf = crate::write::Dwarf::new();
+        for name in [&b"a.s"[..], b"b.s"] {
+            let dir = LineString::LineStringRef(dwarf.line_strings.add(&b"dir"[..]));
+            let file = LineString::LineStringRef(dwarf.line_strings.add(name));
+            let mut program = LineProgram::new(encoding, LineEncoding::default(), dir, file, None);
+            program.begin_sequence(Some(Address::Constant(0x1000)));
+            program.generate_row();
+            program.end_sequence(0x10);
+            dwarf.line_programs.push(program);
+        }
+
+        let mut sections = crate::write::Sections::new(EndianVec::new(LittleEndian));
+        let offsets = dwarf
+            .write_line_programs(&mut sections, &crate::write::WriteOptions::default())
+            .unwrap();
+        assert_eq!(offsets.len(), 2);
+        assert!(sections.debug_info.slice().is_empty());
+        assert!(sections.debug_abbrev.slice().is_empty());
+
+        let read_debug_line = read::DebugLine::new(sections.debug_line.slice(), LittleEndian);
+        let read_debug_line_str =
+            read::DebugLineStr::new(sections.debug_line_str.slice(), LittleEndian);
+        for (offset, name) in offsets.iter().zip([&b"a.s"[..], b"b.s"]) {
+            let read_program = read_debug_line.program(*offset, 4, None, None).unwrap();
+            let file = read_program.header().file(0).unwrap();
+            match file.path_name() {
+                read::AttributeValue::DebugLineStrRef(offset) => {
+                    assert_eq!(read_debug_line_str.get_str(offset).unwrap().slice(), name);
+                }
+                otherwise => panic!("Unexpected path name: {:?}", otherwise),
+            }
+            let mut rows = read_program.rows();
+            let (_, row) = rows.next_row().unwrap().unwrap();
+            assert_eq!(row.address(), 0x1000);
+        }
+    }
 }
@@ -3,9 +3,10 @@ use indexmap::IndexSet;
 use std::ops::{Deref, DerefMut};
 
 use crate::common::{Encoding, LocationListsOffset, SectionId};
+use crate::write::range::{write_base_address, BaseAddressSelection};
 use crate::write::{
-    Address, BaseId, DebugInfoReference, Error, Expression, Result, Section, Sections, UnitOffsets,
-    Writer,
+    Address, AddressTable, BaseId, DebugInfoReference, Error, Expression, Result, Section,
+    Sections, UnitOffsets, WriteOptions, Writer,
 };
 
 define_section!(
@@ -30,6 +31,10 @@ define_id!(
 );
 
 /// A table of location lists that will be stored in a `.debug_loc` or `.debug_loclists` section.
+///
+/// The list entries do not contain segment selectors. The segment of the
+/// addresses is given by the `DW_AT_segment` attribute of the DIE, or for
+/// indexed addresses, by the segment selector in the `AddressTable`.
 #[derive(Debug, Default)]
 pub struct LocationListTable {
     base_id: BaseId,
@@ -44,22 +49,34 @@ impl LocationListTable {
     }
 
     /// Write the location list table to the appropriate section for the given DWARF version.
+    ///
+    /// `addresses` is only used when writing GNU split DWARF location lists.
     pub(crate) fn write<W: Writer>(
         &self,
         sections: &mut Sections<W>,
+        addresses: &mut AddressTable,
         encoding: Encoding,
         unit_offsets: Option<&UnitOffsets>,
+        options: &WriteOptions,
     ) -> Result<LocationListOffsets> {
         if self.locations.is_empty() {
             return Ok(LocationListOffsets::none());
         }
 
         match encoding.version {
+            2..=4 if options.gnu_split_location_lists => self.write_loc_gnu_split(
+                &mut sections.debug_loc,
+                &mut sections.debug_loc_refs,
+                addresses,
+                encoding,
+                unit_offsets,
+            ),
             2..=4 => self.write_loc(
                 &mut sections.debug_loc,
                 &mut sections.debug_loc_refs,
                 encoding,
                 unit_offsets,
+                options.base_address_selection,
             ),
             5 => self.write_loclists(
                 &mut sections.debug_loclists,
@@ -78,20 +95,65 @@ impl LocationListTable {
         refs: &mut Vec<DebugInfoReference>,
         encoding: Encoding,
         unit_offsets: Option<&UnitOffsets>,
+        base_address_selection: bool,
     ) -> Result<LocationListOffsets> {
         let address_size = encoding.address_size;
         let mut offsets = Vec::new();
         for loc_list in self.locations.iter() {
             offsets.push(w.offset());
+            if base_address_selection && loc_list.is_independent_of_unit_base() {
+                let mut base = BaseAddressSelection::default();
+                for loc in &loc_list.0 {
+                    let (begin, end, data) = match *loc {
+                        Location::BaseAddress { address } => {
+                            base.set(address);
+                            write_base_address(&mut w.0, address, address_size)?;
+                            continue;
+                        }
+                        Location::OffsetPair {
+                            begin,
+                            end,
+                            ref data,
+                        } => {
+                            if begin == end {
+                                return Err(Error::InvalidRange);
+                            }
+                            w.write_udata(begin, address_size)?;
+                            w.write_udata(end, address_size)?;
+                            write_expression(&mut w.0, refs, encoding, unit_offsets, data)?;
+                            continue;
+                        }
+                        Location::StartEnd {
+                            begin,
+                            end,
+                            ref data,
+                        } => (begin, end, data),
+                        Location::StartLength {
+                            begin,
+                            length,
+                            ref data,
+                        } => (begin, begin.add(length), data),
+                        Location::DefaultLocation { .. } => {
+                            return Err(Error::InvalidRange);
+                        }
+                    };
+                    if begin == end {
+                        return Err(Error::InvalidRange);
+                    }
+                    base.write_range(&mut w.0, begin, end, address_size)?;
+                    write_expression(&mut w.0, refs, encoding, unit_offsets, data)?;
+                }
+                w.write_udata(0, address_size)?;
+                w.write_udata(0, address_size)?;
+                continue;
+            }
             for loc in &loc_list.0 {
                 // Note that we must ensure none of the ranges have both begin == 0 and end == 0.
                 // We do this by ensuring that begin != end, which is a bit more restrictive
                 // than required, but still seems reasonable.
                 match *loc {
                     Location::BaseAddress { address } => {
-                        let marker = !0 >> (64 - address_size * 8);
-                        w.write_udata(marker, address_size)?;
-                        w.write_address(address, address_size)?;
+                        write_base_address(&mut w.0, address, address_size)?;
                     }
                     Location::OffsetPair {
                         begin,
@@ -122,13 +184,7 @@ impl LocationListTable {
                         length,
                         ref data,
                     } => {
-                        let end = match begin {
-                            Address::Constant(begin) => Address::Constant(begin + length),
-                            Address::Symbol { symbol, addend } => Address::Symbol {
-                                symbol,
-                                addend: addend + length as i64,
-                            },
-                        };
+                        let end = begin.add(length);
                         if begin == end {
                             return Err(Error::InvalidRange);
                         }
@@ -150,6 +206,80 @@ impl LocationListTable {
         })
     }
 
+    /// Write the location list table to the `.debug_loc` section, using the
+    /// `DW_LLE_GNU_*` encodings of the GNU split DWARF extension.
+    ///
+    /// The addresses are written as indices into `addresses`.
+    fn write_loc_gnu_split<W: Writer>(
+        &self,
+        w: &mut DebugLoc<W>,
+        refs: &mut Vec<DebugInfoReference>,
+        addresses: &mut AddressTable,
+        encoding: Encoding,
+        unit_offsets: Option<&UnitOffsets>,
+    ) -> Result<LocationListOffsets> {
+        let mut offsets = Vec::new();
+        for loc_list in self.locations.iter() {
+            offsets.push(w.offset());
+            // The extension has no offset pairs, so they are converted to
+            // start/length entries using the preceding base address entry.
+            let mut base = None;
+            for loc in &loc_list.0 {
+                let (begin, length, data) = match *loc {
+                    Location::BaseAddress { address } => {
+                        base = Some(address);
+                        w.write_u8(crate::constants::DW_LLE_GNU_base_address_selection_entry.0)?;
+                        w.write_uleb128(addresses.add(address).0 as u64)?;
+                        continue;
+                    }
+                    Location::OffsetPair {
+                        begin,
+                        end,
+                        ref data,
+                    } => {
+                        let base = base.ok_or(Error::InvalidRange)?;
+                        if begin > end {
+                            return Err(Error::InvalidRange);
+                        }
+                        (base.add(begin), end - begin, data)
+                    }
+                    Location::StartEnd {
+                        begin,
+                        end,
+                        ref data,
+                    } => {
+                        w.write_u8(crate::constants::DW_LLE_GNU_start_end_entry.0)?;
+                        w.write_uleb128(addresses.add(begin).0 as u64)?;
+                        w.write_uleb128(addresses.add(end).0 as u64)?;
+                        write_expression(&mut w.0, refs, encoding, unit_offsets, data)?;
+                        continue;
+                    }
+                    Location::StartLength {
+                        begin,
+                        length,
+                        ref data,
+                    } => (begin, length, data),
+                    Location::DefaultLocation { .. } => {
+                        return Err(Error::InvalidRange);
+                    }
+                };
+                w.write_u8(crate::constants::DW_LLE_GNU_start_length_entry.0)?;
+                w.write_uleb128(addresses.add(begin).0 as u64)?;
+                // The length is a fixed 4 byte value.
+                if length > u64::from(u32::MAX) {
+                    return Err(Error::ValueTooLarge);
+                }
+                w.write_u32(length as u32)?;
+                write_expression(&mut w.0, refs, encoding, unit_offsets, data)?;
+            }
+            w.write_u8(crate::constants::DW_LLE_GNU_end_of_list_entry.0)?;
+        }
+        Ok(LocationListOffsets {
+            base_id: self.base_id,
+            offsets,
+        })
+    }
+
     /// Write the location list table to the `.debug_loclists` section.
     fn write_loclists<W: Writer>(
         &self,
@@ -235,6 +365,20 @@ impl LocationListTable {
 #[derive(Clone, Debug, Eq, PartialEq, Hash)]
 pub struct LocationList(pub Vec<Location>);
 
+impl LocationList {
+    /// Return true if no offset pairs are relative to the base address of the unit.
+    fn is_independent_of_unit_base(&self) -> bool {
+        for loc in &self.0 {
+            match loc {
+                Location::BaseAddress { .. } => return true,
+                Location::OffsetPair { .. } => return false,
+                _ => {}
+            }
+        }
+        true
+    }
+}
+
 /// A single location.
 #[derive(Clone, Debug, Eq, PartialEq, Hash)]
 pub enum Location {
@@ -427,8 +571,8 @@ mod convert {
 mod tests {
     use super::*;
     use crate::common::{
-        DebugAbbrevOffset, DebugAddrBase, DebugInfoOffset, DebugLocListsBase, DebugRngListsBase,
-        DebugStrOffsetsBase, Format,
+        DebugAbbrevOffset, DebugAddrBase, DebugAddrIndex, DebugInfoOffset, DebugLocListsBase,
+        DebugRngListsBase, DebugStrOffsetsBase, Format,
     };
     use crate::read;
     use crate::write::{
@@ -484,7 +628,15 @@ mod tests {
                     let loc_list_id = locations.add(loc_list.clone());
 
                     let mut sections = Sections::new(EndianVec::new(LittleEndian));
-                    let loc_list_offsets = locations.write(&mut sections, encoding, None).unwrap();
+                    let loc_list_offsets = locations
+                        .write(
+                            &mut sections,
+                            &mut AddressTable::default(),
+                            encoding,
+                            None,
+                            &WriteOptions::default(),
+                        )
+                        .unwrap();
                     assert!(sections.debug_loc_refs.is_empty());
                     assert!(sections.debug_loclists_refs.is_empty());
 
@@ -547,4 +699,173 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_loc_list_v4_options() {
+        let encoding = Encoding {
+            format: Format::Dwarf32,
+            version: 4,
+            address_size: 4,
+        };
+        let mut expression = Expression::new();
+        expression.op_constu(0);
+        let expression_bytes = [crate::constants::DW_OP_lit0.0];
+        let read_expression =
+            read::Expression(read::EndianSlice::new(&expression_bytes, LittleEndian));
+
+        let mut locations = LocationListTable::default();
+        let loc_list_id = locations.add(LocationList(vec![
+            Location::StartLength {
+                begin: Address::Constant(0x1000),
+                length: 0x10,
+                data: expression.clone(),
+            },
+            Location::StartEnd {
+                begin: Address::Constant(0x1020),
+                end: Address::Constant(0x1030),
+                data: expression.clone(),
+            },
+        ]));
+
+        let mut sections = Sections::new(EndianVec::new(LittleEndian));
+        let options = WriteOptions {
+            base_address_selection: true,
+            ..Default::default()
+        };
+        let offsets = locations
+            .write(
+                &mut sections,
+                &mut AddressTable::default(),
+                encoding,
+                None,
+                &options,
+            )
+            .unwrap();
+        assert_eq!(offsets.get(loc_list_id), LocationListsOffset(0));
+
+        let read_loc = read::LocationLists::new(
+            read::DebugLoc::new(sections.debug_loc.slice(), LittleEndian),
+            read::DebugLocLists::new(&[], LittleEndian),
+        );
+        let mut iter = read_loc
+            .raw_locations(LocationListsOffset(0), encoding)
+            .unwrap();
+        let mut entries = Vec::new();
+        while let Some(entry) = iter.next().unwrap() {
+            entries.push(match entry {
+                read::RawLocListEntry::BaseAddress { addr } => (addr, 0, None),
+                read::RawLocListEntry::AddressOrOffsetPair { begin, end, data } => {
+                    (begin, end, Some(data))
+                }
+                otherwise => panic!("Unexpected entry = {:?}", otherwise),
+            });
+        }
+        assert_eq!(
+            entries,
+            [
+                (0x1000, 0, None),
+                (0, 0x10, Some(read_expression)),
+                (0x20, 0x30, Some(read_expression)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_loc_list_gnu_split() {
+        let encoding = Encoding {
+            format: Format::Dwarf32,
+            version: 4,
+            address_size: 4,
+        };
+        let mut expression = Expression::new();
+        expression.op_constu(0);
+
+        let mut locations = LocationListTable::default();
+        let loc_list_id = locations.add(LocationList(vec![
+            Location::StartLength {
+                begin: Address::Constant(0x1000),
+                length: 0x10,
+                data: expression.clone(),
+            },
+            Location::StartEnd {
+                begin: Address::Constant(0x1020),
+                end: Address::Constant(0x1030),
+                data: expression.clone(),
+            },
+            Location::BaseAddress {
+                address: Address::Constant(0x1000),
+            },
+            Location::OffsetPair {
+                begin: 0x40,
+                end: 0x48,
+                data: expression.clone(),
+            },
+        ]));
+
+        let mut sections = Sections::new(EndianVec::new(LittleEndian));
+        let mut addresses = AddressTable::default();
+        let options = WriteOptions {
+            gnu_split_location_lists: true,
+            ..Default::default()
+        };
+        let offsets = locations
+            .write(&mut sections, &mut addresses, encoding, None, &options)
+            .unwrap();
+        assert_eq!(offsets.get(loc_list_id), LocationListsOffset(0));
+
+        #[rustfmt::skip]
+        let expected = [
+            // DW_LLE_GNU_start_length_entry, index, 4 byte length,
+            // 2 byte expression length, expression.
+            0x03, 0x00, 0x10, 0x00, 0x00, 0x00, 0x01, 0x00, 0x30,
+            // DW_LLE_GNU_start_end_entry, index, index, expression.
+            0x02, 0x01, 0x02, 0x01, 0x00, 0x30,
+            // DW_LLE_GNU_base_address_selection_entry, index.
+            0x01, 0x00,
+            // The offset pair, converted to DW_LLE_GNU_start_length_entry.
+            0x03, 0x03, 0x08, 0x00, 0x00, 0x00, 0x01, 0x00, 0x30,
+            // DW_LLE_GNU_end_of_list_entry.
+            0x00,
+        ];
+        assert_eq!(sections.debug_loc.slice(), expected);
+        assert_eq!(addresses.count(), 4);
+        assert_eq!(addresses.get(DebugAddrIndex(3)), Address::Constant(0x1040));
+
+        let base = addresses.write(&mut sections.debug_addr, encoding).unwrap();
+        let read_loc = read::LocationLists::new(
+            read::DebugLoc::new(sections.debug_loc.slice(), LittleEndian),
+            read::DebugLocLists::new(&[], LittleEndian),
+        );
+        let read_debug_addr = read::DebugAddr::from(read::EndianSlice::new(
+            sections.debug_addr.slice(),
+            LittleEndian,
+        ));
+        let mut iter = read_loc
+            .locations_dwo(LocationListsOffset(0), encoding, 0, &read_debug_addr, base)
+            .unwrap();
+        let mut ranges = Vec::new();
+        while let Some(entry) = iter.next().unwrap() {
+            ranges.push((entry.range.begin, entry.range.end));
+        }
+        assert_eq!(
+            ranges,
+            [(0x1000, 0x1010), (0x1020, 0x1030), (0x1040, 0x1048)]
+        );
+
+        // Offset pairs that are relative to the base address of the unit
+        // can't be written.
+        let mut locations = LocationListTable::default();
+        locations.add(LocationList(vec![Location::OffsetPair {
+            begin: 0x40,
+            end: 0x48,
+            data: expression,
+        }]));
+        let mut sections = Sections::new(EndianVec::new(LittleEndian));
+        assert_eq!(
+            locations
+                .write(&mut sections, &mut addresses, encoding, None, &options)
+                .err(),
+            Some(Error::InvalidRange)
+        );
+    }
 }
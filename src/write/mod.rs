@@ -16,6 +16,10 @@
 //! instance, and then use [`Dwarf::from`](./struct.Dwarf.html#method.from) to convert
 //! it to a writable instance.
 //!
+//! To change a few attribute values without converting everything, use
+//! [`rewrite_debug_info`](./fn.rewrite_debug_info.html) instead. This copies the
+//! existing sections and only re-encodes the entries that change.
+//!
 //! ## Example Usage
 //!
 //! Write a compilation unit containing only the top level DIE.
@@ -138,6 +142,12 @@ macro_rules! define_offsets {
 mod abbrev;
 pub use self::abbrev::*;
 
+mod addr;
+pub use self::addr::*;
+
+mod builder;
+pub use self::builder::*;
+
 mod cfi;
 pub use self::cfi::*;
 
@@ -156,6 +166,11 @@ pub use self::op::*;
 mod range;
 pub use self::range::*;
 
+#[cfg(feature = "read")]
+mod rewrite;
+#[cfg(feature = "read")]
+pub use self::rewrite::*;
+
 mod str;
 pub use self::str::*;
 
@@ -201,6 +216,10 @@ pub enum Error {
     UnsupportedCfiExpressionReference,
     /// Unsupported forward reference in expression.
     UnsupportedExpressionForwardReference,
+    /// The piece of a composite location description is invalid.
+    InvalidPiece,
+    /// The line number program instruction is invalid.
+    InvalidLineInstruction,
 }
 
 impl fmt::Display for Error {
@@ -254,6 +273,13 @@ impl fmt::Display for Error {
             Error::UnsupportedExpressionForwardReference => {
                 write!(f, "Unsupported forward reference in expression.")
             }
+            Error::InvalidPiece => write!(
+                f,
+                "The piece of a composite location description is invalid."
+            ),
+            Error::InvalidLineInstruction => {
+                write!(f, "The line number program instruction is invalid.")
+            }
         }
     }
 }
@@ -282,6 +308,19 @@ pub enum Address {
     },
 }
 
+impl Address {
+    /// Return the address that is `offset` bytes after this address.
+    pub(crate) fn add(self, offset: u64) -> Address {
+        match self {
+            Address::Constant(address) => Address::Constant(address + offset),
+            Address::Symbol { symbol, addend } => Address::Symbol {
+                symbol,
+                addend: addend + offset as i64,
+            },
+        }
+    }
+}
+
 /// A reference to a `.debug_info` entry.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Reference {
@@ -325,6 +364,8 @@ mod convert {
     pub enum ConvertError {
         /// An error occurred when reading.
         Read(read::Error),
+        /// An error occurred when writing.
+        Write(Error),
         /// Writing of this attribute value is not implemented yet.
         UnsupportedAttributeValue,
         /// This attribute value is an invalid name/form combination.
@@ -361,6 +402,12 @@ mod convert {
         InvalidBranchTarget,
         /// Writing this unit type is not supported yet.
         UnsupportedUnitType,
+        /// A line number program cannot be converted exactly.
+        ///
+        /// This is only returned when `ConvertOptions::exact_line_programs` is set.
+        InexactLineProgram,
+        /// Updating this reference to a moved entry is not implemented yet.
+        UnsupportedReference,
     }
 
     impl fmt::Display for ConvertError {
@@ -368,6 +415,7 @@ mod convert {
             use self::ConvertError::*;
             match *self {
                 Read(ref e) => e.fmt(f),
+                Write(ref e) => e.fmt(f),
                 UnsupportedAttributeValue => {
                     write!(f, "Writing of this attribute value is not implemented yet.")
                 }
@@ -409,6 +457,13 @@ mod convert {
                 ),
                 InvalidBranchTarget => write!(f, "Operation branch target is invalid."),
                 UnsupportedUnitType => write!(f, "Writing this unit type is not supported yet."),
+                InexactLineProgram => {
+                    write!(f, "A line number program cannot be converted exactly.")
+                }
+                UnsupportedReference => write!(
+                    f,
+                    "Updating this reference to a moved entry is not implemented yet."
+                ),
             }
         }
     }
@@ -421,8 +476,32 @@ mod convert {
         }
     }
 
+    impl From<Error> for ConvertError {
+        fn from(e: Error) -> Self {
+            ConvertError::Write(e)
+        }
+    }
+
     /// The result of a conversion.
     pub type ConvertResult<T> = result::Result<T, ConvertError>;
+
+    /// Options that control the conversion of read values into write values.
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+    pub struct ConvertOptions {
+        /// Convert each line number program instruction to an equivalent instruction,
+        /// instead of regenerating the instructions from the rows.
+        ///
+        /// By default, the instructions are regenerated, which may change the
+        /// encoding of the rows, and duplicate file and directory entries are
+        /// merged. With this option, the converted program uses the same
+        /// instructions for every row, and the same file and directory tables,
+        /// including any duplicate entries. This
+        /// allows the converted sections to be compared with the original sections.
+        ///
+        /// The conversion fails with `ConvertError::InexactLineProgram` if
+        /// an instruction has no equivalent in the written program.
+        pub exact_line_programs: bool,
+    }
 }
 #[cfg(feature = "read")]
 pub use self::convert::*;
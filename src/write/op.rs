@@ -31,6 +31,139 @@ impl Expression {
         }
     }
 
+    /// Create an expression for a location at the given offset from the frame base.
+    ///
+    /// This is a `DW_OP_fbreg` operation. The containing subprogram must have
+    /// a `DW_AT_frame_base` attribute, such as [`Expression::call_frame_cfa`].
+    pub fn frame_base_offset(offset: i64) -> Self {
+        Expression {
+            operations: vec![Operation::FrameOffset(offset)],
+        }
+    }
+
+    /// Create an expression for a `DW_AT_frame_base` attribute that uses the
+    /// call frame address.
+    ///
+    /// This is a `DW_OP_call_frame_cfa` operation.
+    pub fn call_frame_cfa() -> Self {
+        Expression {
+            operations: vec![Operation::Simple(constants::DW_OP_call_frame_cfa)],
+        }
+    }
+
+    /// Create an expression for an object that has no location, but has a known
+    /// value with the given bytes.
+    ///
+    /// This is a `DW_OP_implicit_value` operation.
+    pub fn implicit_value(data: &[u8]) -> Self {
+        Expression {
+            operations: vec![Operation::ImplicitValue(data.into())],
+        }
+    }
+
+    /// Return true if the expression is a composite location description.
+    ///
+    /// That is, the expression ends with a `DW_OP_piece` or `DW_OP_bit_piece`.
+    pub fn is_composite(&self) -> bool {
+        matches!(
+            self.operations.last(),
+            Some(Operation::Piece { .. } | Operation::BitPiece { .. })
+        )
+    }
+
+    /// Return the total size in bits of the pieces of a composite location description.
+    ///
+    /// Returns `None` if the expression is not a composite location description.
+    pub fn composite_size_in_bits(&self) -> Option<u64> {
+        if !self.is_composite() {
+            return None;
+        }
+        let mut size = 0u64;
+        for operation in &self.operations {
+            let piece_size = match *operation {
+                Operation::Piece { size_in_bytes } => size_in_bytes.checked_mul(8)?,
+                Operation::BitPiece { size_in_bits, .. } => size_in_bits,
+                _ => continue,
+            };
+            size = size.checked_add(piece_size)?;
+        }
+        Some(size)
+    }
+
+    /// Add a piece to a composite location description.
+    ///
+    /// `location` is a simple location description for the piece, such as
+    /// a register location created with `op_reg`, a memory location, or an
+    /// implicit value. It may be empty if the piece has no location, such as
+    /// when it has been optimized away.
+    ///
+    /// Returns `Error::InvalidPiece` if the size is zero, if this expression is
+    /// not empty or a composite location description, if `location` contains
+    /// pieces or operations after one that completes a location, or if an
+    /// implicit value in `location` is smaller than the piece.
+    pub fn add_piece(&mut self, location: &Expression, size_in_bytes: u64) -> Result<()> {
+        let size_in_bits = size_in_bytes.checked_mul(8).ok_or(Error::InvalidPiece)?;
+        self.check_piece(location, size_in_bits)?;
+        self.operations.extend(location.operations.iter().cloned());
+        self.operations.push(Operation::Piece { size_in_bytes });
+        Ok(())
+    }
+
+    /// Add a piece with a size and offset in bits to a composite location description.
+    ///
+    /// The `bit_offset` is the offset of the piece within `location`.
+    ///
+    /// See [`Expression::add_piece`] for the requirements of `location`.
+    pub fn add_bit_piece(
+        &mut self,
+        location: &Expression,
+        size_in_bits: u64,
+        bit_offset: u64,
+    ) -> Result<()> {
+        let end = size_in_bits
+            .checked_add(bit_offset)
+            .ok_or(Error::InvalidPiece)?;
+        if size_in_bits == 0 {
+            return Err(Error::InvalidPiece);
+        }
+        self.check_piece(location, end)?;
+        self.operations.extend(location.operations.iter().cloned());
+        self.operations.push(Operation::BitPiece {
+            size_in_bits,
+            bit_offset,
+        });
+        Ok(())
+    }
+
+    /// Check that `location` can be added as a piece that uses the first `size_in_bits` bits.
+    fn check_piece(&self, location: &Expression, size_in_bits: u64) -> Result<()> {
+        if size_in_bits == 0 || !(self.operations.is_empty() || self.is_composite()) {
+            return Err(Error::InvalidPiece);
+        }
+        let count = location.operations.len();
+        for (index, operation) in location.operations.iter().enumerate() {
+            let completes = match *operation {
+                Operation::Piece { .. } | Operation::BitPiece { .. } => {
+                    return Err(Error::InvalidPiece);
+                }
+                Operation::ImplicitValue(ref data) => {
+                    if (data.len() as u64).saturating_mul(8) < size_in_bits {
+                        return Err(Error::InvalidPiece);
+                    }
+                    true
+                }
+                Operation::Simple(constants::DW_OP_stack_value)
+                | Operation::Register(_)
+                | Operation::ImplicitPointer { .. } => true,
+                _ => false,
+            };
+            if completes && index + 1 != count {
+                return Err(Error::InvalidPiece);
+            }
+        }
+        Ok(())
+    }
+
     /// Add an operation to the expression.
     ///
     /// This should only be used for operations that have no explicit operands.
@@ -255,6 +388,17 @@ impl Expression {
         self.operations.push(Operation::WasmStack(index));
     }
 
+    /// Add a `DW_OP_LLVM_user DW_OP_LLVM_form_aspace_address` operation to the expression.
+    pub fn op_llvm_form_aspace_address(&mut self) {
+        self.operations.push(Operation::FormAspaceAddress);
+    }
+
+    /// Add a `DW_OP_LLVM_user DW_OP_LLVM_aspace_bregx` operation to the expression.
+    pub fn op_llvm_aspace_bregx(&mut self, register: Register, offset: i64) {
+        self.operations
+            .push(Operation::AspaceRegisterOffset(register, offset));
+    }
+
     pub(crate) fn size(&self, encoding: Encoding, unit_offsets: Option<&UnitOffsets>) -> usize {
         let mut size = 0;
         for operation in &self.operations {
@@ -495,6 +639,14 @@ enum Operation {
     ///
     /// Represents `DW_OP_WASM_location 0x02`.
     WasmStack(u32),
+    /// Convert an address to a memory location in an address space.
+    ///
+    /// Represents `DW_OP_LLVM_user DW_OP_LLVM_form_aspace_address`.
+    FormAspaceAddress,
+    /// Compute a memory location in an address space from a register and offset.
+    ///
+    /// Represents `DW_OP_LLVM_user DW_OP_LLVM_aspace_bregx`.
+    AspaceRegisterOffset(Register, i64),
 }
 
 impl Operation {
@@ -582,6 +734,14 @@ impl Operation {
             Operation::WasmLocal(index)
             | Operation::WasmGlobal(index)
             | Operation::WasmStack(index) => 1 + uleb128_size(index.into()),
+            Operation::FormAspaceAddress => {
+                uleb128_size(constants::DW_OP_LLVM_form_aspace_address.0.into())
+            }
+            Operation::AspaceRegisterOffset(register, offset) => {
+                uleb128_size(constants::DW_OP_LLVM_aspace_bregx.0.into())
+                    + uleb128_size(register.0.into())
+                    + sleb128_size(offset)
+            }
         }
     }
 
@@ -829,6 +989,16 @@ impl Operation {
                 w.write(&[constants::DW_OP_WASM_location.0, 2])?;
                 w.write_uleb128(index.into())?;
             }
+            Operation::FormAspaceAddress => {
+                w.write_u8(constants::DW_OP_LLVM_user.0)?;
+                w.write_uleb128(constants::DW_OP_LLVM_form_aspace_address.0.into())?;
+            }
+            Operation::AspaceRegisterOffset(register, offset) => {
+                w.write_u8(constants::DW_OP_LLVM_user.0)?;
+                w.write_uleb128(constants::DW_OP_LLVM_aspace_bregx.0.into())?;
+                w.write_uleb128(register.0.into())?;
+                w.write_sleb128(offset)?;
+            }
         }
         Ok(())
     }
@@ -1050,6 +1220,10 @@ pub(crate) mod convert {
                     read::Operation::WasmLocal { index } => Operation::WasmLocal(index),
                     read::Operation::WasmGlobal { index } => Operation::WasmGlobal(index),
                     read::Operation::WasmStack { index } => Operation::WasmStack(index),
+                    read::Operation::FormAspaceAddress => Operation::FormAspaceAddress,
+                    read::Operation::AspaceRegisterOffset { register, offset } => {
+                        Operation::AspaceRegisterOffset(register, offset)
+                    }
                 };
                 operations.push(operation);
             }
@@ -1516,6 +1690,19 @@ mod tests {
                                 Operation::WasmStack(1000),
                                 read::Operation::WasmStack { index: 1000 },
                             ),
+                            (
+                                &|x| x.op_llvm_form_aspace_address(),
+                                Operation::FormAspaceAddress,
+                                read::Operation::FormAspaceAddress,
+                            ),
+                            (
+                                &|x| x.op_llvm_aspace_bregx(Register(3), -8),
+                                Operation::AspaceRegisterOffset(Register(3), -8),
+                                read::Operation::AspaceRegisterOffset {
+                                    register: Register(3),
+                                    offset: -8,
+                                },
+                            ),
                         ];
 
                     let mut expression = Expression::new();
@@ -1621,4 +1808,87 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_expression_helpers() {
+        let encoding = Encoding {
+            format: Format::Dwarf32,
+            version: 4,
+            address_size: 8,
+        };
+        let write = |expression: &Expression| {
+            let mut w = EndianVec::new(LittleEndian);
+            expression.write(&mut w, None, encoding, None).unwrap();
+            w.into_vec()
+        };
+
+        assert_eq!(
+            write(&Expression::frame_base_offset(-16)),
+            [constants::DW_OP_fbreg.0, 0x70]
+        );
+        assert_eq!(
+            write(&Expression::call_frame_cfa()),
+            [constants::DW_OP_call_frame_cfa.0]
+        );
+
+        let mut register = Expression::new();
+        register.op_reg(Register(0));
+        let mut memory = Expression::new();
+        memory.op_breg(Register(7), 8);
+        let implicit = Expression::implicit_value(&[1, 2]);
+
+        let mut composite = Expression::new();
+        assert!(!composite.is_composite());
+        assert_eq!(composite.composite_size_in_bits(), None);
+        composite.add_piece(&register, 4).unwrap();
+        composite.add_piece(&memory, 4).unwrap();
+        composite.add_piece(&Expression::new(), 2).unwrap();
+        composite.add_bit_piece(&implicit, 12, 0).unwrap();
+        assert!(composite.is_composite());
+        assert_eq!(composite.composite_size_in_bits(), Some(92));
+        assert_eq!(
+            write(&composite),
+            [
+                constants::DW_OP_reg0.0,
+                constants::DW_OP_piece.0,
+                4,
+                constants::DW_OP_breg7.0,
+                8,
+                constants::DW_OP_piece.0,
+                4,
+                constants::DW_OP_piece.0,
+                2,
+                constants::DW_OP_implicit_value.0,
+                2,
+                1,
+                2,
+                constants::DW_OP_bit_piece.0,
+                12,
+                0,
+            ]
+        );
+
+        // Invalid pieces.
+        assert_eq!(composite.add_piece(&register, 0), Err(Error::InvalidPiece));
+        assert_eq!(composite.add_piece(&implicit, 3), Err(Error::InvalidPiece));
+        assert_eq!(
+            composite.add_bit_piece(&implicit, 8, 9),
+            Err(Error::InvalidPiece)
+        );
+        assert_eq!(
+            composite.add_piece(&composite.clone(), 1),
+            Err(Error::InvalidPiece)
+        );
+        let mut after_register = register.clone();
+        after_register.op_deref();
+        assert_eq!(
+            composite.add_piece(&after_register, 1),
+            Err(Error::InvalidPiece)
+        );
+        assert_eq!(
+            memory.clone().add_piece(&register, 1),
+            Err(Error::InvalidPiece)
+        );
+        assert_eq!(composite.composite_size_in_bits(), Some(92));
+    }
 }
@@ -3,7 +3,7 @@ use indexmap::IndexSet;
 use std::ops::{Deref, DerefMut};
 
 use crate::common::{Encoding, RangeListsOffset, SectionId};
-use crate::write::{Address, BaseId, Error, Result, Section, Sections, Writer};
+use crate::write::{Address, BaseId, Error, Result, Section, Sections, WriteOptions, Writer};
 
 define_section!(
     DebugRanges,
@@ -27,6 +27,10 @@ define_id!(
 );
 
 /// A table of range lists that will be stored in a `.debug_ranges` or `.debug_rnglists` section.
+///
+/// The list entries do not contain segment selectors. The segment of the
+/// addresses is given by the `DW_AT_segment` attribute of the DIE, or for
+/// indexed addresses, by the segment selector in the `AddressTable`.
 #[derive(Debug, Default)]
 pub struct RangeListTable {
     base_id: BaseId,
@@ -45,13 +49,18 @@ impl RangeListTable {
         &self,
         sections: &mut Sections<W>,
         encoding: Encoding,
+        options: &WriteOptions,
     ) -> Result<RangeListOffsets> {
         if self.ranges.is_empty() {
             return Ok(RangeListOffsets::none());
         }
 
         match encoding.version {
-            2..=4 => self.write_ranges(&mut sections.debug_ranges, encoding.address_size),
+            2..=4 => self.write_ranges(
+                &mut sections.debug_ranges,
+                encoding.address_size,
+                options.base_address_selection,
+            ),
             5 => self.write_rnglists(&mut sections.debug_rnglists, encoding),
             _ => Err(Error::UnsupportedVersion(encoding.version)),
         }
@@ -62,19 +71,47 @@ impl RangeListTable {
         &self,
         w: &mut DebugRanges<W>,
         address_size: u8,
+        base_address_selection: bool,
     ) -> Result<RangeListOffsets> {
         let mut offsets = Vec::new();
         for range_list in self.ranges.iter() {
             offsets.push(w.offset());
+            if base_address_selection && range_list.is_independent_of_unit_base() {
+                let mut base = BaseAddressSelection::default();
+                for range in &range_list.0 {
+                    let (begin, end) = match *range {
+                        Range::BaseAddress { address } => {
+                            base.set(address);
+                            write_base_address(&mut w.0, address, address_size)?;
+                            continue;
+                        }
+                        Range::OffsetPair { begin, end } => {
+                            if begin == end {
+                                return Err(Error::InvalidRange);
+                            }
+                            w.write_udata(begin, address_size)?;
+                            w.write_udata(end, address_size)?;
+                            continue;
+                        }
+                        Range::StartEnd { begin, end } => (begin, end),
+                        Range::StartLength { begin, length } => (begin, begin.add(length)),
+                    };
+                    if begin == end {
+                        return Err(Error::InvalidRange);
+                    }
+                    base.write_range(&mut w.0, begin, end, address_size)?;
+                }
+                w.write_udata(0, address_size)?;
+                w.write_udata(0, address_size)?;
+                continue;
+            }
             for range in &range_list.0 {
                 // Note that we must ensure none of the ranges have both begin == 0 and end == 0.
                 // We do this by ensuring that begin != end, which is a bit more restrictive
                 // than required, but still seems reasonable.
                 match *range {
                     Range::BaseAddress { address } => {
-                        let marker = !0 >> (64 - address_size * 8);
-                        w.write_udata(marker, address_size)?;
-                        w.write_address(address, address_size)?;
+                        write_base_address(&mut w.0, address, address_size)?;
                     }
                     Range::OffsetPair { begin, end } => {
                         if begin == end {
@@ -91,13 +128,7 @@ impl RangeListTable {
                         w.write_address(end, address_size)?;
                     }
                     Range::StartLength { begin, length } => {
-                        let end = match begin {
-                            Address::Constant(begin) => Address::Constant(begin + length),
-                            Address::Symbol { symbol, addend } => Address::Symbol {
-                                symbol,
-                                addend: addend + length as i64,
-                            },
-                        };
+                        let end = begin.add(length);
                         if begin == end {
                             return Err(Error::InvalidRange);
                         }
@@ -179,6 +210,100 @@ impl RangeListTable {
 #[derive(Clone, Debug, Eq, PartialEq, Hash)]
 pub struct RangeList(pub Vec<Range>);
 
+impl RangeList {
+    /// Return true if no offset pairs are relative to the base address of the unit.
+    fn is_independent_of_unit_base(&self) -> bool {
+        for range in &self.0 {
+            match range {
+                Range::BaseAddress { .. } => return true,
+                Range::OffsetPair { .. } => return false,
+                _ => {}
+            }
+        }
+        true
+    }
+}
+
+/// Write a base address selection entry for `.debug_ranges` or `.debug_loc`.
+pub(crate) fn write_base_address<W: Writer>(
+    w: &mut W,
+    address: Address,
+    address_size: u8,
+) -> Result<()> {
+    let marker = !0 >> (64 - address_size * 8);
+    w.write_udata(marker, address_size)?;
+    w.write_address(address, address_size)
+}
+
+/// The current base address while writing a `.debug_ranges` or `.debug_loc` list
+/// using base address selection entries.
+///
+/// Ranges are written as offsets from the current base address where possible.
+/// Otherwise, a base address selection entry is written first, so that the list
+/// never depends on the base address of the unit.
+#[derive(Debug, Default)]
+pub(crate) struct BaseAddressSelection {
+    base: Option<Address>,
+}
+
+impl BaseAddressSelection {
+    /// Set the base address for an explicit base address selection entry.
+    pub(crate) fn set(&mut self, address: Address) {
+        self.base = Some(address);
+    }
+
+    /// Write the range as an offset pair from the current base address, preceded
+    /// by a base address selection entry if needed.
+    pub(crate) fn write_range<W: Writer>(
+        &mut self,
+        w: &mut W,
+        begin: Address,
+        end: Address,
+        address_size: u8,
+    ) -> Result<()> {
+        if let Some(length) = address_offset(begin, end) {
+            let offset = self.base.and_then(|base| address_offset(base, begin));
+            if let Some((begin, end)) =
+                offset.and_then(|offset| Some((offset, offset.checked_add(length)?)))
+            {
+                w.write_udata(begin, address_size)?;
+                w.write_udata(end, address_size)?;
+            } else {
+                self.base = Some(begin);
+                write_base_address(w, begin, address_size)?;
+                w.write_udata(0, address_size)?;
+                w.write_udata(length, address_size)?;
+            }
+        } else {
+            // The addresses are relative to different symbols, so they can't be
+            // written as offsets. Use a base address of 0 instead.
+            let zero = Address::Constant(0);
+            if self.base != Some(zero) {
+                self.base = Some(zero);
+                write_base_address(w, zero, address_size)?;
+            }
+            w.write_address(begin, address_size)?;
+            w.write_address(end, address_size)?;
+        }
+        Ok(())
+    }
+}
+
+/// Return the offset of `address` from `base`, if it can be determined.
+fn address_offset(base: Address, address: Address) -> Option<u64> {
+    match (base, address) {
+        (Address::Constant(base), Address::Constant(address)) => address.checked_sub(base),
+        (
+            Address::Symbol {
+                symbol: base_symbol,
+                addend: base,
+            },
+            Address::Symbol { symbol, addend },
+        ) if base_symbol == symbol && addend >= base => Some(addend.wrapping_sub(base) as u64),
+        _ => None,
+    }
+}
+
 /// A single range.
 #[derive(Clone, Debug, Eq, PartialEq, Hash)]
 pub enum Range {
@@ -353,7 +478,9 @@ mod tests {
                     let range_list_id = ranges.add(range_list.clone());
 
                     let mut sections = Sections::new(EndianVec::new(LittleEndian));
-                    let range_list_offsets = ranges.write(&mut sections, encoding).unwrap();
+                    let range_list_offsets = ranges
+                        .write(&mut sections, encoding, &WriteOptions::default())
+                        .unwrap();
 
                     let read_debug_ranges =
                         read::DebugRanges::new(sections.debug_ranges.slice(), LittleEndian);
@@ -413,4 +540,88 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_range_base_address_selection() {
+        let encoding = Encoding {
+            format: Format::Dwarf32,
+            version: 4,
+            address_size: 4,
+        };
+        let mut ranges = RangeListTable::default();
+        let range_list_id = ranges.add(RangeList(vec![
+            Range::StartLength {
+                begin: Address::Constant(0x1000),
+                length: 0x10,
+            },
+            Range::StartEnd {
+                begin: Address::Constant(0x1020),
+                end: Address::Constant(0x1030),
+            },
+            Range::StartEnd {
+                begin: Address::Constant(0x800),
+                end: Address::Constant(0x810),
+            },
+        ]));
+        let relative_id = ranges.add(RangeList(vec![Range::OffsetPair {
+            begin: 0x10,
+            end: 0x20,
+        }]));
+
+        let mut sections = Sections::new(EndianVec::new(LittleEndian));
+        let options = WriteOptions {
+            base_address_selection: true,
+            ..Default::default()
+        };
+        let range_list_offsets = ranges.write(&mut sections, encoding, &options).unwrap();
+
+        let read_ranges = read::RangeLists::new(
+            read::DebugRanges::new(sections.debug_ranges.slice(), LittleEndian),
+            read::DebugRngLists::new(&[], LittleEndian),
+        );
+        let offset = range_list_offsets.get(range_list_id);
+        let mut raw = read_ranges.raw_ranges(offset, encoding).unwrap();
+        let mut raw_entries = Vec::new();
+        while let Some(entry) = raw.next().unwrap() {
+            // `None` is used for a base address selection entry.
+            raw_entries.push(match entry {
+                read::RawRngListEntry::BaseAddress { addr } => (None, addr),
+                read::RawRngListEntry::AddressOrOffsetPair { begin, end } => (Some(begin), end),
+                otherwise => panic!("Unexpected entry = {:?}", otherwise),
+            });
+        }
+        assert_eq!(
+            raw_entries,
+            [
+                (None, 0x1000),
+                (Some(0), 0x10),
+                (Some(0x20), 0x30),
+                (None, 0x800),
+                (Some(0), 0x10),
+            ]
+        );
+
+        // The ranges don't depend on the base address of the unit.
+        let debug_addr = read::DebugAddr::from(read::EndianSlice::new(&[], LittleEndian));
+        let mut iter = read_ranges
+            .ranges(offset, encoding, 0x5000, &debug_addr, DebugAddrBase(0))
+            .unwrap();
+        let mut read_entries = Vec::new();
+        while let Some(range) = iter.next().unwrap() {
+            read_entries.push((range.begin, range.end));
+        }
+        assert_eq!(
+            read_entries,
+            [(0x1000, 0x1010), (0x1020, 0x1030), (0x800, 0x810)]
+        );
+
+        // Lists that use the base address of the unit are unchanged.
+        let offset = range_list_offsets.get(relative_id);
+        let mut iter = read_ranges
+            .ranges(offset, encoding, 0x5000, &debug_addr, DebugAddrBase(0))
+            .unwrap();
+        let range = iter.next().unwrap().unwrap();
+        assert_eq!((range.begin, range.end), (0x5010, 0x5020));
+        assert_eq!(iter.next(), Ok(None));
+    }
 }
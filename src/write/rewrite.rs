@@ -0,0 +1,772 @@
+use alloc::vec::Vec;
+use std::collections::HashMap;
+
+use crate::common::{DebugInfoOffset, Encoding, SectionId};
+use crate::constants;
+use crate::leb128::write::uleb128_size;
+use crate::read::{self, Reader, ReaderOffset};
+use crate::write::{
+    Abbreviation, AbbreviationTable, AttributeSpecification, ConvertError, ConvertResult,
+    EndianVec, Error, Sections, Writer,
+};
+
+/// The replacement for an attribute value, returned by the callback for
+/// [`rewrite_debug_info`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RewriteAttribute {
+    /// Keep the attribute unchanged.
+    Keep,
+    /// Remove the attribute from the entry.
+    Remove,
+    /// Replace the value with a string.
+    ///
+    /// The string must not contain a null byte.
+    ///
+    /// If the attribute uses `DW_FORM_strp` or `DW_FORM_line_strp`, then the string
+    /// is appended to the corresponding string section and the form is unchanged.
+    /// Otherwise, the value is encoded using `DW_FORM_string`.
+    String(Vec<u8>),
+    /// Replace the value with an unsigned constant.
+    ///
+    /// The form is unchanged if it is `DW_FORM_data*` and the value fits, or if it
+    /// is `DW_FORM_implicit_const`. Otherwise, the value is encoded using
+    /// `DW_FORM_udata`.
+    Udata(u64),
+    /// Replace the value with a signed constant.
+    ///
+    /// The form is unchanged if it is `DW_FORM_implicit_const`. Otherwise, the
+    /// value is encoded using `DW_FORM_sdata`.
+    Sdata(i64),
+    /// Replace the value with a flag.
+    ///
+    /// The form is unchanged if it is `DW_FORM_flag`, or if it is
+    /// `DW_FORM_flag_present` and the flag is true. Otherwise, the value is
+    /// encoded using `DW_FORM_flag`.
+    Flag(bool),
+}
+
+/// The encoding of a replacement attribute value.
+#[derive(Debug)]
+enum Replacement {
+    Remove,
+    Data(constants::DwForm, Vec<u8>),
+    ImplicitConst(i64),
+    Strp(Vec<u8>),
+    LineStrp(Vec<u8>),
+}
+
+impl Replacement {
+    fn new<E: crate::Endianity>(
+        spec: read::AttributeSpecification,
+        rewrite: RewriteAttribute,
+        endian: E,
+    ) -> ConvertResult<Option<Replacement>> {
+        let mut w = EndianVec::new(endian);
+        Ok(Some(match rewrite {
+            RewriteAttribute::Keep => return Ok(None),
+            RewriteAttribute::Remove => Replacement::Remove,
+            RewriteAttribute::String(string) => {
+                if string.contains(&0) {
+                    return Err(ConvertError::InvalidAttributeValue);
+                }
+                match spec.form() {
+                    constants::DW_FORM_strp => Replacement::Strp(string),
+                    constants::DW_FORM_line_strp => Replacement::LineStrp(string),
+                    _ => {
+                        w.write(&string)?;
+                        w.write_u8(0)?;
+                        Replacement::Data(constants::DW_FORM_string, w.into_vec())
+                    }
+                }
+            }
+            RewriteAttribute::Udata(val) => match spec.form() {
+                constants::DW_FORM_implicit_const if val <= i64::MAX as u64 => {
+                    Replacement::ImplicitConst(val as i64)
+                }
+                form @ (constants::DW_FORM_data1
+                | constants::DW_FORM_data2
+                | constants::DW_FORM_data4
+                | constants::DW_FORM_data8)
+                    if w.write_udata(val, form_size(form)).is_ok() =>
+                {
+                    Replacement::Data(form, w.into_vec())
+                }
+                _ => {
+                    w.write_uleb128(val)?;
+                    Replacement::Data(constants::DW_FORM_udata, w.into_vec())
+                }
+            },
+            RewriteAttribute::Sdata(val) => match spec.form() {
+                constants::DW_FORM_implicit_const => Replacement::ImplicitConst(val),
+                _ => {
+                    w.write_sleb128(val)?;
+                    Replacement::Data(constants::DW_FORM_sdata, w.into_vec())
+                }
+            },
+            RewriteAttribute::Flag(val) => match spec.form() {
+                constants::DW_FORM_flag_present if val => {
+                    Replacement::Data(constants::DW_FORM_flag_present, Vec::new())
+                }
+                _ => Replacement::Data(constants::DW_FORM_flag, vec![val as u8]),
+            },
+        }))
+    }
+
+    /// Return true if the replacement requires a different attribute specification.
+    fn changes_spec(&self, spec: read::AttributeSpecification) -> bool {
+        match *self {
+            Replacement::Remove => true,
+            Replacement::Data(form, _) => form != spec.form(),
+            Replacement::ImplicitConst(val) => spec.implicit_const_value() != Some(val),
+            Replacement::Strp(_) | Replacement::LineStrp(_) => false,
+        }
+    }
+}
+
+fn form_size(form: constants::DwForm) -> u8 {
+    match form {
+        constants::DW_FORM_data1 => 1,
+        constants::DW_FORM_data2 => 2,
+        constants::DW_FORM_data4 => 4,
+        _ => 8,
+    }
+}
+
+/// A map from the offsets of units and entries in the original `.debug_info`
+/// section to their offsets in the section written by [`rewrite_debug_info`].
+#[derive(Debug, Default, Clone)]
+pub struct DebugInfoOffsetMap {
+    units: Vec<(usize, usize)>,
+    entries: Vec<(usize, usize)>,
+}
+
+impl DebugInfoOffsetMap {
+    /// Return the new offset of the unit header or entry at the given offset in
+    /// the original `.debug_info` section.
+    ///
+    /// Returns `None` if there is no unit header or entry at the offset.
+    pub fn get(&self, offset: DebugInfoOffset) -> Option<DebugInfoOffset> {
+        self.entry(offset.0)
+            .or_else(|| lookup(&self.units, offset.0))
+            .map(DebugInfoOffset)
+    }
+
+    /// Return true if no unit header or entry has a different offset.
+    pub fn is_identity(&self) -> bool {
+        self.units
+            .iter()
+            .chain(self.entries.iter())
+            .all(|(old, new)| old == new)
+    }
+
+    /// Copy the `.debug_aranges` section to `w`, updating the offsets of the units.
+    pub fn rewrite_debug_aranges<R: Reader, W: Writer>(
+        &self,
+        debug_aranges: &read::DebugAranges<R>,
+        w: &mut W,
+    ) -> ConvertResult<()> {
+        let base = w.len();
+        w.write(&read::Section::reader(debug_aranges).to_slice()?)?;
+        let mut headers = debug_aranges.headers();
+        while let Some(header) = headers.next()? {
+            let format = header.encoding().format;
+            let old = header.debug_info_offset().0.into_u64() as usize;
+            let new = lookup(&self.units, old).ok_or(ConvertError::InvalidDebugInfoOffset)?;
+            // The offset follows the initial length and version.
+            let offset = base
+                + header.offset().0.into_u64() as usize
+                + format.initial_length_size() as usize
+                + 2;
+            w.write_offset_at(offset, new, SectionId::DebugInfo, format.word_size())?;
+        }
+        Ok(())
+    }
+
+    fn entry(&self, offset: usize) -> Option<usize> {
+        lookup(&self.entries, offset)
+    }
+}
+
+/// Find `old` in a list of `(old, new)` pairs that is sorted by `old`.
+fn lookup(offsets: &[(usize, usize)], old: usize) -> Option<usize> {
+    offsets
+        .binary_search_by_key(&old, |&(old, _)| old)
+        .ok()
+        .map(|index| offsets[index].1)
+}
+
+/// A reference that must be updated after the offsets of the entries are known.
+#[derive(Debug)]
+struct Fixup {
+    /// The offset of the reference within the written unit or section.
+    offset: usize,
+    /// The size of the encoded reference.
+    size: usize,
+    /// Whether the reference is encoded as a ULEB128.
+    uleb: bool,
+    /// The offset of the entry in the original unit or section.
+    target: usize,
+}
+
+/// Rewrite selected attribute values in the `.debug_info` section, without
+/// converting the rest of the debugging information.
+///
+/// `rewrite` is called for each attribute of each entry in the units of
+/// `.debug_info`, and returns the replacement for the attribute value. The
+/// callback is given the unit, the tag of the entry and the attribute.
+/// All other data is copied byte-for-byte.
+///
+/// The `.debug_abbrev`, `.debug_info`, `.debug_str` and `.debug_line_str`
+/// sections are written to `sections`, which should be empty. The original
+/// contents of `.debug_abbrev`, `.debug_str` and `.debug_line_str` are kept, and
+/// new data is appended to them. Other sections are not written, and should be
+/// copied from the input unchanged.
+///
+/// A replacement that uses a different form, or that removes an attribute, requires
+/// a new abbreviation. The units that need this are given a new abbreviations
+/// table, which is shared by units that used the same original table.
+///
+/// If the size of an entry changes, then the following entries move. References
+/// using `DW_FORM_ref*` and `DW_FORM_ref_addr`, and the type offsets in unit
+/// headers, are updated. References in expressions can't be updated, so
+/// `ConvertError::UnsupportedReference` is returned if an expression in
+/// `.debug_info` refers to an entry that moved. References in other sections are
+/// not updated; use the returned `DebugInfoOffsetMap` for these, such as with
+/// `DebugInfoOffsetMap::rewrite_debug_aranges`.
+///
+/// ```rust,no_run
+/// use gimli::write::{EndianVec, RewriteAttribute, Sections};
+///
+/// # fn example<R: gimli::Reader>(dwarf: &gimli::read::Dwarf<R>) -> gimli::write::ConvertResult<()> {
+/// let mut sections = Sections::new(EndianVec::new(gimli::LittleEndian));
+/// let offsets = gimli::write::rewrite_debug_info(dwarf, &mut sections, |unit, _, attr| {
+///     Ok(match attr.name() {
+///         gimli::DW_AT_producer => RewriteAttribute::Remove,
+///         gimli::DW_AT_comp_dir => {
+///             let comp_dir = dwarf.attr_string(unit, attr.value())?;
+///             match comp_dir.to_slice()?.strip_prefix(b"/build/".as_ref()) {
+///                 Some(rest) => RewriteAttribute::String([b"/src/".as_ref(), rest].concat()),
+///                 None => RewriteAttribute::Keep,
+///             }
+///         }
+///         _ => RewriteAttribute::Keep,
+///     })
+/// })?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn rewrite_debug_info<R, W, F>(
+    dwarf: &read::Dwarf<R>,
+    sections: &mut Sections<W>,
+    mut rewrite: F,
+) -> ConvertResult<DebugInfoOffsetMap>
+where
+    R: Reader,
+    W: Writer,
+    F: FnMut(
+        &read::Unit<R>,
+        constants::DwTag,
+        &read::Attribute<R>,
+    ) -> ConvertResult<RewriteAttribute>,
+{
+    let endian = read::Section::reader(&dwarf.debug_info).endian();
+    let debug_info = read::Section::reader(&dwarf.debug_info).to_slice()?;
+    sections
+        .debug_abbrev
+        .write(&read::Section::reader(&dwarf.debug_abbrev).to_slice()?)?;
+    sections
+        .debug_str
+        .write(&read::Section::reader(&dwarf.debug_str).to_slice()?)?;
+    sections
+        .debug_line_str
+        .write(&read::Section::reader(&dwarf.debug_line_str).to_slice()?)?;
+
+    let mut strings = HashMap::new();
+    let mut line_strings = HashMap::new();
+    let mut tables = Vec::<AbbreviationTable>::new();
+    let mut table_indices = HashMap::new();
+    // The offset of the abbreviations offset in each unit header, and the
+    // index of the new abbreviations table for the unit.
+    let mut table_units = Vec::new();
+    let mut offsets = DebugInfoOffsetMap::default();
+    let mut info_fixups = Vec::new();
+    let mut info_checks = Vec::new();
+
+    let mut headers = dwarf.units();
+    while let Some(header) = headers.next()? {
+        let unit = dwarf.unit(header)?;
+        let header = &unit.header;
+        let old_start = header
+            .offset()
+            .as_debug_info_offset()
+            .ok_or(ConvertError::InvalidDebugInfoOffset)?
+            .0
+            .into_u64() as usize;
+        let format = header.format();
+
+        // Find the attributes to replace, and whether a new abbreviation is needed.
+        let mut replacements = Vec::new();
+        let mut changes_spec = false;
+        let mut entries = unit.entries_raw(None)?;
+        while !entries.is_empty() {
+            let abbrev = match entries.read_abbreviation()? {
+                Some(abbrev) => abbrev,
+                None => continue,
+            };
+            for spec in abbrev.attributes() {
+                let offset = entries.next_offset();
+                let attr = entries.read_attribute(*spec)?;
+                let rewrite = rewrite(&unit, abbrev.tag(), &attr)?;
+                if let Some(replacement) = Replacement::new(*spec, rewrite, endian)? {
+                    changes_spec |= replacement.changes_spec(*spec);
+                    replacements.push((offset, replacement));
+                }
+            }
+        }
+        let table = if changes_spec {
+            let abbrev_offset = header.debug_abbrev_offset().0.into_u64();
+            let index = *table_indices.entry(abbrev_offset).or_insert_with(|| {
+                tables.push(AbbreviationTable::default());
+                tables.len() - 1
+            });
+            Some(index)
+        } else {
+            None
+        };
+
+        let w = &mut sections.debug_info;
+        let new_start = w.len();
+        offsets.units.push((old_start, new_start));
+
+        // Write the unit header.
+        let length_offset = w.write_initial_length(format)?;
+        let length_base = w.len();
+        w.write_u16(header.version())?;
+        let abbrev_offset = header.debug_abbrev_offset().0.into_u64() as usize;
+        let abbrev_offset_offset;
+        if header.version() >= 5 {
+            w.write_u8(header.type_().dw_ut().0)?;
+            w.write_u8(header.address_size())?;
+            abbrev_offset_offset = w.len();
+            w.write_offset(abbrev_offset, SectionId::DebugAbbrev, format.word_size())?;
+        } else {
+            abbrev_offset_offset = w.len();
+            w.write_offset(abbrev_offset, SectionId::DebugAbbrev, format.word_size())?;
+            w.write_u8(header.address_size())?;
+        }
+        let mut unit_fixups = Vec::new();
+        match header.type_() {
+            read::UnitType::Type {
+                type_signature,
+                type_offset,
+            }
+            | read::UnitType::SplitType {
+                type_signature,
+                type_offset,
+            } => {
+                w.write_u64(type_signature.0)?;
+                unit_fixups.push(Fixup {
+                    offset: w.len() - new_start,
+                    size: format.word_size().into(),
+                    uleb: false,
+                    target: type_offset.0.into_u64() as usize,
+                });
+                w.write_udata(type_offset.0.into_u64(), format.word_size())?;
+            }
+            read::UnitType::Skeleton(dwo_id) | read::UnitType::SplitCompilation(dwo_id) => {
+                w.write_u64(dwo_id.0)?;
+            }
+            read::UnitType::Compilation | read::UnitType::Partial => {}
+        }
+        if let Some(index) = table {
+            table_units.push((abbrev_offset_offset, format, index));
+        }
+
+        // Write the entries.
+        let mut unit_offsets = Vec::new();
+        let mut unit_checks = Vec::new();
+        let mut replacements = replacements.into_iter().peekable();
+        let mut buf = EndianVec::new(endian);
+        let mut buf_fixups = Vec::new();
+        let mut buf_info_fixups = Vec::new();
+        let mut entries = unit.entries_raw(None)?;
+        while !entries.is_empty() {
+            let old_offset = entries.next_offset().0.into_u64() as usize;
+            let abbrev = match entries.read_abbreviation()? {
+                Some(abbrev) => abbrev,
+                None => {
+                    w.write_u8(0)?;
+                    continue;
+                }
+            };
+            unit_offsets.push((old_offset, w.len() - new_start));
+
+            let mut specs = Vec::new();
+            for spec in abbrev.attributes() {
+                let start = entries.next_offset();
+                let attr = entries.read_attribute(*spec)?;
+                let end = entries.next_offset();
+                if replacements.peek().map(|(offset, _)| *offset) == Some(start) {
+                    let (_, replacement) = replacements.next().unwrap();
+                    let string_offset = match replacement {
+                        Replacement::Remove => continue,
+                        Replacement::Data(form, data) => {
+                            specs.push(AttributeSpecification::new(spec.name(), form));
+                            buf.write(&data)?;
+                            continue;
+                        }
+                        Replacement::ImplicitConst(val) => {
+                            specs
+                                .push(AttributeSpecification::new_implicit_const(spec.name(), val));
+                            continue;
+                        }
+                        Replacement::Strp(string) => {
+                            let w = &mut *sections.debug_str;
+                            (SectionId::DebugStr, add_string(&mut strings, w, string)?)
+                        }
+                        Replacement::LineStrp(string) => {
+                            let w = &mut *sections.debug_line_str;
+                            (
+                                SectionId::DebugLineStr,
+                                add_string(&mut line_strings, w, string)?,
+                            )
+                        }
+                    };
+                    specs.push(AttributeSpecification::new(spec.name(), spec.form()));
+                    buf.write_offset(string_offset.1, string_offset.0, format.word_size())?;
+                    continue;
+                }
+
+                specs.push(match spec.implicit_const_value() {
+                    Some(val) => AttributeSpecification::new_implicit_const(spec.name(), val),
+                    None => AttributeSpecification::new(spec.name(), spec.form()),
+                });
+                let section_start = old_start + start.0.into_u64() as usize;
+                let section_end = old_start + end.0.into_u64() as usize;
+                let data = &debug_info[section_start..section_end];
+                match attr.value() {
+                    read::AttributeValue::UnitRef(target) => {
+                        if spec.form() == constants::DW_FORM_indirect {
+                            return Err(ConvertError::UnsupportedAttributeValue);
+                        }
+                        buf_fixups.push(Fixup {
+                            offset: buf.len(),
+                            size: data.len(),
+                            uleb: spec.form() == constants::DW_FORM_ref_udata,
+                            target: target.0.into_u64() as usize,
+                        });
+                    }
+                    read::AttributeValue::DebugInfoRef(target) => {
+                        if spec.form() == constants::DW_FORM_indirect {
+                            return Err(ConvertError::UnsupportedAttributeValue);
+                        }
+                        buf_info_fixups.push(Fixup {
+                            offset: buf.len(),
+                            size: data.len(),
+                            uleb: false,
+                            target: target.0.into_u64() as usize,
+                        });
+                    }
+                    read::AttributeValue::Exprloc(expression) => {
+                        expression_references(
+                            expression,
+                            unit.encoding(),
+                            &mut unit_checks,
+                            &mut info_checks,
+                        );
+                    }
+                    _ => {}
+                }
+                buf.write(data)?;
+            }
+
+            let code = match table {
+                Some(index) => tables[index].add(Abbreviation::new(
+                    abbrev.tag(),
+                    abbrev.has_children(),
+                    specs,
+                )),
+                None => abbrev.code(),
+            };
+            w.write_uleb128(code)?;
+            let base = w.len();
+            for mut fixup in buf_fixups.drain(..) {
+                fixup.offset += base - new_start;
+                unit_fixups.push(fixup);
+            }
+            for mut fixup in buf_info_fixups.drain(..) {
+                fixup.offset += base;
+                info_fixups.push(fixup);
+            }
+            w.write(&buf.take())?;
+        }
+
+        let length = (w.len() - length_base) as u64;
+        w.write_initial_length_at(length_offset, length, format)?;
+        for fixup in unit_fixups {
+            let target = lookup(&unit_offsets, fixup.target).ok_or(ConvertError::InvalidUnitRef)?;
+            write_fixup(
+                &mut **w,
+                new_start + fixup.offset,
+                fixup.size,
+                fixup.uleb,
+                target,
+            )?;
+        }
+        for target in unit_checks {
+            if lookup(&unit_offsets, target).map_or(false, |new| new != target) {
+                return Err(ConvertError::UnsupportedReference);
+            }
+        }
+        offsets.entries.extend(
+            unit_offsets
+                .into_iter()
+                .map(|(old, new)| (old_start + old, new_start + new)),
+        );
+    }
+
+    let w = &mut sections.debug_info;
+    for fixup in info_fixups {
+        let target = offsets
+            .entry(fixup.target)
+            .ok_or(ConvertError::InvalidDebugInfoRef)?;
+        write_fixup(&mut **w, fixup.offset, fixup.size, fixup.uleb, target)?;
+    }
+    for target in info_checks {
+        if offsets.entry(target).map_or(false, |new| new != target) {
+            return Err(ConvertError::UnsupportedReference);
+        }
+    }
+
+    let mut table_offsets = Vec::with_capacity(tables.len());
+    for table in &tables {
+        table_offsets.push(sections.debug_abbrev.len());
+        table.write(&mut sections.debug_abbrev)?;
+    }
+    let w = &mut sections.debug_info;
+    for (offset, format, index) in table_units {
+        w.write_offset_at(
+            offset,
+            table_offsets[index],
+            SectionId::DebugAbbrev,
+            format.word_size(),
+        )?;
+    }
+    Ok(offsets)
+}
+
+/// Append a string to a string section, reusing a previously appended string if possible.
+fn add_string<W: Writer>(
+    strings: &mut HashMap<Vec<u8>, usize>,
+    w: &mut W,
+    string: Vec<u8>,
+) -> Result<usize, Error> {
+    if let Some(offset) = strings.get(&string) {
+        return Ok(*offset);
+    }
+    let offset = w.len();
+    w.write(&string)?;
+    w.write_u8(0)?;
+    strings.insert(string, offset);
+    Ok(offset)
+}
+
+/// Write the new target of a reference.
+fn write_fixup<W: Writer>(
+    w: &mut W,
+    offset: usize,
+    size: usize,
+    uleb: bool,
+    target: usize,
+) -> ConvertResult<()> {
+    if !uleb {
+        w.write_udata_at(offset, target as u64, size as u8)?;
+        return Ok(());
+    }
+    // Pad the ULEB128 so that it keeps the same size.
+    let val = target as u64;
+    if uleb128_size(val) > size {
+        return Err(Error::ValueTooLarge.into());
+    }
+    let mut bytes = [0u8; 10];
+    for (i, byte) in bytes[..size].iter_mut().enumerate() {
+        let shift = 7 * i as u32;
+        *byte = val.checked_shr(shift).unwrap_or(0) as u8 & 0x7f;
+        if i + 1 < size {
+            *byte |= 0x80;
+        }
+    }
+    w.write_at(offset, &bytes[..size])?;
+    Ok(())
+}
+
+/// Add the offsets of the entries that are referenced by an expression.
+///
+/// Unit offsets of 0 are not references. Parsing stops at the first invalid
+/// operation, since the remaining operations can't be found.
+fn expression_references<R: Reader>(
+    expression: read::Expression<R>,
+    encoding: Encoding,
+    unit_refs: &mut Vec<usize>,
+    info_refs: &mut Vec<usize>,
+) {
+    let mut operations = expression.operations(encoding);
+    while let Ok(Some(operation)) = operations.next() {
+        let unit_ref = match operation {
+            read::Operation::Call {
+                offset: read::DieReference::UnitRef(offset),
+            }
+            | read::Operation::ParameterRef { offset } => offset,
+            read::Operation::Deref { base_type, .. }
+            | read::Operation::RegisterOffset { base_type, .. }
+            | read::Operation::TypedLiteral { base_type, .. }
+            | read::Operation::Convert { base_type }
+            | read::Operation::Reinterpret { base_type } => base_type,
+            read::Operation::Call {
+                offset: read::DieReference::DebugInfoRef(offset),
+            }
+            | read::Operation::ImplicitPointer { value: offset, .. } => {
+                info_refs.push(offset.0.into_u64() as usize);
+                continue;
+            }
+            read::Operation::EntryValue { expression } => {
+                expression_references(read::Expression(expression), encoding, unit_refs, info_refs);
+                continue;
+            }
+            _ => continue,
+        };
+        let unit_ref = unit_ref.0.into_u64() as usize;
+        if unit_ref != 0 {
+            unit_refs.push(unit_ref);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::{Encoding, Format};
+    use crate::write::{AttributeValue, Dwarf, LineProgram, Reference, Unit};
+    use crate::LittleEndian;
+
+    #[test]
+    fn test_rewrite_debug_info() {
+        let mut dwarf = Dwarf::new();
+        let mut ids = Vec::new();
+        for version in [4, 5] {
+            let encoding = Encoding {
+                format: Format::Dwarf32,
+                version,
+                address_size: 8,
+            };
+            let unit_id = dwarf.units.add(Unit::new(encoding, LineProgram::none()));
+            let unit = dwarf.units.get_mut(unit_id);
+            let root = unit.root();
+            let base_type = unit.add(root, constants::DW_TAG_base_type);
+            let variable = unit.add(root, constants::DW_TAG_variable);
+            let producer = dwarf.strings.add("producer");
+            let comp_dir = dwarf.strings.add("/build/a");
+            let root = unit.get_mut(root);
+            root.set(
+                constants::DW_AT_producer,
+                AttributeValue::StringRef(producer),
+            );
+            root.set(
+                constants::DW_AT_name,
+                AttributeValue::String(b"a.c".to_vec()),
+            );
+            root.set(
+                constants::DW_AT_comp_dir,
+                AttributeValue::StringRef(comp_dir),
+            );
+            unit.get_mut(base_type)
+                .set(constants::DW_AT_byte_size, AttributeValue::Data1(4));
+            unit.get_mut(variable)
+                .set(constants::DW_AT_type, AttributeValue::UnitRef(base_type));
+            ids.push((unit_id, base_type, variable));
+        }
+        let (unit1, base_type1, _) = ids[0];
+        let (unit2, _, variable2) = ids[1];
+        dwarf.units.get_mut(unit2).get_mut(variable2).set(
+            constants::DW_AT_specification,
+            AttributeValue::DebugInfoRef(Reference::Entry(unit1, base_type1)),
+        );
+
+        let mut sections = Sections::new(EndianVec::new(LittleEndian));
+        dwarf.write(&mut sections).unwrap();
+        let read_dwarf = read::Dwarf {
+            debug_abbrev: read::DebugAbbrev::new(sections.debug_abbrev.slice(), LittleEndian),
+            debug_info: read::DebugInfo::new(sections.debug_info.slice(), LittleEndian),
+            debug_str: read::DebugStr::new(sections.debug_str.slice(), LittleEndian),
+            ..Default::default()
+        };
+
+        let mut rewritten = Sections::new(EndianVec::new(LittleEndian));
+        let offsets = rewrite_debug_info(&read_dwarf, &mut rewritten, |unit, _, attr| {
+            Ok(match attr.name() {
+                constants::DW_AT_producer => RewriteAttribute::Remove,
+                constants::DW_AT_comp_dir => {
+                    let comp_dir = read_dwarf.attr_string(unit, attr.value())?;
+                    assert_eq!(comp_dir.slice(), b"/build/a");
+                    RewriteAttribute::String(b"/src/a".to_vec())
+                }
+                constants::DW_AT_name => RewriteAttribute::String(b"longer.c".to_vec()),
+                constants::DW_AT_byte_size => RewriteAttribute::Udata(8),
+                _ => RewriteAttribute::Keep,
+            })
+        })
+        .unwrap();
+        assert!(!offsets.is_identity());
+
+        let dwarf = read::Dwarf {
+            debug_abbrev: read::DebugAbbrev::new(rewritten.debug_abbrev.slice(), LittleEndian),
+            debug_info: read::DebugInfo::new(rewritten.debug_info.slice(), LittleEndian),
+            debug_str: read::DebugStr::new(rewritten.debug_str.slice(), LittleEndian),
+            ..Default::default()
+        };
+        let mut old_headers = read_dwarf.units();
+        let mut headers = dwarf.units();
+        let mut count = 0;
+        while let Some(header) = headers.next().unwrap() {
+            let old_header = old_headers.next().unwrap().unwrap();
+            assert_eq!(header.version(), old_header.version());
+            assert_eq!(
+                offsets.get(old_header.offset().as_debug_info_offset().unwrap()),
+                header.offset().as_debug_info_offset()
+            );
+            count += 1;
+
+            let unit = dwarf.unit(header).unwrap();
+            assert_eq!(unit.comp_dir.unwrap().slice(), b"/src/a");
+            assert_eq!(unit.name.unwrap().slice(), b"longer.c");
+
+            let mut entries = unit.entries();
+            let (_, root) = entries.next_dfs().unwrap().unwrap();
+            assert_eq!(root.attr(constants::DW_AT_producer).unwrap(), None);
+            let (_, base_type) = entries.next_dfs().unwrap().unwrap();
+            assert_eq!(base_type.tag(), constants::DW_TAG_base_type);
+            assert_eq!(
+                base_type.attr_value(constants::DW_AT_byte_size).unwrap(),
+                Some(read::AttributeValue::Udata(8))
+            );
+            let base_type_offset = base_type.offset();
+            let (_, variable) = entries.next_dfs().unwrap().unwrap();
+            assert_eq!(
+                variable.attr_value(constants::DW_AT_type).unwrap(),
+                Some(read::AttributeValue::UnitRef(base_type_offset))
+            );
+            if count == 2 {
+                let target = match variable.attr_value(constants::DW_AT_specification) {
+                    Ok(Some(read::AttributeValue::DebugInfoRef(target))) => target,
+                    otherwise => panic!("Unexpected {:?}", otherwise),
+                };
+                let mut units = dwarf.units();
+                let first = dwarf.unit(units.next().unwrap().unwrap()).unwrap();
+                let target = target.to_unit_offset(&first.header).unwrap();
+                let entry = first.entry(target).unwrap();
+                assert_eq!(entry.tag(), constants::DW_TAG_base_type);
+            }
+        }
+        assert_eq!(count, 2);
+    }
+}
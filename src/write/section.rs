@@ -4,8 +4,8 @@ use std::vec::Vec;
 
 use crate::common::SectionId;
 use crate::write::{
-    DebugAbbrev, DebugFrame, DebugInfo, DebugInfoReference, DebugLine, DebugLineStr, DebugLoc,
-    DebugLocLists, DebugRanges, DebugRngLists, DebugStr, EhFrame, Writer,
+    DebugAbbrev, DebugAddr, DebugFrame, DebugInfo, DebugInfoReference, DebugLine, DebugLineStr,
+    DebugLoc, DebugLocLists, DebugRanges, DebugRngLists, DebugStr, EhFrame, Writer,
 };
 
 macro_rules! define_section {
@@ -69,6 +69,8 @@ pub trait Section<W: Writer>: DerefMut<Target = W> {
 pub struct Sections<W: Writer> {
     /// The `.debug_abbrev` section.
     pub debug_abbrev: DebugAbbrev<W>,
+    /// The `.debug_addr` section.
+    pub debug_addr: DebugAddr<W>,
     /// The `.debug_info` section.
     pub debug_info: DebugInfo<W>,
     /// The `.debug_line` section.
@@ -102,6 +104,7 @@ impl<W: Writer + Clone> Sections<W> {
     pub fn new(section: W) -> Self {
         Sections {
             debug_abbrev: DebugAbbrev(section.clone()),
+            debug_addr: DebugAddr(section.clone()),
             debug_info: DebugInfo(section.clone()),
             debug_line: DebugLine(section.clone()),
             debug_line_str: DebugLineStr(section.clone()),
@@ -124,6 +127,7 @@ impl<W: Writer> Sections<W> {
     pub fn get(&self, id: SectionId) -> Option<&W> {
         match id {
             SectionId::DebugAbbrev => Some(&self.debug_abbrev.0),
+            SectionId::DebugAddr => Some(&self.debug_addr.0),
             SectionId::DebugInfo => Some(&self.debug_info.0),
             SectionId::DebugLine => Some(&self.debug_line.0),
             SectionId::DebugLineStr => Some(&self.debug_line_str.0),
@@ -142,6 +146,7 @@ impl<W: Writer> Sections<W> {
     pub fn get_mut(&mut self, id: SectionId) -> Option<&mut W> {
         match id {
             SectionId::DebugAbbrev => Some(&mut self.debug_abbrev.0),
+            SectionId::DebugAddr => Some(&mut self.debug_addr.0),
             SectionId::DebugInfo => Some(&mut self.debug_info.0),
             SectionId::DebugLine => Some(&mut self.debug_line.0),
             SectionId::DebugLineStr => Some(&mut self.debug_line_str.0),
@@ -168,6 +173,7 @@ impl<W: Writer> Sections<W> {
         }
         // Ordered so that earlier sections do not reference later sections.
         f!(self.debug_abbrev)?;
+        f!(self.debug_addr)?;
         f!(self.debug_str)?;
         f!(self.debug_line_str)?;
         f!(self.debug_line)?;
@@ -193,6 +199,7 @@ impl<W: Writer> Sections<W> {
         }
         // Ordered so that earlier sections do not reference later sections.
         f!(self.debug_abbrev)?;
+        f!(self.debug_addr)?;
         f!(self.debug_str)?;
         f!(self.debug_line_str)?;
         f!(self.debug_line)?;
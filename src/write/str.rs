@@ -81,6 +81,30 @@ macro_rules! define_string_table {
                     offsets,
                 })
             }
+
+            /// Write the string table to the section, with the strings sorted
+            /// in byte order.
+            ///
+            /// Unlike `write`, the layout of the section does not depend on the
+            /// order in which the strings were added.
+            ///
+            /// Returns the offsets at which the strings are written.
+            pub fn write_sorted<W: Writer>(&self, w: &mut $section<W>) -> Result<$offsets> {
+                let mut order: Vec<usize> = (0..self.strings.len()).collect();
+                order.sort_by_key(|&index| &self.strings[index]);
+
+                let mut offsets = vec![w.offset(); self.strings.len()];
+                for index in order {
+                    offsets[index] = w.offset();
+                    w.write(&self.strings[index])?;
+                    w.write_u8(0)?;
+                }
+
+                Ok($offsets {
+                    base_id: self.base_id,
+                    offsets,
+                })
+            }
         }
     };
 }
@@ -154,6 +178,22 @@ mod tests {
         assert_eq!(offsets.count(), 2);
     }
 
+    #[test]
+    fn test_string_table_sorted() {
+        let mut strings = StringTable::default();
+        let id1 = strings.add(&b"two"[..]);
+        let id2 = strings.add(&b"one"[..]);
+        let id3 = strings.add(&b"three"[..]);
+
+        let mut debug_str = DebugStr::from(EndianVec::new(LittleEndian));
+        let offsets = strings.write_sorted(&mut debug_str).unwrap();
+        assert_eq!(debug_str.slice(), b"one\0three\0two\0");
+        assert_eq!(offsets.get(id1), DebugStrOffset(10));
+        assert_eq!(offsets.get(id2), DebugStrOffset(0));
+        assert_eq!(offsets.get(id3), DebugStrOffset(4));
+        assert_eq!(offsets.count(), 3);
+    }
+
     #[test]
     fn test_string_table_read() {
         let mut strings = StringTable::default();
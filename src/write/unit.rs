@@ -9,10 +9,10 @@ use crate::common::{
 use crate::constants;
 use crate::leb128::write::{sleb128_size, uleb128_size};
 use crate::write::{
-    Abbreviation, AbbreviationTable, Address, AttributeSpecification, BaseId, DebugLineStrOffsets,
-    DebugStrOffsets, Error, Expression, FileId, LineProgram, LineStringId, LocationListId,
-    LocationListOffsets, LocationListTable, RangeListId, RangeListOffsets, RangeListTable,
-    Reference, Result, Section, Sections, StringId, Writer,
+    Abbreviation, AbbreviationTable, Address, AddressTable, AttributeSpecification, BaseId,
+    DebugLineStrOffsets, DebugStrOffsets, Error, Expression, FileId, LineProgram, LineStringId,
+    LocationListId, LocationListOffsets, LocationListTable, RangeListId, RangeListOffsets,
+    RangeListTable, Reference, Result, Section, Sections, StringId, WriteOptions, Writer,
 };
 
 define_id!(UnitId, "An identifier for a unit in a `UnitTable`.");
@@ -87,6 +87,20 @@ impl UnitTable {
         sections: &mut Sections<W>,
         line_strings: &DebugLineStrOffsets,
         strings: &DebugStrOffsets,
+    ) -> Result<DebugInfoOffsets> {
+        self.write_with_options(sections, line_strings, strings, &WriteOptions::default())
+    }
+
+    /// Write the units to the given sections, using the given options.
+    ///
+    /// `strings` must contain the `.debug_str` offsets of the corresponding
+    /// `StringTable`.
+    pub fn write_with_options<W: Writer>(
+        &mut self,
+        sections: &mut Sections<W>,
+        line_strings: &DebugLineStrOffsets,
+        strings: &DebugStrOffsets,
+        options: &WriteOptions,
     ) -> Result<DebugInfoOffsets> {
         let mut offsets = DebugInfoOffsets {
             base_id: self.base_id,
@@ -103,6 +117,7 @@ impl UnitTable {
                 &mut abbrevs,
                 line_strings,
                 strings,
+                options,
             )?);
 
             abbrevs.write(&mut sections.debug_abbrev)?;
@@ -153,6 +168,11 @@ pub struct Unit {
     pub ranges: RangeListTable,
     /// A table of location lists used by this unit.
     pub locations: LocationListTable,
+    /// A table of addresses that are referenced by index from this unit.
+    ///
+    /// Addresses are added to this table when writing location lists with
+    /// `WriteOptions::gnu_split_location_lists`.
+    pub addresses: AddressTable,
     /// All entries in this unit. The order is unrelated to the tree order.
     // Requirements:
     // - entries form a tree
@@ -185,6 +205,7 @@ impl Unit {
             line_program,
             ranges,
             locations,
+            addresses: AddressTable::default(),
             entries,
             root,
         }
@@ -291,6 +312,7 @@ impl Unit {
         abbrevs: &mut AbbreviationTable,
         line_strings: &DebugLineStrOffsets,
         strings: &DebugStrOffsets,
+        options: &WriteOptions,
     ) -> Result<UnitOffsets> {
         let line_program = if self.line_program_in_use() {
             self.entries[self.root.index]
@@ -343,6 +365,11 @@ impl Unit {
         // However, references to base types in expressions use ULEB128, so base types
         // must be moved to the front before we can calculate offsets.
         self.reorder_base_types();
+        if options.sort_abbreviations {
+            // Add the abbreviations in sorted order before any codes are assigned.
+            self.entries[self.root.index].collect_abbreviations(self, abbrevs)?;
+            abbrevs.sort();
+        }
         let mut offset = w.len();
         self.entries[self.root.index].calculate_offsets(
             self,
@@ -351,11 +378,15 @@ impl Unit {
             abbrevs,
         )?;
 
-        let range_lists = self.ranges.write(sections, self.encoding)?;
+        let range_lists = self.ranges.write(sections, self.encoding, options)?;
         // Location lists can't be written until we have DIE offsets.
-        let loc_lists = self
-            .locations
-            .write(sections, self.encoding, Some(&offsets))?;
+        let loc_lists = self.locations.write(
+            sections,
+            &mut self.addresses,
+            self.encoding,
+            Some(&offsets),
+            options,
+        )?;
 
         let w = &mut sections.debug_info;
         let mut unit_refs = Vec::new();
@@ -572,6 +603,14 @@ impl DebuggingInformationEntry {
         ))
     }
 
+    fn collect_abbreviations(&self, unit: &Unit, abbrevs: &mut AbbreviationTable) -> Result<()> {
+        abbrevs.add(self.abbreviation(unit.encoding())?);
+        for child in &self.children {
+            unit.entries[child.index].collect_abbreviations(unit, abbrevs)?;
+        }
+        Ok(())
+    }
+
     fn calculate_offsets(
         &self,
         unit: &Unit,
@@ -700,10 +739,13 @@ impl Attribute {
 
     /// Return the type specification for this attribute.
     fn specification(&self, encoding: Encoding) -> Result<AttributeSpecification> {
-        Ok(AttributeSpecification::new(
-            self.name,
-            self.value.form(encoding)?,
-        ))
+        let form = self.value.form(encoding)?;
+        Ok(match self.value {
+            AttributeValue::ImplicitConst(val) => {
+                AttributeSpecification::new_implicit_const(self.name, val)
+            }
+            _ => AttributeSpecification::new(self.name, form),
+        })
     }
 }
 
@@ -756,6 +798,14 @@ pub enum AttributeValue {
     /// An unsigned integer constant.
     Udata(u64),
 
+    /// A signed integer constant that is stored in the abbreviation instead
+    /// of the entry.
+    ///
+    /// Entries that have the same tag, children flag and attributes,
+    /// including the values of any implicit constants, share an abbreviation.
+    /// This is only supported for DWARF version 5 and later.
+    ImplicitConst(i64),
+
     /// "The information bytes contain a DWARF expression (see Section 2.5) or
     /// location description (see Section 2.6)."
     Exprloc(Expression),
@@ -872,7 +922,6 @@ impl AttributeValue {
     pub fn form(&self, encoding: Encoding) -> Result<constants::DwForm> {
         // TODO: missing forms:
         // - DW_FORM_indirect
-        // - DW_FORM_implicit_const
         // - FW_FORM_block1/block2/block4
         // - DW_FORM_str/strx1/strx2/strx3/strx4
         // - DW_FORM_addrx/addrx1/addrx2/addrx3/addrx4
@@ -940,6 +989,12 @@ impl AttributeValue {
             | AttributeValue::FileIndex(_)
             | AttributeValue::Udata(_) => constants::DW_FORM_udata,
             AttributeValue::Sdata(_) => constants::DW_FORM_sdata,
+            AttributeValue::ImplicitConst(_) => {
+                if encoding.version < 5 {
+                    return Err(Error::NeedVersion(5));
+                }
+                constants::DW_FORM_implicit_const
+            }
         };
         Ok(form)
     }
@@ -983,6 +1038,10 @@ impl AttributeValue {
                 debug_assert_form!(constants::DW_FORM_udata);
                 uleb128_size(val)
             }
+            AttributeValue::ImplicitConst(_) => {
+                debug_assert_form!(constants::DW_FORM_implicit_const);
+                0
+            }
             AttributeValue::Exprloc(ref val) => {
                 debug_assert_form!(constants::DW_FORM_exprloc);
                 let size = val.size(unit.encoding(), Some(offsets));
@@ -1176,6 +1235,9 @@ impl AttributeValue {
                 debug_assert_form!(constants::DW_FORM_udata);
                 w.write_uleb128(val)?;
             }
+            AttributeValue::ImplicitConst(_) => {
+                debug_assert_form!(constants::DW_FORM_implicit_const);
+            }
             AttributeValue::Exprloc(ref val) => {
                 debug_assert_form!(constants::DW_FORM_exprloc);
                 w.write_uleb128(val.size(unit.encoding(), Some(offsets)) as u64)?;
@@ -1475,7 +1537,9 @@ pub(crate) mod convert {
     use super::*;
     use crate::common::{DwoId, UnitSectionOffset};
     use crate::read::{self, Reader};
-    use crate::write::{self, ConvertError, ConvertResult, LocationList, RangeList};
+    use crate::write::{
+        self, ConvertError, ConvertOptions, ConvertResult, LocationList, RangeList,
+    };
     use std::collections::HashMap;
 
     pub(crate) struct ConvertUnit<R: Reader<Offset = usize>> {
@@ -1517,6 +1581,26 @@ pub(crate) mod convert {
             line_strings: &mut write::LineStringTable,
             strings: &mut write::StringTable,
             convert_address: &dyn Fn(u64) -> Option<Address>,
+        ) -> ConvertResult<UnitTable> {
+            Self::from_with_options(
+                dwarf,
+                line_strings,
+                strings,
+                convert_address,
+                &ConvertOptions::default(),
+            )
+        }
+
+        /// Create a unit table by reading the data in the given sections using
+        /// the given options.
+        ///
+        /// See [`UnitTable::from`] for details of the other parameters.
+        pub fn from_with_options<R: Reader<Offset = usize>>(
+            dwarf: &read::Dwarf<R>,
+            line_strings: &mut write::LineStringTable,
+            strings: &mut write::StringTable,
+            convert_address: &dyn Fn(u64) -> Option<Address>,
+            options: &ConvertOptions,
         ) -> ConvertResult<UnitTable> {
             let base_id = BaseId::default();
             let mut unit_entries = Vec::new();
@@ -1544,6 +1628,7 @@ pub(crate) mod convert {
                     line_strings,
                     strings,
                     convert_address,
+                    options,
                 )?);
             }
 
@@ -1604,6 +1689,7 @@ pub(crate) mod convert {
             line_strings: &mut write::LineStringTable,
             strings: &mut write::StringTable,
             convert_address: &dyn Fn(u64) -> Option<Address>,
+            options: &ConvertOptions,
         ) -> ConvertResult<Unit> {
             let from_unit = unit.from_unit;
             let base_address =
@@ -1614,12 +1700,13 @@ pub(crate) mod convert {
                     Some(ref from_program) => {
                         let from_program = from_program.clone();
                         let line_program_offset = from_program.header().offset();
-                        let (line_program, line_program_files) = LineProgram::from(
+                        let (line_program, line_program_files) = LineProgram::from_with_options(
                             from_program,
                             dwarf,
                             line_strings,
                             strings,
                             convert_address,
+                            options,
                         )?;
                         (Some(line_program_offset), line_program, line_program_files)
                     }
@@ -1654,6 +1741,7 @@ pub(crate) mod convert {
                 line_program,
                 ranges,
                 locations,
+                addresses: AddressTable::default(),
                 entries,
                 root: unit.root,
             })
@@ -1904,6 +1992,9 @@ pub(crate) mod convert {
                     return Err(ConvertError::InvalidAttributeValue);
                 }
                 read::AttributeValue::DwoId(DwoId(val)) => AttributeValue::Udata(val),
+                read::AttributeValue::Unknown { .. } => {
+                    return Err(ConvertError::UnsupportedAttributeValue);
+                }
             };
             Ok(Some(to))
         }
@@ -2283,8 +2374,18 @@ mod tests {
                     };
 
                     let mut sections = Sections::new(EndianVec::new(LittleEndian));
-                    let range_list_offsets = ranges.write(&mut sections, encoding).unwrap();
-                    let loc_list_offsets = locations.write(&mut sections, encoding, None).unwrap();
+                    let options = WriteOptions::default();
+                    let range_list_offsets =
+                        ranges.write(&mut sections, encoding, &options).unwrap();
+                    let loc_list_offsets = locations
+                        .write(
+                            &mut sections,
+                            &mut AddressTable::default(),
+                            encoding,
+                            None,
+                            &options,
+                        )
+                        .unwrap();
 
                     let read_debug_ranges =
                         read::DebugRanges::new(sections.debug_ranges.slice(), LittleEndian);
@@ -2875,6 +2976,185 @@ mod tests {
         check_sibling(&read_units.next().unwrap().unwrap(), &read_debug_abbrev);
     }
 
+    #[test]
+    fn test_write_options() {
+        fn build(names: &[&str]) -> crate::write::Dwarf {
+            let encoding = Encoding {
+                format: Format::Dwarf32,
+                version: 4,
+                address_size: 8,
+            };
+            let mut dwarf = crate::write::Dwarf::new();
+            let unit_id = dwarf.units.add(Unit::new(encoding, LineProgram::none()));
+            let unit = dwarf.units.get_mut(unit_id);
+            let root = unit.root();
+            for name in names {
+                let name = dwarf.strings.add(*name);
+                let id = unit.add(root, constants::DW_TAG_variable);
+                unit.get_mut(id)
+                    .set(constants::DW_AT_name, AttributeValue::StringRef(name));
+            }
+            let id = unit.add(root, constants::DW_TAG_array_type);
+            unit.get_mut(id)
+                .set(constants::DW_AT_byte_size, AttributeValue::Data1(4));
+            dwarf
+        }
+
+        fn write(
+            mut dwarf: crate::write::Dwarf,
+            options: &WriteOptions,
+        ) -> Sections<EndianVec<LittleEndian>> {
+            let mut sections = Sections::new(EndianVec::new(LittleEndian));
+            dwarf.write_with_options(&mut sections, options).unwrap();
+            sections
+        }
+
+        fn abbrev_tags(sections: &Sections<EndianVec<LittleEndian>>) -> Vec<constants::DwTag> {
+            let debug_abbrev = read::DebugAbbrev::new(sections.debug_abbrev.slice(), LittleEndian);
+            let abbrevs = debug_abbrev
+                .abbreviations(crate::common::DebugAbbrevOffset(0))
+                .unwrap();
+            (1..)
+                .map_while(|code| abbrevs.get(code).map(|abbrev| abbrev.tag()))
+                .collect()
+        }
+
+        let names = ["b", "c", "a"];
+        let default = WriteOptions::default();
+        let sorted = WriteOptions {
+            sort_strings: true,
+            sort_abbreviations: true,
+            ..Default::default()
+        };
+
+        // Writing the same information twice gives the same result.
+        let sections1 = write(build(&names), &default);
+        let sections2 = write(build(&names), &default);
+        assert_eq!(sections1.debug_info.slice(), sections2.debug_info.slice());
+        assert_eq!(
+            sections1.debug_abbrev.slice(),
+            sections2.debug_abbrev.slice()
+        );
+        assert_eq!(sections1.debug_str.slice(), sections2.debug_str.slice());
+        assert_eq!(sections1.debug_str.slice(), b"b\0c\0a\0");
+        assert_eq!(
+            abbrev_tags(&sections1),
+            [
+                constants::DW_TAG_compile_unit,
+                constants::DW_TAG_variable,
+                constants::DW_TAG_array_type,
+            ]
+        );
+
+        // Sorting gives the same string and abbreviation tables regardless
+        // of insertion order.
+        let sections1 = write(build(&names), &sorted);
+        let sections2 = write(build(&["a", "c", "b"]), &sorted);
+        assert_eq!(
+            sections1.debug_abbrev.slice(),
+            sections2.debug_abbrev.slice()
+        );
+        assert_eq!(sections1.debug_str.slice(), sections2.debug_str.slice());
+        assert_eq!(sections1.debug_str.slice(), b"a\0b\0c\0");
+        assert_eq!(
+            abbrev_tags(&sections1),
+            [
+                constants::DW_TAG_array_type,
+                constants::DW_TAG_compile_unit,
+                constants::DW_TAG_variable,
+            ]
+        );
+
+        // The entries still refer to the correct strings.
+        let read_debug_info = read::DebugInfo::new(sections1.debug_info.slice(), LittleEndian);
+        let read_debug_abbrev =
+            read::DebugAbbrev::new(sections1.debug_abbrev.slice(), LittleEndian);
+        let read_debug_str = read::DebugStr::new(sections1.debug_str.slice(), LittleEndian);
+        let read_unit = read_debug_info.units().next().unwrap().unwrap();
+        let abbrevs = read_unit.abbreviations(&read_debug_abbrev).unwrap();
+        let mut entries = read_unit.entries(&abbrevs);
+        entries.next_dfs().unwrap().unwrap();
+        for name in &names {
+            let (_, entry) = entries.next_dfs().unwrap().unwrap();
+            let offset = match entry.attr_value(constants::DW_AT_name).unwrap() {
+                Some(read::AttributeValue::DebugStrRef(offset)) => offset,
+                otherwise => panic!("unexpected {:?}", otherwise),
+            };
+            let value = read_debug_str.get_str(offset).unwrap();
+            assert_eq!(value.slice(), name.as_bytes());
+        }
+    }
+
+    #[test]
+    fn test_implicit_const() {
+        let encoding = Encoding {
+            format: Format::Dwarf32,
+            version: 5,
+            address_size: 8,
+        };
+        let mut units = UnitTable::default();
+        let unit_id = units.add(Unit::new(encoding, LineProgram::none()));
+        let unit = units.get_mut(unit_id);
+        let root = unit.root();
+        for value in [1, 1, -2] {
+            let id = unit.add(root, constants::DW_TAG_variable);
+            unit.get_mut(id).set(
+                constants::DW_AT_const_value,
+                AttributeValue::ImplicitConst(value),
+            );
+        }
+
+        let debug_line_str_offsets = DebugLineStrOffsets::none();
+        let debug_str_offsets = DebugStrOffsets::none();
+        let mut sections = Sections::new(EndianVec::new(LittleEndian));
+        units
+            .write(&mut sections, &debug_line_str_offsets, &debug_str_offsets)
+            .unwrap();
+
+        let read_debug_info = read::DebugInfo::new(sections.debug_info.slice(), LittleEndian);
+        let read_debug_abbrev = read::DebugAbbrev::new(sections.debug_abbrev.slice(), LittleEndian);
+        let read_unit = read_debug_info.units().next().unwrap().unwrap();
+        let abbrevs = read_unit.abbreviations(&read_debug_abbrev).unwrap();
+        let mut entries = read_unit.entries(&abbrevs);
+        entries.next_dfs().unwrap().unwrap();
+        let mut codes = Vec::new();
+        for value in [1, 1, -2] {
+            let (_, entry) = entries.next_dfs().unwrap().unwrap();
+            let abbrev = abbrevs.get(entry.code()).unwrap();
+            assert_eq!(
+                abbrev.attributes()[0].form(),
+                constants::DW_FORM_implicit_const
+            );
+            assert_eq!(
+                entry.attr_value(constants::DW_AT_const_value).unwrap(),
+                Some(read::AttributeValue::Sdata(value))
+            );
+            codes.push(entry.code());
+        }
+        // Entries with the same implicit value share an abbreviation.
+        assert_eq!(codes[0], codes[1]);
+        assert_ne!(codes[0], codes[2]);
+
+        // Implicit constants require DWARF 5.
+        let encoding = Encoding {
+            version: 4,
+            ..encoding
+        };
+        let mut units = UnitTable::default();
+        let unit_id = units.add(Unit::new(encoding, LineProgram::none()));
+        let unit = units.get_mut(unit_id);
+        let root = unit.root();
+        unit.get_mut(root).set(
+            constants::DW_AT_const_value,
+            AttributeValue::ImplicitConst(1),
+        );
+        let mut sections = Sections::new(EndianVec::new(LittleEndian));
+        assert!(matches!(
+            units.write(&mut sections, &debug_line_str_offsets, &debug_str_offsets),
+            Err(Error::NeedVersion(5))
+        ));
+    }
+
     #[test]
     fn test_line_ref() {
         for &version in &[2, 3, 4, 5] {